@@ -0,0 +1,458 @@
+//! Chat Completions - the model behind AI chat, buffered or streamed
+//!
+//! `stream` yields the reply's text as the provider produces it. Dropping
+//! the stream closes the provider connection, which stops generation, so a
+//! caller that loses interest stops paying for tokens by letting go of it.
+//!
+//! A turn may ask for tools instead of answering; a `ToolRunner` runs them
+//! and their results go back to the model for the next turn.
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde_json::Value;
+use std::collections::VecDeque;
+
+/// Model for chat replies
+const MODEL: &str = "gpt-4o-mini";
+
+/// Most tokens in one reply
+const MAX_TOKENS: u32 = 1000;
+
+/// Turns of tool calls before the model has to answer
+pub const MAX_TOOL_ROUNDS: usize = 4;
+
+/// A tool the model asked for
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolRequest {
+    pub id: String,
+    pub name: String,
+    /// JSON object, as the model wrote it
+    pub arguments: String,
+}
+
+/// One message of a conversation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatMessage {
+    System(String),
+    User(String),
+    Assistant(String),
+    /// The model asking for tools
+    ToolCalls(Vec<ToolRequest>),
+    /// The result of the tool request `call_id`
+    ToolResult { call_id: String, content: String },
+}
+
+impl ChatMessage {
+    /// A stored message; any role but `system` and `user` is the assistant's
+    pub fn from_role(role: &str, content: String) -> Self {
+        match role {
+            "system" => ChatMessage::System(content),
+            "user" => ChatMessage::User(content),
+            _ => ChatMessage::Assistant(content),
+        }
+    }
+
+    fn to_openai(&self) -> Value {
+        match self {
+            ChatMessage::System(content) => serde_json::json!({ "role": "system", "content": content }),
+            ChatMessage::User(content) => serde_json::json!({ "role": "user", "content": content }),
+            ChatMessage::Assistant(content) => serde_json::json!({ "role": "assistant", "content": content }),
+            ChatMessage::ToolCalls(requests) => serde_json::json!({
+                "role": "assistant",
+                "content": null,
+                "tool_calls": requests.iter().map(|r| serde_json::json!({
+                    "id": r.id,
+                    "type": "function",
+                    "function": { "name": r.name, "arguments": r.arguments },
+                })).collect::<Vec<_>>(),
+            }),
+            ChatMessage::ToolResult { call_id, content } => {
+                serde_json::json!({ "role": "tool", "tool_call_id": call_id, "content": content })
+            }
+        }
+    }
+}
+
+/// A whole turn and what it cost; `tool_calls` is empty when the model
+/// answered
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatCompletion {
+    pub content: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub tool_calls: Vec<ToolRequest>,
+}
+
+/// A piece of a streamed turn
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatDelta {
+    Text(String),
+    /// Comes last, once the model has finished asking
+    ToolCalls(Vec<ToolRequest>),
+}
+
+/// A turn, piece by piece
+pub type TokenStream = BoxStream<'static, Result<ChatDelta, String>>;
+
+/// A chat model; `tools` are the function definitions it may call, none
+/// when empty
+#[async_trait]
+pub trait ChatProvider: Send + Sync {
+    async fn complete(&self, messages: &[ChatMessage], tools: &[Value]) -> Result<ChatCompletion, String>;
+
+    async fn stream(&self, messages: &[ChatMessage], tools: &[Value]) -> Result<TokenStream, String>;
+}
+
+/// Runs the tools a model asks for
+#[async_trait]
+pub trait ToolRunner: Send + Sync {
+    /// Function definitions to offer the model
+    fn definitions(&self) -> Vec<Value>;
+
+    /// The result of one request; failures are results too, so the model
+    /// can relay them
+    async fn run(&self, request: &ToolRequest) -> Value;
+}
+
+/// Run `requests` and append them and their results to `messages`
+pub async fn run_tools(tools: &dyn ToolRunner, requests: Vec<ToolRequest>, messages: &mut Vec<ChatMessage>) {
+    let mut results = Vec::with_capacity(requests.len());
+    for request in &requests {
+        let content = tools.run(request).await.to_string();
+        results.push(ChatMessage::ToolResult { call_id: request.id.clone(), content });
+    }
+    messages.push(ChatMessage::ToolCalls(requests));
+    messages.extend(results);
+}
+
+/// The tool definitions for turn `round`; the last offers none, so the
+/// model has to answer
+pub fn tools_for_round(tools: &dyn ToolRunner, round: usize) -> Vec<Value> {
+    if round < MAX_TOOL_ROUNDS {
+        tools.definitions()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Reply to `messages`, running the tools the model asks for on the way;
+/// the usage covers every turn
+pub async fn complete_with_tools(
+    provider: &dyn ChatProvider,
+    tools: &dyn ToolRunner,
+    messages: &mut Vec<ChatMessage>,
+) -> Result<ChatCompletion, String> {
+    let (mut prompt_tokens, mut completion_tokens) = (0, 0);
+    let mut round = 0;
+    loop {
+        let definitions = tools_for_round(tools, round);
+        let mut completion = provider.complete(messages, &definitions).await?;
+        prompt_tokens += completion.prompt_tokens;
+        completion_tokens += completion.completion_tokens;
+
+        if completion.tool_calls.is_empty() || definitions.is_empty() {
+            completion.prompt_tokens = prompt_tokens;
+            completion.completion_tokens = completion_tokens;
+            return Ok(completion);
+        }
+        run_tools(tools, std::mem::take(&mut completion.tool_calls), messages).await;
+        round += 1;
+    }
+}
+
+/// OpenAI chat completions
+pub struct OpenAiChatProvider {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl OpenAiChatProvider {
+    pub fn new(api_key: String) -> Self {
+        Self { client: reqwest::Client::new(), api_key }
+    }
+
+    async fn send(&self, messages: &[ChatMessage], tools: &[Value], stream: bool) -> Result<reqwest::Response, String> {
+        let mut body = serde_json::json!({
+            "model": MODEL,
+            "messages": messages.iter().map(ChatMessage::to_openai).collect::<Vec<_>>(),
+            "temperature": 0.7,
+            "max_tokens": MAX_TOKENS,
+            "stream": stream,
+        });
+        if !tools.is_empty() {
+            body["tools"] = Value::Array(tools.to_vec());
+        }
+        let response = self
+            .client
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(response.text().await.unwrap_or_default());
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl ChatProvider for OpenAiChatProvider {
+    async fn complete(&self, messages: &[ChatMessage], tools: &[Value]) -> Result<ChatCompletion, String> {
+        let json: Value = self.send(messages, tools, false).await?.json().await.map_err(|e| e.to_string())?;
+        let message = &json["choices"][0]["message"];
+        let tool_calls = message["tool_calls"]
+            .as_array()
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|c| ToolRequest {
+                        id: c["id"].as_str().unwrap_or_default().to_string(),
+                        name: c["function"]["name"].as_str().unwrap_or_default().to_string(),
+                        arguments: c["function"]["arguments"].as_str().unwrap_or("{}").to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(ChatCompletion {
+            content: message["content"].as_str().unwrap_or("I couldn't generate a response.").to_string(),
+            prompt_tokens: json["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+            completion_tokens: json["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
+            tool_calls,
+        })
+    }
+
+    async fn stream(&self, messages: &[ChatMessage], tools: &[Value]) -> Result<TokenStream, String> {
+        struct Upstream {
+            response: reqwest::Response,
+            parser: StreamParser,
+            pending: VecDeque<ChatDelta>,
+            done: bool,
+        }
+
+        let upstream = Upstream {
+            response: self.send(messages, tools, true).await?,
+            parser: StreamParser::default(),
+            pending: VecDeque::new(),
+            done: false,
+        };
+        let deltas = stream::unfold(upstream, |mut up| async move {
+            loop {
+                if let Some(delta) = up.pending.pop_front() {
+                    return Some((Ok(delta), up));
+                }
+                if up.done {
+                    return None;
+                }
+                match up.response.chunk().await {
+                    Ok(Some(bytes)) => {
+                        up.done = up.parser.feed(&String::from_utf8_lossy(&bytes), &mut up.pending);
+                    }
+                    Ok(None) => {
+                        up.parser.finish(&mut up.pending);
+                        up.done = true;
+                    }
+                    Err(e) => {
+                        up.done = true;
+                        return Some((Err(e.to_string()), up));
+                    }
+                }
+            }
+        });
+        Ok(deltas.boxed())
+    }
+}
+
+/// Turns OpenAI's streamed `data:` lines into deltas
+#[derive(Default)]
+struct StreamParser {
+    /// A trailing partial line
+    buffer: String,
+    /// Tool requests, built up across lines by index
+    calls: Vec<ToolRequest>,
+}
+
+impl StreamParser {
+    /// Parse the complete lines of `buffer` plus `text` into `deltas`;
+    /// returns whether the stream ended
+    fn feed(&mut self, text: &str, deltas: &mut VecDeque<ChatDelta>) -> bool {
+        self.buffer.push_str(text);
+        let Some(end) = self.buffer.rfind('\n') else {
+            return false;
+        };
+        let lines: String = self.buffer.drain(..=end).collect();
+
+        for line in lines.lines() {
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                self.finish(deltas);
+                return true;
+            }
+            let Ok(json) = serde_json::from_str::<Value>(data) else {
+                continue;
+            };
+            let delta = &json["choices"][0]["delta"];
+            if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+                deltas.push_back(ChatDelta::Text(text.to_string()));
+            }
+            for call in delta["tool_calls"].as_array().into_iter().flatten() {
+                let index = call["index"].as_u64().unwrap_or(0) as usize;
+                if self.calls.len() <= index {
+                    self.calls.resize(index + 1, ToolRequest { id: String::new(), name: String::new(), arguments: String::new() });
+                }
+                let request = &mut self.calls[index];
+                if let Some(id) = call["id"].as_str() {
+                    request.id = id.to_string();
+                }
+                if let Some(name) = call["function"]["name"].as_str() {
+                    request.name.push_str(name);
+                }
+                if let Some(arguments) = call["function"]["arguments"].as_str() {
+                    request.arguments.push_str(arguments);
+                }
+            }
+        }
+        false
+    }
+
+    /// Hand over the tool requests once the turn is over
+    fn finish(&mut self, deltas: &mut VecDeque<ChatDelta>) {
+        if !self.calls.is_empty() {
+            deltas.push_back(ChatDelta::ToolCalls(std::mem::take(&mut self.calls)));
+        }
+    }
+}
+
+/// Echoes the last message, for running without an OpenAI key; never
+/// calls tools
+pub struct MockChatProvider;
+
+impl MockChatProvider {
+    fn reply(messages: &[ChatMessage]) -> String {
+        let last = match messages.last() {
+            Some(ChatMessage::System(c) | ChatMessage::User(c) | ChatMessage::Assistant(c)) => c.as_str(),
+            Some(ChatMessage::ToolResult { content, .. }) => content.as_str(),
+            Some(ChatMessage::ToolCalls(_)) | None => "",
+        };
+        format!("Mock response to: {}", last)
+    }
+}
+
+#[async_trait]
+impl ChatProvider for MockChatProvider {
+    async fn complete(&self, messages: &[ChatMessage], _tools: &[Value]) -> Result<ChatCompletion, String> {
+        Ok(ChatCompletion { content: Self::reply(messages), prompt_tokens: 0, completion_tokens: 0, tool_calls: Vec::new() })
+    }
+
+    /// The reply word by word
+    async fn stream(&self, messages: &[ChatMessage], _tools: &[Value]) -> Result<TokenStream, String> {
+        let words: Vec<_> = Self::reply(messages)
+            .split_inclusive(' ')
+            .map(|w| Ok(ChatDelta::Text(w.to_string())))
+            .collect();
+        Ok(stream::iter(words).boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_deltas_parsed_across_chunk_boundaries() {
+        let mut parser = StreamParser::default();
+        let mut deltas = VecDeque::new();
+
+        assert!(!parser.feed("data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"Hel", &mut deltas));
+        assert!(deltas.is_empty());
+
+        assert!(parser.feed("lo\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\" there\"}}]}\n\ndata: [DONE]\n\n", &mut deltas));
+        assert_eq!(deltas, [ChatDelta::Text("Hello".into()), ChatDelta::Text(" there".into())]);
+    }
+
+    #[test]
+    fn test_streamed_tool_calls_are_assembled() {
+        let mut parser = StreamParser::default();
+        let mut deltas = VecDeque::new();
+        let lines = [
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"count_entities","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"entity_type\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"deal\"}"}}]}}]}"#,
+        ];
+        for line in lines {
+            parser.feed(&format!("data: {}\n\n", line), &mut deltas);
+        }
+        assert!(deltas.is_empty());
+        assert!(parser.feed("data: [DONE]\n\n", &mut deltas));
+
+        let expected = ToolRequest { id: "call_1".into(), name: "count_entities".into(), arguments: r#"{"entity_type":"deal"}"#.into() };
+        assert_eq!(deltas, [ChatDelta::ToolCalls(vec![expected])]);
+    }
+
+    /// Asks for one tool, then answers with what it returned
+    struct ScriptedProvider;
+
+    #[async_trait]
+    impl ChatProvider for ScriptedProvider {
+        async fn complete(&self, messages: &[ChatMessage], tools: &[Value]) -> Result<ChatCompletion, String> {
+            let (content, tool_calls) = match messages.last() {
+                Some(ChatMessage::ToolResult { content, .. }) => (format!("You have {}", content), Vec::new()),
+                _ => {
+                    assert!(!tools.is_empty());
+                    let request = ToolRequest {
+                        id: "call_1".into(),
+                        name: "count_entities".into(),
+                        arguments: r#"{"entity_type":"deal","filter":{"field":"stage","op":"neq","value":"closed"}}"#.into(),
+                    };
+                    (String::new(), vec![request])
+                }
+            };
+            Ok(ChatCompletion { content, prompt_tokens: 10, completion_tokens: 5, tool_calls })
+        }
+
+        async fn stream(&self, _messages: &[ChatMessage], _tools: &[Value]) -> Result<TokenStream, String> {
+            unimplemented!()
+        }
+    }
+
+    /// Records what it was asked to run
+    #[derive(Default)]
+    struct RecordingRunner(Mutex<Vec<ToolRequest>>);
+
+    #[async_trait]
+    impl ToolRunner for RecordingRunner {
+        fn definitions(&self) -> Vec<Value> {
+            vec![serde_json::json!({ "type": "function", "function": { "name": "count_entities" } })]
+        }
+
+        async fn run(&self, request: &ToolRequest) -> Value {
+            self.0.lock().unwrap().push(request.clone());
+            serde_json::json!({ "count": 7 })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requested_tool_is_run_and_result_returned() {
+        let runner = RecordingRunner::default();
+        let mut messages = vec![ChatMessage::User("How many open deals do I have?".into())];
+
+        let reply = complete_with_tools(&ScriptedProvider, &runner, &mut messages).await.unwrap();
+
+        let runs = runner.0.lock().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].name, "count_entities");
+        let arguments: Value = serde_json::from_str(&runs[0].arguments).unwrap();
+        assert_eq!(arguments["entity_type"], "deal");
+        assert_eq!(arguments["filter"]["op"], "neq");
+
+        assert_eq!(reply.content, r#"You have {"count":7}"#);
+        assert_eq!((reply.prompt_tokens, reply.completion_tokens), (20, 10));
+        assert!(matches!(&messages[1], ChatMessage::ToolCalls(calls) if calls[0].id == "call_1"));
+        assert_eq!(messages[2], ChatMessage::ToolResult { call_id: "call_1".into(), content: r#"{"count":7}"#.into() });
+    }
+}
@@ -191,6 +191,17 @@ impl EmbeddingService for OpenAIEmbeddingProvider {
     }
 }
 
+/// An embedding in pgvector's text format, for binding as `$n::vector`
+pub fn vector_literal(embedding: &[f32]) -> String {
+    format!(
+        "[{}]",
+        embedding.iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
 /// Store an entity embedding in the database
 pub async fn store_entity_embedding(
     pool: &PgPool,
@@ -204,14 +215,7 @@ pub async fn store_entity_embedding(
     // Create content hash
     let content_hash = format!("{:x}", md5::compute(content_preview.unwrap_or("")));
     
-    // Convert embedding to pgvector format
-    let embedding_str = format!(
-        "[{}]",
-        embedding.iter()
-            .map(|f| f.to_string())
-            .collect::<Vec<_>>()
-            .join(",")
-    );
+    let embedding_str = vector_literal(embedding);
     
     sqlx::query(
         r#"
@@ -247,13 +251,7 @@ pub async fn find_similar_entities(
     limit: i32,
     threshold: f32,
 ) -> Result<Vec<EntityWithSimilarity>, EmbeddingError> {
-    let embedding_str = format!(
-        "[{}]",
-        query_embedding.iter()
-            .map(|f| f.to_string())
-            .collect::<Vec<_>>()
-            .join(",")
-    );
+    let embedding_str = vector_literal(query_embedding);
     
     let rows = sqlx::query_as::<_, (Uuid, String, f32, Option<String>)>(
         r#"
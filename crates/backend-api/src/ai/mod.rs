@@ -1,3 +1,11 @@
+pub mod chat;
 pub mod embeddings;
+pub mod rag;
+pub mod record_embeddings;
+pub mod redaction;
 pub mod service;
+pub mod tools;
+pub mod transcription;
 pub mod openai;
+
+pub use rag::{delete_document, ingest_document, retrieve, Document, RetrievedChunk};
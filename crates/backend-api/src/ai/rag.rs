@@ -0,0 +1,299 @@
+//! Document RAG - grounds AI chat on a tenant's own documents
+//!
+//! `ingest_document` splits a document into overlapping chunks, embeds them
+//! and stores them in `document_chunks`; `retrieve` finds the chunks closest
+//! to a question. Ingesting a document id again replaces its chunks, so an
+//! edited document never leaves stale text behind.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+
+use crate::ai::embeddings::{vector_literal, EmbeddingError, EmbeddingService, EMBEDDING_DIM};
+
+/// Characters per chunk; about 300 tokens of English
+pub const CHUNK_CHARS: usize = 1200;
+
+/// Characters repeated at the start of the next chunk, so a sentence cut at
+/// a boundary is still whole in one of them
+pub const CHUNK_OVERLAP: usize = 200;
+
+/// Chunks sent to the embedder per request
+const EMBED_BATCH_SIZE: usize = 64;
+
+/// A document to ground answers on
+#[derive(Debug, Clone, Deserialize)]
+pub struct Document {
+    /// Caller-chosen id; ingesting the same id again replaces the document
+    pub id: Uuid,
+    pub title: String,
+    pub text: String,
+}
+
+/// A stored chunk and how close it is to the query
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievedChunk {
+    pub document_id: Uuid,
+    pub title: String,
+    pub chunk_index: i32,
+    pub content: String,
+    /// Cosine similarity, 1.0 for identical direction
+    pub similarity: f32,
+}
+
+/// Split `text` into chunks of at most `size` characters, each starting
+/// `overlap` characters before the previous one ended
+///
+/// A chunk ends at the last whitespace that keeps it longer than the
+/// overlap, so words aren't cut unless one is longer than that.
+pub fn chunk_text(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    assert!(overlap < size, "chunk overlap must be smaller than the chunk size");
+    let chars: Vec<char> = text.chars().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < chars.len() {
+        let mut end = (start + size).min(chars.len());
+        if end < chars.len() {
+            if let Some(space) = (start + overlap + 1..end).rev().find(|&i| chars[i].is_whitespace()) {
+                end = space;
+            }
+        }
+        let chunk: String = chars[start..end].iter().collect();
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk);
+        }
+        if end == chars.len() {
+            break;
+        }
+        start = end - overlap;
+    }
+    chunks
+}
+
+/// Chunk, embed and store `doc`, replacing what was stored under its id;
+/// returns the number of chunks
+pub async fn ingest_document(
+    conn: &mut PgConnection,
+    embedder: &dyn EmbeddingService,
+    tenant_id: Uuid,
+    doc: &Document,
+) -> Result<usize, EmbeddingError> {
+    let chunks = chunk_text(&doc.text, CHUNK_CHARS, CHUNK_OVERLAP);
+
+    // Embed before touching the stored chunks, so a failed call keeps the old ones
+    let mut embeddings = Vec::with_capacity(chunks.len());
+    for batch in chunks.chunks(EMBED_BATCH_SIZE) {
+        let results = embedder.embed_batch(batch).await?;
+        if results.len() != batch.len() {
+            return Err(EmbeddingError::ApiError(format!("Expected {} embeddings, got {}", batch.len(), results.len())));
+        }
+        embeddings.extend(results.into_iter().map(|r| r.embedding));
+    }
+    if let Some(bad) = embeddings.iter().find(|e| e.len() != EMBEDDING_DIM) {
+        return Err(EmbeddingError::InvalidInput(format!("Embedding has {} dimensions, expected {}", bad.len(), EMBEDDING_DIM)));
+    }
+
+    let mut tx = conn.begin().await?;
+    sqlx::query("DELETE FROM document_chunks WHERE tenant_id = $1 AND document_id = $2")
+        .bind(tenant_id)
+        .bind(doc.id)
+        .execute(&mut *tx)
+        .await?;
+    for (index, (content, embedding)) in chunks.iter().zip(&embeddings).enumerate() {
+        sqlx::query(
+            "INSERT INTO document_chunks (tenant_id, document_id, title, chunk_index, content, embedding, model_name)
+             VALUES ($1, $2, $3, $4, $5, $6::vector, $7)",
+        )
+        .bind(tenant_id)
+        .bind(doc.id)
+        .bind(&doc.title)
+        .bind(index as i32)
+        .bind(content)
+        .bind(vector_literal(embedding))
+        .bind(embedder.model_name())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(chunks.len())
+}
+
+/// Remove a document's chunks; returns how many there were
+pub async fn delete_document(conn: &mut PgConnection, tenant_id: Uuid, document_id: Uuid) -> Result<u64, EmbeddingError> {
+    let result = sqlx::query("DELETE FROM document_chunks WHERE tenant_id = $1 AND document_id = $2")
+        .bind(tenant_id)
+        .bind(document_id)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// The `k` chunks most similar to `query`, closest first
+pub async fn retrieve(
+    conn: &mut PgConnection,
+    embedder: &dyn EmbeddingService,
+    tenant_id: Uuid,
+    query: &str,
+    k: i64,
+) -> Result<Vec<RetrievedChunk>, EmbeddingError> {
+    let embedding = embedder.embed(query).await?.embedding;
+    nearest_chunks(conn, tenant_id, &embedding, k).await
+}
+
+/// The `k` chunks nearest to an already computed query embedding
+pub async fn nearest_chunks(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    embedding: &[f32],
+    k: i64,
+) -> Result<Vec<RetrievedChunk>, EmbeddingError> {
+    let rows = sqlx::query_as::<_, (Uuid, String, i32, String, f64)>(
+        "SELECT document_id, title, chunk_index, content, 1 - (embedding <=> $1::vector) AS similarity
+         FROM document_chunks
+         WHERE tenant_id = $2
+         ORDER BY embedding <=> $1::vector
+         LIMIT $3",
+    )
+    .bind(vector_literal(embedding))
+    .bind(tenant_id)
+    .bind(k)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(document_id, title, chunk_index, content, similarity)| RetrievedChunk {
+            document_id,
+            title,
+            chunk_index,
+            content,
+            similarity: similarity as f32,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::embeddings::EmbeddingResult;
+    use async_trait::async_trait;
+    use test_support::get_test_pool;
+
+    #[test]
+    fn test_chunks_overlap_at_boundaries() {
+        let text: String = ('a'..='z').collect();
+        let chunks = chunk_text(&text, 10, 3);
+        assert_eq!(chunks, vec!["abcdefghij", "hijklmnopq", "opqrstuvwx", "vwxyz"]);
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0][7..], pair[1][..3]);
+        }
+
+        // Chunks end on whitespace, not mid-word, and never exceed the size
+        let text = "the quick brown fox jumps over the lazy dog";
+        let chunks = chunk_text(text, 16, 4);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 16));
+        assert_eq!(chunks[0], "the quick brown");
+        assert!(chunks[1].starts_with("rown "));
+        assert!(chunks.last().unwrap().ends_with("lazy dog"));
+
+        assert_eq!(chunk_text("short", 10, 3), vec!["short"]);
+        assert!(chunk_text("", 10, 3).is_empty());
+    }
+
+    /// Embeds a text as its counts of a few topic words
+    struct TopicEmbedder;
+
+    const TOPICS: [&str; 3] = ["parking", "pool", "pets"];
+
+    fn topic_vector(text: &str) -> Vec<f32> {
+        let text = text.to_lowercase();
+        let mut v = vec![0.0; EMBEDDING_DIM];
+        for (i, topic) in TOPICS.iter().enumerate() {
+            v[i] = text.matches(topic).count() as f32;
+        }
+        // Keeps topic-free text off the zero vector
+        v[TOPICS.len()] = 0.1;
+        v
+    }
+
+    #[async_trait]
+    impl EmbeddingService for TopicEmbedder {
+        async fn embed(&self, text: &str) -> Result<EmbeddingResult, EmbeddingError> {
+            Ok(EmbeddingResult { embedding: topic_vector(text), model: "topics".into(), tokens_used: 0 })
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<EmbeddingResult>, EmbeddingError> {
+            let mut results = Vec::new();
+            for text in texts {
+                results.push(self.embed(text).await?);
+            }
+            Ok(results)
+        }
+
+        fn model_name(&self) -> &str {
+            "topics"
+        }
+    }
+
+    async fn chunk_count(conn: &mut PgConnection, document_id: Uuid) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM document_chunks WHERE document_id = $1")
+            .bind(document_id)
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_retrieve_returns_most_similar_chunk() {
+        let pool = get_test_pool().await;
+        // Needs the pgvector extension
+        let has_table: bool = sqlx::query_scalar("SELECT to_regclass('document_chunks') IS NOT NULL")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        if !has_table {
+            return;
+        }
+        let tenant_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'RAG Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("rag-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        // Each topic spans a few chunks, so some hold nothing else
+        let section = |sentence: &str| sentence.repeat(2 * CHUNK_CHARS / sentence.len() + 1);
+        let handbook = Document {
+            id: Uuid::new_v4(),
+            title: "Building handbook".into(),
+            text: [
+                section("Residents may use the pool from 6am to 10pm. "),
+                section("Each unit has one covered parking bay in the basement. "),
+                section("Small pets are welcome with a signed agreement. "),
+            ]
+            .join("\n\n"),
+        };
+        let stored = ingest_document(&mut conn, &TopicEmbedder, tenant_id, &handbook).await.unwrap();
+        assert!(stored > 3);
+
+        let results = retrieve(&mut conn, &TopicEmbedder, tenant_id, "Where is visitor parking?", 3).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].content.contains("parking bay"));
+        assert!(!results[0].content.contains("pool") && !results[0].content.contains("pets"));
+        assert!(results[0].similarity >= results[1].similarity);
+
+        // Re-ingesting replaces the old chunks, deleting removes them
+        let edited = Document { text: "Pets must be kept on a lead.".into(), ..handbook.clone() };
+        assert_eq!(ingest_document(&mut conn, &TopicEmbedder, tenant_id, &edited).await.unwrap(), 1);
+        assert_eq!(chunk_count(&mut conn, handbook.id).await, 1);
+        assert_eq!(delete_document(&mut conn, tenant_id, handbook.id).await.unwrap(), 1);
+        assert_eq!(chunk_count(&mut conn, handbook.id).await, 0);
+
+        sqlx::query("DELETE FROM tenants WHERE id = $1").bind(tenant_id).execute(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+//! Record Embeddings - semantic search over fields marked `intelligence.embed`
+//!
+//! Saving a record queues the text of its embeddable fields in
+//! `entity_embeddings.pending_content`, but only when that text changed: a
+//! record whose embeddable fields are as they were keeps its vector and
+//! costs nothing. The jobs runner embeds queued rows in batches and clears
+//! the queue; until then searches use the previous vector. PII is masked
+//! per the tenant's policy before the text is queued.
+
+use core_models::FieldDef;
+use core_node_engine::{EntityEvent, EventType};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::ai::redaction;
+use crate::state::AppState;
+
+/// The embeddable fields of `data` as `Label: value` lines, or `None` when
+/// they are all empty
+pub fn embeddable_content(fields: &[FieldDef], data: &Value) -> Option<String> {
+    let lines: Vec<String> = fields
+        .iter()
+        .filter(|f| f.intelligence.embed)
+        .filter_map(|f| {
+            let text = match data.get(&f.name)? {
+                Value::Null => return None,
+                Value::String(s) => s.trim().to_string(),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                other => other.to_string(),
+            };
+            (!text.is_empty()).then(|| format!("{}: {}", f.label, text))
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// SHA-256 of the embedded text, to tell whether it changed
+pub fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Queue the record for embedding if its embeddable text changed; returns
+/// whether it was queued
+///
+/// A record left with no embeddable text loses its vector.
+pub async fn queue_record_embedding(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    record_id: Uuid,
+    entity_type: &str,
+    fields: &[FieldDef],
+    data: &Value,
+) -> Result<bool, sqlx::Error> {
+    let Some(content) = embeddable_content(fields, data) else {
+        sqlx::query("DELETE FROM entity_embeddings WHERE tenant_id = $1 AND entity_id = $2")
+            .bind(tenant_id)
+            .bind(record_id)
+            .execute(conn)
+            .await?;
+        return Ok(false);
+    };
+
+    let queued = sqlx::query(
+        r#"
+        INSERT INTO entity_embeddings (tenant_id, entity_id, entity_type, content_hash, pending_content)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (tenant_id, entity_id) DO UPDATE SET
+            entity_type = EXCLUDED.entity_type,
+            content_hash = EXCLUDED.content_hash,
+            pending_content = EXCLUDED.pending_content
+        WHERE entity_embeddings.content_hash IS DISTINCT FROM EXCLUDED.content_hash
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(record_id)
+    .bind(entity_type)
+    .bind(content_hash(&content))
+    .bind(&content)
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(queued == 1)
+}
+
+/// Queue the record `event` saved for embedding, in the background, if the
+/// entity has embeddable fields
+pub(crate) fn spawn_record_embedding(state: Arc<AppState>, entity_type_id: Uuid, event: &EntityEvent) {
+    if !matches!(event.event_type, EventType::Create | EventType::Update) {
+        return;
+    }
+    let Some(data) = event.new_values.clone() else {
+        return;
+    };
+    let (tenant_id, record_id, entity_type) = (event.tenant_id, event.record_id, event.entity_type.clone());
+    let changed = event.changed_fields.clone();
+
+    tokio::spawn(async move {
+        let fields = match state.metadata.get_fields(tenant_id, entity_type_id).await {
+            Ok(f) => f,
+            Err(e) => return tracing::warn!("Embedding skipped, fields unavailable: {}", e),
+        };
+        // Updates that touch no embeddable field can't change the text
+        let touched = |f: &FieldDef| changed.as_ref().is_none_or(|c| c.contains(&f.name));
+        if !fields.iter().any(|f| f.intelligence.embed && touched(f)) {
+            return;
+        }
+
+        let result = match state.pool.acquire().await {
+            Ok(mut conn) => match redaction::tenant_policy(&mut *conn, tenant_id).await {
+                Ok(policy) => {
+                    let data = redaction::redact_record(&fields, &data, policy).data;
+                    queue_record_embedding(&mut conn, tenant_id, record_id, &entity_type, &fields, &data).await
+                }
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Queueing embedding of record {} failed: {}", record_id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::FieldType;
+    use serde_json::json;
+    use test_support::get_test_pool;
+
+    fn listing_fields() -> Vec<FieldDef> {
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut title = FieldDef::new(tenant_id, entity_type_id, "title", "Title", FieldType::Text);
+        title.intelligence.embed = true;
+        let mut description = FieldDef::new(tenant_id, entity_type_id, "description", "Description", FieldType::TextArea);
+        description.intelligence.embed = true;
+        let price = FieldDef::new(tenant_id, entity_type_id, "price", "Price", FieldType::Number { decimals: None });
+        vec![title, description, price]
+    }
+
+    #[test]
+    fn test_content_covers_only_embeddable_fields() {
+        let fields = listing_fields();
+        let data = json!({ "title": "Marina loft", "description": " Sea view ", "price": 1200000 });
+        assert_eq!(embeddable_content(&fields, &data).as_deref(), Some("Title: Marina loft\nDescription: Sea view"));
+        assert_eq!(embeddable_content(&fields, &json!({ "title": "", "price": 5 })), None);
+
+        // Price isn't embedded, so changing it leaves the hash alone
+        let repriced = json!({ "title": "Marina loft", "description": " Sea view ", "price": 990000 });
+        let hash = |d: &Value| content_hash(&embeddable_content(&fields, d).unwrap());
+        assert_eq!(hash(&data), hash(&repriced));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_only_embeddable_edits_requeue() {
+        let pool = get_test_pool().await;
+        // Needs the pgvector extension
+        let has_table: bool = sqlx::query_scalar("SELECT to_regclass('entity_embeddings') IS NOT NULL")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        if !has_table {
+            return;
+        }
+        let (tenant_id, record_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Embedding Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("embed-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let fields = listing_fields();
+        let mut conn = pool.acquire().await.unwrap();
+        // What the jobs runner does once the vector is stored
+        let mark_embedded = "UPDATE entity_embeddings SET pending_content = NULL WHERE entity_id = $1";
+        let pending = "SELECT pending_content FROM entity_embeddings WHERE entity_id = $1";
+
+        let data = json!({ "title": "Marina loft", "description": "Sea view", "price": 1200000 });
+        assert!(queue_record_embedding(&mut conn, tenant_id, record_id, "listing", &fields, &data).await.unwrap());
+        sqlx::query(mark_embedded).bind(record_id).execute(&mut *conn).await.unwrap();
+
+        let repriced = json!({ "title": "Marina loft", "description": "Sea view", "price": 990000 });
+        assert!(!queue_record_embedding(&mut conn, tenant_id, record_id, "listing", &fields, &repriced).await.unwrap());
+        let queued: Option<String> = sqlx::query_scalar(pending).bind(record_id).fetch_one(&mut *conn).await.unwrap();
+        assert_eq!(queued, None);
+
+        let redescribed = json!({ "title": "Marina loft", "description": "Sea view, private pool", "price": 990000 });
+        assert!(queue_record_embedding(&mut conn, tenant_id, record_id, "listing", &fields, &redescribed).await.unwrap());
+        let queued: Option<String> = sqlx::query_scalar(pending).bind(record_id).fetch_one(&mut *conn).await.unwrap();
+        assert_eq!(queued.as_deref(), Some("Title: Marina loft\nDescription: Sea view, private pool"));
+
+        sqlx::query("DELETE FROM tenants WHERE id = $1").bind(tenant_id).execute(&pool).await.unwrap();
+    }
+}
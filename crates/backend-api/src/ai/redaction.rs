@@ -0,0 +1,189 @@
+//! PII Redaction - masks fields marked `intelligence.is_pii` before record
+//! data goes to the AI provider
+//!
+//! Unless the tenant sets `settings.ai.allow_pii`, every PII value is
+//! dropped, hashed or replaced with a placeholder (`settings.ai.pii_redaction`,
+//! placeholder by default) in prompts, chat context and embedded text.
+
+use chrono::Utc;
+use core_models::FieldDef;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::middleware::audit_log::{insert_entry, AuditAction, AuditLogEntry};
+
+/// How a PII value is masked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    /// Leave the field out
+    Drop,
+    /// A short digest, so the model can tell equal values apart
+    Hash,
+    /// `[<Label> redacted]`
+    #[default]
+    Placeholder,
+}
+
+impl RedactionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RedactionMode::Drop => "drop",
+            RedactionMode::Hash => "hash",
+            RedactionMode::Placeholder => "placeholder",
+        }
+    }
+}
+
+/// A tenant's rules for PII sent to the AI provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PiiPolicy {
+    /// PII is sent as is; off unless the tenant turns it on
+    pub allow_pii: bool,
+    pub mode: RedactionMode,
+}
+
+impl PiiPolicy {
+    /// Read from the tenant's `settings.ai`
+    pub fn from_settings(settings: &Value) -> Self {
+        let ai = settings.get("ai");
+        PiiPolicy {
+            allow_pii: ai.and_then(|a| a.get("allow_pii")).and_then(Value::as_bool).unwrap_or(false),
+            mode: ai
+                .and_then(|a| a.get("pii_redaction"))
+                .and_then(|m| serde_json::from_value(m.clone()).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Record data with its PII masked
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redacted {
+    pub data: Value,
+    /// Names of the fields that were masked
+    pub fields: Vec<String>,
+    pub mode: RedactionMode,
+}
+
+/// Mask the PII fields of `data` under `policy`
+pub fn redact_record(fields: &[FieldDef], data: &Value, policy: PiiPolicy) -> Redacted {
+    let mut redacted = Redacted { data: data.clone(), fields: Vec::new(), mode: policy.mode };
+    let Some(map) = redacted.data.as_object_mut().filter(|_| !policy.allow_pii) else {
+        return redacted;
+    };
+
+    for field in fields.iter().filter(|f| f.intelligence.is_pii) {
+        let Some(value) = map.get(&field.name).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        match policy.mode {
+            RedactionMode::Drop => {
+                map.remove(&field.name);
+            }
+            RedactionMode::Hash => {
+                let text = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                let digest = hex::encode(Sha256::digest(text.as_bytes()));
+                map.insert(field.name.clone(), Value::String(format!("pii:{}", &digest[..12])));
+            }
+            RedactionMode::Placeholder => {
+                map.insert(field.name.clone(), Value::String(format!("[{} redacted]", field.label)));
+            }
+        }
+        redacted.fields.push(field.name.clone());
+    }
+    redacted
+}
+
+/// The tenant's PII policy; the default when the tenant is unknown
+pub async fn tenant_policy<'e, E: sqlx::PgExecutor<'e>>(executor: E, tenant_id: Uuid) -> Result<PiiPolicy, sqlx::Error> {
+    let settings: Option<Value> = sqlx::query_scalar("SELECT settings FROM tenants WHERE id = $1")
+        .bind(tenant_id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(settings.map(|s| PiiPolicy::from_settings(&s)).unwrap_or_default())
+}
+
+/// Note in the audit trail that a record was redacted on its way to the
+/// AI provider; nothing is written when no field was masked
+pub async fn audit_redaction<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    entity_type: &str,
+    record_id: Uuid,
+    redacted: &Redacted,
+    purpose: &str,
+) -> Result<(), sqlx::Error> {
+    if redacted.fields.is_empty() {
+        return Ok(());
+    }
+    let entry = AuditLogEntry {
+        id: Uuid::new_v4(),
+        tenant_id,
+        user_id,
+        action: AuditAction::PiiRedacted.to_string(),
+        resource_type: entity_type.to_string(),
+        resource_id: Some(record_id),
+        details: serde_json::json!({
+            "fields": redacted.fields,
+            "mode": redacted.mode.as_str(),
+            "purpose": purpose,
+        }),
+        changes: Vec::new(),
+        ip_address: None,
+        user_agent: None,
+        created_at: Utc::now(),
+    };
+    insert_entry(executor, &entry).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::FieldType;
+    use serde_json::json;
+
+    fn contact_fields() -> Vec<FieldDef> {
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut email = FieldDef::new(tenant_id, entity_type_id, "email", "Email", FieldType::Email);
+        email.intelligence.is_pii = true;
+        vec![FieldDef::new(tenant_id, entity_type_id, "name", "Name", FieldType::Text), email]
+    }
+
+    #[test]
+    fn test_pii_fields_are_masked_by_mode() {
+        let fields = contact_fields();
+        let data = json!({ "name": "Sara Khan", "email": "sara@example.com" });
+        let policy = |mode| PiiPolicy { allow_pii: false, mode };
+
+        let placeholder = redact_record(&fields, &data, policy(RedactionMode::Placeholder));
+        assert_eq!(placeholder.data, json!({ "name": "Sara Khan", "email": "[Email redacted]" }));
+        assert_eq!(placeholder.fields, vec!["email"]);
+
+        let dropped = redact_record(&fields, &data, policy(RedactionMode::Drop));
+        assert_eq!(dropped.data, json!({ "name": "Sara Khan" }));
+
+        let hashed = redact_record(&fields, &data, policy(RedactionMode::Hash));
+        let email = hashed.data["email"].as_str().unwrap();
+        assert!(email.starts_with("pii:") && !email.contains("sara"));
+        assert_eq!(hashed.data, redact_record(&fields, &data, policy(RedactionMode::Hash)).data);
+
+        let allowed = redact_record(&fields, &data, PiiPolicy { allow_pii: true, ..Default::default() });
+        assert_eq!(allowed.data, data);
+        assert!(allowed.fields.is_empty());
+    }
+
+    #[test]
+    fn test_policy_from_tenant_settings() {
+        assert_eq!(PiiPolicy::from_settings(&json!({})), PiiPolicy::default());
+        assert_eq!(
+            PiiPolicy::from_settings(&json!({ "ai": { "allow_pii": true, "pii_redaction": "hash" } })),
+            PiiPolicy { allow_pii: true, mode: RedactionMode::Hash }
+        );
+        // An unknown mode keeps the default
+        assert_eq!(PiiPolicy::from_settings(&json!({ "ai": { "pii_redaction": "shred" } })).mode, RedactionMode::Placeholder);
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 use core_node_engine::ai::AiService;
+use crate::ai::chat::{ChatProvider, MockChatProvider, OpenAiChatProvider};
+use crate::ai::embeddings::{EmbeddingService, OpenAIEmbeddingProvider};
 use crate::ai::openai::OpenAiService;
+use crate::ai::transcription::{MockSttProvider, SttProvider, WhisperSttProvider};
 
 pub fn create_ai_service() -> Arc<dyn AiService> {
     // Check environment variable for API key
@@ -8,3 +11,26 @@ pub fn create_ai_service() -> Arc<dyn AiService> {
     
     Arc::new(OpenAiService::new(api_key))
 }
+
+/// The model behind AI chat; echoes messages without an OpenAI key
+pub fn create_chat_provider() -> Arc<dyn ChatProvider> {
+    match std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty() && k != "mock") {
+        Some(api_key) => Arc::new(OpenAiChatProvider::new(api_key)),
+        None => Arc::new(MockChatProvider),
+    }
+}
+
+/// Transcribes call recordings; describes the audio without an OpenAI key
+pub fn create_stt_provider() -> Arc<dyn SttProvider> {
+    match std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty() && k != "mock") {
+        Some(api_key) => Arc::new(WhisperSttProvider::new(api_key)),
+        None => Arc::new(MockSttProvider),
+    }
+}
+
+/// The embedder for RAG; `None` without an OpenAI key, which turns retrieval off
+pub fn create_embedding_service() -> Option<Arc<dyn EmbeddingService>> {
+    let api_key = std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty() && k != "mock")?;
+
+    Some(Arc::new(OpenAIEmbeddingProvider::new(api_key)))
+}
@@ -0,0 +1,392 @@
+//! Chat Tools - lets the model look things up in the tenant's records
+//!
+//! The model only picks a tool and its arguments. The tenant and user come
+//! from the request, and every call is checked against the caller's
+//! permissions before it touches the database, so no prompt can widen what
+//! a user may see. Records are redacted like any other data sent to the
+//! provider, and failures come back as `{"error"}` results the model can
+//! relay.
+
+use async_trait::async_trait;
+use core_models::{EntityType, FilterExpr};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::ai::chat::{ToolRequest, ToolRunner};
+use crate::ai::redaction;
+use crate::middleware::permission::{can_read_entity, AuthenticatedUser};
+use crate::routes::entities::{filter, lookup_options, record_from_row, LookupFilter};
+use crate::state::AppState;
+
+/// Most records one search returns
+pub const MAX_SEARCH_RESULTS: i64 = 10;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ToolError {
+    #[error("Invalid tool call: {0}")]
+    InvalidCall(String),
+    #[error("Not allowed to read {0} records")]
+    PermissionDenied(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("Tool failed: {0}")]
+    Failed(String),
+}
+
+impl From<sqlx::Error> for ToolError {
+    fn from(e: sqlx::Error) -> Self {
+        ToolError::Failed(e.to_string())
+    }
+}
+
+/// A tool call with its arguments checked
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "name", content = "arguments", rename_all = "snake_case")]
+pub enum ToolCall {
+    /// Records of a type, optionally filtered
+    CountEntities {
+        entity_type: String,
+        #[serde(default)]
+        filter: Option<FilterExpr>,
+    },
+    /// Records whose label starts with `query`, in one type or every type
+    /// the caller can read
+    Search {
+        query: String,
+        #[serde(default)]
+        entity_type: Option<String>,
+    },
+    GetRecord { entity_type: String, id: Uuid },
+}
+
+impl ToolCall {
+    /// Parse the tool `name` with the JSON `arguments` the model wrote
+    pub fn parse(name: &str, arguments: &str) -> Result<Self, ToolError> {
+        let arguments: Value = match arguments.trim() {
+            "" => json!({}),
+            raw => serde_json::from_str(raw).map_err(|e| ToolError::InvalidCall(e.to_string()))?,
+        };
+        serde_json::from_value(json!({ "name": name, "arguments": arguments }))
+            .map_err(|e| ToolError::InvalidCall(e.to_string()))
+    }
+
+    /// Fails unless the caller may read the entity type the call names
+    pub fn authorize(&self, caller: &ToolCaller) -> Result<(), ToolError> {
+        let entity_type = match self {
+            ToolCall::CountEntities { entity_type, .. } | ToolCall::GetRecord { entity_type, .. } => Some(entity_type),
+            ToolCall::Search { entity_type, .. } => entity_type.as_ref(),
+        };
+        match entity_type {
+            Some(entity_type) if !caller.can_read(entity_type) => Err(ToolError::PermissionDenied(entity_type.clone())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Who the tools act for
+#[derive(Debug, Clone)]
+pub struct ToolCaller {
+    pub tenant_id: Uuid,
+    /// `None` when no auth layer ran, like the record routes
+    pub user: Option<AuthenticatedUser>,
+}
+
+impl ToolCaller {
+    fn can_read(&self, entity_type: &str) -> bool {
+        self.user.as_ref().is_none_or(|u| can_read_entity(u, entity_type))
+    }
+}
+
+/// Function definitions in OpenAI's format
+pub fn definitions() -> Vec<Value> {
+    let function = |name: &str, description: &str, parameters: Value| {
+        json!({ "type": "function", "function": { "name": name, "description": description, "parameters": parameters } })
+    };
+    vec![
+        function(
+            "count_entities",
+            "Count the user's records of an entity type, e.g. deal or contact, optionally filtered",
+            json!({
+                "type": "object",
+                "properties": {
+                    "entity_type": { "type": "string" },
+                    "filter": {
+                        "type": "object",
+                        "description": "{\"field\", \"op\", \"value\"} with op one of eq, neq, contains, gt, gte, lt, lte, in, is_empty; or {\"and\": [...]} / {\"or\": [...]}"
+                    }
+                },
+                "required": ["entity_type"]
+            }),
+        ),
+        function(
+            "search",
+            "Find records whose name or title starts with the query",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "entity_type": { "type": "string", "description": "Only search this entity type" }
+                },
+                "required": ["query"]
+            }),
+        ),
+        function(
+            "get_record",
+            "Fetch one record by entity type and id",
+            json!({
+                "type": "object",
+                "properties": {
+                    "entity_type": { "type": "string" },
+                    "id": { "type": "string", "format": "uuid" }
+                },
+                "required": ["entity_type", "id"]
+            }),
+        ),
+    ]
+}
+
+/// The tools of one chat request
+pub struct ChatTools {
+    state: Arc<AppState>,
+    caller: ToolCaller,
+}
+
+impl ChatTools {
+    pub fn new(state: Arc<AppState>, caller: ToolCaller) -> Self {
+        Self { state, caller }
+    }
+
+    /// Run `call` as the caller
+    pub async fn execute(&self, call: &ToolCall) -> Result<Value, ToolError> {
+        call.authorize(&self.caller)?;
+
+        let mut conn = self.state.pool.acquire().await?;
+        sqlx::query("SELECT set_config('app.current_tenant', $1::text, false)")
+            .bind(self.caller.tenant_id)
+            .execute(&mut *conn)
+            .await?;
+
+        match call {
+            ToolCall::CountEntities { entity_type, filter } => self.count(&mut conn, entity_type, filter.as_ref()).await,
+            ToolCall::Search { query, entity_type } => self.search(&mut conn, query, entity_type.as_deref()).await,
+            ToolCall::GetRecord { entity_type, id } => {
+                let entity_type = self.entity_type(entity_type).await?;
+                let record = self.load_records(&mut conn, &entity_type, &[*id]).await?.pop();
+                record.ok_or_else(|| ToolError::NotFound(format!("No {} record {}", entity_type.name, id)))
+            }
+        }
+    }
+
+    async fn entity_type(&self, name: &str) -> Result<EntityType, ToolError> {
+        self.state
+            .metadata
+            .get_entity_type(self.caller.tenant_id, name)
+            .await
+            .map_err(|_| ToolError::NotFound(format!("Entity type '{}' not found", name)))
+    }
+
+    async fn count(&self, conn: &mut PgConnection, name: &str, filter: Option<&FilterExpr>) -> Result<Value, ToolError> {
+        let entity_type = self.entity_type(name).await?;
+        let fields = self.state.metadata.get_fields(self.caller.tenant_id, entity_type.id).await.unwrap_or_default();
+        let filter = filter
+            .map(|f| filter::compile(f, &fields, 3))
+            .transpose()
+            .map_err(|e| ToolError::InvalidCall(e.to_string()))?;
+
+        let sql = format!(
+            "SELECT COUNT(*) FROM entity_records WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL {}",
+            filter.as_ref().map(|f| format!("AND {}", f.sql)).unwrap_or_default()
+        );
+        let mut query = sqlx::query(&sql).bind(self.caller.tenant_id).bind(entity_type.id);
+        if let Some(f) = &filter {
+            query = f.bind(query);
+        }
+        let count: i64 = query.fetch_one(conn).await?.get(0);
+        Ok(json!({ "entity_type": name, "count": count }))
+    }
+
+    async fn search(&self, conn: &mut PgConnection, query: &str, name: Option<&str>) -> Result<Value, ToolError> {
+        let entity_types = match name {
+            Some(name) => vec![self.entity_type(name).await?],
+            None => self
+                .state
+                .metadata
+                .list_entity_types(self.caller.tenant_id, None)
+                .await
+                .map_err(|e| ToolError::Failed(e.to_string()))?
+                .into_iter()
+                .filter(|t| self.caller.can_read(&t.name))
+                .collect(),
+        };
+
+        let mut results = Vec::new();
+        for entity_type in &entity_types {
+            let remaining = MAX_SEARCH_RESULTS - results.len() as i64;
+            if remaining <= 0 {
+                break;
+            }
+            let options = lookup_options(conn, self.caller.tenant_id, entity_type, LookupFilter::Prefix(query.trim()), remaining).await?;
+            if options.is_empty() {
+                continue;
+            }
+            let ids: Vec<Uuid> = options.iter().map(|o| o.value).collect();
+            results.extend(self.load_records(conn, entity_type, &ids).await?);
+        }
+        Ok(json!({ "results": results }))
+    }
+
+    /// The live records with `ids`, PII masked per the tenant's policy
+    async fn load_records(&self, conn: &mut PgConnection, entity_type: &EntityType, ids: &[Uuid]) -> Result<Vec<Value>, ToolError> {
+        let tenant_id = self.caller.tenant_id;
+        let fields = self.state.metadata.get_fields(tenant_id, entity_type.id).await.unwrap_or_default();
+        let rows = sqlx::query(
+            "SELECT id, data, created_at, updated_at, deleted_at, version FROM entity_records
+             WHERE tenant_id = $1 AND entity_type_id = $2 AND id = ANY($3) AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(entity_type.id)
+        .bind(ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let policy = redaction::tenant_policy(&mut *conn, tenant_id).await?;
+        let user_id = self.caller.user.as_ref().map(|u| u.id);
+        let mut records = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let redacted = redaction::redact_record(&fields, &record_from_row(row, &fields), policy);
+            redaction::audit_redaction(&mut *conn, tenant_id, user_id, &entity_type.name, row.get("id"), &redacted, "ai_tool").await?;
+            records.push(json!({ "entity_type": entity_type.name, "record": redacted.data }));
+        }
+        Ok(records)
+    }
+}
+
+#[async_trait]
+impl ToolRunner for ChatTools {
+    fn definitions(&self) -> Vec<Value> {
+        definitions()
+    }
+
+    async fn run(&self, request: &ToolRequest) -> Value {
+        let result = match ToolCall::parse(&request.name, &request.arguments) {
+            Ok(call) => self.execute(&call).await,
+            Err(e) => Err(e),
+        };
+        result.unwrap_or_else(|e| {
+            tracing::debug!("Chat tool {} failed: {}", request.name, e);
+            json!({ "error": e.to_string() })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::{FilterCondition, FilterOp};
+    use sqlx::PgPool;
+    use test_support::get_test_pool;
+
+    fn user(tenant_id: Uuid, role: &str) -> AuthenticatedUser {
+        AuthenticatedUser {
+            id: Uuid::new_v4(),
+            tenant_id,
+            email: "agent@example.com".into(),
+            name: "Agent".into(),
+            role: role.into(),
+            roles: vec![role.into()],
+        }
+    }
+
+    #[test]
+    fn test_tool_calls_parse_with_arguments() {
+        let call = ToolCall::parse("count_entities", r#"{"entity_type":"deal","filter":{"field":"stage","op":"neq","value":"closed"}}"#);
+        let filter = FilterExpr::Condition(FilterCondition { field: "stage".into(), op: FilterOp::Neq, value: json!("closed") });
+        assert_eq!(call, Ok(ToolCall::CountEntities { entity_type: "deal".into(), filter: Some(filter) }));
+
+        assert_eq!(ToolCall::parse("search", r#"{"query":"Marina"}"#), Ok(ToolCall::Search { query: "Marina".into(), entity_type: None }));
+        assert!(matches!(ToolCall::parse("get_record", r#"{"entity_type":"deal","id":"nope"}"#), Err(ToolError::InvalidCall(_))));
+        assert!(matches!(ToolCall::parse("drop_tables", "{}"), Err(ToolError::InvalidCall(_))));
+    }
+
+    #[tokio::test]
+    async fn test_denied_tool_returns_relayable_error() {
+        // Denied before any query, so the pool never connects
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let tenant_id = Uuid::new_v4();
+        let tools = ChatTools::new(Arc::new(AppState::new(pool)), ToolCaller { tenant_id, user: Some(user(tenant_id, "member")) });
+
+        let request = ToolRequest {
+            id: "call_1".into(),
+            name: "get_record".into(),
+            arguments: json!({ "entity_type": "property", "id": Uuid::new_v4() }).to_string(),
+        };
+        assert_eq!(tools.run(&request).await, json!({ "error": "Not allowed to read property records" }));
+
+        // Searching every type skips the ones the caller can't read rather than failing
+        let search = ToolCall::Search { query: "Marina".into(), entity_type: None };
+        assert_eq!(search.authorize(&tools.caller), Ok(()));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_tools_only_see_the_callers_tenant() {
+        let pool = get_test_pool().await;
+        let (tenant_id, other_tenant_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut record_ids = Vec::new();
+        for (tenant, deals) in [(tenant_id, 3), (other_tenant_id, 2)] {
+            sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Tools Test', $2)")
+                .bind(tenant)
+                .bind(format!("tools-{}", tenant.simple()))
+                .execute(&pool)
+                .await
+                .unwrap();
+            let entity_type_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural)
+                 VALUES ($1, $2, 'crm', 'deal', 'Deal', 'Deals')",
+            )
+            .bind(entity_type_id)
+            .bind(tenant)
+            .execute(&pool)
+            .await
+            .unwrap();
+            for i in 0..deals {
+                let id: Uuid = sqlx::query_scalar(
+                    "INSERT INTO entity_records (tenant_id, entity_type_id, data) VALUES ($1, $2, $3) RETURNING id",
+                )
+                .bind(tenant)
+                .bind(entity_type_id)
+                .bind(json!({ "name": format!("Marina deal {}", i) }))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+                record_ids.push(id);
+            }
+        }
+        let tools = ChatTools::new(Arc::new(AppState::new(pool.clone())), ToolCaller { tenant_id, user: Some(user(tenant_id, "agent")) });
+
+        let count = tools.execute(&ToolCall::CountEntities { entity_type: "deal".into(), filter: None }).await.unwrap();
+        assert_eq!(count, json!({ "entity_type": "deal", "count": 3 }));
+
+        let found = tools.execute(&ToolCall::Search { query: "marina".into(), entity_type: None }).await.unwrap();
+        assert_eq!(found["results"].as_array().unwrap().len(), 3);
+
+        let own = tools.execute(&ToolCall::GetRecord { entity_type: "deal".into(), id: record_ids[0] }).await.unwrap();
+        assert_eq!(own["record"]["name"], "Marina deal 0");
+        let others = tools.execute(&ToolCall::GetRecord { entity_type: "deal".into(), id: record_ids[3] }).await;
+        assert!(matches!(others, Err(ToolError::NotFound(_))));
+
+        for tenant in [tenant_id, other_tenant_id] {
+            for sql in [
+                "DELETE FROM entity_records WHERE tenant_id = $1",
+                "DELETE FROM entity_types WHERE tenant_id = $1",
+                "DELETE FROM tenants WHERE id = $1",
+            ] {
+                sqlx::query(sql).bind(tenant).execute(&pool).await.unwrap();
+            }
+        }
+    }
+}
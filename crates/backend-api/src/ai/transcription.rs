@@ -0,0 +1,75 @@
+//! Speech to Text - transcribes call recordings
+//!
+//! Whisper is used when an OpenAI key is configured; without one the mock
+//! provider stands in, so recorded calls still get a timeline entry.
+
+use async_trait::async_trait;
+
+const WHISPER_MODEL: &str = "whisper-1";
+
+/// Turns recorded audio into text
+#[async_trait]
+pub trait SttProvider: Send + Sync {
+    /// The text spoken in `audio`
+    async fn transcribe(&self, audio: Vec<u8>, content_type: &str) -> Result<String, String>;
+}
+
+/// OpenAI Whisper transcriptions
+pub struct WhisperSttProvider {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl WhisperSttProvider {
+    pub fn new(api_key: String) -> Self {
+        Self { client: reqwest::Client::new(), api_key }
+    }
+}
+
+#[async_trait]
+impl SttProvider for WhisperSttProvider {
+    async fn transcribe(&self, audio: Vec<u8>, content_type: &str) -> Result<String, String> {
+        let extension = match content_type {
+            "audio/mpeg" => "mp3",
+            _ => "wav",
+        };
+        let file = reqwest::multipart::Part::bytes(audio)
+            .file_name(format!("recording.{}", extension))
+            .mime_str(content_type)
+            .map_err(|e| e.to_string())?;
+        let form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", WHISPER_MODEL);
+
+        let response = self
+            .client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .bearer_auth(&self.api_key)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Whisper request failed ({}): {}", status, body));
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        json["text"]
+            .as_str()
+            .map(|t| t.trim().to_string())
+            .ok_or_else(|| "Whisper response has no text".to_string())
+    }
+}
+
+/// Describes the audio instead of transcribing it, for running without an
+/// OpenAI key
+pub struct MockSttProvider;
+
+#[async_trait]
+impl SttProvider for MockSttProvider {
+    async fn transcribe(&self, audio: Vec<u8>, _content_type: &str) -> Result<String, String> {
+        Ok(format!("Mock transcript of {} bytes of audio", audio.len()))
+    }
+}
@@ -7,6 +7,8 @@ pub struct Config {
     pub database_url: String,
     pub port: u16,
     pub platform_domain: String,
+    /// Optional; Redis-backed features and health checks are off without it
+    pub redis_url: Option<String>,
 }
 
 impl Config {
@@ -20,6 +22,7 @@ impl Config {
                 .unwrap_or(3000),
             platform_domain: env::var("PLATFORM_DOMAIN")
                 .unwrap_or_else(|_| "saas.local".to_string()),
+            redis_url: env::var("REDIS_URL").ok().filter(|url| !url.is_empty()),
         }
     }
 }
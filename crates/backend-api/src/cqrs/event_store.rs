@@ -5,10 +5,13 @@
 
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::Value as JsonValue;
 use super::{DealEvent, Deal};
 
+/// Events fetched per round trip when replaying
+const REPLAY_BATCH_SIZE: i64 = 500;
+
 /// Default snapshot interval (events)
 /// Can be overridden via SNAPSHOT_INTERVAL env var
 const DEFAULT_SNAPSHOT_INTERVAL: u64 = 50;
@@ -35,12 +38,14 @@ impl EventStore {
         // Try to load from snapshot first
         let (mut deal, snapshot_version) = self.load_from_snapshot(aggregate_id).await?;
         
-        // Fetch only events after the snapshot
+        // Fetch only events after the snapshot. Events are stored at the
+        // version the aggregate had before them, so a snapshot at version N
+        // has already applied events 0..N
         let rows = sqlx::query(
             r#"
             SELECT event_data, aggregate_version
-            FROM events
-            WHERE aggregate_id = $1 AND aggregate_version > $2
+            FROM domain_events
+            WHERE aggregate_id = $1 AND aggregate_version >= $2
             ORDER BY aggregate_version ASC
             "#
         )
@@ -163,7 +168,7 @@ impl EventStore {
         let rows = sqlx::query(
             r#"
             SELECT event_data, aggregate_version
-            FROM events
+            FROM domain_events
             WHERE aggregate_id = $1
             ORDER BY aggregate_version ASC
             "#
@@ -212,12 +217,16 @@ impl EventStore {
             DealEvent::Closed { .. } => "DealClosed",
         };
         
-        // Insert event with optimistic concurrency check
+        // Insert event with optimistic concurrency check: only when the
+        // aggregate is exactly at `expected_version`. Two writers racing for
+        // the same version are caught by the (aggregate_id, aggregate_version)
+        // unique constraint instead.
         let result = sqlx::query(
             r#"
-            INSERT INTO events 
+            INSERT INTO domain_events
                 (aggregate_id, aggregate_type, aggregate_version, event_type, event_data, created_by)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE (SELECT COUNT(*) FROM domain_events WHERE aggregate_id = $1) = $3
             "#
         )
         .bind(aggregate_id)
@@ -230,7 +239,7 @@ impl EventStore {
         .await;
         
         match result {
-            Ok(_) => {
+            Ok(done) if done.rows_affected() == 1 => {
                 // Check if we should create a snapshot
                 if expected_version > 0 && expected_version % get_snapshot_interval() == 0 {
                     tokio::spawn({
@@ -242,16 +251,26 @@ impl EventStore {
                 }
                 Ok(())
             }
+            Ok(_) => Err(self.version_conflict(aggregate_id, expected_version).await),
             Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
-                Err(EventStoreError::ConcurrencyConflict {
-                    aggregate_id,
-                    expected_version,
-                })
+                Err(self.version_conflict(aggregate_id, expected_version).await)
             }
             Err(e) => Err(EventStoreError::DatabaseError(e.to_string())),
         }
     }
     
+    /// Current version of an aggregate: the number of events it has
+    pub async fn current_version(&self, aggregate_id: Uuid) -> Result<u64, EventStoreError> {
+        self.get_event_count(aggregate_id).await
+    }
+    
+    async fn version_conflict(&self, aggregate_id: Uuid, expected: u64) -> EventStoreError {
+        match self.current_version(aggregate_id).await {
+            Ok(actual) => EventStoreError::VersionConflict { expected, actual },
+            Err(e) => e,
+        }
+    }
+    
     /// Get all events for time travel / audit
     pub async fn get_event_stream(
         &self,
@@ -260,7 +279,7 @@ impl EventStore {
         let rows = sqlx::query(
             r#"
             SELECT event_data, aggregate_version, created_at
-            FROM events
+            FROM domain_events
             WHERE aggregate_id = $1
             ORDER BY aggregate_version ASC
             "#
@@ -290,7 +309,7 @@ impl EventStore {
     /// Get event count for an aggregate
     pub async fn get_event_count(&self, aggregate_id: Uuid) -> Result<u64, EventStoreError> {
         let row = sqlx::query(
-            "SELECT COUNT(*) as count FROM events WHERE aggregate_id = $1"
+            "SELECT COUNT(*) as count FROM domain_events WHERE aggregate_id = $1"
         )
         .bind(aggregate_id)
         .fetch_one(&self.pool)
@@ -303,6 +322,77 @@ impl EventStore {
         Ok(count as u64)
     }
     
+    /// All events of an aggregate type after `from_sequence`, in append order
+    ///
+    /// Sequence numbers are global across aggregates, so a projection can
+    /// record the last one it applied and resume from there.
+    pub async fn replay(&self, aggregate_type: &str, from_sequence: i64) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let mut events = Vec::new();
+        let mut after = from_sequence;
+        loop {
+            let batch = self.replay_batch(aggregate_type, after, REPLAY_BATCH_SIZE).await?;
+            let done = (batch.len() as i64) < REPLAY_BATCH_SIZE;
+            if let Some(last) = batch.last() {
+                after = last.sequence_number;
+            }
+            events.extend(batch);
+            if done {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Up to `limit` events of an aggregate type after `from_sequence`
+    pub async fn replay_batch(
+        &self,
+        aggregate_type: &str,
+        from_sequence: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sequence_number, event_id, aggregate_id, aggregate_type, aggregate_version,
+                   event_type, event_data, created_by, created_at
+            FROM domain_events
+            WHERE aggregate_type = $1 AND sequence_number > $2
+            ORDER BY sequence_number ASC
+            LIMIT $3
+            "#
+        )
+        .bind(aggregate_type)
+        .bind(from_sequence)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| EventStoreError::DatabaseError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(StoredEvent {
+                    sequence_number: row.try_get("sequence_number")?,
+                    event_id: row.try_get("event_id")?,
+                    aggregate_id: row.try_get("aggregate_id")?,
+                    aggregate_type: row.try_get("aggregate_type")?,
+                    aggregate_version: row.try_get("aggregate_version")?,
+                    event_type: row.try_get("event_type")?,
+                    event_data: row.try_get("event_data")?,
+                    created_by: row.try_get("created_by")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(|e| EventStoreError::DatabaseError(e.to_string()))
+    }
+    
+    /// Highest sequence number stored for an aggregate type (0 if none)
+    pub async fn last_sequence(&self, aggregate_type: &str) -> Result<i64, EventStoreError> {
+        sqlx::query_scalar("SELECT COALESCE(MAX(sequence_number), 0) FROM domain_events WHERE aggregate_type = $1")
+            .bind(aggregate_type)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| EventStoreError::DatabaseError(e.to_string()))
+    }
+    
     /// Cleanup old snapshots (keep only latest N)
     pub async fn cleanup_old_snapshots(&self, aggregate_id: Uuid, keep_count: i64) -> Result<u64, EventStoreError> {
         let result = sqlx::query(
@@ -327,15 +417,38 @@ impl EventStore {
     }
 }
 
+/// An event as stored in the log, with its position
+#[derive(Debug, Clone)]
+pub struct StoredEvent {
+    pub sequence_number: i64,
+    pub event_id: Uuid,
+    pub aggregate_id: Uuid,
+    pub aggregate_type: String,
+    pub aggregate_version: i64,
+    pub event_type: String,
+    pub event_data: JsonValue,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StoredEvent {
+    /// Decode the payload of a Deal event
+    pub fn deal_event(&self) -> Result<DealEvent, EventStoreError> {
+        serde_json::from_value(self.event_data.clone())
+            .map_err(|e| EventStoreError::DeserializationError(e.to_string()))
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum EventStoreError {
     #[error("Aggregate not found: {0}")]
     AggregateNotFound(Uuid),
     
-    #[error("Concurrency conflict for aggregate {aggregate_id} at version {expected_version}")]
-    ConcurrencyConflict {
-        aggregate_id: Uuid,
-        expected_version: u64,
+    /// Another writer advanced the aggregate; reload and retry
+    #[error("Version conflict: expected version {expected}, aggregate is at {actual}")]
+    VersionConflict {
+        expected: u64,
+        actual: u64,
     },
     
     #[error("Database error: {0}")]
@@ -0,0 +1,93 @@
+//! Deal Command Handler
+//!
+//! Runs commands against the current aggregate state and appends the
+//! resulting event. Every command on an existing deal names the version the
+//! caller last saw; if another writer got there first the command fails with
+//! `VersionConflict` instead of silently overwriting their change, and the
+//! caller can reload and retry.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use super::{Deal, DealError, DealEvent, EventStore, EventStoreError};
+use super::commands::*;
+
+/// Aggregate type recorded for Deal events
+pub const DEAL_AGGREGATE: &str = "Deal";
+
+pub struct DealCommandHandler {
+    store: EventStore,
+}
+
+impl DealCommandHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { store: EventStore::new(pool) }
+    }
+
+    /// Create a deal; returns the event and the new version (1)
+    pub async fn create(&self, cmd: CreateDealCommand) -> Result<(DealEvent, u64), CommandError> {
+        let created_by = cmd.created_by;
+        let event = Deal::create(cmd)?;
+        self.store.save_event(event.deal_id(), DEAL_AGGREGATE, 0, &event, created_by).await?;
+        Ok((event, 1))
+    }
+
+    /// Run a command against a deal the caller last saw at `expected_version`
+    ///
+    /// `decide` validates the command against the current state and returns
+    /// the event to append. Returns the event and the deal's new version.
+    pub async fn execute(
+        &self,
+        deal_id: Uuid,
+        expected_version: u64,
+        caused_by: Uuid,
+        decide: impl FnOnce(&Deal) -> Result<DealEvent, DealError>,
+    ) -> Result<(DealEvent, u64), CommandError> {
+        let deal = self.store.load_aggregate(deal_id).await?;
+        if deal.version != expected_version {
+            return Err(EventStoreError::VersionConflict {
+                expected: expected_version,
+                actual: deal.version,
+            }
+            .into());
+        }
+
+        let event = decide(&deal)?;
+        self.store.save_event(deal_id, DEAL_AGGREGATE, expected_version, &event, caused_by).await?;
+        Ok((event, expected_version + 1))
+    }
+
+    pub async fn update_stage(
+        &self,
+        cmd: UpdateDealStageCommand,
+        expected_version: u64,
+    ) -> Result<(DealEvent, u64), CommandError> {
+        let (deal_id, caused_by) = (cmd.deal_id, cmd.updated_by);
+        self.execute(deal_id, expected_version, caused_by, |deal| deal.update_stage(cmd)).await
+    }
+
+    pub async fn add_value(&self, cmd: AddValueCommand, expected_version: u64) -> Result<(DealEvent, u64), CommandError> {
+        let (deal_id, caused_by) = (cmd.deal_id, cmd.updated_by);
+        self.execute(deal_id, expected_version, caused_by, |deal| deal.add_value(cmd)).await
+    }
+
+    pub async fn close(&self, cmd: CloseDealCommand, expected_version: u64) -> Result<(DealEvent, u64), CommandError> {
+        let (deal_id, caused_by) = (cmd.deal_id, cmd.closed_by);
+        self.execute(deal_id, expected_version, caused_by, |deal| deal.close(cmd)).await
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error(transparent)]
+    Rejected(#[from] DealError),
+
+    #[error(transparent)]
+    Store(#[from] EventStoreError),
+}
+
+impl CommandError {
+    /// Whether the caller should reload the aggregate and retry
+    pub fn is_version_conflict(&self) -> bool {
+        matches!(self, CommandError::Store(EventStoreError::VersionConflict { .. }))
+    }
+}
@@ -9,6 +9,7 @@ pub mod aggregates;
 pub mod projections;
 pub mod event_store;
 pub mod event_bus;
+pub mod handlers;
 
 #[cfg(test)]
 mod tests;
@@ -18,3 +19,4 @@ pub use events::*;
 pub use aggregates::*;
 pub use event_store::*;
 pub use event_bus::*;
+pub use handlers::*;
@@ -4,11 +4,17 @@
 //! update the denormalized read models (entity_records table).
 //! 
 //! This keeps the existing UI working while we migrate to CQRS!
+//!
+//! Read models with their own tables implement [`Projection`] and can be
+//! rebuilt from the event store with [`rebuild`].
 
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use serde_json::json;
-use super::DealEvent;
+use super::{DealEvent, EventStore, StoredEvent};
+
+/// Events applied per transaction batch during a rebuild
+const REBUILD_BATCH_SIZE: i64 = 500;
 
 /// Deal Projection - updates entity_records table
 pub struct DealProjection {
@@ -191,8 +197,229 @@ impl DealProjection {
     }
 }
 
+/// A read model built purely from one aggregate type's events
+#[async_trait::async_trait]
+pub trait Projection: Send + Sync {
+    /// Name used for `projection_state` and [`rebuild`]
+    fn name(&self) -> &'static str;
+
+    /// Aggregate type whose events feed this projection
+    fn aggregate_type(&self) -> &'static str;
+
+    /// Tables owned by the projection; emptied before a rebuild
+    fn read_tables(&self) -> &'static [&'static str];
+
+    /// Apply one event; must be idempotent, since a live write racing a
+    /// rebuild may see the same event twice
+    async fn apply(&self, conn: &mut PgConnection, event: &StoredEvent) -> Result<(), ProjectionError>;
+}
+
+/// Look up a rebuildable projection by name
+pub fn projection(name: &str) -> Option<Box<dyn Projection>> {
+    match name {
+        "deal_summaries" => Some(Box::new(DealSummaryProjection)),
+        _ => None,
+    }
+}
+
+/// Progress of a running rebuild
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebuildProgress {
+    pub projection: &'static str,
+    pub processed: u64,
+    pub total: u64,
+}
+
+/// Empty a projection's read tables and reapply every event in order
+///
+/// Runs in one transaction holding an EXCLUSIVE lock on the read tables:
+/// queries keep seeing the old rows until the rebuilt ones commit, while
+/// live projection writes wait and then apply on top of the result.
+pub async fn rebuild(
+    pool: &PgPool,
+    projection_name: &str,
+    mut on_progress: impl FnMut(&RebuildProgress),
+) -> Result<RebuildProgress, ProjectionError> {
+    let projection = projection(projection_name)
+        .ok_or_else(|| ProjectionError::UnknownProjection(projection_name.to_string()))?;
+    let store = EventStore::new(pool.clone());
+    let db_err = |e: sqlx::Error| ProjectionError::DatabaseError(e.to_string());
+
+    let mut tx = pool.begin().await.map_err(db_err)?;
+    for table in projection.read_tables() {
+        sqlx::query(&format!("LOCK TABLE {} IN EXCLUSIVE MODE", table))
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        sqlx::query(&format!("DELETE FROM {}", table))
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+    }
+
+    // Tables are locked, so nothing appended from here on can be projected
+    // before we commit
+    let last_sequence = store
+        .last_sequence(projection.aggregate_type())
+        .await
+        .map_err(|e| ProjectionError::EventStoreError(e.to_string()))?;
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM domain_events WHERE aggregate_type = $1 AND sequence_number <= $2",
+    )
+    .bind(projection.aggregate_type())
+    .bind(last_sequence)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    let mut progress = RebuildProgress { projection: projection.name(), processed: 0, total: total as u64 };
+    let mut after = 0;
+    while after < last_sequence {
+        let batch = store
+            .replay_batch(projection.aggregate_type(), after, REBUILD_BATCH_SIZE)
+            .await
+            .map_err(|e| ProjectionError::EventStoreError(e.to_string()))?;
+        let Some(last) = batch.last() else { break };
+        after = last.sequence_number;
+
+        for event in batch.iter().filter(|e| e.sequence_number <= last_sequence) {
+            projection.apply(&mut tx, event).await?;
+            progress.processed += 1;
+        }
+        tracing::info!(
+            projection = progress.projection,
+            processed = progress.processed,
+            total = progress.total,
+            "Rebuilding projection"
+        );
+        on_progress(&progress);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO projection_state (projection_name, last_sequence_number, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (projection_name) DO UPDATE SET
+            last_sequence_number = EXCLUDED.last_sequence_number,
+            updated_at = EXCLUDED.updated_at
+        "#,
+    )
+    .bind(projection.name())
+    .bind(last_sequence)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_err)?;
+    tx.commit().await.map_err(db_err)?;
+
+    Ok(progress)
+}
+
+/// Deal Summary Projection - one row per deal in `deal_summaries`
+///
+/// Each row carries the aggregate version it reflects; an event only
+/// applies to a row that hasn't seen it yet.
+pub struct DealSummaryProjection;
+
+#[async_trait::async_trait]
+impl Projection for DealSummaryProjection {
+    fn name(&self) -> &'static str {
+        "deal_summaries"
+    }
+
+    fn aggregate_type(&self) -> &'static str {
+        "Deal"
+    }
+
+    fn read_tables(&self) -> &'static [&'static str] {
+        &["deal_summaries"]
+    }
+
+    async fn apply(&self, conn: &mut PgConnection, event: &StoredEvent) -> Result<(), ProjectionError> {
+        let deal_event = event
+            .deal_event()
+            .map_err(|e| ProjectionError::EventStoreError(e.to_string()))?;
+        let version = event.aggregate_version + 1;
+
+        let query = match &deal_event {
+            DealEvent::Created { deal_id, tenant_id, title, value, stage, contact_id, property_id, created_at, .. } => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO deal_summaries
+                        (deal_id, tenant_id, title, value, stage, contact_id, property_id, status, version, updated_at)
+                    VALUES ($1, $2, $3, $4::numeric, $5, $6, $7, 'active', $8, $9)
+                    ON CONFLICT (deal_id) DO NOTHING
+                    "#
+                )
+                .bind(deal_id)
+                .bind(tenant_id)
+                .bind(title)
+                .bind(value.map(|v| v.to_string()))
+                .bind(stage)
+                .bind(contact_id)
+                .bind(property_id)
+                .bind(version)
+                .bind(created_at)
+            }
+            DealEvent::StageUpdated { deal_id, new_stage, updated_at, .. } => {
+                sqlx::query("UPDATE deal_summaries SET stage = $1, version = $2, updated_at = $3 WHERE deal_id = $4 AND version < $2")
+                    .bind(new_stage)
+                    .bind(version)
+                    .bind(updated_at)
+                    .bind(deal_id)
+            }
+            DealEvent::ValueAdded { deal_id, new_value, updated_at, .. } => {
+                sqlx::query("UPDATE deal_summaries SET value = $1::numeric, version = $2, updated_at = $3 WHERE deal_id = $4 AND version < $2")
+                    .bind(new_value.to_string())
+                    .bind(version)
+                    .bind(updated_at)
+                    .bind(deal_id)
+            }
+            DealEvent::ContactAssigned { deal_id, contact_id, updated_at, .. } => {
+                sqlx::query("UPDATE deal_summaries SET contact_id = $1, version = $2, updated_at = $3 WHERE deal_id = $4 AND version < $2")
+                    .bind(contact_id)
+                    .bind(version)
+                    .bind(updated_at)
+                    .bind(deal_id)
+            }
+            DealEvent::PropertyAssigned { deal_id, property_id, updated_at, .. } => {
+                sqlx::query("UPDATE deal_summaries SET property_id = $1, version = $2, updated_at = $3 WHERE deal_id = $4 AND version < $2")
+                    .bind(property_id)
+                    .bind(version)
+                    .bind(updated_at)
+                    .bind(deal_id)
+            }
+            DealEvent::Closed { deal_id, outcome, final_value, closed_at, .. } => {
+                let status = match outcome {
+                    super::DealOutcome::Won => "won",
+                    super::DealOutcome::Lost => "lost",
+                };
+                sqlx::query(
+                    "UPDATE deal_summaries SET status = $1, value = COALESCE($2::numeric, value), version = $3, updated_at = $4 WHERE deal_id = $5 AND version < $3"
+                )
+                .bind(status)
+                .bind(final_value.map(|v| v.to_string()))
+                .bind(version)
+                .bind(closed_at)
+                .bind(deal_id)
+            }
+        };
+
+        query
+            .execute(conn)
+            .await
+            .map_err(|e| ProjectionError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ProjectionError {
     #[error("Database error: {0}")]
     DatabaseError(String),
+
+    #[error("Event store error: {0}")]
+    EventStoreError(String),
+
+    #[error("Unknown projection: {0}")]
+    UnknownProjection(String),
 }
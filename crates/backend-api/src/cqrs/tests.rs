@@ -5,6 +5,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::*;
+    use test_support::get_test_pool;
     use uuid::Uuid;
     use rust_decimal_macros::dec;
     
@@ -250,4 +251,101 @@ mod tests {
         assert!(deal.is_closed);
         assert_eq!(deal.value, Some(dec!(6000.00)));
     }
+    
+    /// Save a deal's creation and a stage change to the event store
+    async fn seed_deal(store: &EventStore, user_id: Uuid) -> Uuid {
+        let cmd = CreateDealCommand::new(Uuid::new_v4(), "Marina View".to_string(), user_id)
+            .with_value(dec!(250000.00));
+        let created = Deal::create(cmd).unwrap();
+        let deal_id = created.deal_id();
+        store.save_event(deal_id, "Deal", 0, &created, user_id).await.unwrap();
+        
+        let deal = store.load_aggregate(deal_id).await.unwrap();
+        let staged = deal
+            .update_stage(UpdateDealStageCommand::new(deal_id, "negotiation".to_string(), user_id))
+            .unwrap();
+        store.save_event(deal_id, "Deal", deal.version, &staged, user_id).await.unwrap();
+        deal_id
+    }
+    
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_rebuild_restores_corrupted_projection() {
+        let pool = get_test_pool().await;
+        let store = EventStore::new(pool.clone());
+        let user_id = Uuid::new_v4();
+        let deal_id = seed_deal(&store, user_id).await;
+        
+        let mut reports = Vec::new();
+        let first = projections::rebuild(&pool, "deal_summaries", |p| reports.push(p.clone())).await.unwrap();
+        assert_eq!(first.processed, first.total);
+        assert_eq!(reports.last(), Some(&first));
+        
+        let summary = || {
+            sqlx::query_as::<_, (String, String, i64)>(
+                "SELECT stage, value::text, version FROM deal_summaries WHERE deal_id = $1",
+            )
+            .bind(deal_id)
+            .fetch_one(&pool)
+        };
+        let expected = ("negotiation".to_string(), "250000.00".to_string(), 2);
+        assert_eq!(summary().await.unwrap(), expected);
+        
+        sqlx::query("UPDATE deal_summaries SET stage = 'garbage', value = -1 WHERE deal_id = $1")
+            .bind(deal_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        projections::rebuild(&pool, "deal_summaries", |_| {}).await.unwrap();
+        assert_eq!(summary().await.unwrap(), expected);
+        
+        assert!(matches!(
+            projections::rebuild(&pool, "nope", |_| {}).await,
+            Err(projections::ProjectionError::UnknownProjection(_))
+        ));
+        
+        sqlx::query("DELETE FROM domain_events WHERE aggregate_id = $1").bind(deal_id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM aggregate_snapshots WHERE aggregate_id = $1").bind(deal_id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM deal_summaries WHERE deal_id = $1").bind(deal_id).execute(&pool).await.unwrap();
+    }
+    
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_concurrent_commands_on_same_version_conflict() {
+        let pool = get_test_pool().await;
+        let handler = DealCommandHandler::new(pool.clone());
+        let user_id = Uuid::new_v4();
+        
+        let (created, version) = handler
+            .create(CreateDealCommand::new(Uuid::new_v4(), "Palm Villa".to_string(), user_id))
+            .await
+            .unwrap();
+        let deal_id = created.deal_id();
+        
+        let (first, second) = tokio::join!(
+            handler.update_stage(UpdateDealStageCommand::new(deal_id, "viewing".to_string(), user_id), version),
+            handler.update_stage(UpdateDealStageCommand::new(deal_id, "offer".to_string(), user_id), version),
+        );
+        
+        let (winner, loser) = match (first, second) {
+            (Ok(ok), Err(err)) | (Err(err), Ok(ok)) => (ok, err),
+            (first, second) => panic!("expected exactly one success, got {:?} and {:?}", first, second),
+        };
+        assert_eq!(winner.1, 2);
+        assert!(loser.is_version_conflict());
+        assert!(matches!(
+            loser,
+            CommandError::Store(EventStoreError::VersionConflict { expected: 1, actual: 2 })
+        ));
+        
+        // Retrying with fresh state succeeds
+        let (_, version) = handler
+            .update_stage(UpdateDealStageCommand::new(deal_id, "contract".to_string(), user_id), winner.1)
+            .await
+            .unwrap();
+        assert_eq!(version, 3);
+        assert_eq!(EventStore::new(pool.clone()).load_aggregate(deal_id).await.unwrap().stage, "contract");
+        
+        sqlx::query("DELETE FROM domain_events WHERE aggregate_id = $1").bind(deal_id).execute(&pool).await.unwrap();
+    }
 }
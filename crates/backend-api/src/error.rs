@@ -42,28 +42,64 @@ pub enum ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
-            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
-            ApiError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden".to_string()),
-            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
-            ApiError::Auth(e) => match e {
-                AuthError::InvalidCredentials => (StatusCode::UNAUTHORIZED, e.to_string()),
-                AuthError::UserNotFound => (StatusCode::NOT_FOUND, e.to_string()),
-                AuthError::SessionExpired => (StatusCode::UNAUTHORIZED, e.to_string()),
-                _ => (StatusCode::BAD_REQUEST, e.to_string()),
-            },
-            ApiError::Metadata(e) => (StatusCode::NOT_FOUND, e.to_string()),
-            ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string()),
+        let (status, code, message) = match self {
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", msg),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized", "Unauthorized".to_string()),
+            ApiError::Forbidden => (StatusCode::FORBIDDEN, "forbidden", "Forbidden".to_string()),
+            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", msg),
+            // Same body shape, with the auth error's own status and code
+            ApiError::Auth(e) => return e.into_response(),
+            ApiError::Metadata(e) => (StatusCode::NOT_FOUND, "not_found", e.to_string()),
+            ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", "Database error".to_string()),
         };
 
         let body = Json(serde_json::json!({
             "error": message,
+            "code": code,
             "status": status.as_u16()
         }));
 
         (status, body).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn render(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_auth_errors_keep_their_status_and_code() {
+        let (status, body) = render(ApiError::Auth(AuthError::RateLimited { retry_after_secs: 5 })).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body, serde_json::json!({ "error": "Too many attempts, try again later", "code": "rate_limited", "status": 429 }));
+
+        let (status, body) = render(ApiError::Auth(AuthError::InvalidCredentials)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_credentials");
+    }
+
+    #[tokio::test]
+    async fn test_every_error_has_the_same_body_shape() {
+        for error in [
+            ApiError::NotFound("Deal not found".to_string()),
+            ApiError::Forbidden,
+            ApiError::Database(sqlx::Error::RowNotFound),
+            ApiError::Auth(AuthError::TenantSuspended),
+        ] {
+            let (status, body) = render(error).await;
+            let mut keys: Vec<_> = body.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            assert_eq!(keys, ["code", "error", "status"]);
+            assert_eq!(body["status"], status.as_u16());
+        }
+    }
+}
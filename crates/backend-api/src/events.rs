@@ -55,6 +55,50 @@ pub fn emit_interaction_created(
     broadcast_event(&state.ws_channels, tenant_id, event);
 }
 
+/// Broadcast an inbox threads merged event
+pub fn emit_threads_merged(
+    state: &AppState,
+    tenant_id: Uuid,
+    thread_id: Uuid,
+    merged_thread_ids: Vec<Uuid>,
+) {
+    let event = WsEvent::ThreadsMerged {
+        thread_id,
+        merged_thread_ids,
+    };
+    broadcast_event(&state.ws_channels, tenant_id, event);
+}
+
+/// Broadcast an inbox thread split event
+pub fn emit_thread_split(
+    state: &AppState,
+    tenant_id: Uuid,
+    thread_id: Uuid,
+    new_thread_id: Uuid,
+) {
+    let event = WsEvent::ThreadSplit {
+        thread_id,
+        new_thread_id,
+    };
+    broadcast_event(&state.ws_channels, tenant_id, event);
+}
+
+/// Broadcast an unread count change for an inbox thread
+pub fn emit_unread_count_changed(
+    state: &AppState,
+    tenant_id: Uuid,
+    thread_id: Uuid,
+    user_id: Option<Uuid>,
+    unread_count: Option<i64>,
+) {
+    let event = WsEvent::UnreadCountChanged {
+        thread_id,
+        user_id,
+        unread_count,
+    };
+    broadcast_event(&state.ws_channels, tenant_id, event);
+}
+
 /// Broadcast a webhook received event
 pub fn emit_webhook_received(
     state: &AppState,
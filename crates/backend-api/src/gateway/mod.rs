@@ -1,21 +1,45 @@
 //! API Gateway - Rate Limiting & Middleware
 
 use axum::{
-    extract::{Request, State},
+    extract::{ConnectInfo, MatchedPath, Request, State},
     http::{StatusCode, HeaderMap},
     middleware::Next,
     response::Response,
 };
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use chrono::{DateTime, Utc, Duration};
+use core_auth::api_key::ApiKey;
+use uuid::Uuid;
+
+use crate::middleware::permission::AuthenticatedUser;
+use crate::middleware::tenant::ResolvedTenant;
+
+/// Default API budget per principal, see [`rate_limit_middleware`]
+pub const API_REQUESTS_PER_MINUTE: u32 = 600;
 
 /// Rate limiter using token bucket algorithm
+///
+/// Buckets are per principal: the authenticated `(tenant, user)`, else the
+/// API key, else the client IP. Tenants and endpoints can have their own
+/// limits; an endpoint override also gives the endpoint its own bucket.
 pub struct RateLimiter {
     limits: Arc<RwLock<HashMap<String, TokenBucket>>>,
     max_requests: u32,
     window_secs: i64,
+    tenant_limits: HashMap<Uuid, RateLimit>,
+    /// Keyed by route template, e.g. `/api/v1/records/:entity_code`
+    endpoint_limits: HashMap<String, RateLimit>,
+    trusted_proxies: Vec<IpNet>,
+}
+
+/// Requests allowed per window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_requests: u32,
+    pub window_secs: i64,
 }
 
 struct TokenBucket {
@@ -29,14 +53,47 @@ impl RateLimiter {
             limits: Arc::new(RwLock::new(HashMap::new())),
             max_requests,
             window_secs,
+            tenant_limits: HashMap::new(),
+            endpoint_limits: HashMap::new(),
+            trusted_proxies: Vec::new(),
         }
     }
     
+    /// Limit for every principal of `tenant_id`
+    pub fn with_tenant_limit(mut self, tenant_id: Uuid, limit: RateLimit) -> Self {
+        self.tenant_limits.insert(tenant_id, limit);
+        self
+    }
+    
+    /// Limit for one route template; takes precedence over tenant limits
+    pub fn with_endpoint_limit(mut self, route: &str, limit: RateLimit) -> Self {
+        self.endpoint_limits.insert(route.to_string(), limit);
+        self
+    }
+    
+    /// Proxies (IPs or CIDRs) whose `X-Forwarded-For` entries are trusted;
+    /// without any, the client IP is always the peer address
+    pub fn with_trusted_proxies(mut self, proxies: &[&str]) -> Result<Self, String> {
+        self.trusted_proxies = proxies.iter().map(|p| p.parse()).collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+    
+    /// [`RateLimiter::with_trusted_proxies`] for already parsed networks
+    pub fn with_trusted_proxy_nets(mut self, proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = proxies;
+        self
+    }
+    
     pub async fn check_limit(&self, key: &str) -> bool {
+        let limit = RateLimit { max_requests: self.max_requests, window_secs: self.window_secs };
+        self.check_limit_with(key, limit).await
+    }
+    
+    async fn check_limit_with(&self, key: &str, limit: RateLimit) -> bool {
         let mut limits = self.limits.write().await;
         
         let bucket = limits.entry(key.to_string()).or_insert(TokenBucket {
-            tokens: self.max_requests,
+            tokens: limit.max_requests,
             last_refill: Utc::now(),
         });
         
@@ -44,13 +101,13 @@ impl RateLimiter {
         let now = Utc::now();
         let elapsed = (now - bucket.last_refill).num_seconds();
         
-        if elapsed >= self.window_secs {
-            bucket.tokens = self.max_requests;
+        if elapsed >= limit.window_secs {
+            bucket.tokens = limit.max_requests;
             bucket.last_refill = now;
         } else {
             // Gradual refill
-            let tokens_to_add = ((elapsed as f64 / self.window_secs as f64) * self.max_requests as f64) as u32;
-            bucket.tokens = (bucket.tokens + tokens_to_add).min(self.max_requests);
+            let tokens_to_add = ((elapsed as f64 / limit.window_secs as f64) * limit.max_requests as f64) as u32;
+            bucket.tokens = (bucket.tokens + tokens_to_add).min(limit.max_requests);
         }
         
         // Check and consume token
@@ -61,23 +118,149 @@ impl RateLimiter {
             false
         }
     }
+    
+    /// Bucket key and limit for `request`
+    fn bucket_for(&self, request: &Request) -> (String, RateLimit) {
+        let principal = self.principal_key(request);
+        let route = request.extensions().get::<MatchedPath>().map(|p| p.as_str());
+        
+        if let Some((route, limit)) = route.and_then(|r| self.endpoint_limits.get(r).map(|l| (r, *l))) {
+            return (format!("{}@{}", principal, route), limit);
+        }
+        let limit = request_tenant_id(request)
+            .and_then(|id| self.tenant_limits.get(&id).copied())
+            .unwrap_or(RateLimit { max_requests: self.max_requests, window_secs: self.window_secs });
+        (principal, limit)
+    }
+    
+    /// `user:<tenant>:<user>`, `key:<tenant>:<key id>` or `ip:<client ip>`
+    ///
+    /// Only keys the auth layer resolved count; an unknown `x-api-key` is
+    /// limited by IP, so made-up keys don't each get a fresh bucket.
+    fn principal_key(&self, request: &Request) -> String {
+        if let Some(user) = request.extensions().get::<AuthenticatedUser>() {
+            return format!("user:{}:{}", user.tenant_id, user.id);
+        }
+        if let Some(key) = request.extensions().get::<ApiKey>() {
+            return format!("key:{}:{}", key.tenant_id, key.id);
+        }
+        let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0.ip());
+        match client_ip(peer, request.headers(), &self.trusted_proxies) {
+            Some(ip) => format!("ip:{}", ip),
+            None => "anonymous".to_string(),
+        }
+    }
+}
+
+fn request_tenant_id(request: &Request) -> Option<Uuid> {
+    let extensions = request.extensions();
+    extensions
+        .get::<AuthenticatedUser>()
+        .map(|u| u.tenant_id)
+        .or_else(|| extensions.get::<ApiKey>().map(|k| k.tenant_id))
+        .or_else(|| extensions.get::<ResolvedTenant>().map(|t| t.id))
+}
+
+/// The client's IP: walk `X-Forwarded-For` from the right, skipping trusted
+/// proxies, and take the first address that isn't one
+///
+/// `X-Forwarded-For` is only read when the peer itself is a trusted proxy;
+/// anyone else could put any address in it. Without a peer address (e.g.
+/// no `ConnectInfo`), the header is trusted only if proxies are configured.
+pub fn client_ip(peer: Option<IpAddr>, headers: &HeaderMap, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+    match peer {
+        Some(peer) if !is_trusted(&peer) => return Some(peer),
+        None if trusted_proxies.is_empty() => return None,
+        _ => {}
+    }
+    
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect();
+    
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !is_trusted(ip))
+        .or(forwarded.first())
+        .copied()
+        .or(peer)
+}
+
+/// Proxies from `TRUSTED_PROXIES` (comma-separated IPs or CIDRs) whose
+/// `X-Forwarded-For` entries are believed; bad entries are logged and skipped
+pub fn trusted_proxies_from_env() -> Vec<IpNet> {
+    std::env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| match entry.parse() {
+            Ok(net) => Some(net),
+            Err(e) => {
+                tracing::warn!("Ignoring TRUSTED_PROXIES entry: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// An IP network, e.g. `10.0.0.0/8`; a bare address is a single host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(*ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(*ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::str::FromStr for IpNet {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = s.split_once('/').unwrap_or((s, ""));
+        let addr: IpAddr = addr.trim().parse().map_err(|_| format!("Invalid proxy address '{}'", s))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            "" => max,
+            p => p.parse::<u8>().ok().filter(|p| *p <= max).ok_or_else(|| format!("Invalid prefix in '{}'", s))?,
+        };
+        Ok(Self { addr, prefix })
+    }
 }
 
 /// Rate limiting middleware
+///
+/// Layer it inside auth and tenant resolution so the principal is known,
+/// and with `route_layer` so endpoint overrides see the matched route.
 pub async fn rate_limit_middleware(
     State(limiter): State<Arc<RateLimiter>>,
-    headers: HeaderMap,
     request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Extract client identifier (IP or API key)
-    let client_id = headers
-        .get("x-api-key")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("anonymous");
+    let (key, limit) = limiter.bucket_for(&request);
     
     // Check rate limit
-    if !limiter.check_limit(client_id).await {
+    if !limiter.check_limit_with(&key, limit).await {
         return Err(StatusCode::TOO_MANY_REQUESTS);
     }
     
@@ -86,11 +269,29 @@ pub async fn rate_limit_middleware(
 
 /// Request metrics collection
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::fmt::Write as _;
+
+/// Upper bounds (ms) of the request latency histogram buckets
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Route label for requests that matched no route
+const UNMATCHED_ROUTE: &str = "unmatched";
 
 pub struct Metrics {
     total_requests: AtomicU64,
     failed_requests: AtomicU64,
     total_duration_ms: AtomicU64,
+    /// Keyed by route template and status code; never by tenant, to keep
+    /// the label set bounded
+    routes: std::sync::Mutex<HashMap<(String, u16), RouteHistogram>>,
+}
+
+#[derive(Default, Clone)]
+struct RouteHistogram {
+    count: u64,
+    sum_ms: u64,
+    /// Non-cumulative counts per `LATENCY_BUCKETS_MS` bound
+    buckets: [u64; LATENCY_BUCKETS_MS.len()],
 }
 
 impl Metrics {
@@ -99,16 +300,63 @@ impl Metrics {
             total_requests: AtomicU64::new(0),
             failed_requests: AtomicU64::new(0),
             total_duration_ms: AtomicU64::new(0),
+            routes: std::sync::Mutex::new(HashMap::new()),
         }
     }
     
-    pub fn record_request(&self, duration_ms: u64, is_error: bool) {
+    /// Record a request against its route template (e.g. `/records/:entity_code/:id`)
+    pub fn record_request(&self, route: &str, status: u16, duration_ms: u64) {
         self.total_requests.fetch_add(1, Ordering::Relaxed);
         self.total_duration_ms.fetch_add(duration_ms, Ordering::Relaxed);
         
-        if is_error {
+        if status >= 400 {
             self.failed_requests.fetch_add(1, Ordering::Relaxed);
         }
+        
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let histogram = routes.entry((route.to_string(), status)).or_default();
+        histogram.count += 1;
+        histogram.sum_ms += duration_ms;
+        if let Some(i) = LATENCY_BUCKETS_MS.iter().position(|bound| duration_ms <= *bound) {
+            histogram.buckets[i] += 1;
+        }
+    }
+    
+    /// Request counters and latency histograms in Prometheus text format
+    pub fn export_prometheus(&self) -> String {
+        let mut routes: Vec<((String, u16), RouteHistogram)> = self
+            .routes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        routes.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        let mut out = String::from(
+            "# HELP http_route_requests_total HTTP requests by route template and status\n\
+             # TYPE http_route_requests_total counter\n",
+        );
+        for ((route, status), histogram) in &routes {
+            let _ = writeln!(out, "http_route_requests_total{{route=\"{}\",status=\"{}\"}} {}", escape_label(route), status, histogram.count);
+        }
+        
+        out.push_str(
+            "\n# HELP http_route_request_duration_seconds HTTP request latency by route template and status\n\
+             # TYPE http_route_request_duration_seconds histogram\n",
+        );
+        for ((route, status), histogram) in &routes {
+            let labels = format!("route=\"{}\",status=\"{}\"", escape_label(route), status);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(out, "http_route_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, *bound as f64 / 1000.0, cumulative);
+            }
+            let _ = writeln!(out, "http_route_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, histogram.count);
+            let _ = writeln!(out, "http_route_request_duration_seconds_sum{{{}}} {}", labels, histogram.sum_ms as f64 / 1000.0);
+            let _ = writeln!(out, "http_route_request_duration_seconds_count{{{}}} {}", labels, histogram.count);
+        }
+        out
     }
     
     pub fn get_stats(&self) -> MetricsSnapshot {
@@ -148,13 +396,160 @@ pub async fn metrics_middleware(
     next: Next,
 ) -> Response {
     let start = std::time::Instant::now();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
     
     let response = next.run(request).await;
     
     let duration = start.elapsed().as_millis() as u64;
-    let is_error = response.status().is_client_error() || response.status().is_server_error();
-    
-    metrics.record_request(duration, is_error);
+    metrics.record_request(&route, response.status().as_u16(), duration);
     
     response
 }
+
+/// Escape a Prometheus label value
+pub(crate) fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn user(tenant_id: Uuid) -> AuthenticatedUser {
+        AuthenticatedUser {
+            id: Uuid::new_v4(),
+            tenant_id,
+            email: "agent@example.com".to_string(),
+            name: "Agent".to_string(),
+            role: "user".to_string(),
+            roles: Vec::new(),
+        }
+    }
+
+    fn request_as(user: Option<AuthenticatedUser>) -> Request {
+        let mut request = Request::new(Body::empty());
+        if let Some(user) = user {
+            request.extensions_mut().insert(user);
+        }
+        request
+    }
+
+    fn xff(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_users_do_not_share_a_bucket() {
+        let limiter = RateLimiter::new(1, 60);
+        let tenant_id = Uuid::new_v4();
+        let (alice, bob) = (request_as(Some(user(tenant_id))), request_as(Some(user(tenant_id))));
+
+        let (alice_key, limit) = limiter.bucket_for(&alice);
+        let (bob_key, _) = limiter.bucket_for(&bob);
+        assert_ne!(alice_key, bob_key);
+        assert!(limiter.check_limit_with(&alice_key, limit).await);
+        assert!(!limiter.check_limit_with(&alice_key, limit).await);
+        // Alice being throttled leaves Bob's bucket untouched
+        assert!(limiter.check_limit_with(&bob_key, limit).await);
+    }
+
+    #[test]
+    fn test_api_keys_get_their_own_bucket() {
+        let tenant_id = Uuid::new_v4();
+        let bulk = RateLimit { max_requests: 1000, window_secs: 60 };
+        let limiter = RateLimiter::new(100, 60).with_tenant_limit(tenant_id, bulk);
+        let key = ApiKey {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: "Zapier".to_string(),
+            key_prefix: "jk_1a2b3c4d".to_string(),
+            scopes: vec!["*".to_string()],
+            entity_types: None,
+            created_by: None,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+
+        let mut request = request_as(None);
+        request.headers_mut().insert("x-api-key", "jk_secret".parse().unwrap());
+        request.extensions_mut().insert(key.clone());
+        assert_eq!(limiter.bucket_for(&request), (format!("key:{}:{}", tenant_id, key.id), bulk));
+
+        // A key the auth layer didn't accept is limited like any anonymous caller
+        let mut unresolved = request_as(None);
+        unresolved.headers_mut().insert("x-api-key", "jk_made_up".parse().unwrap());
+        assert_eq!(limiter.bucket_for(&unresolved).0, "anonymous");
+    }
+
+    #[tokio::test]
+    async fn test_tenant_and_endpoint_overrides() {
+        let tenant_id = Uuid::new_v4();
+        let bulk = RateLimit { max_requests: 1000, window_secs: 60 };
+        let export = RateLimit { max_requests: 2, window_secs: 3600 };
+        let limiter = RateLimiter::new(100, 60)
+            .with_tenant_limit(tenant_id, bulk)
+            .with_endpoint_limit("/api/v1/export", export);
+
+        let request = request_as(Some(user(tenant_id)));
+        assert_eq!(limiter.bucket_for(&request).1, bulk);
+        assert_eq!(limiter.bucket_for(&request_as(Some(user(Uuid::new_v4())))).1.max_requests, 100);
+
+        // An endpoint override has its own bucket, so it doesn't drain the general one
+        let limiter = Arc::new(RateLimiter::new(100, 60).with_endpoint_limit("/api/v1/export", RateLimit { max_requests: 1, window_secs: 3600 }));
+        let app = axum::Router::new()
+            .route("/api/v1/export", axum::routing::get(|| async { "ok" }))
+            .route("/api/v1/records", axum::routing::get(|| async { "ok" }))
+            .route_layer(axum::middleware::from_fn_with_state(limiter, rate_limit_middleware));
+        let alice = user(tenant_id);
+        let send = |path: &'static str| {
+            let mut request = request_as(Some(alice.clone()));
+            *request.uri_mut() = path.parse().unwrap();
+            tower::ServiceExt::oneshot(app.clone(), request)
+        };
+        assert_eq!(send("/api/v1/export").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send("/api/v1/export").await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(send("/api/v1/records").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_client_ip_from_forwarded_for() {
+        let proxies: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap(), "192.0.2.7".parse().unwrap()];
+        let lb: IpAddr = "10.1.2.3".parse().unwrap();
+
+        // Rightmost untrusted hop is the client; anything left of it is client-supplied
+        let headers = xff("203.0.113.9, 198.51.100.4, 192.0.2.7");
+        assert_eq!(client_ip(Some(lb), &headers, &proxies), "198.51.100.4".parse().ok());
+        // Multiple headers count as one list; junk entries are skipped
+        let mut headers = xff("not-an-ip, 203.0.113.9");
+        headers.append("x-forwarded-for", "10.9.9.9".parse().unwrap());
+        assert_eq!(client_ip(Some(lb), &headers, &proxies), "203.0.113.9".parse().ok());
+        // IPv6
+        assert_eq!(client_ip(Some(lb), &xff("2001:db8::1"), &proxies), "2001:db8::1".parse().ok());
+
+        // An untrusted peer can't spoof its address through the header
+        let direct: IpAddr = "198.51.100.77".parse().unwrap();
+        assert_eq!(client_ip(Some(direct), &xff("203.0.113.9"), &proxies), Some(direct));
+        assert_eq!(client_ip(Some(direct), &xff("203.0.113.9"), &[]), Some(direct));
+        // Only proxies in the chain: the original client is the leftmost
+        assert_eq!(client_ip(Some(lb), &xff("10.0.0.5"), &proxies), "10.0.0.5".parse().ok());
+        assert_eq!(client_ip(Some(lb), &HeaderMap::new(), &proxies), Some(lb));
+    }
+
+    #[test]
+    fn test_ip_net_parsing() {
+        let net: IpNet = "172.16.0.0/12".parse().unwrap();
+        assert!(net.contains(&"172.31.255.1".parse().unwrap()));
+        assert!(!net.contains(&"172.32.0.1".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<IpNet>().unwrap().contains(&"8.8.8.8".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpNet>().is_err());
+        assert!("proxy.local".parse::<IpNet>().is_err());
+    }
+}
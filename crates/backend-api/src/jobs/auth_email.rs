@@ -0,0 +1,70 @@
+//! Account Emails
+//!
+//! Password reset and email verification mails are queued on `job_queue`,
+//! where the jobs-runner email worker builds and sends them. The token the
+//! mail links to is encrypted with the integration key ring, so no usable
+//! token sits in the queue.
+
+use chrono::{DateTime, Utc};
+use core_auth::email_verification::IssuedVerification;
+use core_auth::password_reset::IssuedReset;
+use core_integrations::encryption::KeyRing;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub const SEND_PASSWORD_RESET_EMAIL: &str = "send_password_reset_email";
+pub const SEND_VERIFICATION_EMAIL: &str = "send_verification_email";
+
+/// Queue the mail carrying a password reset token
+pub async fn enqueue_password_reset(pool: &PgPool, reset: &IssuedReset) -> Result<Uuid, String> {
+    let mail = TokenMail {
+        tenant_id: reset.tenant_id,
+        user_id: reset.user_id,
+        to: &reset.email,
+        name: &reset.name,
+        token: &reset.token,
+        expires_at: reset.expires_at,
+    };
+    enqueue(pool, SEND_PASSWORD_RESET_EMAIL, mail).await
+}
+
+/// Queue the mail carrying an email verification token
+pub async fn enqueue_email_verification(pool: &PgPool, verification: &IssuedVerification) -> Result<Uuid, String> {
+    let mail = TokenMail {
+        tenant_id: verification.tenant_id,
+        user_id: verification.user_id,
+        to: &verification.email,
+        name: &verification.name,
+        token: &verification.token,
+        expires_at: verification.expires_at,
+    };
+    enqueue(pool, SEND_VERIFICATION_EMAIL, mail).await
+}
+
+struct TokenMail<'a> {
+    tenant_id: Uuid,
+    user_id: Uuid,
+    to: &'a str,
+    name: &'a str,
+    token: &'a str,
+    expires_at: DateTime<Utc>,
+}
+
+async fn enqueue(pool: &PgPool, job_type: &str, mail: TokenMail<'_>) -> Result<Uuid, String> {
+    let token = KeyRing::from_env().encrypt(mail.token.as_bytes())?;
+    let payload = serde_json::json!({
+        "tenant_id": mail.tenant_id,
+        "user_id": mail.user_id,
+        "to": mail.to,
+        "name": mail.name,
+        "token_encrypted": hex::encode(token),
+        "expires_at": mail.expires_at,
+    });
+    sqlx::query_scalar("INSERT INTO job_queue (tenant_id, job_type, payload) VALUES ($1, $2, $3) RETURNING id")
+        .bind(mail.tenant_id)
+        .bind(job_type)
+        .bind(payload)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,295 @@
+//! Call Transcription Job
+//!
+//! Twilio's recording webhook queues a `transcribe_recording` job carrying
+//! only the ids of the recorded call. The job decrypts the stored recording
+//! URL, downloads the audio, transcribes it with the configured STT provider
+//! and logs the transcript as a call interaction on the contact.
+
+use chrono::{DateTime, Utc};
+use core_integrations::encryption::KeyRing;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::worker::JobHandler;
+use crate::ai::transcription::SttProvider;
+
+/// Queue the voice jobs run on
+pub const VOICE_QUEUE: &str = "voice";
+pub const TRANSCRIBE_RECORDING: &str = "transcribe_recording";
+
+/// Twilio serves recordings as WAV unless another extension is asked for
+const RECORDING_CONTENT_TYPE: &str = "audio/wav";
+
+/// A recorded call waiting for its transcript
+struct RecordedCall {
+    call_sid: String,
+    placed_by: Uuid,
+    entity_type: String,
+    record_id: Uuid,
+    phone: String,
+    recording_sid: Option<String>,
+    recording_url_encrypted: Option<Vec<u8>>,
+    duration_seconds: Option<i32>,
+    status: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Runs `transcribe_recording` jobs
+pub struct Transcriber {
+    pool: PgPool,
+    stt: Arc<dyn SttProvider>,
+    client: reqwest::Client,
+    keys: KeyRing,
+    /// Twilio account SID and auth token, to download protected recordings
+    download_auth: Option<(String, String)>,
+}
+
+impl Transcriber {
+    pub fn new(pool: PgPool, stt: Arc<dyn SttProvider>) -> Self {
+        let download_auth = std::env::var("TWILIO_ACCOUNT_SID")
+            .ok()
+            .zip(std::env::var("TWILIO_AUTH_TOKEN").ok());
+        Self { pool, stt, client: reqwest::Client::new(), keys: KeyRing::from_env(), download_auth }
+    }
+
+    /// Handlers to register with a `WorkerPool` on `VOICE_QUEUE`
+    pub fn handlers(self: Arc<Self>) -> HashMap<String, JobHandler> {
+        let handler: JobHandler = Arc::new(move |payload: serde_json::Value| {
+            let transcriber = self.clone();
+            Box::pin(async move {
+                let id = |key: &str| {
+                    payload[key]
+                        .as_str()
+                        .and_then(|s| Uuid::parse_str(s).ok())
+                        .ok_or_else(|| format!("Job payload has no {}", key))
+                };
+                transcriber.run(id("tenant_id")?, id("recording_id")?).await
+            })
+        });
+        HashMap::from([(TRANSCRIBE_RECORDING.to_string(), handler)])
+    }
+
+    /// Transcribe the recording of a call and log it on the contact; a call
+    /// already transcribed or discarded is left alone
+    pub async fn run(&self, tenant_id: Uuid, recording_id: Uuid) -> Result<(), String> {
+        let call = self.load(tenant_id, recording_id).await.map_err(|e| e.to_string())?;
+        match call.status.as_str() {
+            "queued" => {}
+            "transcribed" | "discarded" => return Ok(()),
+            // The webhook's update isn't committed yet; the retry picks it up
+            _ => return Err(format!("Recording {} is not ready", recording_id)),
+        }
+        let encrypted = call
+            .recording_url_encrypted
+            .as_deref()
+            .ok_or_else(|| format!("Recording {} has no URL", recording_id))?;
+        let url = self.keys.decrypt(encrypted).map_err(|e| e.to_string())?;
+        let url = String::from_utf8(url).map_err(|e| e.to_string())?;
+
+        let audio = self.download(&url).await?;
+        let transcript = self.stt.transcribe(audio, RECORDING_CONTENT_TYPE).await?;
+        self.log_transcript(tenant_id, recording_id, &call, &transcript)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        tracing::info!(call_sid = %call.call_sid, chars = transcript.len(), "Call transcribed");
+        Ok(())
+    }
+
+    async fn load(&self, tenant_id: Uuid, recording_id: Uuid) -> Result<RecordedCall, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("SELECT set_config('app.current_tenant', $1::text, false)")
+            .bind(tenant_id)
+            .execute(&mut *conn)
+            .await?;
+        let row = sqlx::query(
+            r#"
+            SELECT call_sid, placed_by, entity_type, record_id, phone, recording_sid,
+                   recording_url_encrypted, duration_seconds, status, created_at
+            FROM call_recordings
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(recording_id)
+        .bind(tenant_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(RecordedCall {
+            call_sid: row.try_get("call_sid")?,
+            placed_by: row.try_get("placed_by")?,
+            entity_type: row.try_get("entity_type")?,
+            record_id: row.try_get("record_id")?,
+            phone: row.try_get("phone")?,
+            recording_sid: row.try_get("recording_sid")?,
+            recording_url_encrypted: row.try_get("recording_url_encrypted")?,
+            duration_seconds: row.try_get("duration_seconds")?,
+            status: row.try_get("status")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, String> {
+        let mut request = self.client.get(url);
+        if let Some((account_sid, auth_token)) = &self.download_auth {
+            request = request.basic_auth(account_sid, Some(auth_token));
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("Recording download failed ({})", response.status()));
+        }
+        let audio = response.bytes().await.map_err(|e| e.to_string())?;
+        Ok(audio.to_vec())
+    }
+
+    /// Insert the call interaction and mark the recording transcribed
+    async fn log_transcript(
+        &self,
+        tenant_id: Uuid,
+        recording_id: Uuid,
+        call: &RecordedCall,
+        transcript: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT set_config('app.current_tenant', $1::text, false)")
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let interaction_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO interactions (tenant_id, entity_type, record_id, interaction_type, title, content, created_by, occurred_at, duration_minutes, metadata)
+            VALUES ($1, $2, $3, 'call', $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&call.entity_type)
+        .bind(call.record_id)
+        .bind(format!("Call transcript: {}", call.phone))
+        .bind(transcript)
+        .bind(call.placed_by)
+        .bind(call.created_at)
+        .bind(call.duration_seconds.map(|s| (s + 59) / 60))
+        .bind(serde_json::json!({
+            "call_sid": call.call_sid,
+            "recording_sid": call.recording_sid,
+            "source": "call_transcription",
+        }))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE call_recordings SET status = 'transcribed', interaction_id = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(recording_id)
+        .bind(interaction_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::transcription::MockSttProvider;
+    use axum::{routing::get, Router};
+    use test_support::get_test_pool;
+
+    /// Serves `bytes` of fake audio, standing in for Twilio
+    async fn serve_recording(bytes: usize) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/recording", get(move || async move { vec![0u8; bytes] }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/recording", addr)
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_transcript_is_logged_on_the_contact() {
+        let pool = get_test_pool().await;
+        let (tenant_id, user_id, contact_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Transcription Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("transcribe-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, 'agent@example.com', 'Agent', 'x')")
+            .bind(user_id)
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let recording_url = serve_recording(64).await;
+        let recording_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO call_recordings (tenant_id, call_sid, placed_by, entity_type, record_id, phone,
+                                         recording_sid, recording_url_encrypted, duration_seconds, status)
+            VALUES ($1, 'CA-transcribe-test', $2, 'contact', $3, '+971500000000', 'RE1', $4, 95, 'queued')
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(contact_id)
+        .bind(KeyRing::from_env().encrypt(recording_url.as_bytes()).unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let transcriber = Transcriber::new(pool.clone(), Arc::new(MockSttProvider));
+        transcriber.run(tenant_id, recording_id).await.unwrap();
+
+        type Logged = (Uuid, String, String, Option<String>, Uuid, Option<i32>);
+        let logged: Vec<Logged> = sqlx::query_as(
+            "SELECT record_id, interaction_type, title, content, created_by, duration_minutes FROM interactions WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            logged,
+            vec![(
+                contact_id,
+                "call".to_string(),
+                "Call transcript: +971500000000".to_string(),
+                Some("Mock transcript of 64 bytes of audio".to_string()),
+                user_id,
+                Some(2),
+            )]
+        );
+        let status: String = sqlx::query_scalar("SELECT status FROM call_recordings WHERE id = $1")
+            .bind(recording_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "transcribed");
+
+        // A retried job doesn't log the call twice
+        transcriber.run(tenant_id, recording_id).await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM interactions WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        for sql in [
+            "DELETE FROM call_recordings WHERE tenant_id = $1",
+            "DELETE FROM inbox_read_state WHERE tenant_id = $1",
+            "DELETE FROM interactions WHERE tenant_id = $1",
+            "DELETE FROM inbox_threads WHERE tenant_id = $1",
+            "DELETE FROM users WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
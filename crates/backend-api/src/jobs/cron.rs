@@ -0,0 +1,245 @@
+//! Cron Schedules
+//!
+//! Standard 5-field cron expressions (`minute hour day-of-month month
+//! day-of-week`) evaluated in a timezone, so "0 0 * * *" fires at local
+//! midnight on both sides of a DST change.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
+use core_engagement::local_to_utc;
+use std::str::FromStr;
+
+/// How far ahead to search before giving up (covers Feb 29 schedules)
+const MAX_SEARCH_DAYS: i64 = 366 * 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CronError {
+    #[error("Invalid cron expression '{0}': {1}")]
+    Invalid(String, String),
+}
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    /// 0 = Sunday
+    days_of_week: Vec<u32>,
+    /// Whether day-of-month / day-of-week were restricted (not `*`)
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronError;
+
+    /// Parse `*`, lists, ranges and steps, e.g. `*/15 9-17 * * 1-5`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |msg: &str| CronError::Invalid(s.to_string(), msg.to_string());
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid("expected 5 fields"));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7).map_err(|e| invalid(&e))?;
+        // 7 is Sunday too
+        if days_of_week.contains(&7) {
+            days_of_week.retain(|d| *d != 7);
+            if !days_of_week.contains(&0) {
+                days_of_week.insert(0, 0);
+            }
+        }
+
+        Ok(Self {
+            expression: s.trim().to_string(),
+            minutes: parse_field(fields[0], 0, 59).map_err(|e| invalid(&e))?,
+            hours: parse_field(fields[1], 0, 23).map_err(|e| invalid(&e))?,
+            days_of_month: parse_field(fields[2], 1, 31).map_err(|e| invalid(&e))?,
+            months: parse_field(fields[3], 1, 12).map_err(|e| invalid(&e))?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+}
+
+/// Values matched by one field, sorted
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>, String> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| format!("bad step '{}'", step))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err("step must be positive".to_string());
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, min, max)?, parse_value(b, min, max)?)
+        } else {
+            let v = parse_value(range, min, max)?;
+            // `5/10` means from 5 to the end in steps of 10
+            (v, if part.contains('/') { max } else { v })
+        };
+        if start > end {
+            return Err(format!("empty range '{}'", range));
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+fn parse_value(s: &str, min: u32, max: u32) -> Result<u32, String> {
+    s.parse::<u32>()
+        .ok()
+        .filter(|v| (min..=max).contains(v))
+        .ok_or_else(|| format!("'{}' is outside {}-{}", s, min, max))
+}
+
+impl CronSchedule {
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Whether the schedule fires on `date`; as in standard cron, when both
+    /// day fields are restricted either one matching is enough
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let dom = self.days_of_month.contains(&date.day());
+        let dow = self.days_of_week.contains(&date.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// First fire time strictly after `after`, evaluated on the wall clock
+    /// in `tz`
+    ///
+    /// A local time repeated by a DST fall-back fires once, at its first
+    /// instance; one skipped by a spring-forward fires just after the gap.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let local_after = after.with_timezone(&tz).naive_local();
+        let start = local_after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        for day in 0..MAX_SEARCH_DAYS {
+            let date = start.date() + Duration::days(day);
+            if !self.matches_date(date) {
+                continue;
+            }
+            for &hour in &self.hours {
+                for &minute in &self.minutes {
+                    let local = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?);
+                    if local < start {
+                        continue;
+                    }
+                    let fire = local_to_utc(tz, local);
+                    if fire > after {
+                        return Some(fire);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Most recent fire time in `(after, until]`, if any
+    pub fn last_between(&self, after: DateTime<Utc>, until: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let mut last = None;
+        let mut cursor = after;
+        while let Some(fire) = self.next_after(cursor, tz) {
+            if fire > until {
+                break;
+            }
+            last = Some(fire);
+            cursor = fire;
+        }
+        last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let schedule: CronSchedule = "*/15 9-17 1,15 * 1-5".parse().unwrap();
+        assert_eq!(schedule.minutes, vec![0, 15, 30, 45]);
+        assert_eq!(schedule.hours, (9..=17).collect::<Vec<_>>());
+        assert_eq!(schedule.days_of_month, vec![1, 15]);
+        assert_eq!(schedule.days_of_week, vec![1, 2, 3, 4, 5]);
+
+        let sunday: CronSchedule = "0 0 * * 7".parse().unwrap();
+        assert_eq!(sunday.days_of_week, vec![0]);
+
+        assert!("0 0 * *".parse::<CronSchedule>().is_err());
+        assert!("60 0 * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+    }
+
+    #[test]
+    fn test_midnight_across_dst_transition() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let schedule: CronSchedule = "0 0 * * *".parse().unwrap();
+
+        // Clocks go forward at 02:00 on 2025-03-09: midnight is EST before, EDT after
+        let before = schedule.next_after(utc("2025-03-08T12:00:00Z"), tz).unwrap();
+        assert_eq!(before, utc("2025-03-09T05:00:00Z"));
+        let after = schedule.next_after(before, tz).unwrap();
+        assert_eq!(after, utc("2025-03-10T04:00:00Z"));
+        assert_eq!(tz.from_utc_datetime(&after.naive_utc()).hour(), 0);
+    }
+
+    #[test]
+    fn test_dst_gap_and_overlap() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let schedule: CronSchedule = "30 1,2 * * *".parse().unwrap();
+
+        // 02:30 doesn't exist on 2025-03-09; it fires at 03:30 EDT instead
+        let fires: Vec<_> = std::iter::successors(schedule.next_after(utc("2025-03-09T05:00:00Z"), tz), |f| {
+            schedule.next_after(*f, tz)
+        })
+        .take(2)
+        .collect();
+        assert_eq!(fires, vec![utc("2025-03-09T06:30:00Z"), utc("2025-03-09T07:30:00Z")]);
+
+        // 01:30 happens twice on 2025-11-02; it fires only the first time
+        let first = schedule.next_after(utc("2025-11-02T04:00:00Z"), tz).unwrap();
+        assert_eq!(first, utc("2025-11-02T05:30:00Z"));
+        assert_eq!(schedule.next_after(first, tz), Some(utc("2025-11-02T07:30:00Z")));
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        let schedule: CronSchedule = "0 9 13 * 5".parse().unwrap();
+        // Friday 2025-06-06 comes before the 13th
+        assert_eq!(schedule.next_after(utc("2025-06-01T00:00:00Z"), Tz::UTC), Some(utc("2025-06-06T09:00:00Z")));
+        let leap: CronSchedule = "0 0 29 2 *".parse().unwrap();
+        assert_eq!(leap.next_after(utc("2025-01-01T00:00:00Z"), Tz::UTC), Some(utc("2028-02-29T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_last_between() {
+        let schedule: CronSchedule = "0 2 * * *".parse().unwrap();
+        let last = schedule.last_between(utc("2025-01-01T03:00:00Z"), utc("2025-01-04T01:00:00Z"), Tz::UTC);
+        assert_eq!(last, Some(utc("2025-01-03T02:00:00Z")));
+        assert_eq!(schedule.last_between(utc("2025-01-01T03:00:00Z"), utc("2025-01-02T01:00:00Z"), Tz::UTC), None);
+    }
+}
@@ -1,13 +1,17 @@
 //! Background Jobs Module
 
 pub mod queue;
+pub mod auth_email;
+pub mod call_transcription;
+pub mod cron;
 pub mod worker;
 pub mod scheduled_trigger_runner;
 pub mod snapshot_cleanup;
 pub mod scheduler;
 
-pub use queue::{JobQueue, Job, JobStatus, JobError};
+pub use queue::{JobQueue, Job, JobStatus, JobError, JobOptions, DeadLetter, PRIORITY_HIGH, PRIORITY_NORMAL, PRIORITY_BULK};
 pub use worker::{Worker, WorkerPool, JobHandler};
-pub use scheduled_trigger_runner::{ScheduledTriggerRunner, ScheduledTriggerConfig, DelayedActionTrigger};
+pub use scheduled_trigger_runner::{ScheduledTriggerRunner, ScheduledTriggerConfig, DelayedActionTrigger, TriggerSchedule, next_fire};
 pub use snapshot_cleanup::{SnapshotCleanupJob, SnapshotCleanupConfig, BatchSnapshotCreator};
-pub use scheduler::{JobScheduler, SchedulerConfig, start_background_jobs};
+pub use cron::{CronSchedule, CronError};
+pub use scheduler::{JobScheduler, SchedulerConfig, CronJob, start_background_jobs};
@@ -1,27 +1,51 @@
 //! Background Job Queue System
 //! 
-//! Redis-based job queue for async task processing
+//! Postgres-backed job queue for async task processing. Jobs are claimed
+//! highest priority first, skipping those whose `run_at` is still ahead.
+//! Jobs sharing a `lock_key` run one at a time; a job whose key is busy is
+//! passed over for other work. Jobs that fail more than `max_attempts` times
+//! move to the `jobs_dead_letter` table and stay there until requeued by hand.
 
-use redis::{Client, AsyncCommands};
 use serde::{Serialize, Deserialize};
+use sqlx::{PgPool, Row};
 use uuid::Uuid;
 use chrono::{DateTime, Utc, Duration};
-use std::sync::Arc;
-use tokio::time::sleep;
+use std::collections::HashMap;
+
+use crate::middleware::trace_context::current_trace_id;
+
+/// Priority for user-facing work such as password reset emails
+pub const PRIORITY_HIGH: i16 = 10;
+pub const PRIORITY_NORMAL: i16 = 0;
+/// Priority for bulk work that can wait behind everything else
+pub const PRIORITY_BULK: i16 = -10;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Job {
     pub id: Uuid,
     pub job_type: String,
     pub payload: serde_json::Value,
-    pub max_retries: u32,
-    pub retry_count: u32,
+    /// Higher runs first
+    pub priority: i16,
+    /// Not picked up before this time; `None` runs as soon as possible
+    pub run_at: Option<DateTime<Utc>>,
+    /// Jobs with the same key never run concurrently (e.g. the entity id)
+    pub lock_key: Option<String>,
+    /// Failures allowed before the job is dead-lettered; the next one moves it
+    pub max_attempts: u32,
+    /// Failed attempts so far
+    pub attempts: u32,
     pub status: JobStatus,
     pub created_at: DateTime<Utc>,
-    pub scheduled_at: Option<DateTime<Utc>>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub error: Option<String>,
+    /// Error of each failed attempt, oldest first
+    #[serde(default)]
+    pub attempt_errors: Vec<String>,
+    /// Correlation id of the request or job that enqueued this one
+    #[serde(default)]
+    pub trace_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,59 +55,110 @@ pub enum JobStatus {
     Completed,
     Failed,
     Retrying,
+    DeadLettered,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Retrying => "retrying",
+            JobStatus::DeadLettered => "dead_lettered",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(JobStatus::Pending),
+            "running" => Some(JobStatus::Running),
+            "completed" => Some(JobStatus::Completed),
+            "failed" => Some(JobStatus::Failed),
+            "retrying" => Some(JobStatus::Retrying),
+            "dead_lettered" => Some(JobStatus::DeadLettered),
+            _ => None,
+        }
+    }
+}
+
+/// How to enqueue a job
+#[derive(Debug, Clone)]
+pub struct JobOptions {
+    pub priority: i16,
+    pub run_at: Option<DateTime<Utc>>,
+    pub lock_key: Option<String>,
+    pub max_attempts: u32,
+    /// Defaults to the trace id of the enqueuing task
+    pub trace_id: Option<String>,
+}
+
+impl Default for JobOptions {
+    fn default() -> Self {
+        Self {
+            priority: PRIORITY_NORMAL,
+            run_at: None,
+            lock_key: None,
+            max_attempts: 3,
+            trace_id: None,
+        }
+    }
 }
 
+/// A job that exhausted its attempts
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub last_error: String,
+    pub context: serde_json::Value,
+    pub enqueued_at: DateTime<Utc>,
+    pub failed_at: DateTime<Utc>,
+}
+
+const JOB_COLUMNS: &str = "id, job_type, payload, priority, run_at, lock_key, status, attempts, max_attempts, \
+    error, attempt_errors, trace_id, created_at, started_at, completed_at";
+
+/// Due jobs considered per dequeue; leaves room to pass over busy lock keys
+const DEQUEUE_CANDIDATES: i64 = 10;
+
+/// How long a running job holds its lock key unless it finishes first
+const DEFAULT_LOCK_LEASE: Duration = Duration::minutes(15);
+
 pub struct JobQueue {
-    client: Client,
+    pool: PgPool,
     queue_name: String,
+    lock_lease: Duration,
 }
 
 impl JobQueue {
-    pub async fn new(redis_url: &str, queue_name: &str) -> Result<Self, JobError> {
-        let client = Client::open(redis_url)?;
-        
-        Ok(Self {
-            client,
+    pub fn new(pool: PgPool, queue_name: &str) -> Self {
+        Self {
+            pool,
             queue_name: queue_name.to_string(),
-        })
+            lock_lease: DEFAULT_LOCK_LEASE,
+        }
     }
     
-    /// Enqueue a new job
+    /// Set how long a running job may hold its lock key; keep it above the
+    /// longest expected run, since an expired lease frees the key
+    pub fn with_lock_lease(mut self, lease: Duration) -> Self {
+        self.lock_lease = lease;
+        self
+    }
+    
+    /// Enqueue a new job at normal priority
     pub async fn enqueue(
         &self,
         job_type: String,
         payload: serde_json::Value,
-        max_retries: u32,
+        max_attempts: u32,
     ) -> Result<Uuid, JobError> {
-        let mut conn = self.client.get_async_connection().await?;
-        
-        let job = Job {
-            id: Uuid::new_v4(),
-            job_type,
-            payload,
-            max_retries,
-            retry_count: 0,
-            status: JobStatus::Pending,
-            created_at: Utc::now(),
-            scheduled_at: None,
-            started_at: None,
-            completed_at: None,
-            error: None,
-        };
-        
-        let job_json = serde_json::to_string(&job)?;
-        
-        // Add to queue
-        conn.rpush(&self.queue_name, &job_json).await?;
-        
-        // Store job details
-        conn.set_ex(
-            format!("job:{}", job.id),
-            &job_json,
-            86400, // 24 hours
-        ).await?;
-        
-        Ok(job.id)
+        self.enqueue_with(job_type, payload, JobOptions { max_attempts, ..Default::default() }).await
     }
     
     /// Schedule a job for future execution
@@ -91,171 +166,469 @@ impl JobQueue {
         &self,
         job_type: String,
         payload: serde_json::Value,
-        scheduled_at: DateTime<Utc>,
-        max_retries: u32,
+        run_at: DateTime<Utc>,
+        max_attempts: u32,
     ) -> Result<Uuid, JobError> {
-        let mut conn = self.client.get_async_connection().await?;
-        
-        let job = Job {
-            id: Uuid::new_v4(),
-            job_type,
-            payload,
-            max_retries,
-            retry_count: 0,
-            status: JobStatus::Pending,
-            created_at: Utc::now(),
-            scheduled_at: Some(scheduled_at),
-            started_at: None,
-            completed_at: None,
-            error: None,
-        };
+        let options = JobOptions { run_at: Some(run_at), max_attempts, ..Default::default() };
+        self.enqueue_with(job_type, payload, options).await
+    }
+    
+    /// Enqueue a job with explicit priority and start time
+    ///
+    /// Without an explicit `trace_id` the job inherits the caller's, so a job
+    /// enqueued while handling a request carries the request id.
+    pub async fn enqueue_with(
+        &self,
+        job_type: String,
+        payload: serde_json::Value,
+        options: JobOptions,
+    ) -> Result<Uuid, JobError> {
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO jobs (queue_name, job_type, payload, priority, run_at, lock_key, max_attempts, trace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.queue_name)
+        .bind(job_type)
+        .bind(payload)
+        .bind(options.priority)
+        .bind(options.run_at)
+        .bind(options.lock_key)
+        .bind(options.max_attempts as i32)
+        .bind(options.trace_id.or_else(current_trace_id))
+        .fetch_one(&self.pool)
+        .await?;
         
-        let job_json = serde_json::to_string(&job)?;
+        Ok(id)
+    }
+    
+    /// Claim the next due job, if any
+    ///
+    /// Orders by priority, then run_at, then age. Candidates are claimed one
+    /// at a time with `SKIP LOCKED`, so concurrent workers take different jobs
+    /// instead of queueing on the same row. A job whose lock key is held by
+    /// a running job is passed over, so the worker picks up other work
+    /// instead of waiting on it.
+    pub async fn dequeue(&self) -> Result<Option<Job>, JobError> {
+        let mut tx = self.pool.begin().await?;
+        let candidates: Vec<(Uuid, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT id, lock_key FROM jobs j
+            WHERE queue_name = $1
+              AND status IN ('pending', 'retrying')
+              AND (run_at IS NULL OR run_at <= NOW())
+              AND (lock_key IS NULL OR NOT EXISTS (
+                  SELECT 1 FROM jobs r
+                  WHERE r.queue_name = j.queue_name AND r.lock_key = j.lock_key
+                    AND r.status = 'running' AND r.lease_expires_at > NOW()
+              ))
+            ORDER BY priority DESC, COALESCE(run_at, created_at), created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(&self.queue_name)
+        .bind(DEQUEUE_CANDIDATES)
+        .fetch_all(&mut *tx)
+        .await?;
         
-        // Add to scheduled set with score = timestamp
-        let score = scheduled_at.timestamp();
-        conn.zadd(
-            format!("{}:scheduled", self.queue_name),
-            job_json,
-            score,
-        ).await?;
+        for (id, lock_key) in candidates {
+            if let Some(key) = &lock_key {
+                if !self.acquire_lock_key(&mut tx, key).await? {
+                    continue;
+                }
+            }
+            
+            let row = sqlx::query(&format!(
+                r#"
+                UPDATE jobs SET status = 'running', started_at = NOW(),
+                    lease_expires_at = NOW() + make_interval(secs => $2)
+                WHERE id = (
+                    SELECT id FROM jobs
+                    WHERE id = $1 AND status IN ('pending', 'retrying')
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING {}
+                "#,
+                JOB_COLUMNS
+            ))
+            .bind(id)
+            .bind(self.lock_lease.num_seconds() as f64)
+            .fetch_optional(&mut *tx)
+            .await?;
+            
+            // Otherwise another worker claimed it first
+            if let Some(row) = row {
+                tx.commit().await?;
+                return job_from_row(&row).map(Some);
+            }
+        }
+        tx.commit().await?;
         
-        Ok(job.id)
+        Ok(None)
     }
     
-    /// Dequeue next job (blocks until available)
-    pub async fn dequeue(&self, timeout_secs: u64) -> Result<Option<Job>, JobError> {
-        let mut conn = self.client.get_async_connection().await?;
+    /// Whether this transaction may start a job holding `key`
+    ///
+    /// The advisory lock serializes claimers of the same key until commit;
+    /// once held, a fresh statement sees any claim committed before it.
+    async fn acquire_lock_key(&self, conn: &mut sqlx::PgConnection, key: &str) -> Result<bool, JobError> {
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("{}:{}", self.queue_name, key))
+            .fetch_one(&mut *conn)
+            .await?;
+        if !locked {
+            return Ok(false);
+        }
         
-        // Check scheduled jobs first
-        self.process_scheduled_jobs().await?;
+        let busy: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM jobs
+                WHERE queue_name = $1 AND lock_key = $2
+                  AND status = 'running' AND lease_expires_at > NOW()
+            )
+            "#,
+        )
+        .bind(&self.queue_name)
+        .bind(key)
+        .fetch_one(conn)
+        .await?;
         
-        // Pop from queue with timeout
-        let result: Option<(String, String)> = conn
-            .blpop(&self.queue_name, timeout_secs as usize)
+        Ok(!busy)
+    }
+    
+    /// Look up a job by id
+    pub async fn get(&self, job_id: Uuid) -> Result<Option<Job>, JobError> {
+        let row = sqlx::query(&format!("SELECT {} FROM jobs WHERE id = $1 AND queue_name = $2", JOB_COLUMNS))
+            .bind(job_id)
+            .bind(&self.queue_name)
+            .fetch_optional(&self.pool)
             .await?;
         
-        match result {
-            Some((_, job_json)) => {
-                let mut job: Job = serde_json::from_str(&job_json)?;
-                job.status = JobStatus::Running;
-                job.started_at = Some(Utc::now());
-                
-                // Update job status
-                self.update_job(&job).await?;
-                
-                Ok(Some(job))
-            }
-            None => Ok(None),
-        }
+        row.as_ref().map(job_from_row).transpose()
     }
     
     /// Mark job as completed
     pub async fn complete(&self, job_id: Uuid) -> Result<(), JobError> {
-        let mut conn = self.client.get_async_connection().await?;
-        
-        let key = format!("job:{}", job_id);
-        let job_json: Option<String> = conn.get(&key).await?;
-        
-        if let Some(json) = job_json {
-            let mut job: Job = serde_json::from_str(&json)?;
-            job.status = JobStatus::Completed;
-            job.completed_at = Some(Utc::now());
-            
-            let updated_json = serde_json::to_string(&job)?;
-            conn.set_ex(&key, updated_json, 86400).await?;
-        }
+        sqlx::query("UPDATE jobs SET status = 'completed', completed_at = NOW() WHERE id = $1")
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
         
         Ok(())
     }
     
     /// Mark job as failed and retry if possible
-    pub async fn fail(&self, job_id: Uuid, error: String) -> Result<bool, JobError> {
-        let mut conn = self.client.get_async_connection().await?;
-        
-        let key = format!("job:{}", job_id);
-        let job_json: Option<String> = conn.get(&key).await?;
+    ///
+    /// Once the job has failed more than `max_attempts` times it moves to the
+    /// dead letter table with `context` (e.g. the worker id) and the error of
+    /// every attempt. Returns whether it will be retried.
+    pub async fn fail(&self, job_id: Uuid, error: String, context: serde_json::Value) -> Result<bool, JobError> {
+        let Some(mut job) = self.get(job_id).await? else {
+            return Ok(false);
+        };
+        job.attempts += 1;
+        job.attempt_errors.push(error.clone());
+        job.error = Some(error);
         
-        if let Some(json) = job_json {
-            let mut job: Job = serde_json::from_str(&json)?;
-            job.retry_count += 1;
-            job.error = Some(error);
+        if job.attempts <= job.max_attempts {
+            // Retry with exponential backoff
+            let backoff_secs = 2_i64.pow(job.attempts);
+            let retry_at = Utc::now() + Duration::seconds(backoff_secs);
+            sqlx::query(
+                r#"
+                UPDATE jobs SET status = 'retrying', attempts = $2, error = $3,
+                    attempt_errors = $4, run_at = $5
+                WHERE id = $1
+                "#,
+            )
+            .bind(job.id)
+            .bind(job.attempts as i32)
+            .bind(&job.error)
+            .bind(serde_json::json!(job.attempt_errors))
+            .bind(retry_at)
+            .execute(&self.pool)
+            .await?;
             
-            if job.retry_count < job.max_retries {
-                // Retry with exponential backoff
-                job.status = JobStatus::Retrying;
-                let backoff_secs = 2_u64.pow(job.retry_count);
-                let retry_at = Utc::now() + Duration::seconds(backoff_secs as i64);
-                job.scheduled_at = Some(retry_at);
-                
-                // Add to scheduled set
-                let job_json = serde_json::to_string(&job)?;
-                conn.zadd(
-                    format!("{}:scheduled", self.queue_name),
-                    &job_json,
-                    retry_at.timestamp(),
-                ).await?;
-                
-                conn.set_ex(&key, &job_json, 86400).await?;
-                
-                Ok(true) // Will retry
-            } else {
-                // Max retries exceeded
-                job.status = JobStatus::Failed;
-                job.completed_at = Some(Utc::now());
-                
-                let updated_json = serde_json::to_string(&job)?;
-                conn.set_ex(&key, updated_json, 86400).await?;
-                
-                // Move to dead letter queue
-                conn.rpush(
-                    format!("{}:dead_letter", self.queue_name),
-                    updated_json,
-                ).await?;
-                
-                Ok(false) // Failed permanently
-            }
+            Ok(true) // Will retry
         } else {
-            Ok(false)
+            let mut tx = self.pool.begin().await?;
+            self.dead_letter(&mut tx, &job, context).await?;
+            sqlx::query("DELETE FROM jobs WHERE id = $1")
+                .bind(job.id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            
+            Ok(false) // Failed permanently
         }
     }
     
-    /// Process scheduled jobs that are ready
-    async fn process_scheduled_jobs(&self) -> Result<(), JobError> {
-        let mut conn = self.client.get_async_connection().await?;
+    /// Store an exhausted job in the dead letter table
+    async fn dead_letter(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        job: &Job,
+        context: serde_json::Value,
+    ) -> Result<(), JobError> {
+        let mut context = context;
+        if let Some(obj) = context.as_object_mut() {
+            obj.insert("attempt_errors".to_string(), serde_json::json!(job.attempt_errors));
+        }
         
-        let now = Utc::now().timestamp();
-        let key = format!("{}:scheduled", self.queue_name);
+        // A requeued job can come back; keep only its latest failure
+        sqlx::query(
+            r#"
+            INSERT INTO jobs_dead_letter
+                (job_id, queue_name, job_type, payload, priority, lock_key, attempts, max_attempts, last_error, context, enqueued_at, trace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (job_id) DO UPDATE SET
+                attempts = EXCLUDED.attempts,
+                max_attempts = EXCLUDED.max_attempts,
+                last_error = EXCLUDED.last_error,
+                context = EXCLUDED.context,
+                failed_at = NOW()
+            "#,
+        )
+        .bind(job.id)
+        .bind(&self.queue_name)
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.priority)
+        .bind(&job.lock_key)
+        .bind(job.attempts as i32)
+        .bind(job.max_attempts as i32)
+        .bind(job.error.as_deref().unwrap_or_default())
+        .bind(context)
+        .bind(job.created_at)
+        .bind(&job.trace_id)
+        .execute(conn)
+        .await?;
         
-        // Get jobs scheduled before now
-        let jobs: Vec<String> = conn
-            .zrangebyscore_limit(&key, 0, now, 0, 100)
-            .await?;
+        Ok(())
+    }
+    
+    /// Put a dead-lettered job back on the queue with its attempts reset
+    ///
+    /// Returns false if no dead-lettered job has this id.
+    pub async fn requeue_dead_letter(&self, job_id: Uuid) -> Result<bool, JobError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            DELETE FROM jobs_dead_letter
+            WHERE job_id = $1 AND queue_name = $2
+            RETURNING job_type, payload, priority, lock_key, max_attempts, enqueued_at, trace_id
+            "#,
+        )
+        .bind(job_id)
+        .bind(&self.queue_name)
+        .fetch_optional(&mut *tx)
+        .await?;
         
-        for job_json in jobs {
-            // Move to main queue
-            conn.rpush(&self.queue_name, &job_json).await?;
-            // Remove from scheduled set
-            conn.zrem(&key, &job_json).await?;
-        }
+        let Some(row) = row else {
+            return Ok(false);
+        };
         
-        Ok(())
+        sqlx::query(
+            r#"
+            INSERT INTO jobs (id, queue_name, job_type, payload, priority, lock_key, max_attempts, created_at, trace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(job_id)
+        .bind(&self.queue_name)
+        .bind(row.try_get::<String, _>("job_type")?)
+        .bind(row.try_get::<serde_json::Value, _>("payload")?)
+        .bind(row.try_get::<i16, _>("priority")?)
+        .bind(row.try_get::<Option<String>, _>("lock_key")?)
+        .bind(row.try_get::<i32, _>("max_attempts")?)
+        .bind(row.try_get::<DateTime<Utc>, _>("enqueued_at")?)
+        .bind(row.try_get::<Option<String>, _>("trace_id")?)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        Ok(true)
     }
     
-    async fn update_job(&self, job: &Job) -> Result<(), JobError> {
-        let mut conn = self.client.get_async_connection().await?;
-        let key = format!("job:{}", job.id);
-        let job_json = serde_json::to_string(job)?;
-        conn.set_ex(&key, job_json, 86400).await?;
-        Ok(())
+    /// Dead-lettered jobs on this queue, most recent failure first
+    pub async fn dead_letters(&self, limit: i64) -> Result<Vec<DeadLetter>, JobError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT job_id, job_type, payload, attempts, max_attempts, last_error, context, enqueued_at, failed_at
+            FROM jobs_dead_letter
+            WHERE queue_name = $1
+            ORDER BY failed_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(&self.queue_name)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter()
+            .map(|row| Ok(DeadLetter {
+                job_id: row.try_get("job_id")?,
+                job_type: row.try_get("job_type")?,
+                payload: row.try_get("payload")?,
+                attempts: row.try_get::<i32, _>("attempts")? as u32,
+                max_attempts: row.try_get::<i32, _>("max_attempts")? as u32,
+                last_error: row.try_get("last_error")?,
+                context: row.try_get("context")?,
+                enqueued_at: row.try_get("enqueued_at")?,
+                failed_at: row.try_get("failed_at")?,
+            }))
+            .collect()
+    }
+    
+    /// Number of dead-lettered jobs on this queue, by job type
+    pub async fn dead_letter_counts(&self) -> Result<HashMap<String, i64>, JobError> {
+        let counts: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT job_type, COUNT(*) FROM jobs_dead_letter WHERE queue_name = $1 GROUP BY job_type"
+        )
+        .bind(&self.queue_name)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(counts.into_iter().collect())
     }
 }
 
+fn job_from_row(row: &sqlx::postgres::PgRow) -> Result<Job, JobError> {
+    let status: String = row.try_get("status")?;
+    let attempt_errors: serde_json::Value = row.try_get("attempt_errors")?;
+    Ok(Job {
+        id: row.try_get("id")?,
+        job_type: row.try_get("job_type")?,
+        payload: row.try_get("payload")?,
+        priority: row.try_get("priority")?,
+        run_at: row.try_get("run_at")?,
+        lock_key: row.try_get("lock_key")?,
+        max_attempts: row.try_get::<i32, _>("max_attempts")? as u32,
+        attempts: row.try_get::<i32, _>("attempts")? as u32,
+        status: JobStatus::parse(&status).unwrap_or(JobStatus::Pending),
+        created_at: row.try_get("created_at")?,
+        started_at: row.try_get("started_at")?,
+        completed_at: row.try_get("completed_at")?,
+        error: row.try_get("error")?,
+        attempt_errors: serde_json::from_value(attempt_errors)?,
+        trace_id: row.try_get("trace_id")?,
+    })
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum JobError {
-    #[error("Redis error: {0}")]
-    Redis(#[from] redis::RedisError),
-    
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::get_test_pool;
+
+    async fn test_queue() -> JobQueue {
+        JobQueue::new(get_test_pool().await, &format!("test-jobs-{}", Uuid::new_v4().simple()))
+    }
+
+    async fn cleanup(queue: &JobQueue) {
+        for sql in ["DELETE FROM jobs WHERE queue_name = $1", "DELETE FROM jobs_dead_letter WHERE queue_name = $1"] {
+            sqlx::query(sql).bind(&queue.queue_name).execute(&queue.pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_job_failing_past_max_attempts_is_dead_lettered_and_requeued() {
+        let queue = test_queue().await;
+        let max_attempts = 2;
+        let options = JobOptions { priority: PRIORITY_HIGH, max_attempts, ..Default::default() };
+        let job_id = queue.enqueue_with("send_email".into(), serde_json::json!({ "to": "a@b.c" }), options).await.unwrap();
+
+        // Retried up to max_attempts failures, dead-lettered on the next
+        for attempt in 1..=max_attempts {
+            let retry = queue.fail(job_id, format!("smtp down #{}", attempt), serde_json::json!({ "worker": "w1" })).await.unwrap();
+            assert!(retry);
+            assert_eq!(queue.get(job_id).await.unwrap().unwrap().status, JobStatus::Retrying);
+        }
+        let retry = queue.fail(job_id, "smtp down #3".into(), serde_json::json!({ "worker": "w1" })).await.unwrap();
+        assert!(!retry);
+        assert!(queue.get(job_id).await.unwrap().is_none());
+
+        assert_eq!(queue.dead_letter_counts().await.unwrap().get("send_email"), Some(&1));
+        let dead = queue.dead_letters(10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, max_attempts + 1);
+        assert_eq!(dead[0].last_error, "smtp down #3");
+        assert_eq!(dead[0].context["worker"], "w1");
+        assert_eq!(dead[0].context["attempt_errors"].as_array().map(Vec::len), Some(3));
+
+        // Manual replay puts it back with a clean slate
+        assert!(queue.requeue_dead_letter(job_id).await.unwrap());
+        assert!(!queue.requeue_dead_letter(job_id).await.unwrap());
+        assert!(queue.dead_letter_counts().await.unwrap().is_empty());
+        let job = queue.dequeue().await.unwrap().expect("job requeued");
+        assert_eq!(job.id, job_id);
+        assert_eq!(job.attempts, 0);
+        assert_eq!(job.priority, PRIORITY_HIGH);
+        assert_eq!(job.status, JobStatus::Running);
+
+        cleanup(&queue).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_high_priority_job_jumps_ahead_of_bulk_work() {
+        let queue = test_queue().await;
+        let bulk = JobOptions { priority: PRIORITY_BULK, ..Default::default() };
+        let low = queue.enqueue_with("export".into(), serde_json::json!({}), bulk).await.unwrap();
+        let normal = queue.enqueue("digest".into(), serde_json::json!({}), 3).await.unwrap();
+        let urgent = JobOptions { priority: PRIORITY_HIGH, ..Default::default() };
+        let high = queue.enqueue_with("password_reset".into(), serde_json::json!({}), urgent).await.unwrap();
+
+        let mut order = Vec::new();
+        while let Some(job) = queue.dequeue().await.unwrap() {
+            order.push(job.id);
+        }
+        assert_eq!(order, vec![high, normal, low]);
+
+        cleanup(&queue).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_future_job_is_not_picked_up_early() {
+        let queue = test_queue().await;
+        let later = queue.schedule("reminder".into(), serde_json::json!({}), Utc::now() + Duration::hours(1), 3).await.unwrap();
+        let due = queue.schedule("reminder".into(), serde_json::json!({}), Utc::now() - Duration::minutes(1), 3).await.unwrap();
+
+        assert_eq!(queue.dequeue().await.unwrap().map(|j| j.id), Some(due));
+        assert!(queue.dequeue().await.unwrap().is_none());
+        assert_eq!(queue.get(later).await.unwrap().unwrap().status, JobStatus::Pending);
+
+        cleanup(&queue).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_concurrent_dequeues_claim_distinct_jobs() {
+        let queue = test_queue().await;
+        for _ in 0..10 {
+            queue.enqueue("sync".into(), serde_json::json!({}), 3).await.unwrap();
+        }
+
+        let claims = futures::future::join_all((0..10).map(|_| queue.dequeue())).await;
+        let mut ids: Vec<Uuid> = claims.into_iter().filter_map(|c| c.unwrap()).map(|j| j.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 10);
+
+        cleanup(&queue).await;
+    }
 }
@@ -4,13 +4,17 @@
 //! Runs every minute to check for due triggers and execute them.
 
 use std::sync::Arc;
-use chrono::{DateTime, Utc, Duration as ChronoDuration};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc, Weekday, Duration as ChronoDuration};
+use chrono_tz::Tz;
+use core_engagement::local_to_utc;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use tokio::time::{Duration, interval};
 use tracing::{info, warn, error};
 use uuid::Uuid;
 use serde_json::Value as JsonValue;
 
+use super::cron::CronSchedule;
 use crate::workflow_trigger::WorkflowTriggerService;
 
 /// Scheduled trigger runner configuration
@@ -88,25 +92,124 @@ impl ScheduledTriggerRunner {
                     error!(error = %e, "Failed to process scheduled triggers");
                 }
             }
+
+            match self.process_due_resumes().await {
+                Ok(count) => {
+                    if count > 0 {
+                        info!(resumed = count, "Enqueued graph execution resumes");
+                    }
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to enqueue graph execution resumes");
+                }
+            }
         }
     }
 
+    /// Enqueue resume jobs for suspended graph executions that are due
+    ///
+    /// Covers executions suspended by a delay node and delayed action triggers
+    /// bound to a suspended execution. Both continue from the persisted
+    /// checkpoint rather than re-running the graph from scratch.
+    pub async fn process_due_resumes(&self) -> Result<usize, String> {
+        let now = Utc::now();
+
+        // Claim due executions by clearing resume_at so the next tick skips them
+        let mut execution_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE graph_executions
+            SET resume_at = NULL
+            WHERE id IN (
+                SELECT id FROM graph_executions
+                WHERE status = 'suspended'
+                  AND resume_at IS NOT NULL
+                  AND resume_at <= $1
+                ORDER BY resume_at ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id
+            "#
+        )
+        .bind(now)
+        .bind(self.config.max_per_tick as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to fetch due executions: {}", e))?;
+
+        // Delayed actions that wait on an existing execution
+        let delayed: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE workflow_triggers
+            SET is_active = false, last_run_at = NOW()
+            WHERE trigger_type = 'delayed'
+              AND is_active = true
+              AND next_run_at <= $1
+              AND filter_conditions->>'execution_id' IS NOT NULL
+            RETURNING (filter_conditions->>'execution_id')::uuid
+            "#
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to fetch due delayed actions: {}", e))?;
+        execution_ids.extend(delayed);
+
+        let mut enqueued = 0;
+
+        for execution_id in execution_ids {
+            let insert_result = sqlx::query(
+                r#"
+                INSERT INTO job_queue (tenant_id, job_type, payload)
+                SELECT tenant_id, 'graph_resume', jsonb_build_object('execution_id', id)
+                FROM graph_executions
+                WHERE id = $1 AND status = 'suspended'
+                "#
+            )
+            .bind(execution_id)
+            .execute(&self.pool)
+            .await;
+
+            match insert_result {
+                Ok(result) if result.rows_affected() > 0 => {
+                    info!(execution_id = %execution_id, "Graph execution resume queued");
+                    enqueued += 1;
+                }
+                Ok(_) => {
+                    warn!(execution_id = %execution_id, "Skipping resume for execution that is not suspended");
+                }
+                Err(e) => {
+                    warn!(
+                        execution_id = %execution_id,
+                        error = %e,
+                        "Failed to queue graph execution resume"
+                    );
+                }
+            }
+        }
+
+        Ok(enqueued)
+    }
+
     /// Process all due triggers
     pub async fn process_due_triggers(&self) -> Result<usize, String> {
         let now = Utc::now();
         let grace_cutoff = now - ChronoDuration::seconds(self.config.grace_period_secs);
 
         // Find all due scheduled triggers
-        let due_triggers = sqlx::query_as::<_, (Uuid, Uuid, Uuid, String, Option<DateTime<Utc>>)>(
+        let due_triggers = sqlx::query_as::<_, DueTrigger>(
             r#"
             SELECT 
                 t.id,
                 t.graph_id,
                 g.tenant_id,
                 t.cron_expression,
-                t.next_run_at
+                t.next_run_at,
+                t.filter_conditions,
+                tn.settings->>'timezone' AS tenant_timezone
             FROM workflow_triggers t
             JOIN workflow_graphs g ON t.graph_id = g.id
+            JOIN tenants tn ON tn.id = g.tenant_id
             WHERE 
                 t.trigger_type = 'scheduled'
                 AND t.is_active = true
@@ -126,7 +229,26 @@ impl ScheduledTriggerRunner {
 
         let mut processed = 0;
 
-        for (trigger_id, graph_id, tenant_id, cron_expr, last_run) in due_triggers {
+        for trigger in due_triggers {
+            let (schedule, tz) = trigger.schedule();
+            let DueTrigger { id: trigger_id, graph_id, tenant_id, cron_expression: cron_expr, next_run_at: last_run, .. } =
+                trigger;
+
+            // Rescheduled past a blackout added since next_run_at was set
+            let scheduled_date = last_run.map(|at| at.with_timezone(&tz).date_naive());
+            if scheduled_date.is_some_and(|date| !schedule.is_active_on(date)) {
+                let next_run = next_fire(&cron_expr, &schedule, tz, now);
+                if let Err(e) = sqlx::query("UPDATE workflow_triggers SET next_run_at = $1 WHERE id = $2")
+                    .bind(next_run)
+                    .bind(trigger_id)
+                    .execute(&self.pool)
+                    .await
+                {
+                    warn!(trigger_id = %trigger_id, error = %e, "Failed to skip inactive day");
+                }
+                continue;
+            }
+
             // Queue workflow execution
             let execution_id = Uuid::new_v4();
             let trigger_data = serde_json::json!({
@@ -162,7 +284,7 @@ impl ScheduledTriggerRunner {
             }
 
             // Calculate and update next run time
-            let next_run = calculate_next_cron_run(&cron_expr, now);
+            let next_run = next_fire(&cron_expr, &schedule, tz, now);
             
             let update_result = sqlx::query(
                 r#"
@@ -202,82 +324,151 @@ impl ScheduledTriggerRunner {
     }
 }
 
+/// A scheduled trigger that is due
+#[derive(Debug, sqlx::FromRow)]
+struct DueTrigger {
+    id: Uuid,
+    graph_id: Uuid,
+    tenant_id: Uuid,
+    cron_expression: String,
+    next_run_at: Option<DateTime<Utc>>,
+    filter_conditions: Option<JsonValue>,
+    tenant_timezone: Option<String>,
+}
+
+impl DueTrigger {
+    /// The trigger's schedule and the timezone it is read in
+    fn schedule(&self) -> (TriggerSchedule, Tz) {
+        let schedule: TriggerSchedule = match &self.filter_conditions {
+            Some(conditions) => serde_json::from_value(conditions.clone()).unwrap_or_else(|e| {
+                warn!(trigger_id = %self.id, error = %e, "Ignoring invalid trigger schedule");
+                TriggerSchedule::default()
+            }),
+            None => TriggerSchedule::default(),
+        };
+        let tenant_tz = core_engagement::parse_timezone(self.tenant_timezone.as_deref()).unwrap_or(Tz::UTC);
+        let tz = schedule.timezone_or(tenant_tz);
+        (schedule, tz)
+    }
+}
+
+/// When a scheduled trigger may fire, beyond its cron expression
+///
+/// Stored in the trigger's `filter_conditions`, e.g.
+/// `{"timezone": "Asia/Dubai", "weekdays": ["Mon", "Tue"], "blackout_dates": ["2025-12-25"]}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TriggerSchedule {
+    /// IANA zone the cron expression is read in; the tenant's when unset
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Days the trigger fires on; every day when empty
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    /// Local dates the trigger never fires on, e.g. public holidays
+    #[serde(default)]
+    pub blackout_dates: Vec<NaiveDate>,
+}
+
+impl TriggerSchedule {
+    /// Whether the trigger may fire on the local `date`
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        (self.weekdays.is_empty() || self.weekdays.contains(&date.weekday()))
+            && !self.blackout_dates.contains(&date)
+    }
+
+    /// The trigger's own timezone, else `fallback`
+    pub fn timezone_or(&self, fallback: Tz) -> Tz {
+        core_engagement::parse_timezone(self.timezone.as_deref()).unwrap_or_else(|e| {
+            warn!(error = %e, "Ignoring trigger timezone");
+            fallback
+        })
+    }
+}
+
+/// Next fire time after `from`, read in `tz` and skipping days the
+/// schedule excludes
+///
+/// Wall-clock expressions stay at the same local time across DST changes,
+/// so "0 9 * * *" is 9am local all year.
+pub fn next_fire(cron_expr: &str, schedule: &TriggerSchedule, tz: Tz, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let wall_clock = wall_clock_schedule(cron_expr);
+    let mut candidate = calculate_next_cron_run(cron_expr, from, tz)?;
+
+    for _ in 0..MAX_SKIPPED_DAYS {
+        let date = candidate.with_timezone(&tz).date_naive();
+        if schedule.is_active_on(date) {
+            return Some(candidate);
+        }
+        // Resume from the start of the next local day
+        let next_day = local_to_utc(tz, (date + ChronoDuration::days(1)).and_time(NaiveTime::MIN));
+        candidate = match &wall_clock {
+            Some(cron) => cron.next_after(next_day - ChronoDuration::seconds(1), tz)?,
+            None => next_day,
+        };
+    }
+    None
+}
+
+/// Days `next_fire` skips before giving up, e.g. when every weekday is blacked out
+const MAX_SKIPPED_DAYS: usize = 366 * 2;
+
+/// The cron schedule for expressions fixed to the wall clock; `None` for
+/// plain intervals (`@hourly`, `*/N * * * *`, `0 */N * * *`), which run
+/// from the previous fire regardless of timezone
+fn wall_clock_schedule(cron_expr: &str) -> Option<CronSchedule> {
+    let expr = cron_expr.trim().to_lowercase();
+    let expr = match expr.as_str() {
+        "@hourly" => return None,
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@weekly" => "0 0 * * 0",
+        "@monthly" => "0 0 1 * *",
+        other => other,
+    };
+
+    let parts: Vec<&str> = expr.split_whitespace().collect();
+    let is_interval = match parts.as_slice() {
+        [minute, "*", "*", "*", "*"] => minute.starts_with("*/"),
+        ["0", hour, "*", "*", "*"] => hour.starts_with("*/"),
+        _ => false,
+    };
+    if is_interval {
+        return None;
+    }
+    expr.parse().ok()
+}
+
 /// Calculate the next run time from a cron expression
 /// 
-/// Supports simplified cron syntax:
+/// Supports standard 5-field cron syntax read on the wall clock in `tz`,
+/// plus shorthands:
 /// - `@hourly` - Every hour
 /// - `@daily` or `@midnight` - Every day at midnight
 /// - `@weekly` - Every Sunday at midnight
 /// - `@monthly` - First of every month at midnight
 /// - `*/N * * * *` - Every N minutes
 /// - `0 */N * * *` - Every N hours
-/// - `0 0 * * *` - Every day at midnight
-/// - `0 0 * * 0` - Every Sunday at midnight
-fn calculate_next_cron_run(cron_expr: &str, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
-    let expr = cron_expr.trim().to_lowercase();
-    
-    // Handle shorthand expressions
-    match expr.as_str() {
-        "@hourly" => return Some(from + ChronoDuration::hours(1)),
-        "@daily" | "@midnight" => {
-            let tomorrow = from + ChronoDuration::days(1);
-            return Some(tomorrow.date_naive().and_hms_opt(0, 0, 0)?.and_utc());
-        }
-        "@weekly" => {
-            let days_until_sunday = (7 - from.weekday().num_days_from_sunday()) % 7;
-            let next_sunday = from + ChronoDuration::days(days_until_sunday as i64 + 7);
-            return Some(next_sunday.date_naive().and_hms_opt(0, 0, 0)?.and_utc());
-        }
-        "@monthly" => {
-            let next_month = if from.month() == 12 {
-                from.with_year(from.year() + 1)?.with_month(1)?
-            } else {
-                from.with_month(from.month() + 1)?
-            };
-            return Some(next_month.with_day(1)?.date_naive().and_hms_opt(0, 0, 0)?.and_utc());
-        }
-        _ => {}
-    }
-    
-    // Parse standard cron format: minute hour day_of_month month day_of_week
-    let parts: Vec<&str> = expr.split_whitespace().collect();
-    if parts.len() < 5 {
-        warn!(cron_expr = %cron_expr, "Invalid cron expression format");
-        // Default to 1 hour from now if parsing fails
-        return Some(from + ChronoDuration::hours(1));
+fn calculate_next_cron_run(cron_expr: &str, from: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+    if let Some(schedule) = wall_clock_schedule(cron_expr) {
+        return schedule.next_after(from, tz);
     }
 
-    // Simple parsing for common patterns
-    let minute_part = parts[0];
-    let hour_part = parts[1];
-
-    // Handle "*/N" for minutes
-    if minute_part.starts_with("*/") {
-        if let Ok(interval) = minute_part[2..].parse::<i64>() {
-            return Some(from + ChronoDuration::minutes(interval));
-        }
+    let expr = cron_expr.trim().to_lowercase();
+    if expr == "@hourly" {
+        return Some(from + ChronoDuration::hours(1));
     }
 
-    // Handle "*/N" for hours
-    if minute_part == "0" && hour_part.starts_with("*/") {
-        if let Ok(interval) = hour_part[2..].parse::<i64>() {
+    let parts: Vec<&str> = expr.split_whitespace().collect();
+    if let ["0", hour, ..] = parts.as_slice() {
+        if let Some(interval) = hour.strip_prefix("*/").and_then(|n| n.parse::<i64>().ok()) {
             return Some(from + ChronoDuration::hours(interval));
         }
     }
-
-    // Handle specific times (e.g., "0 9 * * *" = 9 AM every day)
-    if let (Ok(minute), Ok(hour)) = (minute_part.parse::<u32>(), hour_part.parse::<u32>()) {
-        let today_at_time = from.date_naive().and_hms_opt(hour, minute, 0)?;
-        if today_at_time.and_utc() > from {
-            return Some(today_at_time.and_utc());
-        } else {
-            // Already passed today, schedule for tomorrow
-            let tomorrow = from.date_naive() + ChronoDuration::days(1);
-            return Some(tomorrow.and_hms_opt(hour, minute, 0)?.and_utc());
-        }
+    if let Some(interval) = parts.first().and_then(|m| m.strip_prefix("*/")).and_then(|n| n.parse::<i64>().ok()) {
+        return Some(from + ChronoDuration::minutes(interval));
     }
 
-    // Fallback: 1 hour from now
+    warn!(cron_expr = %cron_expr, "Invalid cron expression format");
+    // Default to 1 hour from now if parsing fails
     Some(from + ChronoDuration::hours(1))
 }
 
@@ -288,6 +479,8 @@ pub struct DelayedActionTrigger {
     pub delay_duration: ChronoDuration,
     pub action: String,
     pub condition: Option<JsonValue>,
+    /// Suspended execution to resume when due, instead of starting the graph again
+    pub execution_id: Option<Uuid>,
 }
 
 impl DelayedActionTrigger {
@@ -308,6 +501,7 @@ impl DelayedActionTrigger {
             "action": trigger.action,
             "condition": trigger.condition,
             "delay_hours": trigger.delay_duration.num_hours(),
+            "execution_id": trigger.execution_id,
         });
 
         sqlx::query(
@@ -355,18 +549,31 @@ impl DelayedActionTrigger {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Timelike;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn weekdays_only() -> TriggerSchedule {
+        serde_json::from_value(serde_json::json!({
+            "timezone": "America/New_York",
+            "weekdays": ["Mon", "Tue", "Wed", "Thu", "Fri"]
+        }))
+        .unwrap()
+    }
 
     #[test]
     fn test_cron_shortcuts() {
         let now = Utc::now();
         
         // @hourly should be 1 hour from now
-        let next = calculate_next_cron_run("@hourly", now).unwrap();
+        let next = calculate_next_cron_run("@hourly", now, Tz::UTC).unwrap();
         assert!((next - now).num_minutes() >= 59);
         assert!((next - now).num_minutes() <= 61);
 
         // @daily should be within next 24 hours
-        let next = calculate_next_cron_run("@daily", now).unwrap();
+        let next = calculate_next_cron_run("@daily", now, Tz::UTC).unwrap();
         assert!((next - now).num_hours() <= 24);
     }
 
@@ -375,11 +582,44 @@ mod tests {
         let now = Utc::now();
         
         // */15 * * * * = every 15 minutes
-        let next = calculate_next_cron_run("*/15 * * * *", now).unwrap();
+        let next = calculate_next_cron_run("*/15 * * * *", now, Tz::UTC).unwrap();
         assert_eq!((next - now).num_minutes(), 15);
 
         // 0 */2 * * * = every 2 hours
-        let next = calculate_next_cron_run("0 */2 * * *", now).unwrap();
+        let next = calculate_next_cron_run("0 */2 * * *", now, Tz::UTC).unwrap();
         assert_eq!((next - now).num_hours(), 2);
     }
+
+    #[test]
+    fn test_weekday_9am_across_spring_forward() {
+        let schedule = weekdays_only();
+        let tz = schedule.timezone_or(Tz::UTC);
+
+        // Thursday before the change: 9am EST is 14:00 UTC
+        let before = next_fire("0 9 * * *", &schedule, tz, utc("2025-03-06T12:00:00Z")).unwrap();
+        assert_eq!(before, utc("2025-03-06T14:00:00Z"));
+
+        // From Friday's run the weekend is skipped, and clocks went forward on
+        // Sunday 2025-03-09: Monday's 9am EDT is 13:00 UTC
+        let after = next_fire("0 9 * * *", &schedule, tz, utc("2025-03-07T14:00:00Z")).unwrap();
+        assert_eq!(after, utc("2025-03-10T13:00:00Z"));
+        assert_eq!(after.with_timezone(&tz).hour(), 9);
+    }
+
+    #[test]
+    fn test_blackout_dates_and_interval_weekdays() {
+        let mut schedule = weekdays_only();
+        schedule.blackout_dates = vec![NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()];
+        let tz = schedule.timezone_or(Tz::UTC);
+
+        let next = next_fire("0 9 * * *", &schedule, tz, utc("2025-03-07T14:00:00Z")).unwrap();
+        assert_eq!(next, utc("2025-03-11T13:00:00Z"));
+
+        // An interval reaching Saturday resumes at Tuesday's local midnight
+        let next = next_fire("*/15 * * * *", &schedule, tz, utc("2025-03-08T04:50:00Z")).unwrap();
+        assert_eq!(next, utc("2025-03-11T04:00:00Z"));
+
+        let never = TriggerSchedule { weekdays: vec![Weekday::Sat], blackout_dates: vec![], timezone: None };
+        assert!(next_fire("0 9 * * 1-5", &never, Tz::UTC, utc("2025-03-07T14:00:00Z")).is_none());
+    }
 }
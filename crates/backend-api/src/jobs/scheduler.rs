@@ -3,45 +3,91 @@
 //! Runs maintenance jobs at specific times:
 //! - Snapshot cleanup: 2 AM daily (low-traffic hours)
 //! - Scheduled triggers: Every minute
+//! - Cron jobs: enqueued on the `JobQueue` at each fire time
 
 use sqlx::PgPool;
 use std::sync::Arc;
-use tokio::time::{Duration, interval_at, Instant};
+use tokio::time::Duration;
 use tracing::{info, error, warn};
-use chrono::{Utc, Timelike};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
 
+use super::cron::CronSchedule;
+use super::queue::{JobQueue, JobOptions};
 use super::snapshot_cleanup::{SnapshotCleanupJob, SnapshotCleanupConfig};
 use super::scheduled_trigger_runner::{ScheduledTriggerRunner, ScheduledTriggerConfig};
 use crate::workflow_trigger::WorkflowTriggerService;
 
 /// Main scheduler configuration
 pub struct SchedulerConfig {
-    /// Hour to run cleanup (0-23, default: 2 AM)
-    pub cleanup_hour: u32,
-    /// Minute to run cleanup (0-59, default: 0)
-    pub cleanup_minute: u32,
+    /// Cron expression (UTC) for snapshot cleanup (default: 2 AM daily)
+    pub cleanup_schedule: String,
     /// Enable scheduled jobs
     pub enable_snapshot_cleanup: bool,
     /// Enable trigger runner  
     pub enable_scheduled_triggers: bool,
+    /// How often to check cron jobs for due occurrences
+    pub cron_tick_secs: u64,
 }
 
 impl Default for SchedulerConfig {
     fn default() -> Self {
         Self {
-            cleanup_hour: 2,
-            cleanup_minute: 0,
+            cleanup_schedule: "0 2 * * *".to_string(),
             enable_snapshot_cleanup: true,
             enable_scheduled_triggers: true,
+            cron_tick_secs: 30,
         }
     }
 }
 
+/// A recurring job enqueued on each fire time of its schedule
+#[derive(Debug, Clone)]
+pub struct CronJob {
+    /// Unique; keys the last fire time across restarts
+    pub name: String,
+    pub schedule: CronSchedule,
+    /// Fire times follow this tenant's timezone (`settings.timezone`); UTC when `None`
+    pub tenant_id: Option<Uuid>,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub options: JobOptions,
+    /// After downtime, run the missed occurrences once instead of skipping them
+    pub catch_up: bool,
+}
+
+impl CronJob {
+    pub fn new(name: &str, schedule: CronSchedule, job_type: &str, payload: serde_json::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            schedule,
+            tenant_id: None,
+            job_type: job_type.to_string(),
+            payload,
+            options: JobOptions::default(),
+            catch_up: true,
+        }
+    }
+
+    pub fn for_tenant(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    pub fn without_catch_up(mut self) -> Self {
+        self.catch_up = false;
+        self
+    }
+}
+
 /// Main job scheduler
 pub struct JobScheduler {
     pool: PgPool,
     config: SchedulerConfig,
     trigger_service: Option<Arc<WorkflowTriggerService>>,
+    queue: Option<Arc<JobQueue>>,
+    cron_jobs: Vec<CronJob>,
 }
 
 impl JobScheduler {
@@ -51,6 +97,8 @@ impl JobScheduler {
             pool,
             config: SchedulerConfig::default(),
             trigger_service: None,
+            queue: None,
+            cron_jobs: Vec::new(),
         }
     }
 
@@ -60,6 +108,8 @@ impl JobScheduler {
             pool,
             config,
             trigger_service: None,
+            queue: None,
+            cron_jobs: Vec::new(),
         }
     }
 
@@ -69,10 +119,23 @@ impl JobScheduler {
         self
     }
 
+    /// Set the queue cron jobs are enqueued on
+    pub fn with_queue(mut self, queue: Arc<JobQueue>) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Add a recurring job; needs a queue
+    pub fn with_cron_job(mut self, job: CronJob) -> Self {
+        self.cron_jobs.push(job);
+        self
+    }
+
     /// Start all scheduled jobs
     pub async fn start(self) {
         info!(
-            cleanup_hour = self.config.cleanup_hour,
+            cleanup_schedule = %self.config.cleanup_schedule,
+            cron_jobs = self.cron_jobs.len(),
             "Starting job scheduler"
         );
 
@@ -82,13 +145,29 @@ impl JobScheduler {
 
         // Spawn snapshot cleanup task
         if config.enable_snapshot_cleanup {
-            let cleanup_pool = pool.clone();
-            let cleanup_hour = config.cleanup_hour;
-            let cleanup_minute = config.cleanup_minute;
-            
-            tokio::spawn(async move {
-                run_daily_cleanup(cleanup_pool, cleanup_hour, cleanup_minute).await;
-            });
+            match config.cleanup_schedule.parse::<CronSchedule>() {
+                Ok(schedule) => {
+                    let cleanup_pool = pool.clone();
+                    tokio::spawn(async move {
+                        run_scheduled_cleanup(cleanup_pool, schedule).await;
+                    });
+                }
+                Err(e) => error!(error = %e, "Snapshot cleanup disabled"),
+            }
+        }
+
+        // Spawn cron job runner
+        if !self.cron_jobs.is_empty() {
+            if let Some(queue) = self.queue {
+                let cron_pool = pool.clone();
+                let cron_jobs = self.cron_jobs;
+                let tick = Duration::from_secs(config.cron_tick_secs);
+                tokio::spawn(async move {
+                    run_cron_jobs(cron_pool, queue, cron_jobs, tick).await;
+                });
+            } else {
+                warn!("Cron jobs configured but no JobQueue provided");
+            }
         }
 
         // Spawn scheduled trigger runner
@@ -115,37 +194,19 @@ impl JobScheduler {
     }
 }
 
-/// Run daily cleanup at specified hour
-async fn run_daily_cleanup(pool: PgPool, target_hour: u32, target_minute: u32) {
+/// Run snapshot cleanup at each fire time of `schedule`
+async fn run_scheduled_cleanup(pool: PgPool, schedule: CronSchedule) {
     loop {
-        // Calculate time until next cleanup
         let now = Utc::now();
-        let current_hour = now.hour();
-        let current_minute = now.minute();
-        
-        let hours_until = if current_hour < target_hour || 
-            (current_hour == target_hour && current_minute < target_minute) {
-            target_hour - current_hour
-        } else {
-            24 - current_hour + target_hour
-        };
-        
-        let minutes_until = if current_minute <= target_minute {
-            target_minute - current_minute
-        } else {
-            60 - current_minute + target_minute
+        let Some(next) = schedule.next_after(now, Tz::UTC) else {
+            error!(schedule = schedule.expression(), "Snapshot cleanup schedule never fires");
+            return;
         };
-
-        let total_seconds = (hours_until * 3600 + minutes_until * 60) as u64;
         
-        info!(
-            hours_until = hours_until,
-            minutes_until = minutes_until,
-            "Snapshot cleanup scheduled"
-        );
+        info!(next_run = %next, "Snapshot cleanup scheduled");
 
         // Sleep until cleanup time
-        tokio::time::sleep(Duration::from_secs(total_seconds)).await;
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
 
         // Run cleanup
         info!("Starting scheduled snapshot cleanup");
@@ -171,11 +232,108 @@ async fn run_daily_cleanup(pool: PgPool, target_hour: u32, target_minute: u32) {
                 error!(error = %e, "Snapshot cleanup failed");
             }
         }
+    }
+}
+
+/// Check every `tick` whether any cron job is due and enqueue it
+async fn run_cron_jobs(pool: PgPool, queue: Arc<JobQueue>, jobs: Vec<CronJob>, tick: Duration) {
+    // Occurrences this recent count as on time rather than missed
+    let grace = chrono::Duration::from_std(tick * 2).unwrap_or_else(|_| chrono::Duration::minutes(1));
+    let mut ticker = tokio::time::interval(tick);
+    loop {
+        ticker.tick().await;
+        for job in &jobs {
+            if let Err(e) = fire_due(&pool, &queue, job, Utc::now(), grace).await {
+                error!(cron_job = %job.name, error = %e, "Cron job check failed");
+            }
+        }
+    }
+}
 
-        // Sleep at least 1 hour before checking again
-        // This prevents multiple runs if the calculation drifts
-        tokio::time::sleep(Duration::from_secs(3600)).await;
+/// Decide what a cron job last fired at `last_fired` should do at `now`
+///
+/// Returns the latest occurrence in `(last_fired, now]` and whether to
+/// enqueue for it, or `None` if nothing is due. However many occurrences
+/// were missed, at most one run is enqueued; without `catch_up` it only
+/// runs if the latest occurrence is within `grace` of now.
+fn plan_fire(
+    schedule: &CronSchedule,
+    tz: Tz,
+    last_fired: DateTime<Utc>,
+    now: DateTime<Utc>,
+    catch_up: bool,
+    grace: chrono::Duration,
+) -> Option<(DateTime<Utc>, bool)> {
+    let latest = schedule.last_between(last_fired, now, tz)?;
+    Some((latest, catch_up || latest >= now - grace))
+}
+
+/// Enqueue `job` if an occurrence is due; returns whether it was enqueued
+async fn fire_due(
+    pool: &PgPool,
+    queue: &JobQueue,
+    job: &CronJob,
+    now: DateTime<Utc>,
+    grace: chrono::Duration,
+) -> Result<bool, super::queue::JobError> {
+    let last_fired: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT last_fired_at FROM cron_job_runs WHERE name = $1")
+            .bind(&job.name)
+            .fetch_optional(pool)
+            .await?;
+
+    // First sighting: occurrences are counted from now on
+    let Some(last_fired) = last_fired else {
+        sqlx::query("INSERT INTO cron_job_runs (name, last_fired_at) VALUES ($1, $2) ON CONFLICT (name) DO NOTHING")
+            .bind(&job.name)
+            .bind(now)
+            .execute(pool)
+            .await?;
+        return Ok(false);
+    };
+
+    let tz = tenant_timezone(pool, job.tenant_id).await?;
+    let Some((fired_at, enqueue)) = plan_fire(&job.schedule, tz, last_fired, now, job.catch_up, grace) else {
+        return Ok(false);
+    };
+
+    // Compare-and-set so only one scheduler instance takes this occurrence
+    let claimed = sqlx::query(
+        "UPDATE cron_job_runs SET last_fired_at = $2, updated_at = NOW() WHERE name = $1 AND last_fired_at = $3"
+    )
+    .bind(&job.name)
+    .bind(fired_at)
+    .bind(last_fired)
+    .execute(pool)
+    .await?
+    .rows_affected() == 1;
+    if !claimed {
+        return Ok(false);
+    }
+
+    if !enqueue {
+        info!(cron_job = %job.name, missed = %fired_at, "Skipping missed cron occurrence");
+        return Ok(false);
     }
+    let job_id = queue.enqueue_with(job.job_type.clone(), job.payload.clone(), job.options.clone()).await?;
+    info!(cron_job = %job.name, job_id = %job_id, fired_at = %fired_at, "Cron job enqueued");
+    Ok(true)
+}
+
+/// The tenant's configured timezone; UTC when unset or unknown
+async fn tenant_timezone(pool: &PgPool, tenant_id: Option<Uuid>) -> Result<Tz, sqlx::Error> {
+    let Some(tenant_id) = tenant_id else {
+        return Ok(Tz::UTC);
+    };
+    let name: Option<String> = sqlx::query_scalar("SELECT settings->>'timezone' FROM tenants WHERE id = $1")
+        .bind(tenant_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    Ok(core_engagement::parse_timezone(name.as_deref()).unwrap_or_else(|e| {
+        warn!(tenant_id = %tenant_id, error = %e, "Falling back to UTC");
+        Tz::UTC
+    }))
 }
 
 /// Utility to start the scheduler from main.rs
@@ -190,3 +348,66 @@ pub async fn start_background_jobs(pool: PgPool, trigger_service: Option<Arc<Wor
         scheduler.start().await;
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::get_test_pool;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_missed_occurrences_run_once_with_catch_up() {
+        let nightly: CronSchedule = "0 2 * * *".parse().unwrap();
+        let grace = chrono::Duration::minutes(1);
+        // Down from the 1st to the 4th: three 2 AM runs missed
+        let (last_fired, now) = (utc("2025-01-01T02:00:00Z"), utc("2025-01-04T09:00:00Z"));
+
+        let plan = plan_fire(&nightly, Tz::UTC, last_fired, now, true, grace);
+        assert_eq!(plan, Some((utc("2025-01-04T02:00:00Z"), true)));
+        // The next tick has nothing left to run
+        assert_eq!(plan_fire(&nightly, Tz::UTC, utc("2025-01-04T02:00:00Z"), now, true, grace), None);
+
+        // Without catch-up the missed runs are skipped, but still marked fired
+        assert_eq!(plan_fire(&nightly, Tz::UTC, last_fired, now, false, grace), Some((utc("2025-01-04T02:00:00Z"), false)));
+        // An on-time occurrence runs either way
+        let on_time = utc("2025-01-04T02:00:20Z");
+        assert_eq!(plan_fire(&nightly, Tz::UTC, utc("2025-01-03T02:00:00Z"), on_time, false, grace), Some((utc("2025-01-04T02:00:00Z"), true)));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_fire_due_enqueues_missed_occurrences_once() {
+        let pool = get_test_pool().await;
+        let name = format!("test-cron-{}", Uuid::new_v4().simple());
+        let queue = JobQueue::new(pool.clone(), &name);
+        let job = CronJob::new(&name, "0 * * * *".parse().unwrap(), "digest", serde_json::json!({}));
+        let now = Utc::now();
+        let grace = chrono::Duration::minutes(1);
+
+        // First sighting only records a baseline
+        assert!(!fire_due(&pool, &queue, &job, now, grace).await.unwrap());
+        // Scheduler "down" for five hours
+        sqlx::query("UPDATE cron_job_runs SET last_fired_at = $2 WHERE name = $1")
+            .bind(&name)
+            .bind(now - chrono::Duration::hours(5))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(fire_due(&pool, &queue, &job, now, grace).await.unwrap());
+        assert!(!fire_due(&pool, &queue, &job, now, grace).await.unwrap());
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE queue_name = $1")
+            .bind(&name)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+
+        for sql in ["DELETE FROM jobs WHERE queue_name = $1", "DELETE FROM cron_job_runs WHERE name = $1"] {
+            sqlx::query(sql).bind(&name).execute(&pool).await.unwrap();
+        }
+    }
+}
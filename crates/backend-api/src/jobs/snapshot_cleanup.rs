@@ -175,7 +175,7 @@ impl BatchSnapshotCreator {
             SELECT 
                 e.aggregate_id,
                 COUNT(*) as event_count
-            FROM events e
+            FROM domain_events e
             LEFT JOIN aggregate_snapshots s ON e.aggregate_id = s.aggregate_id
             WHERE s.id IS NULL OR e.aggregate_version > COALESCE(s.version, 0)
             GROUP BY e.aggregate_id
@@ -1,9 +1,10 @@
 //! Job Workers - Process background jobs
 
-use super::queue::{JobQueue, Job, JobStatus};
+use super::queue::{JobQueue, JobError};
+use crate::middleware::trace_context::with_trace_id;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
-use tracing::{info, error};
+use tracing::{info, error, Instrument};
 
 pub type JobHandler = Arc<dyn Fn(serde_json::Value) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
 type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
@@ -49,9 +50,9 @@ impl Worker {
         })
     }
     
-    async fn process_next_job(&self) -> Result<bool, Box<dyn std::error::Error>> {
-        // Dequeue with 5 second timeout
-        let job = self.queue.dequeue(5).await?;
+    async fn process_next_job(&self) -> Result<bool, JobError> {
+        // Claim the next due job, highest priority first
+        let job = self.queue.dequeue().await?;
         
         let job = match job {
             Some(j) => j,
@@ -65,30 +66,37 @@ impl Worker {
             Some(h) => h,
             None => {
                 error!("No handler for job type: {}", job.job_type);
-                self.queue.fail(job.id, format!("No handler for type: {}", job.job_type)).await?;
+                self.queue.fail(job.id, format!("No handler for type: {}", job.job_type), self.failure_context()).await?;
                 return Ok(true);
             }
         };
         
-        // Execute job
-        match handler(job.payload.clone()).await {
+        // Execute job under its trace id, so logs and jobs it enqueues carry it
+        let span = tracing::info_span!("job", job_id = %job.id, trace_id = job.trace_id.as_deref().unwrap_or_default());
+        let run = with_trace_id(job.trace_id.clone(), handler(job.payload.clone()));
+        match run.instrument(span).await {
             Ok(()) => {
                 info!("Job {} completed successfully", job.id);
                 self.queue.complete(job.id).await?;
             }
             Err(e) => {
                 error!("Job {} failed: {}", job.id, e);
-                let will_retry = self.queue.fail(job.id, e).await?;
+                let will_retry = self.queue.fail(job.id, e, self.failure_context()).await?;
                 if will_retry {
                     info!("Job {} will be retried", job.id);
                 } else {
-                    error!("Job {} failed permanently", job.id);
+                    error!("Job {} moved to dead letter queue after {} attempts", job.id, job.attempts + 1);
                 }
             }
         }
         
         Ok(true)
     }
+    
+    /// Recorded with a job that ends up dead-lettered
+    fn failure_context(&self) -> serde_json::Value {
+        serde_json::json!({ "worker": self.id })
+    }
 }
 
 /// Worker pool manager
@@ -124,3 +132,94 @@ impl WorkerPool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::queue::{JobOptions, JobStatus};
+    use sqlx::PgPool;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use test_support::get_test_pool;
+    use uuid::Uuid;
+
+    /// Jobs running right now and the most seen at once, per lock key and overall
+    #[derive(Default)]
+    struct Overlap {
+        running: HashMap<String, usize>,
+        max_per_key: HashMap<String, usize>,
+        total: usize,
+        max_total: usize,
+    }
+
+    fn recording_handler(overlap: Arc<Mutex<Overlap>>) -> JobHandler {
+        Arc::new(move |payload: serde_json::Value| {
+            let overlap = overlap.clone();
+            Box::pin(async move {
+                let key = payload["key"].as_str().unwrap_or_default().to_string();
+                {
+                    let mut o = overlap.lock().unwrap();
+                    let running = o.running.entry(key.clone()).or_default();
+                    *running += 1;
+                    let now = *running;
+                    let max = o.max_per_key.entry(key.clone()).or_default();
+                    *max = (*max).max(now);
+                    o.total += 1;
+                    o.max_total = o.max_total.max(o.total);
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+                let mut o = overlap.lock().unwrap();
+                *o.running.get_mut(&key).unwrap() -= 1;
+                o.total -= 1;
+                Ok(())
+            }) as BoxFuture<'static, Result<(), String>>
+        })
+    }
+
+    async fn wait_until_done(queue: &JobQueue, ids: &[Uuid]) {
+        for _ in 0..100 {
+            let mut done = true;
+            for id in ids {
+                let job = queue.get(*id).await.unwrap().unwrap();
+                done &= job.status == JobStatus::Completed;
+            }
+            if done {
+                return;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        panic!("jobs did not complete");
+    }
+
+    async fn run_keyed_jobs(pool: &PgPool, keys: &[&str]) -> Overlap {
+        let queue_name = format!("test-jobs-{}", Uuid::new_v4().simple());
+        let queue = Arc::new(JobQueue::new(pool.clone(), &queue_name));
+        let mut ids = Vec::new();
+        for key in keys {
+            let options = JobOptions { lock_key: Some(key.to_string()), ..Default::default() };
+            ids.push(queue.enqueue_with("sync_record".into(), serde_json::json!({ "key": key }), options).await.unwrap());
+        }
+
+        let overlap = Arc::new(Mutex::new(Overlap::default()));
+        let handlers = HashMap::from([("sync_record".to_string(), recording_handler(overlap.clone()))]);
+        let pool_handle = WorkerPool::new(2, queue.clone(), handlers);
+        wait_until_done(&queue, &ids).await;
+        pool_handle.shutdown().await;
+
+        sqlx::query("DELETE FROM jobs WHERE queue_name = $1").bind(&queue_name).execute(pool).await.unwrap();
+        Arc::try_unwrap(overlap).ok().unwrap().into_inner().unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_jobs_sharing_lock_key_never_overlap() {
+        let pool = get_test_pool().await;
+
+        let same = run_keyed_jobs(&pool, &["deal-1", "deal-1"]).await;
+        assert_eq!(same.max_per_key["deal-1"], 1);
+        assert_eq!(same.max_total, 1);
+
+        let distinct = run_keyed_jobs(&pool, &["deal-1", "deal-2"]).await;
+        assert_eq!(distinct.max_total, 2);
+    }
+}
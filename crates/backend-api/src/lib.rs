@@ -11,9 +11,11 @@ pub mod events;
 pub mod ai;
 pub mod cqrs;
 pub mod cache;
-// pub mod jobs; // TODO: Fix type annotations for never fallback
+pub mod jobs;
 pub mod gateway;
 pub mod websocket;
 pub mod performance;
 pub mod observability;
 pub mod workflow_trigger;
+pub mod outbox;
+pub mod storage;
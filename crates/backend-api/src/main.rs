@@ -2,7 +2,7 @@
 
 use axum::{
     Router,
-    routing::{get, post},
+    routing::post,
     extract::State,
     Json,
     response::IntoResponse,
@@ -22,6 +22,23 @@ mod state;
 mod error;
 mod seed;
 mod middleware;
+mod events;
+mod outbox;
+mod storage;
+// The server runs only some of the jobs the library defines
+#[allow(dead_code, unused_imports)]
+mod jobs;
+#[allow(dead_code, unused_imports)]
+mod workflow_trigger;
+use backend_api::cqrs;
+// Only the rate limiters and request metrics are used by the server so far
+#[allow(dead_code)]
+mod gateway;
+#[allow(dead_code)]
+mod observability;
+// The metrics exporter samples the WebSocket manager's gauges
+#[allow(dead_code)]
+mod websocket;
 pub mod ai;
 
 use state::AppState;
@@ -58,6 +75,39 @@ async fn main() -> anyhow::Result<()> {
     // Create app state
     let state = Arc::new(AppState::new(pool));
 
+    // Deliver outbox events the request path didn't get to
+    let dispatcher = outbox::OutboxDispatcher::new(
+        state.pool.clone(),
+        Arc::new(outbox::StatePublisher(state.clone())),
+    );
+    tokio::spawn(dispatcher.run());
+
+    // Forget webhook events past their replay window
+    tokio::spawn(state.webhook_replay.clone().run_purge(std::time::Duration::from_secs(3600)));
+
+    // Forget failed login counters that no longer lock anyone out
+    tokio::spawn(state.login_throttle.clone().run_purge(std::time::Duration::from_secs(3600)));
+
+    // Forget Idempotency-Key responses past their TTL
+    tokio::spawn(middleware::idempotency::run_purge(state.pool.clone(), std::time::Duration::from_secs(3600)));
+
+    // Transcribe recorded calls
+    let transcriber = Arc::new(jobs::call_transcription::Transcriber::new(
+        state.pool.clone(),
+        ai::service::create_stt_provider(),
+    ));
+    let voice_queue = Arc::new(jobs::JobQueue::new(state.pool.clone(), jobs::call_transcription::VOICE_QUEUE));
+    let _voice_workers = jobs::WorkerPool::new(1, voice_queue, transcriber.handlers());
+
+    // Health checks and the Prometheus scrape endpoint
+    let exporter = Arc::new(observability::metrics::MetricsExporter::new(
+        Arc::new(observability::metrics::AppMetrics::new()),
+        state.request_metrics.clone(),
+        state.pool.clone(),
+    ));
+    let observability_routes = observability::observability_routes(exporter)
+        .with_state((*state).clone());
+
     // Build public routes with tenant middleware
     let public_routes = routes::public::routes()
         .layer(axum_middleware::from_fn_with_state(
@@ -67,8 +117,8 @@ async fn main() -> anyhow::Result<()> {
 
     // Build router
     let app = Router::new()
-        // Health check
-        .route("/health", get(health_check))
+        // Health, readiness, liveness and metrics
+        .merge(observability_routes)
         // Seed endpoint (dev only)
         .route("/seed", post(seed_data))
         // Public routes (with tenant resolution middleware)
@@ -78,11 +128,7 @@ async fn main() -> anyhow::Result<()> {
         // WebSocket routes (real-time events)
         .merge(routes::ws::routes())
         // API routes (authenticated)
-        .nest("/api/v1", routes::api_routes()
-            .layer(axum_middleware::from_fn_with_state(
-                state.clone(),
-                middleware::tenant::resolve_tenant,
-            )))
+        .nest("/api/v1", routes::api_router(state.clone()))
         // Add state
         .with_state(state)
         // Add middleware
@@ -100,7 +146,9 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Starting server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app.layer(axum::middleware::map_response(set_opfs_headers))).await?;
+    // Connect info gives handlers the peer address for per-IP throttling
+    let app = app.layer(axum::middleware::map_response(set_opfs_headers));
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
@@ -115,13 +163,6 @@ async fn set_opfs_headers(response: axum::response::Response) -> axum::response:
     response
 }
 
-async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "ok",
-        "version": env!("CARGO_PKG_VERSION")
-    }))
-}
-
 async fn seed_data(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
@@ -9,9 +9,10 @@ use axum::{
     middleware::Next,
 };
 use chrono::{DateTime, Utc};
+use core_models::FieldDef;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use sqlx::PgPool;
+use sqlx::{types::Json, PgPool};
 use std::sync::Arc;
 use tracing::{info, warn};
 use uuid::Uuid;
@@ -26,11 +27,77 @@ pub struct AuditLogEntry {
     pub resource_type: String,
     pub resource_id: Option<Uuid>,
     pub details: JsonValue,
+    /// Field-level changes, for record updates
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+/// One field's before/after values in an update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// Field label at the time of the change, for display
+    pub label: String,
+    pub old: JsonValue,
+    pub new: JsonValue,
+    /// Values hidden from a viewer without audit PII access
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
+}
+
+impl FieldChange {
+    /// Hide the before/after values, keeping which field changed
+    pub fn redact(&mut self) {
+        self.old = JsonValue::Null;
+        self.new = JsonValue::Null;
+        self.redacted = true;
+    }
+}
+
+/// Changed fields between two versions of a record's data, in field order
+///
+/// Missing and `null` values are treated alike; undefined keys follow the
+/// defined fields in name order.
+pub fn field_changes(fields: &[FieldDef], old: &JsonValue, new: &JsonValue) -> Vec<FieldChange> {
+    let value = |data: &JsonValue, key: &str| data.get(key).cloned().unwrap_or(JsonValue::Null);
+
+    let mut keys: Vec<(&str, &str)> = fields.iter().map(|f| (f.name.as_str(), f.label.as_str())).collect();
+    let mut extra: Vec<&str> = [old, new]
+        .iter()
+        .filter_map(|data| data.as_object())
+        .flat_map(|obj| obj.keys().map(String::as_str))
+        .filter(|key| !fields.iter().any(|f| f.name == *key))
+        .collect();
+    extra.sort_unstable();
+    extra.dedup();
+    keys.extend(extra.into_iter().map(|key| (key, key)));
+
+    keys.into_iter()
+        .filter_map(|(field, label)| {
+            let (old, new) = (value(old, field), value(new, field));
+            (old != new).then(|| FieldChange {
+                field: field.to_string(),
+                label: label.to_string(),
+                old,
+                new,
+                redacted: false,
+            })
+        })
+        .collect()
+}
+
+/// Hide the values of changes to PII fields
+pub fn redact_pii(changes: &mut [FieldChange], fields: &[FieldDef]) {
+    for change in changes {
+        if fields.iter().any(|f| f.name == change.field && f.intelligence.is_pii) {
+            change.redact();
+        }
+    }
+}
+
 /// Audit action types
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -46,6 +113,10 @@ pub enum AuditAction {
     BulkAction,
     WorkflowTrigger,
     ApiCall,
+    AssociationLinked,
+    AssociationUnlinked,
+    /// PII was masked before record data went to the AI provider
+    PiiRedacted,
 }
 
 impl std::fmt::Display for AuditAction {
@@ -62,6 +133,9 @@ impl std::fmt::Display for AuditAction {
             AuditAction::BulkAction => write!(f, "bulk_action"),
             AuditAction::WorkflowTrigger => write!(f, "workflow_trigger"),
             AuditAction::ApiCall => write!(f, "api_call"),
+            AuditAction::AssociationLinked => write!(f, "association_linked"),
+            AuditAction::AssociationUnlinked => write!(f, "association_unlinked"),
+            AuditAction::PiiRedacted => write!(f, "pii_redacted"),
         }
     }
 }
@@ -95,29 +169,9 @@ impl AuditLogger {
 
     /// Log an audit event
     pub async fn log(&self, entry: AuditLogEntry) -> Result<(), String> {
-        sqlx::query(
-            r#"
-            INSERT INTO audit_logs 
-                (id, tenant_id, user_id, action, resource_type, resource_id, details, ip_address, user_agent, created_at)
-            VALUES 
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-            "#
-        )
-        .bind(entry.id)
-        .bind(entry.tenant_id)
-        .bind(entry.user_id)
-        .bind(entry.action)
-        .bind(entry.resource_type)
-        .bind(entry.resource_id)
-        .bind(&entry.details)
-        .bind(&entry.ip_address)
-        .bind(&entry.user_agent)
-        .bind(entry.created_at)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to log audit entry: {}", e))?;
-
-        Ok(())
+        insert_entry(&self.pool, &entry)
+            .await
+            .map_err(|e| format!("Failed to log audit entry: {}", e))
     }
 
     /// Log a simple action
@@ -143,6 +197,7 @@ impl AuditLogger {
             resource_type: resource_type.to_string(),
             resource_id,
             details,
+            changes: Vec::new(),
             ip_address: None,
             user_agent: None,
             created_at: Utc::now(),
@@ -158,10 +213,10 @@ impl AuditLogger {
         limit: i64,
         offset: i64,
     ) -> Result<Vec<AuditLogEntry>, String> {
-        let entries: Vec<(Uuid, Uuid, Option<Uuid>, String, String, Option<Uuid>, JsonValue, Option<String>, Option<String>, DateTime<Utc>)> = 
+        let entries: Vec<AuditRow> = 
             sqlx::query_as(
                 r#"
-                SELECT id, tenant_id, user_id, action, resource_type, resource_id, details, ip_address, user_agent, created_at
+                SELECT id, tenant_id, user_id, action, resource_type, resource_id, details, changes, ip_address, user_agent, created_at
                 FROM audit_logs
                 WHERE tenant_id = $1
                 ORDER BY created_at DESC
@@ -175,18 +230,7 @@ impl AuditLogger {
             .await
             .map_err(|e| format!("Failed to query audit logs: {}", e))?;
 
-        Ok(entries.into_iter().map(|row| AuditLogEntry {
-            id: row.0,
-            tenant_id: row.1,
-            user_id: row.2,
-            action: row.3,
-            resource_type: row.4,
-            resource_id: row.5,
-            details: row.6,
-            ip_address: row.7,
-            user_agent: row.8,
-            created_at: row.9,
-        }).collect())
+        Ok(entries.into_iter().map(AuditLogEntry::from).collect())
     }
 
     /// Query audit logs for a specific resource
@@ -196,10 +240,10 @@ impl AuditLogger {
         resource_type: &str,
         resource_id: Uuid,
     ) -> Result<Vec<AuditLogEntry>, String> {
-        let entries: Vec<(Uuid, Uuid, Option<Uuid>, String, String, Option<Uuid>, JsonValue, Option<String>, Option<String>, DateTime<Utc>)> = 
+        let entries: Vec<AuditRow> = 
             sqlx::query_as(
                 r#"
-                SELECT id, tenant_id, user_id, action, resource_type, resource_id, details, ip_address, user_agent, created_at
+                SELECT id, tenant_id, user_id, action, resource_type, resource_id, details, changes, ip_address, user_agent, created_at
                 FROM audit_logs
                 WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
                 ORDER BY created_at DESC
@@ -212,7 +256,15 @@ impl AuditLogger {
             .await
             .map_err(|e| format!("Failed to query resource history: {}", e))?;
 
-        Ok(entries.into_iter().map(|row| AuditLogEntry {
+        Ok(entries.into_iter().map(AuditLogEntry::from).collect())
+    }
+}
+
+type AuditRow = (Uuid, Uuid, Option<Uuid>, String, String, Option<Uuid>, JsonValue, Json<Vec<FieldChange>>, Option<String>, Option<String>, DateTime<Utc>);
+
+impl From<AuditRow> for AuditLogEntry {
+    fn from(row: AuditRow) -> Self {
+        AuditLogEntry {
             id: row.0,
             tenant_id: row.1,
             user_id: row.2,
@@ -220,13 +272,41 @@ impl AuditLogger {
             resource_type: row.4,
             resource_id: row.5,
             details: row.6,
-            ip_address: row.7,
-            user_agent: row.8,
-            created_at: row.9,
-        }).collect())
+            changes: row.7.0,
+            ip_address: row.8,
+            user_agent: row.9,
+            created_at: row.10,
+        }
     }
 }
 
+/// Insert an entry on any executor, so it can share a request's connection
+pub async fn insert_entry<'e, E: sqlx::PgExecutor<'e>>(executor: E, entry: &AuditLogEntry) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_logs 
+            (id, tenant_id, user_id, action, resource_type, resource_id, details, changes, ip_address, user_agent, created_at)
+        VALUES 
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#
+    )
+    .bind(entry.id)
+    .bind(entry.tenant_id)
+    .bind(entry.user_id)
+    .bind(&entry.action)
+    .bind(&entry.resource_type)
+    .bind(entry.resource_id)
+    .bind(&entry.details)
+    .bind(Json(&entry.changes))
+    .bind(&entry.ip_address)
+    .bind(&entry.user_agent)
+    .bind(entry.created_at)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
 /// Audit logging middleware
 pub async fn audit_log_middleware(
     request: Request,
@@ -287,3 +367,66 @@ fn method_to_action(method: &axum::http::Method) -> AuditAction {
         _ => AuditAction::ApiCall,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::FieldType;
+    use serde_json::json;
+
+    fn fields() -> Vec<FieldDef> {
+        let field = |name: &str, label: &str, field_type: FieldType| {
+            FieldDef::new(Uuid::nil(), Uuid::nil(), name, label, field_type)
+        };
+        let mut phone = field("phone", "Phone", FieldType::Phone);
+        phone.intelligence.is_pii = true;
+
+        vec![
+            field("name", "Name", FieldType::Text),
+            field("stage", "Stage", FieldType::Select { options: vec!["lead".into(), "customer".into()] }),
+            phone,
+            field("budget", "Budget", FieldType::Money { currency_code: Some("AED".into()) }),
+            field("notes", "Notes", FieldType::TextArea),
+        ]
+    }
+
+    fn record() -> JsonValue {
+        json!({ "name": "Layla", "stage": "lead", "phone": "+971500000000", "budget": 1200000, "notes": "Prefers villas" })
+    }
+
+    #[test]
+    fn test_update_of_two_fields_records_two_changes() {
+        let old = record();
+        let mut new = record();
+        new["budget"] = json!(1500000);
+        new["stage"] = json!("customer");
+
+        let changes = field_changes(&fields(), &old, &new);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].label.as_str(), &changes[0].old, &changes[0].new), ("Stage", &json!("lead"), &json!("customer")));
+        assert_eq!(changes[1].field, "budget");
+        // A key removed from the data counts as a change to null
+        let mut cleared = record();
+        cleared.as_object_mut().unwrap().remove("notes");
+        assert_eq!(field_changes(&fields(), &old, &cleared)[0].new, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_pii_changes_are_redacted() {
+        let old = record();
+        let mut new = record();
+        new["phone"] = json!("+971555555555");
+        new["stage"] = json!("customer");
+
+        let mut changes = field_changes(&fields(), &old, &new);
+        redact_pii(&mut changes, &fields());
+
+        let phone = changes.iter().find(|c| c.field == "phone").unwrap();
+        assert!(phone.redacted);
+        assert_eq!((&phone.old, &phone.new), (&JsonValue::Null, &JsonValue::Null));
+        let stage = changes.iter().find(|c| c.field == "stage").unwrap();
+        assert!(!stage.redacted);
+        assert_eq!(serde_json::to_value(stage).unwrap().get("redacted"), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Authentication Middleware
+//!
+//! Runs after `resolve_tenant` on API routes and identifies the caller from
+//! an `Authorization: Bearer` JWT or, failing that, the `session` cookie.
+//! Either way the request gets the `AuthContext` and `AuthenticatedUser`
+//! handlers read, the latter with the user's custom roles added. Requests
+//! with neither carry on unauthenticated; a bearer token that doesn't
+//! validate, or was issued for another tenant, is refused.
+//!
+//! Integrations may instead send an `x-api-key`. The request then carries
+//! the `ApiKey`, not a user, and is refused unless the key's scopes cover
+//! the route: its first path segment is the resource (`entities` counts as
+//! `records`), and the method picks `read`, `write` or `delete`. A key
+//! limited to some entity types is only accepted on record routes for one
+//! of those types.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use core_auth::api_key::{ApiKey, ApiKeyService};
+use core_auth::session::{validate_jwt, JwtKeySet, SessionService};
+use core_auth::AuthError;
+use core_models::AuthContext;
+use std::sync::Arc;
+
+use super::permission::{with_grants, AuthenticatedUser};
+use super::tenant::ResolvedTenant;
+use crate::state::AppState;
+
+/// The token in an `Authorization: Bearer` header
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// The `session` cookie's value
+fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("cookie")?
+        .to_str()
+        .ok()?
+        .split(';')
+        .find_map(|cookie| cookie.trim().strip_prefix("session="))
+        .filter(|token| !token.is_empty())
+}
+
+fn user_for(auth: &AuthContext, roles: Vec<String>) -> AuthenticatedUser {
+    AuthenticatedUser {
+        id: auth.user.id,
+        tenant_id: auth.tenant_id,
+        email: auth.user.email.clone(),
+        name: auth.user.name.clone(),
+        role: auth.user.role.as_str().to_string(),
+        roles,
+    }
+}
+
+/// Who is making the request, if anyone; `Err` when a bearer token is
+/// presented but can't be accepted
+pub async fn identify(
+    headers: &HeaderMap,
+    tenant: Option<&ResolvedTenant>,
+    jwt_keys: Option<&JwtKeySet>,
+    sessions: &SessionService,
+) -> Result<Option<(AuthContext, AuthenticatedUser)>, AuthError> {
+    let same_tenant = |tenant_id| tenant.is_none_or(|tenant| tenant.id == tenant_id);
+
+    if let Some(token) = bearer_token(headers) {
+        let claims = validate_jwt(jwt_keys.ok_or(AuthError::InvalidToken)?, token)?;
+        if !same_tenant(claims.tenant_id) {
+            return Err(AuthError::InvalidToken);
+        }
+        let auth = claims.auth_context();
+        let user = user_for(&auth, claims.roles);
+        return Ok(Some((auth, user)));
+    }
+
+    let Some(token) = session_cookie(headers) else { return Ok(None) };
+    match sessions.validate_session(token).await {
+        Ok(auth) if same_tenant(auth.tenant_id) => {
+            let user = user_for(&auth, Vec::new());
+            Ok(Some((auth, user)))
+        }
+        // A stale or foreign cookie just leaves the request unauthenticated
+        Ok(_) | Err(AuthError::SessionNotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The live key in the `x-api-key` header, if one is sent; `Err` when it's
+/// unknown, revoked or belongs to another tenant
+pub async fn identify_api_key(
+    headers: &HeaderMap,
+    tenant: Option<&ResolvedTenant>,
+    api_keys: &ApiKeyService,
+) -> Result<Option<ApiKey>, AuthError> {
+    let Some(secret) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else { return Ok(None) };
+    let key = api_keys.authenticate(secret.trim()).await?;
+    if tenant.is_some_and(|tenant| tenant.id != key.tenant_id) {
+        return Err(AuthError::InvalidToken);
+    }
+    Ok(Some(key))
+}
+
+/// The resource, action and (for records) entity type a request needs a
+/// scope for
+pub fn required_scope<'a>(method: &Method, path: &'a str) -> (&'a str, &'static str, Option<&'a str>) {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    let mut segments = path.trim_start_matches('/').split('/');
+    let resource = match segments.next().unwrap_or_default() {
+        "entities" => "records",
+        resource => resource,
+    };
+    let action = match *method {
+        Method::GET | Method::HEAD | Method::OPTIONS => "read",
+        Method::DELETE => "delete",
+        _ => "write",
+    };
+    let entity = if resource == "records" { segments.next().filter(|s| !s.is_empty()) } else { None };
+    (resource, action, entity)
+}
+
+/// Whether `key` may make this request. Keys limited to some entity types
+/// only work on routes naming the entity, since search, views, exports and
+/// the like could otherwise reach records of any type
+pub fn key_permits(key: &ApiKey, method: &Method, path: &str) -> bool {
+    let (resource, action, entity) = required_scope(method, path);
+    if !key.allows(resource, action) {
+        return false;
+    }
+    match entity {
+        Some(entity) => key.covers_entity(entity),
+        None => key.entity_types.is_none(),
+    }
+}
+
+/// Same body shape as `ApiError` and `AuthError` responses
+fn refuse(status: StatusCode, error: &str, code: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": error, "code": code, "status": status.as_u16() }))).into_response()
+}
+
+fn database_failure(e: sqlx::Error) -> Response {
+    tracing::error!("Database error authenticating request: {}", e);
+    refuse(StatusCode::INTERNAL_SERVER_ERROR, "Failed to authenticate", "internal_error")
+}
+
+/// Middleware inserting the caller's `AuthContext` and `AuthenticatedUser`,
+/// or their `ApiKey`
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let tenant = request.extensions().get::<ResolvedTenant>().cloned();
+    let identified = identify(request.headers(), tenant.as_ref(), state.jwt_keys.as_deref(), &state.session_service).await;
+
+    match identified {
+        Ok(Some((auth, user))) => {
+            let grants = match state.permission_cache.grants(&state.pool, user.tenant_id, user.id).await {
+                Ok(grants) => grants,
+                Err(e) => return database_failure(e),
+            };
+            request.extensions_mut().insert(auth);
+            request.extensions_mut().insert(with_grants(user, &grants));
+            return next.run(request).await;
+        }
+        Ok(None) => {}
+        Err(AuthError::Database(e)) => return database_failure(e),
+        Err(AuthError::SessionExpired) => {
+            return refuse(StatusCode::UNAUTHORIZED, "Invalid bearer token", "token_expired")
+        }
+        Err(_) => return refuse(StatusCode::UNAUTHORIZED, "Invalid bearer token", "token_invalid"),
+    }
+
+    match identify_api_key(request.headers(), tenant.as_ref(), &state.api_key_service).await {
+        Ok(Some(key)) => {
+            if !key_permits(&key, request.method(), request.uri().path()) {
+                return refuse(StatusCode::FORBIDDEN, "API key is not scoped for this request", "scope_missing");
+            }
+            request.extensions_mut().insert(key);
+            next.run(request).await
+        }
+        Ok(None) => next.run(request).await,
+        Err(AuthError::Database(e)) => database_failure(e),
+        Err(_) => refuse(StatusCode::UNAUTHORIZED, "Invalid API key", "api_key_invalid"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use core_auth::session::issue_jwt;
+    use core_models::{UserInfo, UserRole};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    fn tenant(id: Uuid) -> ResolvedTenant {
+        ResolvedTenant {
+            id,
+            name: "Acme".to_string(),
+            subdomain: "acme".to_string(),
+            settings: serde_json::json!({}),
+            status: "active".to_string(),
+            trial_ends_at: None,
+        }
+    }
+
+    fn user(tenant_id: Uuid) -> UserInfo {
+        UserInfo {
+            id: Uuid::new_v4(),
+            tenant_id,
+            email: "agent@example.com".to_string(),
+            name: "Agent".to_string(),
+            role: UserRole::Agent,
+            avatar_url: None,
+            verification_level: 0,
+            phone: None,
+        }
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, format!("Bearer {}", token).parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_authenticates() {
+        // Bearer tokens never touch the database
+        let sessions = SessionService::new(PgPool::connect_lazy("postgres://localhost/unused").unwrap());
+        let keys = JwtKeySet::new("k1", b"secret");
+        let tenant_id = Uuid::new_v4();
+        let user = user(tenant_id);
+        let token = issue_jwt(&keys, &user, Duration::minutes(15)).unwrap();
+
+        let (auth, authenticated) = identify(&bearer(&token), Some(&tenant(tenant_id)), Some(&keys), &sessions)
+            .await
+            .unwrap()
+            .expect("token identifies the user");
+        assert_eq!(auth.user.id, user.id);
+        assert_eq!(authenticated.id, user.id);
+        assert_eq!(authenticated.tenant_id, tenant_id);
+        assert_eq!(authenticated.role, "agent");
+        assert_eq!(authenticated.role_strings(), vec!["agent".to_string()]);
+
+        // No credentials at all is fine, just anonymous
+        assert!(identify(&HeaderMap::new(), Some(&tenant(tenant_id)), Some(&keys), &sessions)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope(&Method::GET, "/api/v1/records/contact/123"), ("records", "read", Some("contact")));
+        assert_eq!(required_scope(&Method::POST, "/entities/deal"), ("records", "write", Some("deal")));
+        assert_eq!(required_scope(&Method::DELETE, "/tasks/9"), ("tasks", "delete", None));
+        assert_eq!(required_scope(&Method::PATCH, "/records"), ("records", "write", None));
+    }
+
+    #[test]
+    fn test_entity_restricted_keys_only_work_on_entity_routes() {
+        let key = |entity_types: Option<Vec<String>>| ApiKey {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Zapier".to_string(),
+            key_prefix: "jk_abc".to_string(),
+            scopes: vec!["*:read".to_string()],
+            entity_types,
+            created_by: None,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: chrono::Utc::now(),
+        };
+
+        let contacts_only = key(Some(vec!["contact".to_string()]));
+        assert!(key_permits(&contacts_only, &Method::GET, "/api/v1/records/contact/123"));
+        assert!(!key_permits(&contacts_only, &Method::GET, "/api/v1/records/deal/123"));
+        assert!(!key_permits(&contacts_only, &Method::POST, "/api/v1/records/contact"));
+        for path in ["/api/v1/search?q=a", "/api/v1/views", "/api/v1/export/deal", "/api/v1/records"] {
+            assert!(!key_permits(&contacts_only, &Method::GET, path), "{}", path);
+        }
+
+        let unrestricted = key(None);
+        assert!(key_permits(&unrestricted, &Method::GET, "/api/v1/search"));
+        assert!(key_permits(&unrestricted, &Method::GET, "/api/v1/records/deal/123"));
+    }
+
+    #[tokio::test]
+    async fn test_bad_bearer_tokens_are_refused() {
+        let sessions = SessionService::new(PgPool::connect_lazy("postgres://localhost/unused").unwrap());
+        let keys = JwtKeySet::new("k1", b"secret");
+        let tenant_id = Uuid::new_v4();
+
+        let expired = issue_jwt(&keys, &user(tenant_id), Duration::minutes(-5)).unwrap();
+        assert!(matches!(
+            identify(&bearer(&expired), Some(&tenant(tenant_id)), Some(&keys), &sessions).await,
+            Err(AuthError::SessionExpired)
+        ));
+
+        let foreign = issue_jwt(&keys, &user(Uuid::new_v4()), Duration::minutes(15)).unwrap();
+        assert!(matches!(
+            identify(&bearer(&foreign), Some(&tenant(tenant_id)), Some(&keys), &sessions).await,
+            Err(AuthError::InvalidToken)
+        ));
+
+        let forged = issue_jwt(&JwtKeySet::new("k1", b"guessed"), &user(tenant_id), Duration::minutes(15)).unwrap();
+        assert!(matches!(
+            identify(&bearer(&forged), Some(&tenant(tenant_id)), Some(&keys), &sessions).await,
+            Err(AuthError::InvalidToken)
+        ));
+
+        // Bearer auth is off when the deployment has no keys
+        let valid = issue_jwt(&keys, &user(tenant_id), Duration::minutes(15)).unwrap();
+        assert!(identify(&bearer(&valid), Some(&tenant(tenant_id)), None, &sessions).await.is_err());
+    }
+}
@@ -0,0 +1,557 @@
+//! Idempotency Keys
+//!
+//! Runs after `authenticate` on API routes. A mutating request carrying an
+//! `Idempotency-Key` header claims (tenant, key, route) before it runs; its
+//! response is stored and replayed to any retry with the same key, marked
+//! `Idempotent-Replayed: true`. Reusing a key with a different body - or
+//! from a different caller - is a 409, as is a retry while the first
+//! request is still running, or the first response couldn't be kept (a
+//! stream, or too large to store). Server errors release the claim so the
+//! retry runs for real; a claim whose request never finished can be taken
+//! over by a retry after `CLAIM_LEASE_SECS`. Keys expire after
+//! `IDEMPOTENCY_TTL_HOURS`.
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{OriginalUri, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use core_auth::api_key::ApiKey;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::permission::AuthenticatedUser;
+use super::tenant::ResolvedTenant;
+use crate::state::AppState;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Set on a stored response played back to a retry
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// How long a key's response is kept
+pub const IDEMPOTENCY_TTL_HOURS: i64 = 24;
+
+/// How long a claim holds off retries of a request that hasn't finished
+const CLAIM_LEASE_SECS: i64 = 300;
+
+/// Longest accepted key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Largest request or response body that can be stored for replay
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Response headers not replayed
+const UNSTORED_HEADERS: [&str; 3] = ["set-cookie", "content-length", "x-request-id"];
+
+/// What the store holds for a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum Claim {
+    /// Newly claimed; the request should run
+    Claimed,
+    /// Stored response of the first request
+    Completed(StoredResponse),
+    /// The first request finished, but its response wasn't kept
+    CompletedUnstored,
+    /// The first request hasn't finished yet
+    InProgress,
+    /// The key was used for a different request
+    Mismatch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    fn replay(self) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+                headers.insert(name, value);
+            }
+        }
+        headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredRow {
+    request_hash: String,
+    response_status: Option<i32>,
+    response_headers: Option<serde_json::Value>,
+    response_body: Option<Vec<u8>>,
+}
+
+/// Claim `key` for `route`, or report what an earlier request left
+pub async fn claim(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    key: &str,
+    route: &str,
+    request_hash: &str,
+) -> Result<Claim, sqlx::Error> {
+    // An expired key is taken over as if it were new, as is an unfinished
+    // claim on the same request once its lease has run out
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO idempotency_keys (tenant_id, idempotency_key, route, request_hash, expires_at, locked_until)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (tenant_id, idempotency_key, route) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash, response_status = NULL, response_headers = NULL,
+            response_body = NULL, created_at = NOW(), expires_at = EXCLUDED.expires_at,
+            locked_until = EXCLUDED.locked_until
+        WHERE idempotency_keys.expires_at <= NOW()
+           OR (idempotency_keys.response_status IS NULL
+               AND idempotency_keys.locked_until <= NOW()
+               AND idempotency_keys.request_hash = EXCLUDED.request_hash)
+        RETURNING tenant_id
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(key)
+    .bind(route)
+    .bind(request_hash)
+    .bind(Utc::now() + Duration::hours(IDEMPOTENCY_TTL_HOURS))
+    .bind(Utc::now() + Duration::seconds(CLAIM_LEASE_SECS))
+    .fetch_optional(pool)
+    .await?;
+    if claimed.is_some() {
+        return Ok(Claim::Claimed);
+    }
+
+    let existing: Option<StoredRow> = sqlx::query_as(
+        "SELECT request_hash, response_status, response_headers, response_body
+         FROM idempotency_keys WHERE tenant_id = $1 AND idempotency_key = $2 AND route = $3",
+    )
+    .bind(tenant_id)
+    .bind(key)
+    .bind(route)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match existing {
+        // Released between the two queries; the caller can retry
+        None => Claim::InProgress,
+        Some(row) if row.request_hash != request_hash => Claim::Mismatch,
+        Some(StoredRow { response_status: None, .. }) => Claim::InProgress,
+        Some(StoredRow { response_body: None, .. }) => Claim::CompletedUnstored,
+        Some(StoredRow { response_status: Some(status), response_headers, response_body: Some(body), .. }) => {
+            Claim::Completed(StoredResponse {
+                status: status as u16,
+                headers: response_headers.and_then(|h| serde_json::from_value(h).ok()).unwrap_or_default(),
+                body,
+            })
+        }
+    })
+}
+
+/// Store the response for a claimed key
+pub async fn complete(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    key: &str,
+    route: &str,
+    response: &StoredResponse,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE idempotency_keys SET response_status = $4, response_headers = $5, response_body = $6, locked_until = NULL
+         WHERE tenant_id = $1 AND idempotency_key = $2 AND route = $3",
+    )
+    .bind(tenant_id)
+    .bind(key)
+    .bind(route)
+    .bind(response.status as i32)
+    .bind(serde_json::to_value(&response.headers).unwrap_or_default())
+    .bind(&response.body)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark a claimed key done when its response can't be stored, so retries
+/// are refused rather than run again
+pub async fn complete_unstored(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    key: &str,
+    route: &str,
+    status: StatusCode,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE idempotency_keys SET response_status = $4, locked_until = NULL
+         WHERE tenant_id = $1 AND idempotency_key = $2 AND route = $3",
+    )
+    .bind(tenant_id)
+    .bind(key)
+    .bind(route)
+    .bind(status.as_u16() as i32)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Give up a claim so a retry runs the request again
+pub async fn release(pool: &PgPool, tenant_id: Uuid, key: &str, route: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE tenant_id = $1 AND idempotency_key = $2 AND route = $3")
+        .bind(tenant_id)
+        .bind(key)
+        .bind(route)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Forget expired keys every `period`, forever
+pub async fn run_purge(pool: PgPool, period: std::time::Duration) {
+    let mut ticker = tokio::time::interval(period);
+    loop {
+        ticker.tick().await;
+        match sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()").execute(&pool).await {
+            Ok(result) if result.rows_affected() > 0 => info!(removed = result.rows_affected(), "Purged expired idempotency keys"),
+            Ok(_) => {}
+            Err(e) => warn!(error = %e, "Failed to purge idempotency keys"),
+        }
+    }
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+/// Fingerprint of who sent the request and what it carried
+fn request_hash(caller: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(caller.as_bytes());
+    hasher.update([0]);
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn stored_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !UNSTORED_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn refuse(status: StatusCode, error: &str, code: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": error, "code": code }))).into_response()
+}
+
+/// Middleware replaying the stored response for a repeated `Idempotency-Key`
+pub async fn idempotency(State(state): State<Arc<AppState>>, request: Request<Body>, next: Next) -> Response {
+    if !is_mutating(request.method()) {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_HEADER) else {
+        return next.run(request).await;
+    };
+    let Some(tenant_id) = request.extensions().get::<ResolvedTenant>().map(|t| t.id) else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.trim().is_empty() && key.len() <= MAX_KEY_LENGTH => key.trim().to_string(),
+        _ => {
+            return refuse(
+                StatusCode::BAD_REQUEST,
+                "Idempotency-Key must be 1 to 255 visible ASCII characters",
+                "idempotency_key_invalid",
+            )
+        }
+    };
+
+    let path = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri.path().to_string(),
+        None => request.uri().path().to_string(),
+    };
+    let route = format!("{} {}", request.method(), path);
+    let caller = match (request.extensions().get::<AuthenticatedUser>(), request.extensions().get::<ApiKey>()) {
+        (Some(user), _) => format!("user:{}", user.id),
+        (None, Some(api_key)) => format!("api_key:{}", api_key.id),
+        (None, None) => String::new(),
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => {
+            return refuse(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body is too large to send with an Idempotency-Key",
+                "idempotency_body_too_large",
+            )
+        }
+    };
+    let hash = request_hash(&caller, &body);
+
+    match claim(&state.pool, tenant_id, &key, &route, &hash).await {
+        Ok(Claim::Claimed) => {}
+        Ok(Claim::Completed(stored)) => {
+            info!(tenant_id = %tenant_id, route = %route, "Replaying stored response for Idempotency-Key");
+            return stored.replay();
+        }
+        Ok(Claim::CompletedUnstored) => {
+            return refuse(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key already completed, but its response can't be replayed",
+                "idempotency_response_unavailable",
+            )
+        }
+        Ok(Claim::InProgress) => {
+            return refuse(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key is still being processed",
+                "idempotency_key_in_progress",
+            )
+        }
+        Ok(Claim::Mismatch) => {
+            return refuse(
+                StatusCode::CONFLICT,
+                "Idempotency-Key was already used for a different request",
+                "idempotency_key_reused",
+            )
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Idempotency key lookup failed");
+            return refuse(StatusCode::INTERNAL_SERVER_ERROR, "Database error", "database_error");
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Server errors aren't kept: the retry runs again
+    if response.status().is_server_error() {
+        if let Err(e) = release(&state.pool, tenant_id, &key, &route).await {
+            warn!(error = %e, "Failed to release idempotency key");
+        }
+        return response;
+    }
+
+    // Streams, and bodies that may not fit, pass through unread
+    let is_stream = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    let fits = response.body().size_hint().upper().is_some_and(|len| len <= MAX_BODY_BYTES as u64);
+    if is_stream || !fits {
+        info!(tenant_id = %tenant_id, route = %route, "Response not stored for Idempotency-Key");
+        if let Err(e) = complete_unstored(&state.pool, tenant_id, &key, &route, response.status()).await {
+            warn!(error = %e, "Failed to complete idempotency key");
+        }
+        return response;
+    }
+
+    let status = response.status();
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, route = %route, "Response could not be read for Idempotency-Key");
+            if let Err(e) = complete_unstored(&state.pool, tenant_id, &key, &route, status).await {
+                warn!(error = %e, "Failed to complete idempotency key");
+            }
+            return refuse(StatusCode::INTERNAL_SERVER_ERROR, "Response could not be read", "internal_error");
+        }
+    };
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        headers: stored_headers(&parts.headers),
+        body: body.to_vec(),
+    };
+    if let Err(e) = complete(&state.pool, tenant_id, &key, &route, &stored).await {
+        warn!(error = %e, "Failed to store response for Idempotency-Key");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Extension, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use test_support::{get_test_pool, TestTenant};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_request_hash_covers_caller_and_body() {
+        let hash = request_hash("user:1", br#"{"name":"Nadia"}"#);
+        assert_eq!(hash, request_hash("user:1", br#"{"name":"Nadia"}"#));
+        assert_ne!(hash, request_hash("user:1", br#"{"name":"Noor"}"#));
+        assert_ne!(hash, request_hash("user:2", br#"{"name":"Nadia"}"#));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_repeated_key_replays_the_first_response() {
+        let pool = get_test_pool().await;
+        let tenant_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Idempotency Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("idem-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Creates a record per call that actually runs
+        let created = Arc::new(AtomicU32::new(0));
+        let counter = created.clone();
+        let create = move |body: String| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (StatusCode::CREATED, Json(serde_json::json!({ "id": Uuid::new_v4(), "body": body })))
+            }
+        };
+
+        let state = Arc::new(AppState::new(pool.clone()));
+        let tenant = ResolvedTenant {
+            id: tenant_id,
+            name: "Idempotency Test".to_string(),
+            subdomain: format!("idem-{}", tenant_id.simple()),
+            settings: serde_json::json!({}),
+            status: "active".to_string(),
+            trial_ends_at: None,
+        };
+        let app = Router::new()
+            .route("/entities/contact", post(create))
+            .layer(axum::middleware::from_fn_with_state(state, idempotency))
+            .layer(Extension(tenant));
+        let send = |key: &str, body: &str| {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/entities/contact")
+                .header(IDEMPOTENCY_HEADER, key)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        async fn read(response: Response) -> (StatusCode, Option<HeaderValue>, Vec<u8>) {
+            let status = response.status();
+            let replayed = response.headers().get(REPLAYED_HEADER).cloned();
+            (status, replayed, to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+        }
+
+        let (status, replayed, first) = read(send("create-nadia", r#"{"name":"Nadia"}"#).await.unwrap()).await;
+        assert_eq!((status, replayed), (StatusCode::CREATED, None));
+
+        // The retry gets the same response without creating another record
+        let (status, replayed, again) = read(send("create-nadia", r#"{"name":"Nadia"}"#).await.unwrap()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(replayed, Some(HeaderValue::from_static("true")));
+        assert_eq!(again, first);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        // Same key, different body
+        let (status, _, body) = read(send("create-nadia", r#"{"name":"Noor"}"#).await.unwrap()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "idempotency_key_reused");
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        // A new key is a new request
+        let (status, _, _) = read(send("create-noor", r#"{"name":"Noor"}"#).await.unwrap()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        // Once expired the key starts over
+        sqlx::query("UPDATE idempotency_keys SET expires_at = NOW() WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (_, replayed, _) = read(send("create-nadia", r#"{"name":"Noor"}"#).await.unwrap()).await;
+        assert_eq!(replayed, None);
+        assert_eq!(created.load(Ordering::SeqCst), 3);
+
+        sqlx::query("DELETE FROM tenants WHERE id = $1").bind(tenant_id).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_unfinished_claim_is_taken_over_after_its_lease() {
+        let tenant = TestTenant::new("Idempotency Lease").await;
+        let route = "POST /entities/contact";
+        let hash = request_hash("user:1", br#"{"name":"Nadia"}"#);
+        let other = request_hash("user:1", br#"{"name":"Noor"}"#);
+
+        assert_eq!(claim(&tenant.pool, tenant.id, "lease", route, &hash).await.unwrap(), Claim::Claimed);
+        assert_eq!(claim(&tenant.pool, tenant.id, "lease", route, &hash).await.unwrap(), Claim::InProgress);
+
+        // The first request died without finishing
+        sqlx::query("UPDATE idempotency_keys SET locked_until = NOW() WHERE tenant_id = $1")
+            .bind(tenant.id)
+            .execute(&tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(claim(&tenant.pool, tenant.id, "lease", route, &other).await.unwrap(), Claim::Mismatch);
+        assert_eq!(claim(&tenant.pool, tenant.id, "lease", route, &hash).await.unwrap(), Claim::Claimed);
+        assert_eq!(claim(&tenant.pool, tenant.id, "lease", route, &hash).await.unwrap(), Claim::InProgress);
+
+        tenant.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_response_too_large_to_store_is_not_run_again() {
+        let tenant = TestTenant::new("Idempotency Large").await;
+        let exported = Arc::new(AtomicU32::new(0));
+        let counter = exported.clone();
+        let export = move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                vec![b'x'; MAX_BODY_BYTES + 1]
+            }
+        };
+
+        let state = Arc::new(AppState::new(tenant.pool.clone()));
+        let resolved = ResolvedTenant {
+            id: tenant.id,
+            name: "Idempotency Large".to_string(),
+            subdomain: tenant.subdomain.clone(),
+            settings: serde_json::json!({}),
+            status: "active".to_string(),
+            trial_ends_at: None,
+        };
+        let app = Router::new()
+            .route("/exports", post(export))
+            .layer(axum::middleware::from_fn_with_state(state, idempotency))
+            .layer(Extension(resolved));
+        let send = || {
+            let request = Request::post("/exports").header(IDEMPOTENCY_HEADER, "export-1").body(Body::empty()).unwrap();
+            app.clone().oneshot(request)
+        };
+
+        // The caller still gets the whole response
+        let response = send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap().len(), MAX_BODY_BYTES + 1);
+
+        let response = send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["code"], "idempotency_response_unavailable");
+        assert_eq!(exported.load(Ordering::SeqCst), 1);
+
+        tenant.cleanup().await;
+    }
+}
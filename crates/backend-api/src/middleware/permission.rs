@@ -14,10 +14,15 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use core_metadata::cache::{CachePolicy, LoadingCache};
+use core_metadata::MetadataError;
 use core_models::logic::{LogicOp, EvalContext};
+use core_models::UserRole;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sqlx::PgPool;
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// User context available in request extensions (set by auth layer)
@@ -159,6 +164,11 @@ pub fn is_admin_or_manager(user: &AuthenticatedUser) -> bool {
     has_role(user, "admin") || has_role(user, "manager")
 }
 
+/// Check if user may see PII values in audit history
+pub fn can_view_audit_pii(user: &AuthenticatedUser) -> bool {
+    has_role(user, "admin") || has_role(user, "audit_pii")
+}
+
 /// Check if user can access a specific resource with action
 pub fn can_access(
     user: &AuthenticatedUser,
@@ -204,6 +214,22 @@ pub fn can_access(
     check_permission(user, &permission, &ctx).allowed
 }
 
+/// Check if user can read records of an entity type: `can_access` must
+/// allow it and one of their personas must cover the type
+/// (`UserRole::accessible_entities`). Viewers and custom roles aren't limited
+/// by persona.
+pub fn can_read_entity(user: &AuthenticatedUser, entity_code: &str) -> bool {
+    if !can_access(user, entity_code, "read", None) {
+        return false;
+    }
+    user.role_strings().into_iter().any(|role| {
+        match serde_json::from_value::<UserRole>(Value::String(role)) {
+            Ok(UserRole::Viewer) | Err(_) => true,
+            Ok(persona) => persona.accessible_entities().iter().any(|e| *e == "*" || *e == entity_code),
+        }
+    })
+}
+
 /// Middleware to require a specific permission
 /// 
 /// Use with axum::middleware::from_fn:
@@ -270,10 +296,141 @@ pub fn get_user_permissions(user: &AuthenticatedUser) -> HashMap<String, bool> {
     permissions.insert("admin_panel:access".to_string(), is_admin(user));
     permissions.insert("workflows:execute".to_string(), is_admin_or_manager(user));
     permissions.insert("reports:export".to_string(), is_admin_or_manager(user));
+    permissions.insert("audit:view_pii".to_string(), can_view_audit_pii(user));
     
     permissions
 }
 
+/// Custom roles appear in `AuthenticatedUser::roles` under this prefix, so
+/// a tenant-named role can never pass a `has_role` check for a built-in
+/// privilege such as `admin` or `audit_pii`
+pub const CUSTOM_ROLE_PREFIX: &str = "custom:";
+
+/// How custom role `name` appears among a user's roles; `HasRole`
+/// conditions in custom permissions refer to it this way
+pub fn custom_role(name: &str) -> String {
+    format!("{}{}", CUSTOM_ROLE_PREFIX, name)
+}
+
+/// A user's custom roles and the permissions they grant
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoleGrants {
+    pub roles: Vec<String>,
+    pub permissions: Vec<PermissionDef>,
+}
+
+/// Caches each user's `RoleGrants`, keyed by tenant and user
+///
+/// Call [`PermissionCache::invalidate_user`] after assigning or unassigning
+/// a role and [`PermissionCache::invalidate_tenant`] after creating or
+/// deleting one; otherwise entries expire with the TTL. Invalidation only
+/// reaches this process: other API instances keep serving a revoked role
+/// until their entry expires, which the short `PERMISSION_CACHE_POLICY`
+/// bounds to seconds.
+#[derive(Clone)]
+pub struct PermissionCache {
+    cache: Arc<LoadingCache<(Uuid, Uuid), RoleGrants>>,
+}
+
+/// Kept short because invalidation doesn't cross instances
+pub const PERMISSION_CACHE_POLICY: CachePolicy = CachePolicy {
+    soft_ttl: std::time::Duration::from_secs(5),
+    hard_ttl: std::time::Duration::from_secs(15),
+};
+
+impl PermissionCache {
+    pub fn new() -> Self {
+        Self::with_policy(PERMISSION_CACHE_POLICY)
+    }
+
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self { cache: Arc::new(LoadingCache::new(policy)) }
+    }
+
+    /// The custom roles `user_id` holds in `tenant_id`
+    pub async fn grants(&self, pool: &PgPool, tenant_id: Uuid, user_id: Uuid) -> Result<RoleGrants, sqlx::Error> {
+        let pool = pool.clone();
+        self.cache
+            .get_or_load((tenant_id, user_id), move || {
+                let pool = pool.clone();
+                async move { load_role_grants(&pool, tenant_id, user_id).await.map_err(MetadataError::from) }
+            })
+            .await
+            .map_err(|e| match e {
+                MetadataError::Database(e) => e,
+                other => sqlx::Error::Protocol(other.to_string()),
+            })
+    }
+
+    pub fn invalidate_user(&self, tenant_id: Uuid, user_id: Uuid) {
+        self.cache.remove(&(tenant_id, user_id));
+    }
+
+    pub fn invalidate_tenant(&self, tenant_id: Uuid) {
+        self.cache.retain(|(tenant, _), _| *tenant != tenant_id);
+    }
+}
+
+impl Default for PermissionCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn load_role_grants(pool: &PgPool, tenant_id: Uuid, user_id: Uuid) -> Result<RoleGrants, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    sqlx::query("SELECT set_config('app.current_tenant', $1::text, false)")
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?;
+    let rows: Vec<(String, sqlx::types::Json<Vec<PermissionDef>>)> = sqlx::query_as(
+        r#"
+        SELECT r.name, r.permissions
+        FROM user_roles ur
+        JOIN roles r ON r.id = ur.role_id
+        WHERE ur.tenant_id = $1 AND ur.user_id = $2
+        ORDER BY r.name
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut grants = RoleGrants::default();
+    for (name, sqlx::types::Json(permissions)) in rows {
+        grants.roles.push(name);
+        grants.permissions.extend(permissions);
+    }
+    Ok(grants)
+}
+
+/// `user` with their custom roles added, as `custom:<name>`, to the roles
+/// checks see
+pub fn with_grants(mut user: AuthenticatedUser, grants: &RoleGrants) -> AuthenticatedUser {
+    let mut roles = user.role_strings();
+    for role in grants.roles.iter().map(|name| custom_role(name)) {
+        if !roles.contains(&role) {
+            roles.push(role);
+        }
+    }
+    user.roles = roles;
+    user
+}
+
+/// `get_user_permissions`, plus whatever the user's custom roles grant
+pub fn effective_permissions(user: &AuthenticatedUser, grants: &RoleGrants) -> HashMap<String, bool> {
+    let user = with_grants(user.clone(), grants);
+    let mut permissions = get_user_permissions(&user);
+    let ctx = PermissionContext::new();
+    for permission in &grants.permissions {
+        if check_permission(&user, permission, &ctx).allowed {
+            permissions.insert(format!("{}:{}", permission.resource, permission.action), true);
+        }
+    }
+    permissions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,6 +462,53 @@ mod tests {
         // Viewers should not be able to delete (default rules)
     }
     
+    #[test]
+    fn test_read_entity_limited_by_persona() {
+        assert!(can_read_entity(&create_test_user("manager"), "property"));
+        assert!(can_read_entity(&create_test_user("member"), "deal"));
+        assert!(!can_read_entity(&create_test_user("member"), "property"));
+        assert!(can_read_entity(&create_test_user("viewer"), "property"));
+        assert!(can_read_entity(&create_test_user("custom_role"), "property"));
+    }
+    
+    #[test]
+    fn test_custom_role_grants() {
+        let member = create_test_user("member");
+        let grants = RoleGrants {
+            roles: vec!["auditor".to_string()],
+            permissions: vec![PermissionDef {
+                id: Uuid::new_v4(),
+                name: "export_reports".to_string(),
+                resource: "reports".to_string(),
+                action: "export".to_string(),
+                condition: LogicOp::HasRole { role: "custom:auditor".to_string() },
+                description: None,
+            }],
+        };
+
+        let user = with_grants(member.clone(), &grants);
+        assert_eq!(user.role_strings(), vec!["member", "custom:auditor"]);
+
+        let permissions = effective_permissions(&member, &grants);
+        assert_eq!(permissions.get("reports:export"), Some(&true));
+        assert_eq!(get_user_permissions(&member).get("reports:export"), Some(&false));
+    }
+    
+    #[test]
+    fn test_custom_roles_named_like_built_in_privileges_grant_nothing() {
+        let member = create_test_user("member");
+        let grants = RoleGrants {
+            roles: vec!["audit_pii".to_string(), "admin".to_string(), "manager".to_string()],
+            permissions: Vec::new(),
+        };
+
+        let user = with_grants(member.clone(), &grants);
+        assert!(!can_view_audit_pii(&user));
+        assert!(!is_admin(&user));
+        assert!(!is_admin_or_manager(&user));
+        assert_eq!(effective_permissions(&member, &grants), get_user_permissions(&member));
+    }
+
     #[test]
     fn test_permission_check_with_logic_op() {
         let user = create_test_user("manager");
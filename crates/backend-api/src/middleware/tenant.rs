@@ -3,8 +3,11 @@
 //! Resolves tenant from subdomain for public endpoints.
 //! Supports multiple resolution strategies:
 //! 1. X-Tenant-Slug header (for API testing)
-//! 2. Host header (production subdomain)
+//! 2. Host header (custom domain, then production subdomain)
 //! 3. tenant_slug query parameter (dev convenience)
+//!
+//! Host lookups are cached; requests from an unknown host get a 404 rather
+//! than falling through to a default tenant.
 
 use axum::{
     body::Body,
@@ -14,6 +17,9 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use core_metadata::cache::{CachePolicy, LoadingCache};
+use core_metadata::MetadataError;
+use core_models::{region_from_locale, DEFAULT_PHONE_REGION};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 use std::sync::Arc;
@@ -28,6 +34,9 @@ pub struct ResolvedTenant {
     pub name: String,
     pub subdomain: String,
     pub settings: serde_json::Value,
+    /// `active`, `trial` or `suspended`; enforced by `tenant_status`
+    pub status: String,
+    pub trial_ends_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl ResolvedTenant {
@@ -44,6 +53,29 @@ impl ResolvedTenant {
             email: self.settings.get("email").and_then(|v| v.as_str()).map(String::from),
         }
     }
+
+    /// Country national phone numbers are read in
+    pub fn phone_region(&self) -> String {
+        phone_region(&self.settings)
+    }
+}
+
+/// `settings.country`, else the region of `settings.locale`, else the default
+pub fn phone_region(settings: &serde_json::Value) -> String {
+    let setting = |key: &str| settings.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+    setting("country")
+        .map(str::to_uppercase)
+        .or_else(|| setting("locale").and_then(region_from_locale))
+        .unwrap_or_else(|| DEFAULT_PHONE_REGION.to_string())
+}
+
+/// Phone region of a tenant known only by id
+pub async fn tenant_phone_region(pool: &PgPool, tenant_id: Uuid) -> Result<String, sqlx::Error> {
+    let settings: Option<serde_json::Value> = sqlx::query_scalar("SELECT settings FROM tenants WHERE id = $1")
+        .bind(tenant_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(phone_region(&settings.unwrap_or_default()))
 }
 
 /// Public-safe tenant branding information
@@ -65,8 +97,62 @@ pub struct TenantQuery {
     pub tenant_id: Option<Uuid>,
 }
 
+/// Host → tenant mappings, shared by all requests
+///
+/// Only hits are cached, so a tenant created or given a custom domain is
+/// reachable straight away. Call [`TenantHostCache::invalidate_tenant`] after
+/// changing a tenant's settings; otherwise entries expire with the TTL.
+#[derive(Clone)]
+pub struct TenantHostCache {
+    cache: Arc<LoadingCache<String, Option<ResolvedTenant>>>,
+}
+
+impl TenantHostCache {
+    pub fn new() -> Self {
+        Self::with_policy(CachePolicy::default())
+    }
+
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self { cache: Arc::new(LoadingCache::new(policy)) }
+    }
+
+    /// Tenant served at `host`: by custom domain first, then by subdomain
+    pub async fn resolve(&self, pool: &PgPool, host: &str) -> Result<Option<ResolvedTenant>, sqlx::Error> {
+        let host = normalize_host(host);
+        let pool = pool.clone();
+        let key = host.clone();
+        let tenant = self
+            .cache
+            .get_or_load(host.clone(), move || {
+                let (pool, host) = (pool.clone(), key.clone());
+                async move { resolve_tenant_by_host(&pool, &host).await.map_err(MetadataError::from) }
+            })
+            .await
+            .map_err(|e| match e {
+                MetadataError::Database(e) => e,
+                other => sqlx::Error::Protocol(other.to_string()),
+            })?;
+
+        if tenant.is_none() {
+            self.cache.remove(&host);
+        }
+        Ok(tenant)
+    }
+
+    /// Drop every cached host of `tenant_id`
+    pub fn invalidate_tenant(&self, tenant_id: Uuid) {
+        self.cache.retain(|_, tenant| tenant.as_ref().is_none_or(|t| t.id != tenant_id));
+    }
+}
+
+impl Default for TenantHostCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Middleware to resolve tenant from various sources
-/// Priority: X-Tenant-Slug header > Host subdomain > tenant_slug query param
+/// Priority: X-Tenant-Slug header > Host custom domain > Host subdomain > tenant_slug query param
 pub async fn resolve_tenant(
     State(state): State<Arc<AppState>>,
     Host(host): Host,
@@ -82,8 +168,31 @@ pub async fn resolve_tenant(
         .and_then(|v| v.to_str().ok())
         .map(String::from);
     
-    // 2. Fall back to Host header subdomain
-    let slug = slug.or_else(|| extract_subdomain(&host));
+    // 2. Fall back to the Host header: custom domain, then subdomain
+    if slug.is_none() && !is_local_host(&host) && !is_platform_host(&host) {
+        return match state.tenant_hosts.resolve(&state.pool, &host).await {
+            Ok(Some(tenant)) => {
+                request.extensions_mut().insert(tenant);
+                next.run(request).await
+            }
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "error": "No tenant is served at this host",
+                    "host": normalize_host(&host)
+                }))
+            ).into_response(),
+            Err(e) => {
+                tracing::error!("Database error resolving tenant by host: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "error": "Failed to resolve tenant"
+                    }))
+                ).into_response()
+            }
+        };
+    }
     
     // 3. Fall back to query parameter (slug)
     let slug = slug.or(query.tenant_slug);
@@ -120,7 +229,16 @@ pub async fn resolve_tenant(
 
     let slug = match slug {
         Some(s) if !s.is_empty() => s,
-        _ => "demo".to_string(), // Fallback to demo for local development & unidentified requests
+        // Fallback to demo for local development only
+        _ if is_local_host(&host) => "demo".to_string(),
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "error": "Tenant not specified"
+                }))
+            ).into_response();
+        }
     };
     
     // Query database for tenant
@@ -151,13 +269,35 @@ pub async fn resolve_tenant(
     }
 }
 
+/// Subdomains of the platform itself rather than of a tenant
+const PLATFORM_SUBDOMAINS: [&str; 3] = ["www", "api", "app"];
+
+/// Lowercased host without port or trailing dot
+fn normalize_host(host: &str) -> String {
+    let host = host.split(':').next().unwrap_or(host);
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// localhost or an IP address, i.e. local development
+fn is_local_host(host: &str) -> bool {
+    let host = normalize_host(host);
+    host == "localhost" || host.parse::<std::net::IpAddr>().is_ok()
+}
+
+/// The platform's own hosts (e.g. "app.jirsi.com"), where the tenant comes
+/// from the header or query instead
+fn is_platform_host(host: &str) -> bool {
+    let host = normalize_host(host);
+    let parts: Vec<&str> = host.split('.').collect();
+    parts.len() >= 3 && PLATFORM_SUBDOMAINS.contains(&parts[0])
+}
+
 /// Extract subdomain from host (e.g., "acme" from "acme.jirsi.com")
 fn extract_subdomain(host: &str) -> Option<String> {
-    // Remove port if present
-    let host = host.split(':').next().unwrap_or(host);
+    let host = normalize_host(host);
     
     // Skip if localhost or IP address
-    if host == "localhost" || host.parse::<std::net::IpAddr>().is_ok() {
+    if is_local_host(&host) {
         return None;
     }
     
@@ -168,7 +308,7 @@ fn extract_subdomain(host: &str) -> Option<String> {
     if parts.len() >= 3 {
         let subdomain = parts[0];
         // Skip common non-tenant subdomains
-        if subdomain != "www" && subdomain != "api" && subdomain != "app" {
+        if !PLATFORM_SUBDOMAINS.contains(&subdomain) {
             return Some(subdomain.to_string());
         }
     }
@@ -176,13 +316,35 @@ fn extract_subdomain(host: &str) -> Option<String> {
     None
 }
 
+/// Query database for tenant by custom domain, falling back to subdomain
+async fn resolve_tenant_by_host(pool: &PgPool, host: &str) -> Result<Option<ResolvedTenant>, sqlx::Error> {
+    let by_domain = sqlx::query_as::<_, ResolvedTenant>(
+        r#"
+        SELECT id, name, subdomain, settings, status, trial_ends_at
+        FROM tenants
+        WHERE LOWER(custom_domain) = $1 AND status IN ('active', 'trial', 'suspended')
+        "#
+    )
+    .bind(host)
+    .fetch_optional(pool)
+    .await?;
+    if by_domain.is_some() {
+        return Ok(by_domain);
+    }
+    
+    match extract_subdomain(host) {
+        Some(subdomain) => resolve_tenant_from_db(pool, &subdomain).await,
+        None => Ok(None),
+    }
+}
+
 /// Query database for tenant by subdomain
 async fn resolve_tenant_from_db(pool: &PgPool, subdomain: &str) -> Result<Option<ResolvedTenant>, sqlx::Error> {
     let result = sqlx::query_as::<_, ResolvedTenant>(
         r#"
-        SELECT id, name, subdomain, settings
+        SELECT id, name, subdomain, settings, status, trial_ends_at
         FROM tenants
-        WHERE subdomain = $1 AND status IN ('active', 'trial')
+        WHERE subdomain = $1 AND status IN ('active', 'trial', 'suspended')
         "#
     )
     .bind(subdomain)
@@ -196,9 +358,9 @@ async fn resolve_tenant_from_db(pool: &PgPool, subdomain: &str) -> Result<Option
 async fn resolve_tenant_by_id(pool: &PgPool, id: Uuid) -> Result<Option<ResolvedTenant>, sqlx::Error> {
     let result = sqlx::query_as::<_, ResolvedTenant>(
         r#"
-        SELECT id, name, subdomain, settings
+        SELECT id, name, subdomain, settings, status, trial_ends_at
         FROM tenants
-        WHERE id = $1 AND status IN ('active', 'trial')
+        WHERE id = $1 AND status IN ('active', 'trial', 'suspended')
         "#
     )
     .bind(id)
@@ -211,6 +373,7 @@ async fn resolve_tenant_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Resolved
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_support::TestTenant;
     
     #[test]
     fn test_extract_subdomain() {
@@ -221,6 +384,89 @@ mod tests {
         assert_eq!(extract_subdomain("localhost:3000"), None);
         assert_eq!(extract_subdomain("127.0.0.1:3000"), None);
         assert_eq!(extract_subdomain("jirsi.com"), None);
+        assert_eq!(extract_subdomain("Acme.Jirsi.com."), Some("acme".to_string()));
+    }
+
+    use axum::{routing::get, Extension, Router};
+    use tower::ServiceExt;
+
+    struct Fixture {
+        tenant: TestTenant,
+        state: Arc<AppState>,
+        custom_domain: String,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Host Test").await;
+            let custom_domain = format!("crm.{}.example", tenant.subdomain);
+            sqlx::query("UPDATE tenants SET custom_domain = $1 WHERE id = $2")
+                .bind(&custom_domain)
+                .bind(tenant.id)
+                .execute(&tenant.pool)
+                .await
+                .unwrap();
+            let state = Arc::new(AppState::new(tenant.pool.clone()));
+            Self { tenant, state, custom_domain }
+        }
+
+        /// Status and resolved tenant id for a request to `host`
+        async fn get(&self, host: &str) -> (StatusCode, Option<Uuid>) {
+            let app = Router::new()
+                .route("/", get(|Extension(tenant): Extension<ResolvedTenant>| async move { tenant.id.to_string() }))
+                .layer(axum::middleware::from_fn_with_state(self.state.clone(), resolve_tenant));
+            let request = Request::get("/").header("Host", host).body(Body::empty()).unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, Uuid::parse_str(std::str::from_utf8(&body).unwrap()).ok())
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_resolves_custom_domain() {
+        let fx = Fixture::new().await;
+        assert_eq!(fx.get(&fx.custom_domain).await, (StatusCode::OK, Some(fx.tenant.id)));
+        assert_eq!(fx.get(&format!("{}:443", fx.custom_domain.to_uppercase())).await, (StatusCode::OK, Some(fx.tenant.id)));
+
+        // Cached until the tenant's settings change
+        let cached = fx.state.tenant_hosts.resolve(&fx.state.pool, &fx.custom_domain).await.unwrap();
+        assert_eq!(cached.map(|t| t.id), Some(fx.tenant.id));
+        sqlx::query("UPDATE tenants SET custom_domain = NULL WHERE id = $1")
+            .bind(fx.tenant.id)
+            .execute(&fx.state.pool)
+            .await
+            .unwrap();
+        assert_eq!(fx.get(&fx.custom_domain).await.0, StatusCode::OK);
+        fx.state.tenant_hosts.invalidate_tenant(fx.tenant.id);
+        assert_eq!(fx.get(&fx.custom_domain).await, (StatusCode::NOT_FOUND, None));
+
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_falls_back_to_subdomain() {
+        let fx = Fixture::new().await;
+        let host = format!("{}.jirsi.com", fx.tenant.subdomain);
+        assert_eq!(fx.get(&host).await, (StatusCode::OK, Some(fx.tenant.id)));
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_unknown_host_is_rejected() {
+        let fx = Fixture::new().await;
+        // Neither a custom domain nor a tenant subdomain; no default tenant
+        assert_eq!(fx.get("shop.unknown-brand.example").await, (StatusCode::NOT_FOUND, None));
+        assert_eq!(fx.get("unknown-brand.example").await, (StatusCode::NOT_FOUND, None));
+        assert_eq!(fx.get("app.jirsi.com").await, (StatusCode::NOT_FOUND, None));
+        fx.cleanup().await;
     }
 }
 
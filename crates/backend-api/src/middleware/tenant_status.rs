@@ -0,0 +1,167 @@
+//! Tenant Status Enforcement
+//!
+//! Runs after `resolve_tenant` on API routes. Suspended tenants, and trial
+//! tenants past `trial_ends_at`, are refused with a machine-readable code,
+//! except on the routes they need to sign in and reactivate. Public and
+//! webhook routes don't use this layer.
+
+use axum::{
+    body::Body,
+    http::{Method, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use core_auth::AuthError;
+
+use super::tenant::ResolvedTenant;
+
+/// Routes (below `/api/v1`) an inactive tenant can still reach: signing in
+/// and out, resetting a password, and the branding of the sign-in page
+const REACTIVATION_ROUTES: [(Method, &str); 7] = [
+    (Method::POST, "/auth/login"),
+    (Method::POST, "/auth/logout"),
+    (Method::POST, "/auth/token"),
+    (Method::GET, "/auth/me"),
+    (Method::POST, "/auth/password-reset"),
+    (Method::POST, "/auth/password-reset/confirm"),
+    (Method::GET, "/tenant/branding"),
+];
+
+/// Why a tenant's API access is blocked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantBlock {
+    Suspended,
+    TrialExpired,
+}
+
+/// 402 when paying fixes it, 403 otherwise
+impl From<TenantBlock> for AuthError {
+    fn from(block: TenantBlock) -> Self {
+        match block {
+            TenantBlock::Suspended => AuthError::TenantSuspended,
+            TenantBlock::TrialExpired => AuthError::TrialExpired,
+        }
+    }
+}
+
+/// Whether `tenant` may use the API at `now`
+pub fn tenant_block(tenant: &ResolvedTenant, now: DateTime<Utc>) -> Option<TenantBlock> {
+    match tenant.status.as_str() {
+        "suspended" => Some(TenantBlock::Suspended),
+        "trial" | "trialing" if tenant.trial_ends_at.is_some_and(|ends| ends <= now) => Some(TenantBlock::TrialExpired),
+        _ => None,
+    }
+}
+
+/// Whether `method` on `path` is reachable while the tenant is blocked
+fn is_reactivation_route(method: &Method, path: &str) -> bool {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    REACTIVATION_ROUTES.iter().any(|(m, p)| m == method && *p == path)
+}
+
+/// Middleware refusing API access to suspended and trial-expired tenants
+pub async fn enforce_tenant_status(request: Request<Body>, next: Next) -> Response {
+    let block = request
+        .extensions()
+        .get::<ResolvedTenant>()
+        .and_then(|tenant| tenant_block(tenant, Utc::now()));
+
+    match block {
+        Some(block) if !is_reactivation_route(request.method(), request.uri().path()) => {
+            AuthError::from(block).into_response()
+        }
+        _ => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::api_router;
+    use crate::state::AppState;
+    use axum::{http::StatusCode, Router};
+    use chrono::Duration;
+    use std::sync::Arc;
+    use test_support::TestTenant;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    fn tenant(status: &str, trial_ends_at: Option<DateTime<Utc>>) -> ResolvedTenant {
+        ResolvedTenant {
+            id: Uuid::new_v4(),
+            name: "Acme".to_string(),
+            subdomain: "acme".to_string(),
+            settings: serde_json::json!({}),
+            status: status.to_string(),
+            trial_ends_at,
+        }
+    }
+
+    #[test]
+    fn test_tenant_block() {
+        let now = Utc::now();
+        assert_eq!(tenant_block(&tenant("active", None), now), None);
+        assert_eq!(tenant_block(&tenant("suspended", None), now), Some(TenantBlock::Suspended));
+        assert_eq!(tenant_block(&tenant("trial", None), now), None);
+        assert_eq!(tenant_block(&tenant("trial", Some(now + Duration::days(3))), now), None);
+        assert_eq!(tenant_block(&tenant("trial", Some(now - Duration::days(1))), now), Some(TenantBlock::TrialExpired));
+    }
+
+    #[test]
+    fn test_reactivation_routes() {
+        assert!(is_reactivation_route(&Method::POST, "/api/v1/auth/login"));
+        assert!(is_reactivation_route(&Method::GET, "/api/v1/tenant/branding"));
+        assert!(!is_reactivation_route(&Method::GET, "/api/v1/auth/sessions"));
+        assert!(!is_reactivation_route(&Method::PATCH, "/api/v1/tenant/settings"));
+        assert!(!is_reactivation_route(&Method::DELETE, "/api/v1/auth/login"));
+        assert!(!is_reactivation_route(&Method::GET, "/api/v1/records/contact"));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_suspended_tenant_blocked_except_reactivation() {
+        let tenant = TestTenant::new("Suspended Test").await;
+        let set_status = |status: &'static str| {
+            sqlx::query("UPDATE tenants SET status = $2 WHERE id = $1").bind(tenant.id).bind(status).execute(&tenant.pool)
+        };
+        set_status("suspended").await.unwrap();
+
+        let state = Arc::new(AppState::new(tenant.pool.clone()));
+        let app = Router::new().nest("/api/v1", api_router(state.clone())).with_state(state);
+        let send = |method: &str, path: &str, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(path)
+                .header("Host", "localhost")
+                .header("X-Tenant-Slug", &tenant.subdomain)
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = send("GET", "/api/v1/records/contact", serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, serde_json::json!({ "error": "Tenant is suspended", "code": "tenant_suspended", "status": 403 }));
+        // Only the listed methods of a reactivation route are let through
+        let response = send("PATCH", "/api/v1/tenant/settings", serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let branding = format!("/api/v1/tenant/branding?tenant_slug={}", tenant.subdomain);
+        let response = send("GET", &branding, serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let reset = serde_json::json!({ "email": "agent@example.com", "tenant_subdomain": tenant.subdomain });
+        let response = send("POST", "/api/v1/auth/password-reset", reset).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        // Once reactivated, the API opens up again; the tenant has no contacts
+        set_status("active").await.unwrap();
+        let response = send("GET", "/api/v1/records/contact", serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        tenant.cleanup().await;
+    }
+}
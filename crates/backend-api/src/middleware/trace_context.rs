@@ -0,0 +1,26 @@
+//! Trace Context
+//!
+//! Correlation id of the operation being handled (the request id for API
+//! calls), held in a task-local so work started on its behalf - outbox
+//! events, graph runs, jobs - can carry it without threading it through
+//! every signature. `tokio::spawn` doesn't inherit task-locals; capture the
+//! id with [`current_trace_id`] and re-enter it with [`with_trace_id`].
+
+use std::future::Future;
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Trace id of the current task, if it runs inside one
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run `fut` with `trace_id` as the current trace id
+pub async fn with_trace_id<F: Future>(trace_id: Option<String>, fut: F) -> F::Output {
+    match trace_id {
+        Some(id) => TRACE_ID.scope(id, fut).await,
+        None => fut.await,
+    }
+}
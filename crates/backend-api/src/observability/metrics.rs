@@ -14,6 +14,10 @@ use std::time::Instant;
 use uuid::Uuid;
 use tokio::sync::RwLock;
 use std::collections::HashMap;
+use sqlx::PgPool;
+
+use super::JobBacklogSource;
+use crate::websocket::WsManager;
 
 /// Application metrics
 pub struct AppMetrics {
@@ -38,6 +42,7 @@ pub struct AppMetrics {
     pub jobs_queued: AtomicU64,
     pub jobs_processed: AtomicU64,
     pub jobs_failed: AtomicU64,
+    pub jobs_dead_letter: AtomicU64,
     
     // Event store metrics
     pub events_appended: AtomicU64,
@@ -105,6 +110,7 @@ impl AppMetrics {
             jobs_queued: AtomicU64::new(0),
             jobs_processed: AtomicU64::new(0),
             jobs_failed: AtomicU64::new(0),
+            jobs_dead_letter: AtomicU64::new(0),
             events_appended: AtomicU64::new(0),
             events_replayed: AtomicU64::new(0),
             snapshots_created: AtomicU64::new(0),
@@ -184,6 +190,12 @@ impl AppMetrics {
         self.sync_latency_ms.fetch_add(latency_ms, Ordering::Relaxed);
     }
     
+    /// Record dead letter queue size from `JobQueue::dead_letter_counts`
+    pub fn record_dead_letter_counts(&self, counts: &HashMap<String, i64>) {
+        let total: i64 = counts.values().sum();
+        self.jobs_dead_letter.store(total.max(0) as u64, Ordering::Relaxed);
+    }
+    
     // ============ Calculated Metrics ============
     
     fn calculate_cache_hit_ratio(&self) -> f64 {
@@ -271,6 +283,10 @@ jobs_processed_total {}
 # TYPE jobs_failed_total counter
 jobs_failed_total {}
 
+# HELP jobs_dead_letter Jobs waiting in the dead letter queue
+# TYPE jobs_dead_letter gauge
+jobs_dead_letter {}
+
 # HELP events_appended_total Total events appended to event store
 # TYPE events_appended_total counter
 events_appended_total {}
@@ -328,6 +344,7 @@ ws_messages_total {}
             self.jobs_queued.load(Ordering::Relaxed),
             self.jobs_processed.load(Ordering::Relaxed),
             self.jobs_failed.load(Ordering::Relaxed),
+            self.jobs_dead_letter.load(Ordering::Relaxed),
             self.events_appended.load(Ordering::Relaxed),
             self.events_replayed.load(Ordering::Relaxed),
             self.snapshots_created.load(Ordering::Relaxed),
@@ -350,17 +367,70 @@ impl Default for AppMetrics {
     }
 }
 
-/// Metrics handler
-pub async fn metrics_handler(
-    // Extract metrics from state if needed
-) -> impl IntoResponse {
-    // TODO: Get actual metrics from app state
-    let metrics = AppMetrics::new();
+/// Gathers everything `/metrics` reports: the app counters, the gateway's
+/// per-route request histograms, and gauges sampled at scrape time
+pub struct MetricsExporter {
+    pub app: Arc<AppMetrics>,
+    pub gateway: Arc<crate::gateway::Metrics>,
+    pub ws: Option<Arc<WsManager>>,
+    pub pool: PgPool,
+    backlog: Arc<dyn JobBacklogSource>,
+}
+
+impl MetricsExporter {
+    pub fn new(app: Arc<AppMetrics>, gateway: Arc<crate::gateway::Metrics>, pool: PgPool) -> Self {
+        Self { app, gateway, ws: None, backlog: Arc::new(pool.clone()), pool }
+    }
+    
+    pub fn with_ws_manager(mut self, ws: Arc<WsManager>) -> Self {
+        self.ws = Some(ws);
+        self
+    }
     
+    /// Override where the job queue depth comes from
+    pub fn with_backlog_source(mut self, backlog: Arc<dyn JobBacklogSource>) -> Self {
+        self.backlog = backlog;
+        self
+    }
+    
+    /// Refresh the sampled gauges and render the full exposition
+    pub async fn render(&self) -> String {
+        if let Some(ws) = &self.ws {
+            self.app.ws_connections_active.store(ws.connection_count().await as u64, Ordering::Relaxed);
+        }
+        let active = self.pool.size().saturating_sub(self.pool.num_idle() as u32);
+        self.app.db_connections_active.store(active as u64, Ordering::Relaxed);
+        match self.backlog.job_backlog().await {
+            Ok(backlog) => self.app.jobs_queued.store(backlog.pending.max(0) as u64, Ordering::Relaxed),
+            Err(e) => tracing::warn!(error = %e, "Could not read job queue depth for metrics"),
+        }
+        
+        let mut out = self.app.export_prometheus();
+        out.push('\n');
+        out.push_str(&self.gateway.export_prometheus());
+        out.push('\n');
+        out.push_str(&format!(
+            r#"# HELP db_pool_max_connections Configured database pool size
+# TYPE db_pool_max_connections gauge
+db_pool_max_connections {}
+
+# HELP db_pool_idle_connections Idle database pool connections
+# TYPE db_pool_idle_connections gauge
+db_pool_idle_connections {}
+"#,
+            self.pool.options().get_max_connections(),
+            self.pool.num_idle(),
+        ));
+        out
+    }
+}
+
+/// Metrics handler
+pub async fn metrics_handler(State(exporter): State<Arc<MetricsExporter>>) -> impl IntoResponse {
     (
         StatusCode::OK,
         [("Content-Type", "text/plain; version=0.0.4")],
-        metrics.export_prometheus(),
+        exporter.render().await,
     )
 }
 
@@ -438,3 +508,102 @@ impl Drop for RequestTimer {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::JobBacklog;
+    use regex::Regex;
+    use std::collections::HashSet;
+
+    struct FixedBacklog(i64);
+
+    #[async_trait::async_trait]
+    impl JobBacklogSource for FixedBacklog {
+        async fn job_backlog(&self) -> Result<JobBacklog, sqlx::Error> {
+            Ok(JobBacklog { pending: self.0, oldest_pending_age_seconds: 0 })
+        }
+    }
+
+    /// Check the text exposition format: every sample belongs to a family
+    /// declared by `# TYPE`, and histogram buckets are cumulative with
+    /// `+Inf` equal to `_count`
+    fn validate_exposition(text: &str) -> HashSet<String> {
+        let sample = Regex::new(
+            r#"^([a-zA-Z_:][a-zA-Z0-9_:]*)(\{([a-zA-Z_][a-zA-Z0-9_]*="(?:[^"\\]|\\.)*"(?:,[a-zA-Z_][a-zA-Z0-9_]*="(?:[^"\\]|\\.)*")*)?\})? (-?[0-9.eE+-]+|\+Inf|NaN)$"#,
+        )
+        .unwrap();
+        let mut types: HashMap<String, String> = HashMap::new();
+        let mut buckets: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        let mut counts: HashMap<String, f64> = HashMap::new();
+
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').expect("TYPE line has a kind");
+                assert!(["counter", "gauge", "histogram"].contains(&kind), "bad type in {line}");
+                assert!(types.insert(name.to_string(), kind.to_string()).is_none(), "duplicate TYPE for {name}");
+                continue;
+            }
+            if line.starts_with("# HELP ") {
+                continue;
+            }
+            let caps = sample.captures(line).unwrap_or_else(|| panic!("malformed sample: {line}"));
+            let name = &caps[1];
+            let value: f64 = caps[4].parse().unwrap();
+            let family = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix).filter(|base| types.get(*base).map(String::as_str) == Some("histogram")))
+                .unwrap_or(name);
+            assert!(types.contains_key(family), "sample before TYPE: {line}");
+
+            let labels = caps.get(3).map_or("", |m| m.as_str());
+            if name.ends_with("_bucket") {
+                let series = labels.split(",le=").next().unwrap().to_string();
+                buckets.entry(format!("{family}{{{series}}}")).or_default().push((labels.to_string(), value));
+            } else if name.ends_with("_count") && family != name {
+                counts.insert(format!("{family}{{{labels}}}"), value);
+            }
+        }
+
+        for (series, values) in &buckets {
+            assert!(values.windows(2).all(|w| w[0].1 <= w[1].1), "buckets not cumulative: {series}");
+            let (last_labels, last) = values.last().unwrap();
+            assert!(last_labels.ends_with(r#"le="+Inf""#), "missing +Inf bucket: {series}");
+            assert_eq!(counts.get(series), Some(last), "+Inf bucket != _count for {series}");
+        }
+        types.into_keys().collect()
+    }
+
+    #[tokio::test]
+    async fn test_metrics_are_valid_prometheus_exposition() {
+        let gateway = Arc::new(crate::gateway::Metrics::new());
+        gateway.record_request("/api/v1/records/:entity_code", 200, 12);
+        gateway.record_request("/api/v1/records/:entity_code", 200, 700);
+        gateway.record_request("/api/v1/records/:entity_code", 404, 3);
+        gateway.record_request("/weird\"route", 500, 9000);
+
+        let pool = PgPool::connect_lazy("postgres://localhost/metrics_test").unwrap();
+        let exporter = MetricsExporter::new(Arc::new(AppMetrics::new()), gateway, pool)
+            .with_ws_manager(Arc::new(WsManager::new()))
+            .with_backlog_source(Arc::new(FixedBacklog(42)));
+
+        let text = exporter.render().await;
+        let families = validate_exposition(&text);
+
+        for name in [
+            "http_route_requests_total",
+            "http_route_request_duration_seconds",
+            "ws_connections_active",
+            "jobs_queued",
+            "db_connections_active",
+            "db_pool_max_connections",
+            "db_pool_idle_connections",
+        ] {
+            assert!(families.contains(name), "missing {name}");
+        }
+        assert!(text.contains(r#"http_route_requests_total{route="/api/v1/records/:entity_code",status="200"} 2"#));
+        assert!(text.contains(r#"route="/weird\"route""#));
+        assert!(text.contains("jobs_queued 42"));
+        assert!(!text.contains("tenant"));
+    }
+}
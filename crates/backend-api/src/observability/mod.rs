@@ -8,7 +8,9 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use chrono::Utc;
+use sqlx::PgPool;
 
 pub mod metrics;
 pub mod tracing_config;
@@ -28,11 +30,14 @@ pub struct HealthResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthChecks {
     pub database: CheckStatus,
-    pub redis: CheckStatus,
+    /// `None` when Redis isn't configured
+    pub redis: Option<CheckStatus>,
     pub job_queue: CheckStatus,
+    /// `None` when the jobs table couldn't be read
+    pub job_backlog: Option<JobBacklog>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CheckStatus {
     Healthy,
@@ -40,6 +45,99 @@ pub enum CheckStatus {
     Unhealthy,
 }
 
+/// Jobs that are due but not yet picked up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobBacklog {
+    pub pending: i64,
+    /// How long the oldest due job has been waiting
+    pub oldest_pending_age_seconds: i64,
+}
+
+/// Backlog above which the job queue reports `Degraded`
+#[derive(Debug, Clone)]
+pub struct JobQueueThresholds {
+    pub max_pending: i64,
+    pub max_oldest_pending_age_seconds: i64,
+}
+
+impl Default for JobQueueThresholds {
+    fn default() -> Self {
+        Self {
+            max_pending: 1000,
+            max_oldest_pending_age_seconds: 300,
+        }
+    }
+}
+
+/// Where the job queue health check reads the backlog from
+#[async_trait::async_trait]
+pub trait JobBacklogSource: Send + Sync {
+    async fn job_backlog(&self) -> Result<JobBacklog, sqlx::Error>;
+}
+
+#[async_trait::async_trait]
+impl JobBacklogSource for PgPool {
+    async fn job_backlog(&self) -> Result<JobBacklog, sqlx::Error> {
+        let (pending, oldest_pending_age_seconds): (i64, Option<i64>) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   EXTRACT(EPOCH FROM NOW() - MIN(COALESCE(run_at, created_at)))::BIGINT
+            FROM jobs
+            WHERE status IN ('pending', 'retrying')
+              AND (run_at IS NULL OR run_at <= NOW())
+            "#,
+        )
+        .fetch_one(self)
+        .await?;
+
+        Ok(JobBacklog {
+            pending,
+            oldest_pending_age_seconds: oldest_pending_age_seconds.unwrap_or(0),
+        })
+    }
+}
+
+/// Job queue status and backlog; `Unhealthy` when the backlog can't be read
+pub async fn check_job_queue(
+    source: &dyn JobBacklogSource,
+    thresholds: &JobQueueThresholds,
+) -> (CheckStatus, Option<JobBacklog>) {
+    match source.job_backlog().await {
+        Ok(backlog) => {
+            let degraded = backlog.pending > thresholds.max_pending
+                || backlog.oldest_pending_age_seconds > thresholds.max_oldest_pending_age_seconds;
+            let status = if degraded { CheckStatus::Degraded } else { CheckStatus::Healthy };
+            (status, Some(backlog))
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Job queue health check failed");
+            (CheckStatus::Unhealthy, None)
+        }
+    }
+}
+
+/// PING Redis; `None` when no URL is configured
+pub async fn check_redis(redis_url: Option<&str>) -> Option<CheckStatus> {
+    let url = redis_url?;
+    let ping = async {
+        let client = redis::Client::open(url)?;
+        let mut conn = client.get_async_connection().await?;
+        redis::cmd("PING").query_async::<_, String>(&mut conn).await
+    };
+
+    Some(match tokio::time::timeout(Duration::from_secs(2), ping).await {
+        Ok(Ok(_)) => CheckStatus::Healthy,
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "Redis health check failed");
+            CheckStatus::Unhealthy
+        }
+        Err(_) => {
+            tracing::warn!("Redis health check timed out");
+            CheckStatus::Unhealthy
+        }
+    })
+}
+
 /// Health check endpoint
 async fn health_check(
     State(state): State<AppState>,
@@ -57,14 +155,26 @@ async fn health_check(
         Err(_) => CheckStatus::Unhealthy,
     };
     
-    // Redis status check - degraded since we don't have direct cache access in AppState
-    let redis_status = CheckStatus::Degraded;
+    // Check Redis, if configured
+    let redis_url = crate::config::Config::from_env().redis_url;
+    let redis_status = check_redis(redis_url.as_deref()).await;
+    
+    // Check job queue backlog
+    let (job_status, job_backlog) = check_job_queue(&state.pool, &JobQueueThresholds::default()).await;
     
-    // Check job queue
-    let job_status = CheckStatus::Healthy; // TODO: Implement actual check
+    let worst = [Some(db_status), redis_status, Some(job_status)]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(CheckStatus::Healthy);
+    let status = match worst {
+        CheckStatus::Healthy => "ok",
+        CheckStatus::Degraded => "degraded",
+        CheckStatus::Unhealthy => "unhealthy",
+    };
     
     Ok(Json(HealthResponse {
-        status: "ok".to_string(),
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: uptime,
         timestamp: Utc::now().to_rfc3339(),
@@ -72,6 +182,7 @@ async fn health_check(
             database: db_status,
             redis: redis_status,
             job_queue: job_status,
+            job_backlog,
         },
     }))
 }
@@ -95,10 +206,53 @@ async fn liveness_check() -> &'static str {
 }
 
 /// Observability routes
-pub fn observability_routes() -> Router<AppState> {
+pub fn observability_routes(exporter: Arc<metrics::MetricsExporter>) -> Router<AppState> {
     Router::new()
         .route("/health", get(health_check))
         .route("/health/ready", get(readiness_check))
         .route("/health/live", get(liveness_check))
-        .route("/metrics", get(metrics::metrics_handler))
+        .route("/metrics", get(metrics::metrics_handler).with_state(exporter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backlog source standing in for the jobs table
+    struct MockBacklog(Result<JobBacklog, ()>);
+
+    #[async_trait::async_trait]
+    impl JobBacklogSource for MockBacklog {
+        async fn job_backlog(&self) -> Result<JobBacklog, sqlx::Error> {
+            self.0.clone().map_err(|_| sqlx::Error::PoolTimedOut)
+        }
+    }
+
+    fn backlog(pending: i64, oldest_pending_age_seconds: i64) -> MockBacklog {
+        MockBacklog(Ok(JobBacklog { pending, oldest_pending_age_seconds }))
+    }
+
+    #[tokio::test]
+    async fn test_job_queue_status_follows_backlog() {
+        let thresholds = JobQueueThresholds::default();
+
+        let (status, numbers) = check_job_queue(&backlog(12, 3), &thresholds).await;
+        assert_eq!(status, CheckStatus::Healthy);
+        assert_eq!(numbers, Some(JobBacklog { pending: 12, oldest_pending_age_seconds: 3 }));
+
+        let (status, _) = check_job_queue(&backlog(50_000, 3), &thresholds).await;
+        assert_eq!(status, CheckStatus::Degraded);
+        let (status, _) = check_job_queue(&backlog(1, 3600), &thresholds).await;
+        assert_eq!(status, CheckStatus::Degraded);
+
+        let (status, numbers) = check_job_queue(&MockBacklog(Err(())), &thresholds).await;
+        assert_eq!(status, CheckStatus::Unhealthy);
+        assert_eq!(numbers, None);
+    }
+
+    #[tokio::test]
+    async fn test_redis_check_reports_unconfigured_and_unreachable() {
+        assert_eq!(check_redis(None).await, None);
+        assert_eq!(check_redis(Some("redis://127.0.0.1:1/")).await, Some(CheckStatus::Unhealthy));
+    }
 }
@@ -51,9 +51,26 @@ pub fn init_tracing() {
 /// Log request middleware
 use axum::{
     extract::{Request},
+    http::HeaderValue,
     middleware::Next,
     response::Response,
 };
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::middleware::permission::AuthenticatedUser;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::middleware::trace_context::with_trace_id;
+
+/// Header carrying the request id in and out
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Inbound ids longer than this are replaced rather than trusted
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the current request, available in request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
 
 pub async fn logging_middleware(
     request: Request,
@@ -85,3 +102,130 @@ pub async fn logging_middleware(
     
     response
 }
+
+/// Wrap the request in a span carrying `request_id`, `tenant_id` and
+/// `user_id`, so every log line emitted while handling it can be correlated
+///
+/// Layer this inside tenant resolution and auth so their extensions are
+/// present. An inbound `X-Request-Id` is kept; otherwise one is generated.
+/// Either way it's echoed on the response, and it becomes the trace id of
+/// the events and jobs the request starts.
+pub async fn request_context_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+        tenant_id = tracing::field::Empty,
+        user_id = tracing::field::Empty,
+    );
+    let user = request.extensions().get::<AuthenticatedUser>();
+    let tenant_id = request
+        .extensions()
+        .get::<ResolvedTenant>()
+        .map(|t| t.id)
+        .or(user.map(|u| u.tenant_id));
+    if let Some(tenant_id) = tenant_id {
+        span.record("tenant_id", tracing::field::display(tenant_id));
+    }
+    if let Some(user) = user {
+        span.record("user_id", tracing::field::display(user.id));
+    }
+
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+    let mut response = with_trace_id(Some(request_id.clone()), next.run(request).instrument(span)).await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Extension, Router};
+    use test_support::get_test_pool;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(|Extension(id): Extension<RequestId>| async move { id.0 }))
+            .layer(axum::middleware::from_fn(request_context_middleware))
+    }
+
+    fn response_id(response: &Response) -> String {
+        response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_generates_request_id() {
+        let response = app().oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+        let id = response_id(&response);
+        assert!(Uuid::parse_str(&id).is_ok());
+
+        // Handlers see the same id the client gets back
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, id.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_echoes_inbound_request_id() {
+        let request = Request::get("/").header(REQUEST_ID_HEADER, "trace-abc-123").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response_id(&response), "trace-abc-123");
+
+        let oversized = "x".repeat(MAX_REQUEST_ID_LEN + 1);
+        let request = Request::get("/").header(REQUEST_ID_HEADER, oversized.as_str()).body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_ne!(response_id(&response), oversized);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_job_enqueued_during_request_carries_request_trace_id() {
+        use crate::jobs::JobQueue;
+        use std::sync::Arc;
+
+        let pool = get_test_pool().await;
+        let queue = Arc::new(JobQueue::new(pool.clone(), &format!("test-trace-{}", Uuid::new_v4().simple())));
+
+        let enqueuer = queue.clone();
+        let app = Router::new()
+            .route(
+                "/",
+                axum::routing::post(move || async move {
+                    let job_id = enqueuer.enqueue("send_email".into(), serde_json::json!({}), 3).await.unwrap();
+                    job_id.to_string()
+                }),
+            )
+            .layer(axum::middleware::from_fn(request_context_middleware));
+
+        let request = Request::post("/").header(REQUEST_ID_HEADER, "req-42").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response_id(&response), "req-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let job_id = Uuid::parse_str(std::str::from_utf8(&body).unwrap()).unwrap();
+
+        let job = queue.get(job_id).await.unwrap().unwrap();
+        assert_eq!(job.trace_id.as_deref(), Some("req-42"));
+        // Jobs enqueued outside a request have no trace id
+        let untraced = queue.enqueue("send_email".into(), serde_json::json!({}), 3).await.unwrap();
+        assert_eq!(queue.get(untraced).await.unwrap().unwrap().trace_id, None);
+
+        sqlx::query("DELETE FROM jobs WHERE id = ANY($1)")
+            .bind(vec![job_id, untraced])
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
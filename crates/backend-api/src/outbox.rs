@@ -0,0 +1,298 @@
+//! Transactional Outbox - reliable delivery of entity events
+//!
+//! Entity writes enqueue their `EntityEvent` in the same transaction as the
+//! data change. After commit the request publishes the event straight away
+//! and marks the row dispatched; if the process dies in between, the
+//! [`OutboxDispatcher`] picks the row up once it is older than the grace
+//! period. Delivery is at-least-once, so consumers should dedupe on the
+//! event id (`dedup_key`).
+
+use core_node_engine::EntityEvent;
+use sqlx::{PgConnection, PgPool, Row};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::middleware::trace_context::current_trace_id;
+use crate::state::AppState;
+
+/// Store `event` for delivery; call inside the transaction that makes the change
+///
+/// An event without a trace id is stored with the caller's, so redelivery by
+/// the dispatcher keeps it.
+pub async fn enqueue(conn: &mut PgConnection, event: &EntityEvent) -> Result<(), sqlx::Error> {
+    let mut payload = serde_json::to_value(event).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+    if let (None, Some(trace_id)) = (&event.trace_id, current_trace_id()) {
+        payload["trace_id"] = serde_json::Value::String(trace_id);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_outbox (dedup_key, tenant_id, entity_type, record_id, event_type, payload)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (dedup_key) DO NOTHING
+        "#,
+    )
+    .bind(event.id)
+    .bind(event.tenant_id)
+    .bind(&event.entity_type)
+    .bind(event.record_id)
+    .bind(event.event_type.to_string())
+    .bind(payload)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Record that the event with this id has been published
+pub async fn mark_dispatched(pool: &PgPool, dedup_key: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE event_outbox SET dispatched_at = NOW() WHERE dedup_key = $1 AND dispatched_at IS NULL")
+        .bind(dedup_key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Where the dispatcher delivers outbox events
+#[async_trait::async_trait]
+pub trait OutboxPublisher: Send + Sync {
+    async fn publish(&self, event: &EntityEvent) -> Result<(), String>;
+}
+
+/// Publishes through the app's `EventPublisher` and runs workflows, exactly
+/// like the request path does
+pub struct StatePublisher(pub Arc<AppState>);
+
+#[async_trait::async_trait]
+impl OutboxPublisher for StatePublisher {
+    async fn publish(&self, event: &EntityEvent) -> Result<(), String> {
+        let entity_type = self
+            .0
+            .metadata
+            .get_entity_type(event.tenant_id, &event.entity_type)
+            .await
+            .map_err(|e| e.to_string())?;
+        crate::routes::entities::deliver_event(&self.0, entity_type.id, event).await
+    }
+}
+
+/// Outbox dispatcher configuration
+#[derive(Debug, Clone)]
+pub struct OutboxDispatcherConfig {
+    /// How often to poll for undelivered events
+    pub poll_interval: Duration,
+    /// Rows claimed per poll
+    pub batch_size: i64,
+    /// Leave rows this young to the request that wrote them
+    pub grace_period: Duration,
+    /// Delay before the first retry of a failed row; doubles per attempt
+    pub retry_backoff: Duration,
+}
+
+impl Default for OutboxDispatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            batch_size: 100,
+            grace_period: Duration::from_secs(30),
+            retry_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Background task delivering events the request path didn't
+pub struct OutboxDispatcher {
+    pool: PgPool,
+    publisher: Arc<dyn OutboxPublisher>,
+    config: OutboxDispatcherConfig,
+}
+
+impl OutboxDispatcher {
+    pub fn new(pool: PgPool, publisher: Arc<dyn OutboxPublisher>) -> Self {
+        Self::with_config(pool, publisher, OutboxDispatcherConfig::default())
+    }
+
+    pub fn with_config(pool: PgPool, publisher: Arc<dyn OutboxPublisher>, config: OutboxDispatcherConfig) -> Self {
+        Self { pool, publisher, config }
+    }
+
+    /// Poll forever
+    pub async fn run(self) {
+        tracing::info!(poll_secs = self.config.poll_interval.as_secs(), "Starting outbox dispatcher");
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            match self.dispatch_once().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!(count, "Dispatched outbox events"),
+                Err(e) => tracing::error!(error = %e, "Outbox dispatch failed"),
+            }
+        }
+    }
+
+    /// Deliver one batch of due events; returns how many were published
+    ///
+    /// Rows stay locked (`SKIP LOCKED` for other dispatchers) until their
+    /// outcome is committed, so a crash mid-batch redelivers rather than
+    /// drops them.
+    pub async fn dispatch_once(&self) -> Result<usize, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, payload, attempts
+            FROM event_outbox
+            WHERE dispatched_at IS NULL
+              AND available_at <= NOW()
+              AND created_at <= NOW() - make_interval(secs => $1)
+            ORDER BY id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(self.config.grace_period.as_secs_f64())
+        .bind(self.config.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut dispatched = 0;
+        for row in rows {
+            let id: i64 = row.try_get("id")?;
+            let attempts: i32 = row.try_get("attempts")?;
+            let result = match serde_json::from_value::<EntityEvent>(row.try_get("payload")?) {
+                Ok(event) => self.publisher.publish(&event).await,
+                Err(e) => Err(format!("Undecodable outbox payload: {}", e)),
+            };
+
+            match result {
+                Ok(()) => {
+                    sqlx::query("UPDATE event_outbox SET dispatched_at = NOW(), attempts = attempts + 1 WHERE id = $1")
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                    dispatched += 1;
+                }
+                Err(error) => {
+                    let backoff = self.config.retry_backoff.as_secs_f64() * 2f64.powi(attempts.min(10));
+                    tracing::warn!(outbox_id = id, attempts = attempts + 1, error = %error, "Outbox publish failed");
+                    sqlx::query(
+                        "UPDATE event_outbox SET attempts = attempts + 1, last_error = $2,
+                         available_at = NOW() + make_interval(secs => $3) WHERE id = $1",
+                    )
+                    .bind(id)
+                    .bind(error)
+                    .bind(backoff)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+        tx.commit().await?;
+
+        Ok(dispatched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use test_support::TestTenant;
+    use tokio::sync::Mutex;
+
+    /// Records deliveries; the first attempt for each id in `fail_once` fails
+    #[derive(Default)]
+    struct RecordingPublisher {
+        delivered: Mutex<Vec<Uuid>>,
+        fail_once: Mutex<HashSet<Uuid>>,
+    }
+
+    impl RecordingPublisher {
+        async fn delivered(&self, event_id: Uuid) -> usize {
+            self.delivered.lock().await.iter().filter(|id| **id == event_id).count()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OutboxPublisher for RecordingPublisher {
+        async fn publish(&self, event: &EntityEvent) -> Result<(), String> {
+            if self.fail_once.lock().await.remove(&event.id) {
+                return Err("broker unavailable".to_string());
+            }
+            self.delivered.lock().await.push(event.id);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        tenant: TestTenant,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            Self { tenant: TestTenant::new("Outbox Test").await }
+        }
+
+        fn event(&self) -> EntityEvent {
+            EntityEvent::create(self.tenant.id, "lead", Uuid::new_v4(), serde_json::json!({ "name": "Aisha" }), None)
+        }
+
+        /// Commit `event` through the outbox, then "crash" before publishing it
+        async fn commit_without_publish(&self, event: &EntityEvent) {
+            let mut tx = self.tenant.pool.begin().await.unwrap();
+            enqueue(&mut tx, event).await.unwrap();
+            tx.commit().await.unwrap();
+        }
+
+        async fn row(&self, event: &EntityEvent) -> Option<(i32, bool)> {
+            sqlx::query_as("SELECT attempts, dispatched_at IS NOT NULL FROM event_outbox WHERE dedup_key = $1")
+                .bind(event.id)
+                .fetch_optional(&self.tenant.pool)
+                .await
+                .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    // One test, since a dispatcher claims every tenant's due rows
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_events_committed_before_crash_are_delivered_by_dispatcher() {
+        let fx = Fixture::new().await;
+        let (event, flaky) = (fx.event(), fx.event());
+        fx.commit_without_publish(&event).await;
+        fx.commit_without_publish(&flaky).await;
+        assert_eq!(fx.row(&event).await, Some((0, false)));
+
+        let publisher = Arc::new(RecordingPublisher::default());
+        publisher.fail_once.lock().await.insert(flaky.id);
+        let config = OutboxDispatcherConfig { grace_period: Duration::ZERO, retry_backoff: Duration::ZERO, ..Default::default() };
+        let dispatcher = OutboxDispatcher::with_config(fx.tenant.pool.clone(), publisher.clone(), config);
+
+        dispatcher.dispatch_once().await.unwrap();
+        assert_eq!(publisher.delivered(event.id).await, 1);
+        assert_eq!(fx.row(&event).await, Some((1, true)));
+        // A failed publish stays pending and is retried on the next poll
+        assert_eq!(fx.row(&flaky).await, Some((1, false)));
+        dispatcher.dispatch_once().await.unwrap();
+        assert_eq!(publisher.delivered(flaky.id).await, 1);
+        assert_eq!(fx.row(&flaky).await, Some((2, true)));
+
+        // Dispatched rows are not delivered again, and re-enqueueing is a no-op
+        fx.commit_without_publish(&event).await;
+        dispatcher.dispatch_once().await.unwrap();
+        assert_eq!(publisher.delivered(event.id).await, 1);
+
+        // Nothing is enqueued when the write rolls back
+        let rolled_back = fx.event();
+        let mut tx = fx.tenant.pool.begin().await.unwrap();
+        enqueue(&mut tx, &rolled_back).await.unwrap();
+        tx.rollback().await.unwrap();
+        assert_eq!(fx.row(&rolled_back).await, None);
+
+        fx.cleanup().await;
+    }
+}
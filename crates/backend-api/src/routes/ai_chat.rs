@@ -1,26 +1,44 @@
 //! AI Chat API - Context-Aware AI Endpoint with RAG
 //!
 //! Provides:
-//! - `/api/ai/chat` - Chat with AI using entity context
+//! - `/api/ai/chat` - Chat with AI using entity context, streamed as
+//!   server-sent events when the client asks for them
+//! - Tool calling, so answers can draw on the user's records
 //! - RAG (Retrieval-Augmented Generation) integration
+//! - `/api/ai/documents/:id` - Tenant documents the chat is grounded on
 //! - Conversation history management
 
 use axum::{
     extract::{Path, Query, State},
-    routing::{get, post},
+    http::{header, HeaderMap},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post, put},
     Json, Router,
 };
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::convert::Infallible;
+use std::future::Future;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
-use crate::ai::embeddings::{
-    EmbeddingService, OpenAIEmbeddingProvider, 
-    find_similar_entities, entity_to_embedding_content
-};
+use crate::ai::embeddings::{find_similar_entities, entity_to_embedding_content};
+use crate::ai::chat::{self, ChatDelta, ChatMessage, ChatProvider, ToolRunner};
+use crate::ai::tools::{ChatTools, ToolCaller};
+use crate::ai::{self, rag, redaction};
+use crate::middleware::database::RlsConn;
+use crate::middleware::permission::AuthenticatedUser;
+use crate::middleware::tenant::ResolvedTenant;
 use crate::state::AppState;
 
+/// Document chunks added to the chat context
+const DOCUMENT_CONTEXT_CHUNKS: i64 = 4;
+
 /// Chat request
 #[derive(Debug, Deserialize)]
 pub struct ChatRequest {
@@ -38,6 +56,13 @@ pub struct ChatRequest {
 
 fn default_true() -> bool { true }
 
+/// Document ingestion request; the id is in the path
+#[derive(Debug, Deserialize)]
+pub struct IngestDocumentRequest {
+    pub title: String,
+    pub text: String,
+}
+
 /// Chat response
 #[derive(Debug, Serialize)]
 pub struct ChatResponse {
@@ -80,14 +105,24 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/conversations", get(list_conversations))
         .route("/conversations/:id", get(get_conversation))
         .route("/conversations/:id/messages", get(get_conversation_messages))
+        .route("/documents/:id", put(ingest_document).delete(delete_document))
 }
 
 /// Main chat handler with RAG integration
+///
+/// A client that accepts `text/event-stream` gets the reply as it's
+/// generated: `token` events carry `{"delta"}` pieces, a `tool` event names
+/// each tool the model runs, and a `done` event with the `ChatResponse`
+/// ends the stream. Others get the `ChatResponse` as JSON once the reply is
+/// complete.
 async fn chat_handler(
     State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    headers: HeaderMap,
     Json(request): Json<ChatRequest>,
-) -> Result<Json<ChatResponse>, (axum::http::StatusCode, String)> {
-    let tenant_id = get_tenant_id_from_context()?;
+) -> Result<Response, (axum::http::StatusCode, String)> {
+    let tenant_id = tenant.id;
     let user_id = get_user_id_from_context()?;
     
     // Get or create conversation
@@ -109,9 +144,35 @@ async fn chat_handler(
     // Get conversation history
     let history = get_recent_messages(&state.pool, conversation_id, 10).await?;
     
-    // Call OpenAI
-    let api_key = std::env::var("OPENAI_API_KEY").unwrap_or_else(|_| "mock".to_string());
-    let ai_response = call_openai_chat(&api_key, &system_prompt, &history, &request.message).await?;
+    let mut messages = vec![ChatMessage::System(system_prompt)];
+    messages.extend(history.into_iter().map(|(role, content)| ChatMessage::from_role(&role, content)));
+    messages.push(ChatMessage::User(request.message.clone()));
+    
+    // Tools act for the caller, whatever the model asks
+    let caller = ToolCaller { tenant_id, user: user.map(|axum::Extension(u)| u) };
+    let tools = Arc::new(ChatTools::new(state.clone(), caller));
+    
+    if wants_event_stream(&headers) {
+        save_message(&state.pool, conversation_id, "user", &request.message, None).await?;
+        
+        let pool = state.pool.clone();
+        let events = relay_reply(state.chat.clone(), tools, messages, move |reply| async move {
+            if let Err((_, e)) = save_message(&pool, conversation_id, "assistant", &reply, Some(&rag_context)).await {
+                tracing::warn!("Saving streamed reply to conversation {} failed: {}", conversation_id, e);
+            }
+            serde_json::to_value(ChatResponse {
+                message: reply,
+                conversation_id,
+                rag_context: if rag_context.is_empty() { None } else { Some(rag_context) },
+                tokens_used: None,
+            })
+            .unwrap_or_default()
+        });
+        return Ok(Sse::new(events).keep_alive(KeepAlive::default()).into_response());
+    }
+    
+    let ai_response = chat::complete_with_tools(state.chat.as_ref(), tools.as_ref(), &mut messages).await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
     
     // Save messages to database
     save_message(&state.pool, conversation_id, "user", &request.message, None).await?;
@@ -126,23 +187,105 @@ async fn chat_handler(
             completion_tokens: ai_response.completion_tokens,
             total_tokens: ai_response.prompt_tokens + ai_response.completion_tokens,
         }),
-    }))
+    }).into_response())
+}
+
+fn wants_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
 }
 
-/// Build RAG context by finding similar entities
+/// Stream the reply to `messages` as `token` events, running the tools the
+/// model asks for between turns, then end with a `done` event carrying what
+/// `on_complete` makes of the full reply
+///
+/// The relay runs in its own task and stops as soon as the client goes
+/// away: dropping the provider's stream closes its connection, so
+/// generation stops too and `on_complete` never runs. A provider error ends
+/// the stream with an `error` event.
+fn relay_reply<F, Fut>(
+    chat: Arc<dyn ChatProvider>,
+    tools: Arc<dyn ToolRunner>,
+    mut messages: Vec<ChatMessage>,
+    on_complete: F,
+) -> impl Stream<Item = Result<Event, Infallible>>
+where
+    F: FnOnce(String) -> Fut + Send + 'static,
+    Fut: Future<Output = serde_json::Value> + Send,
+{
+    let (tx, rx) = mpsc::channel::<Event>(32);
+    let error_event = |e: String| Event::default().event("error").data(serde_json::json!({ "error": e }).to_string());
+    
+    tokio::spawn(async move {
+        let mut reply = String::new();
+        let mut round = 0;
+        loop {
+            let definitions = chat::tools_for_round(tools.as_ref(), round);
+            let mut tokens = match chat.stream(&messages, &definitions).await {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    let _ = tx.send(error_event(e)).await;
+                    return;
+                }
+            };
+            let mut requested = Vec::new();
+            loop {
+                let next = tokio::select! {
+                    _ = tx.closed() => {
+                        tracing::debug!("Chat stream cancelled by the client");
+                        return;
+                    }
+                    next = tokens.next() => next,
+                };
+                match next {
+                    Some(Ok(ChatDelta::Text(delta))) => {
+                        reply.push_str(&delta);
+                        let event = Event::default().event("token").data(serde_json::json!({ "delta": delta }).to_string());
+                        if tx.send(event).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(ChatDelta::ToolCalls(calls))) => requested = calls,
+                    Some(Err(e)) => {
+                        let _ = tx.send(error_event(e)).await;
+                        return;
+                    }
+                    None => break,
+                }
+            }
+            drop(tokens);
+            
+            if requested.is_empty() || definitions.is_empty() {
+                break;
+            }
+            for request in &requested {
+                let event = Event::default().event("tool").data(serde_json::json!({ "name": request.name }).to_string());
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+            chat::run_tools(tools.as_ref(), requested, &mut messages).await;
+            round += 1;
+        }
+        let done = on_complete(reply).await;
+        let _ = tx.send(Event::default().event("done").data(done.to_string())).await;
+    });
+    
+    futures::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|event| (Ok(event), rx)) })
+}
+
+/// Build RAG context from the tenant's documents and similar entities
 async fn build_rag_context(
     state: &AppState,
     tenant_id: Uuid,
     query: &str,
     context_entity_id: Option<Uuid>,
 ) -> Result<Vec<RagContextItem>, (axum::http::StatusCode, String)> {
-    let api_key = std::env::var("OPENAI_API_KEY").unwrap_or_else(|_| "mock".to_string());
-    
-    if api_key == "mock" {
+    let Some(embedding_service) = state.embedder.as_ref() else {
         return Ok(vec![]); // Skip RAG if no API key
-    }
-    
-    let embedding_service = OpenAIEmbeddingProvider::new(api_key);
+    };
     
     // Generate embedding for the query
     let query_embedding = embedding_service.embed(query).await
@@ -159,21 +302,33 @@ async fn build_rag_context(
     ).await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
+    // Passages from the tenant's documents
+    let mut conn = state.pool.acquire().await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let chunks = rag::nearest_chunks(&mut conn, tenant_id, &query_embedding.embedding, DOCUMENT_CONTEXT_CHUNKS).await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
     // Convert to RAG context items
-    let mut context: Vec<RagContextItem> = similar.into_iter()
-        .map(|e| RagContextItem {
+    let mut context: Vec<RagContextItem> = chunks.into_iter()
+        .map(|c| RagContextItem {
+            entity_id: c.document_id,
+            entity_type: "document".to_string(),
+            similarity: c.similarity,
+            preview: format!("{}: {}", c.title, c.content),
+        })
+        .chain(similar.into_iter().map(|e| RagContextItem {
             entity_id: e.entity_id,
             entity_type: e.entity_type,
             similarity: e.similarity,
             preview: e.content_preview.unwrap_or_default(),
-        })
+        }))
         .collect();
     
     // If there's a specific context entity, ensure it's included
     if let Some(entity_id) = context_entity_id {
         if !context.iter().any(|c| c.entity_id == entity_id) {
             // Fetch the context entity and add it
-            if let Ok(Some(entity_data)) = get_entity_data(&state.pool, tenant_id, entity_id).await {
+            if let Ok(Some(entity_data)) = get_entity_data(state, tenant_id, entity_id).await {
                 context.insert(0, RagContextItem {
                     entity_id,
                     entity_type: entity_data.entity_type,
@@ -192,7 +347,8 @@ fn build_system_prompt(rag_context: &[RagContextItem], entity_type: Option<&str>
     let mut prompt = String::from(
         "You are a helpful AI assistant for a CRM and real estate management platform called Jirsi. \
         You help users manage contacts, properties, deals, and workflows. \
-        Be concise, professional, and helpful.\n\n"
+        Be concise, professional, and helpful. \
+        When a question needs the user's data, look it up with the tools rather than guessing.\n\n"
     );
     
     if let Some(et) = entity_type {
@@ -216,77 +372,6 @@ fn build_system_prompt(rag_context: &[RagContextItem], entity_type: Option<&str>
     prompt
 }
 
-/// OpenAI chat response
-struct OpenAiChatResponse {
-    content: String,
-    prompt_tokens: u32,
-    completion_tokens: u32,
-}
-
-/// Call OpenAI Chat API
-async fn call_openai_chat(
-    api_key: &str,
-    system_prompt: &str,
-    history: &[(String, String)], // (role, content)
-    user_message: &str,
-) -> Result<OpenAiChatResponse, (axum::http::StatusCode, String)> {
-    if api_key == "mock" {
-        return Ok(OpenAiChatResponse {
-            content: format!("Mock response to: {}", user_message),
-            prompt_tokens: 0,
-            completion_tokens: 0,
-        });
-    }
-    
-    let client = reqwest::Client::new();
-    
-    let mut messages = vec![
-        serde_json::json!({ "role": "system", "content": system_prompt })
-    ];
-    
-    for (role, content) in history {
-        messages.push(serde_json::json!({ "role": role, "content": content }));
-    }
-    
-    messages.push(serde_json::json!({ "role": "user", "content": user_message }));
-    
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
-        .json(&serde_json::json!({
-            "model": "gpt-4o-mini",
-            "messages": messages,
-            "temperature": 0.7,
-            "max_tokens": 1000
-        }))
-        .send()
-        .await
-        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    
-    if !response.status().is_success() {
-        let error = response.text().await.unwrap_or_default();
-        return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, error));
-    }
-    
-    let json: serde_json::Value = response.json().await
-        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    
-    let content = json["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("I couldn't generate a response.")
-        .to_string();
-    
-    let prompt_tokens = json["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32;
-    let completion_tokens = json["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32;
-    
-    Ok(OpenAiChatResponse {
-        content,
-        prompt_tokens,
-        completion_tokens,
-    })
-}
-
 // ============================================================================
 // Database helpers
 // ============================================================================
@@ -457,6 +542,39 @@ async fn get_conversation_messages(
     }).collect()))
 }
 
+/// PUT /ai/documents/:id - ingest a document, replacing any earlier version
+async fn ingest_document(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Json(request): Json<IngestDocumentRequest>,
+) -> Result<Json<serde_json::Value>, (axum::http::StatusCode, String)> {
+    let embedder = state.embedder.as_ref()
+        .ok_or((axum::http::StatusCode::SERVICE_UNAVAILABLE, "Embeddings are not configured".to_string()))?;
+    let doc = ai::Document { id, title: request.title, text: request.text };
+    
+    let chunks = ai::ingest_document(&mut conn, embedder.as_ref(), tenant.id, &doc).await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(serde_json::json!({ "id": id, "chunks": chunks })))
+}
+
+/// DELETE /ai/documents/:id
+async fn delete_document(
+    Path(id): Path<Uuid>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+) -> Result<axum::http::StatusCode, (axum::http::StatusCode, String)> {
+    let removed = ai::delete_document(&mut conn, tenant.id, id).await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if removed == 0 {
+        return Err((axum::http::StatusCode::NOT_FOUND, "Document not found".to_string()));
+    }
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
 // ============================================================================
 // Helper functions (stubs for now - would use middleware/extractors)
 // ============================================================================
@@ -476,25 +594,167 @@ struct EntityData {
     preview: String,
 }
 
+/// The context entity as chat context, with PII masked unless the tenant
+/// allows sending it
 async fn get_entity_data(
-    pool: &PgPool,
+    state: &AppState,
     tenant_id: Uuid,
     entity_id: Uuid,
 ) -> Result<Option<EntityData>, sqlx::Error> {
-    let row = sqlx::query_as::<_, (String, serde_json::Value)>(
+    let row = sqlx::query_as::<_, (Uuid, String, serde_json::Value)>(
         r#"
-        SELECT entity_code, data
-        FROM entities
-        WHERE tenant_id = $1 AND id = $2
+        SELECT t.id, t.name, r.data
+        FROM entity_records r
+        JOIN entity_types t ON t.id = r.entity_type_id
+        WHERE r.tenant_id = $1 AND r.id = $2 AND r.deleted_at IS NULL
         "#
     )
     .bind(tenant_id)
     .bind(entity_id)
-    .fetch_optional(pool)
+    .fetch_optional(&state.pool)
     .await?;
     
-    Ok(row.map(|(entity_type, data)| {
-        let preview = entity_to_embedding_content(&entity_type, &data);
-        EntityData { entity_type, preview }
-    }))
+    let Some((entity_type_id, entity_type, data)) = row else {
+        return Ok(None);
+    };
+    let fields = state.metadata.get_fields(tenant_id, entity_type_id).await.unwrap_or_default();
+    let policy = redaction::tenant_policy(&state.pool, tenant_id).await?;
+    let redacted = redaction::redact_record(&fields, &data, policy);
+    redaction::audit_redaction(&state.pool, tenant_id, None, &entity_type, entity_id, &redacted, "ai_chat").await?;
+    
+    let preview = entity_to_embedding_content(&entity_type, &redacted.data);
+    Ok(Some(EntityData { entity_type, preview }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::chat::{MockChatProvider, TokenStream, ToolRequest};
+    use async_trait::async_trait;
+    use crate::ai::redaction::{redact_record, PiiPolicy};
+    use core_models::{FieldDef, FieldType};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_pii_is_masked_in_assembled_prompt() {
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut email = FieldDef::new(tenant_id, entity_type_id, "email", "Email", FieldType::Email);
+        email.intelligence.is_pii = true;
+        let fields = vec![
+            FieldDef::new(tenant_id, entity_type_id, "name", "Name", FieldType::Text),
+            email,
+            FieldDef::new(tenant_id, entity_type_id, "company", "Company", FieldType::Text),
+        ];
+        let data = json!({ "name": "Sara Khan", "email": "sara@example.com", "company": "Emaar" });
+
+        let redacted = redact_record(&fields, &data, PiiPolicy::default());
+        let context = RagContextItem {
+            entity_id: Uuid::new_v4(),
+            entity_type: "contact".to_string(),
+            similarity: 1.0,
+            preview: entity_to_embedding_content("contact", &redacted.data),
+        };
+        let prompt = build_system_prompt(&[context], Some("contact"));
+
+        assert!(!prompt.contains("sara@example.com"));
+        assert!(prompt.contains("Email: [Email redacted]"));
+        assert!(prompt.contains("Contact: Sara Khan") && prompt.contains("Company: Emaar"));
+    }
+
+    /// `(event, data)` pairs of an SSE body
+    fn parse_events(body: &str) -> Vec<(String, serde_json::Value)> {
+        body.split("\n\n")
+            .filter_map(|block| {
+                let field = |name: &str| block.lines().find_map(|l| l.strip_prefix(name).map(str::to_string));
+                Some((field("event: ")?, serde_json::from_str(&field("data: ")?).ok()?))
+            })
+            .collect()
+    }
+
+    /// Offers the model nothing
+    struct NoTools;
+
+    #[async_trait]
+    impl ToolRunner for NoTools {
+        fn definitions(&self) -> Vec<serde_json::Value> {
+            Vec::new()
+        }
+
+        async fn run(&self, _request: &ToolRequest) -> serde_json::Value {
+            unreachable!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_emits_tokens_then_done() {
+        let messages = vec![ChatMessage::User("Which units have a pool?".to_string())];
+        let events = relay_reply(Arc::new(MockChatProvider), Arc::new(NoTools), messages, |reply| async move {
+            json!({ "message": reply })
+        });
+
+        let response = Sse::new(events).into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let events = parse_events(std::str::from_utf8(&body).unwrap());
+
+        let (last, tokens) = events.split_last().unwrap();
+        assert!(tokens.len() > 1);
+        assert!(tokens.iter().all(|(name, _)| name == "token"));
+        let streamed: String = tokens.iter().map(|(_, data)| data["delta"].as_str().unwrap()).collect();
+        assert_eq!(streamed, "Mock response to: Which units have a pool?");
+        assert_eq!(last, &("done".to_string(), json!({ "message": streamed })));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_cancels_upstream() {
+        /// Flags when the provider stream is dropped
+        struct DropFlag(Arc<AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        /// A reply that never ends
+        struct EndlessProvider(Arc<AtomicBool>);
+
+        #[async_trait]
+        impl ChatProvider for EndlessProvider {
+            async fn complete(&self, _: &[ChatMessage], _: &[serde_json::Value]) -> Result<chat::ChatCompletion, String> {
+                unreachable!()
+            }
+
+            async fn stream(&self, _: &[ChatMessage], _: &[serde_json::Value]) -> Result<TokenStream, String> {
+                let guard = DropFlag(self.0.clone());
+                let words = futures::stream::unfold(guard, |guard| async move {
+                    Some((Ok(ChatDelta::Text("word ".to_string())), guard))
+                });
+                Ok(words.boxed())
+            }
+        }
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let completed = Arc::new(AtomicBool::new(false));
+        let done = completed.clone();
+        let provider = Arc::new(EndlessProvider(dropped.clone()));
+        let messages = vec![ChatMessage::User("Tell me everything".to_string())];
+        let events = relay_reply(provider, Arc::new(NoTools), messages, move |_| async move {
+            done.store(true, Ordering::SeqCst);
+            json!({})
+        });
+
+        let mut body = Sse::new(events).into_response().into_body().into_data_stream();
+        assert!(body.next().await.is_some());
+        drop(body);
+
+        for _ in 0..100 {
+            if dropped.load(Ordering::SeqCst) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(dropped.load(Ordering::SeqCst));
+        assert!(!completed.load(Ordering::SeqCst));
+    }
 }
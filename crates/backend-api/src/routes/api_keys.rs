@@ -0,0 +1,232 @@
+//! API key routes
+//!
+//! Tenant admins create, scope and revoke the keys integrations send as
+//! `x-api-key`. The secret is in the create response only; listings show
+//! the key's prefix.
+
+use axum::{
+    Router,
+    routing::{get, put},
+    extract::{State, Path},
+    http::StatusCode,
+    Json,
+};
+use core_auth::api_key::{ApiKey, NewApiKey};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::error::ApiError;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::middleware::permission::{is_admin, AuthenticatedUser};
+use crate::routes::auth::require_verified_email;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_api_keys).post(create_api_key))
+        .route("/:id", put(update_api_key).delete(revoke_api_key))
+}
+
+/// Only a signed-in admin manages keys; an API key can't mint more
+fn require_admin(user: Option<axum::Extension<AuthenticatedUser>>) -> Result<AuthenticatedUser, ApiError> {
+    let axum::Extension(user) = user.ok_or(ApiError::Unauthorized)?;
+    if is_admin(&user) {
+        Ok(user)
+    } else {
+        Err(ApiError::Forbidden)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    /// Shown this once; only its hash is kept
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateApiKeyRequest {
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub entity_types: Option<Vec<String>>,
+}
+
+async fn list_api_keys(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+) -> Result<Json<Vec<ApiKey>>, ApiError> {
+    require_admin(user)?;
+    Ok(Json(state.api_key_service.list(tenant.id).await?))
+}
+
+async fn create_api_key(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Json(req): Json<NewApiKey>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), ApiError> {
+    let user = require_admin(user)?;
+    require_verified_email(&state, user.id).await?;
+    if req.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("API key needs a name".to_string()));
+    }
+    let (key, secret) = state.api_key_service.create(tenant.id, Some(user.id), req).await?;
+    tracing::info!(tenant_id = %tenant.id, key_id = %key.id, "API key created");
+    Ok((StatusCode::CREATED, Json(CreatedApiKey { key, secret })))
+}
+
+async fn update_api_key(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateApiKeyRequest>,
+) -> Result<Json<ApiKey>, ApiError> {
+    require_admin(user)?;
+    state
+        .api_key_service
+        .update_scopes(tenant.id, id, &req.scopes, req.entity_types.as_deref())
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("API key not found".to_string()))
+}
+
+async fn revoke_api_key(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(user)?;
+    if !state.api_key_service.revoke(tenant.id, id).await? {
+        return Err(ApiError::NotFound("API key not found".to_string()));
+    }
+    tracing::info!(tenant_id = %tenant.id, key_id = %id, "API key revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::auth::identify_api_key;
+    use axum::http::HeaderMap;
+    use test_support::get_test_pool;
+
+    fn tenant(id: Uuid) -> ResolvedTenant {
+        ResolvedTenant {
+            id,
+            name: "API Key Test".to_string(),
+            subdomain: format!("keys-{}", id.simple()),
+            settings: serde_json::json!({}),
+            status: "active".to_string(),
+            trial_ends_at: None,
+        }
+    }
+
+    fn admin(id: Uuid, tenant_id: Uuid) -> Option<axum::Extension<AuthenticatedUser>> {
+        Some(axum::Extension(AuthenticatedUser {
+            id,
+            tenant_id,
+            email: "admin@example.com".to_string(),
+            name: "Admin".to_string(),
+            role: "admin".to_string(),
+            roles: Vec::new(),
+        }))
+    }
+
+    fn sending(secret: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", secret.parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_create_use_and_revoke_a_key() {
+        let pool = get_test_pool().await;
+        let (tenant_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'API Key Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("keys-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash, email_verified) VALUES ($1, $2, 'admin@example.com', 'Admin', 'x', TRUE)")
+            .bind(user_id)
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let state = Arc::new(AppState::new(pool.clone()));
+        let ext = || axum::Extension(tenant(tenant_id));
+
+        let (status, Json(created)) = create_api_key(
+            State(state.clone()),
+            ext(),
+            admin(user_id, tenant_id),
+            Json(NewApiKey {
+                name: "Zapier".to_string(),
+                scopes: vec!["records:read".to_string()],
+                entity_types: Some(vec!["contact".to_string()]),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(created.secret.starts_with(&created.key.key_prefix));
+
+        // The key resolves to its tenant and scopes
+        let key = identify_api_key(&sending(&created.secret), Some(&tenant(tenant_id)), &state.api_key_service)
+            .await
+            .unwrap()
+            .expect("key is recognised");
+        assert_eq!((key.id, key.tenant_id), (created.key.id, tenant_id));
+        assert!(key.allows("records", "read") && !key.allows("records", "write"));
+        assert!(key.covers_entity("contact") && !key.covers_entity("deal"));
+        // ...but not on another tenant's host
+        assert!(identify_api_key(&sending(&created.secret), Some(&tenant(Uuid::new_v4())), &state.api_key_service)
+            .await
+            .is_err());
+
+        // The secret never comes back, nor does anything derived from it but the prefix
+        let Json(listed) = list_api_keys(State(state.clone()), ext(), admin(user_id, tenant_id)).await.unwrap();
+        let body = serde_json::to_string(&listed).unwrap();
+        assert!(!body.contains(&created.secret));
+        assert!(!body.contains(&created.secret[created.key.key_prefix.len()..]));
+        assert_eq!(listed[0].key_prefix, created.key.key_prefix);
+        let stored: String = sqlx::query_scalar("SELECT key_hash FROM api_keys WHERE id = $1")
+            .bind(created.key.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_ne!(stored, created.secret);
+
+        // Non-admins can't manage keys
+        let mut member = admin(user_id, tenant_id);
+        member.as_mut().unwrap().0.role = "member".to_string();
+        assert!(matches!(list_api_keys(State(state.clone()), ext(), member).await, Err(ApiError::Forbidden)));
+
+        let status = revoke_api_key(State(state.clone()), ext(), admin(user_id, tenant_id), Path(created.key.id))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(identify_api_key(&sending(&created.secret), Some(&tenant(tenant_id)), &state.api_key_service)
+            .await
+            .is_err());
+        assert!(matches!(
+            revoke_api_key(State(state.clone()), ext(), admin(user_id, tenant_id), Path(created.key.id)).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        for sql in [
+            "DELETE FROM api_keys WHERE tenant_id = $1",
+            "DELETE FROM users WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
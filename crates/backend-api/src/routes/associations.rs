@@ -7,12 +7,16 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use sqlx::Connection;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::Utc;
 
 use crate::state::AppState;
 use crate::error::ApiError;
+use crate::middleware::audit_log::{insert_entry, AuditAction, AuditLogEntry};
+use crate::middleware::permission::AuthenticatedUser;
+use core_models::Cardinality;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
@@ -20,6 +24,7 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/", post(create_association))
         .route("/:id", delete(delete_association))
         .route("/defs", get(list_association_defs))
+        .route("/primary", post(set_primary))
 }
 
 #[derive(Debug, Deserialize)]
@@ -71,6 +76,16 @@ pub struct CreateAssociationRequest {
     pub is_primary: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetPrimaryRequest {
+    /// Record whose primary link changes
+    pub record_id: Uuid,
+    /// Association definition name
+    pub association: String,
+    /// Linked record to make primary
+    pub target_id: Uuid,
+}
+
 // ...
 
 async fn list_association_defs(
@@ -125,6 +140,11 @@ async fn list_associations(
         FROM associations a
         LEFT JOIN entity_records r ON a.target_id = r.id
         WHERE a.tenant_id = $1
+          -- Hide links to soft-deleted records on either side
+          AND NOT EXISTS (
+              SELECT 1 FROM entity_records d
+              WHERE d.id IN (a.source_id, a.target_id) AND d.deleted_at IS NOT NULL
+          )
         "#
     );
     
@@ -164,11 +184,99 @@ async fn list_associations(
 
 async fn create_association(
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     mut conn: RlsConn,
     Json(req): Json<CreateAssociationRequest>,
 ) -> Result<Json<AssociationResponse>, ApiError> {
+    let user_id = user.map(|axum::Extension(u)| u.id);
+    let link = link_records(&mut conn, tenant.id, user_id, &req).await?;
+
+    Ok(Json(AssociationResponse {
+        id: link.id,
+        association_def_id: link.association_def_id,
+        source_id: link.source_id,
+        target_id: link.target_id,
+        role: link.role,
+        is_primary: req.is_primary,
+        target_label: None,
+    }))
+}
+
+async fn delete_association(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = user.map(|axum::Extension(u)| u.id);
+    let deleted = unlink_records(&mut conn, tenant.id, user_id, id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": deleted })))
+}
+
+async fn set_primary(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Json(req): Json<SetPrimaryRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let id = set_primary_association(&mut conn, tenant.id, req.record_id, &req.association, req.target_id).await?;
+
+    Ok(Json(serde_json::json!({ "id": id, "is_primary": true })))
+}
+
+// ============================================================================
+// Linking
+// ============================================================================
+
+/// An association with its definition, as recorded in the audit trail
+#[derive(Debug, sqlx::FromRow)]
+struct LinkRecord {
+    id: Uuid,
+    association_def_id: Uuid,
+    source_id: Uuid,
+    target_id: Uuid,
+    role: Option<String>,
+    name: String,
+    source_entity: String,
+    target_entity: String,
+    source_role: Option<String>,
+    target_role: Option<String>,
+}
+
+async fn fetch_link(conn: &mut sqlx::PgConnection, tenant_id: Uuid, id: Uuid) -> Result<Option<LinkRecord>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT a.id, a.association_def_id, a.source_id, a.target_id, a.role,
+               d.name, d.source_entity, d.target_entity, d.source_role, d.target_role
+        FROM associations a
+        JOIN association_defs d ON d.id = a.association_def_id
+        WHERE a.id = $1 AND a.tenant_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Insert the association and audit it on both records in one transaction
+async fn link_records(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    req: &CreateAssociationRequest,
+) -> Result<LinkRecord, ApiError> {
     let now = Utc::now();
     let id = Uuid::new_v4();
+    let mut tx = conn.begin().await?;
+
+    let def = fetch_link_def(&mut tx, tenant_id, req.association_def_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Association definition not found".to_string()))?;
+    if req.is_primary && !def.allow_primary {
+        return Err(primary_not_allowed(&def.name));
+    }
+    check_cardinality(&mut tx, tenant_id, &def, req).await?;
 
     sqlx::query(
         r#"
@@ -177,7 +285,7 @@ async fn create_association(
         "#,
     )
     .bind(id)
-    .bind(tenant.id)
+    .bind(tenant_id)
     .bind(req.association_def_id)
     .bind(req.source_id)
     .bind(req.target_id)
@@ -185,32 +293,460 @@ async fn create_association(
     .bind(req.is_primary)
     .bind(now)
     .bind(now)
-    .execute(&mut **conn)
+    .execute(&mut *tx)
     .await
     .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-    Ok(Json(AssociationResponse {
-        id,
-        association_def_id: req.association_def_id,
-        source_id: req.source_id,
-        target_id: req.target_id,
-        role: req.role,
-        is_primary: req.is_primary,
-        target_label: None,
-    }))
+    let link = fetch_link(&mut tx, tenant_id, id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Association definition not found".to_string()))?;
+    audit_link(&mut tx, tenant_id, user_id, AuditAction::AssociationLinked, &link).await?;
+    tx.commit().await?;
+
+    Ok(link)
 }
 
-async fn delete_association(
-    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
-    mut conn: RlsConn,
-    Path(id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+/// Delete the association and audit it on both records; `false` if it did not exist
+async fn unlink_records(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    id: Uuid,
+) -> Result<bool, ApiError> {
+    let mut tx = conn.begin().await?;
+    let Some(link) = fetch_link(&mut tx, tenant_id, id).await? else {
+        return Ok(false);
+    };
+
     sqlx::query("DELETE FROM associations WHERE id = $1 AND tenant_id = $2")
         .bind(id)
-        .bind(tenant.id)
-        .execute(&mut **conn)
+        .bind(tenant_id)
+        .execute(&mut *tx)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-    Ok(Json(serde_json::json!({ "deleted": true })))
+    audit_link(&mut tx, tenant_id, user_id, AuditAction::AssociationUnlinked, &link).await?;
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// One entry per side, so the change shows in both records' timelines
+async fn audit_link(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    action: AuditAction,
+    link: &LinkRecord,
+) -> Result<(), sqlx::Error> {
+    let details = serde_json::json!({
+        "association": link.name,
+        "association_id": link.id,
+        "source_entity": link.source_entity,
+        "source_id": link.source_id,
+        "source_role": link.source_role,
+        "target_entity": link.target_entity,
+        "target_id": link.target_id,
+        "target_role": link.target_role,
+        "role": link.role,
+    });
+
+    for (resource_type, resource_id) in [(&link.source_entity, link.source_id), (&link.target_entity, link.target_id)] {
+        let entry = AuditLogEntry {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            action: action.to_string(),
+            resource_type: resource_type.clone(),
+            resource_id: Some(resource_id),
+            details: details.clone(),
+            changes: Vec::new(),
+            ip_address: None,
+            user_agent: None,
+            created_at: Utc::now(),
+        };
+        insert_entry(&mut *conn, &entry).await?;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Integrity
+// ============================================================================
+
+/// The parts of an association definition that constrain new links
+#[derive(Debug, sqlx::FromRow)]
+struct LinkDef {
+    id: Uuid,
+    name: String,
+    cardinality: String,
+    allow_primary: bool,
+}
+
+impl LinkDef {
+    /// Unknown cardinalities are treated as unrestricted
+    fn cardinality(&self) -> Cardinality {
+        serde_json::from_value(serde_json::Value::String(self.cardinality.clone())).unwrap_or(Cardinality::ManyToMany)
+    }
+}
+
+/// Load a definition, locking it so concurrent links are checked one at a time
+async fn fetch_link_def(conn: &mut sqlx::PgConnection, tenant_id: Uuid, id: Uuid) -> Result<Option<LinkDef>, sqlx::Error> {
+    sqlx::query_as("SELECT id, name, cardinality, allow_primary FROM association_defs WHERE id = $1 AND tenant_id = $2 FOR UPDATE")
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Enforce the definition's cardinality on a new link
+///
+/// `one_to_one` allows a single link per record on each side. On the "many"
+/// side of `many_to_one`/`one_to_many` a record may have any number of links
+/// but only one primary.
+async fn check_cardinality(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    def: &LinkDef,
+    req: &CreateAssociationRequest,
+) -> Result<(), ApiError> {
+    let (source_side, target_side, primary_only) = match def.cardinality() {
+        Cardinality::OneToOne => (true, true, false),
+        Cardinality::ManyToOne => (true, false, true),
+        Cardinality::OneToMany => (false, true, true),
+        Cardinality::ManyToMany => return Ok(()),
+    };
+    if primary_only && !req.is_primary {
+        return Ok(());
+    }
+
+    let existing: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM associations
+        WHERE tenant_id = $1 AND association_def_id = $2
+          AND (($3 AND source_id = $4) OR ($5 AND target_id = $6))
+          AND (NOT $7 OR is_primary)
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(def.id)
+    .bind(source_side)
+    .bind(req.source_id)
+    .bind(target_side)
+    .bind(req.target_id)
+    .bind(primary_only)
+    .fetch_one(conn)
+    .await?;
+
+    match existing {
+        0 => Ok(()),
+        _ if primary_only => Err(ApiError::Conflict(format!(
+            "Association '{}' is {} and the record already has a primary link",
+            def.name, def.cardinality
+        ))),
+        _ => Err(ApiError::Conflict(format!(
+            "Association '{}' is {} and one of the records is already linked",
+            def.name, def.cardinality
+        ))),
+    }
+}
+
+fn primary_not_allowed(association: &str) -> ApiError {
+    ApiError::BadRequest(format!("Association '{}' does not allow a primary link", association))
+}
+
+/// Make the link between `record_id` and `target_id` the record's primary
+/// link for `association`, demoting its other links in the same transaction
+///
+/// The group is every link of the definition on `record_id`'s side. Returns
+/// the promoted link's id.
+pub(crate) async fn set_primary_association(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    record_id: Uuid,
+    association: &str,
+    target_id: Uuid,
+) -> Result<Uuid, ApiError> {
+    let mut tx = conn.begin().await?;
+
+    let def: Option<(Uuid, bool)> = sqlx::query_as(
+        "SELECT id, allow_primary FROM association_defs WHERE tenant_id = $1 AND name = $2 FOR UPDATE",
+    )
+    .bind(tenant_id)
+    .bind(association)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (def_id, allow_primary) =
+        def.ok_or_else(|| ApiError::NotFound(format!("Association '{}' not found", association)))?;
+    if !allow_primary {
+        return Err(primary_not_allowed(association));
+    }
+
+    let link: Option<(Uuid, Uuid)> = sqlx::query_as(
+        r#"
+        SELECT id, source_id FROM associations
+        WHERE tenant_id = $1 AND association_def_id = $2
+          AND ((source_id = $3 AND target_id = $4) OR (source_id = $4 AND target_id = $3))
+        ORDER BY created_at
+        LIMIT 1
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(def_id)
+    .bind(record_id)
+    .bind(target_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (link_id, source_id) =
+        link.ok_or_else(|| ApiError::NotFound(format!("Records are not linked through '{}'", association)))?;
+
+    sqlx::query(
+        r#"
+        UPDATE associations SET is_primary = (id = $4), updated_at = NOW()
+        WHERE tenant_id = $1 AND association_def_id = $2
+          AND (CASE WHEN $5 THEN source_id ELSE target_id END) = $3
+          AND (is_primary OR id = $4)
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(def_id)
+    .bind(record_id)
+    .bind(link_id)
+    .bind(source_id == record_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(link_id)
+}
+
+/// A link that prevents deleting a record because its definition does not cascade
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct BlockingLink {
+    pub association_id: Uuid,
+    pub association: String,
+    pub related_entity: String,
+    pub related_id: Uuid,
+}
+
+/// Links to `record_id` whose definition has `cascade_delete = false`
+pub(crate) async fn blocking_links(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    record_id: Uuid,
+) -> Result<Vec<BlockingLink>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT a.id AS association_id, d.name AS association,
+               CASE WHEN a.source_id = $2 THEN d.target_entity ELSE d.source_entity END AS related_entity,
+               CASE WHEN a.source_id = $2 THEN a.target_id ELSE a.source_id END AS related_id
+        FROM associations a
+        JOIN association_defs d ON d.id = a.association_def_id
+        WHERE a.tenant_id = $1 AND (a.source_id = $2 OR a.target_id = $2) AND NOT d.cascade_delete
+        ORDER BY d.name, a.created_at
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(record_id)
+    .fetch_all(conn)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::AuditLogger;
+    use serde_json::json;
+    use test_support::TestTenant;
+
+    /// Tenant with a contact -> company `works_at` definition
+    struct Fixture {
+        tenant: TestTenant,
+        def_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Associations Test").await;
+            let def_id = sqlx::query_scalar(
+                "INSERT INTO association_defs
+                    (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary)
+                 VALUES ($1, $2, 'contact', 'company', 'works_at', 'Works At', 'Employees', 'many_to_one', 'employee', 'employer', true)
+                 RETURNING id",
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant.id)
+            .fetch_one(&tenant.pool)
+            .await
+            .unwrap();
+            Self { tenant, def_id }
+        }
+
+        fn link_request(&self, source_id: Uuid, target_id: Uuid, is_primary: bool) -> CreateAssociationRequest {
+            CreateAssociationRequest { association_def_id: self.def_id, source_id, target_id, role: None, is_primary }
+        }
+
+        async fn primary_targets(&self, source_id: Uuid) -> Vec<Uuid> {
+            sqlx::query_scalar("SELECT target_id FROM associations WHERE source_id = $1 AND is_primary")
+                .bind(source_id)
+                .fetch_all(&self.tenant.pool)
+                .await
+                .unwrap()
+        }
+
+        async fn history(&self, resource_type: &str, id: Uuid) -> Vec<AuditLogEntry> {
+            AuditLogger::new(self.tenant.pool.clone())
+                .query_resource_history(self.tenant.id, resource_type, id)
+                .await
+                .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_linking_is_audited_on_both_records() {
+        let fx = Fixture::new().await;
+        let (contact, company) = (Uuid::new_v4(), Uuid::new_v4());
+        let user = Uuid::new_v4();
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        let req = CreateAssociationRequest {
+            association_def_id: fx.def_id,
+            source_id: contact,
+            target_id: company,
+            role: Some("Head of Sales".to_string()),
+            is_primary: true,
+        };
+        let link = link_records(&mut conn, fx.tenant.id, Some(user), &req).await.unwrap();
+
+        for (resource_type, id) in [("contact", contact), ("company", company)] {
+            let history = fx.history(resource_type, id).await;
+            assert_eq!(history.len(), 1, "{} timeline", resource_type);
+            let entry = &history[0];
+            assert_eq!(entry.action, "association_linked");
+            assert_eq!(entry.user_id, Some(user));
+            assert_eq!(entry.details["association"], json!("works_at"));
+            assert_eq!(entry.details["association_id"], json!(link.id));
+            assert_eq!((&entry.details["source_id"], &entry.details["target_id"]), (&json!(contact), &json!(company)));
+            assert_eq!((&entry.details["source_role"], &entry.details["target_role"]), (&json!("employee"), &json!("employer")));
+            assert_eq!(entry.details["role"], json!("Head of Sales"));
+        }
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_unlinking_is_audited_on_both_records() {
+        let fx = Fixture::new().await;
+        let (contact, company) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let req = CreateAssociationRequest {
+            association_def_id: fx.def_id,
+            source_id: contact,
+            target_id: company,
+            role: None,
+            is_primary: false,
+        };
+        let link = link_records(&mut conn, fx.tenant.id, None, &req).await.unwrap();
+
+        assert!(unlink_records(&mut conn, fx.tenant.id, None, link.id).await.unwrap());
+        assert!(!unlink_records(&mut conn, fx.tenant.id, None, link.id).await.unwrap());
+
+        for (resource_type, id) in [("contact", contact), ("company", company)] {
+            let actions: Vec<String> = fx.history(resource_type, id).await.into_iter().map(|e| e.action).collect();
+            assert_eq!(actions.len(), 2, "{} timeline", resource_type);
+            assert!(actions.contains(&"association_unlinked".to_string()));
+        }
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_many_to_one_rejects_second_primary_link() {
+        let fx = Fixture::new().await;
+        let contact = Uuid::new_v4();
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let req = |target_id: Uuid, is_primary: bool| CreateAssociationRequest {
+            association_def_id: fx.def_id,
+            source_id: contact,
+            target_id,
+            role: None,
+            is_primary,
+        };
+
+        link_records(&mut conn, fx.tenant.id, None, &req(Uuid::new_v4(), true)).await.unwrap();
+        let second = link_records(&mut conn, fx.tenant.id, None, &req(Uuid::new_v4(), true)).await;
+        assert!(matches!(second, Err(ApiError::Conflict(msg)) if msg.contains("primary")));
+        // Further non-primary links are fine
+        link_records(&mut conn, fx.tenant.id, None, &req(Uuid::new_v4(), false)).await.unwrap();
+
+        let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM associations WHERE source_id = $1")
+            .bind(contact)
+            .fetch_one(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(links, 2);
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_promoting_primary_demotes_previous() {
+        let fx = Fixture::new().await;
+        let contact = Uuid::new_v4();
+        let (old_employer, new_employer) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        link_records(&mut conn, fx.tenant.id, None, &fx.link_request(contact, old_employer, true)).await.unwrap();
+        let link = link_records(&mut conn, fx.tenant.id, None, &fx.link_request(contact, new_employer, false)).await.unwrap();
+
+        let promoted = set_primary_association(&mut conn, fx.tenant.id, contact, "works_at", new_employer).await.unwrap();
+
+        assert_eq!(promoted, link.id);
+        assert_eq!(fx.primary_targets(contact).await, vec![new_employer]);
+        // Promoting the current primary again is a no-op
+        set_primary_association(&mut conn, fx.tenant.id, contact, "works_at", new_employer).await.unwrap();
+        assert_eq!(fx.primary_targets(contact).await, vec![new_employer]);
+        assert!(matches!(
+            set_primary_association(&mut conn, fx.tenant.id, contact, "works_at", Uuid::new_v4()).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_association_without_primary_rejects_flag() {
+        let fx = Fixture::new().await;
+        sqlx::query("UPDATE association_defs SET allow_primary = false WHERE id = $1")
+            .bind(fx.def_id)
+            .execute(&fx.tenant.pool)
+            .await
+            .unwrap();
+        let (contact, company) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        let flagged = link_records(&mut conn, fx.tenant.id, None, &fx.link_request(contact, company, true)).await;
+        assert!(matches!(flagged, Err(ApiError::BadRequest(msg)) if msg.contains("does not allow a primary")));
+
+        link_records(&mut conn, fx.tenant.id, None, &fx.link_request(contact, company, false)).await.unwrap();
+        let promoted = set_primary_association(&mut conn, fx.tenant.id, contact, "works_at", company).await;
+        assert!(matches!(promoted, Err(ApiError::BadRequest(_))));
+        assert!(fx.primary_targets(contact).await.is_empty());
+
+        drop(conn);
+        fx.cleanup().await;
+    }
 }
@@ -10,13 +10,16 @@ use axum::{
     response::IntoResponse,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
+use sqlx::{types::Json as SqlJson, Row};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::state::AppState;
 use crate::middleware::tenant::ResolvedTenant;
+use crate::middleware::audit_log::{redact_pii, FieldChange};
+use crate::middleware::permission::{can_view_audit_pii, AuthenticatedUser};
 
 /// Audit log entry returned by API
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,7 +30,10 @@ pub struct AuditLogResponse {
     pub action: String,
     pub resource_type: String,
     pub resource_id: Option<String>,
-    pub changes: Option<serde_json::Value>,
+    pub details: serde_json::Value,
+    /// Field-level before/after values; PII values are redacted unless the
+    /// viewer has audit PII access
+    pub changes: Vec<FieldChange>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -48,6 +54,7 @@ pub struct AuditQuery {
 pub async fn list_audit_logs(
     State(state): State<Arc<AppState>>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Query(query): Query<AuditQuery>,
 ) -> impl IntoResponse {
     let limit = query.limit.unwrap_or(100).min(500);
@@ -55,14 +62,14 @@ pub async fn list_audit_logs(
     
     let sql = r#"
         SELECT 
-            id, tenant_id, user_id, action, resource_type, resource_id,
-            changes, ip_address, user_agent, created_at
+            id, tenant_id, user_id::text AS user_id, action, resource_type, resource_id::text AS resource_id,
+            details, changes, ip_address, user_agent, created_at
         FROM audit_logs
         WHERE tenant_id = $1
             AND ($2::text IS NULL OR resource_type = $2)
-            AND ($3::text IS NULL OR resource_id = $3)
+            AND ($3::text IS NULL OR resource_id::text = $3)
             AND ($4::text IS NULL OR action = $4)
-            AND ($5::text IS NULL OR user_id = $5)
+            AND ($5::text IS NULL OR user_id::text = $5)
         ORDER BY created_at DESC
         LIMIT $6 OFFSET $7
     "#;
@@ -80,20 +87,10 @@ pub async fn list_audit_logs(
     
     match rows {
         Ok(results) => {
-            let logs: Vec<AuditLogResponse> = results.iter().map(|row| {
-                AuditLogResponse {
-                    id: row.get("id"),
-                    tenant_id: row.get("tenant_id"),
-                    user_id: row.try_get("user_id").ok(),
-                    action: row.get("action"),
-                    resource_type: row.get("resource_type"),
-                    resource_id: row.try_get("resource_id").ok(),
-                    changes: row.try_get("changes").ok(),
-                    ip_address: row.try_get("ip_address").ok(),
-                    user_agent: row.try_get("user_agent").ok(),
-                    created_at: row.get("created_at"),
-                }
-            }).collect();
+            let mut logs: Vec<AuditLogResponse> = results.iter().map(audit_log_from_row).collect();
+            if !user.is_some_and(|axum::Extension(u)| can_view_audit_pii(&u)) {
+                redact_for_viewer(&state, tenant.id, &mut logs).await;
+            }
             Json(logs).into_response()
         }
         Err(e) => {
@@ -106,6 +103,7 @@ pub async fn list_audit_logs(
 pub async fn get_entity_audit_logs(
     State(state): State<Arc<AppState>>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Path((entity_type, entity_id)): Path<(String, Uuid)>,
     Query(query): Query<AuditQuery>,
 ) -> impl IntoResponse {
@@ -114,8 +112,8 @@ pub async fn get_entity_audit_logs(
     
     let sql = r#"
         SELECT 
-            id, tenant_id, user_id, action, resource_type, resource_id,
-            changes, ip_address, user_agent, created_at
+            id, tenant_id, user_id::text AS user_id, action, resource_type, resource_id::text AS resource_id,
+            details, changes, ip_address, user_agent, created_at
         FROM audit_logs
         WHERE tenant_id = $1
             AND resource_type = $2
@@ -127,7 +125,7 @@ pub async fn get_entity_audit_logs(
     let rows = sqlx::query(sql)
         .bind(tenant.id)
         .bind(&entity_type)
-        .bind(entity_id.to_string())
+        .bind(entity_id)
         .bind(limit)
         .bind(offset)
         .fetch_all(&state.pool)
@@ -135,20 +133,10 @@ pub async fn get_entity_audit_logs(
     
     match rows {
         Ok(results) => {
-            let logs: Vec<AuditLogResponse> = results.iter().map(|row| {
-                AuditLogResponse {
-                    id: row.get("id"),
-                    tenant_id: row.get("tenant_id"),
-                    user_id: row.try_get("user_id").ok(),
-                    action: row.get("action"),
-                    resource_type: row.get("resource_type"),
-                    resource_id: row.try_get("resource_id").ok(),
-                    changes: row.try_get("changes").ok(),
-                    ip_address: row.try_get("ip_address").ok(),
-                    user_agent: row.try_get("user_agent").ok(),
-                    created_at: row.get("created_at"),
-                }
-            }).collect();
+            let mut logs: Vec<AuditLogResponse> = results.iter().map(audit_log_from_row).collect();
+            if !user.is_some_and(|axum::Extension(u)| can_view_audit_pii(&u)) {
+                redact_for_viewer(&state, tenant.id, &mut logs).await;
+            }
             Json(logs).into_response()
         }
         Err(e) => {
@@ -157,6 +145,41 @@ pub async fn get_entity_audit_logs(
     }
 }
 
+fn audit_log_from_row(row: &sqlx::postgres::PgRow) -> AuditLogResponse {
+    AuditLogResponse {
+        id: row.get("id"),
+        tenant_id: row.get("tenant_id"),
+        user_id: row.try_get("user_id").ok(),
+        action: row.get("action"),
+        resource_type: row.get("resource_type"),
+        resource_id: row.try_get("resource_id").ok(),
+        details: row.try_get("details").unwrap_or_default(),
+        changes: row.try_get::<SqlJson<Vec<FieldChange>>, _>("changes").map(|c| c.0).unwrap_or_default(),
+        ip_address: row.try_get("ip_address").ok(),
+        user_agent: row.try_get("user_agent").ok(),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Hide PII field values, looking up each entity type's fields once
+///
+/// If an entity type's fields cannot be loaded, all of its values are hidden.
+async fn redact_for_viewer(state: &AppState, tenant_id: Uuid, logs: &mut [AuditLogResponse]) {
+    let mut fields_by_type = HashMap::new();
+    for log in logs.iter_mut().filter(|log| !log.changes.is_empty()) {
+        if !fields_by_type.contains_key(&log.resource_type) {
+            let fields = state.metadata.get_fields_by_entity_name(tenant_id, &log.resource_type).await.ok();
+            fields_by_type.insert(log.resource_type.clone(), fields);
+        }
+        match &fields_by_type[&log.resource_type] {
+            Some(fields) => redact_pii(&mut log.changes, fields),
+            None => {
+                log.changes.iter_mut().for_each(FieldChange::redact);
+            }
+        }
+    }
+}
+
 /// Create audit routes
 pub fn audit_routes() -> Router<Arc<AppState>> {
     Router::new()
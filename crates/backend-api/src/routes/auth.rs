@@ -2,30 +2,44 @@
 
 use axum::{
     Router,
-    routing::{get, post},
-    extract::{State, Json},
-    http::{header::SET_COOKIE, HeaderMap},
+    routing::{delete, get, post},
+    extract::{ConnectInfo, Path, State, Json},
+    http::{header::{SET_COOKIE, USER_AGENT}, HeaderMap, StatusCode},
 };
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use core_auth::middleware::ExtractAuth;
+use core_auth::session::{issue_jwt, SessionInfo};
 use core_auth::password::hash_password;
-use core_models::{CreateUser, UserInfo, UserRole, Tenant, TenantStatus, PlanTier};
+use core_auth::AuthError;
+use core_models::{CreateUser, User, UserInfo, UserRole, Tenant, TenantStatus, PlanTier};
 use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::state::AppState;
 use crate::error::ApiError;
+use crate::gateway::{client_ip, RateLimiter};
+use crate::jobs::auth_email::{enqueue_email_verification, enqueue_password_reset};
+use crate::middleware::permission::AuthenticatedUser;
 use crate::seed::seed_new_tenant;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/login", post(login))
         .route("/logout", post(logout))
+        .route("/token", post(issue_token))
+        .route("/password-reset", post(request_password_reset))
+        .route("/password-reset/confirm", post(reset_password))
+        .route("/verify-email", post(verify_email))
+        .route("/resend-verification", post(resend_verification))
         .route("/register", post(register))
         .route("/register-tenant", post(register_tenant))
         .route("/check-subdomain", post(check_subdomain))
         .route("/me", get(me))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/revoke-others", post(revoke_other_sessions))
+        .route("/sessions/:id", delete(revoke_session))
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,8 +55,13 @@ pub struct LoginResponse {
     pub token: String,
 }
 
+/// Longest user agent kept with a session
+const MAX_USER_AGENT_LEN: usize = 512;
+
 async fn login(
     State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    request_headers: HeaderMap,
     Json(req): Json<LoginRequest>,
 ) -> Result<(HeaderMap, Json<LoginResponse>), ApiError> {
     // Resolve tenant
@@ -51,13 +70,16 @@ async fn login(
         .await?;
 
     // Authenticate user
-    let user = state.user_service
-        .authenticate(tenant.id, &req.email, &req.password)
-        .await?;
+    let ip = client_ip(connect_info.map(|c| c.0.ip()), &request_headers, &state.trusted_proxies);
+    let user = authenticate_throttled(&state, tenant.id, &req.email, &req.password, ip).await?;
 
-    // Create session
+    // Create session, noting the device so the user can recognise it later
+    let user_agent = request_headers
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect());
     let (_session, token) = state.session_service
-        .create_session(&user, None, None)
+        .create_session(&user, user_agent, ip.map(|ip| ip.to_string()))
         .await?;
 
     // Set cookie
@@ -75,6 +97,72 @@ async fn login(
     })))
 }
 
+/// Check a password behind the login throttle: locked accounts and IPs are
+/// refused outright, earlier failures slow the attempt down, and the
+/// outcome is counted
+async fn authenticate_throttled(
+    state: &AppState,
+    tenant_id: Uuid,
+    email: &str,
+    password: &str,
+    ip: Option<IpAddr>,
+) -> Result<User, ApiError> {
+    let delay = state.login_throttle.check(tenant_id, email, ip).await?;
+    if delay > Duration::zero() {
+        tokio::time::sleep(delay.to_std().unwrap_or_default()).await;
+    }
+
+    match state.user_service.authenticate(tenant_id, email, password).await {
+        Ok(user) => {
+            state.login_throttle.record_success(tenant_id, email).await?;
+            Ok(user)
+        }
+        Err(AuthError::InvalidCredentials) => {
+            state.login_throttle.record_failure(tenant_id, email, ip).await?;
+            Err(AuthError::InvalidCredentials.into())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// How long an API bearer token lasts
+const API_TOKEN_TTL_MINUTES: i64 = 60;
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the token expires
+    pub expires_in: i64,
+}
+
+/// Exchange credentials for a bearer token, for API clients that don't keep
+/// a session cookie
+async fn issue_token(
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(req): Json<LoginRequest>,
+) -> Result<Json<TokenResponse>, ApiError> {
+    let keys = state.jwt_keys.as_ref()
+        .ok_or_else(|| ApiError::BadRequest("API tokens are not enabled".to_string()))?;
+
+    let tenant = state.tenant_service
+        .get_by_subdomain(&req.tenant_subdomain)
+        .await?;
+    let ip = client_ip(connect_info.map(|c| c.0.ip()), &headers, &state.trusted_proxies);
+    let user = authenticate_throttled(&state, tenant.id, &req.email, &req.password, ip).await?;
+    require_verified_email(&state, user.id).await?;
+
+    let ttl = Duration::minutes(API_TOKEN_TTL_MINUTES);
+    let access_token = issue_jwt(keys, &user.into(), ttl)?;
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: ttl.num_seconds(),
+    }))
+}
+
 async fn logout(
     State(state): State<Arc<AppState>>,
     auth: Option<ExtractAuth>,
@@ -96,6 +184,175 @@ async fn logout(
     Ok((headers, Json(serde_json::json!({ "success": true }))))
 }
 
+// ============================================================================
+// PASSWORD RESET
+// ============================================================================
+
+/// Same answer whether or not the account exists
+const PASSWORD_RESET_SENT: &str = "If an account exists for that email, a reset link is on its way";
+
+/// Window the password reset limits apply over
+const PASSWORD_RESET_WINDOW_SECS: i64 = 900;
+
+/// Window for the per-user limit on verification resends
+pub const VERIFICATION_RESEND_WINDOW_SECS: i64 = 3600;
+
+/// Per-email and per-IP limits on password reset requests
+pub struct PasswordResetLimiter {
+    by_email: RateLimiter,
+    by_ip: RateLimiter,
+}
+
+impl PasswordResetLimiter {
+    pub fn new() -> Self {
+        Self {
+            by_email: RateLimiter::new(3, PASSWORD_RESET_WINDOW_SECS),
+            by_ip: RateLimiter::new(20, PASSWORD_RESET_WINDOW_SECS),
+        }
+    }
+
+    async fn allow(&self, tenant_subdomain: &str, email: &str, ip: Option<IpAddr>) -> bool {
+        let email_ok = self
+            .by_email
+            .check_limit(&format!("{}:{}", tenant_subdomain, email.to_lowercase()))
+            .await;
+        let ip_ok = match ip {
+            Some(ip) => self.by_ip.check_limit(&ip.to_string()).await,
+            None => true,
+        };
+        email_ok && ip_ok
+    }
+}
+
+impl Default for PasswordResetLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+    pub tenant_subdomain: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Start a password reset
+///
+/// The account is looked up after responding, so neither the answer nor
+/// how long it takes says whether the email is registered.
+async fn request_password_reset(
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(req): Json<PasswordResetRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let ip = client_ip(connect_info.map(|c| c.0.ip()), &headers, &state.trusted_proxies);
+    if !state.password_reset_limiter.allow(&req.tenant_subdomain, &req.email, ip).await {
+        return Err(AuthError::RateLimited { retry_after_secs: PASSWORD_RESET_WINDOW_SECS }.into());
+    }
+
+    let worker = state.clone();
+    tokio::spawn(async move {
+        let Ok(tenant) = worker.tenant_service.get_by_subdomain(&req.tenant_subdomain).await else { return };
+        match worker.password_reset_service.request_password_reset(tenant.id, &req.email).await {
+            Ok(Some(reset)) => {
+                if let Err(e) = enqueue_password_reset(&worker.pool, &reset).await {
+                    tracing::error!(user_id = %reset.user_id, "Failed to queue password reset email: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::error!(tenant_id = %tenant.id, "Password reset request failed: {}", e),
+        }
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "message": PASSWORD_RESET_SENT }))))
+}
+
+/// Finish a password reset; signs the user out everywhere
+async fn reset_password(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = state.password_reset_service
+        .reset_password(&req.token, &req.new_password)
+        .await?;
+    tracing::info!(user_id = %user_id, "Password reset");
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+// ============================================================================
+// EMAIL VERIFICATION
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+/// Refuse users who haven't verified their email yet
+pub async fn require_verified_email(state: &AppState, user_id: Uuid) -> Result<(), ApiError> {
+    if state.email_verification_service.is_verified(user_id).await? {
+        Ok(())
+    } else {
+        Err(AuthError::EmailNotVerified.into())
+    }
+}
+
+/// Issue a verification token and queue the mail carrying it
+///
+/// Failures are logged, not returned: the user can always ask for a resend.
+async fn send_verification_email(state: &AppState, user_id: Uuid) {
+    match state.email_verification_service.issue(user_id).await {
+        Ok(Some(verification)) => {
+            if let Err(e) = enqueue_email_verification(&state.pool, &verification).await {
+                tracing::error!(user_id = %user_id, "Failed to queue verification email: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => tracing::error!(user_id = %user_id, "Failed to issue verification token: {}", e),
+    }
+}
+
+async fn verify_email(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<VerifyEmailRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = state.email_verification_service.verify_email(&req.token).await?;
+    tracing::info!(user_id = %user_id, "Email verified");
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Send the signed-in user a fresh verification mail; earlier links stop
+/// working
+async fn resend_verification(
+    State(state): State<Arc<AppState>>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let axum::Extension(user) = user.ok_or(ApiError::Unauthorized)?;
+    if state.email_verification_service.is_verified(user.id).await? {
+        return Err(ApiError::Conflict("Email is already verified".to_string()));
+    }
+    if !state.verification_resend_limiter.check_limit(&user.id.to_string()).await {
+        return Err(AuthError::RateLimited { retry_after_secs: VERIFICATION_RESEND_WINDOW_SECS }.into());
+    }
+
+    let verification = state.email_verification_service
+        .issue(user.id)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Email is already verified".to_string()))?;
+    enqueue_email_verification(&state.pool, &verification)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to queue verification email: {}", e)))?;
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "message": "Verification email sent" }))))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RegisterRequest {
     pub email: String,
@@ -123,6 +380,7 @@ async fn register(
             role: UserRole::Member,
         })
         .await?;
+    send_verification_email(&state, user.id).await;
 
     Ok(Json(user.into()))
 }
@@ -133,6 +391,57 @@ async fn me(
     Ok(Json(auth.0.user))
 }
 
+// ============================================================================
+// SESSIONS
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct SessionEntry {
+    #[serde(flatten)]
+    pub session: SessionInfo,
+    /// The session this request was made with
+    pub current: bool,
+}
+
+/// The signed-in user's active sessions
+async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    ExtractAuth(auth): ExtractAuth,
+) -> Result<Json<Vec<SessionEntry>>, ApiError> {
+    let sessions = state.session_service.list_for_user(auth.user.id).await?;
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| SessionEntry { current: session.id == auth.session_id, session })
+            .collect(),
+    ))
+}
+
+/// Sign one of the user's devices out
+async fn revoke_session(
+    State(state): State<Arc<AppState>>,
+    ExtractAuth(auth): ExtractAuth,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if !state.session_service.revoke(auth.user.id, id).await? {
+        return Err(ApiError::NotFound("Session not found".to_string()));
+    }
+    tracing::info!(user_id = %auth.user.id, session_id = %id, "Session revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Sign out every device but this one
+async fn revoke_other_sessions(
+    State(state): State<Arc<AppState>>,
+    ExtractAuth(auth): ExtractAuth,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let revoked = state.session_service
+        .revoke_all_except(auth.user.id, auth.session_id)
+        .await?;
+    tracing::info!(user_id = %auth.user.id, revoked, "Other sessions revoked");
+    Ok(Json(serde_json::json!({ "revoked": revoked })))
+}
+
 // ============================================================================
 // TENANT REGISTRATION
 // ============================================================================
@@ -332,6 +641,7 @@ async fn register_tenant(
     seed_new_tenant(tenant_id, &state.pool)
         .await
         .map_err(|e| ApiError::Internal(format!("Failed to seed tenant data: {}", e)))?;
+    send_verification_email(&state, admin_id).await;
     
     Ok(Json(RegisterTenantResponse {
         success: true,
@@ -374,3 +684,537 @@ fn is_valid_subdomain(subdomain: &str) -> bool {
     subdomain.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_auth::password::{hash_password, verify_password};
+    use core_auth::password_reset::PasswordResetService;
+    use crate::jobs::auth_email::{SEND_PASSWORD_RESET_EMAIL, SEND_VERIFICATION_EMAIL};
+    use core_auth::email_verification::EmailVerificationService;
+    use core_integrations::encryption::KeyRing;
+    use sqlx::PgPool;
+    use std::time::{Duration, Instant};
+    use core_auth::login_throttle::{LoginThrottle, ThrottlePolicy};
+    use test_support::get_test_pool;
+
+    async fn setup(pool: &PgPool) -> (Uuid, String, Uuid) {
+        let (tenant_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let subdomain = format!("reset-{}", tenant_id.simple());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Reset Test', $2)")
+            .bind(tenant_id)
+            .bind(&subdomain)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, 'owner@example.com', 'Owner', $3)")
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(hash_password("Old-Passw0rd!").unwrap())
+            .execute(pool)
+            .await
+            .unwrap();
+        (tenant_id, subdomain, user_id)
+    }
+
+    async fn cleanup(pool: &PgPool, tenant_id: Uuid) {
+        for sql in [
+            "DELETE FROM job_queue WHERE tenant_id = $1",
+            "DELETE FROM password_reset_tokens WHERE tenant_id = $1",
+            "DELETE FROM email_verification_tokens WHERE tenant_id = $1",
+            "DELETE FROM login_attempts WHERE key LIKE $1::text || ':%'",
+            "DELETE FROM sessions WHERE tenant_id = $1",
+            "DELETE FROM users WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(pool).await.unwrap();
+        }
+    }
+
+    fn asking(subdomain: &str, email: &str) -> Json<PasswordResetRequest> {
+        Json(PasswordResetRequest { email: email.to_string(), tenant_subdomain: subdomain.to_string() })
+    }
+
+    /// The token in the latest `job_type` mail queued for `user_id`, once it's there
+    async fn mailed_token(pool: &PgPool, job_type: &str, user_id: Uuid) -> String {
+        for _ in 0..50 {
+            let payload: Option<serde_json::Value> = sqlx::query_scalar(
+                "SELECT payload FROM job_queue WHERE job_type = $1 AND payload->>'user_id' = $2::text
+                 ORDER BY created_at DESC LIMIT 1",
+            )
+            .bind(job_type)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+            .unwrap();
+            if let Some(payload) = payload {
+                let encrypted = hex::decode(payload["token_encrypted"].as_str().unwrap()).unwrap();
+                return String::from_utf8(KeyRing::from_env().decrypt(&encrypted).unwrap()).unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("no {} was queued", job_type);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_password_reset_is_single_use() {
+        let pool = get_test_pool().await;
+        let (tenant_id, subdomain, user_id) = setup(&pool).await;
+        let state = Arc::new(AppState::new(pool.clone()));
+        sqlx::query("INSERT INTO sessions (id, tenant_id, user_id, token_hash, expires_at) VALUES (gen_random_uuid(), $1, $2, $3, NOW() + INTERVAL '1 day')")
+            .bind(tenant_id)
+            .bind(user_id)
+            .bind(format!("reset-test-{}", user_id))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (status, _) = request_password_reset(State(state.clone()), None, HeaderMap::new(), asking(&subdomain, "owner@example.com")).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let token = mailed_token(&pool, SEND_PASSWORD_RESET_EMAIL, user_id).await;
+
+        // A weak password doesn't spend the token
+        assert!(reset_password(
+            State(state.clone()),
+            Json(ResetPasswordRequest { token: token.clone(), new_password: "short".to_string() }),
+        )
+        .await
+        .is_err());
+
+        let Json(reset) = reset_password(
+            State(state.clone()),
+            Json(ResetPasswordRequest { token: token.clone(), new_password: "N3w-Passw0rd!".to_string() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(reset["success"], true);
+        let hash: String = sqlx::query_scalar("SELECT password_hash FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(verify_password("N3w-Passw0rd!", &hash).unwrap());
+        let sessions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(sessions, 0);
+
+        // Spent
+        assert!(matches!(
+            reset_password(
+                State(state.clone()),
+                Json(ResetPasswordRequest { token, new_password: "An0ther-Passw0rd!".to_string() }),
+            )
+            .await,
+            Err(ApiError::Auth(AuthError::InvalidToken))
+        ));
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_expired_reset_token_is_refused() {
+        let pool = get_test_pool().await;
+        let (tenant_id, _, _) = setup(&pool).await;
+        let resets = PasswordResetService::with_ttl(pool.clone(), chrono::Duration::minutes(-1));
+
+        let issued = resets.request_password_reset(tenant_id, "owner@example.com").await.unwrap().unwrap();
+        assert!(matches!(
+            resets.reset_password(&issued.token, "N3w-Passw0rd!").await,
+            Err(AuthError::TokenExpired)
+        ));
+        assert!(resets.request_password_reset(tenant_id, "nobody@example.com").await.unwrap().is_none());
+
+        // The address matches in any case, as the rate limiter keys it
+        let issued = resets.request_password_reset(tenant_id, "Owner@Example.COM").await.unwrap().unwrap();
+        assert_eq!(issued.email, "owner@example.com");
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_reset_request_does_not_reveal_accounts() {
+        let pool = get_test_pool().await;
+        let (tenant_id, subdomain, user_id) = setup(&pool).await;
+        let state = Arc::new(AppState::new(pool.clone()));
+
+        let started = Instant::now();
+        let (known_status, Json(known)) =
+            request_password_reset(State(state.clone()), None, HeaderMap::new(), asking(&subdomain, "owner@example.com")).await.unwrap();
+        let known_took = started.elapsed();
+        let started = Instant::now();
+        let (unknown_status, Json(unknown)) =
+            request_password_reset(State(state.clone()), None, HeaderMap::new(), asking(&subdomain, "ghost@example.com")).await.unwrap();
+        let unknown_took = started.elapsed();
+
+        assert_eq!(known_status, unknown_status);
+        assert_eq!(known, unknown);
+        // Neither waits on the database
+        assert!(known_took < Duration::from_millis(50) && unknown_took < Duration::from_millis(50));
+
+        mailed_token(&pool, SEND_PASSWORD_RESET_EMAIL, user_id).await;
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM job_queue WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    fn signed_in(user_id: Uuid, tenant_id: Uuid) -> Option<axum::Extension<AuthenticatedUser>> {
+        Some(axum::Extension(AuthenticatedUser {
+            id: user_id,
+            tenant_id,
+            email: "new@example.com".to_string(),
+            name: "New".to_string(),
+            role: "member".to_string(),
+            roles: Vec::new(),
+        }))
+    }
+
+    async fn verify(state: &Arc<AppState>, token: &str) -> Result<Json<serde_json::Value>, ApiError> {
+        verify_email(State(state.clone()), Json(VerifyEmailRequest { token: token.to_string() })).await
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_new_users_verify_their_email() {
+        let pool = get_test_pool().await;
+        let (tenant_id, subdomain, _) = setup(&pool).await;
+        let state = Arc::new(AppState::new(pool.clone()));
+
+        let Json(user) = register(
+            State(state.clone()),
+            Json(RegisterRequest {
+                email: "new@example.com".to_string(),
+                password: "N3w-Passw0rd!".to_string(),
+                name: "New".to_string(),
+                tenant_subdomain: subdomain,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            require_verified_email(&state, user.id).await,
+            Err(ApiError::Auth(AuthError::EmailNotVerified))
+        ));
+
+        let token = mailed_token(&pool, SEND_VERIFICATION_EMAIL, user.id).await;
+        assert_eq!(verify(&state, &token).await.unwrap().0["success"], true);
+        require_verified_email(&state, user.id).await.unwrap();
+        let level: i32 = sqlx::query_scalar("SELECT verification_level FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(level, 1);
+
+        // Spent
+        assert!(matches!(verify(&state, &token).await, Err(ApiError::Auth(AuthError::InvalidToken))));
+        assert!(matches!(verify(&state, "not-a-token").await, Err(ApiError::Auth(AuthError::InvalidToken))));
+        // Nothing left to resend
+        assert!(matches!(
+            resend_verification(State(state.clone()), signed_in(user.id, tenant_id)).await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_expired_verification_token_is_refused() {
+        let pool = get_test_pool().await;
+        let (tenant_id, _, user_id) = setup(&pool).await;
+        let verifications = EmailVerificationService::with_ttl(pool.clone(), chrono::Duration::minutes(-1));
+
+        let issued = verifications.issue(user_id).await.unwrap().unwrap();
+        assert!(matches!(verifications.verify_email(&issued.token).await, Err(AuthError::TokenExpired)));
+        assert!(!verifications.is_verified(user_id).await.unwrap());
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_resend_replaces_the_verification_token() {
+        let pool = get_test_pool().await;
+        let (tenant_id, _, user_id) = setup(&pool).await;
+        let state = Arc::new(AppState::new(pool.clone()));
+
+        let first = state.email_verification_service.issue(user_id).await.unwrap().unwrap();
+        let (status, _) = resend_verification(State(state.clone()), signed_in(user_id, tenant_id)).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let second = mailed_token(&pool, SEND_VERIFICATION_EMAIL, user_id).await;
+        assert_ne!(first.token, second);
+
+        assert!(matches!(verify(&state, &first.token).await, Err(ApiError::Auth(AuthError::InvalidToken))));
+        assert_eq!(verify(&state, &second).await.unwrap().0["success"], true);
+
+        // Resends are limited per user
+        sqlx::query("UPDATE users SET email_verified = FALSE WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            let (status, _) = resend_verification(State(state.clone()), signed_in(user_id, tenant_id)).await.unwrap();
+            assert_eq!(status, StatusCode::ACCEPTED);
+        }
+        assert!(matches!(
+            resend_verification(State(state.clone()), signed_in(user_id, tenant_id)).await,
+            Err(ApiError::Auth(AuthError::RateLimited { .. }))
+        ));
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    async fn call(app: &Router, method: &str, uri: &str, session: Option<&str>, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        use tower::ServiceExt;
+        let mut request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header(USER_AGENT, "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0");
+        if let Some(token) = session {
+            request = request.header("cookie", format!("session={}", token));
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(axum::body::Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_revoked_sessions_stop_working() {
+        let pool = get_test_pool().await;
+        let (tenant_id, subdomain, _) = setup(&pool).await;
+        let state = Arc::new(AppState::new(pool.clone()));
+        let app = routes()
+            .layer(axum::middleware::from_fn_with_state(state.clone(), crate::middleware::auth::authenticate))
+            .with_state(state);
+        let credentials = serde_json::json!({
+            "email": "owner@example.com",
+            "password": "Old-Passw0rd!",
+            "tenant_subdomain": subdomain,
+        });
+        let login = || call(&app, "POST", "/login", None, credentials.clone());
+        let laptop = login().await.1["token"].as_str().unwrap().to_string();
+        let phone = login().await.1["token"].as_str().unwrap().to_string();
+
+        let (status, sessions) = call(&app, "GET", "/sessions", Some(&laptop), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        let sessions = sessions.as_array().unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions.iter().filter(|s| s["current"] == true).count(), 1);
+        assert_eq!(sessions[0]["device"], "Firefox on Linux");
+        let phone_id = sessions.iter().find(|s| s["current"] == false).unwrap()["id"].as_str().unwrap().to_string();
+
+        // The revoked session's very next request is unauthenticated
+        assert_eq!(call(&app, "GET", "/me", Some(&phone), serde_json::Value::Null).await.0, StatusCode::OK);
+        let (status, _) = call(&app, "DELETE", &format!("/sessions/{}", phone_id), Some(&laptop), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(call(&app, "GET", "/me", Some(&phone), serde_json::Value::Null).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(call(&app, "GET", "/me", Some(&laptop), serde_json::Value::Null).await.0, StatusCode::OK);
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_log_out_other_devices_keeps_this_one() {
+        let pool = get_test_pool().await;
+        let (tenant_id, subdomain, _) = setup(&pool).await;
+        let state = Arc::new(AppState::new(pool.clone()));
+        let app = routes()
+            .layer(axum::middleware::from_fn_with_state(state.clone(), crate::middleware::auth::authenticate))
+            .with_state(state);
+        let credentials = serde_json::json!({
+            "email": "owner@example.com",
+            "password": "Old-Passw0rd!",
+            "tenant_subdomain": subdomain,
+        });
+        let mut tokens = Vec::new();
+        for _ in 0..3 {
+            tokens.push(call(&app, "POST", "/login", None, credentials.clone()).await.1["token"].as_str().unwrap().to_string());
+        }
+
+        let (status, body) = call(&app, "POST", "/sessions/revoke-others", Some(&tokens[0]), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["revoked"], 2);
+        assert_eq!(call(&app, "GET", "/me", Some(&tokens[0]), serde_json::Value::Null).await.0, StatusCode::OK);
+        for other in &tokens[1..] {
+            assert_eq!(call(&app, "GET", "/me", Some(other), serde_json::Value::Null).await.0, StatusCode::UNAUTHORIZED);
+        }
+        let (_, sessions) = call(&app, "GET", "/sessions", Some(&tokens[0]), serde_json::Value::Null).await;
+        assert_eq!(sessions.as_array().unwrap().len(), 1);
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    fn strict_policy(lockout: chrono::Duration) -> ThrottlePolicy {
+        ThrottlePolicy {
+            max_account_failures: 3,
+            max_ip_failures: 5,
+            window: chrono::Duration::minutes(15),
+            lockout,
+            base_delay: chrono::Duration::milliseconds(10),
+            max_delay: chrono::Duration::milliseconds(40),
+        }
+    }
+
+    fn throttled_state(pool: &PgPool, lockout: chrono::Duration) -> AppState {
+        let mut state = AppState::new(pool.clone());
+        state.login_throttle = LoginThrottle::with_policy(pool.clone(), strict_policy(lockout));
+        state
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_repeated_failures_lock_the_account() {
+        let pool = get_test_pool().await;
+        let (tenant_id, _, _) = setup(&pool).await;
+        let state = throttled_state(&pool, chrono::Duration::minutes(15));
+        let login = |email: &'static str, password: &'static str| {
+            authenticate_throttled(&state, tenant_id, email, password, None)
+        };
+
+        // Each failure makes the next attempt wait longer
+        assert!(matches!(login("owner@example.com", "wrong").await, Err(ApiError::Auth(AuthError::InvalidCredentials))));
+        let delay = state.login_throttle.check(tenant_id, "owner@example.com", None).await.unwrap();
+        assert_eq!(delay, chrono::Duration::milliseconds(10));
+        assert!(matches!(login("owner@example.com", "wrong").await, Err(ApiError::Auth(AuthError::InvalidCredentials))));
+        let delay = state.login_throttle.check(tenant_id, "owner@example.com", None).await.unwrap();
+        assert_eq!(delay, chrono::Duration::milliseconds(20));
+
+        // The third failure locks; after that even the right password is refused
+        assert!(matches!(login("owner@example.com", "wrong").await, Err(ApiError::Auth(AuthError::InvalidCredentials))));
+        assert!(matches!(
+            login("OWNER@example.com", "Old-Passw0rd!").await,
+            Err(ApiError::Auth(AuthError::RateLimited { retry_after_secs })) if retry_after_secs > 0
+        ));
+
+        // An unknown email fails and locks exactly like a real one
+        for _ in 0..3 {
+            assert!(matches!(login("ghost@example.com", "wrong").await, Err(ApiError::Auth(AuthError::InvalidCredentials))));
+        }
+        assert!(matches!(login("ghost@example.com", "wrong").await, Err(ApiError::Auth(AuthError::RateLimited { .. }))));
+
+        // Unlocking lets the owner back in, and success wipes the slate
+        state.login_throttle.unlock(tenant_id, "owner@example.com").await.unwrap();
+        assert!(matches!(login("owner@example.com", "wrong").await, Err(ApiError::Auth(AuthError::InvalidCredentials))));
+        login("owner@example.com", "Old-Passw0rd!").await.unwrap();
+        for _ in 0..2 {
+            assert!(matches!(login("owner@example.com", "wrong").await, Err(ApiError::Auth(AuthError::InvalidCredentials))));
+        }
+        login("owner@example.com", "Old-Passw0rd!").await.unwrap();
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_lockout_expires() {
+        let pool = get_test_pool().await;
+        let (tenant_id, _, _) = setup(&pool).await;
+        let state = throttled_state(&pool, chrono::Duration::seconds(1));
+
+        for _ in 0..3 {
+            assert!(authenticate_throttled(&state, tenant_id, "owner@example.com", "wrong", None).await.is_err());
+        }
+        assert!(matches!(
+            authenticate_throttled(&state, tenant_id, "owner@example.com", "Old-Passw0rd!", None).await,
+            Err(ApiError::Auth(AuthError::RateLimited { .. }))
+        ));
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        // The lock is over and so is the count that led to it
+        assert_eq!(state.login_throttle.check(tenant_id, "owner@example.com", None).await.unwrap(), chrono::Duration::zero());
+        authenticate_throttled(&state, tenant_id, "owner@example.com", "Old-Passw0rd!", None).await.unwrap();
+
+        cleanup(&pool, tenant_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_failures_from_one_ip_lock_it() {
+        let pool = get_test_pool().await;
+        let (tenant_id, _, _) = setup(&pool).await;
+        let state = throttled_state(&pool, chrono::Duration::minutes(15));
+        let ip: IpAddr = format!("198.51.100.{}", tenant_id.as_bytes()[0]).parse().unwrap();
+
+        // Spraying one password over many accounts trips the IP limit
+        for n in 0..5 {
+            let email = format!("user{}@example.com", n);
+            assert!(matches!(
+                authenticate_throttled(&state, tenant_id, &email, "Summer2024!", Some(ip)).await,
+                Err(ApiError::Auth(AuthError::InvalidCredentials))
+            ));
+        }
+        assert!(matches!(
+            authenticate_throttled(&state, tenant_id, "owner@example.com", "Old-Passw0rd!", Some(ip)).await,
+            Err(ApiError::Auth(AuthError::RateLimited { .. }))
+        ));
+        // ...while the same account from elsewhere is fine
+        authenticate_throttled(&state, tenant_id, "owner@example.com", "Old-Passw0rd!", None).await.unwrap();
+
+        sqlx::query("DELETE FROM login_attempts WHERE scope = 'ip' AND key = $1")
+            .bind(ip.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        cleanup(&pool, tenant_id).await;
+    }
+
+    /// Serve the API routes the way `main` does, with connect info
+    async fn serve_api(state: AppState) -> String {
+        let app = crate::routes::api_routes().with_state(Arc::new(state));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap()
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_failed_logins_over_http_lock_the_client_ip() {
+        let pool = get_test_pool().await;
+        let (tenant_id, subdomain, _) = setup(&pool).await;
+        let clear_ip = || sqlx::query("DELETE FROM login_attempts WHERE scope = 'ip' AND key = '127.0.0.1'").execute(&pool);
+        clear_ip().await.unwrap();
+
+        let base = serve_api(throttled_state(&pool, chrono::Duration::minutes(15))).await;
+        let client = reqwest::Client::new();
+        let login = |email: String, password: &str| {
+            client
+                .post(format!("{}/auth/login", base))
+                .json(&serde_json::json!({ "email": email, "password": password, "tenant_subdomain": subdomain }))
+                .send()
+        };
+
+        for n in 0..5 {
+            let response = login(format!("user{}@example.com", n), "Summer2024!").await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        }
+        // The peer address was counted, so even the right password is refused
+        let response = login("owner@example.com".to_string(), "Old-Passw0rd!").await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+
+        clear_ip().await.unwrap();
+        cleanup(&pool, tenant_id).await;
+    }
+}
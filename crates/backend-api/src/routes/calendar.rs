@@ -0,0 +1,277 @@
+//! Calendar routes - iCalendar feed of a user's events
+//!
+//! External calendar apps (Google, Apple, Outlook) poll the feed without a
+//! session, so it is authenticated by a per-user feed token passed in the
+//! query string. Only the token's SHA-256 hash is stored; issuing a new
+//! token or revoking it immediately invalidates the old URL.
+
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use core_auth::middleware::ExtractAuth;
+use core_engagement::{to_ics, CalendarEvent, EventStatus, OccurrenceOverride};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Past non-recurring events older than this are left out of the feed
+const FEED_HISTORY_DAYS: i32 = 90;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/feed.ics", get(get_feed))
+        .route("/feed-token", post(create_feed_token).delete(revoke_feed_token))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    pub token: String,
+}
+
+/// GET /calendar/feed.ics?token=...
+async fn get_feed(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FeedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (tenant_id, user_id) = feed_owner(&state.pool, &query.token)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    let events = feed_events(&state.pool, tenant_id, user_id).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "inline; filename=\"calendar.ics\""),
+            (header::CACHE_CONTROL, "private, max-age=300"),
+        ],
+        to_ics(&events),
+    ))
+}
+
+/// POST /calendar/feed-token
+///
+/// Issue a new feed token for the current user, revoking any previous one.
+/// The token is only returned here.
+async fn create_feed_token(
+    State(state): State<Arc<AppState>>,
+    auth: ExtractAuth,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let token = issue_feed_token(&state.pool, auth.0.user.tenant_id, auth.0.user.id).await?;
+
+    Ok(Json(serde_json::json!({
+        "token": token,
+        "feed_path": format!("/api/v1/calendar/feed.ics?token={}", token),
+        "message": "Store this URL securely - it will not be shown again"
+    })))
+}
+
+/// DELETE /calendar/feed-token
+async fn revoke_feed_token(
+    State(state): State<Arc<AppState>>,
+    auth: ExtractAuth,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let revoked = revoke_feed_tokens(&state.pool, auth.0.user.tenant_id, auth.0.user.id).await?;
+
+    Ok(Json(serde_json::json!({ "revoked": revoked })))
+}
+
+// ============================================================================
+// Feed tokens
+// ============================================================================
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Replace the user's active feed token with a fresh random one
+pub(crate) async fn issue_feed_token(pool: &PgPool, tenant_id: Uuid, user_id: Uuid) -> Result<String, ApiError> {
+    use rand::Rng;
+    let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE calendar_feed_tokens SET revoked_at = NOW() WHERE tenant_id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("INSERT INTO calendar_feed_tokens (tenant_id, user_id, token_hash) VALUES ($1, $2, $3)")
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(token)
+}
+
+/// Revoke the user's active feed token; false if there was none
+pub(crate) async fn revoke_feed_tokens(pool: &PgPool, tenant_id: Uuid, user_id: Uuid) -> Result<bool, ApiError> {
+    let result = sqlx::query(
+        "UPDATE calendar_feed_tokens SET revoked_at = NOW() WHERE tenant_id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Tenant and user an active feed token belongs to
+pub(crate) async fn feed_owner(pool: &PgPool, token: &str) -> Result<Option<(Uuid, Uuid)>, ApiError> {
+    Ok(sqlx::query_as(
+        "SELECT tenant_id, user_id FROM calendar_feed_tokens WHERE token_hash = $1 AND revoked_at IS NULL",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await?)
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// The user's recurring events and their one-off events from the last
+/// [`FEED_HISTORY_DAYS`] onwards
+pub(crate) async fn feed_events(pool: &PgPool, tenant_id: Uuid, user_id: Uuid) -> Result<Vec<CalendarEvent>, ApiError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, tenant_id, calendar_id, title, description, location, start_at, end_at, all_day,
+               status, meeting_url, owner_id, linked_entity_type, linked_entity_id, recurrence_rule,
+               timezone, exdates, overrides, external_id, created_at, updated_at
+        FROM calendar_events
+        WHERE tenant_id = $1 AND owner_id = $2
+          AND (recurrence_rule IS NOT NULL OR end_at >= NOW() - make_interval(days => $3))
+        ORDER BY start_at
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(FEED_HISTORY_DAYS)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(event_from_row).collect()
+}
+
+fn event_from_row(row: &sqlx::postgres::PgRow) -> Result<CalendarEvent, ApiError> {
+    let status: String = row.try_get("status")?;
+    let exdates: serde_json::Value = row.try_get("exdates")?;
+    let overrides: serde_json::Value = row.try_get("overrides")?;
+
+    Ok(CalendarEvent {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        calendar_id: row.try_get("calendar_id")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        location: row.try_get("location")?,
+        start_at: row.try_get("start_at")?,
+        end_at: row.try_get("end_at")?,
+        all_day: row.try_get("all_day")?,
+        status: serde_json::from_value(serde_json::Value::String(status)).unwrap_or(EventStatus::Confirmed),
+        meeting_url: row.try_get("meeting_url")?,
+        owner_id: row.try_get("owner_id")?,
+        linked_entity_type: row.try_get("linked_entity_type")?,
+        linked_entity_id: row.try_get("linked_entity_id")?,
+        recurrence_rule: row.try_get("recurrence_rule")?,
+        timezone: row.try_get("timezone")?,
+        exdates: serde_json::from_value(exdates).unwrap_or_default(),
+        overrides: serde_json::from_value::<Vec<OccurrenceOverride>>(overrides).unwrap_or_default(),
+        external_id: row.try_get("external_id")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::TestTenant;
+
+    struct Fixture {
+        tenant: TestTenant,
+        user_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Calendar Test").await;
+            let user_id = tenant.user("agent@example.com").await;
+            Self { tenant, user_id }
+        }
+
+        async fn add_event(&self, title: &str, rrule: Option<&str>, days_from_now: i64) {
+            let start = chrono::Utc::now() + chrono::Duration::days(days_from_now);
+            sqlx::query(
+                "INSERT INTO calendar_events (tenant_id, owner_id, title, start_at, end_at, recurrence_rule, timezone, exdates)
+                 VALUES ($1, $2, $3, $4, $5, $6, 'Asia/Dubai', '[\"2025-01-01T10:00:00Z\"]')",
+            )
+            .bind(self.tenant.id)
+            .bind(self.user_id)
+            .bind(title)
+            .bind(start)
+            .bind(start + chrono::Duration::hours(1))
+            .bind(rrule)
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_feed_token_resolves_owner_until_rotated_or_revoked() {
+        let fx = Fixture::new().await;
+
+        let first = issue_feed_token(&fx.tenant.pool, fx.tenant.id, fx.user_id).await.unwrap();
+        assert_eq!(feed_owner(&fx.tenant.pool, &first).await.unwrap(), Some((fx.tenant.id, fx.user_id)));
+
+        let second = issue_feed_token(&fx.tenant.pool, fx.tenant.id, fx.user_id).await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(feed_owner(&fx.tenant.pool, &first).await.unwrap(), None);
+        assert!(feed_owner(&fx.tenant.pool, &second).await.unwrap().is_some());
+
+        assert!(revoke_feed_tokens(&fx.tenant.pool, fx.tenant.id, fx.user_id).await.unwrap());
+        assert_eq!(feed_owner(&fx.tenant.pool, &second).await.unwrap(), None);
+        assert!(!revoke_feed_tokens(&fx.tenant.pool, fx.tenant.id, fx.user_id).await.unwrap());
+
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_feed_includes_recurring_and_recent_events() {
+        let fx = Fixture::new().await;
+        fx.add_event("Weekly viewing", Some("FREQ=WEEKLY;BYDAY=TU"), -400).await;
+        fx.add_event("Contract signing", None, 3).await;
+        fx.add_event("Old call", None, -400).await;
+
+        let events = feed_events(&fx.tenant.pool, fx.tenant.id, fx.user_id).await.unwrap();
+
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Weekly viewing", "Contract signing"]);
+        assert_eq!(events[0].exdates.len(), 1);
+        let ics = to_ics(&events);
+        assert!(ics.contains("SUMMARY:Contract signing\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;BYDAY=TU\r\n"));
+
+        fx.cleanup().await;
+    }
+}
@@ -92,6 +92,8 @@ async fn create_deal(
 pub struct UpdateStageRequest {
     pub new_stage: String,
     pub reason: Option<String>,
+    /// Version the client last saw; the update is rejected if the deal has moved on
+    pub expected_version: u64,
 }
 
 async fn update_deal_stage(
@@ -101,26 +103,21 @@ async fn update_deal_stage(
 ) -> Result<Json<()>, (StatusCode, String)> {
     let user_id = Uuid::new_v4(); // TODO: Extract from JWT
     
-    // Load aggregate from event store
-    let deal = state.event_store
-        .load_aggregate(deal_id)
-        .await
-        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
-    
-    // Execute command
+    // Execute command against the version the client saw
     let mut cmd = UpdateDealStageCommand::new(deal_id, req.new_stage, user_id);
     if let Some(reason) = req.reason {
         cmd = cmd.with_reason(reason);
     }
     
-    let event = deal.update_stage(cmd)
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
-    
-    // Save event
-    state.event_store
-        .save_event(deal_id, "Deal", deal.version, &event, user_id)
+    let (event, _version) = DealCommandHandler::new(state.pool.clone())
+        .update_stage(cmd, req.expected_version)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| match e {
+            CommandError::Rejected(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+            CommandError::Store(EventStoreError::VersionConflict { .. }) => (StatusCode::CONFLICT, e.to_string()),
+            CommandError::Store(EventStoreError::AggregateNotFound(_)) => (StatusCode::NOT_FOUND, e.to_string()),
+            CommandError::Store(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
     
     // Update read model
     state.deal_projection
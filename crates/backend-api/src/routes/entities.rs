@@ -3,9 +3,11 @@ use axum::{
     http::{StatusCode, header},
     Json,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as Base64Url, Engine};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::Row;
@@ -14,18 +16,102 @@ use uuid::Uuid;
 use crate::state::AppState;
 use crate::middleware::tenant::ResolvedTenant;
 use crate::middleware::database::RlsConn;
-use core_models::{FieldDef, FieldType}; 
+use crate::middleware::permission::{can_access, is_admin, require_admin, AuthenticatedUser};
+use crate::middleware::audit_log::{field_changes, insert_entry, AuditAction, AuditLogEntry};
+use crate::routes::associations::{blocking_links, BlockingLink};
+use crate::middleware::trace_context::{current_trace_id, with_trace_id};
+use crate::outbox;
+use crate::ai::record_embeddings;
+use crate::error::ApiError;
+use crate::routes::{geocode, segments};
+use core_node_engine::EntityEvent;
+use core_models::{normalize_phone, EntityType, FieldDef, FieldType, FilterExpr, Money, DEFAULT_CURRENCY};
+
+pub(crate) mod filter;
+
+use filter::{escape_like, SqlFilter};
 
 // ============================================================================
 // Types
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct ListQuery {
     pub page: Option<i32>,
     pub per_page: Option<i32>,
     pub search: Option<String>,
     pub view_id: Option<Uuid>,
+    /// Cursor mode: opaque `next_cursor` from the previous page
+    pub after: Option<String>,
+    /// Cursor mode: page size
+    pub limit: Option<i64>,
+    /// `created_at` (default) or `updated_at`, newest first
+    pub sort: Option<String>,
+    /// Admins only: include soft-deleted records
+    pub include_deleted: Option<bool>,
+    /// JSON `FilterExpr`, e.g. `{"field":"stage","op":"eq","value":"won"}`
+    pub filter: Option<String>,
+    /// Only records in this segment; combined with `filter` when both are given
+    pub segment: Option<Uuid>,
+}
+
+/// Keyset page, returned when `after` or `limit` is given
+#[derive(Debug, Serialize)]
+pub struct CursorPage {
+    pub items: Vec<Value>,
+    pub next_cursor: Option<String>,
+}
+
+/// Sortable system columns for cursor pagination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl SortKey {
+    fn parse(s: Option<&str>) -> Option<Self> {
+        match s {
+            None | Some("created_at") => Some(SortKey::CreatedAt),
+            Some("updated_at") => Some(SortKey::UpdatedAt),
+            _ => None,
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            SortKey::CreatedAt => "created_at",
+            SortKey::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+/// What to fetch in one keyset page
+struct CursorParams<'a> {
+    sort: SortKey,
+    after: Option<&'a Cursor>,
+    limit: i64,
+    include_deleted: bool,
+    /// Compiled with placeholders from [`CURSOR_FILTER_PARAM`]
+    filter: Option<&'a SqlFilter>,
+}
+
+/// First placeholder free for a filter in the keyset query
+const CURSOR_FILTER_PARAM: usize = 7;
+
+/// Position after the last row of a page: its sort key value and id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Cursor(SortKey, DateTime<Utc>, Uuid);
+
+impl Cursor {
+    fn encode(&self) -> String {
+        Base64Url.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(s: &str) -> Option<Self> {
+        serde_json::from_slice(&Base64Url.decode(s).ok()?).ok()
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -36,6 +122,30 @@ pub struct ListResponse {
     pub per_page: i32,
 }
 
+/// Key carrying a record's version in reads and expected version in updates
+pub const VERSION_KEY: &str = "_version";
+
+/// Largest number of records accepted by one batch create
+pub const MAX_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct BatchQuery {
+    /// Roll back the whole batch on the first invalid record
+    pub stop_on_error: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchError {
+    pub index: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub created: Vec<Uuid>,
+    pub errors: Vec<BatchError>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LookupQuery {
     pub q: Option<String>,
@@ -48,22 +158,61 @@ pub struct LookupResult {
     pub sub_label: Option<String>,
 }
 
+/// Options for link pickers: a prefix search, or the labels of `ids`
+#[derive(Debug, Deserialize)]
+pub struct EntityLookupQuery {
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+    /// Comma-separated record ids, e.g. the current selection
+    pub ids: Option<String>,
+}
+
+/// A record as a picker option
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LookupOption {
+    pub value: Uuid,
+    pub label: String,
+}
+
+/// Which records a lookup returns
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LookupFilter<'a> {
+    /// Labels starting with the text, or with a word starting with it
+    Prefix(&'a str),
+    Ids(&'a [Uuid]),
+}
+
+const LOOKUP_DEFAULT_LIMIT: i64 = 20;
+const LOOKUP_MAX_LIMIT: i64 = 100;
+
 // ============================================================================
 // Routes
 // ============================================================================
 
 pub fn routes() -> Router<Arc<AppState>> {
+    // Permanent deletion bypasses the restore window
+    let admin = Router::new()
+        .route("/records/:entity_code/:id/hard", delete(hard_delete_record))
+        .route("/entities/:entity_code/:id/hard", delete(hard_delete_record))
+        .route_layer(axum::middleware::from_fn(require_admin));
+
     Router::new()
         // Generic CRUD
         .route("/records/:entity_code", get(list_records).post(create_record))
-        .route("/records/:entity_code/:id", get(get_record).put(update_record).delete(delete_record))
+        .route("/records/:entity_code/batch", post(create_records_batch))
+        .route("/records/:entity_code/:id", get(get_record).put(update_record).patch(update_record).delete(delete_record))
+        .route("/records/:entity_code/:id/restore", post(restore_record))
         
         // Standard alias
         .route("/entities/:entity_code", get(list_records).post(create_record))
-        .route("/entities/:entity_code/:id", get(get_record).put(update_record).delete(delete_record))
+        .route("/entities/:entity_code/batch", post(create_records_batch))
+        .route("/entities/:entity_code/lookup", get(lookup_records))
+        .route("/entities/:entity_code/:id", get(get_record).put(update_record).patch(update_record).delete(delete_record))
+        .route("/entities/:entity_code/:id/restore", post(restore_record))
         
         // Lookup
         .route("/lookup/:entity_code", get(lookup_entity))
+        .merge(admin)
 }
 
 // ============================================================================
@@ -76,14 +225,52 @@ async fn list_records(
     Path(entity_code): Path<String>,
     Query(query): Query<ListQuery>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     mut conn: RlsConn,
 ) -> impl IntoResponse {
+    let include_deleted = query.include_deleted.unwrap_or(false);
+    if include_deleted && !user.is_some_and(|axum::Extension(u)| is_admin(&u)) {
+        return (StatusCode::FORBIDDEN, "Admin access required to include deleted records").into_response();
+    }
+
     // 1. Resolve Entity Type
     let entity_type = match state.metadata.get_entity_type(tenant.id, &entity_code).await {
         Ok(e) => e,
         Err(_) => return (StatusCode::NOT_FOUND, format!("Entity type '{}' not found", entity_code)).into_response(),
     };
 
+    let fields = state.metadata.get_fields(tenant.id, entity_type.id).await.unwrap_or_default();
+
+    let filter_expr = match resolve_filter(&mut conn, tenant.id, &entity_code, &query).await {
+        Ok(expr) => expr,
+        Err(e) => return e.into_response(),
+    };
+    let compile_filter = |first_param| {
+        filter_expr.as_ref().map(|expr| filter::compile(expr, &fields, first_param)).transpose()
+    };
+
+    if query.after.is_some() || query.limit.is_some() {
+        let Some(sort) = SortKey::parse(query.sort.as_deref()) else {
+            return (StatusCode::BAD_REQUEST, "Unsupported sort field").into_response();
+        };
+        let after = match query.after.as_deref().map(Cursor::decode) {
+            None => None,
+            Some(Some(cursor)) if cursor.0 == sort => Some(cursor),
+            Some(_) => return (StatusCode::BAD_REQUEST, "Invalid cursor").into_response(),
+        };
+        let limit = query.limit.unwrap_or(25).clamp(1, 100);
+        let sql_filter = match compile_filter(CURSOR_FILTER_PARAM) {
+            Ok(f) => f,
+            Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+        };
+
+        let params = CursorParams { sort, after: after.as_ref(), limit, include_deleted, filter: sql_filter.as_ref() };
+        return match fetch_cursor_page(&mut conn, tenant.id, entity_type.id, &fields, &params).await {
+            Ok(page) => Json(page).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    }
+
     // 2. Pagination
     let page = query.page.unwrap_or(1).max(1);
     let per_page = query.per_page.unwrap_or(25).min(100).max(1);
@@ -91,14 +278,26 @@ async fn list_records(
 
     // 3. View Logic (Filtering/Sorting)
     let sort_clause = "ORDER BY created_at DESC".to_string();
+    let (count_filter, list_filter) = match (compile_filter(3), compile_filter(5)) {
+        (Ok(count), Ok(list)) => (count, list),
+        (Err(e), _) | (_, Err(e)) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))).into_response()
+        }
+    };
+    let where_filter = |f: &Option<SqlFilter>| f.as_ref().map(|f| format!("AND {}", f.sql)).unwrap_or_default();
     
     // 4. Query
-    let count_sql = "SELECT COUNT(*) as count FROM entity_records WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL";
-    let count_row = sqlx::query(count_sql)
-        .bind(tenant.id)
-        .bind(entity_type.id)
-        .fetch_one(&mut **conn)
-        .await;
+    let deleted_filter = if include_deleted { "" } else { "AND deleted_at IS NULL" };
+    let count_sql = format!(
+        "SELECT COUNT(*) as count FROM entity_records WHERE tenant_id = $1 AND entity_type_id = $2 {} {}",
+        deleted_filter,
+        where_filter(&count_filter)
+    );
+    let mut count_query = sqlx::query(&count_sql).bind(tenant.id).bind(entity_type.id);
+    if let Some(f) = &count_filter {
+        count_query = f.bind(count_query);
+    }
+    let count_row = count_query.fetch_one(&mut **conn).await;
 
     let total: i64 = match count_row {
         Ok(r) => r.try_get("count").unwrap_or(0),
@@ -106,28 +305,23 @@ async fn list_records(
     };
 
     let sql = format!(
-        "SELECT id, data, created_at, updated_at FROM entity_records WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL {} LIMIT $3 OFFSET $4",
-        sort_clause
+        "SELECT id, data, created_at, updated_at, deleted_at, version FROM entity_records WHERE tenant_id = $1 AND entity_type_id = $2 {} {} {} LIMIT $3 OFFSET $4",
+        deleted_filter, where_filter(&list_filter), sort_clause
     );
 
-    let rows = sqlx::query(&sql)
+    let mut list_query = sqlx::query(&sql)
         .bind(tenant.id)
         .bind(entity_type.id)
         .bind(per_page as i64)
-        .bind(offset as i64)
-        .fetch_all(&mut **conn)
-        .await;
+        .bind(offset as i64);
+    if let Some(f) = &list_filter {
+        list_query = f.bind(list_query);
+    }
+    let rows = list_query.fetch_all(&mut **conn).await;
 
     match rows {
         Ok(results) => {
-             let data: Vec<Value> = results.iter().map(|row| {
-                let mut map = row.try_get::<Value, _>("data").unwrap_or(serde_json::json!({})).as_object().unwrap_or(&serde_json::Map::new()).clone();
-                // Inject system fields
-                map.insert("id".to_string(), serde_json::json!(row.get::<Uuid, _>("id")));
-                map.insert("created_at".to_string(), serde_json::json!(row.get::<chrono::DateTime<chrono::Utc>, _>("created_at")));
-                map.insert("updated_at".to_string(), serde_json::json!(row.get::<chrono::DateTime<chrono::Utc>, _>("updated_at")));
-                Value::Object(map)
-            }).collect();
+             let data: Vec<Value> = results.iter().map(|row| record_from_row(row, &fields)).collect();
 
             Json(ListResponse { data, total, page, per_page }).into_response()
         },
@@ -135,6 +329,28 @@ async fn list_records(
     }
 }
 
+/// The list filter from `?filter=` and `?segment=`, ANDed when both are given
+async fn resolve_filter(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_code: &str,
+    query: &ListQuery,
+) -> Result<Option<FilterExpr>, ApiError> {
+    let explicit = query.filter.as_deref().map(filter::parse).transpose().map_err(ApiError::BadRequest)?;
+    let Some(segment_id) = query.segment else {
+        return Ok(explicit);
+    };
+
+    let segment = segments::load_segment(conn, tenant_id, segment_id).await?;
+    if segment.entity_type != entity_code {
+        return Err(ApiError::BadRequest(format!("Segment is for '{}', not '{}'", segment.entity_type, entity_code)));
+    }
+    Ok(Some(match explicit {
+        Some(expr) => FilterExpr::And { and: vec![segment.filter, expr] },
+        None => segment.filter,
+    }))
+}
+
 /// POST /records/:entity_code
 async fn create_record(
     State(state): State<Arc<AppState>>,
@@ -152,75 +368,18 @@ async fn create_record(
     let entity_type = state.metadata.get_entity_type(tenant.id, &entity_code).await.unwrap();
 
     // 2. Validate
-    let processed_data = match validate_and_process_payload(&fields, &payload, false) {
+    let processed_data = match validate_and_process_payload(&fields, &payload, false, &tenant.phone_region()) {
         Ok(d) => d,
         Err(msg) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": msg}))).into_response(),
     };
 
-    // 3. Insert
-    let new_id = Uuid::new_v4();
-    let result = sqlx::query(
-        "INSERT INTO entity_records (id, tenant_id, entity_type_id, data) VALUES ($1, $2, $3, $4) RETURNING id"
-    )
-    .bind(new_id)
-    .bind(tenant.id)
-    .bind(entity_type.id)
-    .bind(&processed_data)
-    .fetch_one(&mut **conn)
-    .await;
-
-    match result {
-        Ok(row) => {
-            let record_id: Uuid = row.get("id");
+    // 3. Insert, with the create event in the outbox
+    match insert_record(&mut conn, tenant.id, entity_type.id, &entity_code, processed_data).await {
+        Ok(event) => {
+            let record_id = event.record_id;
 
             // Trigger workflows (Async)
-            let state_clone = state.clone();
-            let tid = tenant.id;
-            let entity_type_id = entity_type.id;
-            let entity_code_str = entity_code.clone();
-            let pdata = processed_data.clone(); // Use processed data for event
-            
-            tokio::spawn(async move {
-                // 1. Publish Event
-                let event = core_node_engine::EntityEvent::create(
-                    tid,
-                    &entity_code_str,
-                    record_id,
-                    pdata,
-                    None, // Triggered by user ID (TODO: extract from session)
-                );
-                
-                if let Err(e) = state_clone.event_publisher.publish(&event).await {
-                    tracing::error!("Failed to publish create event: {}", e);
-                    return;
-                }
-
-                // 2. Fetch Active Workflows
-                match state_clone.graph_repo.get_graphs_for_entity_event(tid, entity_type_id).await {
-                    Ok(graphs) => {
-                        for graph in graphs {
-                             tracing::info!("Triggering workflow: {} for entity: {}", graph.name, entity_code_str);
-                             // 3. Execute Graph
-                             // Prepare trigger data
-                             let trigger_data = event.to_trigger_data();
-                             
-                             // Get nodes and edges
-                             match state_clone.graph_repo.get_nodes(graph.id).await {
-                                 Ok(nodes) => {
-                                     match state_clone.graph_repo.get_edges(graph.id).await {
-                                         Ok(edges) => {
-                                             let _ = state_clone.graph_executor.execute(&graph, &nodes, &edges, trigger_data).await;
-                                         }
-                                         Err(e) => tracing::error!("Failed to fetch edges for graph {}: {}", graph.id, e),
-                                     }
-                                 }
-                                 Err(e) => tracing::error!("Failed to fetch nodes for graph {}: {}", graph.id, e),
-                             }
-                        }
-                    }
-                    Err(e) => tracing::error!("Failed to fetch workflows: {}", e),
-                }
-            });
+            spawn_workflows(state.clone(), entity_type.id, event);
 
             Json(serde_json::json!({
                 "id": record_id,
@@ -231,13 +390,58 @@ async fn create_record(
     }
 }
 
+/// POST /records/:entity_code/batch?stop_on_error=bool
+///
+/// Inserts every valid record in one transaction. With `stop_on_error` the
+/// first invalid record rolls the whole batch back.
+async fn create_records_batch(
+    State(state): State<Arc<AppState>>,
+    Path(entity_code): Path<String>,
+    Query(query): Query<BatchQuery>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Json(records): Json<Vec<Value>>,
+) -> impl IntoResponse {
+    if records.len() > MAX_BATCH_SIZE {
+        let msg = format!("Batch exceeds the maximum of {} records", MAX_BATCH_SIZE);
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(serde_json::json!({"error": msg}))).into_response();
+    }
+
+    let entity_type = match state.metadata.get_entity_type(tenant.id, &entity_code).await {
+        Ok(e) => e,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Invalid entity type: {}", e)).into_response(),
+    };
+    let fields = match state.metadata.get_fields(tenant.id, entity_type.id).await {
+        Ok(f) => f,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let stop_on_error = query.stop_on_error.unwrap_or(false);
+    let outcome = match insert_batch(&mut conn, tenant.id, entity_type.id, &entity_code, &fields, &records, stop_on_error, &tenant.phone_region()).await {
+        Ok(o) => o,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let status = if stop_on_error && !outcome.errors.is_empty() { StatusCode::BAD_REQUEST } else { StatusCode::OK };
+    let mut created = Vec::with_capacity(outcome.created.len());
+    for event in outcome.created {
+        created.push(event.record_id);
+        spawn_workflows(state.clone(), entity_type.id, event);
+    }
+
+    (status, Json(BatchResponse { created, errors: outcome.errors })).into_response()
+}
+
 /// GET /records/:entity_code/:id
 async fn get_record(
-    Path((_entity_code, id)): Path<(String, Uuid)>,
+    State(state): State<Arc<AppState>>,
+    Path((entity_code, id)): Path<(String, Uuid)>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
     mut conn: RlsConn,
 ) -> impl IntoResponse {
-    let result = sqlx::query("SELECT id, data FROM entity_records WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL")
+    let fields = state.metadata.get_fields_by_entity_name(tenant.id, &entity_code).await.unwrap_or_default();
+
+    let result = sqlx::query("SELECT id, data, version FROM entity_records WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL")
         .bind(id)
         .bind(tenant.id)
         .fetch_optional(&mut **conn)
@@ -248,7 +452,9 @@ async fn get_record(
             let mut data = row.get::<Value, _>("data");
             if let Some(obj) = data.as_object_mut() {
                 obj.insert("id".to_string(), serde_json::json!(row.get::<Uuid, _>("id")));
+                obj.insert(VERSION_KEY.to_string(), serde_json::json!(row.get::<i32, _>("version")));
             }
+            inject_computed_fields(&fields, &mut data);
             Json(data).into_response()
         },
         Ok(None) => (StatusCode::NOT_FOUND, "Record not found").into_response(),
@@ -256,11 +462,14 @@ async fn get_record(
     }
 }
 
-/// PUT /records/:entity_code/:id
+/// PUT | PATCH /records/:entity_code/:id
+///
+/// Both merge the given fields into the record.
 async fn update_record(
     State(state): State<Arc<AppState>>,
     Path((entity_code, id)): Path<(String, Uuid)>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     mut conn: RlsConn,
     Json(payload): Json<Value>,
 ) -> impl IntoResponse {
@@ -269,141 +478,116 @@ async fn update_record(
         Err(_) => return (StatusCode::NOT_FOUND, "Entity type not found").into_response(),
     };
 
+    // Optimistic concurrency: clients send back the `_version` they last read
+    let mut payload = payload;
+    let expected_version = match payload.as_object_mut().and_then(|o| o.remove(VERSION_KEY)).and_then(|v| v.as_i64()) {
+        Some(v) => v as i32,
+        None => {
+            let msg = format!("'{}' is required to update a record", VERSION_KEY);
+            return (StatusCode::PRECONDITION_REQUIRED, Json(serde_json::json!({"error": msg}))).into_response();
+        }
+    };
+
     // Validate (is_update = true -> allow partials)
-    let processed_data = match validate_and_process_payload(&fields, &payload, true) {
+    let processed_data = match validate_and_process_payload(&fields, &payload, true, &tenant.phone_region()) {
         Ok(d) => d,
         Err(msg) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": msg}))).into_response(),
     };
 
-    // 1. Fetch old data for workflow triggers
-    let old_data: Value = match sqlx::query_scalar::<_, Value>("SELECT data FROM entity_records WHERE id = $1 AND tenant_id = $2")
-        .bind(id)
-        .bind(tenant.id)
-        .fetch_optional(&mut **conn)
-        .await {
-            Ok(Some(d)) => d,
-            Ok(None) => return (StatusCode::NOT_FOUND, "Record not found").into_response(),
-            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-        };
+    use sqlx::Connection;
 
-    // 2. Update using JSONB merge (|| operator)
-    let result = sqlx::query(
-        "UPDATE entity_records SET data = data || $1, updated_at = NOW() WHERE id = $2 AND tenant_id = $3 RETURNING data"
-    )
-    .bind(&processed_data)
-    .bind(id)
-    .bind(tenant.id)
-    .fetch_one(&mut **conn)
-    .await;
+    let mut tx = match conn.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    match update_versioned(&mut tx, tenant.id, id, &processed_data, expected_version).await {
+        Ok(VersionedUpdate::Updated { old_data, new_data, version }) => {
+            let user_id = user.map(|axum::Extension(u)| u.id);
+            let update = AuditedUpdate { entity_code: &entity_code, id, old_data: &old_data, new_data: &new_data, version };
+            if let Err(e) = audit_update(&mut tx, tenant.id, user_id, &fields, &update).await {
+                tracing::warn!("Failed to write audit entry for {} {}: {}", entity_code, id, e);
+            }
 
-    match result {
-        Ok(row) => {
-            let new_data: Value = row.get("data");
-
-            // 3. Trigger workflows (Async)
-            let state_clone = state.clone();
-            let tid = tenant.id;
-            // Need entity_type_id to look up graphs. We have entity_code string.
-            // But getting entity_type_id requires async call to metadata which we can't do easily inside spawn unless we resolve it first.
-            // We already resolved `fields`, we need `entity_type.id`.
-            // Wait, we didn't resolve entity_type id in the main handler body of update_record yet!
-            // I should modify the handler to resolve entity_type fully.
-            
-            // Re-resolve entity type inside spawn? Or pass it in.
-            // Let's assume we fetch it inside spawn or pass it.
-            let entity_code_str = entity_code.clone();
-            
-            tokio::spawn(async move {
-                // Resolve Entity Type (needed for ID)
-                 let entity_type = match state_clone.metadata.get_entity_type(tid, &entity_code_str).await {
-                    Ok(e) => e,
-                    Err(e) => {
-                        tracing::error!("Failed to resolve entity type for workflow trigger: {}", e);
-                        return;
-                    }
-                };
-
-                // 1. Publish Event
-                // Calculate changed fields
-                let changed_fields: Vec<String> = if let Some(new_obj) = new_data.as_object() {
-                    if let Some(old_obj) = old_data.as_object() {
-                        new_obj.keys()
-                            .filter(|k| new_obj.get(*k) != old_obj.get(*k))
-                            .cloned()
-                            .collect()
-                    } else {
-                        vec![]
-                    }
-                } else {
-                     vec![]
-                };
-
-                let event = core_node_engine::EntityEvent::update(
-                    tid,
-                    &entity_code_str,
-                    id,
-                    old_data,
-                    new_data,
-                    changed_fields,
-                    None, // Triggered by user ID
-                );
-                
-                if let Err(e) = state_clone.event_publisher.publish(&event).await {
-                    tracing::error!("Failed to publish update event: {}", e);
-                    return;
-                }
+            let changed_fields: Vec<String> = match (new_data.as_object(), old_data.as_object()) {
+                (Some(new_obj), Some(old_obj)) => new_obj.keys()
+                    .filter(|k| new_obj.get(*k) != old_obj.get(*k))
+                    .cloned()
+                    .collect(),
+                _ => vec![],
+            };
+            let event = EntityEvent::update(tenant.id, &entity_code, id, old_data, new_data, changed_fields, None);
+            if let Err(e) = commit_with_event(tx, &event).await {
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
 
-                // 2. Fetch Active Workflows
-                match state_clone.graph_repo.get_graphs_for_entity_event(tid, entity_type.id).await {
-                    Ok(graphs) => {
-                        for graph in graphs {
-                             tracing::info!("Triggering workflow: {} for entity: {}", graph.name, entity_code_str);
-                             let trigger_data = event.to_trigger_data();
-                             
-                             match state_clone.graph_repo.get_nodes(graph.id).await {
-                                 Ok(nodes) => {
-                                     match state_clone.graph_repo.get_edges(graph.id).await {
-                                         Ok(edges) => {
-                                             let _ = state_clone.graph_executor.execute(&graph, &nodes, &edges, trigger_data).await;
-                                         }
-                                         Err(e) => tracing::error!("Failed to fetch edges for graph {}: {}", graph.id, e),
-                                     }
-                                 }
-                                 Err(e) => tracing::error!("Failed to fetch nodes for graph {}: {}", graph.id, e),
-                             }
-                        }
-                    }
-                    Err(e) => tracing::error!("Failed to fetch workflows: {}", e),
-                }
-            });
+            // Trigger workflows (Async)
+            spawn_event_workflows(state, &entity_code, event).await;
 
-            Json(serde_json::json!({"status": "updated"})).into_response()
+            Json(serde_json::json!({"status": "updated", "version": version})).into_response()
         },
+        Ok(VersionedUpdate::Conflict(current)) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Record was changed by someone else; merge with the current version and retry",
+                "current": current
+            })),
+        ).into_response(),
+        Ok(VersionedUpdate::NotFound) => (StatusCode::NOT_FOUND, "Record not found").into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
 /// DELETE /records/:entity_code/:id
+///
+/// Soft delete: the record is hidden until restored or hard-deleted
 async fn delete_record(
+    State(state): State<Arc<AppState>>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
     mut conn: RlsConn,
-    Path((_entity_code, id)): Path<(String, Uuid)>,
+    Path((entity_code, id)): Path<(String, Uuid)>,
 ) -> impl IntoResponse {
-    // Soft delete
-    let result = sqlx::query("UPDATE entity_records SET deleted_at = NOW() WHERE id = $1 AND tenant_id = $2")
-        .bind(id)
-        .bind(tenant.id)
-        .execute(&mut **conn)
-        .await;
+    match soft_delete(&mut conn, tenant.id, &entity_code, id).await {
+        Ok(DeleteOutcome::Deleted(event)) => {
+            spawn_event_workflows(state, &entity_code, event).await;
+            Json(serde_json::json!({"status": "deleted"})).into_response()
+        },
+        Ok(DeleteOutcome::Blocked(links)) => blocked_response(links),
+        Ok(DeleteOutcome::NotFound) => (StatusCode::NOT_FOUND, "Record not found").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
 
-    match result {
-        Ok(r) => {
-            if r.rows_affected() > 0 {
-                Json(serde_json::json!({"status": "deleted"})).into_response()
-            } else {
-                 (StatusCode::NOT_FOUND, "Record not found").into_response()
-            }
+/// POST /records/:entity_code/:id/restore
+async fn restore_record(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Path((entity_code, id)): Path<(String, Uuid)>,
+) -> impl IntoResponse {
+    match restore(&mut conn, tenant.id, &entity_code, id).await {
+        Ok(Some(event)) => {
+            spawn_event_workflows(state, &entity_code, event).await;
+            Json(serde_json::json!({"status": "restored"})).into_response()
+        },
+        Ok(None) => (StatusCode::NOT_FOUND, "Deleted record not found").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// DELETE /records/:entity_code/:id/hard (admin only)
+async fn hard_delete_record(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Path((entity_code, id)): Path<(String, Uuid)>,
+) -> impl IntoResponse {
+    match hard_delete(&mut conn, tenant.id, &entity_code, id).await {
+        Ok(DeleteOutcome::Deleted(event)) => {
+            spawn_event_workflows(state, &entity_code, event).await;
+            Json(serde_json::json!({"status": "purged"})).into_response()
         },
+        Ok(DeleteOutcome::Blocked(links)) => blocked_response(links),
+        Ok(DeleteOutcome::NotFound) => (StatusCode::NOT_FOUND, "Record not found").into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
@@ -421,19 +605,11 @@ async fn lookup_entity(
         Err(_) => return (StatusCode::NOT_FOUND, "Entity type not found").into_response(),
     };
 
-    // Determine display field. Currently inferred from code.
-    // Future: add `display_field` col to entity_types
-    let display_field_sql = match entity_code.as_str() {
-        "contact" => "data->>'first_name' || ' ' || data->>'last_name'",
-        "company" | "deal" => "data->>'name'",
-        "task" | "property" => "data->>'title'",
-        "contract" => "data->>'contract_number'",
-        _ => "data->>'name'", // Fallback
-    };
+    let display_field_sql = label_sql(&entity_type, 4);
     
     // Search filter
     let search_term = query.q.unwrap_or_default();
-    let search_pattern = format!("%{}%", search_term);
+    let search_pattern = format!("%{}%", escape_like(&search_term));
     
     let sql = format!(
         r#"
@@ -449,12 +625,14 @@ async fn lookup_entity(
         display_field_sql, display_field_sql 
     );
 
-    let rows = sqlx::query(&sql)
+    let mut lookup = sqlx::query(&sql)
         .bind(tenant.id)
         .bind(entity_type.id)
-        .bind(search_pattern)
-        .fetch_all(&mut **conn)
-        .await;
+        .bind(search_pattern);
+    if let Some(field) = &entity_type.display_field {
+        lookup = lookup.bind(field);
+    }
+    let rows = lookup.fetch_all(&mut **conn).await;
 
     match rows {
         Ok(results) => {
@@ -471,14 +649,597 @@ async fn lookup_entity(
     }
 }
 
+/// GET /entities/:entity_code/lookup?q=&limit=&ids=
+///
+/// `{value, label}` options for link pickers, labelled by the entity's
+/// display field. With `ids`, resolves those records' labels instead of
+/// searching.
+async fn lookup_records(
+    State(state): State<Arc<AppState>>,
+    Path(entity_code): Path<String>,
+    Query(query): Query<EntityLookupQuery>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+) -> impl IntoResponse {
+    if user.is_some_and(|axum::Extension(u)| !can_access(&u, &entity_code, "read", None)) {
+        return (StatusCode::FORBIDDEN, "Not allowed to read these records").into_response();
+    }
+    let entity_type = match state.metadata.get_entity_type(tenant.id, &entity_code).await {
+        Ok(e) => e,
+        Err(_) => return (StatusCode::NOT_FOUND, "Entity type not found").into_response(),
+    };
+
+    let ids = match query.ids.as_deref().map(parse_ids).transpose() {
+        Ok(ids) => ids,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    let (filter, limit) = match &ids {
+        Some(ids) => (LookupFilter::Ids(ids), ids.len() as i64),
+        None => (
+            LookupFilter::Prefix(query.q.as_deref().unwrap_or("").trim()),
+            query.limit.unwrap_or(LOOKUP_DEFAULT_LIMIT),
+        ),
+    };
+
+    match lookup_options(&mut conn, tenant.id, &entity_type, filter, limit).await {
+        Ok(options) => Json(options).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 // ============================================================================
 // Helpers
 // ============================================================================
 
-fn validate_and_process_payload(
+/// Parse a comma-separated id list, ignoring empty entries
+fn parse_ids(ids: &str) -> Result<Vec<Uuid>, String> {
+    ids.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| Uuid::parse_str(id).map_err(|_| format!("Invalid id: {}", id)))
+        .collect()
+}
+
+/// SQL for a record's label: the entity's display field, bound to `$param`,
+/// or a built-in label when none is configured
+fn label_sql(entity_type: &EntityType, param: usize) -> String {
+    if entity_type.display_field.is_some() {
+        return format!("COALESCE(data->>${}, '')", param);
+    }
+    match entity_type.name.as_str() {
+        "contact" => "TRIM(COALESCE(data->>'first_name', '') || ' ' || COALESCE(data->>'last_name', ''))",
+        "task" | "property" => "COALESCE(data->>'title', '')",
+        "contract" => "COALESCE(data->>'contract_number', '')",
+        _ => "COALESCE(data->>'name', '')",
+    }
+    .to_string()
+}
+
+/// Picker options of `entity_type` in the tenant, ordered by label
+pub(crate) async fn lookup_options(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type: &EntityType,
+    filter: LookupFilter<'_>,
+    limit: i64,
+) -> Result<Vec<LookupOption>, sqlx::Error> {
+    let label = label_sql(entity_type, 5);
+    let condition = match filter {
+        LookupFilter::Prefix(_) => format!("({0} ILIKE $3 OR {0} ILIKE '% ' || $3)", label),
+        LookupFilter::Ids(_) => "id = ANY($3)".to_string(),
+    };
+    let sql = format!(
+        "SELECT id, {} AS label FROM entity_records
+         WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL AND {}
+         ORDER BY label, id
+         LIMIT $4",
+        label, condition
+    );
+
+    let query = sqlx::query(&sql).bind(tenant_id).bind(entity_type.id);
+    let query = match filter {
+        LookupFilter::Prefix(text) => query.bind(format!("{}%", escape_like(text))),
+        LookupFilter::Ids(ids) => query.bind(ids.to_vec()),
+    };
+    let mut query = query.bind(limit.clamp(1, LOOKUP_MAX_LIMIT));
+    if let Some(field) = &entity_type.display_field {
+        query = query.bind(field);
+    }
+
+    let rows = query.fetch_all(conn).await?;
+    Ok(rows
+        .iter()
+        .map(|row| LookupOption { value: row.get("id"), label: row.get("label") })
+        .collect())
+}
+
+/// Record JSON for list responses: stored data plus system and computed fields
+pub(crate) fn record_from_row(row: &sqlx::postgres::PgRow, fields: &[FieldDef]) -> Value {
+    let mut map = row.try_get::<Value, _>("data").unwrap_or(serde_json::json!({})).as_object().unwrap_or(&serde_json::Map::new()).clone();
+    // Inject system fields
+    map.insert("id".to_string(), serde_json::json!(row.get::<Uuid, _>("id")));
+    map.insert("created_at".to_string(), serde_json::json!(row.get::<DateTime<Utc>, _>("created_at")));
+    map.insert("updated_at".to_string(), serde_json::json!(row.get::<DateTime<Utc>, _>("updated_at")));
+    map.insert(VERSION_KEY.to_string(), serde_json::json!(row.get::<i32, _>("version")));
+    if let Ok(Some(deleted_at)) = row.try_get::<Option<DateTime<Utc>>, _>("deleted_at") {
+        map.insert("deleted_at".to_string(), serde_json::json!(deleted_at));
+    }
+    let mut record = Value::Object(map);
+    inject_computed_fields(fields, &mut record);
+    record
+}
+
+/// Keyset page ordered by `(sort, id)` descending
+///
+/// Rows inserted while paging sort ahead of the cursor, so every row that
+/// existed when paging started is returned exactly once.
+async fn fetch_cursor_page(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    fields: &[FieldDef],
+    params: &CursorParams<'_>,
+) -> Result<CursorPage, sqlx::Error> {
+    let CursorParams { sort, after, limit, include_deleted, filter } = *params;
+    let column = sort.column();
+    let filter_sql = filter.map(|f| format!("AND {}", f.sql)).unwrap_or_default();
+    let sql = format!(
+        "SELECT id, data, created_at, updated_at, deleted_at, version FROM entity_records
+         WHERE tenant_id = $1 AND entity_type_id = $2 AND ($6 OR deleted_at IS NULL)
+           AND ($3::timestamptz IS NULL OR ({column}, id) < ($3, $4)) {filter_sql}
+         ORDER BY {column} DESC, id DESC
+         LIMIT $5"
+    );
+
+    // One extra row tells us whether there is a next page
+    let mut query = sqlx::query(&sql)
+        .bind(tenant_id)
+        .bind(entity_type_id)
+        .bind(after.map(|c| c.1))
+        .bind(after.map(|c| c.2))
+        .bind(limit + 1)
+        .bind(include_deleted);
+    if let Some(f) = filter {
+        query = f.bind(query);
+    }
+    let mut rows = query.fetch_all(conn).await?;
+
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let next_cursor = match rows.last() {
+        Some(last) if has_more => Some(Cursor(sort, last.get(column), last.get("id")).encode()),
+        _ => None,
+    };
+
+    Ok(CursorPage {
+        items: rows.iter().map(|row| record_from_row(row, fields)).collect(),
+        next_cursor,
+    })
+}
+
+/// Publish `event`, run the entity type's workflows, and geocode changed
+/// addresses in the background
+///
+/// The event is already in the outbox; it is marked dispatched once
+/// published, otherwise the outbox dispatcher retries it later.
+pub(crate) fn spawn_workflows(state: Arc<AppState>, entity_type_id: Uuid, event: EntityEvent) {
+    geocode::spawn_geocoding(state.clone(), entity_type_id, &event);
+    record_embeddings::spawn_record_embedding(state.clone(), entity_type_id, &event);
+    let trace_id = event.trace_id.clone().or_else(current_trace_id);
+    let event = event.with_trace_id(trace_id.clone());
+    tokio::spawn(with_trace_id(trace_id, async move {
+        if let Err(e) = deliver_event(&state, entity_type_id, &event).await {
+            tracing::error!("{}", e);
+            return;
+        }
+        if let Err(e) = outbox::mark_dispatched(&state.pool, event.id).await {
+            tracing::warn!("Failed to mark event {} dispatched: {}", event.id, e);
+        }
+    }));
+}
+
+/// Publish `event` and run the workflows triggered by it
+///
+/// Fails only if publishing fails; workflow errors are logged.
+pub(crate) async fn deliver_event(state: &AppState, entity_type_id: Uuid, event: &EntityEvent) -> Result<(), String> {
+    // 1. Publish Event
+    if let Err(e) = state.event_publisher.publish(event).await {
+        return Err(format!("Failed to publish {} event: {}", event.event_type.to_string(), e));
+    }
+
+    // 2. Fetch Active Workflows
+    match state.graph_repo.get_graphs_for_entity_event(event.tenant_id, entity_type_id).await {
+        Ok(graphs) => {
+            for graph in graphs {
+                 tracing::info!("Triggering workflow: {} for entity: {}", graph.name, event.entity_type);
+                 // 3. Execute Graph
+                 // Prepare trigger data
+                 let trigger_data = event.to_trigger_data();
+                 
+                 // Get nodes and edges
+                 match state.graph_repo.get_nodes(graph.id).await {
+                     Ok(nodes) => {
+                         match state.graph_repo.get_edges(graph.id).await {
+                             Ok(edges) => {
+                                 let _ = state.graph_executor.execute_traced(&graph, &nodes, &edges, trigger_data, event.trace_id.clone()).await;
+                             }
+                             Err(e) => tracing::error!("Failed to fetch edges for graph {}: {}", graph.id, e),
+                         }
+                     }
+                     Err(e) => tracing::error!("Failed to fetch nodes for graph {}: {}", graph.id, e),
+                 }
+            }
+        }
+        Err(e) => tracing::error!("Failed to fetch workflows: {}", e),
+    }
+
+    Ok(())
+}
+
+/// `spawn_workflows` for handlers that only know the entity code
+async fn spawn_event_workflows(state: Arc<AppState>, entity_code: &str, event: EntityEvent) {
+    match state.metadata.get_entity_type(event.tenant_id, entity_code).await {
+        Ok(entity_type) => spawn_workflows(state, entity_type.id, event),
+        Err(e) => tracing::error!("Failed to resolve entity type for workflow trigger: {}", e),
+    }
+}
+
+/// Outcome of `update_versioned`
+enum VersionedUpdate {
+    Updated { old_data: Value, new_data: Value, version: i32 },
+    /// The expected version is stale; carries the current record
+    Conflict(Value),
+    NotFound,
+}
+
+/// Live record's data and version
+async fn current_version(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    id: Uuid,
+) -> Result<Option<(Value, i32)>, sqlx::Error> {
+    sqlx::query_as("SELECT data, version FROM entity_records WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL")
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Current record as returned to a client that has to merge
+fn conflict(id: Uuid, (data, version): (Value, i32)) -> VersionedUpdate {
+    let mut record = data;
+    if let Some(obj) = record.as_object_mut() {
+        obj.insert("id".to_string(), serde_json::json!(id));
+        obj.insert(VERSION_KEY.to_string(), serde_json::json!(version));
+    }
+    VersionedUpdate::Conflict(record)
+}
+
+/// Merge `patch` into the record if it is still at `expected_version`,
+/// bumping the version in the same statement
+async fn update_versioned(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    id: Uuid,
+    patch: &Value,
+    expected_version: i32,
+) -> Result<VersionedUpdate, sqlx::Error> {
+    let Some((old_data, version)) = current_version(conn, tenant_id, id).await? else {
+        return Ok(VersionedUpdate::NotFound);
+    };
+    if version != expected_version {
+        return Ok(conflict(id, (old_data, version)));
+    }
+
+    let updated: Option<(Value, i32)> = sqlx::query_as(
+        "UPDATE entity_records SET data = data || $1, version = version + 1, updated_at = NOW()
+         WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL AND version = $4
+         RETURNING data, version"
+    )
+    .bind(patch)
+    .bind(id)
+    .bind(tenant_id)
+    .bind(expected_version)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match updated {
+        Some((new_data, version)) => Ok(VersionedUpdate::Updated { old_data, new_data, version }),
+        // Another writer got in between the read and the update
+        None => Ok(match current_version(conn, tenant_id, id).await? {
+            Some(current) => conflict(id, current),
+            None => VersionedUpdate::NotFound,
+        }),
+    }
+}
+
+/// A successful update, as recorded in the audit trail
+struct AuditedUpdate<'a> {
+    entity_code: &'a str,
+    id: Uuid,
+    old_data: &'a Value,
+    new_data: &'a Value,
+    version: i32,
+}
+
+/// Write an `update` audit entry holding only the fields that changed
+///
+/// Runs in a savepoint so a failed audit write doesn't abort the caller's
+/// transaction.
+async fn audit_update(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    fields: &[FieldDef],
+    update: &AuditedUpdate<'_>,
+) -> Result<(), sqlx::Error> {
+    use sqlx::Connection;
+
+    let changes = field_changes(fields, update.old_data, update.new_data);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let entry = AuditLogEntry {
+        id: Uuid::new_v4(),
+        tenant_id,
+        user_id,
+        action: AuditAction::Update.to_string(),
+        resource_type: update.entity_code.to_string(),
+        resource_id: Some(update.id),
+        details: serde_json::json!({ "version": update.version }),
+        changes,
+        ip_address: None,
+        user_agent: None,
+        created_at: Utc::now(),
+    };
+    let mut savepoint = conn.begin().await?;
+    insert_entry(&mut *savepoint, &entry).await?;
+    savepoint.commit().await
+}
+
+/// Result of deleting a record
+enum DeleteOutcome {
+    Deleted(EntityEvent),
+    /// Links whose definition does not cascade still point at the record
+    Blocked(Vec<BlockingLink>),
+    NotFound,
+}
+
+fn blocked_response(links: Vec<BlockingLink>) -> axum::response::Response {
+    (
+        StatusCode::CONFLICT,
+        Json(serde_json::json!({
+            "error": "Record is still linked; remove these associations first",
+            "blocking_links": links
+        })),
+    ).into_response()
+}
+
+/// Hide a live record unless non-cascading links still point at it
+///
+/// Cascading links are kept, hidden with the record, so a restore brings
+/// them back; they are removed on hard delete.
+async fn soft_delete(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_code: &str,
+    id: Uuid,
+) -> Result<DeleteOutcome, sqlx::Error> {
+    use sqlx::Connection;
+
+    let mut tx = conn.begin().await?;
+    let blocking = blocking_links(&mut tx, tenant_id, id).await?;
+    if !blocking.is_empty() {
+        return Ok(DeleteOutcome::Blocked(blocking));
+    }
+    let old_data: Option<Value> = sqlx::query_scalar(
+        "UPDATE entity_records SET deleted_at = NOW(), version = version + 1 WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL RETURNING data"
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(data) = old_data else {
+        return Ok(DeleteOutcome::NotFound);
+    };
+    let event = EntityEvent::delete(tenant_id, entity_code, id, data, None);
+    commit_with_event(tx, &event).await?;
+    Ok(DeleteOutcome::Deleted(event))
+}
+
+/// Un-delete a soft-deleted record; returns the restore event, or `None` if it isn't deleted
+async fn restore(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_code: &str,
+    id: Uuid,
+) -> Result<Option<EntityEvent>, sqlx::Error> {
+    use sqlx::Connection;
+
+    let mut tx = conn.begin().await?;
+    let data: Option<Value> = sqlx::query_scalar(
+        "UPDATE entity_records SET deleted_at = NULL, updated_at = NOW(), version = version + 1 WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NOT NULL RETURNING data"
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(data) = data else {
+        return Ok(None);
+    };
+    let event = EntityEvent::restore(tenant_id, entity_code, id, data, None);
+    commit_with_event(tx, &event).await?;
+    Ok(Some(event))
+}
+
+/// Permanently remove a record, deleted or not, along with its cascading links
+///
+/// Emits a delete event even for already soft-deleted records so consumers
+/// holding copies can drop them.
+async fn hard_delete(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_code: &str,
+    id: Uuid,
+) -> Result<DeleteOutcome, sqlx::Error> {
+    use sqlx::Connection;
+
+    let mut tx = conn.begin().await?;
+    let blocking = blocking_links(&mut tx, tenant_id, id).await?;
+    if !blocking.is_empty() {
+        return Ok(DeleteOutcome::Blocked(blocking));
+    }
+    // Only cascading links are left
+    sqlx::query("DELETE FROM associations WHERE tenant_id = $1 AND (source_id = $2 OR target_id = $2)")
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let old_data: Option<Value> = sqlx::query_scalar(
+        "DELETE FROM entity_records WHERE id = $1 AND tenant_id = $2 RETURNING data"
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(data) = old_data else {
+        return Ok(DeleteOutcome::NotFound);
+    };
+    let event = EntityEvent::delete(tenant_id, entity_code, id, data, None);
+    commit_with_event(tx, &event).await?;
+    Ok(DeleteOutcome::Deleted(event))
+}
+
+/// Insert a record; returns its create event, enqueued in the same transaction
+async fn insert_record(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    entity_code: &str,
+    data: Value,
+) -> Result<EntityEvent, sqlx::Error> {
+    use sqlx::Connection;
+
+    let mut tx = conn.begin().await?;
+    let id: Uuid = sqlx::query_scalar(
+        "INSERT INTO entity_records (id, tenant_id, entity_type_id, data) VALUES ($1, $2, $3, $4) RETURNING id"
+    )
+    .bind(Uuid::new_v4())
+    .bind(tenant_id)
+    .bind(entity_type_id)
+    .bind(&data)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let event = EntityEvent::create(tenant_id, entity_code, id, data, None);
+    commit_with_event(tx, &event).await?;
+    Ok(event)
+}
+
+/// Enqueue `event` in the outbox and commit the change it describes
+async fn commit_with_event(mut tx: sqlx::Transaction<'_, sqlx::Postgres>, event: &EntityEvent) -> Result<(), sqlx::Error> {
+    outbox::enqueue(&mut tx, event).await?;
+    tx.commit().await
+}
+
+/// Result of `insert_batch`: create events for the inserted records, and rejected indexes
+struct BatchOutcome {
+    created: Vec<EntityEvent>,
+    errors: Vec<BatchError>,
+}
+
+/// Validate and insert `records` in a single transaction
+///
+/// Each insert runs in its own savepoint so a failing row doesn't abort the
+/// rest. With `stop_on_error` the first failure rolls everything back and
+/// `created` is empty. Create events are enqueued in the outbox before commit.
+#[allow(clippy::too_many_arguments)]
+async fn insert_batch(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    entity_code: &str,
+    fields: &[FieldDef],
+    records: &[Value],
+    stop_on_error: bool,
+    phone_region: &str,
+) -> Result<BatchOutcome, sqlx::Error> {
+    use sqlx::Connection;
+
+    let mut tx = conn.begin().await?;
+    let mut outcome = BatchOutcome { created: Vec::new(), errors: Vec::new() };
+
+    for (index, payload) in records.iter().enumerate() {
+        let data = match validate_and_process_payload(fields, payload, false, phone_region) {
+            Ok(d) => d,
+            Err(message) => {
+                outcome.errors.push(BatchError { index, message });
+                if stop_on_error {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let mut savepoint = tx.begin().await?;
+        let inserted = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO entity_records (id, tenant_id, entity_type_id, data) VALUES ($1, $2, $3, $4) RETURNING id"
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(entity_type_id)
+        .bind(&data)
+        .fetch_one(&mut *savepoint)
+        .await;
+
+        match inserted {
+            Ok(id) => {
+                savepoint.commit().await?;
+                outcome.created.push(EntityEvent::create(tenant_id, entity_code, id, data, None));
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                outcome.errors.push(BatchError { index, message: e.to_string() });
+                if stop_on_error {
+                    break;
+                }
+            }
+        }
+    }
+
+    if stop_on_error && !outcome.errors.is_empty() {
+        tx.rollback().await?;
+        outcome.created.clear();
+    } else {
+        for event in &outcome.created {
+            outbox::enqueue(&mut tx, event).await?;
+        }
+        tx.commit().await?;
+    }
+
+    Ok(outcome)
+}
+
+/// Computed values are derived on every read; a broken expression set
+/// (e.g. a dependency cycle) leaves the stored data untouched
+fn inject_computed_fields(fields: &[FieldDef], record: &mut Value) {
+    if let Err(e) = core_metadata::apply_computed_fields(fields, record) {
+        tracing::warn!("Failed to evaluate computed fields: {}", e);
+    }
+}
+
+/// Validate `payload` against `fields`, filling defaults and normalizing phone
+/// numbers to E.164 (national numbers are read in `phone_region`)
+pub(crate) fn validate_and_process_payload(
     fields: &[FieldDef], 
     payload: &Value, 
-    is_update: bool
+    is_update: bool,
+    phone_region: &str,
 ) -> Result<Value, String> {
     let mut processed = payload.clone();
 
@@ -489,6 +1250,12 @@ fn validate_and_process_payload(
     let obj = processed.as_object_mut().unwrap();
 
     for field in fields {
+        // Computed fields are read-only; never store client-supplied values
+        if field.is_computed() {
+            obj.remove(&field.name);
+            continue;
+        }
+
         let value = obj.get(&field.name);
 
         // 1. Default Value (if missing on CREATE)
@@ -513,6 +1280,15 @@ fn validate_and_process_payload(
                 validate_field_type(field, v)?;
             }
         }
+
+        // 4. Canonical phone numbers, so the same number dedups and dials alike
+        if let (FieldType::Phone, Some(Value::String(phone))) = (&field.field_type, obj.get(&field.name)) {
+            if !phone.trim().is_empty() {
+                let normalized = normalize_phone(phone, phone_region)
+                    .map_err(|e| format!("Field '{}': {}", field.label, e))?;
+                obj.insert(field.name.clone(), Value::String(normalized));
+            }
+        }
     }
     
     Ok(processed)
@@ -539,7 +1315,22 @@ fn validate_field_type(field: &FieldDef, value: &Value) -> Result<(), String> {
                 return Err(format!("Field '{}' must be a valid email", field.label));
             }
         },
-        FieldType::Number { .. } | FieldType::Money { .. } | FieldType::Score { .. } => {
+        FieldType::Money { currency_code } => {
+             let currency = currency_code.as_deref().unwrap_or(DEFAULT_CURRENCY);
+             let Some(money) = Money::from_value(value, currency) else {
+                 return Err(format!("Field '{}' must be an amount", field.label));
+             };
+             if let Some(val) = &field.validation {
+                 let n = money.to_major();
+                 if let Some(min) = val.min_value {
+                     if n < min { return Err(format!("'{}' must be >= {}", field.label, min)); }
+                 }
+                 if let Some(max) = val.max_value {
+                     if n > max { return Err(format!("'{}' must be <= {}", field.label, max)); }
+                 }
+             }
+        },
+        FieldType::Number { .. } | FieldType::Score { .. } => {
              if !value.is_number() {
                  return Err(format!("Field '{}' must be a number", field.label));
              }
@@ -586,6 +1377,16 @@ fn validate_field_type(field: &FieldDef, value: &Value) -> Result<(), String> {
                 return Err(format!("Field '{}' must be a URL string or metadata object", field.label));
             }
         },
+        FieldType::Signature => {
+            // Signatures are uploaded (and retained) first; the record keeps the URL
+            match value.as_str() {
+                Some(s) if s.starts_with("data:") => {
+                    return Err(format!("Field '{}' must be an uploaded signature URL, not image data", field.label));
+                }
+                Some(_) => {}
+                None => return Err(format!("Field '{}' must be a URL string", field.label)),
+            }
+        },
         FieldType::Json => {
             // No specific validation for raw JSON fields
         },
@@ -593,3 +1394,598 @@ fn validate_field_type(field: &FieldDef, value: &Value) -> Result<(), String> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use core_models::DEFAULT_PHONE_REGION;
+    use test_support::TestTenant;
+
+    /// Tenant with one `lead` entity type; removed again by `cleanup`
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+        fields: Vec<FieldDef>,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Entities Test").await;
+            let entity_type_id = tenant.entity_type("crm", "lead", "Lead", "Leads").await;
+            let fields = vec![
+                FieldDef::new(tenant.id, entity_type_id, "name", "Name", FieldType::Text).required(),
+                FieldDef::new(tenant.id, entity_type_id, "budget", "Budget", FieldType::Number { decimals: None }),
+            ];
+            Self { tenant, entity_type_id, fields }
+        }
+
+        async fn batch(&self, records: &[Value], stop_on_error: bool) -> BatchOutcome {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            insert_batch(&mut conn, self.tenant.id, self.entity_type_id, "lead", &self.fields, records, stop_on_error, DEFAULT_PHONE_REGION)
+                .await
+                .unwrap()
+        }
+
+        async fn page(&self, conn: &mut sqlx::PgConnection, params: &CursorParams<'_>) -> CursorPage {
+            fetch_cursor_page(conn, self.tenant.id, self.entity_type_id, &self.fields, params).await.unwrap()
+        }
+
+        async fn listed_ids(&self, include_deleted: bool) -> Vec<Uuid> {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            let params = CursorParams { sort: SortKey::CreatedAt, after: None, limit: 100, include_deleted, filter: None };
+            self.page(&mut conn, &params)
+                .await
+                .items
+                .iter()
+                .map(|r| r["id"].as_str().unwrap().parse().unwrap())
+                .collect()
+        }
+
+        async fn stored_names(&self) -> Vec<String> {
+            sqlx::query_scalar(
+                "SELECT data->>'name' FROM entity_records WHERE entity_type_id = $1 ORDER BY data->>'name'",
+            )
+            .bind(self.entity_type_id)
+            .fetch_all(&self.tenant.pool)
+            .await
+            .unwrap()
+        }
+
+        /// Link two records through a new `lead` -> `lead` definition
+        async fn link(&self, name: &str, cascade_delete: bool, source: Uuid, target: Uuid) -> Uuid {
+            let def_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO association_defs
+                    (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, cascade_delete)
+                 VALUES ($1, $2, 'lead', 'lead', $3, $3, $3, 'one_to_many', $4)",
+            )
+            .bind(def_id)
+            .bind(self.tenant.id)
+            .bind(name)
+            .bind(cascade_delete)
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+            let id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO associations (id, tenant_id, association_def_id, source_id, target_id) VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(id)
+            .bind(self.tenant.id)
+            .bind(def_id)
+            .bind(source)
+            .bind(target)
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+            id
+        }
+
+        async fn lookup(&self, tenant_id: Uuid, display_field: Option<&str>, filter: LookupFilter<'_>) -> Vec<LookupOption> {
+            let entity_type = EntityType {
+                id: self.entity_type_id,
+                display_field: display_field.map(String::from),
+                ..EntityType::new(self.tenant.id, "crm", "lead", "Lead")
+            };
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            lookup_options(&mut conn, tenant_id, &entity_type, filter, 10).await.unwrap()
+        }
+
+        async fn outbox_record_ids(&self) -> Vec<Uuid> {
+            sqlx::query_scalar("SELECT record_id FROM event_outbox WHERE tenant_id = $1 ORDER BY id")
+                .bind(self.tenant.id)
+                .fetch_all(&self.tenant.pool)
+                .await
+                .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    async fn insert_at(fx: &Fixture, name: &str, created_at: DateTime<Utc>) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO entity_records (tenant_id, entity_type_id, data, created_at) VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(fx.tenant.id)
+        .bind(fx.entity_type_id)
+        .bind(json!({ "name": name }))
+        .bind(created_at)
+        .fetch_one(&fx.tenant.pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_cursor_pagination_returns_each_row_once() {
+        let fx = Fixture::new().await;
+        let base = Utc::now() - chrono::Duration::hours(1);
+        let mut expected = Vec::new();
+        for i in 0..7 {
+            // Pairs share a timestamp so the id tie-breaker is exercised
+            expected.push(insert_at(&fx, &format!("lead-{}", i), base + chrono::Duration::minutes(i / 2)).await);
+        }
+
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let mut seen = Vec::new();
+        let mut after: Option<Cursor> = None;
+        loop {
+            let params = CursorParams { sort: SortKey::CreatedAt, after: after.as_ref(), limit: 3, include_deleted: false, filter: None };
+            let page = fx.page(&mut conn, &params).await;
+            seen.extend(page.items.iter().map(|r| r["id"].as_str().unwrap().parse::<Uuid>().unwrap()));
+
+            if seen.len() == 3 {
+                // A row created mid-iteration must not shift later pages
+                insert_at(&fx, "late", Utc::now()).await;
+            }
+            match page.next_cursor {
+                Some(c) => after = Some(Cursor::decode(&c).unwrap()),
+                None => break,
+            }
+        }
+
+        assert_eq!(seen.len(), expected.len());
+        for id in &expected {
+            assert_eq!(seen.iter().filter(|s| *s == id).count(), 1);
+        }
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[test]
+    fn test_cursor_is_opaque_and_round_trips() {
+        let cursor = Cursor(SortKey::UpdatedAt, Utc::now(), Uuid::new_v4());
+        let encoded = cursor.encode();
+
+        assert!(!encoded.contains("updated_at"));
+        assert_eq!(Cursor::decode(&encoded), Some(cursor));
+        assert_eq!(Cursor::decode("not-a-cursor"), None);
+    }
+
+    #[test]
+    fn test_phone_numbers_are_stored_in_e164() {
+        let fields = vec![FieldDef::new(Uuid::new_v4(), Uuid::new_v4(), "phone", "Phone", FieldType::Phone)];
+
+        let data = validate_and_process_payload(&fields, &json!({ "phone": "050 123 4567" }), false, "AE").unwrap();
+        assert_eq!(data["phone"], json!("+971501234567"));
+        let data = validate_and_process_payload(&fields, &json!({ "phone": "+971 50 123 4567" }), true, DEFAULT_PHONE_REGION).unwrap();
+        assert_eq!(data["phone"], json!("+971501234567"));
+
+        let err = validate_and_process_payload(&fields, &json!({ "phone": "call me" }), false, "AE").unwrap_err();
+        assert!(err.starts_with("Field 'Phone'"));
+    }
+
+    #[test]
+    fn test_signature_is_stored_as_url() {
+        let fields = vec![FieldDef::new(Uuid::new_v4(), Uuid::new_v4(), "signed_by", "Signature", FieldType::Signature)];
+        let url = "/api/v1/uploads/files/1b1d26f2-3b0e-4d3f-bc9f-62d7193274af/signatures/0d6b4c9e-7a51-4f0e-9a53-3f1e2c4b8d21.png";
+
+        let data = validate_and_process_payload(&fields, &json!({ "signed_by": url }), false, DEFAULT_PHONE_REGION).unwrap();
+        assert_eq!(data["signed_by"], json!(url));
+
+        // The raw canvas capture must be uploaded first
+        let err = validate_and_process_payload(&fields, &json!({ "signed_by": "data:image/png;base64,iVBORw0KGgo=" }), false, DEFAULT_PHONE_REGION)
+            .unwrap_err();
+        assert!(err.contains("uploaded signature URL"));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_nested_filter_selects_matching_records() {
+        let fx = Fixture::new().await;
+        let records = [
+            json!({"name": "Amal", "budget": 900000}),
+            json!({"name": "Noor", "budget": 20000}),
+            json!({"name": "Noora", "budget": 500}),
+            json!({"name": "Omar"}),
+            json!({"name": "Sara"}),
+        ];
+        fx.batch(&records, true).await;
+
+        // budget arrives as a string, as the filter builder sends it
+        let expr = filter::parse(
+            r#"{"or": [
+                {"and": [{"field": "budget", "op": "gte", "value": "10000"}, {"field": "name", "op": "contains", "value": "OO"}]},
+                {"and": [{"field": "name", "op": "equals", "value": "Omar"}, {"field": "budget", "op": "is_empty"}]}
+            ]}"#,
+        )
+        .unwrap();
+        let compiled = filter::compile(&expr, &fx.fields, CURSOR_FILTER_PARAM).unwrap();
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let params = CursorParams { sort: SortKey::CreatedAt, after: None, limit: 100, include_deleted: false, filter: Some(&compiled) };
+        let mut names: Vec<String> =
+            fx.page(&mut conn, &params).await.items.iter().map(|r| r["name"].as_str().unwrap().to_string()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Noor", "Omar"]);
+
+        let unknown = filter::parse(r#"{"and": [{"field": "owner", "op": "eq", "value": "x"}]}"#).unwrap();
+        assert_eq!(
+            filter::compile(&unknown, &fx.fields, CURSOR_FILTER_PARAM),
+            Err(filter::FilterError::UnknownField("owner".to_string()))
+        );
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_segment_filters_list() {
+        let fx = Fixture::new().await;
+        let records = [
+            json!({"name": "Amal", "budget": 900000}),
+            json!({"name": "Adel", "budget": 50000}),
+            json!({"name": "Omar", "budget": 1000}),
+        ];
+        fx.batch(&records, true).await;
+        let segment_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO segments (tenant_id, entity_type, name, filter) VALUES ($1, 'lead', 'Big budgets', $2) RETURNING id",
+        )
+        .bind(fx.tenant.id)
+        .bind(json!({"field": "budget", "op": "gt", "value": 10000}))
+        .fetch_one(&fx.tenant.pool)
+        .await
+        .unwrap();
+
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let mut names_for = async |query: ListQuery| {
+            let expr = resolve_filter(&mut conn, fx.tenant.id, "lead", &query).await.unwrap();
+            let compiled = expr.map(|e| filter::compile(&e, &fx.fields, CURSOR_FILTER_PARAM).unwrap());
+            let params = CursorParams { sort: SortKey::CreatedAt, after: None, limit: 100, include_deleted: false, filter: compiled.as_ref() };
+            let page = fx.page(&mut conn, &params).await;
+            let mut names: Vec<String> = page.items.iter().map(|r| r["name"].as_str().unwrap().to_string()).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names_for(ListQuery { segment: Some(segment_id), ..Default::default() }).await, vec!["Adel", "Amal"]);
+        let narrowed = ListQuery {
+            segment: Some(segment_id),
+            filter: Some(r#"{"field": "name", "op": "eq", "value": "Adel"}"#.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names_for(narrowed).await, vec!["Adel"]);
+
+        let other_entity = resolve_filter(&mut conn, fx.tenant.id, "deal", &ListQuery { segment: Some(segment_id), ..Default::default() }).await;
+        assert!(matches!(other_entity, Err(ApiError::BadRequest(_))));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_batch_all_valid() {
+        let fx = Fixture::new().await;
+
+        let outcome = fx
+            .batch(&[json!({"name": "Amal", "budget": 900000}), json!({"name": "Omar"})], false)
+            .await;
+
+        assert_eq!(outcome.created.len(), 2);
+        assert!(outcome.errors.is_empty());
+        assert_eq!(fx.stored_names().await, vec!["Amal", "Omar"]);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_batch_mixed_commits_valid_rows() {
+        let fx = Fixture::new().await;
+
+        let outcome = fx
+            .batch(
+                &[
+                    json!({"name": "Amal"}),
+                    json!({"budget": 100}),
+                    json!({"name": "Omar", "budget": "a lot"}),
+                    json!({"name": "Sara"}),
+                ],
+                false,
+            )
+            .await;
+
+        assert_eq!(outcome.created.len(), 2);
+        let failed: Vec<usize> = outcome.errors.iter().map(|e| e.index).collect();
+        assert_eq!(failed, vec![1, 2]);
+        assert_eq!(outcome.errors[0].message, "Field 'Name' is required");
+        assert_eq!(fx.stored_names().await, vec!["Amal", "Sara"]);
+        // One create event per committed row, written with the rows
+        let created: Vec<Uuid> = outcome.created.iter().map(|e| e.record_id).collect();
+        assert_eq!(fx.outbox_record_ids().await, created);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_batch_stop_on_error_rolls_back() {
+        let fx = Fixture::new().await;
+
+        let outcome = fx
+            .batch(&[json!({"name": "Amal"}), json!({"budget": 100}), json!({"name": "Sara"})], true)
+            .await;
+
+        assert!(outcome.created.is_empty());
+        assert_eq!(outcome.errors.len(), 1);
+        assert_eq!(outcome.errors[0].index, 1);
+        assert!(fx.stored_names().await.is_empty());
+        assert!(fx.outbox_record_ids().await.is_empty());
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_soft_delete_hides_and_restore_returns_record() {
+        let fx = Fixture::new().await;
+        let kept = insert_at(&fx, "Amal", Utc::now()).await;
+        let deleted = insert_at(&fx, "Omar", Utc::now()).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        let DeleteOutcome::Deleted(event) = soft_delete(&mut conn, fx.tenant.id, "lead", deleted).await.unwrap() else {
+            panic!("expected delete")
+        };
+        assert_eq!(event.event_type, core_node_engine::EventType::Delete);
+        assert_eq!(event.record_id, deleted);
+        assert_eq!(event.old_values, Some(json!({ "name": "Omar" })));
+        // Deleting twice is a no-op
+        assert!(matches!(
+            soft_delete(&mut conn, fx.tenant.id, "lead", deleted).await.unwrap(),
+            DeleteOutcome::NotFound
+        ));
+
+        assert_eq!(fx.listed_ids(false).await, vec![kept]);
+        assert_eq!(fx.listed_ids(true).await.len(), 2);
+
+        let event = restore(&mut conn, fx.tenant.id, "lead", deleted).await.unwrap().unwrap();
+        assert_eq!(event.event_type, core_node_engine::EventType::Restore);
+        assert_eq!(event.new_values, Some(json!({ "name": "Omar" })));
+        assert!(restore(&mut conn, fx.tenant.id, "lead", deleted).await.unwrap().is_none());
+        assert_eq!(fx.listed_ids(false).await.len(), 2);
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_hard_delete_removes_record_and_associations() {
+        let fx = Fixture::new().await;
+        let source = insert_at(&fx, "Amal", Utc::now()).await;
+        let target = insert_at(&fx, "Omar", Utc::now()).await;
+        fx.link("referred", true, source, target).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        // Cascading links neither block deletion nor survive a hard delete
+        assert!(matches!(
+            soft_delete(&mut conn, fx.tenant.id, "lead", target).await.unwrap(),
+            DeleteOutcome::Deleted(_)
+        ));
+        let DeleteOutcome::Deleted(event) = hard_delete(&mut conn, fx.tenant.id, "lead", target).await.unwrap() else {
+            panic!("expected delete")
+        };
+
+        assert_eq!(event.event_type, core_node_engine::EventType::Delete);
+        assert_eq!(fx.listed_ids(true).await, vec![source]);
+        let associations: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM associations WHERE tenant_id = $1")
+            .bind(fx.tenant.id)
+            .fetch_one(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(associations, 0);
+        assert!(restore(&mut conn, fx.tenant.id, "lead", target).await.unwrap().is_none());
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_restrict_links_block_delete_with_conflict() {
+        let fx = Fixture::new().await;
+        let agent = insert_at(&fx, "Amal", Utc::now()).await;
+        let lead = insert_at(&fx, "Omar", Utc::now()).await;
+        let restricting = fx.link("assigned_to", false, lead, agent).await;
+        fx.link("referred", true, agent, lead).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        for outcome in [
+            soft_delete(&mut conn, fx.tenant.id, "lead", agent).await.unwrap(),
+            hard_delete(&mut conn, fx.tenant.id, "lead", agent).await.unwrap(),
+        ] {
+            let DeleteOutcome::Blocked(links) = outcome else { panic!("expected blocking links") };
+            assert_eq!(links.len(), 1);
+            assert_eq!(links[0].association_id, restricting);
+            assert_eq!(links[0].association, "assigned_to");
+            assert_eq!((links[0].related_entity.as_str(), links[0].related_id), ("lead", lead));
+        }
+        assert_eq!(fx.listed_ids(false).await.len(), 2);
+        let associations: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM associations WHERE tenant_id = $1")
+            .bind(fx.tenant.id)
+            .fetch_one(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(associations, 2);
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_versioned_update_succeeds_and_bumps_version() {
+        let fx = Fixture::new().await;
+        let id = insert_at(&fx, "Amal", Utc::now()).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        let outcome = update_versioned(&mut conn, fx.tenant.id, id, &json!({ "budget": 750000 }), 1).await.unwrap();
+
+        let VersionedUpdate::Updated { old_data, new_data, version } = outcome else { panic!("expected update") };
+        assert_eq!(version, 2);
+        assert_eq!(old_data, json!({ "name": "Amal" }));
+        assert_eq!(new_data, json!({ "name": "Amal", "budget": 750000 }));
+        assert!(matches!(
+            update_versioned(&mut conn, fx.tenant.id, id, &json!({ "budget": 1 }), 2).await.unwrap(),
+            VersionedUpdate::Updated { version: 3, .. }
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_stale_version_conflict_returns_current_record() {
+        let fx = Fixture::new().await;
+        let id = insert_at(&fx, "Amal", Utc::now()).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        // Both clients read version 1; the second write is stale
+        update_versioned(&mut conn, fx.tenant.id, id, &json!({ "name": "Amal Haddad" }), 1).await.unwrap();
+        let outcome = update_versioned(&mut conn, fx.tenant.id, id, &json!({ "name": "Amal H." }), 1).await.unwrap();
+
+        let VersionedUpdate::Conflict(current) = outcome else { panic!("expected conflict") };
+        assert_eq!(current["name"], json!("Amal Haddad"));
+        assert_eq!(current[VERSION_KEY], json!(2));
+        assert_eq!(current["id"], json!(id));
+        assert_eq!(fx.stored_names().await, vec!["Amal Haddad"]);
+        assert!(matches!(
+            update_versioned(&mut conn, fx.tenant.id, Uuid::new_v4(), &json!({}), 1).await.unwrap(),
+            VersionedUpdate::NotFound
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_update_audit_records_only_changed_fields() {
+        let mut fx = Fixture::new().await;
+        for (name, label) in [("stage", "Stage"), ("source", "Source"), ("notes", "Notes")] {
+            fx.fields.push(FieldDef::new(fx.tenant.id, fx.entity_type_id, name, label, FieldType::Text));
+        }
+        let id = insert_at(&fx, "Amal", Utc::now()).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let seed = json!({ "budget": 500000, "stage": "lead", "source": "web", "notes": "Call back" });
+        update_versioned(&mut conn, fx.tenant.id, id, &seed, 1).await.unwrap();
+
+        let patch = json!({ "stage": "customer", "budget": 650000, "source": "web" });
+        let VersionedUpdate::Updated { old_data, new_data, version } =
+            update_versioned(&mut conn, fx.tenant.id, id, &patch, 2).await.unwrap()
+        else {
+            panic!("expected update")
+        };
+        let update = AuditedUpdate { entity_code: "lead", id, old_data: &old_data, new_data: &new_data, version };
+        audit_update(&mut conn, fx.tenant.id, None, &fx.fields, &update).await.unwrap();
+
+        let history = crate::middleware::AuditLogger::new(fx.tenant.pool.clone())
+            .query_resource_history(fx.tenant.id, "lead", id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, "update");
+        assert_eq!(history[0].details["version"], json!(3));
+        let changed: Vec<(&str, &str)> =
+            history[0].changes.iter().map(|c| (c.label.as_str(), c.new.as_str().unwrap_or_default())).collect();
+        assert_eq!(changed, vec![("Budget", ""), ("Stage", "customer")]);
+        assert_eq!(history[0].changes[0].new, json!(650000));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    fn labels(options: &[LookupOption]) -> Vec<&str> {
+        options.iter().map(|o| o.label.as_str()).collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_lookup_prefix_search() {
+        let fx = Fixture::new().await;
+        for name in ["Marina Villa", "Palm Tower", "Marble House", "Old Marina", "100% Mar"] {
+            insert_at(&fx, name, Utc::now()).await;
+        }
+
+        let options = fx.lookup(fx.tenant.id, None, LookupFilter::Prefix("mar")).await;
+        assert_eq!(labels(&options), vec!["100% Mar", "Marble House", "Marina Villa", "Old Marina"]);
+        // LIKE wildcards in the search are literal
+        assert_eq!(labels(&fx.lookup(fx.tenant.id, None, LookupFilter::Prefix("100%")).await), vec!["100% Mar"]);
+        assert!(fx.lookup(fx.tenant.id, None, LookupFilter::Prefix("%")).await.is_empty());
+
+        // A configured display field labels the options
+        sqlx::query("UPDATE entity_records SET data = data || jsonb_build_object('code', 'L-' || (data->>'name')) WHERE entity_type_id = $1")
+            .bind(fx.entity_type_id)
+            .execute(&fx.tenant.pool)
+            .await
+            .unwrap();
+        let options = fx.lookup(fx.tenant.id, Some("code"), LookupFilter::Prefix("l-pa")).await;
+        assert_eq!(labels(&options), vec!["L-Palm Tower"]);
+
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_lookup_resolves_ids_in_one_batch() {
+        let fx = Fixture::new().await;
+        let amal = insert_at(&fx, "Amal", Utc::now()).await;
+        let omar = insert_at(&fx, "Omar", Utc::now()).await;
+        insert_at(&fx, "Sara", Utc::now()).await;
+        let deleted = insert_at(&fx, "Zaid", Utc::now()).await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        soft_delete(&mut conn, fx.tenant.id, "lead", deleted).await.unwrap();
+
+        let ids = parse_ids(&format!("{}, {},{},", omar, amal, deleted)).unwrap();
+        let options = fx.lookup(fx.tenant.id, None, LookupFilter::Ids(&ids)).await;
+        assert_eq!(
+            options,
+            vec![
+                LookupOption { value: amal, label: "Amal".to_string() },
+                LookupOption { value: omar, label: "Omar".to_string() },
+            ]
+        );
+        assert!(parse_ids("not-an-id").is_err());
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_lookup_is_scoped_to_tenant() {
+        let fx = Fixture::new().await;
+        let other = Fixture::new().await;
+        let id = insert_at(&fx, "Marina Villa", Utc::now()).await;
+
+        // Another tenant neither finds the record nor resolves its id
+        assert!(fx.lookup(other.tenant.id, None, LookupFilter::Prefix("mar")).await.is_empty());
+        assert!(fx.lookup(other.tenant.id, None, LookupFilter::Ids(&[id])).await.is_empty());
+        assert_eq!(fx.lookup(fx.tenant.id, None, LookupFilter::Ids(&[id])).await.len(), 1);
+
+        other.cleanup().await;
+        fx.cleanup().await;
+    }
+}
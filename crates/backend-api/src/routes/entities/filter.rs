@@ -0,0 +1,335 @@
+//! Filter Translation
+//!
+//! Compiles a [`FilterExpr`] into a parameterized `WHERE` fragment over
+//! `entity_records`. Field names are checked against the entity's
+//! `FieldDef`s and every name and value is bound, so nothing from the client
+//! is spliced into the SQL text.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use core_models::{FieldDef, FieldType, FilterCondition, FilterExpr, FilterOp, MAX_FILTER_DEPTH};
+use serde_json::Value;
+use sqlx::postgres::{PgArguments, Postgres};
+use sqlx::query::Query;
+
+/// System columns that can be filtered on besides the entity's fields
+const TIMESTAMP_COLUMNS: [&str; 2] = ["created_at", "updated_at"];
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum FilterError {
+    #[error("Unknown filter field '{0}'")]
+    UnknownField(String),
+    #[error("Operator {op:?} is not supported on field '{field}'")]
+    UnsupportedOperator { field: String, op: FilterOp },
+    #[error("Invalid value for filter on '{field}': {message}")]
+    InvalidValue { field: String, message: String },
+    #[error("Filter nests deeper than {MAX_FILTER_DEPTH} groups")]
+    TooDeep,
+}
+
+/// A bound filter value
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterParam {
+    Text(String),
+    Json(Value),
+    JsonList(Vec<Value>),
+    Timestamp(DateTime<Utc>),
+}
+
+/// SQL fragment plus the values for its placeholders, in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlFilter {
+    pub sql: String,
+    pub params: Vec<FilterParam>,
+}
+
+impl SqlFilter {
+    /// Bind the parameters onto `query`, after any it already has
+    pub fn bind<'q>(&self, mut query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        for param in &self.params {
+            query = match param.clone() {
+                FilterParam::Text(v) => query.bind(v),
+                FilterParam::Json(v) => query.bind(v),
+                FilterParam::JsonList(v) => query.bind(v),
+                FilterParam::Timestamp(v) => query.bind(v),
+            };
+        }
+        query
+    }
+}
+
+/// Compile `expr` for an entity with `fields`; placeholders start at
+/// `$first_param`
+pub fn compile(expr: &FilterExpr, fields: &[FieldDef], first_param: usize) -> Result<SqlFilter, FilterError> {
+    if expr.depth() > MAX_FILTER_DEPTH {
+        return Err(FilterError::TooDeep);
+    }
+    let mut compiler = Compiler { fields, first_param, params: Vec::new() };
+    let sql = compiler.expr(expr)?;
+    Ok(SqlFilter { sql, params: compiler.params })
+}
+
+/// Parse the JSON `filter` query parameter
+pub fn parse(raw: &str) -> Result<FilterExpr, String> {
+    serde_json::from_str(raw).map_err(|e| format!("Invalid filter: {}", e))
+}
+
+struct Compiler<'a> {
+    fields: &'a [FieldDef],
+    first_param: usize,
+    params: Vec<FilterParam>,
+}
+
+impl Compiler<'_> {
+    fn placeholder(&mut self, param: FilterParam) -> String {
+        self.params.push(param);
+        format!("${}", self.first_param + self.params.len() - 1)
+    }
+
+    fn expr(&mut self, expr: &FilterExpr) -> Result<String, FilterError> {
+        match expr {
+            FilterExpr::And { and } => self.group(and, " AND ", "TRUE"),
+            FilterExpr::Or { or } => self.group(or, " OR ", "FALSE"),
+            FilterExpr::Condition(condition) => self.condition(condition),
+        }
+    }
+
+    /// An empty AND matches everything, an empty OR nothing
+    fn group(&mut self, children: &[FilterExpr], joiner: &str, empty: &str) -> Result<String, FilterError> {
+        if children.is_empty() {
+            return Ok(empty.to_string());
+        }
+        let parts = children.iter().map(|child| self.expr(child)).collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("({})", parts.join(joiner)))
+    }
+
+    fn condition(&mut self, condition: &FilterCondition) -> Result<String, FilterError> {
+        if condition.op.takes_value() && condition.value.is_null() {
+            return Err(invalid(condition, "a value is required"));
+        }
+        if TIMESTAMP_COLUMNS.contains(&condition.field.as_str()) {
+            return self.timestamp_condition(condition);
+        }
+
+        let field = self
+            .fields
+            .iter()
+            .find(|f| f.name == condition.field)
+            .ok_or_else(|| FilterError::UnknownField(condition.field.clone()))?;
+        // Computed values only exist on read
+        if matches!(field.field_type, FieldType::Computed { .. }) {
+            return Err(FilterError::UnknownField(condition.field.clone()));
+        }
+
+        let key = self.placeholder(FilterParam::Text(field.name.clone()));
+        let col = format!("data->{}", key);
+        let value = |c: &mut Self, v: &Value| {
+            let coerced = coerce(&field.field_type, v);
+            c.placeholder(FilterParam::Json(coerced))
+        };
+
+        let sql = match condition.op {
+            FilterOp::Eq => format!("{} = {}", col, value(self, &condition.value)),
+            FilterOp::Neq => format!("{} IS DISTINCT FROM {}", col, value(self, &condition.value)),
+            FilterOp::Contains | FilterOp::NotContains => {
+                let contains = if is_list(&field.field_type) {
+                    let item = coerce(&field.field_type, &condition.value);
+                    format!("{} @> {}", col, self.placeholder(FilterParam::Json(Value::Array(vec![item]))))
+                } else {
+                    let needle = match &condition.value {
+                        Value::String(s) => s.clone(),
+                        Value::Number(n) => n.to_string(),
+                        _ => return Err(invalid(condition, "expected text")),
+                    };
+                    let pattern = self.placeholder(FilterParam::Text(format!("%{}%", escape_like(&needle))));
+                    format!("data->>{} ILIKE {}", key, pattern)
+                };
+                if condition.op == FilterOp::Contains {
+                    contains
+                } else {
+                    format!("NOT COALESCE({}, FALSE)", contains)
+                }
+            }
+            FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte => {
+                let v = value(self, &condition.value);
+                format!("(jsonb_typeof({col}) = jsonb_typeof({v}) AND {col} {} {v})", comparison(condition.op))
+            }
+            FilterOp::Between => {
+                let (low, high) = bounds(condition)?;
+                let (low, high) = (value(self, low), value(self, high));
+                format!("(jsonb_typeof({col}) = jsonb_typeof({low}) AND {col} >= {low} AND {col} <= {high})")
+            }
+            FilterOp::In | FilterOp::NotIn => {
+                let Value::Array(items) = &condition.value else {
+                    return Err(invalid(condition, "expected an array"));
+                };
+                let items = items.iter().map(|v| coerce(&field.field_type, v)).collect();
+                let any = format!("{} = ANY({})", col, self.placeholder(FilterParam::JsonList(items)));
+                if condition.op == FilterOp::In {
+                    any
+                } else {
+                    format!("NOT COALESCE({}, FALSE)", any)
+                }
+            }
+            FilterOp::IsEmpty => empty_check(&col),
+            FilterOp::IsNotEmpty => format!("NOT {}", empty_check(&col)),
+            FilterOp::IsTrue => format!("{} = 'true'::jsonb", col),
+            FilterOp::IsFalse => format!("{} IS DISTINCT FROM 'true'::jsonb", col),
+        };
+        Ok(sql)
+    }
+
+    /// `created_at` / `updated_at`: comparisons against RFC 3339 timestamps
+    /// or plain dates (midnight UTC)
+    fn timestamp_condition(&mut self, condition: &FilterCondition) -> Result<String, FilterError> {
+        let column = condition.field.as_str();
+        let timestamp = |c: &mut Self, v: &Value| {
+            let ts = parse_timestamp(v).ok_or_else(|| invalid(condition, "expected a timestamp"))?;
+            Ok::<_, FilterError>(c.placeholder(FilterParam::Timestamp(ts)))
+        };
+
+        let sql = match condition.op {
+            FilterOp::Eq => format!("{} = {}", column, timestamp(self, &condition.value)?),
+            FilterOp::Neq => format!("{} <> {}", column, timestamp(self, &condition.value)?),
+            FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte => {
+                format!("{} {} {}", column, comparison(condition.op), timestamp(self, &condition.value)?)
+            }
+            FilterOp::Between => {
+                let (low, high) = bounds(condition)?;
+                let (low, high) = (timestamp(self, low)?, timestamp(self, high)?);
+                format!("{} BETWEEN {} AND {}", column, low, high)
+            }
+            op => return Err(FilterError::UnsupportedOperator { field: condition.field.clone(), op }),
+        };
+        Ok(sql)
+    }
+}
+
+fn invalid(condition: &FilterCondition, message: &str) -> FilterError {
+    FilterError::InvalidValue { field: condition.field.clone(), message: message.to_string() }
+}
+
+fn comparison(op: FilterOp) -> &'static str {
+    match op {
+        FilterOp::Gt => ">",
+        FilterOp::Gte => ">=",
+        FilterOp::Lt => "<",
+        _ => "<=",
+    }
+}
+
+fn bounds(condition: &FilterCondition) -> Result<(&Value, &Value), FilterError> {
+    match condition.value.as_array().map(Vec::as_slice) {
+        Some([low, high]) => Ok((low, high)),
+        _ => Err(invalid(condition, "expected [low, high]")),
+    }
+}
+
+/// Missing, null, "" and [] all count as empty
+fn empty_check(col: &str) -> String {
+    format!("({col} IS NULL OR {col} IN ('null'::jsonb, '\"\"'::jsonb, '[]'::jsonb))")
+}
+
+fn is_list(field_type: &FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::MultiSelect { .. } | FieldType::MultiLink { .. } | FieldType::TagList
+    )
+}
+
+/// Match the stored JSON type: the filter builder sends numbers and booleans
+/// as strings
+fn coerce(field_type: &FieldType, value: &Value) -> Value {
+    let Value::String(s) = value else {
+        return value.clone();
+    };
+    match field_type {
+        FieldType::Number { .. }
+        | FieldType::Money { .. }
+        | FieldType::Score { .. }
+        | FieldType::Progress { .. }
+        | FieldType::Rating { .. } => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or_else(|| value.clone()),
+        FieldType::Boolean => match s.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+pub(super) fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    let s = value.as_str()?;
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field(name: &str, field_type: FieldType) -> FieldDef {
+        FieldDef::new(uuid::Uuid::nil(), uuid::Uuid::nil(), name, name, field_type)
+    }
+
+    fn fields() -> Vec<FieldDef> {
+        vec![
+            field("name", FieldType::Text),
+            field("budget", FieldType::Number { decimals: None }),
+            field("tags", FieldType::TagList),
+        ]
+    }
+
+    #[test]
+    fn test_nested_groups_compile_to_bound_sql() {
+        let expr: FilterExpr = serde_json::from_value(json!({"and": [
+            {"field": "name", "op": "contains", "value": "50%_off"},
+            {"or": [
+                {"field": "budget", "op": "gte", "value": "1000"},
+                {"field": "tags", "op": "contains", "value": "vip"},
+                {"field": "created_at", "op": "before", "value": "2025-01-01"}
+            ]}
+        ]}))
+        .unwrap();
+
+        let filter = compile(&expr, &fields(), 3).unwrap();
+        assert_eq!(
+            filter.sql,
+            "(data->>$3 ILIKE $4 AND \
+             ((jsonb_typeof(data->$5) = jsonb_typeof($6) AND data->$5 >= $6) OR data->$7 @> $8 OR created_at < $9))"
+        );
+        assert_eq!(filter.params[1], FilterParam::Text("%50\\%\\_off%".to_string()));
+        assert_eq!(filter.params[3], FilterParam::Json(json!(1000.0)));
+        assert_eq!(filter.params[5], FilterParam::Json(json!(["vip"])));
+        assert_eq!(filter.params.len(), 7);
+    }
+
+    #[test]
+    fn test_rejects_unknown_fields_and_bad_values() {
+        let unknown = FilterExpr::condition("name'); DROP TABLE x; --", FilterOp::Eq, json!("x"));
+        assert!(matches!(compile(&unknown, &fields(), 1), Err(FilterError::UnknownField(_))));
+
+        let between = FilterExpr::condition("budget", FilterOp::Between, json!([1]));
+        assert!(matches!(compile(&between, &fields(), 1), Err(FilterError::InvalidValue { .. })));
+
+        let missing = FilterExpr::condition("name", FilterOp::Eq, Value::Null);
+        assert!(matches!(compile(&missing, &fields(), 1), Err(FilterError::InvalidValue { .. })));
+
+        let deep = (0..=MAX_FILTER_DEPTH).fold(FilterExpr::condition("name", FilterOp::IsEmpty, Value::Null), |e, _| {
+            FilterExpr::And { and: vec![e] }
+        });
+        assert_eq!(compile(&deep, &fields(), 1), Err(FilterError::TooDeep));
+    }
+}
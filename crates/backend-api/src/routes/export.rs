@@ -0,0 +1,724 @@
+//! Entity Export - streams a view's records as CSV or XLSX
+//!
+//! Columns, filters and sort come from the requested view (or the entity's
+//! default view). Rows are encoded as they are read from the database and
+//! sent to the client in chunks, so memory use does not grow with the export.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, NaiveDate};
+use core_models::{FieldDef, FieldType, FilterOperator, Money, SortDirection, ViewDef, ViewFilter, ViewSort, DEFAULT_CURRENCY};
+use futures::TryStreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::Row;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use zip::{write::{SimpleFileOptions, StreamWriter}, CompressionMethod, ZipWriter};
+
+use crate::middleware::database::RlsConn;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::routes::entities::record_from_row;
+use crate::state::AppState;
+
+/// Exports matching more records than this are refused; narrow the view's filters
+pub const MAX_EXPORT_ROWS: i64 = 50_000;
+
+/// Encoded bytes buffered before a chunk is sent to the client
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// View to export; defaults to the entity's default view
+    pub view_id: Option<Uuid>,
+}
+
+/// One exported column: the record key it reads and its header label
+#[derive(Debug, Clone)]
+pub struct ExportColumn {
+    pub field: String,
+    pub header: String,
+    pub field_type: Option<FieldType>,
+}
+
+/// A formatted cell; numbers stay numeric so spreadsheets can sum them
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+// ============================================================================
+// Routes
+// ============================================================================
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/entities/:entity_code/export", get(export_records))
+        .route("/records/:entity_code/export", get(export_records))
+}
+
+/// GET /entities/:entity_code/export?format=csv|xlsx&view_id=
+async fn export_records(
+    State(state): State<Arc<AppState>>,
+    Path(entity_code): Path<String>,
+    Query(query): Query<ExportQuery>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+) -> Response {
+    let entity_type = match state.metadata.get_entity_type(tenant.id, &entity_code).await {
+        Ok(e) => e,
+        Err(_) => return (StatusCode::NOT_FOUND, format!("Entity type '{}' not found", entity_code)).into_response(),
+    };
+    let fields = state.metadata.get_fields(tenant.id, entity_type.id).await.unwrap_or_default();
+
+    let view = match query.view_id {
+        Some(view_id) => {
+            let views = state.metadata.get_views(tenant.id, entity_type.id).await.unwrap_or_default();
+            match views.into_iter().find(|v| v.id == view_id) {
+                Some(view) => Some(view),
+                None => return (StatusCode::NOT_FOUND, "View not found").into_response(),
+            }
+        }
+        None => state.metadata.get_default_view(tenant.id, entity_type.id).await.ok().flatten(),
+    };
+
+    let columns = export_columns(view.as_ref(), &fields);
+    let (filters, sort) = view.map(|v| (v.filters, v.sort)).unwrap_or_default();
+    let select = RecordSelect::new(&filters, &sort);
+
+    let total = match select.count(&mut conn, tenant.id, entity_type.id).await {
+        Ok(total) => total,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if total > MAX_EXPORT_ROWS {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Export matches {} records; narrow the view's filters to at most {}", total, MAX_EXPORT_ROWS),
+        )
+            .into_response();
+    }
+
+    let writer: Box<dyn ExportWriter> = match query.format {
+        ExportFormat::Csv => Box::new(CsvWriter::default()),
+        ExportFormat::Xlsx => match XlsxWriter::new() {
+            Ok(writer) => Box::new(writer),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+    };
+
+    // The connection moves into the task, which feeds encoded chunks to the body
+    let (tx, rx) = mpsc::channel(4);
+    let export = Export { tenant_id: tenant.id, entity_type_id: entity_type.id, fields, columns, select };
+    tokio::spawn(export.run(conn, writer, tx));
+
+    let body = Body::from_stream(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }));
+    let disposition = format!("attachment; filename=\"{}.{}\"", entity_code, query.format.extension());
+
+    (
+        [(header::CONTENT_TYPE, query.format.content_type().to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        body,
+    )
+        .into_response()
+}
+
+// ============================================================================
+// Query
+// ============================================================================
+
+/// A bound parameter of the generated query
+enum Bind {
+    Text(String),
+    Json(Value),
+}
+
+/// Filter and sort SQL for a view over `entity_records.data`
+///
+/// Field names and values are always bound, never interpolated. Placeholders
+/// start at `$3`; `$1` and `$2` are the tenant and entity type.
+pub(crate) struct RecordSelect {
+    pub(crate) conditions: String,
+    order_by: String,
+    binds: Vec<Bind>,
+}
+
+impl RecordSelect {
+    pub(crate) fn new(filters: &[ViewFilter], sort: &[ViewSort]) -> Self {
+        let mut select = RecordSelect { conditions: String::new(), order_by: String::new(), binds: Vec::new() };
+
+        for filter in filters {
+            let condition = select.filter_condition(filter);
+            select.conditions.push_str(" AND ");
+            select.conditions.push_str(&condition);
+        }
+
+        let mut order: Vec<String> = sort
+            .iter()
+            .map(|s| {
+                let direction = match s.direction {
+                    SortDirection::Asc => "ASC",
+                    SortDirection::Desc => "DESC",
+                };
+                match s.field.as_str() {
+                    "created_at" | "updated_at" => format!("{} {}", s.field, direction),
+                    field => {
+                        let key = select.bind(Bind::Text(field.to_string()));
+                        format!("data -> {}::text {} NULLS LAST", key, direction)
+                    }
+                }
+            })
+            .collect();
+        order.push("created_at DESC, id DESC".to_string());
+        select.order_by = format!("ORDER BY {}", order.join(", "));
+
+        select
+    }
+
+    /// The first placeholder free for binds added after the view's own
+    pub(crate) fn next_param(&self) -> usize {
+        self.binds.len() + 3
+    }
+
+    fn bind(&mut self, bind: Bind) -> String {
+        self.binds.push(bind);
+        format!("${}", self.binds.len() + 2)
+    }
+
+    fn filter_condition(&mut self, filter: &ViewFilter) -> String {
+        let key = self.bind(Bind::Text(filter.field.clone()));
+        let json = format!("(data -> {}::text)", key);
+        let text = format!("lower(data ->> {}::text)", key);
+
+        match filter.operator {
+            FilterOperator::IsNull => return format!("COALESCE({}, 'null'::jsonb) = 'null'::jsonb", json),
+            FilterOperator::IsNotNull => return format!("COALESCE({}, 'null'::jsonb) <> 'null'::jsonb", json),
+            _ => {}
+        }
+
+        let value = self.bind(Bind::Json(filter.value.clone()));
+        let value_text = format!("lower({}::jsonb #>> '{{}}')", value);
+        match filter.operator {
+            FilterOperator::Equals => format!("{} = {}::jsonb", json, value),
+            FilterOperator::NotEquals => format!("{} IS DISTINCT FROM {}::jsonb", json, value),
+            FilterOperator::Contains => format!("strpos({}, {}) > 0", text, value_text),
+            FilterOperator::NotContains => format!("COALESCE(strpos({}, {}), 0) = 0", text, value_text),
+            FilterOperator::StartsWith => format!("starts_with({}, {})", text, value_text),
+            FilterOperator::EndsWith => format!("right({}, length({})) = {}", text, value_text, value_text),
+            FilterOperator::GreaterThan => format!("{} > {}::jsonb", json, value),
+            FilterOperator::GreaterThanOrEqual => format!("{} >= {}::jsonb", json, value),
+            FilterOperator::LessThan => format!("{} < {}::jsonb", json, value),
+            FilterOperator::LessThanOrEqual => format!("{} <= {}::jsonb", json, value),
+            FilterOperator::In => format!("{}::jsonb @> jsonb_build_array({})", value, json),
+            FilterOperator::NotIn => format!("NOT ({}::jsonb @> jsonb_build_array({}))", value, json),
+            FilterOperator::Between => {
+                format!("{} BETWEEN ({}::jsonb -> 0) AND ({}::jsonb -> 1)", json, value, value)
+            }
+            FilterOperator::IsNull | FilterOperator::IsNotNull => unreachable!(),
+        }
+    }
+
+    pub(crate) fn query<'q>(&'q self, sql: &'q str, tenant_id: Uuid, entity_type_id: Uuid) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+        self.binds.iter().fold(sqlx::query(sql).bind(tenant_id).bind(entity_type_id), |q, bind| match bind {
+            Bind::Text(s) => q.bind(s.as_str()),
+            Bind::Json(v) => q.bind(v),
+        })
+    }
+
+    async fn count(&self, conn: &mut sqlx::PgConnection, tenant_id: Uuid, entity_type_id: Uuid) -> Result<i64, sqlx::Error> {
+        let sql = format!(
+            "SELECT COUNT(*) AS count FROM entity_records
+             WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL{}",
+            self.conditions
+        );
+        self.query(&sql, tenant_id, entity_type_id).fetch_one(conn).await?.try_get("count")
+    }
+}
+
+// ============================================================================
+// Streaming
+// ============================================================================
+
+struct Export {
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    fields: Vec<FieldDef>,
+    columns: Vec<ExportColumn>,
+    select: RecordSelect,
+}
+
+impl Export {
+    async fn run(self, mut conn: RlsConn, mut writer: Box<dyn ExportWriter>, tx: mpsc::Sender<io::Result<Vec<u8>>>) {
+        if let Err(e) = self.write_all(&mut conn, &mut writer, &tx).await {
+            tracing::error!("Export of entity type {} failed: {}", self.entity_type_id, e);
+            let _ = tx.send(Err(e)).await;
+            return;
+        }
+        let tail = writer.finish();
+        let _ = tx.send(tail).await;
+    }
+
+    /// Encode every row, sending a chunk whenever enough output is buffered
+    async fn write_all(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        writer: &mut Box<dyn ExportWriter>,
+        tx: &mpsc::Sender<io::Result<Vec<u8>>>,
+    ) -> io::Result<()> {
+        let header: Vec<Cell> = self.columns.iter().map(|c| Cell::Text(c.header.clone())).collect();
+        writer.write_row(&header)?;
+
+        let sql = format!(
+            "SELECT id, data, created_at, updated_at, deleted_at, version FROM entity_records
+             WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL{} {}",
+            self.select.conditions, self.select.order_by
+        );
+        let mut rows = self.select.query(&sql, self.tenant_id, self.entity_type_id).fetch(conn);
+
+        while let Some(row) = rows.try_next().await.map_err(io::Error::other)? {
+            let record = record_from_row(&row, &self.fields);
+            writer.write_row(&row_cells(&self.columns, &record))?;
+
+            // A closed channel means the client went away
+            if writer.buffered() >= CHUNK_SIZE && tx.send(Ok(writer.take_chunk())).await.is_err() {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Columns to export: the view's visible columns in display order, falling
+/// back to the entity's list fields (or every field) when there is no view
+pub fn export_columns(view: Option<&ViewDef>, fields: &[FieldDef]) -> Vec<ExportColumn> {
+    let column = |name: &str| {
+        let field = fields.iter().find(|f| f.name == name);
+        ExportColumn {
+            field: name.to_string(),
+            header: field.map(|f| f.label.clone()).unwrap_or_else(|| name.to_string()),
+            field_type: field.map(|f| f.field_type.clone()),
+        }
+    };
+
+    let mut view_columns: Vec<_> = view.map(|v| v.columns.iter().filter(|c| c.visible).collect()).unwrap_or_default();
+    if !view_columns.is_empty() {
+        view_columns.sort_by_key(|c| c.sort_order);
+        return view_columns.into_iter().map(|c| column(&c.field)).collect();
+    }
+
+    let mut listed: Vec<&FieldDef> = fields.iter().filter(|f| f.show_in_list).collect();
+    if listed.is_empty() {
+        listed = fields.iter().collect();
+    }
+    listed.sort_by_key(|f| f.sort_order);
+    listed.into_iter().map(|f| column(&f.name)).collect()
+}
+
+fn row_cells(columns: &[ExportColumn], record: &Value) -> Vec<Cell> {
+    columns
+        .iter()
+        .map(|c| format_cell(c.field_type.as_ref(), record.get(&c.field).unwrap_or(&Value::Null)))
+        .collect()
+}
+
+/// Render a value per its field's metadata: money with its currency, dates as
+/// ISO dates, booleans as Yes/No and lists joined with `; `
+pub fn format_cell(field_type: Option<&FieldType>, value: &Value) -> Cell {
+    match (field_type, value) {
+        (_, Value::Null) => Cell::Text(String::new()),
+        (Some(FieldType::Money { .. }), Value::Object(_)) => match Money::from_value(value, DEFAULT_CURRENCY) {
+            Some(money) => Cell::Text(format_money(money.to_major(), Some(&money.currency))),
+            None => Cell::Text(text(value)),
+        },
+        (Some(FieldType::Money { currency_code }), _) => match number(value) {
+            Some(amount) => Cell::Text(format_money(amount, currency_code.as_deref())),
+            None => Cell::Text(text(value)),
+        },
+        (Some(FieldType::Number { decimals }), _) => match (number(value), decimals) {
+            (Some(n), Some(d)) => {
+                let scale = 10f64.powi(i32::from(*d));
+                Cell::Number((n * scale).round() / scale)
+            }
+            (Some(n), None) => Cell::Number(n),
+            (None, _) => Cell::Text(text(value)),
+        },
+        (Some(FieldType::Date), Value::String(s)) => Cell::Text(
+            DateTime::parse_from_rfc3339(s)
+                .map(|dt| dt.date_naive())
+                .or_else(|_| NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d"))
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|_| s.clone()),
+        ),
+        (Some(FieldType::DateTime), Value::String(s)) => Cell::Text(
+            DateTime::parse_from_rfc3339(s)
+                .map(|dt| dt.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|_| s.clone()),
+        ),
+        (_, Value::Bool(b)) => Cell::Text(if *b { "Yes" } else { "No" }.to_string()),
+        (_, Value::Number(n)) => n.as_f64().map(Cell::Number).unwrap_or_else(|| Cell::Text(n.to_string())),
+        (_, Value::Array(items)) => Cell::Text(items.iter().map(text).collect::<Vec<_>>().join("; ")),
+        _ => Cell::Text(text(value)),
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// `AED 1,250.50`; the currency prefix is omitted when the field has none
+fn format_money(amount: f64, currency_code: Option<&str>) -> String {
+    let fixed = format!("{:.2}", amount.abs());
+    let (whole, cents) = fixed.split_once('.').unwrap_or((&fixed, "00"));
+
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if amount < 0.0 && fixed != "0.00" { "-" } else { "" };
+    match currency_code {
+        Some(code) => format!("{} {}{}.{}", code, sign, grouped, cents),
+        None => format!("{}{}.{}", sign, grouped, cents),
+    }
+}
+
+// ============================================================================
+// Writers
+// ============================================================================
+
+/// Row-at-a-time encoder; output accumulates until taken with `take_chunk`
+pub trait ExportWriter: Send {
+    fn write_row(&mut self, cells: &[Cell]) -> io::Result<()>;
+
+    /// Bytes encoded but not yet taken
+    fn buffered(&self) -> usize;
+
+    fn take_chunk(&mut self) -> Vec<u8>;
+
+    /// Close the document and return the remaining output
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>>;
+}
+
+/// RFC 4180 CSV
+#[derive(Default)]
+pub struct CsvWriter {
+    buf: Vec<u8>,
+}
+
+impl ExportWriter for CsvWriter {
+    fn write_row(&mut self, cells: &[Cell]) -> io::Result<()> {
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                self.buf.push(b',');
+            }
+            match cell {
+                Cell::Number(n) => write!(self.buf, "{}", n)?,
+                Cell::Text(s) => write_csv_field(&mut self.buf, s),
+            }
+        }
+        self.buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn take_chunk(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>> {
+        Ok(self.buf)
+    }
+}
+
+fn write_csv_field(buf: &mut Vec<u8>, s: &str) {
+    // Neutralise text a spreadsheet would otherwise evaluate as a formula
+    let guard = if s.starts_with(['=', '+', '-', '@']) { "'" } else { "" };
+    if s.contains([',', '"', '\r', '\n']) {
+        buf.push(b'"');
+        buf.extend_from_slice(guard.as_bytes());
+        buf.extend_from_slice(s.replace('"', "\"\"").as_bytes());
+        buf.push(b'"');
+    } else {
+        buf.extend_from_slice(guard.as_bytes());
+        buf.extend_from_slice(s.as_bytes());
+    }
+}
+
+/// Output buffer shared between the zip encoder and `take_chunk`
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuf {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<u8>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Write for SharedBuf {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.lock().extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Single-sheet XLSX written as a forward-only zip stream
+///
+/// Cells use inline strings, so no shared string table has to be held
+/// until the end of the export.
+pub struct XlsxWriter {
+    zip: ZipWriter<StreamWriter<SharedBuf>>,
+    out: SharedBuf,
+}
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+
+const ROOT_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Export" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+
+const WORKBOOK_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+const SHEET_START_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#;
+
+const SHEET_END_XML: &str = "</sheetData></worksheet>";
+
+impl XlsxWriter {
+    /// Write the workbook parts and open the sheet for rows
+    pub fn new() -> io::Result<Self> {
+        let out = SharedBuf::default();
+        let mut zip = ZipWriter::new_stream(out.clone());
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        for (name, xml) in [
+            ("[Content_Types].xml", CONTENT_TYPES_XML),
+            ("_rels/.rels", ROOT_RELS_XML),
+            ("xl/workbook.xml", WORKBOOK_XML),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS_XML),
+        ] {
+            zip.start_file(name, options).map_err(io::Error::other)?;
+            zip.write_all(xml.as_bytes())?;
+        }
+        zip.start_file("xl/worksheets/sheet1.xml", options).map_err(io::Error::other)?;
+        zip.write_all(SHEET_START_XML.as_bytes())?;
+
+        Ok(XlsxWriter { zip, out })
+    }
+}
+
+impl ExportWriter for XlsxWriter {
+    fn write_row(&mut self, cells: &[Cell]) -> io::Result<()> {
+        let mut xml = String::from("<row>");
+        for cell in cells {
+            match cell {
+                Cell::Number(n) if n.is_finite() => xml.push_str(&format!("<c><v>{}</v></c>", n)),
+                Cell::Number(n) => xml.push_str(&format!("<c t=\"inlineStr\"><is><t>{}</t></is></c>", n)),
+                Cell::Text(s) => {
+                    xml.push_str("<c t=\"inlineStr\"><is><t xml:space=\"preserve\">");
+                    xml.push_str(&xml_escape(s));
+                    xml.push_str("</t></is></c>");
+                }
+            }
+        }
+        xml.push_str("</row>");
+        self.zip.write_all(xml.as_bytes())
+    }
+
+    fn buffered(&self) -> usize {
+        self.out.lock().len()
+    }
+
+    fn take_chunk(&mut self) -> Vec<u8> {
+        std::mem::take(&mut *self.out.lock())
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>> {
+        let XlsxWriter { mut zip, out } = *self;
+        zip.write_all(SHEET_END_XML.as_bytes())?;
+        zip.finish().map_err(io::Error::other)?;
+        let remaining = std::mem::take(&mut *out.lock());
+        Ok(remaining)
+    }
+}
+
+/// Escape XML text, dropping control characters XML 1.0 cannot represent
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::ViewColumn;
+    use serde_json::json;
+    use std::io::Read;
+
+    fn field(name: &str, label: &str, field_type: FieldType) -> FieldDef {
+        FieldDef::new(Uuid::nil(), Uuid::nil(), name, label, field_type)
+    }
+
+    fn view_column(field: &str, sort_order: i32, visible: bool) -> ViewColumn {
+        ViewColumn { field: field.to_string(), width: None, visible, sort_order }
+    }
+
+    fn fields() -> Vec<FieldDef> {
+        vec![
+            field("name", "Deal Name", FieldType::Text),
+            field("amount", "Amount", FieldType::Money { currency_code: Some("AED".to_string()) }),
+            field("stage", "Stage", FieldType::Select { options: vec![] }),
+            field("close_date", "Close Date", FieldType::Date),
+        ]
+    }
+
+    fn encode(writer: Box<dyn ExportWriter>, columns: &[ExportColumn], records: &[Value]) -> Vec<u8> {
+        let mut writer = writer;
+        let header: Vec<Cell> = columns.iter().map(|c| Cell::Text(c.header.clone())).collect();
+        writer.write_row(&header).unwrap();
+        let mut out = Vec::new();
+        for record in records {
+            writer.write_row(&row_cells(columns, record)).unwrap();
+            out.extend(writer.take_chunk());
+        }
+        out.extend(writer.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn test_csv_header_follows_view_columns() {
+        let view = ViewDef::table(Uuid::nil(), Uuid::nil(), "pipeline", "Pipeline").with_columns(vec![
+            view_column("amount", 2, true),
+            view_column("stage", 1, false),
+            view_column("close_date", 1, true),
+            view_column("name", 0, true),
+        ]);
+
+        let columns = export_columns(Some(&view), &fields());
+        let record = json!({ "name": "Palm Villa", "amount": 1250000, "stage": "won", "close_date": "2025-03-01T00:00:00Z" });
+        let csv = String::from_utf8(encode(Box::new(CsvWriter::default()), &columns, &[record])).unwrap();
+
+        let mut lines = csv.split("\r\n");
+        assert_eq!(lines.next(), Some("Deal Name,Close Date,Amount"));
+        assert_eq!(lines.next(), Some("Palm Villa,2025-03-01,\"AED 1,250,000.00\""));
+    }
+
+    #[test]
+    fn test_money_cells_are_formatted_with_currency() {
+        let aed = FieldType::Money { currency_code: Some("AED".to_string()) };
+
+        assert_eq!(format_cell(Some(&aed), &json!(1250.5)), Cell::Text("AED 1,250.50".to_string()));
+        assert_eq!(format_cell(Some(&aed), &json!("-980")), Cell::Text("AED -980.00".to_string()));
+        assert_eq!(
+            format_cell(Some(&FieldType::Money { currency_code: None }), &json!(1234567.891)),
+            Cell::Text("1,234,567.89".to_string())
+        );
+        assert_eq!(format_cell(Some(&aed), &Value::Null), Cell::Text(String::new()));
+        assert_eq!(
+            format_cell(Some(&aed), &json!({ "amount_minor": 125_050, "currency": "EUR" })),
+            Cell::Text("EUR 1,250.50".to_string())
+        );
+        assert_eq!(
+            format_cell(Some(&FieldType::Number { decimals: Some(1) }), &json!(2.46)),
+            Cell::Number(2.5)
+        );
+    }
+
+    #[test]
+    fn test_xlsx_stream_is_a_readable_workbook() {
+        let columns = export_columns(None, &fields());
+        let records = [
+            json!({ "name": "Marina <Tower>", "amount": 900, "stage": "open" }),
+            json!({ "name": "Palm Villa", "amount": 1250.5, "stage": "won", "close_date": "2025-03-01" }),
+        ];
+
+        let bytes = encode(Box::new(XlsxWriter::new().unwrap()), &columns, &records);
+
+        let mut archive = zip::ZipArchive::new(io::Cursor::new(bytes)).unwrap();
+        let mut sheet = String::new();
+        archive.by_name("xl/worksheets/sheet1.xml").unwrap().read_to_string(&mut sheet).unwrap();
+        assert!(archive.by_name("xl/workbook.xml").is_ok());
+        assert!(sheet.ends_with("</sheetData></worksheet>"));
+        assert_eq!(sheet.matches("<row>").count(), 3);
+        assert!(sheet.contains("Marina &lt;Tower&gt;"));
+        assert!(sheet.contains("AED 1,250.50"));
+    }
+}
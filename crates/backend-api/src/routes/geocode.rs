@@ -0,0 +1,308 @@
+//! Geocoding - fills a record's coordinates from its Location fields
+//!
+//! A Location field with `lat_field` and `lng_field` configured is geocoded
+//! after every create, and every update that changes it (see
+//! `spawn_workflows`), and on demand through
+//! `POST /records/:entity_code/:id/geocode`. Ambiguous and unknown addresses
+//! leave the coordinates as they were; the endpoint reports them so the user
+//! can make the address more specific.
+//!
+//! Coordinates are derived data, so writing them doesn't bump the record's
+//! version or raise another event.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use core_integrations::{CachedGeocoder, GeocodeOutcome};
+use core_models::{FieldDef, FieldType};
+use core_node_engine::{EntityEvent, EventType};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::middleware::database::RlsConn;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::state::AppState;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/records/:entity_code/:id/geocode", post(geocode_record_handler))
+        .route("/entities/:entity_code/:id/geocode", post(geocode_record_handler))
+}
+
+/// A Location field and the fields its coordinates go to
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeocodeTarget<'a> {
+    pub address_field: &'a str,
+    pub lat_field: &'a str,
+    pub lng_field: &'a str,
+}
+
+/// Location fields with both coordinate fields configured
+pub fn geocode_targets(fields: &[FieldDef]) -> Vec<GeocodeTarget<'_>> {
+    fields
+        .iter()
+        .filter_map(|field| match &field.field_type {
+            FieldType::Location { lat_field: Some(lat), lng_field: Some(lng), .. } => Some(GeocodeTarget {
+                address_field: &field.name,
+                lat_field: lat,
+                lng_field: lng,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// How one Location field resolved
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldGeocode {
+    pub field: String,
+    #[serde(flatten)]
+    pub outcome: GeocodeOutcome,
+}
+
+/// The address in a Location value: the string itself, or `{"address": ...}`
+fn address_of(value: &Value) -> Option<&str> {
+    let address = match value {
+        Value::String(s) => s.as_str(),
+        Value::Object(map) => map.get("address")?.as_str()?,
+        _ => return None,
+    };
+    Some(address.trim()).filter(|a| !a.is_empty())
+}
+
+/// Geocode the addresses `data` holds for `targets` and write the coordinates
+/// that were found to the record
+pub async fn geocode_record(
+    conn: &mut PgConnection,
+    geocoder: &CachedGeocoder,
+    tenant_id: Uuid,
+    record_id: Uuid,
+    targets: &[GeocodeTarget<'_>],
+    data: &Value,
+) -> Result<Vec<FieldGeocode>, String> {
+    let mut results = Vec::new();
+    let mut patch = Map::new();
+
+    for target in targets {
+        let Some(address) = data.get(target.address_field).and_then(address_of) else {
+            continue;
+        };
+        let outcome = geocoder.geocode(address).await.map_err(|e| e.to_string())?;
+        if let GeocodeOutcome::Found { point, .. } = &outcome {
+            patch.insert(target.lat_field.to_string(), point.lat.into());
+            patch.insert(target.lng_field.to_string(), point.lng.into());
+        }
+        results.push(FieldGeocode { field: target.address_field.to_string(), outcome });
+    }
+
+    if !patch.is_empty() {
+        sqlx::query("UPDATE entity_records SET data = data || $1 WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL")
+            .bind(Value::Object(patch))
+            .bind(record_id)
+            .bind(tenant_id)
+            .execute(conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(results)
+}
+
+/// Geocode in the background after `event` saved a record, if it set an
+/// address that has coordinate fields
+pub(crate) fn spawn_geocoding(state: Arc<AppState>, entity_type_id: Uuid, event: &EntityEvent) {
+    let Some(geocoder) = state.geocoder.clone() else {
+        return;
+    };
+    if !matches!(event.event_type, EventType::Create | EventType::Update) {
+        return;
+    }
+    let Some(data) = event.new_values.clone() else {
+        return;
+    };
+    let (tenant_id, record_id, changed) = (event.tenant_id, event.record_id, event.changed_fields.clone());
+
+    tokio::spawn(async move {
+        let fields = match state.metadata.get_fields(tenant_id, entity_type_id).await {
+            Ok(f) => f,
+            Err(e) => return tracing::warn!("Geocoding skipped, fields unavailable: {}", e),
+        };
+        // Updates only re-geocode the addresses they changed
+        let targets: Vec<GeocodeTarget> = geocode_targets(&fields)
+            .into_iter()
+            .filter(|t| changed.as_ref().is_none_or(|c| c.iter().any(|f| f == t.address_field)))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        let result = match state.pool.acquire().await {
+            Ok(mut conn) => geocode_record(&mut conn, &geocoder, tenant_id, record_id, &targets, &data).await,
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(results) => {
+                for r in results.iter().filter(|r| !matches!(r.outcome, GeocodeOutcome::Found { .. })) {
+                    tracing::info!("Address in '{}' of record {} was not geocoded: {:?}", r.field, record_id, r.outcome);
+                }
+            }
+            Err(e) => tracing::warn!("Geocoding record {} failed: {}", record_id, e),
+        }
+    });
+}
+
+/// POST /records/:entity_code/:id/geocode
+///
+/// Geocodes the record's addresses now and reports each one's outcome,
+/// including the candidates of an ambiguous address.
+async fn geocode_record_handler(
+    State(state): State<Arc<AppState>>,
+    Path((entity_code, id)): Path<(String, Uuid)>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+) -> Response {
+    let Some(geocoder) = state.geocoder.clone() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Geocoding is not configured").into_response();
+    };
+    let fields = match state.metadata.get_fields_by_entity_name(tenant.id, &entity_code).await {
+        Ok(f) => f,
+        Err(_) => return (StatusCode::NOT_FOUND, "Entity type not found").into_response(),
+    };
+    let data: Option<Value> = match sqlx::query_scalar(
+        "SELECT data FROM entity_records WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .bind(tenant.id)
+    .fetch_optional(&mut **conn)
+    .await
+    {
+        Ok(d) => d,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let Some(data) = data else {
+        return (StatusCode::NOT_FOUND, "Record not found").into_response();
+    };
+
+    match geocode_record(&mut conn, &geocoder, tenant.id, id, &geocode_targets(&fields), &data).await {
+        Ok(results) => Json(results).into_response(),
+        Err(e) => (StatusCode::BAD_GATEWAY, e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use core_integrations::geocoding::{GeocodeError, GeocodeMatch};
+    use core_integrations::{GeoPoint, Geocoder};
+    use serde_json::json;
+    use std::time::Duration;
+    use test_support::get_test_pool;
+
+    struct MockGeocoder;
+
+    #[async_trait]
+    impl Geocoder for MockGeocoder {
+        async fn geocode(&self, address: &str) -> Result<Vec<GeocodeMatch>, GeocodeError> {
+            Ok(match address {
+                "Dubai Marina" => vec![GeocodeMatch {
+                    point: GeoPoint { lat: 25.0805, lng: 55.1403 },
+                    label: "Dubai Marina, Dubai".to_string(),
+                    confidence: 0.9,
+                }],
+                _ => vec![],
+            })
+        }
+    }
+
+    fn property_fields() -> Vec<FieldDef> {
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        vec![
+            FieldDef::new(tenant_id, entity_type_id, "title", "Title", FieldType::Text),
+            FieldDef::new(
+                tenant_id,
+                entity_type_id,
+                "address",
+                "Address",
+                FieldType::Location { show_map: true, lat_field: Some("lat".into()), lng_field: Some("lng".into()) },
+            ),
+            // Not geocoded: no coordinate fields
+            FieldDef::new(tenant_id, entity_type_id, "billing", "Billing", FieldType::Location { show_map: false, lat_field: None, lng_field: None }),
+        ]
+    }
+
+    #[test]
+    fn test_only_configured_locations_are_targets() {
+        let fields = property_fields();
+        assert_eq!(
+            geocode_targets(&fields),
+            vec![GeocodeTarget { address_field: "address", lat_field: "lat", lng_field: "lng" }]
+        );
+        assert_eq!(address_of(&json!({ "address": " Dubai Marina " })), Some("Dubai Marina"));
+        assert_eq!(address_of(&json!("")), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_found_address_fills_coordinates() {
+        let pool = get_test_pool().await;
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Geocode Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("geocode-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural)
+             VALUES ($1, $2, 'properties', 'property', 'Property', 'Properties')",
+        )
+        .bind(entity_type_id)
+        .bind(tenant_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let insert = |data: Value| {
+            sqlx::query_scalar::<_, Uuid>("INSERT INTO entity_records (tenant_id, entity_type_id, data) VALUES ($1, $2, $3) RETURNING id")
+                .bind(tenant_id)
+                .bind(entity_type_id)
+                .bind(data)
+                .fetch_one(&pool)
+        };
+        let data_of = |id: Uuid| sqlx::query_scalar::<_, Value>("SELECT data FROM entity_records WHERE id = $1").bind(id).fetch_one(&pool);
+
+        let geocoder = CachedGeocoder::new(Arc::new(MockGeocoder), Duration::ZERO);
+        let fields = property_fields();
+        let targets = geocode_targets(&fields);
+        let mut conn = pool.acquire().await.unwrap();
+
+        let marina = json!({ "title": "Marina Flat", "address": "Dubai Marina" });
+        let id = insert(marina.clone()).await.unwrap();
+        let results = geocode_record(&mut conn, &geocoder, tenant_id, id, &targets, &marina).await.unwrap();
+        assert!(matches!(results[0].outcome, GeocodeOutcome::Found { .. }));
+        let data = data_of(id).await.unwrap();
+        assert_eq!((data["lat"].clone(), data["lng"].clone()), (json!(25.0805), json!(55.1403)));
+        assert_eq!(data["title"], json!("Marina Flat"));
+
+        // An unknown address keeps the coordinates the record had
+        let unknown = json!({ "title": "Old Listing", "address": "Nowhere Lane 99", "lat": 1.5, "lng": 2.5 });
+        let id = insert(unknown.clone()).await.unwrap();
+        let results = geocode_record(&mut conn, &geocoder, tenant_id, id, &targets, &unknown).await.unwrap();
+        assert_eq!(results[0].outcome, GeocodeOutcome::NotFound);
+        assert_eq!(data_of(id).await.unwrap(), unknown);
+
+        for sql in [
+            "DELETE FROM entity_records WHERE tenant_id = $1",
+            "DELETE FROM entity_types WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
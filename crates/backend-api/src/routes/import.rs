@@ -0,0 +1,675 @@
+//! Entity Import - loads CSV rows into an entity's records
+//!
+//! The CSV is the request body; `mapping` (JSON, CSV column → field name)
+//! says which column feeds which field, and `upsert_key` names a field whose
+//! value identifies an existing record to update instead of duplicating it.
+//! Cells are coerced to the field types from metadata and validated like any
+//! other write. The body is parsed as it arrives, so large files are never
+//! held in memory whole.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use core_models::{FieldDef, FieldType};
+use core_node_engine::EntityEvent;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{Connection, PgConnection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::middleware::database::RlsConn;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::outbox;
+use crate::routes::entities::{spawn_workflows, validate_and_process_payload};
+use crate::state::AppState;
+
+/// Imports longer than this are rolled back; split the file
+pub const MAX_IMPORT_ROWS: usize = 50_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportQuery {
+    /// JSON object of CSV column → field name; defaults to columns named
+    /// after a field's name or label
+    pub mapping: Option<String>,
+    /// Field identifying existing records, e.g. `email`
+    pub upsert_key: Option<String>,
+}
+
+/// What happened to one CSV row
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RowOutcome {
+    Created { id: Uuid },
+    Updated { id: Uuid },
+    Skipped { reason: String },
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowResult {
+    /// 1-based data row, not counting the header
+    pub row: usize,
+    #[serde(flatten)]
+    pub outcome: RowOutcome,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub errors: usize,
+    pub rows: Vec<RowResult>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Import exceeds the maximum of {MAX_IMPORT_ROWS} rows")]
+    TooLarge,
+    #[error("Failed to read upload: {0}")]
+    Body(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl IntoResponse for ImportError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ImportError::Invalid(_) | ImportError::Body(_) => StatusCode::BAD_REQUEST,
+            ImportError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ImportError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(serde_json::json!({"error": self.to_string()}))).into_response()
+    }
+}
+
+// ============================================================================
+// Routes
+// ============================================================================
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/entities/:entity_code/import", post(import_records))
+        .route("/records/:entity_code/import", post(import_records))
+}
+
+/// POST /entities/:entity_code/import?mapping={"Email Address":"email"}&upsert_key=email
+///
+/// All rows are written in one transaction; a failing row is reported and
+/// skipped without affecting the others.
+async fn import_records(
+    State(state): State<Arc<AppState>>,
+    Path(entity_code): Path<String>,
+    Query(query): Query<ImportQuery>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    body: Body,
+) -> Response {
+    let entity_type = match state.metadata.get_entity_type(tenant.id, &entity_code).await {
+        Ok(e) => e,
+        Err(_) => return (StatusCode::NOT_FOUND, format!("Entity type '{}' not found", entity_code)).into_response(),
+    };
+    let fields = match state.metadata.get_fields(tenant.id, entity_type.id).await {
+        Ok(f) => f,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let mapping = match query.mapping.as_deref().map(serde_json::from_str::<HashMap<String, String>>).transpose() {
+        Ok(m) => m,
+        Err(e) => return ImportError::Invalid(format!("Invalid mapping: {}", e)).into_response(),
+    };
+
+    let phone_region = tenant.phone_region();
+    let target = ImportTarget {
+        tenant_id: tenant.id,
+        entity_type_id: entity_type.id,
+        entity_code: &entity_code,
+        fields: &fields,
+        phone_region: &phone_region,
+    };
+    let options = ImportOptions { mapping: mapping.as_ref(), upsert_key: query.upsert_key.as_deref() };
+    let chunks = body.into_data_stream().map(|chunk| chunk.map_err(|e| e.to_string()));
+
+    match import_csv(&mut conn, &target, &options, chunks).await {
+        Ok((summary, events)) => {
+            for event in events {
+                spawn_workflows(state.clone(), entity_type.id, event);
+            }
+            Json(summary).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Entity the rows are imported into
+pub struct ImportTarget<'a> {
+    pub tenant_id: Uuid,
+    pub entity_type_id: Uuid,
+    pub entity_code: &'a str,
+    pub fields: &'a [FieldDef],
+    /// Country national phone numbers are read in
+    pub phone_region: &'a str,
+}
+
+pub struct ImportOptions<'a> {
+    pub mapping: Option<&'a HashMap<String, String>>,
+    pub upsert_key: Option<&'a str>,
+}
+
+/// Parse `chunks` as CSV and write every row, returning the per-row results
+/// and the committed events for the caller to publish
+pub async fn import_csv<S>(
+    conn: &mut PgConnection,
+    target: &ImportTarget<'_>,
+    options: &ImportOptions<'_>,
+    mut chunks: S,
+) -> Result<(ImportSummary, Vec<EntityEvent>), ImportError>
+where
+    S: Stream<Item = Result<Bytes, String>> + Unpin,
+{
+    let mut parser = CsvParser::default();
+    let mut importer: Option<RowImporter> = None;
+    let mut tx = conn.begin().await?;
+
+    let mut pending = Vec::new();
+    loop {
+        let chunk = chunks.next().await.transpose().map_err(ImportError::Body)?;
+        match &chunk {
+            Some(bytes) => pending.extend(parser.push(bytes)),
+            None => pending.extend(parser.finish().map_err(ImportError::Invalid)?),
+        }
+
+        for record in pending.drain(..) {
+            match &mut importer {
+                None => importer = Some(RowImporter::new(target, options, &record)?),
+                Some(importer) => {
+                    if importer.summary.rows.len() >= MAX_IMPORT_ROWS {
+                        return Err(ImportError::TooLarge);
+                    }
+                    importer.import_row(&mut tx, record).await?;
+                }
+            }
+        }
+
+        if chunk.is_none() {
+            break;
+        }
+    }
+
+    let importer = importer.ok_or_else(|| ImportError::Invalid("CSV has no header row".to_string()))?;
+    for event in &importer.events {
+        outbox::enqueue(&mut tx, event).await?;
+    }
+    tx.commit().await?;
+
+    Ok((importer.summary, importer.events))
+}
+
+/// Writes rows once the header has been matched to fields
+struct RowImporter<'a> {
+    target: &'a ImportTarget<'a>,
+    /// Field fed by each CSV column, by position
+    columns: Vec<Option<&'a FieldDef>>,
+    upsert_key: Option<&'a FieldDef>,
+    summary: ImportSummary,
+    events: Vec<EntityEvent>,
+}
+
+impl<'a> RowImporter<'a> {
+    fn new(target: &'a ImportTarget<'a>, options: &ImportOptions<'_>, header: &[String]) -> Result<Self, ImportError> {
+        let field = |name: &str| {
+            target
+                .fields
+                .iter()
+                .find(|f| f.name == name && !f.is_computed())
+                .ok_or_else(|| ImportError::Invalid(format!("Unknown field '{}'", name)))
+        };
+
+        let columns = match options.mapping {
+            Some(mapping) => {
+                if let Some(missing) = mapping.keys().find(|col| !header.iter().any(|h| h.trim() == col.as_str())) {
+                    return Err(ImportError::Invalid(format!("Mapped column '{}' is not in the CSV header", missing)));
+                }
+                header
+                    .iter()
+                    .map(|h| mapping.get(h.trim()).map(|name| field(name)).transpose())
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            None => header
+                .iter()
+                .map(|h| {
+                    let h = h.trim();
+                    target.fields.iter().find(|f| {
+                        !f.is_computed() && (f.name.eq_ignore_ascii_case(h) || f.label.eq_ignore_ascii_case(h))
+                    })
+                })
+                .collect(),
+        };
+        if columns.iter().all(Option::is_none) {
+            return Err(ImportError::Invalid("No CSV column maps to a field".to_string()));
+        }
+
+        let upsert_key = options.upsert_key.map(field).transpose()?;
+        if let Some(key) = upsert_key {
+            if !columns.iter().flatten().any(|f| f.name == key.name) {
+                return Err(ImportError::Invalid(format!("Upsert key '{}' is not mapped from any column", key.name)));
+            }
+        }
+
+        Ok(Self { target, columns, upsert_key, summary: ImportSummary::default(), events: Vec::new() })
+    }
+
+    async fn import_row(&mut self, tx: &mut PgConnection, cells: Vec<String>) -> Result<(), sqlx::Error> {
+        let outcome = match self.row_data(&cells) {
+            Err(message) => RowOutcome::Error { message },
+            Ok(data) if data.is_empty() => RowOutcome::Skipped { reason: "Empty row".to_string() },
+            Ok(data) => self.write(tx, Value::Object(data)).await?,
+        };
+
+        match &outcome {
+            RowOutcome::Created { .. } => self.summary.created += 1,
+            RowOutcome::Updated { .. } => self.summary.updated += 1,
+            RowOutcome::Skipped { .. } => self.summary.skipped += 1,
+            RowOutcome::Error { .. } => self.summary.errors += 1,
+        }
+        let row = self.summary.rows.len() + 1;
+        self.summary.rows.push(RowResult { row, outcome });
+        Ok(())
+    }
+
+    /// Coerced values of the mapped, non-blank cells
+    fn row_data(&self, cells: &[String]) -> Result<Map<String, Value>, String> {
+        let mut data = Map::new();
+        for (cell, field) in cells.iter().zip(&self.columns) {
+            let (Some(field), cell) = (field, cell.trim()) else { continue };
+            if !cell.is_empty() {
+                data.insert(field.name.clone(), coerce_cell(field, cell)?);
+            }
+        }
+        Ok(data)
+    }
+
+    async fn write(&mut self, tx: &mut PgConnection, data: Value) -> Result<RowOutcome, sqlx::Error> {
+        let existing = match self.upsert_key {
+            Some(key) => {
+                let Some(value) = data.get(&key.name) else {
+                    return Ok(RowOutcome::Error { message: format!("Missing value for upsert key '{}'", key.name) });
+                };
+                match self.find_existing(tx, key, value).await? {
+                    Ok(existing) => existing,
+                    Err(message) => return Ok(RowOutcome::Error { message }),
+                }
+            }
+            None => None,
+        };
+
+        // A matched record keeps its key as stored, e.g. the email's casing
+        let mut data = data;
+        if let (Some(key), Some(_), Some(obj)) = (self.upsert_key, &existing, data.as_object_mut()) {
+            obj.remove(&key.name);
+        }
+
+        let is_update = existing.is_some();
+        let data = match validate_and_process_payload(self.target.fields, &data, is_update, self.target.phone_region) {
+            Ok(d) => d,
+            Err(message) => return Ok(RowOutcome::Error { message }),
+        };
+
+        let mut savepoint = tx.begin().await?;
+        let written = match &existing {
+            Some((id, old_data)) => {
+                let changed_fields: Vec<String> = data
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(k, v)| old_data.get(k.as_str()) != Some(*v))
+                    .map(|(k, _)| k.clone())
+                    .collect();
+                if changed_fields.is_empty() {
+                    savepoint.rollback().await?;
+                    return Ok(RowOutcome::Skipped { reason: "No changes".to_string() });
+                }
+                sqlx::query_scalar::<_, Value>(
+                    "UPDATE entity_records SET data = data || $1, version = version + 1, updated_at = NOW()
+                     WHERE id = $2 AND tenant_id = $3 RETURNING data",
+                )
+                .bind(&data)
+                .bind(id)
+                .bind(self.target.tenant_id)
+                .fetch_one(&mut *savepoint)
+                .await
+                .map(|new_data| {
+                    let event = EntityEvent::update(
+                        self.target.tenant_id,
+                        self.target.entity_code,
+                        *id,
+                        old_data.clone(),
+                        new_data,
+                        changed_fields,
+                        None,
+                    );
+                    (event, RowOutcome::Updated { id: *id })
+                })
+            }
+            None => sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO entity_records (id, tenant_id, entity_type_id, data) VALUES ($1, $2, $3, $4) RETURNING id",
+            )
+            .bind(Uuid::new_v4())
+            .bind(self.target.tenant_id)
+            .bind(self.target.entity_type_id)
+            .bind(&data)
+            .fetch_one(&mut *savepoint)
+            .await
+            .map(|id| {
+                let event = EntityEvent::create(self.target.tenant_id, self.target.entity_code, id, data.clone(), None);
+                (event, RowOutcome::Created { id })
+            }),
+        };
+
+        match written {
+            Ok((event, outcome)) => {
+                savepoint.commit().await?;
+                self.events.push(event);
+                Ok(outcome)
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                Ok(RowOutcome::Error { message: e.to_string() })
+            }
+        }
+    }
+
+    /// The live record whose `key` equals `value`; text compares
+    /// case-insensitively, so `A@x.com` matches `a@x.com`
+    async fn find_existing(
+        &self,
+        tx: &mut PgConnection,
+        key: &FieldDef,
+        value: &Value,
+    ) -> Result<Result<Option<(Uuid, Value)>, String>, sqlx::Error> {
+        let matches: Vec<(Uuid, Value)> = sqlx::query_as(
+            "SELECT id, data FROM entity_records
+             WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL
+               AND CASE WHEN jsonb_typeof($4) = 'string' THEN lower(data->>$3) = lower($4 #>> '{}')
+                        ELSE data->$3 = $4 END
+             LIMIT 2",
+        )
+        .bind(self.target.tenant_id)
+        .bind(self.target.entity_type_id)
+        .bind(&key.name)
+        .bind(value)
+        .fetch_all(tx)
+        .await?;
+
+        Ok(match matches.len() {
+            0 | 1 => Ok(matches.into_iter().next()),
+            _ => Err(format!("Several records have {} = {}", key.name, value)),
+        })
+    }
+}
+
+/// Convert a CSV cell to the JSON the field stores
+fn coerce_cell(field: &FieldDef, cell: &str) -> Result<Value, String> {
+    match &field.field_type {
+        FieldType::Number { .. }
+        | FieldType::Money { .. }
+        | FieldType::Score { .. }
+        | FieldType::Progress { .. }
+        | FieldType::Rating { .. } => {
+            // Thousands separators are common in spreadsheet exports
+            let digits = cell.replace(',', "");
+            digits
+                .parse::<i64>()
+                .map(Value::from)
+                .ok()
+                .or_else(|| digits.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number))
+                .ok_or_else(|| format!("Field '{}' must be a number, got '{}'", field.label, cell))
+        }
+        FieldType::Boolean => match cell.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Ok(Value::Bool(true)),
+            "false" | "no" | "n" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("Field '{}' must be true or false, got '{}'", field.label, cell)),
+        },
+        FieldType::MultiSelect { .. } | FieldType::MultiLink { .. } | FieldType::TagList => Ok(Value::Array(
+            cell.split([';', ','])
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| Value::String(s.to_string()))
+                .collect(),
+        )),
+        FieldType::Json => serde_json::from_str(cell).map_err(|e| format!("Field '{}' must be JSON: {}", field.label, e)),
+        _ => Ok(Value::String(cell.to_string())),
+    }
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/// Incremental RFC 4180 parser: feed it chunks, get back complete records
+#[derive(Default)]
+struct CsvParser {
+    field: Vec<u8>,
+    record: Vec<String>,
+    in_quotes: bool,
+    /// A quote inside a quoted field: either `""` or the closing quote
+    quote_seen: bool,
+    started: bool,
+}
+
+impl CsvParser {
+    fn push(&mut self, bytes: &[u8]) -> Vec<Vec<String>> {
+        let mut bytes = bytes;
+        if !self.started && !bytes.is_empty() {
+            self.started = true;
+            bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        }
+
+        let mut records = Vec::new();
+        for &b in bytes {
+            if self.in_quotes {
+                if self.quote_seen {
+                    self.quote_seen = false;
+                    if b == b'"' {
+                        self.field.push(b'"');
+                        continue;
+                    }
+                    self.in_quotes = false;
+                } else {
+                    if b == b'"' {
+                        self.quote_seen = true;
+                    } else {
+                        self.field.push(b);
+                    }
+                    continue;
+                }
+            }
+
+            match b {
+                b'"' if self.field.is_empty() => self.in_quotes = true,
+                b',' => self.end_field(),
+                b'\n' => {
+                    if let Some(record) = self.end_record() {
+                        records.push(record);
+                    }
+                }
+                b'\r' => {}
+                _ => self.field.push(b),
+            }
+        }
+        records
+    }
+
+    /// The last record, when the input doesn't end with a newline
+    fn finish(&mut self) -> Result<Option<Vec<String>>, String> {
+        if self.in_quotes && !self.quote_seen {
+            return Err("CSV ends inside a quoted field".to_string());
+        }
+        self.in_quotes = false;
+        self.quote_seen = false;
+        Ok(self.end_record())
+    }
+
+    fn end_field(&mut self) {
+        let field = std::mem::take(&mut self.field);
+        self.record.push(String::from_utf8_lossy(&field).into_owned());
+    }
+
+    /// Blank lines produce no record
+    fn end_record(&mut self) -> Option<Vec<String>> {
+        if self.record.is_empty() && self.field.is_empty() {
+            return None;
+        }
+        self.end_field();
+        Some(std::mem::take(&mut self.record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_support::TestTenant;
+
+    fn parse_chunks(chunks: &[&str]) -> Vec<Vec<String>> {
+        let mut parser = CsvParser::default();
+        let mut records: Vec<Vec<String>> = chunks.iter().flat_map(|c| parser.push(c.as_bytes())).collect();
+        records.extend(parser.finish().unwrap());
+        records
+    }
+
+    #[test]
+    fn test_csv_parser_across_chunk_boundaries() {
+        let records = parse_chunks(&["\u{FEFF}name,no", "tes\r\n\"Smith, J\",\"said \"", "\"hi\"\"\"\n\nlast,\"multi\nline\""]);
+        assert_eq!(
+            records,
+            vec![
+                vec!["name".to_string(), "notes".to_string()],
+                vec!["Smith, J".to_string(), "said \"hi\"".to_string()],
+                vec!["last".to_string(), "multi\nline".to_string()],
+            ]
+        );
+        let mut unterminated = CsvParser::default();
+        unterminated.push(b"a,\"open");
+        assert!(unterminated.finish().is_err());
+    }
+
+    /// Tenant with a `contact` entity type; removed again by `cleanup`
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+        fields: Vec<FieldDef>,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Import Test").await;
+            let entity_type_id = tenant.entity_type("crm", "contact", "Contact", "Contacts").await;
+            let fields = vec![
+                FieldDef::new(tenant.id, entity_type_id, "name", "Name", FieldType::Text).required(),
+                FieldDef::new(tenant.id, entity_type_id, "email", "Email", FieldType::Email),
+                FieldDef::new(tenant.id, entity_type_id, "budget", "Budget", FieldType::Number { decimals: None }),
+            ];
+            Self { tenant, entity_type_id, fields }
+        }
+
+        /// Import `csv`, delivered in small chunks as a client upload would be
+        async fn import(&self, csv: &str, mapping: &HashMap<String, String>, upsert_key: Option<&str>) -> ImportSummary {
+            let target = ImportTarget {
+                tenant_id: self.tenant.id,
+                entity_type_id: self.entity_type_id,
+                entity_code: "contact",
+                fields: &self.fields,
+                phone_region: core_models::DEFAULT_PHONE_REGION,
+            };
+            let options = ImportOptions { mapping: Some(mapping), upsert_key };
+            let chunks: Vec<Result<Bytes, String>> =
+                csv.as_bytes().chunks(7).map(|c| Ok(Bytes::copy_from_slice(c))).collect();
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            import_csv(&mut conn, &target, &options, futures::stream::iter(chunks)).await.unwrap().0
+        }
+
+        async fn records(&self) -> Vec<Value> {
+            sqlx::query_scalar("SELECT data FROM entity_records WHERE entity_type_id = $1 ORDER BY data->>'name'")
+                .bind(self.entity_type_id)
+                .fetch_all(&self.tenant.pool)
+                .await
+                .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    fn mapping(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(col, field)| (col.to_string(), field.to_string())).collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_import_upserts_on_email() {
+        let fx = Fixture::new().await;
+        let mapping = mapping(&[("Full Name", "name"), ("E-mail", "email")]);
+        let first = fx.import("Full Name,E-mail,Ignored\nAmal,amal@example.com,x\nOmar,omar@example.com,y\n", &mapping, Some("email")).await;
+        assert_eq!((first.created, first.updated), (2, 0));
+
+        let second = fx
+            .import(
+                "E-mail,Full Name\nAMAL@example.com,Amal Haddad\nomar@example.com,Omar\nsara@example.com,Sara",
+                &mapping,
+                Some("email"),
+            )
+            .await;
+        assert_eq!((second.created, second.updated, second.skipped), (1, 1, 1));
+        assert!(matches!(second.rows[0].outcome, RowOutcome::Updated { .. }));
+        assert_eq!(second.rows[1].outcome, RowOutcome::Skipped { reason: "No changes".to_string() });
+
+        let records = fx.records().await;
+        let names: Vec<&str> = records.iter().map(|r| r["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["Amal Haddad", "Omar", "Sara"]);
+        // Updates keep the stored key as it was
+        assert_eq!(records[0]["email"], json!("amal@example.com"));
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_import_coerces_numbers_and_reports_invalid_rows() {
+        let fx = Fixture::new().await;
+        let summary = fx
+            .import(
+                "Full Name,Annual Budget\nAmal,\"1,250,000\"\nOmar,12.5\n,300\nSara,lots\n",
+                &mapping(&[("Full Name", "name"), ("Annual Budget", "budget")]),
+                None,
+            )
+            .await;
+
+        assert_eq!((summary.created, summary.errors), (2, 2));
+        assert_eq!(summary.rows[2].outcome, RowOutcome::Error { message: "Field 'Name' is required".to_string() });
+        assert!(matches!(&summary.rows[3].outcome, RowOutcome::Error { message } if message.contains("must be a number")));
+        assert_eq!(summary.rows[3].row, 4);
+
+        let records = fx.records().await;
+        assert_eq!(records[0]["budget"], json!(1250000));
+        assert_eq!(records[1]["budget"], json!(12.5));
+        fx.cleanup().await;
+    }
+}
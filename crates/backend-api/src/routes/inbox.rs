@@ -7,10 +7,15 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use sqlx::Connection;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use core_auth::middleware::ExtractAuth;
+use core_engagement::{message_direction, split_messages, InboxError, ThreadMessage};
 
+use crate::events::{emit_thread_split, emit_threads_merged, emit_unread_count_changed};
 use crate::state::AppState;
 use crate::error::ApiError;
 
@@ -19,8 +24,17 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/threads", get(list_threads))
         .route("/threads/:entity_id/messages", get(get_thread_messages))
         .route("/threads/:entity_id/reply", post(send_reply))
+        .route("/merge", post(merge_threads_handler))
+        .route("/split", post(split_thread_handler))
+        .route("/read", post(mark_read_handler))
+        .route("/unread", get(get_unread))
 }
 
+/// Whether an `interactions` row (aliased `i`) is a message from the customer;
+/// mirrors `core_engagement::message_direction`
+const INBOUND_SQL: &str = "COALESCE(i.metadata->>'direction', \
+    CASE WHEN LOWER(i.interaction_type) IN ('email', 'message') THEN 'inbound' ELSE 'outbound' END) = 'inbound'";
+
 use crate::middleware::database::RlsConn;
 use crate::middleware::tenant::ResolvedTenant;
 
@@ -34,6 +48,8 @@ pub struct ThreadsQuery {
 
 #[derive(Debug, Serialize)]
 pub struct InboxThreadResponse {
+    pub thread_id: Uuid,
+    pub channel: String,
     pub entity_id: Uuid,
     pub entity_type: String,
     pub entity_name: String,
@@ -61,6 +77,61 @@ pub struct ThreadMessageResponse {
     pub duration_minutes: Option<i32>,
 }
 
+impl From<ThreadMessage> for ThreadMessageResponse {
+    fn from(m: ThreadMessage) -> Self {
+        Self {
+            id: m.id,
+            interaction_type: m.interaction_type,
+            title: m.title,
+            content: m.content,
+            created_by: m.created_by,
+            occurred_at: m.occurred_at,
+            direction: m.direction,
+            duration_minutes: m.duration_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessagesQuery {
+    /// Restrict to one thread of the entity (all of its threads by default)
+    #[serde(default)]
+    pub thread_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeThreadsRequest {
+    /// Threads to combine; the first one survives
+    pub thread_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SplitThreadRequest {
+    pub thread_id: Uuid,
+    /// First message moved to the new thread
+    pub from_message_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkReadRequest {
+    pub thread_id: Uuid,
+    /// Last message the user has seen; earlier messages are read too
+    pub up_to_message_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadResponse {
+    pub total: i64,
+    /// Unread messages per thread, for threads with any
+    pub threads: HashMap<Uuid, i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThreadChangeResponse {
+    pub thread_id: Uuid,
+    pub affected_thread_ids: Vec<Uuid>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MessagesListResponse {
     pub data: Vec<ThreadMessageResponse>,
@@ -80,36 +151,47 @@ pub struct ReplyRequest {
 /// GET /inbox/threads - List all conversation threads
 async fn list_threads(
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    auth: Option<ExtractAuth>,
     mut conn: RlsConn,
 ) -> Result<Json<ThreadListResponse>, ApiError> {
     use sqlx::Row;
 
+    let unread = match &auth {
+        Some(ExtractAuth(ctx)) => unread_by_thread(&mut conn, tenant.id, ctx.user.id, None).await?,
+        None => HashMap::new(),
+    };
+
     // Query to aggregate interactions into threads
-    // Groups by record_id (entity), gets latest message info
+    // Groups by thread (entity + channel), gets latest message info
     let sql = r#"
         WITH thread_summary AS (
             SELECT 
-                i.record_id as entity_id,
-                i.entity_type,
+                i.thread_id,
+                t.record_id as entity_id,
+                t.entity_type,
+                t.channel,
                 MAX(i.occurred_at) as last_message_at,
                 COUNT(*) as message_count
             FROM interactions i
+            JOIN inbox_threads t ON t.id = i.thread_id
             WHERE i.tenant_id = $1
-            GROUP BY i.record_id, i.entity_type
+            GROUP BY i.thread_id, t.record_id, t.entity_type, t.channel
             ORDER BY MAX(i.occurred_at) DESC
             LIMIT 50
         ),
         latest_messages AS (
-            SELECT DISTINCT ON (i.record_id)
-                i.record_id as entity_id,
+            SELECT DISTINCT ON (i.thread_id)
+                i.thread_id,
                 i.title,
                 i.content,
                 i.interaction_type
             FROM interactions i
             WHERE i.tenant_id = $1
-            ORDER BY i.record_id, i.occurred_at DESC
+            ORDER BY i.thread_id, i.occurred_at DESC, i.id DESC
         )
         SELECT 
+            ts.thread_id,
+            ts.channel,
             ts.entity_id,
             ts.entity_type,
             ts.last_message_at,
@@ -118,7 +200,7 @@ async fn list_threads(
             lm.content as last_content,
             lm.interaction_type as last_interaction_type
         FROM thread_summary ts
-        JOIN latest_messages lm ON ts.entity_id = lm.entity_id
+        JOIN latest_messages lm ON ts.thread_id = lm.thread_id
         ORDER BY ts.last_message_at DESC
     "#;
 
@@ -131,6 +213,8 @@ async fn list_threads(
     let mut threads: Vec<InboxThreadResponse> = Vec::new();
 
     for row in rows {
+        let thread_id: Uuid = row.try_get("thread_id").unwrap_or_default();
+        let channel: String = row.try_get("channel").unwrap_or_default();
         let entity_id: Uuid = row.try_get("entity_id").unwrap_or_default();
         let entity_type: String = row.try_get("entity_type").unwrap_or_default();
         let last_message_at: DateTime<Utc> = row.try_get("last_message_at").unwrap_or_else(|_| Utc::now());
@@ -148,12 +232,14 @@ async fn list_threads(
         let last_message_preview = truncate_preview(preview, 80);
 
         threads.push(InboxThreadResponse {
+            thread_id,
+            channel,
             entity_id,
             entity_type,
             entity_name,
             last_message_preview,
             last_message_at,
-            unread_count: 0, // TODO: Implement read tracking
+            unread_count: unread.get(&thread_id).copied().unwrap_or(0),
             last_interaction_type,
         });
     }
@@ -167,6 +253,7 @@ async fn list_threads(
 async fn get_thread_messages(
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
     Path(entity_id): Path<Uuid>,
+    Query(query): Query<MessagesQuery>,
     mut conn: RlsConn,
 ) -> Result<Json<MessagesListResponse>, ApiError> {
     use sqlx::Row;
@@ -187,7 +274,7 @@ async fn get_thread_messages(
 
     let entity_name = get_entity_name(&mut conn, &entity_type, entity_id).await;
 
-    // Fetch all messages for this thread
+    // Fetch all messages for this entity, or for one of its threads
     let sql = r#"
         SELECT 
             id,
@@ -196,38 +283,23 @@ async fn get_thread_messages(
             content,
             created_by,
             occurred_at,
-            duration_minutes
+            duration_minutes,
+            metadata->>'direction' AS direction
         FROM interactions
-        WHERE record_id = $1 AND tenant_id = $2
-        ORDER BY occurred_at ASC
+        WHERE tenant_id = $2
+          AND CASE WHEN $3::uuid IS NULL THEN record_id = $1 ELSE thread_id = $3 END
+        ORDER BY occurred_at ASC, id ASC
     "#;
 
     let rows = sqlx::query(sql)
         .bind(entity_id)
         .bind(tenant.id)
+        .bind(query.thread_id)
         .fetch_all(&mut **conn)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-    let messages: Vec<ThreadMessageResponse> = rows.iter().map(|row| {
-        let interaction_type: String = row.try_get("interaction_type").unwrap_or_default();
-        
-        let direction = match interaction_type.to_lowercase().as_str() {
-            "email" | "message" => "inbound",
-            _ => "outbound",
-        };
-
-        ThreadMessageResponse {
-            id: row.try_get("id").unwrap_or_default(),
-            interaction_type,
-            title: row.try_get("title").unwrap_or_default(),
-            content: row.try_get("content").ok(),
-            created_by: row.try_get("created_by").unwrap_or_default(),
-            occurred_at: row.try_get("occurred_at").unwrap_or_else(|_| Utc::now()),
-            direction: direction.to_string(),
-            duration_minutes: row.try_get("duration_minutes").ok(),
-        }
-    }).collect();
+    let messages: Vec<ThreadMessageResponse> = rows.iter().map(|row| message_from_row(row).into()).collect();
 
     Ok(Json(MessagesListResponse {
         data: messages,
@@ -266,8 +338,8 @@ async fn send_reply(
     // Create the interaction
     sqlx::query(
         r#"
-        INSERT INTO interactions (id, tenant_id, entity_type, record_id, interaction_type, title, content, created_by, occurred_at, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        INSERT INTO interactions (id, tenant_id, entity_type, record_id, interaction_type, title, content, created_by, occurred_at, created_at, updated_at, metadata)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, '{"direction": "outbound"}')
         "#,
     )
     .bind(id)
@@ -297,6 +369,322 @@ async fn send_reply(
     }))
 }
 
+/// POST /inbox/merge - Combine threads into the first one
+async fn merge_threads_handler(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Json(req): Json<MergeThreadsRequest>,
+) -> Result<Json<ThreadChangeResponse>, ApiError> {
+    let thread_id = merge_threads(&mut conn, tenant.id, &req.thread_ids).await?;
+    let merged: Vec<Uuid> = req.thread_ids.into_iter().filter(|id| *id != thread_id).collect();
+
+    emit_threads_merged(&state, tenant.id, thread_id, merged.clone());
+
+    Ok(Json(ThreadChangeResponse { thread_id, affected_thread_ids: merged }))
+}
+
+/// POST /inbox/split - Move a message and everything after it to a new thread
+async fn split_thread_handler(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Json(req): Json<SplitThreadRequest>,
+) -> Result<Json<ThreadChangeResponse>, ApiError> {
+    let new_thread_id = split_thread(&mut conn, tenant.id, req.thread_id, req.from_message_id).await?;
+
+    emit_thread_split(&state, tenant.id, req.thread_id, new_thread_id);
+
+    Ok(Json(ThreadChangeResponse { thread_id: req.thread_id, affected_thread_ids: vec![new_thread_id] }))
+}
+
+/// POST /inbox/read - Mark a thread read up to a message for the current user
+async fn mark_read_handler(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    auth: ExtractAuth,
+    mut conn: RlsConn,
+    Json(req): Json<MarkReadRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = auth.0.user.id;
+    let shared = mark_read(&mut conn, tenant.id, user_id, req.thread_id, req.up_to_message_id).await?;
+    let total = unread_count(&mut conn, tenant.id, user_id).await?;
+
+    // A shared thread changes every agent's count, so clients refetch their own
+    if shared {
+        emit_unread_count_changed(&state, tenant.id, req.thread_id, None, None);
+    } else {
+        emit_unread_count_changed(&state, tenant.id, req.thread_id, Some(user_id), Some(total));
+    }
+
+    Ok(Json(serde_json::json!({ "total": total })))
+}
+
+/// GET /inbox/unread - Unread message counts for the current user,
+/// optionally for one thread
+async fn get_unread(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    auth: ExtractAuth,
+    Query(query): Query<MessagesQuery>,
+    mut conn: RlsConn,
+) -> Result<Json<UnreadResponse>, ApiError> {
+    let user_id = auth.0.user.id;
+    if let Some(thread_id) = query.thread_id {
+        let count = thread_unread_count(&mut conn, tenant.id, user_id, thread_id).await?;
+        let threads = HashMap::from([(thread_id, count)]);
+        return Ok(Json(UnreadResponse { total: count, threads }));
+    }
+
+    let threads = unread_by_thread(&mut conn, tenant.id, user_id, None).await?;
+    Ok(Json(UnreadResponse { total: threads.values().sum(), threads }))
+}
+
+// ============================================================================
+// Read state
+// ============================================================================
+
+/// Record that `user_id` has read `thread_id` up to and including
+/// `up_to_message_id`. The marker never moves backwards. On a shared thread
+/// the marker is team-wide; returns whether the thread is shared.
+pub(crate) async fn mark_read(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    thread_id: Uuid,
+    up_to_message_id: Uuid,
+) -> Result<bool, ApiError> {
+    let message: Option<(DateTime<Utc>, bool)> = sqlx::query_as(
+        r#"
+        SELECT i.occurred_at, t.shared_read
+        FROM interactions i
+        JOIN inbox_threads t ON t.id = i.thread_id
+        WHERE i.tenant_id = $1 AND i.id = $2 AND i.thread_id = $3
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(up_to_message_id)
+    .bind(thread_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (read_at, shared) = message.ok_or_else(|| ApiError::NotFound("Message not found in thread".to_string()))?;
+
+    let conflict = if shared { "(thread_id) WHERE user_id IS NULL" } else { "(thread_id, user_id) WHERE user_id IS NOT NULL" };
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO inbox_read_state (tenant_id, thread_id, user_id, last_read_at, last_read_message_id)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT {}
+        DO UPDATE SET last_read_at = EXCLUDED.last_read_at,
+                      last_read_message_id = EXCLUDED.last_read_message_id,
+                      updated_at = NOW()
+        WHERE (inbox_read_state.last_read_at, inbox_read_state.last_read_message_id)
+            < (EXCLUDED.last_read_at, EXCLUDED.last_read_message_id)
+        "#,
+        conflict
+    ))
+    .bind(tenant_id)
+    .bind(thread_id)
+    .bind((!shared).then_some(user_id))
+    .bind(read_at)
+    .bind(up_to_message_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(shared)
+}
+
+/// Inbound messages `user_id` has not read, per thread (only threads with
+/// unread messages appear). Messages the user sent never count.
+pub(crate) async fn unread_by_thread(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    thread_id: Option<Uuid>,
+) -> Result<HashMap<Uuid, i64>, ApiError> {
+    let rows: Vec<(Uuid, i64)> = sqlx::query_as(&format!(
+        r#"
+        SELECT i.thread_id, COUNT(*)
+        FROM interactions i
+        JOIN inbox_threads t ON t.id = i.thread_id
+        LEFT JOIN inbox_read_state r ON r.thread_id = t.id
+            AND CASE WHEN t.shared_read THEN r.user_id IS NULL ELSE r.user_id = $2 END
+        WHERE i.tenant_id = $1
+          AND ($3::uuid IS NULL OR i.thread_id = $3)
+          AND i.created_by <> $2
+          AND {}
+          AND (r.id IS NULL OR (i.occurred_at, i.id) > (r.last_read_at, r.last_read_message_id))
+        GROUP BY i.thread_id
+        "#,
+        INBOUND_SQL
+    ))
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(thread_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Total unread inbound messages for `user_id` across all threads
+pub(crate) async fn unread_count(conn: &mut sqlx::PgConnection, tenant_id: Uuid, user_id: Uuid) -> Result<i64, ApiError> {
+    Ok(unread_by_thread(conn, tenant_id, user_id, None).await?.values().sum())
+}
+
+/// Unread inbound messages for `user_id` in one thread
+pub(crate) async fn thread_unread_count(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    thread_id: Uuid,
+) -> Result<i64, ApiError> {
+    Ok(unread_by_thread(conn, tenant_id, user_id, Some(thread_id)).await?.get(&thread_id).copied().unwrap_or(0))
+}
+
+// ============================================================================
+// Thread merge & split
+// ============================================================================
+
+/// Combine threads into the first of `thread_ids`, returning its id
+///
+/// Messages move to the surviving thread, and the other threads (plus any
+/// already merged into them) point at it through `merged_into`, so new
+/// messages on their (record, channel) keep landing in the merged thread.
+pub(crate) async fn merge_threads(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    thread_ids: &[Uuid],
+) -> Result<Uuid, ApiError> {
+    let mut ids: Vec<Uuid> = Vec::with_capacity(thread_ids.len());
+    for id in thread_ids {
+        if !ids.contains(id) {
+            ids.push(*id);
+        }
+    }
+    let [target, others @ ..] = ids.as_slice() else {
+        return Err(ApiError::BadRequest(InboxError::NothingToMerge.to_string()));
+    };
+    if others.is_empty() {
+        return Err(ApiError::BadRequest(InboxError::NothingToMerge.to_string()));
+    }
+
+    let mut tx = conn.begin().await?;
+    let found: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM inbox_threads WHERE tenant_id = $1 AND id = ANY($2) AND merged_into IS NULL FOR UPDATE",
+    )
+    .bind(tenant_id)
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    if found.len() != ids.len() {
+        return Err(ApiError::NotFound("Thread not found".to_string()));
+    }
+
+    sqlx::query("UPDATE interactions SET thread_id = $2 WHERE tenant_id = $1 AND thread_id = ANY($3)")
+        .bind(tenant_id)
+        .bind(target)
+        .bind(others)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        UPDATE inbox_threads SET merged_into = $2, updated_at = NOW()
+        WHERE tenant_id = $1 AND (id = ANY($3) OR merged_into = ANY($3))
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(target)
+    .bind(others)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE inbox_threads SET updated_at = NOW() WHERE id = $1")
+        .bind(target)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(*target)
+}
+
+/// Move `from_message_id` and every later message of the thread to a new
+/// thread with the same (record, channel), returning the new thread's id
+///
+/// New messages on that (record, channel) join the new thread, as the most
+/// recent conversation.
+pub(crate) async fn split_thread(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    thread_id: Uuid,
+    from_message_id: Uuid,
+) -> Result<Uuid, ApiError> {
+    let mut tx = conn.begin().await?;
+    let thread: Option<(String, Uuid, String)> = sqlx::query_as(
+        "SELECT entity_type, record_id, channel FROM inbox_threads WHERE tenant_id = $1 AND id = $2 AND merged_into IS NULL FOR UPDATE",
+    )
+    .bind(tenant_id)
+    .bind(thread_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (entity_type, record_id, channel) = thread.ok_or_else(|| ApiError::NotFound("Thread not found".to_string()))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, interaction_type, title, content, created_by, occurred_at, duration_minutes, metadata->>'direction' AS direction
+        FROM interactions
+        WHERE tenant_id = $1 AND thread_id = $2
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(thread_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    let messages = rows.iter().map(message_from_row).collect();
+
+    let (_, moved) = split_messages(messages, from_message_id).map_err(|e| match e {
+        InboxError::MessageNotInThread(_) => ApiError::NotFound(e.to_string()),
+        _ => ApiError::BadRequest(e.to_string()),
+    })?;
+    let moved: Vec<Uuid> = moved.into_iter().map(|m| m.id).collect();
+
+    let new_thread_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO inbox_threads (tenant_id, entity_type, record_id, channel) VALUES ($1, $2, $3, $4) RETURNING id",
+    )
+    .bind(tenant_id)
+    .bind(&entity_type)
+    .bind(record_id)
+    .bind(&channel)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE interactions SET thread_id = $2 WHERE tenant_id = $1 AND id = ANY($3)")
+        .bind(tenant_id)
+        .bind(new_thread_id)
+        .bind(&moved)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(new_thread_id)
+}
+
+fn message_from_row(row: &sqlx::postgres::PgRow) -> ThreadMessage {
+    use sqlx::Row;
+
+    let interaction_type: String = row.try_get("interaction_type").unwrap_or_default();
+    let declared: Option<String> = row.try_get("direction").unwrap_or_default();
+    let direction = message_direction(&interaction_type, declared.as_deref());
+
+    ThreadMessage {
+        id: row.try_get("id").unwrap_or_default(),
+        interaction_type,
+        title: row.try_get("title").unwrap_or_default(),
+        content: row.try_get("content").ok(),
+        created_by: row.try_get("created_by").unwrap_or_default(),
+        occurred_at: row.try_get("occurred_at").unwrap_or_else(|_| Utc::now()),
+        direction: direction.to_string(),
+        duration_minutes: row.try_get("duration_minutes").ok(),
+    }
+}
+
 /// Helper to get entity name by type and ID
 async fn get_entity_name(conn: &mut RlsConn, entity_type: &str, entity_id: Uuid) -> String {
     use sqlx::Row;
@@ -353,3 +741,168 @@ fn truncate_preview(text: &str, max_len: usize) -> String {
         format!("{}...", &text[..max_len.saturating_sub(3)])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::TestTenant;
+
+    struct Fixture {
+        tenant: TestTenant,
+        user_id: Uuid,
+        contact_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Inbox Test").await;
+            let user_id = tenant.user("agent@example.com").await;
+            Self { tenant, user_id, contact_id: Uuid::new_v4() }
+        }
+
+        /// Log a message on the contact `minute` minutes into the conversation
+        async fn message(&self, interaction_type: &str, minute: i64) -> (Uuid, Uuid) {
+            let at = DateTime::parse_from_rfc3339("2025-03-01T09:00:00Z").unwrap().with_timezone(&Utc)
+                + chrono::Duration::minutes(minute);
+            sqlx::query_as(
+                "INSERT INTO interactions (tenant_id, entity_type, record_id, interaction_type, title, created_by, occurred_at)
+                 VALUES ($1, 'contact', $2, $3, $4, $5, $6)
+                 RETURNING id, thread_id",
+            )
+            .bind(self.tenant.id)
+            .bind(self.contact_id)
+            .bind(interaction_type)
+            .bind(format!("{} at {}", interaction_type, minute))
+            .bind(self.user_id)
+            .bind(at)
+            .fetch_one(&self.tenant.pool)
+            .await
+            .unwrap()
+        }
+
+        async fn thread_titles(&self, thread_id: Uuid) -> Vec<String> {
+            sqlx::query_scalar("SELECT title FROM interactions WHERE thread_id = $1 ORDER BY occurred_at, id")
+                .bind(thread_id)
+                .fetch_all(&self.tenant.pool)
+                .await
+                .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_merge_combines_channels_in_time_order() {
+        let fx = Fixture::new().await;
+        let (_, email_thread) = fx.message("email", 0).await;
+        let (_, whatsapp_thread) = fx.message("message", 10).await;
+        assert_eq!(fx.message("email", 20).await.1, email_thread);
+        assert_eq!(fx.message("message", 30).await.1, whatsapp_thread);
+        assert_ne!(email_thread, whatsapp_thread);
+
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let merged = merge_threads(&mut conn, fx.tenant.id, &[email_thread, whatsapp_thread]).await.unwrap();
+
+        assert_eq!(merged, email_thread);
+        assert_eq!(
+            fx.thread_titles(email_thread).await,
+            vec!["email at 0", "message at 10", "email at 20", "message at 30"]
+        );
+        assert!(fx.thread_titles(whatsapp_thread).await.is_empty());
+        // The merged channel keeps resolving to the surviving thread
+        assert_eq!(fx.message("message", 40).await.1, email_thread);
+        assert!(matches!(
+            merge_threads(&mut conn, fx.tenant.id, &[email_thread, whatsapp_thread]).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_split_partitions_thread_at_message() {
+        let fx = Fixture::new().await;
+        let (first, thread_id) = fx.message("email", 0).await;
+        fx.message("email", 10).await;
+        let (from, _) = fx.message("email", 20).await;
+        fx.message("email", 30).await;
+
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let new_thread = split_thread(&mut conn, fx.tenant.id, thread_id, from).await.unwrap();
+
+        assert_ne!(new_thread, thread_id);
+        assert_eq!(fx.thread_titles(thread_id).await, vec!["email at 0", "email at 10"]);
+        assert_eq!(fx.thread_titles(new_thread).await, vec!["email at 20", "email at 30"]);
+        // Later email continues the newest conversation
+        assert_eq!(fx.message("email", 40).await.1, new_thread);
+        assert!(matches!(
+            split_thread(&mut conn, fx.tenant.id, thread_id, first).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_unread_increments_on_inbound_and_resets_on_read() {
+        let fx = Fixture::new().await;
+        let (alice, bob) = (fx.tenant.user("alice@example.com").await, fx.tenant.user("bob@example.com").await);
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+
+        let (_, thread_id) = fx.message("email", 0).await;
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, alice).await.unwrap(), 1);
+        let (latest, _) = fx.message("email", 10).await;
+        fx.message("call", 20).await;
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, alice).await.unwrap(), 2);
+        assert_eq!(thread_unread_count(&mut conn, fx.tenant.id, alice, thread_id).await.unwrap(), 2);
+
+        let shared = mark_read(&mut conn, fx.tenant.id, alice, thread_id, latest).await.unwrap();
+
+        assert!(!shared);
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, alice).await.unwrap(), 0);
+        // Another agent's unread count is untouched
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, bob).await.unwrap(), 2);
+        fx.message("email", 30).await;
+        assert_eq!(thread_unread_count(&mut conn, fx.tenant.id, alice, thread_id).await.unwrap(), 1);
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_read_marker_never_moves_back_and_shared_threads_clear_for_all() {
+        let fx = Fixture::new().await;
+        let (alice, bob) = (fx.tenant.user("alice@example.com").await, fx.tenant.user("bob@example.com").await);
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let (first, thread_id) = fx.message("message", 0).await;
+        let (second, _) = fx.message("message", 10).await;
+
+        mark_read(&mut conn, fx.tenant.id, alice, thread_id, second).await.unwrap();
+        mark_read(&mut conn, fx.tenant.id, alice, thread_id, first).await.unwrap();
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, alice).await.unwrap(), 0);
+
+        sqlx::query("UPDATE inbox_threads SET shared_read = true WHERE id = $1")
+            .bind(thread_id)
+            .execute(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, bob).await.unwrap(), 2);
+        assert!(mark_read(&mut conn, fx.tenant.id, alice, thread_id, second).await.unwrap());
+        assert_eq!(unread_count(&mut conn, fx.tenant.id, bob).await.unwrap(), 0);
+        assert!(matches!(
+            mark_read(&mut conn, fx.tenant.id, alice, thread_id, Uuid::new_v4()).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+}
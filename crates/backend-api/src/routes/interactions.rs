@@ -11,6 +11,10 @@ use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use core_engagement::{merge_timeline, TimelineCursor, TimelineItem, TimelineOptions, TimelinePage, TimelineSource};
+
+use crate::events::emit_unread_count_changed;
+use crate::middleware::audit_log::FieldChange;
 use crate::state::AppState;
 use crate::error::ApiError;
 
@@ -19,6 +23,7 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/", get(list_interactions))
         .route("/", post(create_interaction))
         .route("/summary/:entity_type/:record_id", get(get_interaction_summary))
+        .route("/timeline/:entity_type/:record_id", get(get_timeline))
         .route("/:id", get(get_interaction))
         .route("/:id", delete(delete_interaction))
 }
@@ -162,6 +167,7 @@ async fn list_interactions(
 }
 
 async fn create_interaction(
+    State(state): State<Arc<AppState>>,
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
     mut conn: RlsConn,
     Json(req): Json<CreateInteractionRequest>,
@@ -169,10 +175,11 @@ async fn create_interaction(
     let now = Utc::now();
     let id = Uuid::new_v4();
 
-    sqlx::query(
+    let thread_id: Option<Uuid> = sqlx::query_scalar(
         r#"
         INSERT INTO interactions (id, tenant_id, entity_type, record_id, interaction_type, title, content, created_by, occurred_at, duration_minutes, created_at, updated_at)
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING thread_id
         "#,
     )
     .bind(id)
@@ -187,10 +194,17 @@ async fn create_interaction(
     .bind(req.duration_minutes)
     .bind(now)
     .bind(now)
-    .execute(&mut **conn)
+    .fetch_one(&mut **conn)
     .await
     .map_err(|e| ApiError::Internal(e.to_string()))?;
 
+    // A new customer message raises every agent's unread count
+    if let Some(thread_id) = thread_id {
+        if core_engagement::message_direction(&req.interaction_type, None) == "inbound" {
+            emit_unread_count_changed(&state, tenant.id, thread_id, None, None);
+        }
+    }
+
     Ok(Json(InteractionResponse {
         id,
         entity_type: req.entity_type,
@@ -303,3 +317,396 @@ async fn get_interaction_summary(
         counts_by_type,
     }))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineQuery {
+    #[serde(default)]
+    pub include_associated: bool,
+    /// Comma-separated item types, e.g. `call,email,task`
+    pub types: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// GET /interactions/timeline/:entity_type/:record_id
+async fn get_timeline(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Path((entity_type, record_id)): Path<(String, Uuid)>,
+    Query(query): Query<TimelineQuery>,
+    mut conn: RlsConn,
+) -> Result<Json<TimelinePage>, ApiError> {
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(str::parse::<TimelineCursor>)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+    let options = TimelineOptions {
+        include_associated: query.include_associated,
+        item_types: query
+            .types
+            .as_deref()
+            .map(|types| types.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_lowercase).collect())
+            .unwrap_or_default(),
+        cursor,
+        limit: query.limit,
+    };
+
+    Ok(Json(timeline_for(&mut conn, tenant.id, &entity_type, record_id, &options).await?))
+}
+
+/// A record's interactions, field changes and tasks, newest first
+///
+/// With `include_associated`, interactions logged on records directly
+/// associated with this one (in either direction) are pulled in too, tagged
+/// with the association they came through. Field changes only carry the
+/// changed field labels - values stay behind the audit log's PII checks.
+pub(crate) async fn timeline_for(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type: &str,
+    record_id: Uuid,
+    options: &TimelineOptions,
+) -> Result<TimelinePage, ApiError> {
+    // One extra row per source tells whether there is another page
+    let fetch = options.limit() as i64 + 1;
+    let (cursor_at, cursor_id) = options.cursor.map(|c| (c.occurred_at, c.id)).unzip();
+    let item_types: Vec<String> = options.item_types.iter().map(|t| t.to_lowercase()).collect();
+
+    let mut sources = vec![
+        timeline_interactions(conn, tenant_id, entity_type, record_id, options.include_associated, &item_types, cursor_at, cursor_id, fetch)
+            .await?,
+    ];
+    if options.includes("field_change") {
+        sources.push(timeline_field_changes(conn, tenant_id, entity_type, record_id, cursor_at, cursor_id, fetch).await?);
+    }
+    if options.includes("task") {
+        sources.push(timeline_tasks(conn, tenant_id, entity_type, record_id, cursor_at, cursor_id, fetch).await?);
+    }
+
+    Ok(merge_timeline(sources, options))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn timeline_interactions(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type: &str,
+    record_id: Uuid,
+    include_associated: bool,
+    item_types: &[String],
+    cursor_at: Option<DateTime<Utc>>,
+    cursor_id: Option<Uuid>,
+    fetch: i64,
+) -> Result<Vec<TimelineItem>, ApiError> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(
+        r#"
+        WITH linked AS (
+            SELECT $2::uuid AS record_id, $3::text AS entity_type, NULL::text AS association
+            UNION ALL
+            SELECT a.target_id, d.target_entity, d.name
+            FROM associations a JOIN association_defs d ON d.id = a.association_def_id
+            WHERE $4 AND a.tenant_id = $1 AND a.source_id = $2 AND d.source_entity = $3
+            UNION ALL
+            SELECT a.source_id, d.source_entity, d.name
+            FROM associations a JOIN association_defs d ON d.id = a.association_def_id
+            WHERE $4 AND a.tenant_id = $1 AND a.target_id = $2 AND d.target_entity = $3
+        ),
+        records AS (
+            SELECT l.record_id, l.entity_type, MIN(l.association) AS association
+            FROM linked l
+            WHERE NOT EXISTS (SELECT 1 FROM entity_records r WHERE r.id = l.record_id AND r.deleted_at IS NOT NULL)
+            GROUP BY l.record_id, l.entity_type
+        )
+        SELECT i.id, i.interaction_type, i.occurred_at, i.entity_type, i.record_id, i.title, i.content, i.created_by,
+               r.association
+        FROM interactions i
+        JOIN records r ON r.record_id = i.record_id AND r.entity_type = i.entity_type
+        WHERE i.tenant_id = $1
+          AND (cardinality($5::text[]) = 0 OR lower(i.interaction_type) = ANY($5))
+          AND ($6::timestamptz IS NULL OR (i.occurred_at, i.id) < ($6, $7))
+        ORDER BY i.occurred_at DESC, i.id DESC
+        LIMIT $8
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(record_id)
+    .bind(entity_type)
+    .bind(include_associated)
+    .bind(item_types)
+    .bind(cursor_at)
+    .bind(cursor_id)
+    .bind(fetch)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(TimelineItem {
+                id: row.try_get("id")?,
+                source: TimelineSource::Interaction,
+                item_type: row.try_get::<String, _>("interaction_type")?.to_lowercase(),
+                occurred_at: row.try_get("occurred_at")?,
+                entity_type: row.try_get("entity_type")?,
+                record_id: row.try_get("record_id")?,
+                title: row.try_get("title")?,
+                summary: row.try_get("content")?,
+                actor_id: row.try_get("created_by")?,
+                via_association: row.try_get("association")?,
+            })
+        })
+        .collect()
+}
+
+async fn timeline_field_changes(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type: &str,
+    record_id: Uuid,
+    cursor_at: Option<DateTime<Utc>>,
+    cursor_id: Option<Uuid>,
+    fetch: i64,
+) -> Result<Vec<TimelineItem>, ApiError> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, changes, created_at
+        FROM audit_logs
+        WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
+          AND action = 'update' AND jsonb_array_length(changes) > 0
+          AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(entity_type)
+    .bind(record_id)
+    .bind(cursor_at)
+    .bind(cursor_id)
+    .bind(fetch)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let changes: Vec<FieldChange> =
+                serde_json::from_value(row.try_get::<serde_json::Value, _>("changes")?).unwrap_or_default();
+            let labels: Vec<&str> = changes
+                .iter()
+                .map(|c| if c.label.is_empty() { c.field.as_str() } else { c.label.as_str() })
+                .collect();
+
+            Ok(TimelineItem {
+                id: row.try_get("id")?,
+                source: TimelineSource::FieldChange,
+                item_type: "field_change".to_string(),
+                occurred_at: row.try_get("created_at")?,
+                entity_type: entity_type.to_string(),
+                record_id,
+                title: "Updated fields".to_string(),
+                summary: (!labels.is_empty()).then(|| labels.join(", ")),
+                actor_id: row.try_get("user_id")?,
+                via_association: None,
+            })
+        })
+        .collect()
+}
+
+async fn timeline_tasks(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: Uuid,
+    entity_type: &str,
+    record_id: Uuid,
+    cursor_at: Option<DateTime<Utc>>,
+    cursor_id: Option<Uuid>,
+    fetch: i64,
+) -> Result<Vec<TimelineItem>, ApiError> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, status, created_by, created_at
+        FROM tasks
+        WHERE tenant_id = $1 AND linked_entity_type = $2 AND linked_entity_id = $3
+          AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(entity_type)
+    .bind(record_id)
+    .bind(cursor_at)
+    .bind(cursor_id)
+    .bind(fetch)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(TimelineItem {
+                id: row.try_get("id")?,
+                source: TimelineSource::Task,
+                item_type: "task".to_string(),
+                occurred_at: row.try_get("created_at")?,
+                entity_type: entity_type.to_string(),
+                record_id,
+                title: row.try_get("title")?,
+                summary: Some(format!("Status: {}", row.try_get::<String, _>("status")?)),
+                actor_id: row.try_get("created_by")?,
+                via_association: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::TestTenant;
+
+    struct Fixture {
+        tenant: TestTenant,
+        user_id: Uuid,
+        deal_id: Uuid,
+        contact_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Timeline Test").await;
+            let user_id = tenant.user("agent@example.com").await;
+            let (deal_id, contact_id) = (Uuid::new_v4(), Uuid::new_v4());
+            let def_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality)
+                 VALUES ($1, $2, 'deal', 'contact', 'deal_contacts', 'Contacts', 'Deals', 'many_to_many') RETURNING id",
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant.id)
+            .fetch_one(&tenant.pool)
+            .await
+            .unwrap();
+            sqlx::query("INSERT INTO associations (id, tenant_id, association_def_id, source_id, target_id) VALUES ($1, $2, $3, $4, $5)")
+                .bind(Uuid::new_v4())
+                .bind(tenant.id)
+                .bind(def_id)
+                .bind(deal_id)
+                .bind(contact_id)
+                .execute(&tenant.pool)
+                .await
+                .unwrap();
+            Self { tenant, user_id, deal_id, contact_id }
+        }
+
+        fn at(minute: i64) -> DateTime<Utc> {
+            DateTime::parse_from_rfc3339("2025-03-01T09:00:00Z").unwrap().with_timezone(&Utc) + chrono::Duration::minutes(minute)
+        }
+
+        async fn interaction(&self, entity_type: &str, record_id: Uuid, interaction_type: &str, title: &str, minute: i64) {
+            sqlx::query(
+                "INSERT INTO interactions (tenant_id, entity_type, record_id, interaction_type, title, created_by, occurred_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(self.tenant.id)
+            .bind(entity_type)
+            .bind(record_id)
+            .bind(interaction_type)
+            .bind(title)
+            .bind(self.user_id)
+            .bind(Self::at(minute))
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+        }
+
+        async fn deal_activity(&self) {
+            sqlx::query(
+                "INSERT INTO tasks (tenant_id, title, linked_entity_type, linked_entity_id, created_by, created_at)
+                 VALUES ($1, 'Send contract', 'deal', $2, $3, $4)",
+            )
+            .bind(self.tenant.id)
+            .bind(self.deal_id)
+            .bind(self.user_id)
+            .bind(Self::at(20))
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO audit_logs (tenant_id, user_id, action, resource_type, resource_id, changes, created_at)
+                 VALUES ($1, $2, 'update', 'deal', $3, $4, $5)",
+            )
+            .bind(self.tenant.id)
+            .bind(self.user_id)
+            .bind(self.deal_id)
+            .bind(serde_json::json!([{ "field": "amount", "label": "Amount", "old": 100, "new": 200 }]))
+            .bind(Self::at(30))
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+        }
+
+        async fn timeline(&self, options: &TimelineOptions) -> TimelinePage {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            timeline_for(&mut conn, self.tenant.id, "deal", self.deal_id, options).await.unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    fn titles(page: &TimelinePage) -> Vec<&str> {
+        page.items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_deal_timeline_includes_associated_contact_interactions() {
+        let fx = Fixture::new().await;
+        fx.interaction("deal", fx.deal_id, "meeting", "Kick-off", 10).await;
+        fx.interaction("contact", fx.contact_id, "call", "Called buyer", 40).await;
+        fx.deal_activity().await;
+
+        let own = fx.timeline(&TimelineOptions::default()).await;
+        assert_eq!(titles(&own), vec!["Updated fields", "Send contract", "Kick-off"]);
+        assert_eq!(own.items[0].summary.as_deref(), Some("Amount"));
+
+        let options = TimelineOptions { include_associated: true, ..Default::default() };
+        let all = fx.timeline(&options).await;
+        assert_eq!(titles(&all), vec!["Called buyer", "Updated fields", "Send contract", "Kick-off"]);
+        assert_eq!(all.items[0].record_id, fx.contact_id);
+        assert_eq!(all.items[0].via_association.as_deref(), Some("deal_contacts"));
+
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_timeline_pages_and_filters_by_type() {
+        let fx = Fixture::new().await;
+        fx.interaction("deal", fx.deal_id, "meeting", "Kick-off", 10).await;
+        fx.interaction("contact", fx.contact_id, "call", "Called buyer", 40).await;
+        fx.deal_activity().await;
+
+        let mut options = TimelineOptions { include_associated: true, limit: Some(2), ..Default::default() };
+        let first = fx.timeline(&options).await;
+        assert_eq!(titles(&first), vec!["Called buyer", "Updated fields"]);
+        options.cursor = Some(first.next_cursor.unwrap().parse().unwrap());
+        let second = fx.timeline(&options).await;
+        assert_eq!(titles(&second), vec!["Send contract", "Kick-off"]);
+        assert_eq!(second.next_cursor, None);
+
+        let options = TimelineOptions {
+            include_associated: true,
+            item_types: vec!["Call".to_string(), "task".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(titles(&fx.timeline(&options).await), vec!["Called buyer", "Send contract"]);
+
+        fx.cleanup().await;
+    }
+}
@@ -0,0 +1,526 @@
+//! Lead capture - public website forms that create contacts
+//!
+//! A form posts JSON to `/webhooks/lead/:token`; the token identifies the
+//! tenant's form, whose mapping turns the posted keys into contact fields.
+//! The contact is created as a `lead` and the record_created workflows run
+//! as for any other new contact. Only the token's SHA-256 hash is stored.
+//!
+//! Spam guards: a per-form rate limit, a honeypot input that only bots fill
+//! in, and dropping repeat submissions for the same email within the form's
+//! dedup window. Dropped submissions get the same response as accepted ones.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use core_auth::middleware::ExtractAuth;
+use core_models::FieldDef;
+use core_node_engine::EntityEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, PgConnection, PgPool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::middleware::tenant::tenant_phone_region;
+use crate::middleware::rate_limit::{RateLimitConfig, RateLimitResult, RateLimiter};
+use crate::outbox;
+use crate::routes::entities::{spawn_workflows, validate_and_process_payload};
+use crate::state::AppState;
+
+/// Entity type leads are created as
+const LEAD_ENTITY: &str = "contact";
+
+/// Stage set on every captured contact
+const LEAD_STAGE: &str = "lead";
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_forms).post(create_form))
+        .route("/:id", delete(delete_form))
+}
+
+/// Submissions per form per minute, with a small burst on top
+pub fn rate_limiter() -> RateLimiter {
+    RateLimiter::with_config(RateLimitConfig { requests_per_window: 30, window_secs: 60, burst_size: 10 })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFormRequest {
+    pub name: String,
+    #[serde(default)]
+    pub field_mapping: HashMap<String, String>,
+    pub honeypot_field: Option<String>,
+    pub dedup_window_secs: Option<i32>,
+}
+
+/// A form as configured; the token is never read back
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LeadForm {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub field_mapping: sqlx::types::Json<HashMap<String, String>>,
+    pub honeypot_field: String,
+    pub dedup_window_secs: i32,
+    pub is_enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What happened to a submission
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeadOutcome {
+    Created(Uuid),
+    /// Same email already captured within the window
+    Duplicate(Uuid),
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+/// GET /lead-forms
+async fn list_forms(State(state): State<Arc<AppState>>, auth: ExtractAuth) -> Result<Json<Vec<LeadForm>>, ApiError> {
+    let forms = sqlx::query_as(&format!("SELECT {} FROM lead_capture_forms WHERE tenant_id = $1 ORDER BY name", FORM_COLUMNS))
+        .bind(auth.0.user.tenant_id)
+        .fetch_all(&state.pool)
+        .await?;
+    Ok(Json(forms))
+}
+
+/// POST /lead-forms
+///
+/// The token is only returned here.
+async fn create_form(
+    State(state): State<Arc<AppState>>,
+    auth: ExtractAuth,
+    Json(payload): Json<CreateFormRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let tenant_id = auth.0.user.tenant_id;
+    let entity_type = state.metadata.get_entity_type(tenant_id, LEAD_ENTITY).await?;
+    let fields = state.metadata.get_fields(tenant_id, entity_type.id).await?;
+
+    let (form, token) = insert_form(&state.pool, tenant_id, Some(auth.0.user.id), &payload, &fields).await?;
+    Ok(Json(json!({
+        "form": form,
+        "token": token,
+        "webhook_path": format!("/webhooks/lead/{}", token),
+        "message": "Store this URL securely - it will not be shown again"
+    })))
+}
+
+/// DELETE /lead-forms/:id
+async fn delete_form(
+    State(state): State<Arc<AppState>>,
+    auth: ExtractAuth,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let result = sqlx::query("DELETE FROM lead_capture_forms WHERE id = $1 AND tenant_id = $2")
+        .bind(id)
+        .bind(auth.0.user.tenant_id)
+        .execute(&state.pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Lead form not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+const FORM_COLUMNS: &str =
+    "id, tenant_id, name, field_mapping, honeypot_field, dedup_window_secs, is_enabled, created_at";
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Store a form with a fresh random token, returning both
+pub(crate) async fn insert_form(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by: Option<Uuid>,
+    payload: &CreateFormRequest,
+    fields: &[FieldDef],
+) -> Result<(LeadForm, String), ApiError> {
+    if payload.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("Form name is required".to_string()));
+    }
+    if let Some(unknown) = payload.field_mapping.values().find(|name| !fields.iter().any(|f| &f.name == *name)) {
+        return Err(ApiError::BadRequest(format!("Unknown contact field '{}'", unknown)));
+    }
+    if payload.dedup_window_secs.is_some_and(|secs| secs < 0) {
+        return Err(ApiError::BadRequest("dedup_window_secs cannot be negative".to_string()));
+    }
+
+    use rand::Rng;
+    let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+    let form = sqlx::query_as(&format!(
+        "INSERT INTO lead_capture_forms (tenant_id, name, token_hash, field_mapping, honeypot_field, dedup_window_secs, created_by)
+         VALUES ($1, $2, $3, $4, COALESCE($5, '_honeypot'), COALESCE($6, 86400), $7)
+         RETURNING {}",
+        FORM_COLUMNS
+    ))
+    .bind(tenant_id)
+    .bind(payload.name.trim())
+    .bind(hash_token(&token))
+    .bind(sqlx::types::Json(&payload.field_mapping))
+    .bind(payload.honeypot_field.as_deref())
+    .bind(payload.dedup_window_secs)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((form, token))
+}
+
+/// Enabled form a webhook token belongs to
+pub(crate) async fn form_for_token(pool: &PgPool, token: &str) -> Result<Option<LeadForm>, ApiError> {
+    Ok(sqlx::query_as(&format!(
+        "SELECT {} FROM lead_capture_forms WHERE token_hash = $1 AND is_enabled",
+        FORM_COLUMNS
+    ))
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await?)
+}
+
+// ============================================================================
+// Webhook
+// ============================================================================
+
+/// POST /webhooks/lead/:tenant_token
+pub async fn receive_lead(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    Json(submission): Json<Value>,
+) -> Response {
+    let form = match form_for_token(&state.pool, &token).await {
+        Ok(Some(form)) => form,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Unknown lead form").into_response(),
+        Err(e) => return e.into_response(),
+    };
+
+    if let RateLimitResult::Limited { retry_after_secs } = state.lead_capture_limiter.check_limit(form.id).await {
+        warn!(tenant_id = %form.tenant_id, form_id = %form.id, "Lead form rate limited");
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after_secs.to_string())],
+            "Too many submissions",
+        )
+            .into_response();
+    }
+
+    let Some(submission) = submission.as_object() else {
+        return (StatusCode::BAD_REQUEST, "Expected a JSON object").into_response();
+    };
+    if is_honeypot_filled(&form, submission) {
+        info!(tenant_id = %form.tenant_id, form_id = %form.id, "Lead form honeypot filled; dropping submission");
+        return accepted();
+    }
+
+    let entity_type = match state.metadata.get_entity_type(form.tenant_id, LEAD_ENTITY).await {
+        Ok(e) => e,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let fields = match state.metadata.get_fields(form.tenant_id, entity_type.id).await {
+        Ok(f) => f,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let phone_region = match tenant_phone_region(&state.pool, form.tenant_id).await {
+        Ok(region) => region,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let data = match lead_data(&form, submission, &fields, &phone_region) {
+        Ok(data) => data,
+        Err(message) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({ "error": message }))).into_response(),
+    };
+
+    let mut conn = match state.pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    match capture_lead(&mut conn, &form, entity_type.id, data).await {
+        Ok((LeadOutcome::Created(id), Some(event))) => {
+            info!(tenant_id = %form.tenant_id, form_id = %form.id, record_id = %id, "Lead captured");
+            spawn_workflows(state.clone(), entity_type.id, event);
+            accepted()
+        }
+        Ok((outcome, _)) => {
+            info!(tenant_id = %form.tenant_id, form_id = %form.id, ?outcome, "Duplicate lead dropped");
+            accepted()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// The response for every submission the form takes, kept or not
+fn accepted() -> Response {
+    (StatusCode::ACCEPTED, Json(json!({ "status": "received" }))).into_response()
+}
+
+fn is_honeypot_filled(form: &LeadForm, submission: &Map<String, Value>) -> bool {
+    match submission.get(&form.honeypot_field) {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(_) => true,
+    }
+}
+
+/// Contact data for a submission: mapped keys, validated, as a lead
+fn lead_data(form: &LeadForm, submission: &Map<String, Value>, fields: &[FieldDef], phone_region: &str) -> Result<Value, String> {
+    let mut data = Map::new();
+    for (key, value) in submission {
+        let target = if form.field_mapping.is_empty() {
+            Some(key)
+        } else {
+            form.field_mapping.get(key)
+        };
+        let Some(field) = target.and_then(|name| fields.iter().find(|f| &f.name == name && !f.is_computed())) else {
+            continue;
+        };
+        match value {
+            Value::Null => {}
+            Value::String(s) if s.trim().is_empty() => {}
+            Value::String(s) => {
+                data.insert(field.name.clone(), Value::String(s.trim().to_string()));
+            }
+            other => {
+                data.insert(field.name.clone(), other.clone());
+            }
+        }
+    }
+    if data.is_empty() {
+        return Err("Submission has no contact fields".to_string());
+    }
+    data.insert("lifecycle_stage".to_string(), json!(LEAD_STAGE));
+
+    validate_and_process_payload(fields, &Value::Object(data), false, phone_region)
+}
+
+/// Create the lead unless its email was captured within the form's window
+///
+/// Submissions for one email are serialized with an advisory lock, so a
+/// double-clicked submit cannot create two contacts. The created event is
+/// in the outbox on return, for the caller to publish.
+pub(crate) async fn capture_lead(
+    conn: &mut PgConnection,
+    form: &LeadForm,
+    entity_type_id: Uuid,
+    data: Value,
+) -> Result<(LeadOutcome, Option<EntityEvent>), ApiError> {
+    let mut tx = conn.begin().await?;
+
+    let email = data.get("email").and_then(Value::as_str).map(str::to_lowercase);
+    if let Some(email) = &email {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("lead:{}:{}", form.tenant_id, email))
+            .execute(&mut *tx)
+            .await?;
+
+        let existing: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM entity_records
+             WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL
+               AND lower(data->>'email') = $3
+               AND created_at > NOW() - make_interval(secs => $4)
+             ORDER BY created_at DESC
+             LIMIT 1",
+        )
+        .bind(form.tenant_id)
+        .bind(entity_type_id)
+        .bind(email)
+        .bind(form.dedup_window_secs as f64)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(id) = existing {
+            tx.rollback().await?;
+            return Ok((LeadOutcome::Duplicate(id), None));
+        }
+    }
+
+    let event = insert_lead(&mut tx, form.tenant_id, entity_type_id, data).await?;
+    tx.commit().await?;
+
+    Ok((LeadOutcome::Created(event.record_id), Some(event)))
+}
+
+/// Insert a lead contact and queue its created event in the outbox
+async fn insert_lead(conn: &mut PgConnection, tenant_id: Uuid, entity_type_id: Uuid, data: Value) -> Result<EntityEvent, ApiError> {
+    let id: Uuid = sqlx::query_scalar(
+        "INSERT INTO entity_records (id, tenant_id, entity_type_id, data) VALUES ($1, $2, $3, $4) RETURNING id",
+    )
+    .bind(Uuid::new_v4())
+    .bind(tenant_id)
+    .bind(entity_type_id)
+    .bind(&data)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let event = EntityEvent::create(tenant_id, LEAD_ENTITY, id, data, None);
+    outbox::enqueue(conn, &event).await?;
+    Ok(event)
+}
+
+// ============================================================================
+// Provider leads
+// ============================================================================
+
+/// Create a lead contact from one an integration sent (e.g. a Facebook
+/// lead ad); `contact` is keyed by contact field name
+///
+/// Keys that aren't contact fields are dropped. Returns `None` when what's
+/// left doesn't validate, since a redelivery would fail the same way.
+pub(crate) async fn capture_provider_lead(
+    state: &Arc<AppState>,
+    tenant_id: Uuid,
+    contact: Map<String, Value>,
+) -> Result<Option<Uuid>, ApiError> {
+    let entity_type = state.metadata.get_entity_type(tenant_id, LEAD_ENTITY).await?;
+    let fields = state.metadata.get_fields(tenant_id, entity_type.id).await?;
+    let phone_region = tenant_phone_region(&state.pool, tenant_id).await?;
+
+    let mut data: Map<String, Value> = contact
+        .into_iter()
+        .filter(|(key, value)| {
+            !value.is_null() && fields.iter().any(|f| &f.name == key && !f.is_computed())
+        })
+        .collect();
+    data.insert("lifecycle_stage".to_string(), json!(LEAD_STAGE));
+    let data = match validate_and_process_payload(&fields, &Value::Object(data), false, &phone_region) {
+        Ok(data) => data,
+        Err(message) => {
+            warn!(tenant_id = %tenant_id, error = %message, "Dropping provider lead that doesn't validate");
+            return Ok(None);
+        }
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+    let event = insert_lead(&mut tx, tenant_id, entity_type.id, data).await?;
+    tx.commit().await?;
+
+    let id = event.record_id;
+    spawn_workflows(state.clone(), entity_type.id, event);
+    Ok(Some(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::{FieldType, DEFAULT_PHONE_REGION};
+    use test_support::TestTenant;
+
+    /// Tenant with a `contact` entity type and a lead form; removed again by `cleanup`
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+        fields: Vec<FieldDef>,
+        form: LeadForm,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Lead Test").await;
+            let entity_type_id = tenant.entity_type("crm", "contact", "Contact", "Contacts").await;
+            let fields = vec![
+                FieldDef::new(tenant.id, entity_type_id, "first_name", "First Name", FieldType::Text).required(),
+                FieldDef::new(tenant.id, entity_type_id, "email", "Email", FieldType::Email),
+                FieldDef::new(tenant.id, entity_type_id, "lifecycle_stage", "Lifecycle Stage", FieldType::Text),
+            ];
+            let request = CreateFormRequest {
+                name: "Website".to_string(),
+                field_mapping: [("name", "first_name"), ("email_address", "email")]
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                honeypot_field: None,
+                dedup_window_secs: Some(3600),
+            };
+            let (form, token) = insert_form(&tenant.pool, tenant.id, None, &request, &fields).await.unwrap();
+            assert_eq!(form_for_token(&tenant.pool, &token).await.unwrap().map(|f| f.id), Some(form.id));
+
+            Self { tenant, entity_type_id, fields, form }
+        }
+
+        async fn submit(&self, submission: Value) -> LeadOutcome {
+            let data = lead_data(&self.form, submission.as_object().unwrap(), &self.fields, DEFAULT_PHONE_REGION).unwrap();
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            capture_lead(&mut conn, &self.form, self.entity_type_id, data).await.unwrap().0
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[test]
+    fn test_lead_route_coexists_with_provider_routes() {
+        // Panics on a conflicting route
+        let _ = crate::routes::webhooks::routes();
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_capture_creates_lead_contact() {
+        let fx = Fixture::new().await;
+        let outcome = fx
+            .submit(json!({"name": " Amal ", "email_address": "amal@example.com", "utm_source": "ads", "_honeypot": ""}))
+            .await;
+        let LeadOutcome::Created(id) = outcome else { panic!("expected a new contact, got {:?}", outcome) };
+
+        let data: Value = sqlx::query_scalar("SELECT data FROM entity_records WHERE id = $1")
+            .bind(id)
+            .fetch_one(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(data, json!({"first_name": "Amal", "email": "amal@example.com", "lifecycle_stage": "lead"}));
+
+        // The record_created event is queued for the workflows
+        let queued: Vec<String> = sqlx::query_scalar("SELECT event_type FROM event_outbox WHERE tenant_id = $1")
+            .bind(fx.tenant.id)
+            .fetch_all(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, vec!["create"]);
+
+        assert!(is_honeypot_filled(&fx.form, json!({"_honeypot": "http://spam"}).as_object().unwrap()));
+        assert!(lead_data(&fx.form, json!({"utm_source": "ads"}).as_object().unwrap(), &fx.fields, DEFAULT_PHONE_REGION).is_err());
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_duplicate_within_window_is_deduped() {
+        let fx = Fixture::new().await;
+        let LeadOutcome::Created(first) = fx.submit(json!({"name": "Omar", "email_address": "omar@example.com"})).await else {
+            panic!("first submission should create a contact");
+        };
+        let again = fx.submit(json!({"name": "Omar H", "email_address": "OMAR@example.com"})).await;
+        assert_eq!(again, LeadOutcome::Duplicate(first));
+
+        // Outside the window the same email is a new lead
+        sqlx::query("UPDATE entity_records SET created_at = NOW() - INTERVAL '2 hours' WHERE id = $1")
+            .bind(first)
+            .execute(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert!(matches!(
+            fx.submit(json!({"name": "Omar", "email_address": "omar@example.com"})).await,
+            LeadOutcome::Created(_)
+        ));
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM entity_records WHERE tenant_id = $1")
+            .bind(fx.tenant.id)
+            .fetch_one(&fx.tenant.pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        fx.cleanup().await;
+    }
+}
@@ -6,6 +6,8 @@ use axum::{
 };
 use core_models::{AppDef, EntityType, FieldDef, ViewDef};
 use serde::Deserialize;
+use sqlx::PgConnection;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -20,6 +22,7 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/entities", get(list_entities).post(create_entity))
         .route("/entities/:name", get(get_entity))
         .route("/entities/:name/fields", get(get_fields).post(create_field))
+        .route("/entities/:name/fields/reorder", post(reorder_fields))
         .route("/entities/:name/fields/:field_id", patch(update_field))
         .route("/entities/:name/fields/:field_id/options", post(add_field_option))
         .route("/entities/:name/fields/:field_id/options/:option_value", delete(delete_field_option))
@@ -74,6 +77,8 @@ pub struct CreateEntityRequest {
     pub label: String,
     pub icon: Option<String>,
     pub description: Option<String>,
+    /// Field that labels records in lookups
+    pub display_field: Option<String>,
 }
 
 async fn create_entity(
@@ -86,8 +91,8 @@ async fn create_entity(
 
     sqlx::query(
         r#"INSERT INTO entity_types 
-           (id, tenant_id, app_id, name, label, label_plural, icon, description, flags, soft_delete, created_at, updated_at)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, '{}', true, $9, $10)"#
+           (id, tenant_id, app_id, name, label, label_plural, icon, description, flags, soft_delete, display_field, created_at, updated_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, '{}', true, $9, $10, $11)"#
     )
     .bind(id)
     .bind(tenant.id)
@@ -97,6 +102,7 @@ async fn create_entity(
     .bind(format!("{}s", payload.label)) // Simple pluralization default
     .bind(&payload.icon)
     .bind(&payload.description)
+    .bind(&payload.display_field)
     .bind(now)
     .bind(now)
     .execute(&state.pool)
@@ -106,6 +112,8 @@ async fn create_entity(
         ApiError::Internal(e.to_string())
     })?;
 
+    record_schema_change(&state, tenant.id, &payload.name).await;
+
     Ok(Json(id))
 }
 
@@ -208,8 +216,7 @@ async fn create_field(
     .await
     .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-    // Invalidate cache
-    state.metadata.invalidate_fields(entity.id);
+    record_schema_change(&state, tenant.id, &entity.name).await;
 
     Ok(Json(id))
 }
@@ -286,13 +293,106 @@ async fn update_field(
     .await
     .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-    state.metadata.invalidate_fields(entity.id);
+    record_schema_change(&state, tenant.id, &entity.name).await;
 
     Ok(Json(serde_json::json!({"status": "updated"})))
 }
 
 
 
+#[derive(Debug, Deserialize)]
+pub struct ReorderFieldsRequest {
+    /// Every field of the entity, in the new order
+    pub field_ids: Vec<Uuid>,
+    /// Form section (`layout.section`) per field; unlisted fields keep
+    /// theirs and `null` clears it
+    #[serde(default)]
+    pub sections: HashMap<Uuid, Option<String>>,
+}
+
+/// Set `sort_order` of all fields at once, optionally moving fields between
+/// form sections
+async fn reorder_fields(
+    State(state): State<Arc<AppState>>,
+    Path(entity_name): Path<String>,
+    Extension(tenant): Extension<ResolvedTenant>,
+    Json(payload): Json<ReorderFieldsRequest>,
+) -> Result<Json<Vec<FieldDef>>, ApiError> {
+    let entity = state.metadata.get_entity_type(tenant.id, &entity_name).await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| ApiError::Internal(e.to_string()))?;
+    apply_field_order(&mut tx, tenant.id, entity.id, &payload).await?;
+    tx.commit().await.map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    record_schema_change(&state, tenant.id, &entity.name).await;
+
+    let fields = state.metadata.get_fields_by_entity_name(tenant.id, &entity.name).await?;
+    Ok(Json(fields))
+}
+
+/// Validate that `payload.field_ids` is a permutation of the entity's fields
+/// and write the new order and sections
+async fn apply_field_order(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    payload: &ReorderFieldsRequest,
+) -> Result<(), ApiError> {
+    let existing: HashSet<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM field_defs WHERE tenant_id = $1 AND entity_type_id = $2 FOR UPDATE",
+    )
+    .bind(tenant_id)
+    .bind(entity_type_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .into_iter()
+    .collect();
+
+    let mut seen = HashSet::new();
+    for id in &payload.field_ids {
+        if !existing.contains(id) {
+            return Err(ApiError::BadRequest(format!("Field {} does not belong to this entity", id)));
+        }
+        if !seen.insert(*id) {
+            return Err(ApiError::BadRequest(format!("Field {} is listed more than once", id)));
+        }
+    }
+    if seen.len() != existing.len() {
+        return Err(ApiError::BadRequest(format!(
+            "Expected all {} fields of the entity, got {}",
+            existing.len(),
+            seen.len()
+        )));
+    }
+    if let Some(id) = payload.sections.keys().find(|id| !existing.contains(id)) {
+        return Err(ApiError::BadRequest(format!("Field {} does not belong to this entity", id)));
+    }
+
+    let now = chrono::Utc::now();
+    for (position, id) in payload.field_ids.iter().enumerate() {
+        let section = payload.sections.get(id);
+        sqlx::query(
+            r#"UPDATE field_defs SET
+               sort_order = $1,
+               layout = CASE WHEN $2 THEN jsonb_set(COALESCE(layout, '{}'::jsonb), '{section}', $3) ELSE layout END,
+               updated_at = $4
+               WHERE id = $5 AND tenant_id = $6"#
+        )
+        .bind(position as i32 + 1)
+        .bind(section.is_some())
+        .bind(serde_json::json!(section.cloned().flatten()))
+        .bind(now)
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
 async fn get_views(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
@@ -435,15 +535,8 @@ async fn add_field_option(
         "Successfully added new option to field"
     );
     
-    // IMPORTANT: Invalidate the fields cache so the updated options appear on next fetch
-    // First get the entity type to get its ID for cache invalidation
-    if let Ok(entity) = state.metadata.get_entity_type(tenant.id, &entity_name).await {
-        state.metadata.invalidate_fields(entity.id);
-        tracing::debug!(
-            entity_type_id = %entity.id,
-            "Invalidated fields cache after adding option"
-        );
-    }
+    // IMPORTANT: Invalidates the fields cache so the updated options appear on next fetch
+    record_schema_change(&state, tenant.id, &entity_name).await;
     
     Ok(Json(serde_json::json!({
         "success": true,
@@ -509,17 +602,135 @@ async fn delete_field_option(
         "Successfully deleted option from field"
     );
     
-    // Invalidate the fields cache
-    if let Ok(entity) = state.metadata.get_entity_type(tenant.id, &entity_name).await {
-        state.metadata.invalidate_fields(entity.id);
-        tracing::debug!(
-            entity_type_id = %entity.id,
-            "Invalidated fields cache after deleting option"
-        );
-    }
+    record_schema_change(&state, tenant.id, &entity_name).await;
     
     Ok(Json(serde_json::json!({
         "success": true,
         "deleted_value": option_value
     })))
 }
+
+/// Invalidate cached metadata and snapshot a new schema version.
+/// The write has already committed, so a failed snapshot is only logged.
+async fn record_schema_change(state: &AppState, tenant_id: Uuid, entity_name: &str) {
+    if let Err(e) = state.metadata.schema_changed(tenant_id, entity_name).await {
+        tracing::warn!(
+            entity = %entity_name,
+            error = %e,
+            "Failed to record schema version"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::blueprint::{seed_from_blueprint, EntityBlueprint};
+    use test_support::TestTenant;
+
+    /// Tenant with a three-field `task_item` entity
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Metadata Test").await;
+            let blueprint = EntityBlueprint::from_json(
+                r#"{"entities": [{"name": "task_item", "label": "Task", "label_plural": "Tasks", "fields": [
+                    {"name": "title", "label": "Title", "field_type": "text"},
+                    {"name": "due", "label": "Due", "field_type": "date"},
+                    {"name": "notes", "label": "Notes", "field_type": "textarea"}
+                ]}]}"#,
+            )
+            .unwrap();
+            let mut tx = tenant.pool.begin().await.unwrap();
+            seed_from_blueprint(&mut tx, tenant.id, &blueprint).await.unwrap();
+            tx.commit().await.unwrap();
+
+            let entity_type_id = sqlx::query_scalar("SELECT id FROM entity_types WHERE tenant_id = $1")
+                .bind(tenant.id)
+                .fetch_one(&tenant.pool)
+                .await
+                .unwrap();
+            Self { tenant, entity_type_id }
+        }
+
+        async fn field_id(&self, name: &str) -> Uuid {
+            sqlx::query_scalar("SELECT id FROM field_defs WHERE entity_type_id = $1 AND name = $2")
+                .bind(self.entity_type_id)
+                .bind(name)
+                .fetch_one(&self.tenant.pool)
+                .await
+                .unwrap()
+        }
+
+        /// (name, sort_order, layout.section) in sort order
+        async fn fields(&self) -> Vec<(String, i32, Option<String>)> {
+            sqlx::query_as(
+                "SELECT name, sort_order, layout->>'section' FROM field_defs WHERE entity_type_id = $1 ORDER BY sort_order",
+            )
+            .bind(self.entity_type_id)
+            .fetch_all(&self.tenant.pool)
+            .await
+            .unwrap()
+        }
+
+        async fn reorder(&self, payload: &ReorderFieldsRequest) -> Result<(), ApiError> {
+            let mut tx = self.tenant.pool.begin().await.unwrap();
+            apply_field_order(&mut tx, self.tenant.id, self.entity_type_id, payload).await?;
+            tx.commit().await.unwrap();
+            Ok(())
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_reorder_fields_and_assign_sections() {
+        let fx = Fixture::new().await;
+        let (title, due, notes) = (fx.field_id("title").await, fx.field_id("due").await, fx.field_id("notes").await);
+
+        let payload = ReorderFieldsRequest {
+            field_ids: vec![notes, title, due],
+            sections: HashMap::from([(notes, Some("Details".to_string())), (title, None)]),
+        };
+        fx.reorder(&payload).await.unwrap();
+
+        assert_eq!(
+            fx.fields().await,
+            vec![
+                ("notes".to_string(), 1, Some("Details".to_string())),
+                ("title".to_string(), 2, None),
+                ("due".to_string(), 3, None),
+            ]
+        );
+
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_reorder_rejects_foreign_or_missing_fields() {
+        let fx = Fixture::new().await;
+        let (title, due, notes) = (fx.field_id("title").await, fx.field_id("due").await, fx.field_id("notes").await);
+        let before = fx.fields().await;
+
+        let rejected = [
+            vec![title, due, Uuid::new_v4()],
+            vec![title, due],
+            vec![title, due, notes, notes],
+        ];
+        for field_ids in rejected {
+            let payload = ReorderFieldsRequest { field_ids, sections: HashMap::new() };
+            assert!(matches!(fx.reorder(&payload).await, Err(ApiError::BadRequest(_))));
+        }
+        assert_eq!(fx.fields().await, before);
+
+        fx.cleanup().await;
+    }
+}
@@ -1,28 +1,42 @@
 //! API routes
 
+use axum::middleware::{from_fn, from_fn_with_state};
 use axum::Router;
 use std::sync::Arc;
 
 use crate::state::AppState;
+use crate::{gateway, middleware, observability};
 
 pub mod ai_chat;
 pub mod analytics;
+pub mod api_keys;
 pub mod associations;
 pub mod audit;
 pub mod auth;
+pub mod calendar;
 pub mod entities;
+pub mod export;
+pub mod geocode;
+pub mod import;
 pub mod inbox;
 pub mod integrations;
 pub mod interactions;
+pub mod lead_capture;
 pub mod metadata;
 pub mod properties;
+pub mod property_map;
 pub mod public;
 pub mod public_listing;
+pub mod roles;
 pub mod search;
+pub mod segments;
+pub mod semantic_search;
 pub mod tasks;
 pub mod tenant;
+pub mod uploads;
 pub mod views;
 pub mod voice;
+pub mod webhook_subscriptions;
 pub mod webhooks;
 pub mod workflow_graph;
 pub mod workflow_triggers;
@@ -41,24 +55,47 @@ pub fn api_routes() -> Router<Arc<AppState>> {
         .merge(search::routes())
         // Auth routes (public - login, register, logout, register-tenant)
         .nest("/auth", auth::routes())
+        // API key management (tenant admins)
+        .nest("/api-keys", api_keys::routes())
+        // Role and permission management
+        .nest("/roles", roles::routes())
+        .nest("/users", roles::user_routes())
         // Tenant settings routes
         .nest("/tenant", tenant::routes())
         // Metadata routes (authentication enforced via extractors in handlers)
         .nest("/metadata", metadata::routes())
         // Entity CRUD routes (authentication enforced via extractors in handlers)
         .merge(entities::routes())
+        // Entity export routes (CSV/XLSX downloads of a view)
+        .merge(export::routes())
+        // Semantic search over fields marked for embedding
+        .merge(semantic_search::routes())
+        // Entity import routes (CSV upload with column mapping and upserts)
+        .merge(import::routes())
+        // Geocoding routes (Location fields into lat/lng)
+        .merge(geocode::routes())
+        // Upload routes (files behind Image and Attachment fields)
+        .merge(uploads::routes())
         // Association routes (linking records together)
         .nest("/associations", associations::routes())
         // Interactions routes (timeline/activities)
         .nest("/interactions", interactions::routes())
         // Tasks routes (entity-linked tasks)
         .nest("/tasks", tasks::routes())
+        // Calendar routes (token-authenticated iCalendar feed)
+        .nest("/calendar", calendar::routes())
         // Views routes (saved user views)
         .nest("/views", views::routes())
+        // Segment routes (saved filters with live counts)
+        .nest("/segments", segments::routes())
+        // Lead capture form settings (public submissions go to /webhooks/lead)
+        .nest("/lead-forms", lead_capture::routes())
         // Audit trail routes
         .nest("/audit", audit::audit_routes())
         // Properties routes (Phase 3 - real estate)
         .merge(properties::router())
+        // Clustered property map data
+        .merge(property_map::routes())
         // Analytics routes (dashboard)
         .nest("/analytics", analytics::routes())
         // Inbox routes (unified messaging)
@@ -67,8 +104,156 @@ pub fn api_routes() -> Router<Arc<AppState>> {
         .nest("/workflows", workflow_graph::routes())
         // Integration settings routes
         .merge(integrations::routes())
+        // Outbound webhook subscriptions and their delivery logs
+        .nest("/webhook-subscriptions", webhook_subscriptions::routes())
         // Workflow trigger routes (webhook invocation)
         .merge(workflow_triggers::routes())
 }
 
+/// [`api_routes`] behind the middleware the server mounts them with at
+/// `/api/v1`; each layer runs before the ones listed above it
+pub fn api_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    api_routes()
+        .route_layer(from_fn_with_state(state.api_rate_limiter.clone(), gateway::rate_limit_middleware))
+        .layer(from_fn_with_state(state.clone(), middleware::idempotency::idempotency))
+        // Inside tenant resolution and auth, so its span names the tenant and user
+        .layer(from_fn(observability::tracing_config::request_context_middleware))
+        .layer(from_fn_with_state(state.clone(), middleware::auth::authenticate))
+        .layer(from_fn(middleware::tenant_status::enforce_tenant_status))
+        .layer(from_fn_with_state(state.clone(), middleware::tenant::resolve_tenant))
+        // Outermost, so requests refused by the layers above are counted too
+        .layer(from_fn_with_state(state.request_metrics.clone(), gateway::metrics_middleware))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{request, Request, StatusCode};
+    use test_support::TestTenant;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::gateway::RateLimiter;
+    use crate::observability::tracing_config::REQUEST_ID_HEADER;
+
+    fn app(state: Arc<AppState>) -> Router {
+        Router::new().nest("/api/v1", api_router(state.clone())).with_state(state)
+    }
+
+    /// A request to `uri` on `tenant`'s API
+    fn to(tenant: &TestTenant, method: &str, uri: &str) -> request::Builder {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Host", "localhost")
+            .header("X-Tenant-Slug", &tenant.subdomain)
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_api_requests_are_counted_by_route_template() {
+        let tenant = TestTenant::new("Router Metrics").await;
+        let state = Arc::new(AppState::new(tenant.pool.clone()));
+
+        let request = to(&tenant, "GET", &format!("/api/v1/views/{}", Uuid::new_v4())).body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let exported = state.request_metrics.export_prometheus();
+        assert!(
+            exported.contains(r#"http_route_requests_total{route="/api/v1/views/:id",status="404"} 1"#),
+            "{}",
+            exported
+        );
+
+        tenant.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_api_responses_carry_request_id() {
+        let tenant = TestTenant::new("Router Request Id").await;
+        let state = Arc::new(AppState::new(tenant.pool.clone()));
+        let uri = format!("/api/v1/views/{}", Uuid::new_v4());
+
+        let request = to(&tenant, "GET", &uri).header(REQUEST_ID_HEADER, "req-router-1").body(Body::empty()).unwrap();
+        let response = app(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-router-1");
 
+        let response = app(state).oneshot(to(&tenant, "GET", &uri).body(Body::empty()).unwrap()).await.unwrap();
+        let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(Uuid::parse_str(generated).is_ok());
+
+        tenant.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_job_enqueued_by_api_carries_request_id_as_trace_id() {
+        use crate::jobs::call_transcription::VOICE_QUEUE;
+
+        let tenant = TestTenant::new("Router Trace").await;
+        sqlx::query("UPDATE tenants SET settings = $2 WHERE id = $1")
+            .bind(tenant.id)
+            .bind(serde_json::json!({ "voice": { "recording_allowed": true } }))
+            .execute(&tenant.pool)
+            .await
+            .unwrap();
+        let user_id = tenant.user("agent@example.com").await;
+        let recording_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO call_recordings (tenant_id, call_sid, placed_by, entity_type, record_id, phone)
+             VALUES ($1, 'CA-router-trace', $2, 'contact', $3, '+971500000000') RETURNING id",
+        )
+        .bind(tenant.id)
+        .bind(user_id)
+        .bind(Uuid::new_v4())
+        .fetch_one(&tenant.pool)
+        .await
+        .unwrap();
+        let state = Arc::new(AppState::new(tenant.pool.clone()));
+
+        let request = to(&tenant, "POST", &format!("/api/v1/voice/webhook/recording/{}", recording_id))
+            .header(REQUEST_ID_HEADER, "req-router-trace")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(Body::from("CallSid=CA-router-trace&RecordingSid=RE1&RecordingUrl=https%3A%2F%2Fapi.twilio.com%2FRE1&RecordingStatus=completed"))
+            .unwrap();
+        let response = app(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let trace_ids: Vec<Option<String>> = sqlx::query_scalar(
+            "SELECT trace_id FROM jobs WHERE queue_name = $1 AND payload->>'recording_id' = $2",
+        )
+        .bind(VOICE_QUEUE)
+        .bind(recording_id.to_string())
+        .fetch_all(&tenant.pool)
+        .await
+        .unwrap();
+        assert_eq!(trace_ids, vec![Some("req-router-trace".to_string())]);
+
+        sqlx::query("DELETE FROM jobs WHERE queue_name = $1 AND payload->>'tenant_id' = $2")
+            .bind(VOICE_QUEUE)
+            .bind(tenant.id.to_string())
+            .execute(&tenant.pool)
+            .await
+            .unwrap();
+        tenant.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_api_requests_are_rate_limited() {
+        let tenant = TestTenant::new("Router Rate Limit").await;
+        let state = Arc::new(AppState {
+            api_rate_limiter: Arc::new(RateLimiter::new(1, 60)),
+            ..AppState::new(tenant.pool.clone())
+        });
+        let uri = format!("/api/v1/views/{}", Uuid::new_v4());
+        let send = || app(state.clone()).oneshot(to(&tenant, "GET", &uri).body(Body::empty()).unwrap());
+
+        assert_eq!(send().await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(send().await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+
+        tenant.cleanup().await;
+    }
+}
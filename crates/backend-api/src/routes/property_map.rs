@@ -0,0 +1,339 @@
+//! Property Map - clustered map data for the property map view
+//!
+//! `GET /properties/map?bbox=&zoom=` returns the properties inside the
+//! visible bounds. Zoomed out, nearby properties are merged into grid
+//! clusters that carry a count and their centroid, so the client draws a
+//! handful of markers instead of thousands; from `POINTS_MIN_ZOOM` in, every
+//! property comes back as its own point.
+//!
+//! Coordinates are read from the fields the map view's settings name, and
+//! the view's filters apply as they do to the list and the export.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use core_models::ViewType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::middleware::database::RlsConn;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::routes::export::RecordSelect;
+use crate::state::AppState;
+
+/// Zoom level from which properties are returned individually
+pub const POINTS_MIN_ZOOM: u8 = 15;
+
+/// Grid cells per 256px map tile edge; 4 makes cells about 64px wide
+const CELLS_PER_TILE: f64 = 4.0;
+
+/// Individual points returned at most; more sets `truncated`
+pub const MAX_MAP_POINTS: i64 = 2_000;
+
+/// A JSON value that casts cleanly to `float8`
+const NUMERIC_PATTERN: &str = r"^\s*-?[0-9]+(\.[0-9]+)?\s*$";
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/properties/map", get(property_map))
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct MapQuery {
+    /// `west,south,east,north` in degrees, as Leaflet's `toBBoxString()`
+    pub bbox: String,
+    pub zoom: u8,
+    /// View whose filters and coordinate fields apply; defaults to the
+    /// property map view
+    pub view_id: Option<Uuid>,
+}
+
+/// Visible bounds; `west > east` when they cross the antimeridian
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBox {
+    pub west: f64,
+    pub south: f64,
+    pub east: f64,
+    pub north: f64,
+}
+
+impl BBox {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let parts: Vec<f64> = raw
+            .split(',')
+            .map(|p| p.trim().parse::<f64>().map_err(|_| format!("Invalid bbox coordinate '{}'", p.trim())))
+            .collect::<Result<_, _>>()?;
+        let [west, south, east, north] = parts[..] else {
+            return Err("bbox must be west,south,east,north".to_string());
+        };
+        if !(-90.0..=90.0).contains(&south) || !(-90.0..=90.0).contains(&north) || south > north {
+            return Err("bbox latitudes must be within -90..90 with south <= north".to_string());
+        }
+        if !(-180.0..=180.0).contains(&west) || !(-180.0..=180.0).contains(&east) {
+            return Err("bbox longitudes must be within -180..180".to_string());
+        }
+        Ok(BBox { west, south, east, north })
+    }
+}
+
+/// Several properties drawn as one marker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MapCluster {
+    pub lat: f64,
+    pub lng: f64,
+    pub count: i64,
+}
+
+/// A single property
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MapPoint {
+    pub id: Uuid,
+    pub lat: f64,
+    pub lng: f64,
+    pub data: Value,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MapData {
+    pub clusters: Vec<MapCluster>,
+    pub points: Vec<MapPoint>,
+    /// More than `MAX_MAP_POINTS` properties matched; zoom in to see the rest
+    pub truncated: bool,
+}
+
+/// Grid cell edge in degrees at `zoom`, or `None` when points aren't clustered
+pub fn cell_size(zoom: u8) -> Option<f64> {
+    (zoom < POINTS_MIN_ZOOM).then(|| 360.0 / 2f64.powi(zoom as i32) / CELLS_PER_TILE)
+}
+
+// ============================================================================
+// Routes
+// ============================================================================
+
+/// GET /properties/map?bbox=west,south,east,north&zoom=&view_id=
+async fn property_map(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MapQuery>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+) -> Response {
+    let bbox = match BBox::parse(&query.bbox) {
+        Ok(b) => b,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let entity_type = match state.metadata.get_entity_type(tenant.id, "property").await {
+        Ok(e) => e,
+        Err(_) => return (StatusCode::NOT_FOUND, "Entity type 'property' not found").into_response(),
+    };
+
+    let views = state.metadata.get_views(tenant.id, entity_type.id).await.unwrap_or_default();
+    let view = match query.view_id {
+        Some(view_id) => match views.into_iter().find(|v| v.id == view_id) {
+            Some(view) => Some(view),
+            None => return (StatusCode::NOT_FOUND, "View not found").into_response(),
+        },
+        None => views.into_iter().find(|v| v.view_type == ViewType::Map),
+    };
+
+    let setting = |key: &str, default: &'static str| {
+        view.as_ref()
+            .and_then(|v| v.settings.get(key))
+            .and_then(Value::as_str)
+            .unwrap_or(default)
+            .to_string()
+    };
+    let (lat_field, lng_field) = (setting("lat_field", "lat"), setting("lng_field", "lng"));
+    let select = RecordSelect::new(view.as_ref().map(|v| v.filters.as_slice()).unwrap_or_default(), &[]);
+
+    match load_map(&mut conn, tenant.id, entity_type.id, &select, (&lat_field, &lng_field), bbox, query.zoom).await {
+        Ok(data) => Json(data).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ============================================================================
+// Query
+// ============================================================================
+
+/// Clusters or points for the records `select` matches inside `bbox`;
+/// `coords` names the latitude and longitude fields
+pub(crate) async fn load_map(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    select: &RecordSelect,
+    coords: (&str, &str),
+    bbox: BBox,
+    zoom: u8,
+) -> Result<MapData, sqlx::Error> {
+    let p = select.next_param();
+    let coordinate = |param: usize| {
+        format!(
+            "CASE WHEN data ->> ${param}::text ~ '{NUMERIC_PATTERN}' THEN (data ->> ${param}::text)::float8 END"
+        )
+    };
+    let (west, south, east, north) = (p + 2, p + 3, p + 4, p + 5);
+    let in_bounds = format!(
+        "WITH located AS (
+            SELECT id, data, {lat} AS lat, {lng} AS lng FROM entity_records
+            WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL{conditions}
+        ), in_bounds AS (
+            SELECT * FROM located
+            WHERE lat BETWEEN ${south} AND ${north}
+              AND CASE WHEN ${west} <= ${east} THEN lng BETWEEN ${west} AND ${east}
+                       ELSE lng >= ${west} OR lng <= ${east} END
+        )",
+        lat = coordinate(p),
+        lng = coordinate(p + 1),
+        conditions = select.conditions,
+    );
+
+    let cell = cell_size(zoom);
+    let sql = match cell {
+        Some(_) => format!(
+            "{in_bounds}
+             SELECT COUNT(*) AS count, AVG(lat) AS lat, AVG(lng) AS lng,
+                    CASE WHEN COUNT(*) = 1 THEN (array_agg(id))[1] END AS id,
+                    CASE WHEN COUNT(*) = 1 THEN (array_agg(data))[1] END AS data
+             FROM in_bounds
+             GROUP BY floor(lat / ${c}), floor(lng / ${c})",
+            c = p + 6
+        ),
+        None => format!("{in_bounds} SELECT 1::int8 AS count, lat, lng, id, data FROM in_bounds ORDER BY id LIMIT ${}", p + 6),
+    };
+
+    let query = select
+        .query(&sql, tenant_id, entity_type_id)
+        .bind(coords.0)
+        .bind(coords.1)
+        .bind(bbox.west)
+        .bind(bbox.south)
+        .bind(bbox.east)
+        .bind(bbox.north);
+    // One row past the limit tells a full page from a truncated one
+    let query = match cell {
+        Some(cell) => query.bind(cell),
+        None => query.bind(MAX_MAP_POINTS + 1),
+    };
+    let rows = query.fetch_all(conn).await?;
+
+    let mut map = MapData::default();
+    for row in rows {
+        let (lat, lng): (f64, f64) = (row.try_get("lat")?, row.try_get("lng")?);
+        match row.try_get::<Option<Uuid>, _>("id")? {
+            Some(id) => map.points.push(MapPoint { id, lat, lng, data: row.try_get("data")? }),
+            None => map.clusters.push(MapCluster { lat, lng, count: row.try_get("count")? }),
+        }
+    }
+    if map.points.len() as i64 > MAX_MAP_POINTS {
+        map.points.truncate(MAX_MAP_POINTS as usize);
+        map.truncated = true;
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::{FilterOperator, ViewFilter};
+    use serde_json::json;
+    use test_support::get_test_pool;
+
+    const DUBAI: BBox = BBox { west: 55.0, south: 25.0, east: 55.5, north: 25.5 };
+
+    #[test]
+    fn test_bbox_and_cell_size() {
+        assert_eq!(BBox::parse("55.0, 25.0,55.5,25.5"), Ok(DUBAI));
+        assert!(BBox::parse("55,25,55.5").is_err());
+        assert!(BBox::parse("55,26,55.5,25").is_err());
+        assert!(BBox::parse("a,25,55.5,25.5").is_err());
+
+        assert_eq!(cell_size(0), Some(90.0));
+        assert!(cell_size(POINTS_MIN_ZOOM - 1).is_some());
+        assert_eq!(cell_size(POINTS_MIN_ZOOM), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_dense_bbox_clusters_when_zoomed_out() {
+        let pool = get_test_pool().await;
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Map Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("map-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural)
+             VALUES ($1, $2, 'properties', 'property', 'Property', 'Properties')",
+        )
+        .bind(entity_type_id)
+        .bind(tenant_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // 40 active listings a few hundred metres apart, coordinates as
+        // numbers and as strings
+        let mut records: Vec<Value> = (0..40)
+            .map(|i| {
+                let (lat, lng) = (25.20 + (i % 8) as f64 * 0.002, 55.27 + (i / 8) as f64 * 0.002);
+                if i % 2 == 0 {
+                    json!({ "title": format!("Unit {}", i), "status": "active", "lat": lat, "lng": lng })
+                } else {
+                    json!({ "title": format!("Unit {}", i), "status": "active", "lat": lat.to_string(), "lng": lng.to_string() })
+                }
+            })
+            .collect();
+        // Filtered out by the view, outside the bounds, and not located
+        records.push(json!({ "title": "Sold", "status": "sold", "lat": 25.21, "lng": 55.28 }));
+        records.push(json!({ "title": "Abu Dhabi", "status": "active", "lat": 24.45, "lng": 54.38 }));
+        records.push(json!({ "title": "No address", "status": "active", "lat": "", "lng": null }));
+        for data in records {
+            sqlx::query("INSERT INTO entity_records (tenant_id, entity_type_id, data) VALUES ($1, $2, $3)")
+                .bind(tenant_id)
+                .bind(entity_type_id)
+                .bind(data)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let filters = vec![ViewFilter { field: "status".into(), operator: FilterOperator::Equals, value: json!("active") }];
+        let select = RecordSelect::new(&filters, &[]);
+        let mut conn = pool.acquire().await.unwrap();
+
+        let zoomed_out = load_map(&mut conn, tenant_id, entity_type_id, &select, ("lat", "lng"), DUBAI, 9).await.unwrap();
+        assert!(zoomed_out.points.is_empty());
+        assert!(!zoomed_out.clusters.is_empty() && zoomed_out.clusters.len() < 40);
+        assert_eq!(zoomed_out.clusters.iter().map(|c| c.count).sum::<i64>(), 40);
+        for cluster in &zoomed_out.clusters {
+            assert!((25.20..=25.22).contains(&cluster.lat) && (55.27..=55.28).contains(&cluster.lng));
+        }
+
+        let zoomed_in = load_map(&mut conn, tenant_id, entity_type_id, &select, ("lat", "lng"), DUBAI, 16).await.unwrap();
+        assert!(zoomed_in.clusters.is_empty());
+        assert_eq!(zoomed_in.points.len(), 40);
+        assert!(!zoomed_in.truncated);
+        assert!(zoomed_in.points.iter().all(|p| p.data["status"] == json!("active")));
+
+        for sql in [
+            "DELETE FROM entity_records WHERE tenant_id = $1",
+            "DELETE FROM entity_types WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
@@ -5,14 +5,17 @@
 
 use axum::{
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
+use core_metadata::cache::{CachePolicy, LoadingCache};
+use core_metadata::MetadataError;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgPool};
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::middleware::tenant::{ResolvedTenant, TenantBranding};
@@ -41,7 +44,7 @@ pub struct PublicTenantResponse {
 }
 
 /// Public property listing (safe subset of data)
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct PublicListing {
     pub id: Uuid,
     pub reference: Option<String>,
@@ -92,18 +95,34 @@ pub struct PublicListingDetail {
 }
 
 /// Listing query parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct ListingQuery {
     pub min_price: Option<f64>,
     pub max_price: Option<f64>,
     pub bedrooms: Option<i32>,
     pub bathrooms: Option<i32>,
+    #[serde(alias = "type")]
     pub property_type: Option<String>,
     pub city: Option<String>,
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
 
+impl ListingQuery {
+    /// Clamp paging and drop blank filters, so equivalent searches share a
+    /// cache entry
+    pub fn normalized(self) -> Self {
+        let text = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Self {
+            property_type: text(self.property_type),
+            city: text(self.city).map(|c| c.to_lowercase()),
+            page: Some(self.page.unwrap_or(1).max(1)),
+            per_page: Some(self.per_page.unwrap_or(20).clamp(1, 100)),
+            ..self
+        }
+    }
+}
+
 /// Inquiry submission request
 #[derive(Debug, Deserialize)]
 pub struct InquiryRequest {
@@ -124,7 +143,7 @@ pub struct InquiryResponse {
 }
 
 /// Paginated listings response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ListingsResponse {
     pub data: Vec<PublicListing>,
     pub total: i64,
@@ -149,26 +168,53 @@ async fn get_tenant_info(
 }
 
 /// GET /public/listings - Get published property listings
+///
+/// Only active properties flagged `published` are listed. Results are cached
+/// per tenant and search, and may be served up to a minute stale.
 async fn get_listings(
     State(state): State<Arc<AppState>>,
     Extension(tenant): Extension<ResolvedTenant>,
     Query(params): Query<ListingQuery>,
 ) -> impl IntoResponse {
-    let page = params.page.unwrap_or(1).max(1);
-    let per_page = params.per_page.unwrap_or(20).min(100);
-    let offset = (page - 1) * per_page;
-    
-    // Build query to extract fields from JSONB data
-    // Note: We use status='active' as valid public property
-    let mut conditions = vec![
-        "er.tenant_id = $1", 
-        "et.name = 'property'",
-        "(er.data->>'status') = 'active'",
-        // Optional: check deleted_at if column exists, usually handled by policy but we are explicit
-    ];
-    let mut param_idx = 2;
-    
-    // Build base query
+    match state.public_listings.search(&state.pool, tenant.id, params.normalized()).await {
+        Ok(listings) => ([(header::CACHE_CONTROL, "public, max-age=60")], Json(listings)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to fetch listings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to fetch listings"}))
+            ).into_response()
+        }
+    }
+}
+
+/// Numeric value of a JSONB key, NULL when it isn't a number
+fn numeric(key: &str) -> String {
+    format!("CASE WHEN jsonb_typeof(er.data->'{key}') = 'number' THEN (er.data->>'{key}')::float8 END")
+}
+
+/// One page of the tenant's public listings matching `params`, newest first
+async fn search_listings(pool: &PgPool, tenant_id: Uuid, params: &ListingQuery) -> Result<ListingsResponse, sqlx::Error> {
+    let (page, per_page) = (params.page.unwrap_or(1), params.per_page.unwrap_or(20));
+
+    // Every optional filter is bound; NULL disables it
+    let conditions = format!(
+        "er.tenant_id = $1
+         AND et.name = 'property'
+         AND er.deleted_at IS NULL
+         AND er.data->>'status' = 'active'
+         AND er.data->'published' = 'true'::jsonb
+         AND ($2::float8 IS NULL OR {price} >= $2)
+         AND ($3::float8 IS NULL OR {price} <= $3)
+         AND ($4::int IS NULL OR {bedrooms} = $4)
+         AND ($5::int IS NULL OR {bathrooms} = $5)
+         AND ($6::text IS NULL OR er.data->>'property_type' = $6)
+         AND ($7::text IS NULL OR lower(er.data->>'city') = $7)",
+        price = numeric("price"),
+        bedrooms = numeric("bedrooms"),
+        bathrooms = numeric("bathrooms"),
+    );
+
     let base_query = format!(
         r#"
         SELECT 
@@ -180,62 +226,97 @@ async fn get_listings(
             er.data->>'status' as status,
             er.data->>'city' as city,
             er.data->>'region' as area, -- 'area' mapped to 'region' in some migrations, or just 'area'
-            CAST(er.data->>'bedrooms' AS INTEGER) as bedrooms,
-            CAST(er.data->>'bathrooms' AS INTEGER) as bathrooms,
-            CAST(er.data->>'size_sqm' AS FLOAT8) as size_sqm,
-            CAST(er.data->>'price' AS FLOAT8) as price, 
-            CAST(er.data->>'rent_amount' AS FLOAT8) as rent_amount, 
+            CAST({bedrooms} AS INTEGER) as bedrooms,
+            CAST({bathrooms} AS INTEGER) as bathrooms,
+            {size} as size_sqm,
+            {price} as price, 
+            {rent} as rent_amount, 
             er.data->>'currency' as currency,
             er.data->'images' as photos, -- mapped from 'images' in migration
             er.created_at as listed_at
         FROM entity_records er
         JOIN entity_types et ON er.entity_type_id = et.id
-        WHERE {}
-        ORDER BY er.created_at DESC
-        LIMIT {} OFFSET {}
+        WHERE {conditions}
+        ORDER BY er.created_at DESC, er.id DESC
+        LIMIT $8 OFFSET $9
         "#,
-        conditions.join(" AND "),
-        per_page,
-        offset
+        bedrooms = numeric("bedrooms"),
+        bathrooms = numeric("bathrooms"),
+        size = numeric("size_sqm"),
+        price = numeric("price"),
+        rent = numeric("rent_amount"),
     );
-    
     let count_query = format!(
         "SELECT COUNT(*) FROM entity_records er JOIN entity_types et ON er.entity_type_id = et.id WHERE {}",
-        conditions.join(" AND ")
+        conditions
     );
-    
-    // Execute queries
-    let listings: Vec<PublicListing> = match sqlx::query_as(&base_query)
-        .bind(tenant.id)
-        .fetch_all(&state.pool)
-        .await
-    {
-        Ok(rows) => rows,
-        Err(e) => {
-            tracing::error!("Failed to fetch listings: {}", e);
-            // Fallback: Return empty for now to avoid 500 while schemas stabilize
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Failed to fetch listings: {}", e)}))
-            ).into_response();
-        }
-    };
-    
-    let total: i64 = match sqlx::query_scalar(&count_query)
-        .bind(tenant.id)
-        .fetch_one(&state.pool)
-        .await
-    {
-        Ok(count) => count,
-        Err(_) => 0,
-    };
-    
-    Json(ListingsResponse {
-        data: listings,
-        total,
-        page,
-        per_page,
-    }).into_response()
+
+    let data: Vec<PublicListing> = sqlx::query_as(&base_query)
+        .bind(tenant_id)
+        .bind(params.min_price)
+        .bind(params.max_price)
+        .bind(params.bedrooms)
+        .bind(params.bathrooms)
+        .bind(&params.property_type)
+        .bind(&params.city)
+        .bind(per_page as i64)
+        .bind((page - 1) as i64 * per_page as i64)
+        .fetch_all(pool)
+        .await?;
+    let total: i64 = sqlx::query_scalar(&count_query)
+        .bind(tenant_id)
+        .bind(params.min_price)
+        .bind(params.max_price)
+        .bind(params.bedrooms)
+        .bind(params.bathrooms)
+        .bind(&params.property_type)
+        .bind(&params.city)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(ListingsResponse { data, total, page, per_page })
+}
+
+/// Public listing searches, keyed by tenant and normalized query
+///
+/// Listings change rarely compared to how often the public site is browsed,
+/// so entries are served for a minute and refreshed in the background for up
+/// to fifteen.
+#[derive(Clone)]
+pub struct PublicListingCache {
+    cache: Arc<LoadingCache<(Uuid, String), Arc<ListingsResponse>>>,
+}
+
+impl PublicListingCache {
+    pub fn new() -> Self {
+        Self::with_policy(CachePolicy { soft_ttl: Duration::from_secs(60), hard_ttl: Duration::from_secs(15 * 60) })
+    }
+
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self { cache: Arc::new(LoadingCache::new(policy)) }
+    }
+
+    /// `params` should already be [`ListingQuery::normalized`]
+    pub async fn search(&self, pool: &PgPool, tenant_id: Uuid, params: ListingQuery) -> Result<Arc<ListingsResponse>, sqlx::Error> {
+        let (pool, params) = (pool.clone(), Arc::new(params));
+        let key = (tenant_id, format!("{:?}", params));
+        self.cache
+            .get_or_load(key, move || {
+                let (pool, params) = (pool.clone(), params.clone());
+                async move { Ok(Arc::new(search_listings(&pool, tenant_id, &params).await?)) }
+            })
+            .await
+            .map_err(|e| match e {
+                MetadataError::Database(e) => e,
+                other => sqlx::Error::Protocol(other.to_string()),
+            })
+    }
+}
+
+impl Default for PublicListingCache {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// GET /public/listings/:id - Get single property detail
@@ -471,3 +552,98 @@ async fn submit_inquiry(
         contact_id: Some(contact_id),
     }).into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_support::TestTenant;
+
+    /// Tenant with a `property` entity type; removed again by `cleanup`
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Public Listings Test").await;
+            let entity_type_id = tenant.entity_type("real_estate", "property", "Property", "Properties").await;
+            Self { tenant, entity_type_id }
+        }
+
+        /// An active, published property; `extra` overrides or adds keys
+        async fn property(&self, title: &str, extra: serde_json::Value) {
+            let mut data = json!({
+                "title": title, "status": "active", "published": true, "property_type": "apartment",
+                "city": "Dubai", "price": 1000000, "bedrooms": 2,
+            });
+            data.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            sqlx::query("INSERT INTO entity_records (tenant_id, entity_type_id, data) VALUES ($1, $2, $3)")
+                .bind(self.tenant.id)
+                .bind(self.entity_type_id)
+                .bind(data)
+                .execute(&self.tenant.pool)
+                .await
+                .unwrap();
+        }
+
+        async fn titles(&self, params: ListingQuery) -> (Vec<String>, i64) {
+            let page = search_listings(&self.tenant.pool, self.tenant.id, &params.normalized()).await.unwrap();
+            let mut titles: Vec<String> = page.data.into_iter().map(|l| l.title).collect();
+            titles.sort();
+            (titles, page.total)
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_listing_filters() {
+        let fx = Fixture::new().await;
+        fx.property("Marina Flat", json!({})).await;
+        fx.property("Palm Villa", json!({"property_type": "house", "price": 5000000, "bedrooms": 5})).await;
+        fx.property("Abu Dhabi Studio", json!({"city": "Abu Dhabi", "price": 400000, "bedrooms": 0})).await;
+        fx.property("Unpriced Loft", json!({"price": "on request"})).await;
+
+        let q: ListingQuery = serde_json::from_value(json!({"type": "house"})).unwrap();
+        assert_eq!(fx.titles(q).await.0, vec!["Palm Villa"]);
+        let q = ListingQuery { min_price: Some(500000.0), max_price: Some(2000000.0), ..Default::default() };
+        assert_eq!(fx.titles(q).await.0, vec!["Marina Flat"]);
+        let q = ListingQuery { bedrooms: Some(2), city: Some(" dubai ".to_string()), ..Default::default() };
+        assert_eq!(fx.titles(q).await.0, vec!["Marina Flat", "Unpriced Loft"]);
+
+        // Total counts every match, not just the page
+        let (page, total) = fx.titles(ListingQuery { per_page: Some(3), page: Some(2), ..Default::default() }).await;
+        assert_eq!((page.len(), total), (1, 4));
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_only_active_published_listings_without_internal_fields() {
+        let fx = Fixture::new().await;
+        fx.property("Listed", json!({"internal_notes": "Owner will take 10% less", "commission_percent": 2})).await;
+        fx.property("Sold", json!({"status": "sold"})).await;
+        fx.property("Draft", json!({"published": false})).await;
+        fx.property("Never Published", json!({"published": null})).await;
+
+        let cache = PublicListingCache::new();
+        let page = cache.search(&fx.tenant.pool, fx.tenant.id, ListingQuery::default().normalized()).await.unwrap();
+        assert_eq!(page.data.iter().map(|l| l.title.as_str()).collect::<Vec<_>>(), vec!["Listed"]);
+        let body = serde_json::to_value(&*page).unwrap();
+        let listing = body["data"][0].as_object().unwrap();
+        assert!(!listing.contains_key("internal_notes") && !listing.contains_key("commission_percent"));
+
+        // Cached per tenant and query until the entry goes stale
+        fx.property("Fresh", json!({})).await;
+        let cached = cache.search(&fx.tenant.pool, fx.tenant.id, ListingQuery::default().normalized()).await.unwrap();
+        assert_eq!(cached.total, 1);
+        let other_query = ListingQuery { city: Some("Dubai".to_string()), ..Default::default() }.normalized();
+        assert_eq!(cache.search(&fx.tenant.pool, fx.tenant.id, other_query).await.unwrap().total, 2);
+        fx.cleanup().await;
+    }
+}
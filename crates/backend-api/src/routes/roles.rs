@@ -0,0 +1,589 @@
+//! Role management routes
+//!
+//! Built-in roles are the personas in `users.role`; a user holds exactly
+//! one. Tenants can add custom roles carrying `PermissionDef`s, and a user
+//! may hold any number of those. Role checks see custom roles as
+//! `custom:<name>`, so a custom role never passes a check for a built-in
+//! privilege whatever it's called. Every change to who holds what drops the
+//! affected entries from the permission cache. A tenant always keeps at
+//! least one active admin. Admins can also lift a user's login lockout.
+
+use axum::{
+    Router,
+    routing::{get, post, put, delete},
+    extract::{State, Path},
+    http::StatusCode,
+    Json,
+};
+use core_models::UserRole;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::error::ApiError;
+use crate::middleware::database::RlsConn;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::middleware::permission::{effective_permissions, is_admin, AuthenticatedUser, PermissionDef};
+
+/// `/roles`
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_roles).post(create_role))
+        .route("/:role", delete(delete_role))
+}
+
+/// `/users`
+pub fn user_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/:user_id/permissions", get(user_permissions))
+        .route("/:user_id/roles/:role", put(assign_role).delete(unassign_role))
+        .route("/:user_id/unlock", post(unlock_user))
+}
+
+fn signed_in(user: Option<axum::Extension<AuthenticatedUser>>) -> Result<AuthenticatedUser, ApiError> {
+    user.map(|axum::Extension(user)| user).ok_or(ApiError::Unauthorized)
+}
+
+fn require_admin(user: Option<axum::Extension<AuthenticatedUser>>) -> Result<AuthenticatedUser, ApiError> {
+    let user = signed_in(user)?;
+    if is_admin(&user) {
+        Ok(user)
+    } else {
+        Err(ApiError::Forbidden)
+    }
+}
+
+fn built_in(name: &str) -> Option<UserRole> {
+    UserRole::ALL.into_iter().find(|role| role.as_str() == name)
+}
+
+/// Lowercase letters, digits and underscores, starting with a letter
+fn is_valid_role_name(name: &str) -> bool {
+    (2..=64).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleResponse {
+    /// `None` for built-in roles
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub label: String,
+    pub description: Option<String>,
+    pub built_in: bool,
+    pub permissions: Vec<PermissionDef>,
+    pub members: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<PermissionDef>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPermissions {
+    pub user_id: Uuid,
+    /// Built-in role first, then custom roles
+    pub roles: Vec<String>,
+    pub permissions: HashMap<String, bool>,
+}
+
+async fn list_roles(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+) -> Result<Json<Vec<RoleResponse>>, ApiError> {
+    signed_in(user)?;
+
+    let counts: HashMap<String, i64> = sqlx::query_as(
+        "SELECT role, COUNT(*) FROM users WHERE tenant_id = $1 AND status = 'active' GROUP BY role",
+    )
+    .bind(tenant.id)
+    .fetch_all(&mut **conn)
+    .await?
+    .into_iter()
+    .collect();
+    let mut roles: Vec<RoleResponse> = UserRole::ALL
+        .iter()
+        .map(|role| {
+            let name = role.as_str();
+            let mut label = name.to_string();
+            label[..1].make_ascii_uppercase();
+            RoleResponse {
+                id: None,
+                name: name.to_string(),
+                label,
+                description: None,
+                built_in: true,
+                permissions: Vec::new(),
+                members: counts.get(name).copied().unwrap_or(0),
+            }
+        })
+        .collect();
+
+    let custom = sqlx::query(
+        r#"
+        SELECT r.id, r.name, r.label, r.description, r.permissions,
+               (SELECT COUNT(*) FROM user_roles ur WHERE ur.role_id = r.id) AS members
+        FROM roles r
+        WHERE r.tenant_id = $1
+        ORDER BY r.name
+        "#,
+    )
+    .bind(tenant.id)
+    .fetch_all(&mut **conn)
+    .await?;
+    for row in &custom {
+        let permissions: sqlx::types::Json<Vec<PermissionDef>> = row.try_get("permissions")?;
+        roles.push(RoleResponse {
+            id: Some(row.try_get("id")?),
+            name: row.try_get("name")?,
+            label: row.try_get("label")?,
+            description: row.try_get("description")?,
+            built_in: false,
+            permissions: permissions.0,
+            members: row.try_get("members")?,
+        });
+    }
+
+    Ok(Json(roles))
+}
+
+async fn create_role(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Json(req): Json<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<RoleResponse>), ApiError> {
+    let user = require_admin(user)?;
+    if !is_valid_role_name(&req.name) {
+        return Err(ApiError::BadRequest(
+            "Role name must be 2-64 lowercase letters, digits or underscores, starting with a letter".to_string(),
+        ));
+    }
+    if built_in(&req.name).is_some() {
+        return Err(ApiError::Conflict(format!("'{}' is a built-in role", req.name)));
+    }
+
+    let label = req.label.filter(|l| !l.trim().is_empty()).unwrap_or_else(|| req.name.clone());
+    let id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        INSERT INTO roles (tenant_id, name, label, description, permissions, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (tenant_id, name) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(tenant.id)
+    .bind(&req.name)
+    .bind(&label)
+    .bind(&req.description)
+    .bind(sqlx::types::Json(&req.permissions))
+    .bind(user.id)
+    .fetch_optional(&mut **conn)
+    .await?;
+    let id = id.ok_or_else(|| ApiError::Conflict(format!("Role '{}' already exists", req.name)))?;
+
+    Ok((StatusCode::CREATED, Json(RoleResponse {
+        id: Some(id),
+        name: req.name,
+        label,
+        description: req.description,
+        built_in: false,
+        permissions: req.permissions,
+        members: 0,
+    })))
+}
+
+async fn delete_role(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(role): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(user)?;
+    if built_in(&role).is_some() {
+        return Err(ApiError::BadRequest("Built-in roles can't be deleted".to_string()));
+    }
+    let deleted = sqlx::query("DELETE FROM roles WHERE tenant_id = $1 AND name = $2")
+        .bind(tenant.id)
+        .bind(&role)
+        .execute(&mut **conn)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Role '{}' not found", role)));
+    }
+    state.permission_cache.invalidate_tenant(tenant.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The user's built-in role, locked for the rest of the transaction
+async fn current_role(conn: &mut PgConnection, tenant_id: Uuid, user_id: Uuid) -> Result<String, ApiError> {
+    sqlx::query_scalar("SELECT role FROM users WHERE tenant_id = $1 AND id = $2 FOR UPDATE")
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))
+}
+
+/// Refuse to take the admin role away from the tenant's only active admin
+async fn ensure_not_last_admin(conn: &mut PgConnection, tenant_id: Uuid, user_id: Uuid) -> Result<(), ApiError> {
+    let admins: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM users WHERE tenant_id = $1 AND role = 'admin' AND status = 'active' FOR UPDATE",
+    )
+    .bind(tenant_id)
+    .fetch_all(conn)
+    .await?;
+    if admins.iter().all(|id| *id == user_id) {
+        return Err(ApiError::Conflict("A tenant must keep at least one admin".to_string()));
+    }
+    Ok(())
+}
+
+/// Give `user_id` its built-in role, checking the last-admin guard
+async fn set_built_in_role(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    role: UserRole,
+) -> Result<(), ApiError> {
+    let mut tx = sqlx::Connection::begin(conn).await?;
+    let current = current_role(&mut tx, tenant_id, user_id).await?;
+    if current == UserRole::Admin.as_str() && role != UserRole::Admin {
+        ensure_not_last_admin(&mut tx, tenant_id, user_id).await?;
+    }
+    sqlx::query("UPDATE users SET role = $3, updated_at = NOW() WHERE tenant_id = $1 AND id = $2")
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(role.as_str())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn custom_role_id(conn: &mut PgConnection, tenant_id: Uuid, role: &str) -> Result<Uuid, ApiError> {
+    sqlx::query_scalar("SELECT id FROM roles WHERE tenant_id = $1 AND name = $2")
+        .bind(tenant_id)
+        .bind(role)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Role '{}' not found", role)))
+}
+
+/// Assign a role; a built-in role replaces the user's current one
+async fn assign_role(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path((user_id, role)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ApiError> {
+    let admin = require_admin(user)?;
+
+    if let Some(role) = built_in(&role) {
+        set_built_in_role(&mut conn, tenant.id, user_id, role).await?;
+    } else {
+        let role_id = custom_role_id(&mut conn, tenant.id, &role).await?;
+        current_role(&mut conn, tenant.id, user_id).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO user_roles (tenant_id, user_id, role_id, assigned_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, role_id) DO NOTHING
+            "#,
+        )
+        .bind(tenant.id)
+        .bind(user_id)
+        .bind(role_id)
+        .bind(admin.id)
+        .execute(&mut **conn)
+        .await?;
+    }
+
+    state.permission_cache.invalidate_user(tenant.id, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unassign a role; losing a built-in role makes the user a member
+async fn unassign_role(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path((user_id, role)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(user)?;
+
+    if let Some(role) = built_in(&role) {
+        if current_role(&mut conn, tenant.id, user_id).await? != role.as_str() {
+            return Err(ApiError::NotFound(format!("User doesn't have the '{}' role", role.as_str())));
+        }
+        set_built_in_role(&mut conn, tenant.id, user_id, UserRole::Member).await?;
+    } else {
+        let role_id = custom_role_id(&mut conn, tenant.id, &role).await?;
+        let removed = sqlx::query("DELETE FROM user_roles WHERE tenant_id = $1 AND user_id = $2 AND role_id = $3")
+            .bind(tenant.id)
+            .bind(user_id)
+            .bind(role_id)
+            .execute(&mut **conn)
+            .await?;
+        if removed.rows_affected() == 0 {
+            return Err(ApiError::NotFound(format!("User doesn't have the '{}' role", role)));
+        }
+    }
+
+    state.permission_cache.invalidate_user(tenant.id, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A user's roles and effective permissions; admins can see anyone's
+async fn user_permissions(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserPermissions>, ApiError> {
+    let caller = signed_in(user)?;
+    if caller.id != user_id && !is_admin(&caller) {
+        return Err(ApiError::Forbidden);
+    }
+
+    let (email, name, role): (String, String, String) =
+        sqlx::query_as("SELECT email, name, role FROM users WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant.id)
+            .bind(user_id)
+            .fetch_optional(&mut **conn)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+    let grants = state.permission_cache.grants(&state.pool, tenant.id, user_id).await?;
+
+    let subject = AuthenticatedUser { id: user_id, tenant_id: tenant.id, email, name, role: role.clone(), roles: Vec::new() };
+    let mut roles = vec![role];
+    roles.extend(grants.roles.iter().cloned());
+    Ok(Json(UserPermissions {
+        user_id,
+        roles,
+        permissions: effective_permissions(&subject, &grants),
+    }))
+}
+
+/// Clear a user's failed logins, ending any lockout
+async fn unlock_user(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let admin = require_admin(user)?;
+    let email: String = sqlx::query_scalar("SELECT email FROM users WHERE tenant_id = $1 AND id = $2")
+        .bind(tenant.id)
+        .bind(user_id)
+        .fetch_optional(&mut **conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+    state.login_throttle.unlock(tenant.id, &email).await?;
+    tracing::info!(tenant_id = %tenant.id, user_id = %user_id, unlocked_by = %admin.id, "Login lockout lifted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::logic::LogicOp;
+    use sqlx::PgPool;
+    use test_support::get_test_pool;
+
+    fn tenant(id: Uuid) -> ResolvedTenant {
+        ResolvedTenant {
+            id,
+            name: "Roles Test".to_string(),
+            subdomain: format!("roles-{}", id.simple()),
+            settings: serde_json::json!({}),
+            status: "active".to_string(),
+            trial_ends_at: None,
+        }
+    }
+
+    fn as_admin(id: Uuid, tenant_id: Uuid) -> Option<axum::Extension<AuthenticatedUser>> {
+        Some(axum::Extension(AuthenticatedUser {
+            id,
+            tenant_id,
+            email: "admin@example.com".to_string(),
+            name: "Admin".to_string(),
+            role: "admin".to_string(),
+            roles: Vec::new(),
+        }))
+    }
+
+    async fn conn(pool: &PgPool, tenant_id: Uuid) -> RlsConn {
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("SELECT set_config('app.current_tenant', $1::text, false)")
+            .bind(tenant_id)
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        RlsConn(conn)
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_custom_roles_and_last_admin_guard() {
+        let pool = get_test_pool().await;
+        let (tenant_id, admin_id, member_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Roles Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("roles-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, email, role) in [(admin_id, "admin@example.com", "admin"), (member_id, "member@example.com", "member")] {
+            sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash, role) VALUES ($1, $2, $3, 'User', 'x', $4)")
+                .bind(id)
+                .bind(tenant_id)
+                .bind(email)
+                .bind(role)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let state = Arc::new(AppState::new(pool.clone()));
+        let ext = || axum::Extension(tenant(tenant_id));
+        let admin = || as_admin(admin_id, tenant_id);
+
+        // Create a custom role granting report exports
+        let (status, Json(created)) = create_role(ext(), admin(), conn(&pool, tenant_id).await, Json(CreateRoleRequest {
+            name: "auditor".to_string(),
+            label: Some("Auditor".to_string()),
+            description: None,
+            permissions: vec![PermissionDef {
+                id: Uuid::new_v4(),
+                name: "export_reports".to_string(),
+                resource: "reports".to_string(),
+                action: "export".to_string(),
+                condition: LogicOp::HasRole { role: "custom:auditor".to_string() },
+                description: None,
+            }],
+        }))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(!created.built_in);
+        let duplicate = CreateRoleRequest { name: "auditor".to_string(), label: None, description: None, permissions: Vec::new() };
+        assert!(matches!(
+            create_role(ext(), admin(), conn(&pool, tenant_id).await, Json(duplicate)).await,
+            Err(ApiError::Conflict(_))
+        ));
+        let shadowing = CreateRoleRequest { name: "admin".to_string(), label: None, description: None, permissions: Vec::new() };
+        assert!(create_role(ext(), admin(), conn(&pool, tenant_id).await, Json(shadowing)).await.is_err());
+
+        // Assigning it reaches the cached grants straight away
+        assert!(state.permission_cache.grants(&pool, tenant_id, member_id).await.unwrap().roles.is_empty());
+        assign_role(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path((member_id, "auditor".to_string())))
+            .await
+            .unwrap();
+        assert_eq!(state.permission_cache.grants(&pool, tenant_id, member_id).await.unwrap().roles, vec!["auditor"]);
+        let Json(effective) = user_permissions(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path(member_id))
+            .await
+            .unwrap();
+        assert_eq!(effective.roles, vec!["member", "auditor"]);
+        assert_eq!(effective.permissions.get("reports:export"), Some(&true));
+
+        let Json(roles) = list_roles(ext(), admin(), conn(&pool, tenant_id).await).await.unwrap();
+        let auditor = roles.iter().find(|r| r.name == "auditor").unwrap();
+        assert_eq!((auditor.members, auditor.permissions.len()), (1, 1));
+        assert_eq!(roles.iter().find(|r| r.name == "admin").unwrap().members, 1);
+
+        // The only admin can't be demoted, by unassigning or by reassigning
+        assert!(matches!(
+            unassign_role(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path((admin_id, "admin".to_string()))).await,
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(matches!(
+            assign_role(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path((admin_id, "viewer".to_string()))).await,
+            Err(ApiError::Conflict(_))
+        ));
+        // Once there's another admin they can be
+        assign_role(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path((member_id, "admin".to_string())))
+            .await
+            .unwrap();
+        unassign_role(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path((admin_id, "admin".to_string())))
+            .await
+            .unwrap();
+        let role: String = sqlx::query_scalar("SELECT role FROM users WHERE id = $1").bind(admin_id).fetch_one(&pool).await.unwrap();
+        assert_eq!(role, "member");
+
+        // Deleting the role drops it from everyone's grants
+        delete_role(State(state.clone()), ext(), admin(), conn(&pool, tenant_id).await, Path("auditor".to_string()))
+            .await
+            .unwrap();
+        assert!(state.permission_cache.grants(&pool, tenant_id, member_id).await.unwrap().roles.is_empty());
+
+        for sql in [
+            "DELETE FROM user_roles WHERE tenant_id = $1",
+            "DELETE FROM roles WHERE tenant_id = $1",
+            "DELETE FROM users WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_admin_lifts_a_login_lockout() {
+        let pool = get_test_pool().await;
+        let (tenant_id, admin_id, member_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Roles Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("roles-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, 'member@example.com', 'User', 'x')")
+            .bind(member_id)
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let state = Arc::new(AppState::new(pool.clone()));
+        for _ in 0..5 {
+            state.login_throttle.record_failure(tenant_id, "member@example.com", None).await.unwrap();
+        }
+        assert!(state.login_throttle.check(tenant_id, "member@example.com", None).await.is_err());
+
+        let mut member = as_admin(member_id, tenant_id);
+        member.as_mut().unwrap().0.role = "member".to_string();
+        assert!(matches!(
+            unlock_user(State(state.clone()), axum::Extension(tenant(tenant_id)), member, conn(&pool, tenant_id).await, Path(member_id)).await,
+            Err(ApiError::Forbidden)
+        ));
+        let status = unlock_user(
+            State(state.clone()),
+            axum::Extension(tenant(tenant_id)),
+            as_admin(admin_id, tenant_id),
+            conn(&pool, tenant_id).await,
+            Path(member_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(state.login_throttle.check(tenant_id, "member@example.com", None).await.is_ok());
+
+        for sql in ["DELETE FROM users WHERE tenant_id = $1", "DELETE FROM tenants WHERE id = $1"] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Segments
+//!
+//! Named, saved filters over an entity type ("Hot Leads"). A segment stores
+//! only its `FilterExpr`, so its members and count follow the data; list
+//! endpoints accept `?segment=<id>` as a filter source.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use core_metadata::cache::{CachePolicy, LoadingCache};
+use core_metadata::MetadataError;
+use core_models::{FieldDef, FilterExpr, Segment};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgConnection, PgRow};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::middleware::database::RlsConn;
+use crate::middleware::permission::AuthenticatedUser;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::routes::entities::filter::{self, SqlFilter};
+use crate::state::AppState;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_segments).post(create_segment))
+        .route("/:id", get(get_segment).delete(delete_segment))
+        .route("/:id/count", get(segment_count))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SegmentQuery {
+    pub entity_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSegmentRequest {
+    pub entity_type: String,
+    pub name: String,
+    pub filter: FilterExpr,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SegmentCount {
+    pub segment_id: Uuid,
+    pub count: i64,
+}
+
+/// Segment id → matching record count, kept for a few seconds
+///
+/// Counts run a full filtered scan, so dashboards polling several segments
+/// share one query per segment per TTL.
+#[derive(Clone)]
+pub struct SegmentCountCache {
+    cache: Arc<LoadingCache<Uuid, i64>>,
+}
+
+impl SegmentCountCache {
+    pub fn new() -> Self {
+        Self::with_policy(CachePolicy { soft_ttl: Duration::from_secs(10), hard_ttl: Duration::from_secs(30) })
+    }
+
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self { cache: Arc::new(LoadingCache::new(policy)) }
+    }
+
+    /// Records of `entity_type_id` currently matching `segment`
+    pub async fn count(
+        &self,
+        pool: &PgPool,
+        segment: &Segment,
+        entity_type_id: Uuid,
+        fields: &[FieldDef],
+    ) -> Result<i64, ApiError> {
+        let compiled = filter::compile(&segment.filter, fields, 3).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let (pool, tenant_id) = (pool.clone(), segment.tenant_id);
+        self.cache
+            .get_or_load(segment.id, move || {
+                let (pool, compiled) = (pool.clone(), compiled.clone());
+                async move {
+                    let mut conn = pool.acquire().await?;
+                    Ok(count_matching(&mut conn, tenant_id, entity_type_id, &compiled).await?)
+                }
+            })
+            .await
+            .map_err(|e| match e {
+                MetadataError::Database(e) => ApiError::Database(e),
+                other => ApiError::Internal(other.to_string()),
+            })
+    }
+
+    pub fn invalidate(&self, segment_id: Uuid) {
+        self.cache.remove(&segment_id);
+    }
+}
+
+impl Default for SegmentCountCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn list_segments(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Query(query): Query<SegmentQuery>,
+    mut conn: RlsConn,
+) -> Result<Json<Vec<Segment>>, ApiError> {
+    let rows = sqlx::query(
+        "SELECT id, tenant_id, entity_type, name, filter, created_by, created_at FROM segments
+         WHERE tenant_id = $1 AND ($2::text IS NULL OR entity_type = $2)
+         ORDER BY entity_type, name",
+    )
+    .bind(tenant.id)
+    .bind(query.entity_type)
+    .fetch_all(&mut **conn)
+    .await?;
+
+    Ok(Json(rows.iter().map(segment_from_row).collect::<Result<_, _>>()?))
+}
+
+async fn create_segment(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Json(payload): Json<CreateSegmentRequest>,
+) -> Result<Json<Segment>, ApiError> {
+    let entity_type = state.metadata.get_entity_type(tenant.id, &payload.entity_type).await?;
+    let fields = state.metadata.get_fields(tenant.id, entity_type.id).await?;
+    let created_by = user.map(|axum::Extension(u)| u.id);
+
+    let segment = insert_segment(&mut conn, tenant.id, created_by, &payload, &fields).await?;
+    Ok(Json(segment))
+}
+
+async fn get_segment(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Path(id): Path<Uuid>,
+    mut conn: RlsConn,
+) -> Result<Json<Segment>, ApiError> {
+    Ok(Json(load_segment(&mut conn, tenant.id, id).await?))
+}
+
+async fn delete_segment(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Path(id): Path<Uuid>,
+    mut conn: RlsConn,
+) -> Result<StatusCode, ApiError> {
+    let deleted = sqlx::query("DELETE FROM segments WHERE tenant_id = $1 AND id = $2")
+        .bind(tenant.id)
+        .bind(id)
+        .execute(&mut **conn)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(ApiError::NotFound("Segment not found".to_string()));
+    }
+    state.segment_counts.invalidate(id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /segments/:id/count
+async fn segment_count(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Path(id): Path<Uuid>,
+    mut conn: RlsConn,
+) -> Result<Json<SegmentCount>, ApiError> {
+    let segment = load_segment(&mut conn, tenant.id, id).await?;
+    let entity_type = state.metadata.get_entity_type(tenant.id, &segment.entity_type).await?;
+    let fields = state.metadata.get_fields(tenant.id, entity_type.id).await?;
+
+    let count = state.segment_counts.count(&state.pool, &segment, entity_type.id, &fields).await?;
+    Ok(Json(SegmentCount { segment_id: id, count }))
+}
+
+/// Store a segment after checking its filter compiles against `fields`
+async fn insert_segment(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    created_by: Option<Uuid>,
+    payload: &CreateSegmentRequest,
+    fields: &[FieldDef],
+) -> Result<Segment, ApiError> {
+    if payload.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("Segment name is required".to_string()));
+    }
+    filter::compile(&payload.filter, fields, 1).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let filter_json = serde_json::to_value(&payload.filter).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let row = sqlx::query(
+        "INSERT INTO segments (tenant_id, entity_type, name, filter, created_by)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (tenant_id, entity_type, name) DO NOTHING
+         RETURNING id, tenant_id, entity_type, name, filter, created_by, created_at",
+    )
+    .bind(tenant_id)
+    .bind(&payload.entity_type)
+    .bind(payload.name.trim())
+    .bind(filter_json)
+    .bind(created_by)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| ApiError::Conflict(format!("Segment '{}' already exists", payload.name.trim())))?;
+
+    segment_from_row(&row)
+}
+
+/// The tenant's segment `id`
+pub(crate) async fn load_segment(conn: &mut PgConnection, tenant_id: Uuid, id: Uuid) -> Result<Segment, ApiError> {
+    let row = sqlx::query(
+        "SELECT id, tenant_id, entity_type, name, filter, created_by, created_at FROM segments
+         WHERE tenant_id = $1 AND id = $2",
+    )
+    .bind(tenant_id)
+    .bind(id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Segment not found".to_string()))?;
+
+    segment_from_row(&row)
+}
+
+fn segment_from_row(row: &PgRow) -> Result<Segment, ApiError> {
+    let filter = serde_json::from_value(row.try_get("filter")?)
+        .map_err(|e| ApiError::Internal(format!("Stored segment filter is invalid: {}", e)))?;
+    Ok(Segment {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        entity_type: row.try_get("entity_type")?,
+        name: row.try_get("name")?,
+        filter,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Live records of `entity_type_id` matching `filter`, compiled from `$3`
+async fn count_matching(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    filter: &SqlFilter,
+) -> Result<i64, sqlx::Error> {
+    let sql = format!(
+        "SELECT COUNT(*) FROM entity_records
+         WHERE tenant_id = $1 AND entity_type_id = $2 AND deleted_at IS NULL AND {}",
+        filter.sql
+    );
+    filter.bind(sqlx::query(&sql).bind(tenant_id).bind(entity_type_id)).fetch_one(conn).await?.try_get(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::FieldType;
+    use serde_json::json;
+    use test_support::TestTenant;
+
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+        fields: Vec<FieldDef>,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Segments Test").await;
+            let entity_type_id = tenant.entity_type("crm", "lead", "Lead", "Leads").await;
+            let fields = vec![
+                FieldDef::new(tenant.id, entity_type_id, "name", "Name", FieldType::Text),
+                FieldDef::new(tenant.id, entity_type_id, "score", "Score", FieldType::Number { decimals: None }),
+            ];
+            Self { tenant, entity_type_id, fields }
+        }
+
+        async fn insert_lead(&self, data: serde_json::Value) {
+            sqlx::query("INSERT INTO entity_records (tenant_id, entity_type_id, data) VALUES ($1, $2, $3)")
+                .bind(self.tenant.id)
+                .bind(self.entity_type_id)
+                .bind(data)
+                .execute(&self.tenant.pool)
+                .await
+                .unwrap();
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_segment_count_follows_inserts() {
+        let fx = Fixture::new().await;
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let request = CreateSegmentRequest {
+            entity_type: "lead".to_string(),
+            name: "Hot Leads".to_string(),
+            filter: serde_json::from_value(json!({"field": "score", "op": "gte", "value": 80})).unwrap(),
+        };
+        let segment = insert_segment(&mut conn, fx.tenant.id, None, &request, &fx.fields).await.unwrap();
+        assert_eq!(load_segment(&mut conn, fx.tenant.id, segment.id).await.unwrap(), segment);
+        assert!(matches!(
+            insert_segment(&mut conn, fx.tenant.id, None, &request, &fx.fields).await,
+            Err(ApiError::Conflict(_))
+        ));
+
+        fx.insert_lead(json!({"name": "Amal", "score": 90})).await;
+        fx.insert_lead(json!({"name": "Omar", "score": 20})).await;
+
+        let uncached = SegmentCountCache::with_policy(CachePolicy { soft_ttl: Duration::ZERO, hard_ttl: Duration::ZERO });
+        let cached = SegmentCountCache::new();
+        assert_eq!(uncached.count(&fx.tenant.pool, &segment, fx.entity_type_id, &fx.fields).await.unwrap(), 1);
+        assert_eq!(cached.count(&fx.tenant.pool, &segment, fx.entity_type_id, &fx.fields).await.unwrap(), 1);
+
+        fx.insert_lead(json!({"name": "Sara", "score": 85})).await;
+        assert_eq!(uncached.count(&fx.tenant.pool, &segment, fx.entity_type_id, &fx.fields).await.unwrap(), 2);
+        // Served from cache until it expires or is invalidated
+        assert_eq!(cached.count(&fx.tenant.pool, &segment, fx.entity_type_id, &fx.fields).await.unwrap(), 1);
+        cached.invalidate(segment.id);
+        assert_eq!(cached.count(&fx.tenant.pool, &segment, fx.entity_type_id, &fx.fields).await.unwrap(), 2);
+
+        let unknown = CreateSegmentRequest {
+            name: "Broken".to_string(),
+            filter: serde_json::from_value(json!({"field": "owner", "op": "eq", "value": "x"})).unwrap(),
+            ..request
+        };
+        assert!(matches!(
+            insert_segment(&mut conn, fx.tenant.id, None, &unknown, &fx.fields).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+}
@@ -0,0 +1,111 @@
+//! Semantic Search - finds records by meaning rather than keywords
+//!
+//! Matches the query's embedding against the vectors of records' embeddable
+//! fields (see `ai::record_embeddings`). Records whose vector is still being
+//! computed are found by their previous one, and new records once theirs is
+//! stored.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Row;
+use std::sync::Arc;
+
+use crate::ai::embeddings::vector_literal;
+use crate::middleware::database::RlsConn;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::routes::entities::record_from_row;
+use crate::state::AppState;
+
+/// Most results one search returns
+pub const MAX_RESULTS: i64 = 50;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/entities/:entity_code/semantic-search", post(semantic_search))
+        .route("/records/:entity_code/semantic-search", post(semantic_search))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SemanticSearchRequest {
+    pub query: String,
+    #[serde(default = "default_k")]
+    pub k: i64,
+}
+
+fn default_k() -> i64 {
+    10
+}
+
+#[derive(Debug, Serialize)]
+pub struct SemanticMatch {
+    pub record: Value,
+    /// Cosine similarity to the query, 1.0 for identical direction
+    pub similarity: f32,
+}
+
+/// POST /entities/:entity_code/semantic-search { query, k }
+async fn semantic_search(
+    State(state): State<Arc<AppState>>,
+    Path(entity_code): Path<String>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    mut conn: RlsConn,
+    Json(request): Json<SemanticSearchRequest>,
+) -> Response {
+    let Some(embedder) = state.embedder.clone() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Embeddings are not configured").into_response();
+    };
+    if request.query.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, "Query must not be empty").into_response();
+    }
+    let entity_type = match state.metadata.get_entity_type(tenant.id, &entity_code).await {
+        Ok(e) => e,
+        Err(_) => return (StatusCode::NOT_FOUND, format!("Entity type '{}' not found", entity_code)).into_response(),
+    };
+    let fields = state.metadata.get_fields(tenant.id, entity_type.id).await.unwrap_or_default();
+
+    let embedding = match embedder.embed(&request.query).await {
+        Ok(e) => e.embedding,
+        Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT r.id, r.data, r.created_at, r.updated_at, r.deleted_at, r.version,
+               1 - (e.embedding <=> $1::vector) AS similarity
+        FROM entity_embeddings e
+        JOIN entity_records r ON r.id = e.entity_id AND r.tenant_id = e.tenant_id
+        WHERE e.tenant_id = $2 AND e.entity_type = $3 AND e.embedding IS NOT NULL
+          AND r.entity_type_id = $4 AND r.deleted_at IS NULL
+        ORDER BY e.embedding <=> $1::vector
+        LIMIT $5
+        "#,
+    )
+    .bind(vector_literal(&embedding))
+    .bind(tenant.id)
+    .bind(&entity_code)
+    .bind(entity_type.id)
+    .bind(request.k.clamp(1, MAX_RESULTS))
+    .fetch_all(&mut **conn)
+    .await;
+
+    match rows {
+        Ok(rows) => {
+            let matches: Vec<SemanticMatch> = rows
+                .iter()
+                .map(|row| SemanticMatch {
+                    record: record_from_row(row, &fields),
+                    similarity: row.try_get::<f64, _>("similarity").unwrap_or_default() as f32,
+                })
+                .collect();
+            Json(matches).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
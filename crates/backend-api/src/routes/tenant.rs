@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::ai::redaction::RedactionMode;
 use crate::state::AppState;
 use crate::error::ApiError;
 
@@ -60,6 +61,27 @@ pub struct TenantContact {
     pub address: Option<String>,
 }
 
+/// What record data may reach the AI provider (see `ai::redaction`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TenantAi {
+    /// Send PII fields unmasked
+    #[serde(default)]
+    pub allow_pii: Option<bool>,
+    #[serde(default)]
+    pub pii_redaction: Option<RedactionMode>,
+}
+
+/// Call recording rules (see `routes::voice`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TenantVoice {
+    /// Calls may be recorded and transcribed; off unless the tenant turns it on
+    #[serde(default)]
+    pub recording_allowed: Option<bool>,
+    /// The agent must confirm the callee agreed before a call is recorded
+    #[serde(default)]
+    pub require_consent: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TenantSettings {
     #[serde(default)]
@@ -68,6 +90,10 @@ pub struct TenantSettings {
     pub hero: TenantHero,
     #[serde(default)]
     pub contact: TenantContact,
+    #[serde(default)]
+    pub ai: TenantAi,
+    #[serde(default)]
+    pub voice: TenantVoice,
 }
 
 #[derive(Debug, Serialize)]
@@ -174,6 +200,22 @@ async fn update_settings(
         current.contact.address = new_settings.contact.address;
     }
     
+    // Update AI
+    if new_settings.ai.allow_pii.is_some() {
+        current.ai.allow_pii = new_settings.ai.allow_pii;
+    }
+    if new_settings.ai.pii_redaction.is_some() {
+        current.ai.pii_redaction = new_settings.ai.pii_redaction;
+    }
+    
+    // Update voice
+    if new_settings.voice.recording_allowed.is_some() {
+        current.voice.recording_allowed = new_settings.voice.recording_allowed;
+    }
+    if new_settings.voice.require_consent.is_some() {
+        current.voice.require_consent = new_settings.voice.require_consent;
+    }
+    
     // Save updated settings
     let settings_json = serde_json::to_value(&current)
         .map_err(|e| ApiError::Internal(format!("Failed to serialize settings: {}", e)))?;
@@ -186,6 +228,7 @@ async fn update_settings(
     .bind(tenant_id)
     .execute(&state.pool)
     .await?;
+    state.tenant_hosts.invalidate_tenant(tenant_id);
     
     Ok(Json(SettingsResponse {
         tenant_id,
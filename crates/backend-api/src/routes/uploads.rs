@@ -0,0 +1,537 @@
+//! Uploads - files behind Image and Attachment fields
+//!
+//! `POST /uploads` takes a multipart form with a `file` part, checks it
+//! against the tenant's size and type limits, stores it in the configured
+//! `FileStore`, and returns its URL with size, type and SHA-256 checksum.
+//! Records store only the URL; each upload is also logged in `file_uploads`.
+//! A file's extension, declared content type, and leading bytes must all
+//! agree with one entry of the allowlist, so an HTML page renamed to `.png`
+//! is refused.
+//!
+//! A `purpose=signature` part marks the file as a captured signature: it
+//! must be a PNG and is retained for audit, whatever later happens to the
+//! record that points at it.
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::middleware::database::RlsConn;
+use crate::middleware::permission::AuthenticatedUser;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::state::AppState;
+use crate::storage::{FileStore, StorageError};
+
+/// Size limit when the tenant doesn't set one
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Tenants can't raise their limit past this
+pub const MAX_UPLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Extensions accepted for upload and the content type each must carry
+const ALLOWED_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+    ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("pptx", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+    ("csv", "text/csv"),
+    ("txt", "text/plain"),
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What an upload is for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadPurpose {
+    /// Behind an Image or Attachment field
+    #[default]
+    File,
+    /// Captured by a Signature field; PNG only, retained for audit
+    Signature,
+}
+
+impl UploadPurpose {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "file" => Some(Self::File),
+            "signature" => Some(Self::Signature),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Signature => "signature",
+        }
+    }
+
+    /// Retained uploads can't be deleted while the tenant exists
+    pub fn retained(self) -> bool {
+        self == Self::Signature
+    }
+}
+
+/// A stored upload
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UploadedFile {
+    pub url: String,
+    pub key: String,
+    pub file_name: String,
+    pub size: u64,
+    pub content_type: String,
+    /// Hex SHA-256 of the contents
+    pub checksum: String,
+    pub purpose: UploadPurpose,
+}
+
+/// A tenant's upload limits, from `settings.uploads`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadLimits {
+    pub max_bytes: u64,
+    /// Content types the tenant accepts ("image/*" matches any image); `None`
+    /// accepts the whole allowlist. Tenants can narrow the allowlist, not widen it.
+    pub allowed_types: Option<Vec<String>>,
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self { max_bytes: DEFAULT_MAX_UPLOAD_BYTES, allowed_types: None }
+    }
+}
+
+impl UploadLimits {
+    /// Read `{"uploads": {"max_bytes": 5242880, "allowed_types": ["image/*"]}}`
+    pub fn from_settings(settings: &Value) -> Self {
+        let uploads = settings.get("uploads");
+        let max_bytes = uploads
+            .and_then(|u| u.get("max_bytes"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MAX_UPLOAD_BYTES)
+            .min(MAX_UPLOAD_BYTES);
+        let allowed_types = uploads
+            .and_then(|u| u.get("allowed_types"))
+            .and_then(|v| v.as_array())
+            .map(|types| types.iter().filter_map(|t| t.as_str()).map(|t| t.trim().to_lowercase()).collect());
+        Self { max_bytes, allowed_types }
+    }
+
+    fn allows(&self, content_type: &str) -> bool {
+        self.allowed_types.as_ref().is_none_or(|types| {
+            types.iter().any(|allowed| match allowed.strip_suffix("/*") {
+                Some(family) => content_type.split('/').next() == Some(family),
+                None => allowed == content_type,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UploadError {
+    #[error("File is larger than the {} MB limit", .0 / (1024 * 1024))]
+    TooLarge(u64),
+
+    #[error("{0}")]
+    UnsupportedType(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("Could not read upload: {0}")]
+    Body(String),
+
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl IntoResponse for UploadError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            UploadError::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            UploadError::UnsupportedType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            UploadError::Invalid(_) | UploadError::Body(_) => StatusCode::BAD_REQUEST,
+            UploadError::Storage(e) => {
+                tracing::error!("Upload storage failed: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            UploadError::Database(e) => {
+                tracing::error!("Recording upload failed: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let message = match &self {
+            UploadError::Database(_) => "Database error".to_string(),
+            other => other.to_string(),
+        };
+        let body = Json(serde_json::json!({ "error": message, "status": status.as_u16() }));
+        (status, body).into_response()
+    }
+}
+
+// ============================================================================
+// Routes
+// ============================================================================
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        // The tenant's own limit is enforced while reading; this only stops
+        // bodies no tenant could upload
+        .route("/uploads", post(upload_file).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES as usize + 64 * 1024)))
+        .route("/uploads/files/:tenant_id/*name", get(download_file))
+}
+
+/// POST /uploads (multipart/form-data with a `file` part, after an optional
+/// `purpose` part)
+async fn upload_file(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    mut multipart: Multipart,
+) -> Result<Json<UploadedFile>, UploadError> {
+    let limits = UploadLimits::from_settings(&tenant.settings);
+    let mut purpose = UploadPurpose::File;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| UploadError::Body(e.to_string()))? {
+        if field.name() == Some("purpose") {
+            let value = field.text().await.map_err(|e| UploadError::Body(e.to_string()))?;
+            purpose = UploadPurpose::parse(&value)
+                .ok_or_else(|| UploadError::Invalid(format!("Unknown upload purpose '{}'", value)))?;
+            continue;
+        }
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or_default().to_string();
+        let content_type = field.content_type().map(str::to_string);
+        let chunks = field.map_err(|e| e.to_string());
+
+        let uploaded = store_upload(
+            state.file_store.as_ref(),
+            &limits,
+            tenant.id,
+            purpose,
+            &file_name,
+            content_type.as_deref(),
+            chunks,
+        )
+        .await?;
+        record_upload(&mut conn, tenant.id, user.map(|axum::Extension(u)| u.id), &uploaded).await?;
+        return Ok(Json(uploaded));
+    }
+
+    Err(UploadError::Invalid("Expected a multipart form with a 'file' part".to_string()))
+}
+
+/// GET /uploads/files/:tenant_id/*name
+///
+/// Serves files from stores that keep them on this server.
+async fn download_file(
+    State(state): State<Arc<AppState>>,
+    Path((tenant_id, name)): Path<(Uuid, String)>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+) -> Response {
+    let Some(content_type) = name.rsplit_once('.').and_then(|(_, ext)| content_type_for(ext)) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if tenant_id != tenant.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match state.file_store.get(&format!("{}/{}", tenant_id, name)).await {
+        Ok(Some(bytes)) => {
+            // Only images render inline; everything else downloads
+            let disposition = if content_type.starts_with("image/") { "inline" } else { "attachment" };
+            (
+                [
+                    (header::CONTENT_TYPE, content_type),
+                    (header::CONTENT_DISPOSITION, disposition),
+                    (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+                    // Keys are never reused, so the contents never change
+                    (header::CACHE_CONTROL, "private, max-age=31536000, immutable"),
+                ],
+                bytes,
+            )
+                .into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => UploadError::from(e).into_response(),
+    }
+}
+
+// ============================================================================
+// Upload
+// ============================================================================
+
+fn content_type_for(extension: &str) -> Option<&'static str> {
+    let extension = extension.to_lowercase();
+    ALLOWED_TYPES.iter().find(|(ext, _)| *ext == extension).map(|(_, mime)| *mime)
+}
+
+/// Whether the leading bytes of a file look like `content_type`
+fn sniff_matches(content_type: &str, head: &[u8]) -> bool {
+    match content_type {
+        "image/png" => head.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/jpeg" => head.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/gif" => head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a"),
+        "image/webp" => head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP"),
+        "application/pdf" => head.starts_with(b"%PDF-"),
+        // Office documents are zip archives
+        t if t.starts_with("application/vnd.openxmlformats") => head.starts_with(b"PK\x03\x04"),
+        // Text must not contain binary content
+        t if t.starts_with("text/") => !head.contains(&0) && std::str::from_utf8(head).is_ok(),
+        _ => false,
+    }
+}
+
+/// Check an upload's name and declared type, read it within the limit, and store it
+pub async fn store_upload<S>(
+    store: &dyn FileStore,
+    limits: &UploadLimits,
+    tenant_id: Uuid,
+    purpose: UploadPurpose,
+    file_name: &str,
+    declared_type: Option<&str>,
+    mut chunks: S,
+) -> Result<UploadedFile, UploadError>
+where
+    S: Stream<Item = Result<Bytes, String>> + Unpin,
+{
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .ok_or_else(|| UploadError::UnsupportedType("File name needs an extension".to_string()))?;
+    let content_type = content_type_for(&extension)
+        .ok_or_else(|| UploadError::UnsupportedType(format!(".{} files are not accepted", extension)))?;
+
+    // Browsers send octet-stream for types they don't know; anything else must agree
+    let declared = declared_type.map(|t| t.split(';').next().unwrap_or("").trim().to_lowercase());
+    if let Some(declared) = declared.filter(|t| !t.is_empty() && t != "application/octet-stream") {
+        if declared != content_type {
+            return Err(UploadError::UnsupportedType(format!(
+                "Content type {} doesn't match a .{} file",
+                declared, extension
+            )));
+        }
+    }
+    if purpose == UploadPurpose::Signature && content_type != "image/png" {
+        return Err(UploadError::UnsupportedType("Signatures must be PNG images".to_string()));
+    }
+    // Signatures are captured, not chosen, so the tenant's type list doesn't apply
+    if purpose == UploadPurpose::File && !limits.allows(content_type) {
+        return Err(UploadError::UnsupportedType(format!("{} files are not accepted", content_type)));
+    }
+
+    let mut bytes = Vec::new();
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = chunks.next().await.transpose().map_err(UploadError::Body)? {
+        if (bytes.len() + chunk.len()) as u64 > limits.max_bytes {
+            return Err(UploadError::TooLarge(limits.max_bytes));
+        }
+        hasher.update(&chunk);
+        bytes.extend_from_slice(&chunk);
+    }
+    if bytes.is_empty() {
+        return Err(UploadError::Invalid("File is empty".to_string()));
+    }
+    if !sniff_matches(content_type, &bytes[..bytes.len().min(512)]) {
+        return Err(UploadError::UnsupportedType(format!("File contents are not a valid .{} file", extension)));
+    }
+
+    let checksum = hex::encode(hasher.finalize());
+    let key = match purpose {
+        UploadPurpose::File => format!("{}/{}.{}", tenant_id, Uuid::new_v4(), extension),
+        UploadPurpose::Signature => format!("{}/signatures/{}.{}", tenant_id, Uuid::new_v4(), extension),
+    };
+    let size = bytes.len() as u64;
+    store.put(&key, bytes, content_type, &checksum).await?;
+
+    Ok(UploadedFile {
+        url: store.url(&key),
+        key,
+        file_name: file_name.to_string(),
+        size,
+        content_type: content_type.to_string(),
+        checksum,
+        purpose,
+    })
+}
+
+/// Log a stored upload in `file_uploads`
+pub async fn record_upload(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    uploaded_by: Option<Uuid>,
+    file: &UploadedFile,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO file_uploads
+             (tenant_id, storage_key, url, file_name, size, content_type, checksum, purpose, retained, uploaded_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         RETURNING id",
+    )
+    .bind(tenant_id)
+    .bind(&file.key)
+    .bind(&file.url)
+    .bind(&file.file_name)
+    .bind(file.size as i64)
+    .bind(&file.content_type)
+    .bind(&file.checksum)
+    .bind(file.purpose.as_str())
+    .bind(file.purpose.retained())
+    .bind(uploaded_by)
+    .fetch_one(conn)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalStore;
+    use serde_json::json;
+    use test_support::get_test_pool;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn temp_store() -> (LocalStore, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("uploads-test-{}", Uuid::new_v4()));
+        (LocalStore::new(&dir, "/files"), dir)
+    }
+
+    fn chunks(parts: &[&[u8]]) -> impl Stream<Item = Result<Bytes, String>> + Unpin {
+        futures::stream::iter(parts.iter().map(|p| Ok(Bytes::copy_from_slice(p))).collect::<Vec<_>>())
+    }
+
+    #[tokio::test]
+    async fn test_upload_is_stored_with_metadata() {
+        let (store, dir) = temp_store();
+        let tenant_id = Uuid::new_v4();
+
+        let uploaded = store_upload(&store, &UploadLimits::default(), tenant_id, UploadPurpose::File, "Logo.PNG", Some("image/png"), chunks(&[&PNG[..8], &PNG[8..]]))
+            .await
+            .unwrap();
+
+        assert_eq!(uploaded.size, PNG.len() as u64);
+        assert_eq!(uploaded.content_type, "image/png");
+        assert_eq!(uploaded.file_name, "Logo.PNG");
+        assert_eq!(uploaded.checksum, hex::encode(Sha256::digest(PNG)));
+        assert!(uploaded.key.starts_with(&tenant_id.to_string()) && uploaded.key.ends_with(".png"));
+        assert_eq!(uploaded.url, format!("/files/{}", uploaded.key));
+        assert_eq!(store.get(&uploaded.key).await.unwrap().as_deref(), Some(PNG));
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_oversize_upload_is_rejected() {
+        let (store, dir) = temp_store();
+        let limits = UploadLimits::from_settings(&json!({ "uploads": { "max_bytes": 16 } }));
+        assert_eq!(limits.max_bytes, 16);
+
+        let result = store_upload(&store, &limits, Uuid::new_v4(), UploadPurpose::File, "notes.txt", Some("text/plain"), chunks(&[b"0123456789", b"0123456789"])).await;
+        assert!(matches!(result, Err(UploadError::TooLarge(16))));
+        assert!(!dir.exists(), "nothing is written for a rejected upload");
+
+        // Tenants can't raise the limit past the server's
+        let limits = UploadLimits::from_settings(&json!({ "uploads": { "max_bytes": u64::MAX } }));
+        assert_eq!(limits.max_bytes, MAX_UPLOAD_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_content_type_is_rejected() {
+        let (store, dir) = temp_store();
+        let limits = UploadLimits::default();
+        let tenant_id = Uuid::new_v4();
+
+        // Extension outside the allowlist
+        let result = store_upload(&store, &limits, tenant_id, UploadPurpose::File, "setup.exe", Some("application/octet-stream"), chunks(&[b"MZ"])).await;
+        assert!(matches!(result, Err(UploadError::UnsupportedType(_))));
+
+        // Declared type disagrees with the extension
+        let result = store_upload(&store, &limits, tenant_id, UploadPurpose::File, "page.png", Some("text/html"), chunks(&[PNG])).await;
+        assert!(matches!(result, Err(UploadError::UnsupportedType(_))));
+
+        // Contents disagree with the extension
+        let result = store_upload(&store, &limits, tenant_id, UploadPurpose::File, "page.png", Some("image/png"), chunks(&[b"<html><script>"])).await;
+        assert!(matches!(result, Err(UploadError::UnsupportedType(_))));
+
+        // Signatures are PNGs only
+        let result = store_upload(&store, &limits, tenant_id, UploadPurpose::Signature, "signature.jpg", Some("image/jpeg"), chunks(&[&[0xFF, 0xD8, 0xFF]])).await;
+        assert!(matches!(result, Err(UploadError::UnsupportedType(_))));
+
+        // The tenant narrowed the allowlist to images
+        let images_only = UploadLimits::from_settings(&json!({ "uploads": { "allowed_types": ["image/*"] } }));
+        let result = store_upload(&store, &images_only, tenant_id, UploadPurpose::File, "report.pdf", Some("application/pdf"), chunks(&[b"%PDF-1.7"])).await;
+        assert!(matches!(result, Err(UploadError::UnsupportedType(_))));
+        assert!(store_upload(&store, &images_only, tenant_id, UploadPurpose::File, "logo.png", None, chunks(&[PNG])).await.is_ok());
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_signature_upload_is_retained() {
+        let pool = get_test_pool().await;
+        let (store, dir) = temp_store();
+        let limits = UploadLimits::default();
+        let tenant_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Uploads Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("uploads-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let signature = store_upload(&store, &limits, tenant_id, UploadPurpose::Signature, "signature.png", Some("image/png"), chunks(&[PNG]))
+            .await
+            .unwrap();
+        assert!(signature.key.starts_with(&format!("{}/signatures/", tenant_id)));
+        let file = store_upload(&store, &limits, tenant_id, UploadPurpose::File, "logo.png", Some("image/png"), chunks(&[PNG]))
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let signature_id = record_upload(&mut conn, tenant_id, None, &signature).await.unwrap();
+        let file_id = record_upload(&mut conn, tenant_id, None, &file).await.unwrap();
+
+        // Ordinary uploads can be cleaned up; signatures can't
+        let delete = |id: Uuid| sqlx::query("DELETE FROM file_uploads WHERE id = $1").bind(id).execute(&pool);
+        assert!(delete(file_id).await.is_ok());
+        assert!(delete(signature_id).await.is_err());
+
+        // Removing the tenant still removes its uploads
+        sqlx::query("DELETE FROM tenants WHERE id = $1").bind(tenant_id).execute(&pool).await.unwrap();
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM file_uploads WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -13,6 +13,9 @@ use crate::state::AppState;
 use crate::error::ApiError;
 use crate::middleware::tenant::ResolvedTenant;
 use crate::middleware::database::RlsConn;
+use crate::middleware::permission::{is_admin, AuthenticatedUser};
+use core_models::ViewVisibility;
+use sqlx::postgres::{PgConnection, PgRow};
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
@@ -21,9 +24,12 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/:id", get(get_view))
         .route("/:id", put(update_view))
         .route("/:id", delete(delete_view))
+        .route("/:id/clone", post(clone_view))
+        .route("/:id/share", post(share_view))
+        .route("/:id/columns", put(save_columns))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[allow(dead_code)]
 pub struct ViewQuery {
     pub entity_type_id: Option<Uuid>,
@@ -31,6 +37,43 @@ pub struct ViewQuery {
     pub user_id: Option<Uuid>,
 }
 
+/// Who is asking for views; admins see every view in the tenant
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Viewer {
+    pub user_id: Option<Uuid>,
+    pub is_admin: bool,
+}
+
+impl Viewer {
+    fn from_user(user: Option<axum::Extension<AuthenticatedUser>>) -> Self {
+        match user {
+            Some(axum::Extension(user)) => Self { user_id: Some(user.id), is_admin: is_admin(&user) },
+            None => Self::default(),
+        }
+    }
+
+    /// May change sharing, i.e. owns the view or is an admin
+    fn can_manage(&self, created_by: Option<Uuid>) -> bool {
+        self.is_admin || (self.user_id.is_some() && self.user_id == created_by)
+    }
+}
+
+/// SQL condition: view `v` is visible to the user bound at `$user` (NULL when
+/// anonymous), or to everyone when `$admin` is true
+fn visible_to(user: usize, admin: usize) -> String {
+    format!(
+        "(${admin} OR v.is_system OR v.created_by IS NULL OR v.visibility = 'tenant'
+          OR v.created_by = ${user} OR ${user} = ANY(v.shared_with)
+          OR (v.visibility = 'team' AND EXISTS (
+                SELECT 1 FROM user_teams owner_team
+                JOIN user_teams viewer_team ON viewer_team.team_id = owner_team.team_id
+                WHERE owner_team.user_id = v.created_by AND viewer_team.user_id = ${user})))"
+    )
+}
+
+const VIEW_COLUMNS: &str = "v.id, v.entity_type_id, v.name, v.label, v.view_type, v.is_default, v.is_system, v.created_by, \
+    v.visibility, v.shared_with, v.columns, v.filters, v.sort, v.settings";
+
 #[derive(Debug, Serialize)]
 pub struct ViewResponse {
     pub id: Uuid,
@@ -41,6 +84,8 @@ pub struct ViewResponse {
     pub is_default: bool,
     pub is_system: bool,
     pub created_by: Option<Uuid>,
+    pub visibility: ViewVisibility,
+    pub shared_with: Vec<Uuid>,
     pub columns: serde_json::Value,
     pub filters: serde_json::Value,
     pub sort: serde_json::Value,
@@ -56,6 +101,8 @@ pub struct CreateViewRequest {
     pub view_type: String,
     pub created_by: Uuid,
     #[serde(default)]
+    pub visibility: ViewVisibility,
+    #[serde(default)]
     pub columns: serde_json::Value,
     #[serde(default)]
     pub filters: serde_json::Value,
@@ -75,48 +122,88 @@ pub struct ViewListResponse {
 
 async fn list_views(
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     Query(query): Query<ViewQuery>,
     mut conn: RlsConn,
 ) -> Result<Json<ViewListResponse>, ApiError> {
-    use sqlx::Row;
+    let data = query_views(&mut conn, tenant.id, Viewer::from_user(user), &query).await?;
+    let total = data.len() as i64;
+    Ok(Json(ViewListResponse { data, total }))
+}
 
-    let mut sql = String::from(
-        "SELECT v.id, v.entity_type_id, v.name, v.label, v.view_type, v.is_default, v.is_system, v.created_by, v.columns, v.filters, v.sort, v.settings 
+async fn query_views(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    viewer: Viewer,
+    query: &ViewQuery,
+) -> Result<Vec<ViewResponse>, ApiError> {
+    let sql = format!(
+        "SELECT {VIEW_COLUMNS}
          FROM view_defs v
          JOIN entity_types et ON v.entity_type_id = et.id
-         WHERE v.tenant_id = $1"
+         WHERE v.tenant_id = $1
+           AND ($2::uuid IS NULL OR v.entity_type_id = $2)
+           AND ($3::text IS NULL OR et.name = $3)
+           AND {}
+         ORDER BY v.is_default DESC, v.is_system DESC, v.name ASC",
+        visible_to(4, 5)
     );
-    
-    let rows = if let Some(entity_id) = query.entity_type_id {
-        sql.push_str(" AND v.entity_type_id = $2 ORDER BY v.is_default DESC, v.is_system DESC, v.name ASC");
-        sqlx::query(&sql).bind(tenant.id).bind(entity_id).fetch_all(&mut **conn).await
-    } else if let Some(entity_code) = query.entity_code {
-        sql.push_str(" AND et.name = $2 ORDER BY v.is_default DESC, v.is_system DESC, v.name ASC");
-        sqlx::query(&sql).bind(tenant.id).bind(entity_code).fetch_all(&mut **conn).await
-    } else {
-        sql.push_str(" ORDER BY v.is_default DESC, v.is_system DESC, v.name ASC");
-        sqlx::query(&sql).bind(tenant.id).fetch_all(&mut **conn).await
-    }.map_err(|e| ApiError::Database(e))?;
-
-    let data: Vec<ViewResponse> = rows.iter().map(|row| {
-        ViewResponse {
-            id: row.try_get("id").unwrap_or_default(),
-            entity_type_id: row.try_get("entity_type_id").unwrap_or_default(),
-            name: row.try_get("name").unwrap_or_default(),
-            label: row.try_get("label").unwrap_or_default(),
-            view_type: row.try_get("view_type").unwrap_or_default(),
-            is_default: row.try_get("is_default").unwrap_or(false),
-            is_system: row.try_get("is_system").unwrap_or(false),
-            created_by: row.try_get("created_by").ok(),
-            columns: row.try_get("columns").unwrap_or(serde_json::json!([])),
-            filters: row.try_get("filters").unwrap_or(serde_json::json!([])),
-            sort: row.try_get("sort").unwrap_or(serde_json::json!([])),
-            settings: row.try_get("settings").unwrap_or(serde_json::json!({})),
+    let rows = sqlx::query(&sql)
+        .bind(tenant_id)
+        .bind(query.entity_type_id)
+        .bind(&query.entity_code)
+        .bind(viewer.user_id)
+        .bind(viewer.is_admin)
+        .fetch_all(conn)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(apply_overrides(rows.iter().map(view_from_row).collect(), viewer))
+}
+
+/// The view a personal override stands in for
+fn override_of(view: &ViewResponse) -> Option<Uuid> {
+    view.settings.get("override_of").and_then(|v| v.as_str()).and_then(|v| Uuid::parse_str(v).ok())
+}
+
+/// Show the viewer's personal overrides in place of the views they override,
+/// and hide other users' overrides
+fn apply_overrides(views: Vec<ViewResponse>, viewer: Viewer) -> Vec<ViewResponse> {
+    let (overrides, mut views): (Vec<_>, Vec<_>) = views.into_iter().partition(|v| override_of(v).is_some());
+    for personal in overrides {
+        if viewer.user_id.is_none() || personal.created_by != viewer.user_id {
+            continue;
+        }
+        if let Some(slot) = views.iter_mut().find(|v| Some(v.id) == override_of(&personal)) {
+            *slot = ViewResponse { is_default: slot.is_default, ..personal };
         }
-    }).collect();
+    }
+    views
+}
 
-    let total = data.len() as i64;
-    Ok(Json(ViewListResponse { data, total }))
+fn view_from_row(row: &PgRow) -> ViewResponse {
+    use sqlx::Row;
+
+    ViewResponse {
+        id: row.try_get("id").unwrap_or_default(),
+        entity_type_id: row.try_get("entity_type_id").unwrap_or_default(),
+        name: row.try_get("name").unwrap_or_default(),
+        label: row.try_get("label").unwrap_or_default(),
+        view_type: row.try_get("view_type").unwrap_or_default(),
+        is_default: row.try_get("is_default").unwrap_or(false),
+        is_system: row.try_get("is_system").unwrap_or(false),
+        created_by: row.try_get("created_by").ok(),
+        visibility: row
+            .try_get::<String, _>("visibility")
+            .ok()
+            .and_then(|v| ViewVisibility::parse(&v))
+            .unwrap_or_default(),
+        shared_with: row.try_get("shared_with").unwrap_or_default(),
+        columns: row.try_get("columns").unwrap_or(serde_json::json!([])),
+        filters: row.try_get("filters").unwrap_or(serde_json::json!([])),
+        sort: row.try_get("sort").unwrap_or(serde_json::json!([])),
+        settings: row.try_get("settings").unwrap_or(serde_json::json!({})),
+    }
 }
 
 async fn create_view(
@@ -129,8 +216,8 @@ async fn create_view(
 
     sqlx::query(
         r#"
-        INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, created_by, columns, filters, sort, settings, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, false, false, $7, $8, $9, $10, $11, $12, $13)
+        INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, created_by, visibility, columns, filters, sort, settings, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, false, false, $7, $8, $9, $10, $11, $12, $13, $14)
         "#,
     )
     .bind(id)
@@ -140,6 +227,7 @@ async fn create_view(
     .bind(&req.label)
     .bind(&req.view_type)
     .bind(req.created_by)
+    .bind(req.visibility.as_str())
     .bind(&req.columns)
     .bind(&req.filters)
     .bind(&req.sort)
@@ -159,6 +247,8 @@ async fn create_view(
         is_default: false,
         is_system: false,
         created_by: Some(req.created_by),
+        visibility: req.visibility,
+        shared_with: Vec::new(),
         columns: req.columns,
         filters: req.filters,
         sort: req.sort,
@@ -168,37 +258,36 @@ async fn create_view(
 
 async fn get_view(
     axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
     mut conn: RlsConn,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ViewResponse>, ApiError> {
-    use sqlx::Row;
+    let view = fetch_visible_view(&mut conn, tenant.id, Viewer::from_user(user), id).await?;
+    Ok(Json(view))
+}
 
-    let row = sqlx::query(
-        "SELECT id, entity_type_id, name, label, view_type, is_default, is_system, created_by, columns, filters, sort, settings FROM view_defs WHERE id = $1 AND tenant_id = $2"
-    )
-    .bind(id)
-    .bind(tenant.id)
-    .fetch_optional(&mut **conn)
-    .await
-    .map_err(|e| ApiError::Database(e))?;
+/// A view the viewer may see; views they can't see are reported as missing
+async fn fetch_visible_view(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    viewer: Viewer,
+    id: Uuid,
+) -> Result<ViewResponse, ApiError> {
+    let sql = format!(
+        "SELECT {VIEW_COLUMNS} FROM view_defs v WHERE v.id = $1 AND v.tenant_id = $2 AND {}",
+        visible_to(3, 4)
+    );
+    let row = sqlx::query(&sql)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(viewer.user_id)
+        .bind(viewer.is_admin)
+        .fetch_optional(conn)
+        .await
+        .map_err(ApiError::Database)?;
 
-    match row {
-        Some(r) => Ok(Json(ViewResponse {
-            id: r.try_get("id").unwrap_or_default(),
-            entity_type_id: r.try_get("entity_type_id").unwrap_or_default(),
-            name: r.try_get("name").unwrap_or_default(),
-            label: r.try_get("label").unwrap_or_default(),
-            view_type: r.try_get("view_type").unwrap_or_default(),
-            is_default: r.try_get("is_default").unwrap_or(false),
-            is_system: r.try_get("is_system").unwrap_or(false),
-            created_by: r.try_get("created_by").ok(),
-            columns: r.try_get("columns").unwrap_or(serde_json::json!([])),
-            filters: r.try_get("filters").unwrap_or(serde_json::json!([])),
-            sort: r.try_get("sort").unwrap_or(serde_json::json!([])),
-            settings: r.try_get("settings").unwrap_or(serde_json::json!({})),
-        })),
-        None => Err(ApiError::NotFound(format!("View {} not found", id))),
-    }
+    row.map(|r| view_from_row(&r))
+        .ok_or_else(|| ApiError::NotFound(format!("View {} not found", id)))
 }
 
 async fn update_view(
@@ -255,3 +344,415 @@ async fn delete_view(
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CloneViewRequest {
+    pub name: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Copy a view (typically a system one) into an editable view owned by the
+/// requesting user
+async fn clone_view(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(id): Path<Uuid>,
+    req: Option<Json<CloneViewRequest>>,
+) -> Result<Json<ViewResponse>, ApiError> {
+    let viewer = Viewer::from_user(user);
+    let user_id = viewer.user_id.ok_or(ApiError::Unauthorized)?;
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    fetch_visible_view(&mut conn, tenant.id, viewer, id).await?;
+    let view = clone_view_def(&mut conn, tenant.id, user_id, id, &req).await?;
+    Ok(Json(view))
+}
+
+/// Insert a private, user-owned copy of view `id`; the JSON columns are
+/// copied by value, so the two views evolve independently
+async fn clone_view_def(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    id: Uuid,
+    req: &CloneViewRequest,
+) -> Result<ViewResponse, ApiError> {
+    let now = Utc::now();
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, created_by, visibility, columns, filters, sort, settings, created_at, updated_at)
+        SELECT $1, tenant_id, entity_type_id, COALESCE($4, name || '_copy'), COALESCE($5, label || ' (Copy)'), view_type,
+               false, false, $6, 'private', columns, filters, sort, settings, $7, $7
+        FROM view_defs
+        WHERE id = $2 AND tenant_id = $3
+        RETURNING id, entity_type_id, name, label, view_type, is_default, is_system, created_by, visibility, shared_with,
+                  columns, filters, sort, settings
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(id)
+    .bind(tenant_id)
+    .bind(&req.name)
+    .bind(&req.label)
+    .bind(user_id)
+    .bind(now)
+    .fetch_optional(conn)
+    .await
+    .map_err(ApiError::Database)?;
+
+    row.map(|r| view_from_row(&r))
+        .ok_or_else(|| ApiError::NotFound(format!("View {} not found", id)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveColumnsRequest {
+    pub columns: serde_json::Value,
+}
+
+/// Save a view's column layout (widths, order, visibility)
+async fn save_columns(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SaveColumnsRequest>,
+) -> Result<Json<ViewResponse>, ApiError> {
+    let view = save_view_columns(&mut conn, tenant.id, Viewer::from_user(user), id, &req.columns).await?;
+    Ok(Json(view))
+}
+
+/// Write `columns` to the viewer's own view. System views and views shared by
+/// someone else stay as they are: the layout goes to the viewer's personal
+/// override of the view instead, created on first use. Returns the view written.
+async fn save_view_columns(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    viewer: Viewer,
+    id: Uuid,
+    columns: &serde_json::Value,
+) -> Result<ViewResponse, ApiError> {
+    let view = fetch_visible_view(conn, tenant_id, viewer, id).await?;
+    let user_id = viewer.user_id.ok_or(ApiError::Unauthorized)?;
+
+    let (target, overrides) = if !view.is_system && view.created_by == Some(user_id) {
+        (view.id, None)
+    } else {
+        let existing: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM view_defs WHERE tenant_id = $1 AND created_by = $2 AND settings->>'override_of' = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(ApiError::Database)?;
+
+        let target = match existing {
+            Some(existing) => existing,
+            None => {
+                let req = CloneViewRequest { name: Some(view.name.clone()), label: Some(view.label.clone()) };
+                clone_view_def(conn, tenant_id, user_id, id, &req).await?.id
+            }
+        };
+        (target, Some(id))
+    };
+
+    let sql = format!(
+        "UPDATE view_defs v
+         SET columns = $3,
+             settings = CASE WHEN $4::uuid IS NULL THEN v.settings
+                             ELSE v.settings || jsonb_build_object('override_of', $4::text) END,
+             updated_at = $5
+         WHERE v.id = $1 AND v.tenant_id = $2
+         RETURNING {VIEW_COLUMNS}"
+    );
+    let row = sqlx::query(&sql)
+        .bind(target)
+        .bind(tenant_id)
+        .bind(columns)
+        .bind(overrides)
+        .bind(Utc::now())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(view_from_row(&row))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareViewRequest {
+    pub visibility: Option<ViewVisibility>,
+    /// Replaces the explicit share list
+    pub shared_with: Option<Vec<Uuid>>,
+}
+
+/// Change who can see a view; only its creator or an admin may
+async fn share_view(
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ShareViewRequest>,
+) -> Result<Json<ViewResponse>, ApiError> {
+    let view = update_sharing(&mut conn, tenant.id, Viewer::from_user(user), id, &req).await?;
+    Ok(Json(view))
+}
+
+async fn update_sharing(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    viewer: Viewer,
+    id: Uuid,
+    req: &ShareViewRequest,
+) -> Result<ViewResponse, ApiError> {
+    let view = fetch_visible_view(conn, tenant_id, viewer, id).await?;
+    if view.is_system {
+        return Err(ApiError::BadRequest("System views are visible to everyone".to_string()));
+    }
+    if !viewer.can_manage(view.created_by) {
+        return Err(ApiError::Forbidden);
+    }
+
+    if let Some(shared_with) = &req.shared_with {
+        let members: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND id = ANY($2)")
+            .bind(tenant_id)
+            .bind(shared_with)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(ApiError::Database)?;
+        let distinct: std::collections::HashSet<_> = shared_with.iter().collect();
+        if members != distinct.len() as i64 {
+            return Err(ApiError::BadRequest("Views can only be shared with users of this tenant".to_string()));
+        }
+    }
+
+    let sql = format!(
+        "UPDATE view_defs v
+         SET visibility = COALESCE($3, v.visibility), shared_with = COALESCE($4, v.shared_with), updated_at = $5
+         WHERE v.id = $1 AND v.tenant_id = $2
+         RETURNING {VIEW_COLUMNS}"
+    );
+    let row = sqlx::query(&sql)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(req.visibility.map(|v| v.as_str()))
+        .bind(&req.shared_with)
+        .bind(Utc::now())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(view_from_row(&row))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::blueprint::{seed_from_blueprint, EntityBlueprint};
+    use test_support::TestTenant;
+
+    /// Tenant with a `ticket` entity, a system kanban view and two users
+    struct Fixture {
+        tenant: TestTenant,
+        user_a: Uuid,
+        user_b: Uuid,
+        system_view: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Views Test").await;
+            let user_a = tenant.user("a@views.test").await;
+            let user_b = tenant.user("b@views.test").await;
+
+            let blueprint = EntityBlueprint::from_json(
+                r#"{"entities": [{"name": "ticket", "label": "Ticket", "label_plural": "Tickets",
+                    "fields": [
+                        {"name": "subject", "label": "Subject", "field_type": "text"},
+                        {"name": "status", "label": "Status", "field_type": "select"}
+                    ],
+                    "views": [{"name": "board", "label": "Board", "view_type": "kanban", "columns": ["subject"],
+                               "settings": {"group_by_field": "status", "card_fields": ["subject"]}}]
+                }]}"#,
+            )
+            .unwrap();
+            let mut tx = tenant.pool.begin().await.unwrap();
+            seed_from_blueprint(&mut tx, tenant.id, &blueprint).await.unwrap();
+            tx.commit().await.unwrap();
+
+            let system_view = sqlx::query_scalar("SELECT id FROM view_defs WHERE tenant_id = $1 AND name = 'board'")
+                .bind(tenant.id)
+                .fetch_one(&tenant.pool)
+                .await
+                .unwrap();
+            Self { tenant, user_a, user_b, system_view }
+        }
+
+        fn viewer(user_id: Uuid) -> Viewer {
+            Viewer { user_id: Some(user_id), is_admin: false }
+        }
+
+        async fn view(&self, id: Uuid) -> ViewResponse {
+            let row = sqlx::query("SELECT * FROM view_defs WHERE id = $1").bind(id).fetch_one(&self.tenant.pool).await.unwrap();
+            view_from_row(&row)
+        }
+
+        async fn listed(&self, viewer: Viewer) -> Vec<Uuid> {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            let query = ViewQuery { entity_code: Some("ticket".to_string()), ..Default::default() };
+            query_views(&mut conn, self.tenant.id, viewer, &query).await.unwrap().iter().map(|v| v.id).collect()
+        }
+
+        async fn can_get(&self, viewer: Viewer, id: Uuid) -> bool {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            fetch_visible_view(&mut conn, self.tenant.id, viewer, id).await.is_ok()
+        }
+
+        async fn share(&self, viewer: Viewer, id: Uuid, req: ShareViewRequest) -> Result<ViewResponse, ApiError> {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            update_sharing(&mut conn, self.tenant.id, viewer, id, &req).await
+        }
+
+        async fn clone_for(&self, user_id: Uuid) -> ViewResponse {
+            let mut conn = self.tenant.pool.acquire().await.unwrap();
+            clone_view_def(&mut conn, self.tenant.id, user_id, self.system_view, &CloneViewRequest::default())
+                .await
+                .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_clone_is_an_independent_user_view() {
+        let fx = Fixture::new().await;
+        let original = fx.view(fx.system_view).await;
+
+        let clone = fx.clone_for(fx.user_a).await;
+        assert_ne!(clone.id, original.id);
+        assert_eq!((clone.name.as_str(), clone.label.as_str()), ("board_copy", "Board (Copy)"));
+        assert_eq!(clone.view_type, "kanban");
+        assert_eq!(clone.entity_type_id, original.entity_type_id);
+        assert!(!clone.is_system && !clone.is_default);
+        assert_eq!((clone.created_by, clone.visibility), (Some(fx.user_a), ViewVisibility::Private));
+        assert_eq!((&clone.columns, &clone.settings), (&original.columns, &original.settings));
+
+        // Editing the clone leaves the original alone
+        sqlx::query("UPDATE view_defs SET settings = jsonb_set(settings, '{group_by_field}', '\"subject\"'), columns = '[]' WHERE id = $1")
+            .bind(clone.id)
+            .execute(&fx.tenant.pool)
+            .await
+            .unwrap();
+        let after = fx.view(fx.system_view).await;
+        assert_eq!((after.columns, after.settings), (original.columns, original.settings));
+
+        // It shows up in the user's list
+        let listed = fx.listed(Fixture::viewer(fx.user_a)).await;
+        assert!(listed.contains(&clone.id) && listed.contains(&fx.system_view));
+
+        // Views of other tenants can't be cloned
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let missing = clone_view_def(&mut conn, Uuid::new_v4(), fx.user_a, fx.system_view, &CloneViewRequest::default()).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+
+        drop(conn);
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_view_visibility_and_sharing() {
+        let fx = Fixture::new().await;
+        let (a, b) = (Fixture::viewer(fx.user_a), Fixture::viewer(fx.user_b));
+        let view = fx.clone_for(fx.user_a).await;
+
+        // Private: only the owner and admins
+        assert!(fx.can_get(a, view.id).await);
+        assert!(!fx.can_get(b, view.id).await);
+        assert!(!fx.listed(b).await.contains(&view.id));
+        assert!(!fx.can_get(Viewer::default(), view.id).await);
+        assert!(fx.can_get(Viewer { user_id: Some(fx.user_b), is_admin: true }, view.id).await);
+
+        // Only the owner may change sharing
+        let to_tenant = || ShareViewRequest { visibility: Some(ViewVisibility::Tenant), shared_with: None };
+        assert!(matches!(fx.share(b, view.id, to_tenant()).await, Err(ApiError::NotFound(_))));
+
+        // Explicit share
+        let shared = fx.share(a, view.id, ShareViewRequest { visibility: None, shared_with: Some(vec![fx.user_b]) }).await.unwrap();
+        assert_eq!((shared.visibility, shared.shared_with), (ViewVisibility::Private, vec![fx.user_b]));
+        assert!(fx.can_get(b, view.id).await);
+        assert!(matches!(fx.share(b, view.id, to_tenant()).await, Err(ApiError::Forbidden)));
+        let outsider = ShareViewRequest { visibility: None, shared_with: Some(vec![Uuid::new_v4()]) };
+        assert!(matches!(fx.share(a, view.id, outsider).await, Err(ApiError::BadRequest(_))));
+
+        // Team: visible to members of the owner's teams
+        fx.share(a, view.id, ShareViewRequest { visibility: Some(ViewVisibility::Team), shared_with: Some(vec![]) }).await.unwrap();
+        assert!(!fx.can_get(b, view.id).await);
+        let team_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO teams (id, tenant_id, name) VALUES ($1, $2, 'Sales')")
+            .bind(team_id)
+            .bind(fx.tenant.id)
+            .execute(&fx.tenant.pool)
+            .await
+            .unwrap();
+        for user_id in [fx.user_a, fx.user_b] {
+            sqlx::query("INSERT INTO user_teams (user_id, team_id) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(team_id)
+                .execute(&fx.tenant.pool)
+                .await
+                .unwrap();
+        }
+        assert!(fx.can_get(b, view.id).await);
+
+        // Tenant: everyone, including in lists
+        sqlx::query("DELETE FROM user_teams WHERE team_id = $1").bind(team_id).execute(&fx.tenant.pool).await.unwrap();
+        fx.share(a, view.id, to_tenant()).await.unwrap();
+        assert!(fx.listed(b).await.contains(&view.id));
+
+        fx.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_resizing_system_view_creates_personal_override() {
+        let fx = Fixture::new().await;
+        let (a, b) = (Fixture::viewer(fx.user_a), Fixture::viewer(fx.user_b));
+        let original = fx.view(fx.system_view).await;
+        let resized = serde_json::json!([{"field": "subject", "width": "240px", "visible": true, "sort_order": 0}]);
+
+        let mut conn = fx.tenant.pool.acquire().await.unwrap();
+        let personal = save_view_columns(&mut conn, fx.tenant.id, a, fx.system_view, &resized).await.unwrap();
+        assert_ne!(personal.id, fx.system_view);
+        assert_eq!((personal.created_by, personal.visibility), (Some(fx.user_a), ViewVisibility::Private));
+        assert_eq!((personal.name.as_str(), personal.label.as_str()), ("board", "Board"));
+        assert_eq!(personal.columns, resized);
+        assert_eq!(override_of(&personal), Some(fx.system_view));
+        assert_eq!(personal.settings["group_by_field"], "status");
+
+        // The system view is untouched
+        assert_eq!(fx.view(fx.system_view).await.columns, original.columns);
+
+        // Resizing again updates the same override, whichever id is used
+        let narrower = serde_json::json!([{"field": "subject", "width": "120px", "visible": true, "sort_order": 0}]);
+        let again = save_view_columns(&mut conn, fx.tenant.id, a, fx.system_view, &narrower).await.unwrap();
+        assert_eq!(again.id, personal.id);
+        let again = save_view_columns(&mut conn, fx.tenant.id, a, personal.id, &resized).await.unwrap();
+        assert_eq!((again.id, override_of(&again)), (personal.id, Some(fx.system_view)));
+        drop(conn);
+
+        // The owner sees the override in place of the system view; others see the original
+        let listed = fx.listed(a).await;
+        assert!(listed.contains(&personal.id) && !listed.contains(&fx.system_view));
+        let listed = fx.listed(b).await;
+        assert!(listed.contains(&fx.system_view) && !listed.contains(&personal.id));
+        let admin = fx.listed(Viewer { user_id: Some(fx.user_b), is_admin: true }).await;
+        assert!(!admin.contains(&personal.id));
+
+        fx.cleanup().await;
+    }
+}
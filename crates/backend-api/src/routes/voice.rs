@@ -2,20 +2,30 @@
 //!
 //! Provides:
 //! - Twilio Client token generation
-//! - Voice call initiation
-//! - Call recording
-//! - Whisper transcription integration
-//! - Auto-extraction of requirements from transcripts
+//! - Voice call initiation, tracked as a call session that Twilio's status
+//!   callbacks keep up to date
+//! - Call dispositions, logged on the contact's timeline
+//! - Call recording, where the tenant allows it (`settings.voice`)
+//! - Transcripts of recorded calls logged on the contact
+//!   (see `jobs::call_transcription`)
 
 use axum::{
     extract::{Path, Query, State},
-    routing::{get, post},
+    http::StatusCode,
+    routing::{get, post, put},
     Json, Router,
 };
+use core_integrations::encryption::KeyRing;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 use std::sync::Arc;
 
+use crate::jobs::call_transcription::{TRANSCRIBE_RECORDING, VOICE_QUEUE};
+use crate::jobs::{JobOptions, JobQueue};
+use crate::middleware::database::RlsConn;
+use crate::middleware::permission::AuthenticatedUser;
+use crate::middleware::tenant::ResolvedTenant;
 use crate::state::AppState;
 
 /// Twilio capability token request
@@ -43,6 +53,187 @@ pub struct InitiateCallRequest {
     /// Whether to record the call
     #[serde(default)]
     pub record: bool,
+    /// The callee agreed to be recorded; needed when the tenant requires consent
+    #[serde(default)]
+    pub consent_given: bool,
+}
+
+/// A tenant's rules for recording calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordingPolicy {
+    /// Off unless the tenant turns it on
+    pub recording_allowed: bool,
+    pub require_consent: bool,
+}
+
+impl RecordingPolicy {
+    /// Read from the tenant's `settings.voice`
+    pub fn from_settings(settings: &Value) -> Self {
+        let flag = |key: &str| settings.get("voice").and_then(|v| v.get(key)).and_then(Value::as_bool).unwrap_or(false);
+        RecordingPolicy {
+            recording_allowed: flag("recording_allowed"),
+            require_consent: flag("require_consent"),
+        }
+    }
+}
+
+/// Recording status callback from Twilio
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecordingCallback {
+    pub call_sid: String,
+    pub recording_sid: Option<String>,
+    pub recording_url: Option<String>,
+    /// `completed` once the audio can be downloaded
+    pub recording_status: Option<String>,
+    pub recording_duration: Option<i32>,
+}
+
+/// Where a call is, as reported by Twilio's status callbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallState {
+    Queued,
+    Ringing,
+    InProgress,
+    Completed,
+    Busy,
+    NoAnswer,
+    Failed,
+    Canceled,
+}
+
+impl CallState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallState::Queued => "queued",
+            CallState::Ringing => "ringing",
+            CallState::InProgress => "in_progress",
+            CallState::Completed => "completed",
+            CallState::Busy => "busy",
+            CallState::NoAnswer => "no_answer",
+            CallState::Failed => "failed",
+            CallState::Canceled => "canceled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(CallState::Queued),
+            "ringing" => Some(CallState::Ringing),
+            "in_progress" => Some(CallState::InProgress),
+            "completed" => Some(CallState::Completed),
+            "busy" => Some(CallState::Busy),
+            "no_answer" => Some(CallState::NoAnswer),
+            "failed" => Some(CallState::Failed),
+            "canceled" => Some(CallState::Canceled),
+            _ => None,
+        }
+    }
+
+    /// From Twilio's `CallStatus`
+    pub fn from_twilio(status: &str) -> Option<Self> {
+        match status {
+            "queued" | "initiated" => Some(CallState::Queued),
+            "in-progress" | "answered" => Some(CallState::InProgress),
+            "no-answer" => Some(CallState::NoAnswer),
+            other => Self::parse(other),
+        }
+    }
+
+    /// The call is over
+    pub fn is_final(&self) -> bool {
+        !matches!(self, CallState::Queued | CallState::Ringing | CallState::InProgress)
+    }
+
+    fn stage(&self) -> u8 {
+        match self {
+            CallState::Queued => 0,
+            CallState::Ringing => 1,
+            CallState::InProgress => 2,
+            _ => 3,
+        }
+    }
+
+    /// The state a callback reporting `reported` moves the call to; `None`
+    /// when it arrived after the call had got further, or had ended
+    pub fn advance(self, reported: CallState) -> Option<CallState> {
+        (!self.is_final() && reported.stage() > self.stage()).then_some(reported)
+    }
+}
+
+/// How a call went, set by the agent afterwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Disposition {
+    Connected,
+    Voicemail,
+    NoAnswer,
+    Busy,
+    WrongNumber,
+}
+
+impl Disposition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Disposition::Connected => "connected",
+            Disposition::Voicemail => "voicemail",
+            Disposition::NoAnswer => "no_answer",
+            Disposition::Busy => "busy",
+            Disposition::WrongNumber => "wrong_number",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Disposition::Connected => "Connected",
+            Disposition::Voicemail => "Voicemail",
+            Disposition::NoAnswer => "No answer",
+            Disposition::Busy => "Busy",
+            Disposition::WrongNumber => "Wrong number",
+        }
+    }
+}
+
+/// A call placed from the dialer
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CallSession {
+    pub id: Uuid,
+    pub call_sid: String,
+    pub agent_id: Uuid,
+    pub entity_type: Option<String>,
+    pub record_id: Option<Uuid>,
+    pub phone: String,
+    pub direction: String,
+    pub state: String,
+    pub answered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub duration_seconds: Option<i32>,
+    pub disposition: Option<String>,
+    pub notes: Option<String>,
+    /// The call's entry on the contact's timeline, once dispositioned
+    pub interaction_id: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+const CALL_SESSION_COLUMNS: &str = "id, call_sid, agent_id, entity_type, record_id, phone, direction, state, \
+    answered_at, ended_at, duration_seconds, disposition, notes, interaction_id, created_at";
+
+/// Call status callback from Twilio
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StatusCallback {
+    pub call_sid: String,
+    pub call_status: String,
+    /// Seconds, sent once the call has ended
+    pub call_duration: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DispositionRequest {
+    pub disposition: Disposition,
+    #[serde(default)]
+    pub notes: Option<String>,
 }
 
 /// Call status
@@ -55,6 +246,8 @@ pub struct CallStatus {
     pub duration: Option<u32>,
     pub recording_url: Option<String>,
     pub transcription: Option<String>,
+    /// The call session tracking a call placed from the dialer
+    pub session_id: Option<Uuid>,
 }
 
 /// Build voice routes
@@ -64,8 +257,10 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/voice/call", post(initiate_call))
         .route("/voice/call/:call_sid", get(get_call_status))
         .route("/voice/call/:call_sid/end", post(end_call))
-        .route("/voice/webhook/status", post(call_status_webhook))
-        .route("/voice/webhook/recording", post(recording_webhook))
+        .route("/voice/sessions/:session_id", get(get_call_session))
+        .route("/voice/sessions/:session_id/disposition", put(set_disposition))
+        .route("/voice/webhook/status/:session_id", post(call_status_webhook))
+        .route("/voice/webhook/recording/:recording_id", post(recording_webhook))
 }
 
 /// Generate Twilio capability token for browser-based calling
@@ -124,10 +319,34 @@ async fn generate_token(
 }
 
 /// Initiate an outbound call
+///
+/// The call is tracked as a call session of the signed-in agent. A recorded
+/// call must be placed from a contact, so its transcript has somewhere to go.
 async fn initiate_call(
-    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    mut conn: RlsConn,
     Json(request): Json<InitiateCallRequest>,
 ) -> Result<Json<CallStatus>, (axum::http::StatusCode, String)> {
+    let Some(axum::Extension(user)) = user else {
+        return Err((StatusCode::UNAUTHORIZED, "Placing a call requires a signed-in user".to_string()));
+    };
+    let recording = if request.record {
+        let policy = RecordingPolicy::from_settings(&tenant.settings);
+        if !policy.recording_allowed {
+            return Err((StatusCode::FORBIDDEN, "Call recording is turned off for this tenant".to_string()));
+        }
+        if policy.require_consent && !request.consent_given {
+            return Err((StatusCode::BAD_REQUEST, "The callee must consent before the call is recorded".to_string()));
+        }
+        let (Some(entity_id), Some(entity_type)) = (request.entity_id, request.entity_type.clone()) else {
+            return Err((StatusCode::BAD_REQUEST, "Recording a call requires the contact it's with".to_string()));
+        };
+        Some((Uuid::new_v4(), entity_type, entity_id))
+    } else {
+        None
+    };
+
     let account_sid = std::env::var("TWILIO_ACCOUNT_SID")
         .map_err(|_| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Missing TWILIO_ACCOUNT_SID".to_string()))?;
     let auth_token = std::env::var("TWILIO_AUTH_TOKEN")
@@ -142,17 +361,21 @@ async fn initiate_call(
     let base_url = std::env::var("APP_BASE_URL").unwrap_or_else(|_| "https://app.jirsi.com".to_string());
     let twiml_url = format!("{}/api/v1/voice/twiml?to={}", base_url, urlencoding::encode(&request.to));
     
+    let session_id = Uuid::new_v4();
     let mut form = vec![
         ("To", request.to.clone()),
         ("From", from_number.clone()),
         ("Url", twiml_url),
-        ("StatusCallback", format!("{}/api/v1/voice/webhook/status", base_url)),
+        ("StatusCallback", format!("{}/api/v1/voice/webhook/status/{}?tenant_id={}", base_url, session_id, tenant.id)),
         ("StatusCallbackEvent", "initiated ringing answered completed".to_string()),
     ];
     
-    if request.record {
+    if let Some((recording_id, ..)) = &recording {
         form.push(("Record", "true".to_string()));
-        form.push(("RecordingStatusCallback", format!("{}/api/v1/voice/webhook/recording", base_url)));
+        form.push((
+            "RecordingStatusCallback",
+            format!("{}/api/v1/voice/webhook/recording/{}?tenant_id={}", base_url, recording_id, tenant.id),
+        ));
     }
     
     let response = client
@@ -170,21 +393,56 @@ async fn initiate_call(
     
     let json: serde_json::Value = response.json().await
         .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    
-    // Log call in database
-    if let Some(entity_id) = request.entity_id {
-        log_call_interaction(&state.pool, entity_id, &request.entity_type.unwrap_or_default(), 
-            json["sid"].as_str().unwrap_or(""), &request.to).await;
+
+    let call_sid = json["sid"].as_str().unwrap_or("");
+    let call_state = json["status"].as_str().and_then(CallState::from_twilio).unwrap_or(CallState::Queued);
+    sqlx::query(
+        r#"
+        INSERT INTO call_sessions (id, tenant_id, call_sid, agent_id, entity_type, record_id, phone, direction, state)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'outbound', $8)
+        "#
+    )
+    .bind(session_id)
+    .bind(tenant.id)
+    .bind(call_sid)
+    .bind(user.id)
+    .bind(&request.entity_type)
+    .bind(request.entity_id)
+    .bind(&request.to)
+    .bind(call_state.as_str())
+    .execute(&mut **conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // Remember who the recording belongs to, for its webhook
+    if let Some((recording_id, entity_type, entity_id)) = &recording {
+        sqlx::query(
+            r#"
+            INSERT INTO call_recordings (id, tenant_id, call_sid, placed_by, entity_type, record_id, phone)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#
+        )
+        .bind(recording_id)
+        .bind(tenant.id)
+        .bind(call_sid)
+        .bind(user.id)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(&request.to)
+        .execute(&mut **conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     }
-    
+
     Ok(Json(CallStatus {
-        call_sid: json["sid"].as_str().unwrap_or("").to_string(),
+        call_sid: call_sid.to_string(),
         status: json["status"].as_str().unwrap_or("queued").to_string(),
         to: request.to,
         from: from_number,
         duration: None,
         recording_url: None,
         transcription: None,
+        session_id: Some(session_id),
     }))
 }
 
@@ -218,6 +476,7 @@ async fn get_call_status(
         duration: json["duration"].as_str().and_then(|s| s.parse().ok()),
         recording_url: None,
         transcription: None,
+        session_id: None,
     }))
 }
 
@@ -247,109 +506,524 @@ async fn end_call(
     })))
 }
 
+/// Get a call session
+async fn get_call_session(
+    Path(session_id): Path<Uuid>,
+    mut conn: RlsConn,
+) -> Result<Json<CallSession>, (StatusCode, String)> {
+    let session = sqlx::query_as::<_, CallSession>(&format!("SELECT {} FROM call_sessions WHERE id = $1", CALL_SESSION_COLUMNS))
+        .bind(session_id)
+        .fetch_optional(&mut **conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Call session not found".to_string()))?;
+    Ok(Json(session))
+}
+
+/// Set how a call went, once it has ended, and log it on the contact
+///
+/// Setting it again updates the same timeline entry.
+async fn set_disposition(
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path(session_id): Path<Uuid>,
+    mut conn: RlsConn,
+    Json(request): Json<DispositionRequest>,
+) -> Result<Json<CallSession>, (StatusCode, String)> {
+    let internal = |e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    if user.is_none() {
+        return Err((StatusCode::UNAUTHORIZED, "Setting a disposition requires a signed-in user".to_string()));
+    }
+
+    let mut tx = sqlx::Connection::begin(&mut **conn).await.map_err(internal)?;
+    let session = sqlx::query_as::<_, CallSession>(&format!("SELECT {} FROM call_sessions WHERE id = $1 FOR UPDATE", CALL_SESSION_COLUMNS))
+        .bind(session_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "Call session not found".to_string()))?;
+    if !CallState::parse(&session.state).is_some_and(|s| s.is_final()) {
+        return Err((StatusCode::CONFLICT, "The call hasn't ended yet".to_string()));
+    }
+
+    let title = format!("Call with {}: {}", session.phone, request.disposition.label());
+    let metadata = serde_json::json!({
+        "call_sid": session.call_sid,
+        "call_session_id": session.id,
+        "direction": session.direction,
+        "disposition": request.disposition.as_str(),
+    });
+    let interaction_id = match (session.interaction_id, &session.entity_type, session.record_id) {
+        (Some(id), _, _) => {
+            sqlx::query("UPDATE interactions SET title = $2, content = $3, outcome = $4, metadata = $5, updated_at = NOW() WHERE id = $1")
+                .bind(id)
+                .bind(&title)
+                .bind(&request.notes)
+                .bind(request.disposition.as_str())
+                .bind(&metadata)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal)?;
+            Some(id)
+        }
+        (None, Some(entity_type), Some(record_id)) => Some(
+            sqlx::query_scalar(
+                r#"
+                INSERT INTO interactions (tenant_id, entity_type, record_id, interaction_type, title, content, created_by, occurred_at, duration_minutes, outcome, metadata)
+                SELECT tenant_id, $2, $3, 'call', $4, $5, agent_id, COALESCE(answered_at, created_at), (duration_seconds + 59) / 60, $6, $7
+                FROM call_sessions WHERE id = $1
+                RETURNING id
+                "#
+            )
+            .bind(session.id)
+            .bind(entity_type)
+            .bind(record_id)
+            .bind(&title)
+            .bind(&request.notes)
+            .bind(request.disposition.as_str())
+            .bind(&metadata)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(internal)?,
+        ),
+        // A call dialed without a contact has no timeline to go on
+        (None, _, _) => None,
+    };
+
+    let session = sqlx::query_as::<_, CallSession>(&format!(
+        "UPDATE call_sessions SET disposition = $2, notes = $3, interaction_id = $4, updated_at = NOW() WHERE id = $1 RETURNING {}",
+        CALL_SESSION_COLUMNS
+    ))
+    .bind(session_id)
+    .bind(request.disposition.as_str())
+    .bind(&request.notes)
+    .bind(interaction_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    Ok(Json(session))
+}
+
 /// Webhook for call status updates
+///
+/// Callbacks can arrive out of order; one reporting a state the call has
+/// already passed is ignored.
 async fn call_status_webhook(
-    State(state): State<Arc<AppState>>,
-    axum::Form(payload): axum::Form<std::collections::HashMap<String, String>>,
-) -> &'static str {
-    tracing::info!("Call status webhook: {:?}", payload);
-    
-    // Update call status in database
-    if let (Some(call_sid), Some(status)) = (payload.get("CallSid"), payload.get("CallStatus")) {
-        tracing::info!(call_sid = %call_sid, status = %status, "Call status updated");
-    }
-    
-    "OK"
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Path(session_id): Path<Uuid>,
+    mut conn: RlsConn,
+    axum::Form(callback): axum::Form<StatusCallback>,
+) -> Result<&'static str, (StatusCode, String)> {
+    let internal = |e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    let Some(reported) = CallState::from_twilio(&callback.call_status) else {
+        tracing::warn!(call_sid = %callback.call_sid, status = %callback.call_status, "Unknown call status");
+        return Ok("OK");
+    };
+
+    let mut tx = sqlx::Connection::begin(&mut **conn).await.map_err(internal)?;
+    let current: Option<String> = sqlx::query_scalar(
+        "SELECT state FROM call_sessions WHERE id = $1 AND tenant_id = $2 AND call_sid = $3 FOR UPDATE"
+    )
+    .bind(session_id)
+    .bind(tenant.id)
+    .bind(&callback.call_sid)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?;
+    let current = current
+        .as_deref()
+        .and_then(CallState::parse)
+        .ok_or((StatusCode::NOT_FOUND, "Call session not found".to_string()))?;
+    let Some(next) = current.advance(reported) else {
+        return Ok("OK");
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE call_sessions SET
+            state = $2,
+            answered_at = CASE WHEN $2 = 'in_progress' THEN NOW() ELSE answered_at END,
+            ended_at = CASE WHEN $3 THEN NOW() ELSE ended_at END,
+            duration_seconds = CASE
+                WHEN $3 THEN COALESCE($4, EXTRACT(EPOCH FROM NOW() - answered_at)::INTEGER, 0)
+                ELSE duration_seconds
+            END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(session_id)
+    .bind(next.as_str())
+    .bind(next.is_final())
+    .bind(callback.call_duration)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    tracing::info!(call_sid = %callback.call_sid, state = next.as_str(), "Call status updated");
+    Ok("OK")
 }
 
 /// Webhook for recording completion
+///
+/// Stores the recording URL encrypted and queues its transcription. If the
+/// tenant has turned recording off since the call was placed, the recording
+/// is discarded instead.
 async fn recording_webhook(
     State(state): State<Arc<AppState>>,
-    axum::Form(payload): axum::Form<std::collections::HashMap<String, String>>,
-) -> &'static str {
-    tracing::info!("Recording webhook: {:?}", payload);
-    
-    if let Some(recording_url) = payload.get("RecordingUrl") {
-        let call_sid = payload.get("CallSid").map(|s| s.as_str()).unwrap_or("");
-        
-        // Trigger transcription with Whisper
-        tokio::spawn(async move {
-            if let Err(e) = transcribe_recording(recording_url).await {
-                tracing::error!("Transcription failed: {}", e);
-            }
-        });
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    Path(recording_id): Path<Uuid>,
+    mut conn: RlsConn,
+    axum::Form(callback): axum::Form<RecordingCallback>,
+) -> Result<&'static str, (StatusCode, String)> {
+    let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    if callback.recording_status.as_deref().is_some_and(|s| s != "completed") {
+        return Ok("OK");
     }
-    
-    "OK"
-}
 
-/// Transcribe recording using OpenAI Whisper
-async fn transcribe_recording(recording_url: &str) -> Result<String, String> {
-    let api_key = std::env::var("OPENAI_API_KEY")
-        .map_err(|_| "Missing OPENAI_API_KEY".to_string())?;
-    
-    // Download the recording
-    let client = reqwest::Client::new();
-    let audio_bytes = client.get(recording_url)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .bytes()
-        .await
-        .map_err(|e| e.to_string())?;
-    
-    // Send to Whisper API
-    let form = reqwest::multipart::Form::new()
-        .part("file", reqwest::multipart::Part::bytes(audio_bytes.to_vec())
-            .file_name("recording.wav")
-            .mime_str("audio/wav")
-            .unwrap())
-        .text("model", "whisper-1")
-        .text("language", "en");
-    
-    let response = client
-        .post("https://api.openai.com/v1/audio/transcriptions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-    
-    if !response.status().is_success() {
-        return Err("Whisper API error".to_string());
+    let status: Option<String> = sqlx::query_scalar(
+        "SELECT status FROM call_recordings WHERE id = $1 AND tenant_id = $2 AND call_sid = $3"
+    )
+    .bind(recording_id)
+    .bind(tenant.id)
+    .bind(&callback.call_sid)
+    .fetch_optional(&mut **conn)
+    .await
+    .map_err(|e| internal(e.to_string()))?;
+    match status.as_deref() {
+        None => return Err((StatusCode::NOT_FOUND, "Recording not found".to_string())),
+        // Twilio retried a callback we already handled
+        Some(s) if s != "recording" => return Ok("OK"),
+        Some(_) => {}
+    }
+
+    if !RecordingPolicy::from_settings(&tenant.settings).recording_allowed {
+        sqlx::query("UPDATE call_recordings SET status = 'discarded', updated_at = NOW() WHERE id = $1")
+            .bind(recording_id)
+            .execute(&mut **conn)
+            .await
+            .map_err(|e| internal(e.to_string()))?;
+        tracing::info!(call_sid = %callback.call_sid, "Recording discarded; recording is off for the tenant");
+        return Ok("OK");
     }
-    
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    let transcription = json["text"].as_str().unwrap_or("").to_string();
-    
-    tracing::info!("Transcription complete: {} chars", transcription.len());
-    
-    // TODO: Extract requirements using RAG
-    // extract_requirements_from_transcript(&transcription).await;
-    
-    Ok(transcription)
-}
 
-/// Log call as an interaction
-async fn log_call_interaction(
-    pool: &sqlx::PgPool,
-    entity_id: Uuid,
-    entity_type: &str,
-    call_sid: &str,
-    phone: &str,
-) {
-    let _ = sqlx::query(
+    let url = callback
+        .recording_url
+        .as_deref()
+        .ok_or((StatusCode::BAD_REQUEST, "Missing RecordingUrl".to_string()))?;
+    let encrypted = KeyRing::from_env().encrypt(url.as_bytes()).map_err(internal)?;
+
+    // The job is only queued if the URL is stored
+    let mut tx = sqlx::Connection::begin(&mut **conn).await.map_err(|e| internal(e.to_string()))?;
+    sqlx::query(
         r#"
-        INSERT INTO interactions (id, tenant_id, entity_id, entity_type, interaction_type, direction, subject, metadata)
-        VALUES ($1, $2, $3, $4, 'call', 'outbound', $5, $6)
+        UPDATE call_recordings
+        SET recording_sid = $2, recording_url_encrypted = $3, duration_seconds = $4, status = 'queued', updated_at = NOW()
+        WHERE id = $1
         "#
     )
-    .bind(Uuid::new_v4())
-    .bind(Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap()) // TODO: Get from context
-    .bind(entity_id)
-    .bind(entity_type)
-    .bind(format!("Call to {}", phone))
-    .bind(serde_json::json!({"call_sid": call_sid, "phone": phone}))
-    .execute(pool)
-    .await;
+    .bind(recording_id)
+    .bind(&callback.recording_sid)
+    .bind(encrypted)
+    .bind(callback.recording_duration)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| internal(e.to_string()))?;
+
+    let options = JobOptions { lock_key: Some(recording_id.to_string()), ..Default::default() };
+    JobQueue::new(state.pool.clone(), VOICE_QUEUE)
+        .enqueue_with(
+            TRANSCRIBE_RECORDING.to_string(),
+            serde_json::json!({ "tenant_id": tenant.id, "recording_id": recording_id }),
+            options,
+        )
+        .await
+        .map_err(|e| internal(e.to_string()))?;
+    tx.commit().await.map_err(|e| internal(e.to_string()))?;
+
+    Ok("OK")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_support::get_test_pool;
+
+    #[test]
+    fn test_recording_is_off_unless_the_tenant_allows_it() {
+        assert_eq!(RecordingPolicy::from_settings(&json!({})), RecordingPolicy::default());
+        assert_eq!(
+            RecordingPolicy::from_settings(&json!({ "voice": { "recording_allowed": true, "require_consent": true } })),
+            RecordingPolicy { recording_allowed: true, require_consent: true }
+        );
+    }
+
+    #[test]
+    fn test_status_callbacks_only_move_calls_forward() {
+        use CallState::*;
+        assert_eq!(CallState::from_twilio("in-progress"), Some(InProgress));
+        assert_eq!(CallState::from_twilio("no-answer"), Some(NoAnswer));
+        assert_eq!(CallState::from_twilio("initiated"), Some(Queued));
+
+        assert_eq!(Queued.advance(Ringing), Some(Ringing));
+        assert_eq!(Ringing.advance(InProgress), Some(InProgress));
+        assert_eq!(InProgress.advance(Completed), Some(Completed));
+        // Unanswered calls end without passing through in-progress
+        assert_eq!(Ringing.advance(NoAnswer), Some(NoAnswer));
+        assert_eq!(Queued.advance(Failed), Some(Failed));
+        // Late and repeated callbacks are ignored
+        assert_eq!(InProgress.advance(Ringing), None);
+        assert_eq!(Ringing.advance(Ringing), None);
+        assert_eq!(Completed.advance(Canceled), None);
+        assert_eq!(Busy.advance(InProgress), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_disposition_logs_call_on_contact() {
+        let pool = get_test_pool().await;
+        let (tenant_id, agent_id, contact_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Dialer Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("dialer-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, 'agent@example.com', 'Agent', 'x')")
+            .bind(agent_id)
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let tenant = ResolvedTenant {
+            id: tenant_id,
+            name: "Dialer Test".into(),
+            subdomain: format!("dialer-{}", tenant_id.simple()),
+            settings: json!({}),
+            status: "active".into(),
+            trial_ends_at: None,
+        };
+        let agent = AuthenticatedUser {
+            id: agent_id,
+            tenant_id,
+            email: "agent@example.com".into(),
+            name: "Agent".into(),
+            role: "agent".into(),
+            roles: vec![],
+        };
+        let session_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO call_sessions (tenant_id, call_sid, agent_id, entity_type, record_id, phone)
+             VALUES ($1, 'CA-dialer-test', $2, 'contact', $3, '+971500000000') RETURNING id",
+        )
+        .bind(tenant_id)
+        .bind(agent_id)
+        .bind(contact_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let conn = || async { RlsConn(pool.acquire().await.unwrap()) };
+        let status = |call_status: &str, call_duration: Option<i32>| {
+            let callback = StatusCallback { call_sid: "CA-dialer-test".into(), call_status: call_status.into(), call_duration };
+            let tenant = tenant.clone();
+            async move { call_status_webhook(axum::Extension(tenant), Path(session_id), conn().await, axum::Form(callback)).await }
+        };
+        let session = || async { get_call_session(Path(session_id), conn().await).await.unwrap().0 };
+        let dispose = |disposition: Disposition, notes: &str| {
+            let request = DispositionRequest { disposition, notes: Some(notes.to_string()) };
+            let agent = agent.clone();
+            async move { set_disposition(Some(axum::Extension(agent)), Path(session_id), conn().await, Json(request)).await }
+        };
+
+        status("ringing", None).await.unwrap();
+        status("in-progress", None).await.unwrap();
+        let answered = session().await;
+        assert_eq!(answered.state, "in_progress");
+        assert!(answered.answered_at.is_some() && answered.ended_at.is_none());
+
+        // Nothing to disposition until the call has ended
+        let (code, _) = dispose(Disposition::Voicemail, "").await.unwrap_err();
+        assert_eq!(code, StatusCode::CONFLICT);
+
+        status("completed", Some(125)).await.unwrap();
+        // A ringing callback that arrives late changes nothing
+        status("ringing", None).await.unwrap();
+        let ended = session().await;
+        assert_eq!((ended.state.as_str(), ended.duration_seconds), ("completed", Some(125)));
+        assert!(ended.ended_at.is_some());
+
+        let dispositioned = dispose(Disposition::Voicemail, "Left a message about the viewing").await.unwrap().0;
+        assert_eq!(dispositioned.disposition.as_deref(), Some("voicemail"));
+        let timeline = || async {
+            sqlx::query_as::<_, (Uuid, String, String, Option<String>, Option<String>, Uuid, Option<i32>)>(
+                "SELECT record_id, interaction_type, title, content, outcome, created_by, duration_minutes
+                 FROM interactions WHERE tenant_id = $1",
+            )
+            .bind(tenant_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+        assert_eq!(
+            timeline().await,
+            vec![(
+                contact_id,
+                "call".to_string(),
+                "Call with +971500000000: Voicemail".to_string(),
+                Some("Left a message about the viewing".to_string()),
+                Some("voicemail".to_string()),
+                agent_id,
+                Some(3),
+            )]
+        );
+        assert!(dispositioned.interaction_id.is_some());
+
+        // Correcting the disposition updates the same entry
+        let corrected = dispose(Disposition::Connected, "Booked a viewing").await.unwrap().0;
+        assert_eq!(corrected.interaction_id, dispositioned.interaction_id);
+        let entries = timeline().await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].2, "Call with +971500000000: Connected");
+
+        for sql in [
+            "DELETE FROM call_sessions WHERE tenant_id = $1",
+            "DELETE FROM inbox_read_state WHERE tenant_id = $1",
+            "DELETE FROM interactions WHERE tenant_id = $1",
+            "DELETE FROM inbox_threads WHERE tenant_id = $1",
+            "DELETE FROM users WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_recording_webhook_queues_transcription() {
+        let pool = get_test_pool().await;
+        let (tenant_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let settings = json!({ "voice": { "recording_allowed": true } });
+        sqlx::query("INSERT INTO tenants (id, name, subdomain, settings) VALUES ($1, 'Voice Test', $2, $3)")
+            .bind(tenant_id)
+            .bind(format!("voice-{}", tenant_id.simple()))
+            .bind(&settings)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, 'agent@example.com', 'Agent', 'x')")
+            .bind(user_id)
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let tenant = ResolvedTenant {
+            id: tenant_id,
+            name: "Voice Test".into(),
+            subdomain: format!("voice-{}", tenant_id.simple()),
+            settings,
+            status: "active".into(),
+            trial_ends_at: None,
+        };
+        let state = Arc::new(AppState::new(pool.clone()));
+
+        let place_call = |call_sid: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, Uuid>(
+                    "INSERT INTO call_recordings (tenant_id, call_sid, placed_by, entity_type, record_id, phone)
+                     VALUES ($1, $2, $3, 'contact', $4, '+971500000000') RETURNING id",
+                )
+                .bind(tenant_id)
+                .bind(call_sid)
+                .bind(user_id)
+                .bind(Uuid::new_v4())
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+            }
+        };
+        let recording_url = "https://api.twilio.com/2010-04-01/Accounts/AC1/Recordings/RE1";
+        let webhook = |tenant: ResolvedTenant, recording_id: Uuid, call_sid: &str| {
+            let state = state.clone();
+            let callback = RecordingCallback {
+                call_sid: call_sid.to_string(),
+                recording_sid: Some("RE1".into()),
+                recording_url: Some(recording_url.into()),
+                recording_status: Some("completed".into()),
+                recording_duration: Some(95),
+            };
+            async move {
+                let conn = RlsConn(state.pool.acquire().await.unwrap());
+                recording_webhook(State(state), axum::Extension(tenant), Path(recording_id), conn, axum::Form(callback)).await
+            }
+        };
+        let queued_jobs = |recording_id: Uuid| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_as::<_, (String, Value)>(
+                    "SELECT job_type, payload FROM jobs WHERE queue_name = $1 AND payload->>'recording_id' = $2",
+                )
+                .bind(VOICE_QUEUE)
+                .bind(recording_id.to_string())
+                .fetch_all(&pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        let recording_id = place_call("CA-voice-test-1").await;
+        assert_eq!(webhook(tenant.clone(), recording_id, "CA-voice-test-1").await.unwrap(), "OK");
+        let jobs = queued_jobs(recording_id).await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].0, TRANSCRIBE_RECORDING);
+        assert_eq!(jobs[0].1["tenant_id"], json!(tenant_id));
+
+        // Stored encrypted, never in the clear
+        let (status, stored): (String, Vec<u8>) =
+            sqlx::query_as("SELECT status, recording_url_encrypted FROM call_recordings WHERE id = $1")
+                .bind(recording_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(status, "queued");
+        assert!(!String::from_utf8_lossy(&stored).contains("twilio.com"));
+        assert_eq!(KeyRing::from_env().decrypt(&stored).unwrap(), recording_url.as_bytes());
+
+        // Twilio retrying the callback doesn't queue the job twice
+        webhook(tenant.clone(), recording_id, "CA-voice-test-1").await.unwrap();
+        assert_eq!(queued_jobs(recording_id).await.len(), 1);
+        // Nor does a callback naming another call
+        let (code, _) = webhook(tenant.clone(), recording_id, "CA-someone-else").await.unwrap_err();
+        assert_eq!(code, StatusCode::NOT_FOUND);
+
+        // Once the tenant turns recording off, new recordings are discarded
+        let recording_off = ResolvedTenant { settings: json!({ "voice": { "recording_allowed": false } }), ..tenant };
+        let discarded_id = place_call("CA-voice-test-2").await;
+        webhook(recording_off, discarded_id, "CA-voice-test-2").await.unwrap();
+        assert!(queued_jobs(discarded_id).await.is_empty());
+        let (status, stored): (String, Option<Vec<u8>>) =
+            sqlx::query_as("SELECT status, recording_url_encrypted FROM call_recordings WHERE id = $1")
+                .bind(discarded_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), stored), ("discarded", None));
+
+        sqlx::query("DELETE FROM jobs WHERE queue_name = $1 AND payload->>'tenant_id' = $2")
+            .bind(VOICE_QUEUE)
+            .bind(tenant_id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        for sql in [
+            "DELETE FROM call_recordings WHERE tenant_id = $1",
+            "DELETE FROM users WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
 }
@@ -0,0 +1,334 @@
+//! Webhook subscription routes
+//!
+//! Tenant admins point subscriptions at their own endpoints to be told when
+//! records change; the jobs worker sends the events. Event types are
+//! `<entity_type>.<event>` (e.g. `contact.create`), with `*` for either
+//! side. The signing secret is in the create response only. Each
+//! subscription's deliveries are its log, and dead-lettered ones can be
+//! retried.
+
+use axum::{
+    Router,
+    routing::{get, post, put},
+    extract::{State, Path, Query},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use core_integrations::egress::EgressPolicy;
+use core_integrations::encryption::{generate_webhook_secret, KeyRing};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::error::ApiError;
+use crate::middleware::tenant::ResolvedTenant;
+use crate::middleware::permission::{is_admin, AuthenticatedUser};
+
+/// Attempts per delivery unless the subscription says otherwise
+const DEFAULT_MAX_ATTEMPTS: i32 = 8;
+
+/// Deliveries per page of a subscription's log
+const DELIVERY_PAGE_SIZE: i64 = 50;
+
+const SUBSCRIPTION_COLUMNS: &str = "id, tenant_id, target_url, event_types, max_attempts, is_active, created_at, updated_at";
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_subscriptions).post(create_subscription))
+        .route("/:id", put(update_subscription).delete(delete_subscription))
+        .route("/:id/deliveries", get(list_deliveries))
+        .route("/:id/deliveries/:delivery_id/retry", post(retry_delivery))
+}
+
+fn require_admin(user: Option<axum::Extension<AuthenticatedUser>>) -> Result<AuthenticatedUser, ApiError> {
+    let axum::Extension(user) = user.ok_or(ApiError::Unauthorized)?;
+    if is_admin(&user) {
+        Ok(user)
+    } else {
+        Err(ApiError::Forbidden)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub target_url: String,
+    pub event_types: Vec<String>,
+    pub max_attempts: i32,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedSubscription {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    /// Shown this once; deliveries carry `X-Jirsi-Signature` made with it
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSubscriptionRequest {
+    pub target_url: String,
+    pub event_types: Vec<String>,
+    pub max_attempts: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSubscriptionRequest {
+    pub target_url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub max_attempts: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+/// One entry of a subscription's delivery log
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    /// pending, delivered or dead
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryQuery {
+    pub status: Option<String>,
+    /// Deliveries created before this one's
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// An http(s) URL whose host resolves to public addresses only; the worker
+/// checks again before each send
+async fn validate_target_url(target_url: &str) -> Result<(), ApiError> {
+    EgressPolicy::public_only()
+        .check(target_url)
+        .await
+        .map(|_| ())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid target URL: {}", e)))
+}
+
+/// Each type is `<entity_type>.<event>`, either side a name or `*`
+fn validate_event_types(event_types: &[String]) -> Result<(), ApiError> {
+    if event_types.is_empty() {
+        return Err(ApiError::BadRequest("Subscribe to at least one event type".to_string()));
+    }
+    let is_part = |part: &str| {
+        part == "*" || (!part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':')))
+    };
+    for event_type in event_types {
+        let valid = event_type == "*"
+            || event_type.split_once('.').is_some_and(|(entity, event)| is_part(entity) && is_part(event));
+        if !valid {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid event type '{}'; expected <entity_type>.<event> such as contact.create",
+                event_type
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_max_attempts(max_attempts: Option<i32>) -> Result<(), ApiError> {
+    if max_attempts.is_some_and(|n| !(1..=20).contains(&n)) {
+        return Err(ApiError::BadRequest("max_attempts must be between 1 and 20".to_string()));
+    }
+    Ok(())
+}
+
+async fn list_subscriptions(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+) -> Result<Json<Vec<WebhookSubscription>>, ApiError> {
+    require_admin(user)?;
+    let subscriptions = sqlx::query_as(&format!(
+        "SELECT {} FROM webhook_subscriptions WHERE tenant_id = $1 ORDER BY created_at",
+        SUBSCRIPTION_COLUMNS
+    ))
+    .bind(tenant.id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(subscriptions))
+}
+
+async fn create_subscription(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Json(req): Json<CreateSubscriptionRequest>,
+) -> Result<(StatusCode, Json<CreatedSubscription>), ApiError> {
+    let user = require_admin(user)?;
+    validate_target_url(&req.target_url).await?;
+    validate_event_types(&req.event_types)?;
+    validate_max_attempts(req.max_attempts)?;
+
+    let secret = generate_webhook_secret();
+    let encrypted = KeyRing::from_env().encrypt(secret.as_bytes()).map_err(ApiError::Internal)?;
+    let subscription: WebhookSubscription = sqlx::query_as(&format!(
+        "INSERT INTO webhook_subscriptions (tenant_id, target_url, event_types, secret_encrypted, max_attempts, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING {}",
+        SUBSCRIPTION_COLUMNS
+    ))
+    .bind(tenant.id)
+    .bind(&req.target_url)
+    .bind(&req.event_types)
+    .bind(encrypted)
+    .bind(req.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS))
+    .bind(user.id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    tracing::info!(tenant_id = %tenant.id, subscription_id = %subscription.id, "Webhook subscription created");
+    Ok((StatusCode::CREATED, Json(CreatedSubscription { subscription, secret })))
+}
+
+async fn update_subscription(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateSubscriptionRequest>,
+) -> Result<Json<WebhookSubscription>, ApiError> {
+    require_admin(user)?;
+    if let Some(target_url) = &req.target_url {
+        validate_target_url(target_url).await?;
+    }
+    if let Some(event_types) = &req.event_types {
+        validate_event_types(event_types)?;
+    }
+    validate_max_attempts(req.max_attempts)?;
+
+    sqlx::query_as(&format!(
+        "UPDATE webhook_subscriptions
+         SET target_url = COALESCE($3, target_url),
+             event_types = COALESCE($4, event_types),
+             max_attempts = COALESCE($5, max_attempts),
+             is_active = COALESCE($6, is_active),
+             updated_at = NOW()
+         WHERE tenant_id = $1 AND id = $2
+         RETURNING {}",
+        SUBSCRIPTION_COLUMNS
+    ))
+    .bind(tenant.id)
+    .bind(id)
+    .bind(&req.target_url)
+    .bind(&req.event_types)
+    .bind(req.max_attempts)
+    .bind(req.is_active)
+    .fetch_optional(&state.pool)
+    .await?
+    .map(Json)
+    .ok_or_else(|| ApiError::NotFound("Webhook subscription not found".to_string()))
+}
+
+async fn delete_subscription(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(user)?;
+    let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE tenant_id = $1 AND id = $2")
+        .bind(tenant.id)
+        .bind(id)
+        .execute(&state.pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Webhook subscription not found".to_string()));
+    }
+    tracing::info!(tenant_id = %tenant.id, subscription_id = %id, "Webhook subscription deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Newest first, a page at a time
+async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeliveryQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>, ApiError> {
+    require_admin(user)?;
+    let deliveries = sqlx::query_as(
+        "SELECT id, event_id, event_type, status, attempts, next_attempt_at, last_status_code, last_error, created_at, delivered_at
+         FROM webhook_deliveries
+         WHERE tenant_id = $1 AND subscription_id = $2
+           AND ($3::TEXT IS NULL OR status = $3)
+           AND ($4::TIMESTAMPTZ IS NULL OR created_at < $4)
+         ORDER BY created_at DESC
+         LIMIT $5",
+    )
+    .bind(tenant.id)
+    .bind(id)
+    .bind(&query.status)
+    .bind(query.before)
+    .bind(DELIVERY_PAGE_SIZE)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(deliveries))
+}
+
+/// Put a dead-lettered delivery back in the queue with a fresh set of attempts
+async fn retry_delivery(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(tenant): axum::Extension<ResolvedTenant>,
+    user: Option<axum::Extension<AuthenticatedUser>>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(user)?;
+    let result = sqlx::query(
+        "UPDATE webhook_deliveries
+         SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+         WHERE tenant_id = $1 AND subscription_id = $2 AND id = $3 AND status = 'dead'",
+    )
+    .bind(tenant.id)
+    .bind(id)
+    .bind(delivery_id)
+    .execute(&state.pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("No dead-lettered delivery with that id".to_string()));
+    }
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_event_types_name_an_entity_and_an_event() {
+        assert!(validate_event_types(&types(&["contact.create", "deal.*", "*.delete", "*", "lead.custom:qualified"])).is_ok());
+        assert!(validate_event_types(&[]).is_err());
+        for invalid in ["contact", "contact.", ".create", "contact.create.extra", "con tact.create"] {
+            assert!(validate_event_types(&types(&[invalid])).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_target_url_must_be_public_http() {
+        assert!(validate_target_url("https://93.184.216.34/jirsi").await.is_ok());
+        assert!(validate_target_url("ftp://hooks.example.com").await.is_err());
+        assert!(validate_target_url("not a url").await.is_err());
+        for internal in ["http://localhost:8080/hook", "http://169.254.169.254/latest/meta-data/", "http://10.0.0.5/", "http://[::1]/"] {
+            assert!(validate_target_url(internal).await.is_err(), "{} should be rejected", internal);
+        }
+    }
+}
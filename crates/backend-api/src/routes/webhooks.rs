@@ -2,7 +2,10 @@
 //!
 //! These endpoints receive webhooks from Twilio, Facebook, etc.
 //! NO AUTH - Tenant resolution from URL path only.
-//! Security via signature validation.
+//! Security via signature validation, once the integration has a webhook
+//! secret. Providers redeliver on timeouts and errors, so each event is
+//! processed once per replay window and repeats are acknowledged without
+//! running again.
 
 use axum::{
     body::Bytes,
@@ -12,11 +15,18 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use core_integrations::models::SystemEvent;
+use core_integrations::providers::{facebook::FacebookHandler, twilio::TwilioHandler};
+use core_integrations::webhook::{MessageStatusUpdate, SignatureError, WebhookError};
+use core_integrations::{Provider, WebhookHandler, WhatsAppStatusWebhook};
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::error::ApiError;
+use crate::routes::lead_capture;
 use crate::state::AppState;
 
 /// Webhook query params
@@ -34,6 +44,8 @@ pub struct WebhookQuery {
 /// Create webhook routes - NO AUTH MIDDLEWARE
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
+        // Public lead forms; the token stands in for the tenant
+        .route("/webhooks/lead/:tenant_token", post(lead_capture::receive_lead))
         .route("/webhooks/:tenant_id/:provider", get(verify_webhook))
         .route("/webhooks/:tenant_id/:provider", post(receive_webhook))
 }
@@ -87,7 +99,7 @@ async fn verify_webhook(
 async fn receive_webhook(
     State(state): State<Arc<AppState>>,
     Path((tenant_id, provider)): Path<(String, String)>,
-    _headers: HeaderMap,
+    headers: HeaderMap,
     body: Bytes,
 ) -> impl IntoResponse {
     // Parse tenant ID
@@ -141,6 +153,14 @@ async fn receive_webhook(
         return StatusCode::FORBIDDEN;
     }
 
+    let handler = Provider::from_str(&provider).and_then(|p| handler_for(&state, tenant_uuid, p));
+    if let Some(handler) = &handler {
+        if let Err(e) = verify_signature(handler.as_ref(), &headers, &body, &webhook_secret) {
+            warn!(tenant_id = %tenant_uuid, provider = %provider, error = %e, "Webhook signature rejected");
+            return StatusCode::UNAUTHORIZED;
+        }
+    }
+
     // Log the webhook
     let _ = sqlx::query(
         r#"
@@ -155,6 +175,28 @@ async fn receive_webhook(
     .execute(&state.pool)
     .await;
 
+    if let Some(handler) = &handler {
+        match process_delivery(&state, tenant_uuid, handler.as_ref(), &headers, body).await {
+            Ok(Delivery::Processed { events }) => {
+                info!(tenant_id = %tenant_uuid, provider = %provider, events, "Webhook events processed");
+            }
+            Ok(Delivery::Duplicate) => {
+                // The provider retried something we already handled; acknowledge it again
+                info!(tenant_id = %tenant_uuid, provider = %provider, "Duplicate webhook delivery skipped");
+                return StatusCode::OK;
+            }
+            Err(DeliveryError::Unreadable(e)) => {
+                warn!(tenant_id = %tenant_uuid, provider = %provider, error = %e, "Unreadable webhook payload");
+                return StatusCode::BAD_REQUEST;
+            }
+            Err(DeliveryError::Failed(e)) => {
+                error!(tenant_id = %tenant_uuid, provider = %provider, error = %e, "Webhook processing failed");
+                // Non-2xx so the provider redelivers
+                return StatusCode::INTERNAL_SERVER_ERROR;
+            }
+        }
+    }
+
     // Update last webhook timestamp
     let _ = sqlx::query(
         "UPDATE integrations SET last_webhook_at = NOW(), webhook_success_count = webhook_success_count + 1 WHERE tenant_id = $1 AND provider = $2"
@@ -166,9 +208,248 @@ async fn receive_webhook(
 
     info!(tenant_id = %tenant_uuid, provider = %provider, "Webhook processed");
 
-    // Suppress unused variable warning
-    let _ = webhook_secret;
-    
     // Return 200 OK quickly to acknowledge receipt
     StatusCode::OK
 }
+
+/// Handler for a provider's deliveries; `None` for providers we only log
+fn handler_for(state: &AppState, tenant_id: Uuid, provider: Provider) -> Option<Box<dyn WebhookHandler>> {
+    match provider {
+        Provider::Twilio => {
+            // Twilio signs the URL it posted to
+            let base_url = std::env::var("WEBHOOK_BASE_URL")
+                .unwrap_or_else(|_| "https://api.jirsi.com".to_string());
+            let url = format!("{}/webhooks/{}/{}", base_url, tenant_id, provider.as_str());
+            Some(Box::new(TwilioHandler::new(url)))
+        }
+        Provider::Facebook => Some(Box::new(FacebookHandler::new(String::new()))),
+        Provider::WhatsApp => Some(Box::new(WhatsAppStatusWebhook::new(state.pool.clone()))),
+        Provider::Email => None,
+    }
+}
+
+/// Check a delivery's signature. Integrations set up without a webhook
+/// secret have nothing to check against, so their deliveries are let through
+fn verify_signature(
+    handler: &dyn WebhookHandler,
+    headers: &HeaderMap,
+    body: &[u8],
+    secret: &str,
+) -> Result<(), SignatureError> {
+    if secret.is_empty() {
+        warn!(provider = %handler.provider().as_str(), "No webhook secret configured, signature not checked");
+        return Ok(());
+    }
+    handler.verify(headers, body, secret)
+}
+
+/// What became of a signed delivery
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Delivery {
+    Processed { events: usize },
+    /// Its events were already processed within the replay window
+    Duplicate,
+}
+
+#[derive(Debug)]
+pub(crate) enum DeliveryError {
+    /// The payload doesn't parse; a redelivery won't either
+    Unreadable(WebhookError),
+    /// Applying an event failed; its claim is released so a redelivery
+    /// retries it
+    Failed(ApiError),
+}
+
+/// Run a delivery's events, each at most once. Events are claimed one by
+/// one under the delivery's replay key and their position in it, so when
+/// one fails a redelivery skips the events before it instead of repeating
+/// them.
+pub(crate) async fn process_delivery(
+    state: &Arc<AppState>,
+    tenant_id: Uuid,
+    handler: &dyn WebhookHandler,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Delivery, DeliveryError> {
+    let provider = handler.provider();
+    let key = handler.replay_key(&body, headers);
+    let events = handler
+        .handle_webhook(tenant_id, body, headers)
+        .map_err(DeliveryError::Unreadable)?;
+
+    let count = events.len();
+    let mut applied = 0;
+    for (index, event) in events.into_iter().enumerate() {
+        let event_key = format!("{}#{}", key, index);
+        let claimed = state
+            .webhook_replay
+            .claim(tenant_id, provider, &event_key)
+            .await
+            .map_err(|e| DeliveryError::Failed(e.into()))?;
+        if !claimed {
+            continue;
+        }
+
+        if let Err(e) = apply_event(state, tenant_id, event).await {
+            if let Err(release_err) = state.webhook_replay.release(tenant_id, provider, &event_key).await {
+                warn!(tenant_id = %tenant_id, error = %release_err, "Failed to release webhook replay key");
+            }
+            return Err(DeliveryError::Failed(e));
+        }
+        applied += 1;
+    }
+
+    if count > 0 && applied == 0 {
+        return Ok(Delivery::Duplicate);
+    }
+    Ok(Delivery::Processed { events: applied })
+}
+
+/// Act on one event a provider delivery produced
+async fn apply_event(state: &Arc<AppState>, tenant_id: Uuid, event: SystemEvent) -> Result<(), ApiError> {
+    match event {
+        SystemEvent::ContactCreated { email, phone, first_name, last_name, source, external_id, .. } => {
+            let contact: Map<String, Value> = [("email", email), ("phone", phone), ("first_name", first_name), ("last_name", last_name)]
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|v| (key.to_string(), Value::String(v))))
+                .collect();
+            if let Some(id) = lead_capture::capture_provider_lead(state, tenant_id, contact).await? {
+                info!(tenant_id = %tenant_id, contact_id = %id, source = %source, external_id = %external_id, "Lead created from webhook");
+            }
+        }
+        SystemEvent::MessageStatusUpdated { external_id, status, error, .. } => {
+            let update = MessageStatusUpdate { provider_message_id: external_id, status, error };
+            WhatsAppStatusWebhook::new(state.pool.clone())
+                .apply_updates(tenant_id, &[update])
+                .await?;
+        }
+        SystemEvent::InteractionCreated { .. } | SystemEvent::WebhookReceived { .. } => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use test_support::get_test_pool;
+
+    fn facebook_leads(leads: &[(&str, &str)]) -> Bytes {
+        let changes: Vec<Value> = leads
+            .iter()
+            .map(|(leadgen_id, email)| {
+                serde_json::json!({
+                    "field": "leadgen",
+                    "value": {
+                        "form_id": "444",
+                        "leadgen_id": leadgen_id,
+                        "page_id": "153125381133",
+                        "field_data": [
+                            { "name": "full_name", "values": ["Layla Haddad"] },
+                            { "name": "email", "values": [email] }
+                        ]
+                    }
+                })
+            })
+            .collect();
+        let payload = serde_json::json!({
+            "object": "page",
+            "entry": [{ "id": "153125381133", "time": 1700000000, "changes": changes }]
+        });
+        Bytes::from(payload.to_string())
+    }
+
+    fn facebook_lead(leadgen_id: &str, email: &str) -> Bytes {
+        facebook_leads(&[(leadgen_id, email)])
+    }
+
+    #[test]
+    fn test_signature_only_checked_when_a_secret_is_configured() {
+        let handler = FacebookHandler::new(String::new());
+        let body = facebook_lead("9001", "layla@example.com");
+
+        assert!(verify_signature(&handler, &HeaderMap::new(), &body, "").is_ok());
+        assert!(verify_signature(&handler, &HeaderMap::new(), &body, "app-secret").is_err());
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"app-secret").unwrap();
+        mac.update(&body);
+        let mut headers = HeaderMap::new();
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        headers.insert("X-Hub-Signature-256", signature.parse().unwrap());
+        assert!(verify_signature(&handler, &headers, &body, "app-secret").is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_redelivered_facebook_lead_creates_one_contact() {
+        let pool = get_test_pool().await;
+        let (tenant_id, entity_type_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Webhook Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("webhook-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural)
+             VALUES ($1, $2, 'crm', 'contact', 'Contact', 'Contacts')",
+        )
+        .bind(entity_type_id)
+        .bind(tenant_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for name in ["first_name", "last_name", "email", "lifecycle_stage"] {
+            sqlx::query(
+                "INSERT INTO field_defs (id, tenant_id, entity_type_id, name, label, field_type)
+                 VALUES (gen_random_uuid(), $1, $2, $3, $3, 'text')",
+            )
+            .bind(tenant_id)
+            .bind(entity_type_id)
+            .bind(name)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let state = Arc::new(AppState::new(pool.clone()));
+        let handler = FacebookHandler::new(String::new());
+        let headers = HeaderMap::new();
+        let deliver = |body: Bytes| process_delivery(&state, tenant_id, &handler, &headers, body);
+
+        assert_eq!(deliver(facebook_lead("9001", "layla@example.com")).await.unwrap(), Delivery::Processed { events: 1 });
+        // Facebook retries the same lead after a timeout
+        assert_eq!(deliver(facebook_lead("9001", "layla@example.com")).await.unwrap(), Delivery::Duplicate);
+        // A different lead is processed even when its contents match
+        assert_eq!(deliver(facebook_lead("9002", "layla@example.com")).await.unwrap(), Delivery::Processed { events: 1 });
+
+        // An earlier attempt applied the first of two leads, then failed:
+        // the redelivery applies only the second
+        let both = facebook_leads(&[("9003", "omar@example.com"), ("9004", "sara@example.com")]);
+        let key = handler.replay_key(&both, &headers);
+        assert!(state.webhook_replay.claim(tenant_id, Provider::Facebook, &format!("{}#0", key)).await.unwrap());
+        assert_eq!(deliver(both).await.unwrap(), Delivery::Processed { events: 1 });
+
+        let emails: Vec<Option<String>> =
+            sqlx::query_scalar("SELECT data->>'email' FROM entity_records WHERE tenant_id = $1 ORDER BY 1")
+                .bind(tenant_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let mut expected = vec![Some("layla@example.com".to_string()); 2];
+        expected.push(Some("sara@example.com".to_string()));
+        assert_eq!(emails, expected);
+
+        for sql in [
+            "DELETE FROM processed_webhook_events WHERE tenant_id = $1",
+            "DELETE FROM event_outbox WHERE tenant_id = $1",
+            "DELETE FROM entity_records WHERE tenant_id = $1",
+            "DELETE FROM field_defs WHERE tenant_id = $1",
+            "DELETE FROM entity_types WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
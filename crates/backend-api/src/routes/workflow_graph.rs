@@ -16,6 +16,9 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use core_models::{EdgeDef, NodeDef, NodeType};
+use core_node_engine::ports::validate_port_types;
+
 use crate::state::AppState;
 
 /// Node definition for the visual editor
@@ -404,6 +407,8 @@ fn validate_graph(nodes: &[GraphNode], edges: &[GraphEdge]) -> Result<(), String
         return Err("Graph contains a cycle - workflows must be acyclic".to_string());
     }
 
+    check_port_types(nodes, edges)?;
+
     // Count trigger nodes
     let trigger_count = nodes.iter()
         .filter(|n| n.node_type.starts_with("trigger"))
@@ -416,6 +421,27 @@ fn validate_graph(nodes: &[GraphNode], edges: &[GraphEdge]) -> Result<(), String
     Ok(())
 }
 
+/// Check that edges connect compatible port types. Nodes whose type the
+/// engine does not know are left unchecked.
+fn check_port_types(nodes: &[GraphNode], edges: &[GraphEdge]) -> Result<(), String> {
+    let engine_nodes: Vec<NodeDef> = nodes
+        .iter()
+        .filter_map(|n| {
+            let node_type: NodeType = serde_json::from_value(json!(n.node_type)).ok()?;
+            Some(NodeDef { id: n.id, ..NodeDef::new(Uuid::nil(), node_type, &n.label, n.x, n.y) })
+        })
+        .collect();
+    let engine_edges: Vec<EdgeDef> = edges
+        .iter()
+        .map(|e| EdgeDef {
+            id: e.id,
+            ..EdgeDef::new(Uuid::nil(), e.source_node, &e.source_port, e.target_node, &e.target_port)
+        })
+        .collect();
+
+    validate_port_types(&engine_nodes, &engine_edges).map_err(|e| e.to_string())
+}
+
 /// Check if the graph has a cycle using DFS
 fn has_cycle(nodes: &[GraphNode], edges: &[GraphEdge]) -> bool {
     let node_ids: HashSet<Uuid> = nodes.iter().map(|n| n.id).collect();
@@ -59,6 +59,23 @@ pub enum WsEvent {
         entity_id: Uuid,
         interaction_type: String,
     },
+    /// Inbox threads merged into `thread_id`
+    ThreadsMerged {
+        thread_id: Uuid,
+        merged_thread_ids: Vec<Uuid>,
+    },
+    /// Messages of `thread_id` moved to `new_thread_id`
+    ThreadSplit {
+        thread_id: Uuid,
+        new_thread_id: Uuid,
+    },
+    /// Unread counts changed for a thread; `user_id` is the only affected
+    /// agent (with their new total), or `None` when every agent should refetch
+    UnreadCountChanged {
+        thread_id: Uuid,
+        user_id: Option<Uuid>,
+        unread_count: Option<i64>,
+    },
     /// Webhook received
     WebhookReceived {
         provider: String,
@@ -5,6 +5,8 @@ use core_auth::password::hash_password;
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+pub mod blueprint;
+
 // ============================================================================
 // PUBLIC API: New Tenant Seeding (Transactional)
 // ============================================================================
@@ -12,21 +14,25 @@ use uuid::Uuid;
 /// Seeds all required data for a new tenant within a transaction.
 /// If any step fails, the entire operation is rolled back.
 /// 
-/// This is the main entry point for tenant onboarding.
-pub async fn seed_new_tenant(tenant_id: Uuid, pool: &PgPool) -> Result<(), SeedError> {
+/// This is the main entry point for tenant onboarding. Every step checks
+/// for its rows before inserting, so a tenant that was partially seeded can
+/// simply be seeded again.
+pub async fn seed_new_tenant(tenant_id: Uuid, pool: &PgPool) -> Result<SeedSummary, SeedError> {
     let mut tx = pool.begin().await?;
+    let mut summary = SeedSummary::default();
     
     // Seed in order of dependencies
-    seed_entity_metadata_tx(&mut tx, tenant_id).await?;
-    seed_associations_tx(&mut tx, tenant_id).await?;
-    seed_views_tx(&mut tx, tenant_id).await?;
-    seed_standard_workflows_tx(&mut tx, tenant_id).await?;
-    seed_property_entity_tx(&mut tx, tenant_id).await?;
-    seed_listing_entity_tx(&mut tx, tenant_id).await?;
-    seed_viewing_entity_tx(&mut tx, tenant_id).await?;
+    seed_entity_metadata_tx(&mut tx, &mut summary, tenant_id).await?;
+    // Before the views, which include the property table and map
+    seed_property_entity_tx(&mut tx, &mut summary, tenant_id).await?;
+    seed_associations_tx(&mut tx, &mut summary, tenant_id).await?;
+    seed_views_tx(&mut tx, &mut summary, tenant_id).await?;
+    seed_standard_workflows_tx(&mut tx, &mut summary, tenant_id).await?;
+    seed_listing_entity_tx(&mut tx, &mut summary, tenant_id).await?;
+    seed_viewing_entity_tx(&mut tx, &mut summary, tenant_id).await?;
     
     tx.commit().await?;
-    Ok(())
+    Ok(summary)
 }
 
 /// Error type for seeding operations
@@ -34,6 +40,7 @@ pub async fn seed_new_tenant(tenant_id: Uuid, pool: &PgPool) -> Result<(), SeedE
 pub enum SeedError {
     Database(sqlx::Error),
     PasswordHash(String),
+    InvalidBlueprint(String),
 }
 
 impl From<sqlx::Error> for SeedError {
@@ -47,45 +54,148 @@ impl std::fmt::Display for SeedError {
         match self {
             SeedError::Database(e) => write!(f, "Database error: {}", e),
             SeedError::PasswordHash(e) => write!(f, "Password hash error: {}", e),
+            SeedError::InvalidBlueprint(e) => write!(f, "Invalid blueprint: {}", e),
         }
     }
 }
 
 impl std::error::Error for SeedError {}
 
-// ============================================================================
-// TRANSACTIONAL SEEDERS (for new tenant onboarding)
-// ============================================================================
+/// What a tenant seeding run inserted and what it found already there
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SeedSummary {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+}
 
-/// Seed entity types and field definitions within a transaction
-async fn seed_entity_metadata_tx(
+impl SeedSummary {
+    /// Whether the run found everything already seeded
+    pub fn is_all_skipped(&self) -> bool {
+        self.created.is_empty()
+    }
+}
+
+/// A row the tenant seeders insert, identified by its natural key
+enum SeedItem<'a> {
+    EntityType(&'a str),
+    Field { entity_type_id: Uuid, entity: &'a str, name: &'a str },
+    View { entity_type_id: Uuid, entity: &'a str, name: &'a str },
+    Association(&'a str),
+    Workflow(&'a str),
+}
+
+impl std::fmt::Display for SeedItem<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeedItem::EntityType(name) => write!(f, "entity_type:{}", name),
+            SeedItem::Field { entity, name, .. } => write!(f, "field:{}.{}", entity, name),
+            SeedItem::View { entity, name, .. } => write!(f, "view:{}.{}", entity, name),
+            SeedItem::Association(name) => write!(f, "association:{}", name),
+            SeedItem::Workflow(name) => write!(f, "workflow:{}", name),
+        }
+    }
+}
+
+/// Look `item` up and record it in `summary`; true when it still needs inserting
+async fn needs_insert_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
-) -> Result<(), sqlx::Error> {
-    let now = Utc::now();
+    item: SeedItem<'_>,
+) -> Result<bool, sqlx::Error> {
+    let (sql, scope, name) = match &item {
+        SeedItem::EntityType(name) => ("SELECT 1 FROM entity_types WHERE tenant_id = $1 AND name = $2", tenant_id, *name),
+        SeedItem::Field { entity_type_id, name, .. } => {
+            ("SELECT 1 FROM field_defs WHERE entity_type_id = $1 AND name = $2", *entity_type_id, *name)
+        }
+        SeedItem::View { entity_type_id, name, .. } => {
+            ("SELECT 1 FROM view_defs WHERE entity_type_id = $1 AND name = $2", *entity_type_id, *name)
+        }
+        SeedItem::Association(name) => ("SELECT 1 FROM association_defs WHERE tenant_id = $1 AND name = $2", tenant_id, *name),
+        SeedItem::Workflow(name) => ("SELECT 1 FROM workflow_defs WHERE tenant_id = $1 AND name = $2", tenant_id, *name),
+    };
+    let exists = sqlx::query(sql).bind(scope).bind(name).fetch_optional(&mut **tx).await?.is_some();
 
-    // Create Contact entity type
-    let contact_id = Uuid::new_v4();
+    if exists {
+        summary.skipped.push(item.to_string());
+    } else {
+        summary.created.push(item.to_string());
+    }
+    Ok(!exists)
+}
+
+/// Insert an entity type unless the tenant already has one by that name;
+/// returns its id either way
+#[allow(clippy::too_many_arguments)]
+async fn ensure_entity_type_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
+    tenant_id: Uuid,
+    app_id: &str,
+    name: &str,
+    label: &str,
+    label_plural: &str,
+    icon: &str,
+    flags: serde_json::Value,
+) -> Result<Uuid, sqlx::Error> {
+    use sqlx::Row;
+
+    if !needs_insert_tx(tx, summary, tenant_id, SeedItem::EntityType(name)).await? {
+        return sqlx::query("SELECT id FROM entity_types WHERE tenant_id = $1 AND name = $2")
+            .bind(tenant_id)
+            .bind(name)
+            .fetch_one(&mut **tx)
+            .await?
+            .try_get("id");
+    }
+
+    let now = Utc::now();
+    let id = Uuid::new_v4();
     sqlx::query(
         r#"
         INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
-        VALUES ($1, $2, 'crm', 'contact', 'Contact', 'Contacts', 'user', $3, $4, $5)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         "#
     )
-    .bind(contact_id)
+    .bind(id)
     .bind(tenant_id)
-    .bind(serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true}))
+    .bind(app_id)
+    .bind(name)
+    .bind(label)
+    .bind(label_plural)
+    .bind(icon)
+    .bind(flags)
     .bind(now)
     .bind(now)
     .execute(&mut **tx)
     .await?;
 
+    Ok(id)
+}
+
+// ============================================================================
+// TRANSACTIONAL SEEDERS (for new tenant onboarding)
+// ============================================================================
+
+/// Seed entity types and field definitions within a transaction
+async fn seed_entity_metadata_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
+    tenant_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    // Create Contact entity type
+    let contact_id = ensure_entity_type_tx(
+        tx, summary, tenant_id, "crm", "contact", "Contact", "Contacts", "user",
+        serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true}),
+    )
+    .await?;
+
     // Contact fields
     // Contact fields
-    seed_field_tx(tx, tenant_id, contact_id, "first_name", "First Name", "text", true, true, 1, None).await?;
-    seed_field_tx(tx, tenant_id, contact_id, "last_name", "Last Name", "text", true, true, 2, None).await?;
-    seed_field_tx(tx, tenant_id, contact_id, "email", "Email", "email", false, true, 3, None).await?;
-    seed_field_tx(tx, tenant_id, contact_id, "phone", "Phone", "phone", false, true, 4, None).await?;
+    seed_field_tx(tx, summary, tenant_id, contact_id, "contact", "first_name", "First Name", "text", true, true, 1, None).await?;
+    seed_field_tx(tx, summary, tenant_id, contact_id, "contact", "last_name", "Last Name", "text", true, true, 2, None).await?;
+    seed_field_tx(tx, summary, tenant_id, contact_id, "contact", "email", "Email", "email", false, true, 3, None).await?;
+    seed_field_tx(tx, summary, tenant_id, contact_id, "contact", "phone", "Phone", "phone", false, true, 4, None).await?;
     
     let lifecycle_options = serde_json::json!([
         {"value": "subscriber", "label": "Subscriber"},
@@ -96,28 +206,19 @@ async fn seed_entity_metadata_tx(
         {"value": "evangelist", "label": "Evangelist"},
         {"value": "other", "label": "Other"}
     ]);
-    seed_field_tx(tx, tenant_id, contact_id, "lifecycle_stage", "Lifecycle Stage", "select", false, true, 5, Some(lifecycle_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, contact_id, "contact", "lifecycle_stage", "Lifecycle Stage", "select", false, true, 5, Some(lifecycle_options)).await?;
 
     // Create Company entity type
-    let company_id = Uuid::new_v4();
-    sqlx::query(
-        r#"
-        INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
-        VALUES ($1, $2, 'crm', 'company', 'Company', 'Companies', 'building', $3, $4, $5)
-        "#
+    let company_id = ensure_entity_type_tx(
+        tx, summary, tenant_id, "crm", "company", "Company", "Companies", "building",
+        serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true}),
     )
-    .bind(company_id)
-    .bind(tenant_id)
-    .bind(serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true}))
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
     .await?;
 
     // Company fields
     // Company fields
-    seed_field_tx(tx, tenant_id, company_id, "name", "Company Name", "text", true, true, 1, None).await?;
-    seed_field_tx(tx, tenant_id, company_id, "domain", "Domain", "url", false, true, 2, None).await?;
+    seed_field_tx(tx, summary, tenant_id, company_id, "company", "name", "Company Name", "text", true, true, 1, None).await?;
+    seed_field_tx(tx, summary, tenant_id, company_id, "company", "domain", "Domain", "url", false, true, 2, None).await?;
     
     let industry_options = serde_json::json!([
         {"value": "tech", "label": "Technology"},
@@ -127,30 +228,21 @@ async fn seed_entity_metadata_tx(
         {"value": "manufacturing", "label": "Manufacturing"},
         {"value": "other", "label": "Other"}
     ]);
-    seed_field_tx(tx, tenant_id, company_id, "industry", "Industry", "select", false, true, 3, Some(industry_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, company_id, "company", "industry", "Industry", "select", false, true, 3, Some(industry_options)).await?;
     
-    seed_field_tx(tx, tenant_id, company_id, "phone", "Phone", "phone", false, true, 4, None).await?;
+    seed_field_tx(tx, summary, tenant_id, company_id, "company", "phone", "Phone", "phone", false, true, 4, None).await?;
 
     // Create Deal entity type
-    let deal_id = Uuid::new_v4();
-    sqlx::query(
-        r#"
-        INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
-        VALUES ($1, $2, 'crm', 'deal', 'Deal', 'Deals', 'dollar-sign', $3, $4, $5)
-        "#
+    let deal_id = ensure_entity_type_tx(
+        tx, summary, tenant_id, "crm", "deal", "Deal", "Deals", "dollar-sign",
+        serde_json::json!({"has_pipeline": true, "has_activities": true, "is_searchable": true, "show_in_nav": true}),
     )
-    .bind(deal_id)
-    .bind(tenant_id)
-    .bind(serde_json::json!({"has_pipeline": true, "has_activities": true, "is_searchable": true, "show_in_nav": true}))
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
     .await?;
 
     // Deal fields
     // Deal fields
-    seed_field_tx(tx, tenant_id, deal_id, "name", "Deal Name", "text", true, true, 1, None).await?;
-    seed_field_tx(tx, tenant_id, deal_id, "amount", "Amount", "money", false, true, 2, None).await?;
+    seed_field_tx(tx, summary, tenant_id, deal_id, "deal", "name", "Deal Name", "text", true, true, 1, None).await?;
+    seed_field_tx(tx, summary, tenant_id, deal_id, "deal", "amount", "Amount", "money", false, true, 2, None).await?;
     
     let stage_options = serde_json::json!([
         {"value": "appointment_scheduled", "label": "Appointment Scheduled"},
@@ -161,9 +253,9 @@ async fn seed_entity_metadata_tx(
         {"value": "closed_won", "label": "Closed Won"},
         {"value": "closed_lost", "label": "Closed Lost"}
     ]);
-    seed_field_tx(tx, tenant_id, deal_id, "stage", "Stage", "select", true, true, 3, Some(stage_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, deal_id, "deal", "stage", "Stage", "select", true, true, 3, Some(stage_options)).await?;
     
-    seed_field_tx(tx, tenant_id, deal_id, "expected_close_date", "Expected Close", "date", false, true, 4, None).await?;
+    seed_field_tx(tx, summary, tenant_id, deal_id, "deal", "expected_close_date", "Expected Close", "date", false, true, 4, None).await?;
 
     Ok(())
 }
@@ -171,30 +263,20 @@ async fn seed_entity_metadata_tx(
 /// Seed Property entity type for Real Estate
 async fn seed_property_entity_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
 ) -> Result<(), sqlx::Error> {
-    let now = Utc::now();
-
     // Create Property entity type
-    let property_id = Uuid::new_v4();
-    sqlx::query(
-        r#"
-        INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
-        VALUES ($1, $2, 'real_estate', 'property', 'Property', 'Properties', 'home', $3, $4, $5)
-        "#
+    let property_id = ensure_entity_type_tx(
+        tx, summary, tenant_id, "real_estate", "property", "Property", "Properties", "home",
+        serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true, "has_map": true}),
     )
-    .bind(property_id)
-    .bind(tenant_id)
-    .bind(serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true, "has_map": true}))
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
     .await?;
 
     // Property fields
     // Property fields
-    seed_field_tx(tx, tenant_id, property_id, "title", "Title", "text", true, true, 1, None).await?;
-    seed_field_tx(tx, tenant_id, property_id, "price", "Price", "money", true, true, 2, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "title", "Title", "text", true, true, 1, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "price", "Price", "money", true, true, 2, None).await?;
     
     let status_options = serde_json::json!([
         {"value": "active", "label": "Active"},
@@ -203,7 +285,7 @@ async fn seed_property_entity_tx(
         {"value": "rented", "label": "Rented"},
         {"value": "withdrawn", "label": "Withdrawn"}
     ]);
-    seed_field_tx(tx, tenant_id, property_id, "status", "Status", "select", true, true, 3, Some(status_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "status", "Status", "select", true, true, 3, Some(status_options)).await?;
     
     let type_options = serde_json::json!([
         {"value": "apartment", "label": "Apartment"},
@@ -211,24 +293,29 @@ async fn seed_property_entity_tx(
         {"value": "commercial", "label": "Commercial"},
         {"value": "land", "label": "Land"}
     ]);
-    seed_field_tx(tx, tenant_id, property_id, "property_type", "Property Type", "select", false, true, 4, Some(type_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "property_type", "Property Type", "select", false, true, 4, Some(type_options)).await?;
     
-    seed_field_tx(tx, tenant_id, property_id, "bedrooms", "Bedrooms", "number", false, true, 5, None).await?;
-    seed_field_tx(tx, tenant_id, property_id, "bathrooms", "Bathrooms", "number", false, true, 6, None).await?;
-    seed_field_tx(tx, tenant_id, property_id, "area_sqm", "Area (sqm)", "number", false, true, 7, None).await?;
-    seed_field_tx(tx, tenant_id, property_id, "address", "Address", "text", false, true, 8, None).await?;
-    seed_field_tx(tx, tenant_id, property_id, "city", "City", "text", false, true, 9, None).await?;
-    seed_field_tx(tx, tenant_id, property_id, "description", "Description", "textarea", false, false, 10, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "bedrooms", "Bedrooms", "number", false, true, 5, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "bathrooms", "Bathrooms", "number", false, true, 6, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "area_sqm", "Area (sqm)", "number", false, true, 7, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "address", "Address", "text", false, true, 8, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "city", "City", "text", false, true, 9, None).await?;
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "description", "Description", "textarea", false, false, 10, None).await?;
+    // Only published, active properties appear on the public site
+    seed_field_tx(tx, summary, tenant_id, property_id, "property", "published", "Published on Website", "boolean", false, true, 11, None).await?;
 
     Ok(())
 }
 
 /// Helper to seed a single field definition within a transaction
 /// Now includes Antigravity Diamond layers with sensible defaults
+#[allow(clippy::too_many_arguments)]
 async fn seed_field_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
     entity_type_id: Uuid,
+    entity: &str,
     name: &str,
     label: &str,
     field_type: &str,
@@ -237,6 +324,10 @@ async fn seed_field_tx(
     sort_order: i32,
     options: Option<serde_json::Value>,
 ) -> Result<(), sqlx::Error> {
+    if !needs_insert_tx(tx, summary, tenant_id, SeedItem::Field { entity_type_id, entity, name }).await? {
+        return Ok(());
+    }
+
     let now = Utc::now();
     let id = Uuid::new_v4();
     
@@ -299,65 +390,74 @@ async fn seed_field_tx(
 /// Seed association definitions within a transaction
 async fn seed_associations_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
 ) -> Result<(), sqlx::Error> {
     let now = Utc::now();
 
     // Contact ↔ Company (many contacts can work at one company)
-    sqlx::query(
-        r#"
-        INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
-        VALUES ($1, $2, 'contact', 'company', 'contact_company', 'Company', 'Contacts', 'many_to_one', 'employee', 'employer', true, false, $3, $4)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Association("contact_company")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, 'contact', 'company', 'contact_company', 'Company', 'Contacts', 'many_to_one', 'employee', 'employer', true, false, $3, $4)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // Deal ↔ Contact (deals can be linked to multiple contacts)
-    sqlx::query(
-        r#"
-        INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
-        VALUES ($1, $2, 'deal', 'contact', 'deal_contact', 'Contacts', 'Deals', 'many_to_many', NULL, NULL, true, false, $3, $4)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Association("deal_contact")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, 'deal', 'contact', 'deal_contact', 'Contacts', 'Deals', 'many_to_many', NULL, NULL, true, false, $3, $4)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // Deal ↔ Company
-    sqlx::query(
-        r#"
-        INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
-        VALUES ($1, $2, 'deal', 'company', 'deal_company', 'Company', 'Deals', 'many_to_one', NULL, NULL, true, false, $3, $4)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Association("deal_company")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, 'deal', 'company', 'deal_company', 'Company', 'Deals', 'many_to_one', NULL, NULL, true, false, $3, $4)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // Contact ↔ Property (buyer interest)
-    sqlx::query(
-        r#"
-        INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
-        VALUES ($1, $2, 'contact', 'property', 'contact_property', 'Properties', 'Interested Contacts', 'many_to_many', 'buyer', NULL, false, false, $3, $4)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Association("contact_property")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, 'contact', 'property', 'contact_property', 'Properties', 'Interested Contacts', 'many_to_many', 'buyer', NULL, false, false, $3, $4)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     Ok(())
 }
@@ -365,6 +465,7 @@ async fn seed_associations_tx(
 /// Seed view definitions within a transaction
 async fn seed_views_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
 ) -> Result<(), sqlx::Error> {
     use sqlx::Row;
@@ -404,20 +505,23 @@ async fn seed_views_tx(
             {"field": "phone", "width": "150", "visible": true, "sort_order": 4}
         ]);
 
-        sqlx::query(
-            r#"
-            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
-            VALUES ($1, $2, $3, 'default_table', 'All Contacts', 'table', true, true, $4, '[]', '[]', '{}', $5, $6)
-            "#
-        )
-        .bind(Uuid::new_v4())
-        .bind(tenant_id)
-        .bind(entity_id)
-        .bind(columns)
-        .bind(now)
-        .bind(now)
-        .execute(&mut **tx)
-        .await?;
+        let view = SeedItem::View { entity_type_id: entity_id, entity: "contact", name: "default_table" };
+        if needs_insert_tx(tx, summary, tenant_id, view).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+                VALUES ($1, $2, $3, 'default_table', 'All Contacts', 'table', true, true, $4, '[]', '[]', '{}', $5, $6)
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(columns)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
     }
 
     // Company - Default Table View
@@ -428,20 +532,23 @@ async fn seed_views_tx(
             {"field": "industry", "width": "150", "visible": true, "sort_order": 3}
         ]);
 
-        sqlx::query(
-            r#"
-            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
-            VALUES ($1, $2, $3, 'default_table', 'All Companies', 'table', true, true, $4, '[]', '[]', '{}', $5, $6)
-            "#
-        )
-        .bind(Uuid::new_v4())
-        .bind(tenant_id)
-        .bind(entity_id)
-        .bind(columns)
-        .bind(now)
-        .bind(now)
-        .execute(&mut **tx)
-        .await?;
+        let view = SeedItem::View { entity_type_id: entity_id, entity: "company", name: "default_table" };
+        if needs_insert_tx(tx, summary, tenant_id, view).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+                VALUES ($1, $2, $3, 'default_table', 'All Companies', 'table', true, true, $4, '[]', '[]', '{}', $5, $6)
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(columns)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
     }
 
     // Deal - Kanban View (Pipeline)
@@ -455,20 +562,23 @@ async fn seed_views_tx(
         });
 
         // Kanban view
-        sqlx::query(
-            r#"
-            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
-            VALUES ($1, $2, $3, 'pipeline', 'Pipeline', 'kanban', true, true, '[]', '[]', '[]', $4, $5, $6)
-            "#
-        )
-        .bind(Uuid::new_v4())
-        .bind(tenant_id)
-        .bind(entity_id)
-        .bind(kanban_settings)
-        .bind(now)
-        .bind(now)
-        .execute(&mut **tx)
-        .await?;
+        let view = SeedItem::View { entity_type_id: entity_id, entity: "deal", name: "pipeline" };
+        if needs_insert_tx(tx, summary, tenant_id, view).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+                VALUES ($1, $2, $3, 'pipeline', 'Pipeline', 'kanban', true, true, '[]', '[]', '[]', $4, $5, $6)
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(kanban_settings)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
 
         // Also add a table view for deals
         let table_columns = serde_json::json!([
@@ -478,20 +588,23 @@ async fn seed_views_tx(
             {"field": "expected_close_date", "width": "150", "visible": true, "sort_order": 4}
         ]);
 
-        sqlx::query(
-            r#"
-            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
-            VALUES ($1, $2, $3, 'deals_table', 'All Deals', 'table', false, true, $4, '[]', '[]', '{}', $5, $6)
-            "#
-        )
-        .bind(Uuid::new_v4())
-        .bind(tenant_id)
-        .bind(entity_id)
-        .bind(table_columns)
-        .bind(now)
-        .bind(now)
-        .execute(&mut **tx)
-        .await?;
+        let view = SeedItem::View { entity_type_id: entity_id, entity: "deal", name: "deals_table" };
+        if needs_insert_tx(tx, summary, tenant_id, view).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+                VALUES ($1, $2, $3, 'deals_table', 'All Deals', 'table', false, true, $4, '[]', '[]', '{}', $5, $6)
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(table_columns)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
     }
 
     // Property - Table and Map Views
@@ -504,20 +617,23 @@ async fn seed_views_tx(
             {"field": "city", "width": "120", "visible": true, "sort_order": 5}
         ]);
 
-        sqlx::query(
-            r#"
-            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
-            VALUES ($1, $2, $3, 'default_table', 'All Properties', 'table', true, true, $4, '[]', '[]', '{}', $5, $6)
-            "#
-        )
-        .bind(Uuid::new_v4())
-        .bind(tenant_id)
-        .bind(entity_id)
-        .bind(columns)
-        .bind(now)
-        .bind(now)
-        .execute(&mut **tx)
-        .await?;
+        let view = SeedItem::View { entity_type_id: entity_id, entity: "property", name: "default_table" };
+        if needs_insert_tx(tx, summary, tenant_id, view).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+                VALUES ($1, $2, $3, 'default_table', 'All Properties', 'table', true, true, $4, '[]', '[]', '{}', $5, $6)
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(columns)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
 
         // Map view for properties
         let map_settings = serde_json::json!({
@@ -527,20 +643,23 @@ async fn seed_views_tx(
             "popup_fields": ["price", "status", "property_type"]
         });
 
-        sqlx::query(
-            r#"
-            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
-            VALUES ($1, $2, $3, 'map_view', 'Map View', 'map', false, true, '[]', '[]', '[]', $4, $5, $6)
-            "#
-        )
-        .bind(Uuid::new_v4())
-        .bind(tenant_id)
-        .bind(entity_id)
-        .bind(map_settings)
-        .bind(now)
-        .bind(now)
-        .execute(&mut **tx)
-        .await?;
+        let view = SeedItem::View { entity_type_id: entity_id, entity: "property", name: "map_view" };
+        if needs_insert_tx(tx, summary, tenant_id, view).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+                VALUES ($1, $2, $3, 'map_view', 'Map View', 'map', false, true, '[]', '[]', '[]', $4, $5, $6)
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(map_settings)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
     }
 
     Ok(())
@@ -549,6 +668,7 @@ async fn seed_views_tx(
 /// Seed standard workflows within a transaction
 async fn seed_standard_workflows_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
 ) -> Result<(), sqlx::Error> {
     let now = Utc::now();
@@ -572,19 +692,21 @@ async fn seed_standard_workflows_tx(
         }
     ]);
 
-    sqlx::query(
-        r#"
-        INSERT INTO workflow_defs (id, tenant_id, name, description, is_active, is_system, trigger_type, trigger_entity, conditions, actions, created_at, updated_at)
-        VALUES ($1, $2, 'New Lead Intake', 'Automatically creates follow-up task when a new contact is created', true, true, 'record_created', 'contact', '{}', $3, $4, $5)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(lead_intake_actions)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Workflow("New Lead Intake")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_defs (id, tenant_id, name, description, is_active, is_system, trigger_type, trigger_entity, conditions, actions, created_at, updated_at)
+            VALUES ($1, $2, 'New Lead Intake', 'Automatically creates follow-up task when a new contact is created', true, true, 'record_created', 'contact', '{}', $3, $4, $5)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(lead_intake_actions)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // WORKFLOW 2: Deal Won (CRM)
     let deal_won_conditions = serde_json::json!({
@@ -611,20 +733,22 @@ async fn seed_standard_workflows_tx(
         }
     ]);
 
-    sqlx::query(
-        r#"
-        INSERT INTO workflow_defs (id, tenant_id, name, description, is_active, is_system, trigger_type, trigger_entity, conditions, actions, created_at, updated_at)
-        VALUES ($1, $2, 'Deal Won', 'Updates contact lifecycle and sends celebration notification', true, true, 'field_changed', 'deal', $3, $4, $5, $6)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(deal_won_conditions)
-    .bind(deal_won_actions)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Workflow("Deal Won")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_defs (id, tenant_id, name, description, is_active, is_system, trigger_type, trigger_entity, conditions, actions, created_at, updated_at)
+            VALUES ($1, $2, 'Deal Won', 'Updates contact lifecycle and sends celebration notification', true, true, 'field_changed', 'deal', $3, $4, $5, $6)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(deal_won_conditions)
+        .bind(deal_won_actions)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // WORKFLOW 3: Offer Accepted (Real Estate)
     let offer_conditions = serde_json::json!({
@@ -651,20 +775,22 @@ async fn seed_standard_workflows_tx(
         }
     ]);
 
-    sqlx::query(
-        r#"
-        INSERT INTO workflow_defs (id, tenant_id, name, description, is_active, is_system, trigger_type, trigger_entity, conditions, actions, created_at, updated_at)
-        VALUES ($1, $2, 'Offer Accepted', 'Creates contract preparation task when offer is accepted', true, true, 'field_changed', 'property', $3, $4, $5, $6)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(offer_conditions)
-    .bind(offer_actions)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Workflow("Offer Accepted")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_defs (id, tenant_id, name, description, is_active, is_system, trigger_type, trigger_entity, conditions, actions, created_at, updated_at)
+            VALUES ($1, $2, 'Offer Accepted', 'Creates contract preparation task when offer is accepted', true, true, 'field_changed', 'property', $3, $4, $5, $6)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(offer_conditions)
+        .bind(offer_actions)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     Ok(())
 }
@@ -1445,9 +1571,12 @@ async fn seed_real_estate_metadata(pool: &PgPool, tenant_id: Uuid) -> Result<(),
         seed_field(pool, tenant_id, id, "bedrooms", "Bedrooms", "number", false, true, 5, None).await?;
         seed_field(pool, tenant_id, id, "bathrooms", "Bathrooms", "number", false, true, 6, None).await?;
         seed_field(pool, tenant_id, id, "area_sqm", "Area (sqm)", "number", false, true, 7, None).await?;
-        seed_field(pool, tenant_id, id, "address", "Address", "text", false, true, 8, None).await?;
+        // Geocoded into the map view's lat/lng fields on save
+        let address_options = serde_json::json!({ "lat_field": "lat", "lng_field": "lng", "show_map": true });
+        seed_field(pool, tenant_id, id, "address", "Address", "location", false, true, 8, Some(address_options)).await?;
         seed_field(pool, tenant_id, id, "city", "City", "text", false, true, 9, None).await?;
         seed_field(pool, tenant_id, id, "description", "Description", "textarea", false, false, 10, None).await?;
+        seed_field(pool, tenant_id, id, "published", "Published on Website", "boolean", false, true, 11, None).await?;
         
         id
     } else {
@@ -1838,28 +1967,18 @@ async fn seed_real_estate_metadata(pool: &PgPool, tenant_id: Uuid) -> Result<(),
 /// Seed Listing entity type for Real Estate (Transactional)
 async fn seed_listing_entity_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
 ) -> Result<(), sqlx::Error> {
-    let now = Utc::now();
-
     // Create Listing entity type
-    let listing_id = Uuid::new_v4();
-    sqlx::query(
-        r#"
-        INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
-        VALUES ($1, $2, 'real_estate', 'listing', 'Listing', 'Listings', 'list', $3, $4, $5)
-        "#
+    let listing_id = ensure_entity_type_tx(
+        tx, summary, tenant_id, "real_estate", "listing", "Listing", "Listings", "list",
+        serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true}),
     )
-    .bind(listing_id)
-    .bind(tenant_id)
-    .bind(serde_json::json!({"has_activities": true, "has_tasks": true, "is_searchable": true, "show_in_nav": true}))
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
     .await?;
 
     // Listing fields
-    seed_field_tx(tx, tenant_id, listing_id, "title", "Listing Title", "text", true, true, 1, None).await?;
+    seed_field_tx(tx, summary, tenant_id, listing_id, "listing", "title", "Listing Title", "text", true, true, 1, None).await?;
     
     let status_options = serde_json::json!([
         {"value": "draft", "label": "Draft"},
@@ -1867,10 +1986,10 @@ async fn seed_listing_entity_tx(
         {"value": "sold", "label": "Sold"},
         {"value": "expired", "label": "Expired"}
     ]);
-    seed_field_tx(tx, tenant_id, listing_id, "status", "Status", "select", true, true, 2, Some(status_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, listing_id, "listing", "status", "Status", "select", true, true, 2, Some(status_options)).await?;
     
-    seed_field_tx(tx, tenant_id, listing_id, "price", "List Price", "money", false, true, 3, None).await?;
-    seed_field_tx(tx, tenant_id, listing_id, "published_at", "Published Date", "date", false, true, 4, None).await?;
+    seed_field_tx(tx, summary, tenant_id, listing_id, "listing", "price", "List Price", "money", false, true, 3, None).await?;
+    seed_field_tx(tx, summary, tenant_id, listing_id, "listing", "published_at", "Published Date", "date", false, true, 4, None).await?;
 
     Ok(())
 }
@@ -1880,28 +1999,20 @@ async fn seed_listing_entity_tx(
 /// Seed Viewing entity type for Real Estate (Transactional)
 async fn seed_viewing_entity_tx(
     tx: &mut Transaction<'_, Postgres>,
+    summary: &mut SeedSummary,
     tenant_id: Uuid,
 ) -> Result<(), sqlx::Error> {
     let now = Utc::now();
 
     // Create Viewing entity type
-    let viewing_id = Uuid::new_v4();
-    sqlx::query(
-        r#"
-        INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
-        VALUES ($1, $2, 'real_estate', 'viewing', 'Viewing', 'Viewings', 'eye', $3, $4, $5)
-        "#
+    let viewing_id = ensure_entity_type_tx(
+        tx, summary, tenant_id, "real_estate", "viewing", "Viewing", "Viewings", "eye",
+        serde_json::json!({"has_activities": true, "has_tasks": true, "has_calendar": true, "width": "500px", "show_in_nav": true}),
     )
-    .bind(viewing_id)
-    .bind(tenant_id)
-    .bind(serde_json::json!({"has_activities": true, "has_tasks": true, "has_calendar": true, "width": "500px", "show_in_nav": true}))
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
     .await?;
 
     // Fields
-    seed_field_tx(tx, tenant_id, viewing_id, "scheduled_at", "Scheduled Time", "datetime", true, true, 1, None).await?;
+    seed_field_tx(tx, summary, tenant_id, viewing_id, "viewing", "scheduled_at", "Scheduled Time", "datetime", true, true, 1, None).await?;
     
     let status_options = serde_json::json!([
         {"value": "scheduled", "label": "Scheduled"},
@@ -1910,47 +2021,46 @@ async fn seed_viewing_entity_tx(
         {"value": "cancelled", "label": "Cancelled"},
         {"value": "no_show", "label": "No Show"}
     ]);
-    seed_field_tx(tx, tenant_id, viewing_id, "status", "Status", "select", true, true, 2, Some(status_options)).await?;
+    seed_field_tx(tx, summary, tenant_id, viewing_id, "viewing", "status", "Status", "select", true, true, 2, Some(status_options)).await?;
     
-    seed_field_tx(tx, tenant_id, viewing_id, "feedback", "Feedback", "textarea", false, false, 3, None).await?;
+    seed_field_tx(tx, summary, tenant_id, viewing_id, "viewing", "feedback", "Feedback", "textarea", false, false, 3, None).await?;
     
     // Associations (Property, Contact)
     // Viewing -> Property (One viewing is for one property)
-    sqlx::query(
-        r#"
-        INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
-        VALUES ($1, $2, 'viewing', 'property', 'viewing_property', 'Property', 'Viewings', 'many_to_one', NULL, NULL, true, false, $3, $4)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Association("viewing_property")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, 'viewing', 'property', 'viewing_property', 'Property', 'Viewings', 'many_to_one', NULL, NULL, true, false, $3, $4)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // Viewing -> Contact (One viewing is by one contact/buyer)
-    sqlx::query(
-        r#"
-        INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
-        VALUES ($1, $2, 'viewing', 'contact', 'viewing_contact', 'Attendee', 'Viewings', 'many_to_one', 'attendee', 'viewing', true, false, $3, $4)
-        "#
-    )
-    .bind(Uuid::new_v4())
-    .bind(tenant_id)
-    .bind(now)
-    .bind(now)
-    .execute(&mut **tx)
-    .await?;
+    if needs_insert_tx(tx, summary, tenant_id, SeedItem::Association("viewing_contact")).await? {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, 'viewing', 'contact', 'viewing_contact', 'Attendee', 'Viewings', 'many_to_one', 'attendee', 'viewing', true, false, $3, $4)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
 
     // Kanban View for Viewings
-    let kanban_exists: bool = sqlx::query("SELECT id FROM view_defs WHERE entity_type_id = $1 AND name = 'viewing_kanban'")
-        .bind(viewing_id)
-        .fetch_optional(&mut **tx)
-        .await?
-        .is_some();
-
-    if !kanban_exists {
+    let view = SeedItem::View { entity_type_id: viewing_id, entity: "viewing", name: "viewing_kanban" };
+    if needs_insert_tx(tx, summary, tenant_id, view).await? {
         let kanban_settings = serde_json::json!({
             "group_by_field": "status",
             "title_field": "scheduled_at",
@@ -1976,13 +2086,8 @@ async fn seed_viewing_entity_tx(
     }
 
     // Calendar View for Viewings
-    let calendar_exists: bool = sqlx::query("SELECT id FROM view_defs WHERE entity_type_id = $1 AND name = 'viewing_calendar'")
-        .bind(viewing_id)
-        .fetch_optional(&mut **tx)
-        .await?
-        .is_some();
-
-    if !calendar_exists {
+    let view = SeedItem::View { entity_type_id: viewing_id, entity: "viewing", name: "viewing_calendar" };
+    if needs_insert_tx(tx, summary, tenant_id, view).await? {
         let calendar_settings = serde_json::json!({
             "date_field": "scheduled_at",
             "title_field": "status", // Using status as title for now
@@ -2014,13 +2119,8 @@ async fn seed_viewing_entity_tx(
 
     
     // Table View
-    let table_exists: bool = sqlx::query("SELECT id FROM view_defs WHERE entity_type_id = $1 AND name = 'viewing_table'")
-        .bind(viewing_id)
-        .fetch_optional(&mut **tx)
-        .await?
-        .is_some();
-
-    if !table_exists {
+    let view = SeedItem::View { entity_type_id: viewing_id, entity: "viewing", name: "viewing_table" };
+    if needs_insert_tx(tx, summary, tenant_id, view).await? {
         let table_columns = serde_json::json!([
             {"field": "scheduled_at", "width": "180", "visible": true, "sort_order": 1},
             {"field": "status", "width": "120", "visible": true, "sort_order": 2},
@@ -2091,3 +2191,63 @@ async fn seed_acme_tenant(pool: &PgPool) -> Result<Uuid, sqlx::Error> {
     Ok(tenant_id)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::get_test_pool;
+
+    /// Every seeded row for the tenant, as (table, id, name), in a stable order
+    async fn snapshot(pool: &PgPool, tenant_id: Uuid) -> Vec<(String, Uuid, String)> {
+        let mut rows = Vec::new();
+        for table in ["entity_types", "field_defs", "view_defs", "association_defs", "workflow_defs"] {
+            let sql = format!("SELECT '{table}', id, name::text FROM {table} WHERE tenant_id = $1 ORDER BY id");
+            rows.extend(sqlx::query_as::<_, (String, Uuid, String)>(&sql).bind(tenant_id).fetch_all(pool).await.unwrap());
+        }
+        rows
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_seed_new_tenant_is_idempotent() {
+        let pool = get_test_pool().await;
+        let tenant_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Seed Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("seed-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let first = seed_new_tenant(tenant_id, &pool).await.unwrap();
+        assert!(first.skipped.is_empty(), "{:?}", first.skipped);
+        assert!(first.created.contains(&"view:property.map_view".to_string()));
+        let seeded = snapshot(&pool, tenant_id).await;
+        assert_eq!(seeded.len(), first.created.len());
+
+        let second = seed_new_tenant(tenant_id, &pool).await.unwrap();
+        assert!(second.is_all_skipped(), "{:?}", second.created);
+        assert_eq!(second.skipped, first.created);
+        assert_eq!(snapshot(&pool, tenant_id).await, seeded);
+
+        // A partially seeded tenant only gets what is missing
+        sqlx::query("DELETE FROM view_defs WHERE tenant_id = $1 AND name = 'pipeline'")
+            .bind(tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let retry = seed_new_tenant(tenant_id, &pool).await.unwrap();
+        assert_eq!(retry.created, vec!["view:deal.pipeline".to_string()]);
+
+        for sql in [
+            "DELETE FROM workflow_defs WHERE tenant_id = $1",
+            "DELETE FROM view_defs WHERE tenant_id = $1",
+            "DELETE FROM field_defs WHERE tenant_id = $1",
+            "DELETE FROM association_defs WHERE tenant_id = $1",
+            "DELETE FROM entity_types WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,601 @@
+//! JSON-driven entity blueprints
+//!
+//! An [`EntityBlueprint`] describes entity types, their fields (with the
+//! Diamond layers), associations and views, so an app template can be seeded
+//! without a bespoke seed function. [`seed_from_blueprint`] is idempotent:
+//! rows are matched by their natural key and updated in place.
+
+use chrono::Utc;
+use core_models::{Cardinality, ViewType};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Row, Transaction};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::SeedError;
+
+/// Fields every record has, which views may reference without declaring
+const BUILTIN_FIELDS: &[&str] = &["id", "created_at", "updated_at"];
+
+/// A set of entity types, associations and views to seed for a tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityBlueprint {
+    #[serde(default = "default_app_id")]
+    pub app_id: String,
+    #[serde(default)]
+    pub entities: Vec<EntityTypeBlueprint>,
+    #[serde(default)]
+    pub associations: Vec<AssociationBlueprint>,
+}
+
+fn default_app_id() -> String {
+    "crm".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityTypeBlueprint {
+    pub name: String,
+    pub label: String,
+    pub label_plural: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// e.g. `{"has_activities": true, "show_in_nav": true}`
+    #[serde(default = "empty_object")]
+    pub flags: serde_json::Value,
+    #[serde(default)]
+    pub fields: Vec<FieldBlueprint>,
+    #[serde(default)]
+    pub views: Vec<ViewBlueprint>,
+}
+
+/// A field definition; unset Diamond layers get the same defaults as the
+/// hand-written seeders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldBlueprint {
+    pub name: String,
+    pub label: String,
+    pub field_type: String,
+    #[serde(default)]
+    pub is_required: bool,
+    #[serde(default)]
+    pub show_in_list: bool,
+    /// Defaults to the field's position in the blueprint
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+    #[serde(default)]
+    pub options: Option<serde_json::Value>,
+    #[serde(default)]
+    pub layout: Option<serde_json::Value>,
+    #[serde(default)]
+    pub physics: Option<serde_json::Value>,
+    #[serde(default)]
+    pub intelligence: Option<serde_json::Value>,
+    #[serde(default)]
+    pub rules: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssociationBlueprint {
+    pub name: String,
+    pub source_entity: String,
+    pub target_entity: String,
+    pub label_source: String,
+    pub label_target: String,
+    pub cardinality: Cardinality,
+    #[serde(default)]
+    pub source_role: Option<String>,
+    #[serde(default)]
+    pub target_role: Option<String>,
+    #[serde(default)]
+    pub allow_primary: bool,
+    #[serde(default)]
+    pub cascade_delete: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewBlueprint {
+    pub name: String,
+    pub label: String,
+    #[serde(default)]
+    pub view_type: ViewType,
+    #[serde(default)]
+    pub is_default: bool,
+    /// Field names, shown in this order
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default = "empty_array")]
+    pub filters: serde_json::Value,
+    #[serde(default = "empty_array")]
+    pub sort: serde_json::Value,
+    /// View-type settings; `*_field` / `*_fields` keys must name fields
+    #[serde(default = "empty_object")]
+    pub settings: serde_json::Value,
+}
+
+fn empty_object() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+fn empty_array() -> serde_json::Value {
+    serde_json::json!([])
+}
+
+impl EntityBlueprint {
+    pub fn from_json(json: &str) -> Result<Self, SeedError> {
+        serde_json::from_str(json).map_err(|e| SeedError::InvalidBlueprint(e.to_string()))
+    }
+
+    /// Check the blueprint is self-consistent: no duplicate names, and
+    /// every field a view references exists on its entity
+    pub fn validate(&self) -> Result<(), SeedError> {
+        let invalid = |msg: String| Err(SeedError::InvalidBlueprint(msg));
+
+        let mut entity_names = HashSet::new();
+        for entity in &self.entities {
+            if !entity_names.insert(entity.name.as_str()) {
+                return invalid(format!("duplicate entity type '{}'", entity.name));
+            }
+            if !entity.flags.is_object() {
+                return invalid(format!("flags of '{}' must be an object", entity.name));
+            }
+
+            let mut fields = HashSet::new();
+            for field in &entity.fields {
+                if !fields.insert(field.name.as_str()) {
+                    return invalid(format!("duplicate field '{}.{}'", entity.name, field.name));
+                }
+                if field.field_type.is_empty() {
+                    return invalid(format!("field '{}.{}' has no field_type", entity.name, field.name));
+                }
+            }
+
+            let mut views = HashSet::new();
+            for view in &entity.views {
+                if !views.insert(view.name.as_str()) {
+                    return invalid(format!("duplicate view '{}.{}'", entity.name, view.name));
+                }
+                for field in view.referenced_fields() {
+                    if !fields.contains(field) && !BUILTIN_FIELDS.contains(&field) {
+                        return invalid(format!(
+                            "view '{}.{}' references unknown field '{}'",
+                            entity.name, view.name, field
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut associations = HashSet::new();
+        for association in &self.associations {
+            if !associations.insert(association.name.as_str()) {
+                return invalid(format!("duplicate association '{}'", association.name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ViewBlueprint {
+    /// Field names used by columns, filters, sort and `*_field(s)` settings
+    fn referenced_fields(&self) -> Vec<&str> {
+        let mut fields: Vec<&str> = self.columns.iter().map(String::as_str).collect();
+        for clause in self.filters.as_array().into_iter().chain(self.sort.as_array()).flatten() {
+            if let Some(field) = clause.get("field").and_then(|f| f.as_str()) {
+                fields.push(field);
+            }
+        }
+        if let Some(settings) = self.settings.as_object() {
+            for (key, value) in settings {
+                if key.ends_with("_field") {
+                    fields.extend(value.as_str());
+                } else if key.ends_with("_fields") {
+                    fields.extend(value.as_array().into_iter().flatten().filter_map(|v| v.as_str()));
+                }
+            }
+        }
+        fields
+    }
+
+    fn columns_json(&self) -> serde_json::Value {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, field)| serde_json::json!({"field": field, "width": "150", "visible": true, "sort_order": i + 1}))
+            .collect()
+    }
+}
+
+/// Apply `blueprint` for `tenant_id`; re-running it updates rows in place
+///
+/// Associations may reference entity types outside the blueprint as long as
+/// the tenant already has them.
+pub async fn seed_from_blueprint(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: Uuid,
+    blueprint: &EntityBlueprint,
+) -> Result<(), SeedError> {
+    blueprint.validate()?;
+    let now = Utc::now();
+
+    for entity in &blueprint.entities {
+        let entity_type_id: Uuid = sqlx::query(
+            r#"
+            INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural, icon, flags, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            ON CONFLICT (tenant_id, name) DO UPDATE SET
+                app_id = EXCLUDED.app_id, label = EXCLUDED.label, label_plural = EXCLUDED.label_plural,
+                icon = EXCLUDED.icon, flags = EXCLUDED.flags, updated_at = EXCLUDED.updated_at
+            RETURNING id
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(&blueprint.app_id)
+        .bind(&entity.name)
+        .bind(&entity.label)
+        .bind(&entity.label_plural)
+        .bind(&entity.icon)
+        .bind(&entity.flags)
+        .bind(now)
+        .fetch_one(&mut **tx)
+        .await?
+        .try_get("id")?;
+
+        for (position, field) in entity.fields.iter().enumerate() {
+            seed_blueprint_field(tx, tenant_id, entity_type_id, field, position as i32 + 1).await?;
+        }
+        for view in &entity.views {
+            seed_blueprint_view(tx, tenant_id, entity_type_id, view).await?;
+        }
+    }
+
+    let known: HashSet<String> = sqlx::query("SELECT name FROM entity_types WHERE tenant_id = $1")
+        .bind(tenant_id)
+        .fetch_all(&mut **tx)
+        .await?
+        .iter()
+        .map(|row| row.try_get("name"))
+        .collect::<Result<_, _>>()?;
+    for association in &blueprint.associations {
+        for entity in [&association.source_entity, &association.target_entity] {
+            if !known.contains(entity) {
+                return Err(SeedError::InvalidBlueprint(format!(
+                    "association '{}' references unknown entity type '{}'",
+                    association.name, entity
+                )));
+            }
+        }
+        seed_blueprint_association(tx, tenant_id, association).await?;
+    }
+
+    Ok(())
+}
+
+async fn seed_blueprint_field(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    field: &FieldBlueprint,
+    position: i32,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+
+    // Same defaults as seed_field_tx
+    let layout = field.layout.clone().unwrap_or_else(|| {
+        serde_json::json!({
+            "form_span": 12,
+            "section": null,
+            "visible_if": {"op": "always"},
+            "readonly_if": {"op": "never"}
+        })
+    });
+    let physics = field.physics.clone().unwrap_or_else(|| serde_json::json!("lastWriteWins"));
+    let intelligence = field.intelligence.clone().unwrap_or_else(|| {
+        serde_json::json!({
+            "description": null,
+            "is_pii": false,
+            "embed": false,
+            "auto_generate": false
+        })
+    });
+    let rules = field.rules.clone().unwrap_or_else(|| {
+        if field.is_required {
+            serde_json::json!([{"rule": "required"}])
+        } else {
+            serde_json::json!([])
+        }
+    });
+
+    sqlx::query(
+        r#"
+        INSERT INTO field_defs (
+            id, tenant_id, entity_type_id, name, label, field_type,
+            is_required, show_in_list, sort_order, options,
+            layout, physics, intelligence, rules, is_system,
+            created_at, updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, false, $15, $15)
+        ON CONFLICT (entity_type_id, name) DO UPDATE SET
+            label = EXCLUDED.label, field_type = EXCLUDED.field_type, is_required = EXCLUDED.is_required,
+            show_in_list = EXCLUDED.show_in_list, sort_order = EXCLUDED.sort_order, options = EXCLUDED.options,
+            layout = EXCLUDED.layout, physics = EXCLUDED.physics, intelligence = EXCLUDED.intelligence,
+            rules = EXCLUDED.rules, updated_at = EXCLUDED.updated_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(tenant_id)
+    .bind(entity_type_id)
+    .bind(&field.name)
+    .bind(&field.label)
+    .bind(&field.field_type)
+    .bind(field.is_required)
+    .bind(field.show_in_list)
+    .bind(field.sort_order.unwrap_or(position))
+    .bind(&field.options)
+    .bind(layout)
+    .bind(physics)
+    .bind(intelligence)
+    .bind(rules)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// view_defs has no unique key, so match on (entity type, name) by hand
+async fn seed_blueprint_view(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: Uuid,
+    entity_type_id: Uuid,
+    view: &ViewBlueprint,
+) -> Result<(), SeedError> {
+    let now = Utc::now();
+    let view_type = enum_str(&view.view_type)?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE view_defs
+        SET label = $4, view_type = $5, is_default = $6, columns = $7, filters = $8, sort = $9, settings = $10, updated_at = $11
+        WHERE tenant_id = $1 AND entity_type_id = $2 AND name = $3
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(entity_type_id)
+    .bind(&view.name)
+    .bind(&view.label)
+    .bind(&view_type)
+    .bind(view.is_default)
+    .bind(view.columns_json())
+    .bind(&view.filters)
+    .bind(&view.sort)
+    .bind(&view.settings)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        sqlx::query(
+            r#"
+            INSERT INTO view_defs (id, tenant_id, entity_type_id, name, label, view_type, is_default, is_system, columns, filters, sort, settings, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, true, $8, $9, $10, $11, $12, $12)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(entity_type_id)
+        .bind(&view.name)
+        .bind(&view.label)
+        .bind(&view_type)
+        .bind(view.is_default)
+        .bind(view.columns_json())
+        .bind(&view.filters)
+        .bind(&view.sort)
+        .bind(&view.settings)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// association_defs has no unique key either; associations match on name
+async fn seed_blueprint_association(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: Uuid,
+    association: &AssociationBlueprint,
+) -> Result<(), SeedError> {
+    let now = Utc::now();
+    let cardinality = enum_str(&association.cardinality)?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE association_defs
+        SET source_entity = $3, target_entity = $4, label_source = $5, label_target = $6, cardinality = $7,
+            source_role = $8, target_role = $9, allow_primary = $10, cascade_delete = $11, updated_at = $12
+        WHERE tenant_id = $1 AND name = $2
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(&association.name)
+    .bind(&association.source_entity)
+    .bind(&association.target_entity)
+    .bind(&association.label_source)
+    .bind(&association.label_target)
+    .bind(&cardinality)
+    .bind(&association.source_role)
+    .bind(&association.target_role)
+    .bind(association.allow_primary)
+    .bind(association.cascade_delete)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        sqlx::query(
+            r#"
+            INSERT INTO association_defs (id, tenant_id, source_entity, target_entity, name, label_source, label_target, cardinality, source_role, target_role, allow_primary, cascade_delete, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $13)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(&association.source_entity)
+        .bind(&association.target_entity)
+        .bind(&association.name)
+        .bind(&association.label_source)
+        .bind(&association.label_target)
+        .bind(&cardinality)
+        .bind(&association.source_role)
+        .bind(&association.target_role)
+        .bind(association.allow_primary)
+        .bind(association.cascade_delete)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// The snake_case name a unit enum serializes to
+fn enum_str<T: Serialize>(value: &T) -> Result<String, SeedError> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => Ok(s),
+        _ => Err(SeedError::InvalidBlueprint("expected a unit enum".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::get_test_pool;
+
+    const BLUEPRINT: &str = r#"{
+        "app_id": "crm",
+        "entities": [
+            {
+                "name": "bp_project",
+                "label": "Project",
+                "label_plural": "Projects",
+                "icon": "folder",
+                "flags": {"has_activities": true, "show_in_nav": true},
+                "fields": [
+                    {"name": "title", "label": "Title", "field_type": "text", "is_required": true, "show_in_list": true},
+                    {"name": "status", "label": "Status", "field_type": "select",
+                     "options": {"choices": ["open", "done"]},
+                     "layout": {"form_span": 6, "section": "Main", "visible_if": {"op": "always"}, "readonly_if": {"op": "never"}},
+                     "intelligence": {"description": "Lifecycle", "is_pii": false, "embed": true, "auto_generate": false}}
+                ],
+                "views": [
+                    {"name": "all", "label": "All Projects", "is_default": true, "columns": ["title", "status"],
+                     "sort": [{"field": "created_at", "direction": "desc"}]},
+                    {"name": "board", "label": "Board", "view_type": "kanban",
+                     "settings": {"group_by_field": "status", "title_field": "title", "card_fields": []}}
+                ]
+            }
+        ],
+        "associations": [
+            {"name": "bp_project_parent", "source_entity": "bp_project", "target_entity": "bp_project",
+             "label_source": "Parent", "label_target": "Children", "cardinality": "many_to_one"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_view_referencing_unknown_field_is_rejected() {
+        let mut blueprint = EntityBlueprint::from_json(BLUEPRINT).unwrap();
+        blueprint.validate().unwrap();
+
+        blueprint.entities[0].views[1].settings["group_by_field"] = serde_json::json!("stage");
+        let err = blueprint.validate().unwrap_err().to_string();
+        assert!(err.contains("unknown field 'stage'"), "{}", err);
+
+        let mut duplicate = EntityBlueprint::from_json(BLUEPRINT).unwrap();
+        let title = duplicate.entities[0].fields[0].clone();
+        duplicate.entities[0].fields.push(title);
+        assert!(duplicate.validate().is_err());
+
+        assert!(EntityBlueprint::from_json(&BLUEPRINT.replace("many_to_one", "some_to_few")).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_seed_from_blueprint_is_idempotent() {
+        let pool = get_test_pool().await;
+        let tenant_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Blueprint Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("bp-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut blueprint = EntityBlueprint::from_json(BLUEPRINT).unwrap();
+        for _ in 0..2 {
+            let mut tx = pool.begin().await.unwrap();
+            seed_from_blueprint(&mut tx, tenant_id, &blueprint).await.unwrap();
+            tx.commit().await.unwrap();
+        }
+
+        let (entity_type_id, flags): (Uuid, serde_json::Value) =
+            sqlx::query_as("SELECT id, flags FROM entity_types WHERE tenant_id = $1 AND name = 'bp_project'")
+                .bind(tenant_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(flags["has_activities"], true);
+
+        let fields: Vec<(String, bool, i32, serde_json::Value, serde_json::Value)> = sqlx::query_as(
+            "SELECT name, is_required, sort_order, layout, rules FROM field_defs WHERE entity_type_id = $1 ORDER BY sort_order",
+        )
+        .bind(entity_type_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!((fields[0].0.as_str(), fields[0].1, fields[0].2), ("title", true, 1));
+        assert_eq!(fields[0].3["form_span"], 12);
+        assert_eq!(fields[0].4, serde_json::json!([{"rule": "required"}]));
+        assert_eq!(fields[1].3["section"], "Main");
+
+        let views: Vec<(String, String, serde_json::Value)> =
+            sqlx::query_as("SELECT name, view_type, columns FROM view_defs WHERE entity_type_id = $1 ORDER BY name")
+                .bind(entity_type_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(views.len(), 2);
+        assert_eq!((views[0].0.as_str(), views[0].1.as_str()), ("all", "table"));
+        assert_eq!(views[0].2[1]["field"], "status");
+        assert_eq!(views[1].1, "kanban");
+
+        let associations: Vec<(String,)> =
+            sqlx::query_as("SELECT cardinality FROM association_defs WHERE tenant_id = $1")
+                .bind(tenant_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(associations, vec![("many_to_one".to_string(),)]);
+
+        // An association to an entity type the tenant doesn't have rolls back
+        blueprint.associations[0].target_entity = "nonexistent".to_string();
+        let mut tx = pool.begin().await.unwrap();
+        assert!(matches!(
+            seed_from_blueprint(&mut tx, tenant_id, &blueprint).await,
+            Err(SeedError::InvalidBlueprint(_))
+        ));
+        tx.rollback().await.unwrap();
+
+        for sql in [
+            "DELETE FROM view_defs WHERE tenant_id = $1",
+            "DELETE FROM field_defs WHERE tenant_id = $1",
+            "DELETE FROM association_defs WHERE tenant_id = $1",
+            "DELETE FROM entity_types WHERE tenant_id = $1",
+            "DELETE FROM tenants WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
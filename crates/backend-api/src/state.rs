@@ -1,6 +1,8 @@
 //! Application state
 
-use core_auth::{session::SessionService, tenant::TenantService, user::UserService};
+use core_auth::{api_key::ApiKeyService, email_verification::EmailVerificationService, login_throttle::LoginThrottle, password_reset::PasswordResetService, session::{JwtKeySet, SessionService}, tenant::TenantService, user::UserService};
+use core_integrations::geocoding::{geocoder_from_env, CachedGeocoder};
+use core_integrations::WebhookReplayStore;
 use core_metadata::MetadataService;
 use sqlx::PgPool;
 
@@ -8,7 +10,18 @@ use crate::routes::ws::{create_ws_channels, create_document_rooms, WsChannels, D
 
 use core_node_engine::{ai::AiService, EventPublisher, GraphExecutor, repository::NodeGraphRepository};
 use std::sync::Arc;
-use crate::ai::service::create_ai_service;
+use crate::ai::chat::ChatProvider;
+use crate::ai::embeddings::EmbeddingService;
+use crate::ai::service::{create_ai_service, create_chat_provider, create_embedding_service};
+use crate::middleware::tenant::TenantHostCache;
+use crate::middleware::permission::PermissionCache;
+use crate::middleware::SharedRateLimiter;
+use crate::gateway::{trusted_proxies_from_env, IpNet, Metrics, RateLimiter, API_REQUESTS_PER_MINUTE};
+use crate::routes::auth::{PasswordResetLimiter, VERIFICATION_RESEND_WINDOW_SECS};
+use crate::routes::lead_capture;
+use crate::routes::public::PublicListingCache;
+use crate::routes::segments::SegmentCountCache;
+use crate::storage::{create_file_store, FileStore};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -17,12 +30,40 @@ pub struct AppState {
     pub tenant_service: TenantService,
     pub user_service: UserService,
     pub session_service: SessionService,
+    pub login_throttle: LoginThrottle,
+    /// Proxies whose `X-Forwarded-For` is believed (`TRUSTED_PROXIES`)
+    pub trusted_proxies: Arc<Vec<IpNet>>,
+    /// Set when `JWT_JWKS` or `JWT_SECRET` is configured; bearer auth is off without it
+    pub jwt_keys: Option<Arc<JwtKeySet>>,
+    pub api_key_service: ApiKeyService,
+    pub permission_cache: PermissionCache,
+    pub password_reset_service: PasswordResetService,
+    pub password_reset_limiter: Arc<PasswordResetLimiter>,
+    pub email_verification_service: EmailVerificationService,
+    /// Verification resends, per user
+    pub verification_resend_limiter: Arc<RateLimiter>,
     pub ws_channels: WsChannels,
     pub document_rooms: Option<DocumentRooms>,
     pub ai_service: Arc<dyn AiService>,
+    pub chat: Arc<dyn ChatProvider>,
+    /// Set when an OpenAI key is configured; retrieval is off without it
+    pub embedder: Option<Arc<dyn EmbeddingService>>,
     pub event_publisher: EventPublisher,
     pub graph_executor: Arc<GraphExecutor>,
     pub graph_repo: NodeGraphRepository,
+    pub tenant_hosts: TenantHostCache,
+    pub segment_counts: SegmentCountCache,
+    pub public_listings: PublicListingCache,
+    pub lead_capture_limiter: SharedRateLimiter,
+    /// Inbound provider events already processed
+    pub webhook_replay: WebhookReplayStore,
+    pub file_store: Arc<dyn FileStore>,
+    /// Set when `GEOCODER` names a provider
+    pub geocoder: Option<Arc<CachedGeocoder>>,
+    /// API requests, per principal
+    pub api_rate_limiter: Arc<RateLimiter>,
+    /// Per-route request counts and latencies reported at `/metrics`
+    pub request_metrics: Arc<Metrics>,
 }
 
 impl AppState {
@@ -33,18 +74,39 @@ impl AppState {
         let event_publisher = EventPublisher::new(pool.clone());
         let graph_repo = NodeGraphRepository::new(pool.clone());
         let graph_executor = Arc::new(GraphExecutor::new(pool.clone()).with_ai_service(ai_service.clone()));
+        let trusted_proxies = trusted_proxies_from_env();
 
         Self {
             metadata: MetadataService::new(pool.clone()),
             tenant_service: TenantService::new(pool.clone()),
             user_service: UserService::new(pool.clone()),
             session_service: SessionService::new(pool.clone()),
+            login_throttle: LoginThrottle::new(pool.clone()),
+            trusted_proxies: Arc::new(trusted_proxies.clone()),
+            jwt_keys: JwtKeySet::from_env().map(Arc::new),
+            api_key_service: ApiKeyService::new(pool.clone()),
+            permission_cache: PermissionCache::new(),
+            password_reset_service: PasswordResetService::new(pool.clone()),
+            password_reset_limiter: Arc::new(PasswordResetLimiter::new()),
+            email_verification_service: EmailVerificationService::new(pool.clone()),
+            verification_resend_limiter: Arc::new(RateLimiter::new(3, VERIFICATION_RESEND_WINDOW_SECS)),
             ws_channels: create_ws_channels(),
             document_rooms: Some(create_document_rooms()),
             ai_service,
+            chat: create_chat_provider(),
+            embedder: create_embedding_service(),
             event_publisher,
             graph_executor,
             graph_repo,
+            tenant_hosts: TenantHostCache::new(),
+            segment_counts: SegmentCountCache::new(),
+            public_listings: PublicListingCache::new(),
+            lead_capture_limiter: Arc::new(lead_capture::rate_limiter()),
+            webhook_replay: WebhookReplayStore::new(pool.clone()),
+            file_store: create_file_store(),
+            geocoder: geocoder_from_env().map(Arc::new),
+            api_rate_limiter: Arc::new(RateLimiter::new(API_REQUESTS_PER_MINUTE, 60).with_trusted_proxy_nets(trusted_proxies)),
+            request_metrics: Arc::new(Metrics::new()),
             pool,
         }
     }
@@ -0,0 +1,235 @@
+//! File storage for uploads
+//!
+//! Files go to a local directory by default, or to an S3-compatible bucket
+//! (AWS, MinIO, R2, ...) when `UPLOAD_STORAGE=s3`. Either way a stored file
+//! has a stable URL derived from its key, so records can keep the URL.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// URL prefix local files are served under (see `routes::uploads`)
+pub const LOCAL_UPLOADS_URL: &str = "/api/v1/uploads/files";
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Object store error: {0}")]
+    Remote(String),
+}
+
+/// Where uploaded bytes live
+#[async_trait]
+pub trait FileStore: Send + Sync {
+    /// Store `bytes` under `key`; `checksum` is their hex SHA-256
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str, checksum: &str) -> Result<(), StorageError>;
+
+    /// Read a file back, for stores whose URLs point at this server
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Stable URL of the file stored under `key`
+    fn url(&self, key: &str) -> String;
+}
+
+/// Build the store configured by the environment
+pub fn create_file_store() -> Arc<dyn FileStore> {
+    let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+    if env("UPLOAD_STORAGE").as_deref() == Some("s3") {
+        match S3Store::from_env() {
+            Some(store) => return Arc::new(store),
+            None => tracing::warn!("UPLOAD_STORAGE=s3 but S3_BUCKET or credentials are missing; storing uploads locally"),
+        }
+    }
+
+    let dir = env("UPLOAD_DIR").unwrap_or_else(|| "./uploads".to_string());
+    let base_url = env("UPLOAD_PUBLIC_URL").unwrap_or_else(|| LOCAL_UPLOADS_URL.to_string());
+    Arc::new(LocalStore::new(dir, base_url))
+}
+
+/// Keys are relative paths made of plain segments, so they can't escape the store
+fn check_key(key: &str) -> Result<(), StorageError> {
+    let safe = !key.is_empty()
+        && Path::new(key).components().all(|c| matches!(c, Component::Normal(_)))
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c));
+    if safe {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+// ============================================================================
+// Local directory
+// ============================================================================
+
+/// Files under a directory on this server, served back by the API
+pub struct LocalStore {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>, base_url: impl Into<String>) -> Self {
+        Self { root: root.into(), base_url: base_url.into().trim_end_matches('/').to_string() }
+    }
+}
+
+#[async_trait]
+impl FileStore for LocalStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str, _checksum: &str) -> Result<(), StorageError> {
+        check_key(key)?;
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write then rename, so a reader never sees half a file
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, bytes).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        check_key(key)?;
+        match tokio::fs::read(self.root.join(key)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+}
+
+// ============================================================================
+// S3-compatible bucket
+// ============================================================================
+
+/// Objects in an S3-compatible bucket, addressed path-style and signed with
+/// AWS Signature V4
+pub struct S3Store {
+    client: reqwest::Client,
+    /// e.g. "https://s3.eu-west-1.amazonaws.com" or "http://localhost:9000"
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    /// Where clients fetch objects, e.g. a CDN; defaults to the bucket URL
+    public_url: String,
+}
+
+impl S3Store {
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        let region = env("S3_REGION").unwrap_or_else(|| "us-east-1".to_string());
+        let endpoint = env("S3_ENDPOINT")
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region))
+            .trim_end_matches('/')
+            .to_string();
+        let bucket = env("S3_BUCKET")?;
+        let public_url = env("S3_PUBLIC_URL")
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| format!("{}/{}", endpoint, bucket));
+
+        Some(Self {
+            client: reqwest::Client::new(),
+            access_key: env("S3_ACCESS_KEY_ID")?,
+            secret_key: env("S3_SECRET_ACCESS_KEY")?,
+            endpoint,
+            bucket,
+            region,
+            public_url,
+        })
+    }
+
+    /// `Authorization` header for a PUT of `key`
+    fn authorization(&self, key: &str, content_type: &str, payload_hash: &str, amz_date: &str) -> Result<String, StorageError> {
+        let host = self
+            .endpoint
+            .split("://")
+            .nth(1)
+            .ok_or_else(|| StorageError::Remote(format!("S3_ENDPOINT needs a scheme: {}", self.endpoint)))?;
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let signed_headers = "content-type;host;x-amz-content-sha256;x-amz-date";
+
+        // Keys pass `check_key`, so they need no URI encoding
+        let canonical_request = format!(
+            "PUT\n/{}/{}\n\ncontent-type:{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            self.bucket, key, content_type, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut signing_key = hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac_sha256(&signing_key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        Ok(format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        ))
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[async_trait]
+impl FileStore for S3Store {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str, checksum: &str) -> Result<(), StorageError> {
+        check_key(key)?;
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = self.authorization(key, content_type, checksum, &amz_date)?;
+
+        let response = self
+            .client
+            .put(format!("{}/{}/{}", self.endpoint, self.bucket, key))
+            .header("Authorization", authorization)
+            .header("Content-Type", content_type)
+            .header("x-amz-content-sha256", checksum)
+            .header("x-amz-date", amz_date)
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| StorageError::Remote(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(StorageError::Remote(format!("PUT {} returned {}: {}", key, status, body)));
+        }
+        Ok(())
+    }
+
+    /// Objects are fetched from `public_url`, not through the API
+    async fn get(&self, _key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(None)
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
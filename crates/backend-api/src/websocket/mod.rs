@@ -150,6 +150,11 @@ impl WsManager {
             .filter(|c| c.tenant_id == tenant_id)
             .count()
     }
+    
+    /// Open connections across all tenants
+    pub async fn connection_count(&self) -> usize {
+        self.clients.read().await.len()
+    }
 }
 
 /// WebSocket upgrade handler
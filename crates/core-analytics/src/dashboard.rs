@@ -4,6 +4,7 @@ use sqlx::{PgPool, Row};
 use serde::{Deserialize, Serialize};
 use chrono::{NaiveDate, Utc, Datelike};
 use std::collections::HashMap;
+use thiserror::Error;
 use uuid::Uuid;
 use crate::targets::{get_target_for_user, MetricType};
 
@@ -536,5 +537,273 @@ pub async fn get_dashboard(
     })
 }
 
+// ============================================================================
+// Saved Layouts
+// ============================================================================
 
+/// Width of the dashboard grid in columns
+pub const GRID_COLUMNS: i32 = 12;
 
+#[derive(Debug, Error)]
+pub enum DashboardError {
+    #[error("Invalid dashboard layout: {0}")]
+    InvalidLayout(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Where a widget sits on the grid, in column/row units
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WidgetPlacement {
+    /// Built-in KPI name (e.g. `total_leads`) or a metric definition id
+    pub metric_id: String,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl WidgetPlacement {
+    pub fn new(metric_id: &str, x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { metric_id: metric_id.to_string(), x, y, w, h }
+    }
+
+    fn overlaps(&self, other: &WidgetPlacement) -> bool {
+        self.x < other.x + other.w
+            && other.x < self.x + self.w
+            && self.y < other.y + other.h
+            && other.y < self.y + self.h
+    }
+}
+
+/// A saved dashboard arrangement; `user_id: None` is the tenant default
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DashboardLayout {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub widgets: Vec<WidgetPlacement>,
+}
+
+impl DashboardLayout {
+    /// Built-in layout used when neither the user nor the tenant saved one
+    pub fn system_default(tenant_id: Uuid) -> Self {
+        Self {
+            id: Uuid::nil(),
+            tenant_id,
+            user_id: None,
+            widgets: vec![
+                WidgetPlacement::new("total_leads", 0, 0, 3, 2),
+                WidgetPlacement::new("total_deals", 3, 0, 3, 2),
+                WidgetPlacement::new("forecasted_revenue", 6, 0, 3, 2),
+                WidgetPlacement::new("win_rate", 9, 0, 3, 2),
+                WidgetPlacement::new("sales_trend", 0, 2, 8, 4),
+                WidgetPlacement::new("funnel", 8, 2, 4, 4),
+            ],
+        }
+    }
+
+    /// Every widget must have a positive size, fit the grid, and not overlap another
+    pub fn validate(&self) -> Result<(), DashboardError> {
+        for widget in &self.widgets {
+            if widget.w < 1 || widget.h < 1 {
+                return Err(DashboardError::InvalidLayout(format!(
+                    "widget {} must be at least 1x1",
+                    widget.metric_id
+                )));
+            }
+            if widget.x < 0 || widget.y < 0 || widget.x + widget.w > GRID_COLUMNS {
+                return Err(DashboardError::InvalidLayout(format!(
+                    "widget {} does not fit the {}-column grid",
+                    widget.metric_id, GRID_COLUMNS
+                )));
+            }
+        }
+
+        for (i, a) in self.widgets.iter().enumerate() {
+            if let Some(b) = self.widgets[i + 1..].iter().find(|b| a.overlaps(b)) {
+                return Err(DashboardError::InvalidLayout(format!(
+                    "widgets {} and {} overlap",
+                    a.metric_id, b.metric_id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate and upsert a layout for its user (or as the tenant default)
+pub async fn save_layout(
+    pool: &PgPool,
+    layout: &DashboardLayout,
+) -> Result<DashboardLayout, DashboardError> {
+    layout.validate()?;
+
+    let query = if layout.user_id.is_some() {
+        r#"
+        INSERT INTO dashboard_layouts (id, tenant_id, user_id, widgets)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (tenant_id, user_id) WHERE user_id IS NOT NULL
+        DO UPDATE SET widgets = EXCLUDED.widgets, updated_at = NOW()
+        RETURNING id
+        "#
+    } else {
+        r#"
+        INSERT INTO dashboard_layouts (id, tenant_id, user_id, widgets)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (tenant_id) WHERE user_id IS NULL
+        DO UPDATE SET widgets = EXCLUDED.widgets, updated_at = NOW()
+        RETURNING id
+        "#
+    };
+
+    let id: Uuid = sqlx::query_scalar(query)
+        .bind(layout.id)
+        .bind(layout.tenant_id)
+        .bind(layout.user_id)
+        .bind(serde_json::json!(layout.widgets))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(DashboardLayout { id, ..layout.clone() })
+}
+
+/// The user's layout, else the tenant default, else [`DashboardLayout::system_default`]
+pub async fn get_layout(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<DashboardLayout, DashboardError> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, user_id, widgets
+        FROM dashboard_layouts
+        WHERE tenant_id = $1
+          AND (user_id = $2 OR user_id IS NULL)
+        ORDER BY user_id NULLS LAST
+        LIMIT 1
+        "#
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(DashboardLayout::system_default(tenant_id));
+    };
+
+    let widgets: serde_json::Value = row.get("widgets");
+    Ok(DashboardLayout {
+        id: row.get("id"),
+        tenant_id,
+        user_id: row.get("user_id"),
+        widgets: serde_json::from_value(widgets).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::get_test_pool;
+
+    fn layout(widgets: Vec<WidgetPlacement>) -> DashboardLayout {
+        DashboardLayout { id: Uuid::new_v4(), tenant_id: Uuid::new_v4(), user_id: None, widgets }
+    }
+
+    #[test]
+    fn test_system_default_is_valid() {
+        assert!(DashboardLayout::system_default(Uuid::new_v4()).validate().is_ok());
+    }
+
+    #[test]
+    fn test_overlap_detection() {
+        let touching = layout(vec![
+            WidgetPlacement::new("total_leads", 0, 0, 6, 2),
+            WidgetPlacement::new("total_deals", 6, 0, 6, 2),
+            WidgetPlacement::new("sales_trend", 0, 2, 12, 4),
+        ]);
+        assert!(touching.validate().is_ok());
+
+        let overlapping = layout(vec![
+            WidgetPlacement::new("total_leads", 0, 0, 6, 2),
+            WidgetPlacement::new("sales_trend", 0, 2, 12, 4),
+            WidgetPlacement::new("funnel", 5, 1, 2, 2),
+        ]);
+        let err = overlapping.validate().unwrap_err();
+        assert!(matches!(err, DashboardError::InvalidLayout(ref msg) if msg.contains("total_leads and funnel")));
+    }
+
+    #[test]
+    fn test_grid_bounds() {
+        for widget in [
+            WidgetPlacement::new("wide", 8, 0, 5, 2),
+            WidgetPlacement::new("negative", -1, 0, 2, 2),
+            WidgetPlacement::new("empty", 0, 0, 0, 2),
+        ] {
+            assert!(matches!(layout(vec![widget]).validate(), Err(DashboardError::InvalidLayout(_))));
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_layout_fallback_chain() {
+        let pool = get_test_pool().await;
+        let tenant_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Layout Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("layout-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, $3, 'Layout User', 'x')")
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(format!("{}@example.com", user_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Nothing saved: system default
+        let layout = get_layout(&pool, tenant_id, user_id).await.unwrap();
+        assert_eq!(layout, DashboardLayout::system_default(tenant_id));
+
+        // Tenant default
+        let tenant_default = DashboardLayout {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id: None,
+            widgets: vec![WidgetPlacement::new("win_rate", 0, 0, 12, 3)],
+        };
+        save_layout(&pool, &tenant_default).await.unwrap();
+        assert_eq!(get_layout(&pool, tenant_id, user_id).await.unwrap(), tenant_default);
+
+        // User layout wins; saving again updates in place
+        let mut mine = DashboardLayout {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id: Some(user_id),
+            widgets: vec![WidgetPlacement::new("funnel", 0, 0, 4, 4)],
+        };
+        let saved = save_layout(&pool, &mine).await.unwrap();
+        mine.widgets.push(WidgetPlacement::new("total_leads", 4, 0, 4, 2));
+        mine.id = Uuid::new_v4();
+        let updated = save_layout(&pool, &mine).await.unwrap();
+        assert_eq!(updated.id, saved.id);
+        assert_eq!(get_layout(&pool, tenant_id, user_id).await.unwrap(), updated);
+
+        // Other users still get the tenant default
+        assert_eq!(get_layout(&pool, tenant_id, Uuid::new_v4()).await.unwrap(), tenant_default);
+
+        // Invalid layouts are rejected before hitting the database
+        mine.widgets.push(WidgetPlacement::new("overlap", 0, 0, 2, 2));
+        assert!(matches!(save_layout(&pool, &mine).await, Err(DashboardError::InvalidLayout(_))));
+
+        for sql in ["DELETE FROM users WHERE tenant_id = $1", "DELETE FROM tenants WHERE id = $1"] {
+            sqlx::query(sql).bind(tenant_id).execute(&pool).await.unwrap();
+        }
+    }
+}
@@ -1,9 +1,13 @@
 //! Metrics and dashboard models
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use thiserror::Error;
 use uuid::Uuid;
 
+use crate::dashboard::DateRange;
+
 /// Aggregation function for metrics
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -96,3 +100,306 @@ pub struct MetricValue {
     pub formatted_value: String,
     pub computed_at: DateTime<Utc>,
 }
+
+/// Bucket width for a metric series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interval {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl Interval {
+    /// `date_trunc` field name; weeks start on Monday
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interval::Hour => "hour",
+            Interval::Day => "day",
+            Interval::Week => "week",
+            Interval::Month => "month",
+        }
+    }
+}
+
+/// One bucket of a metric series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricPoint {
+    /// Bucket start as wall-clock time in the tenant's timezone
+    pub bucket: NaiveDateTime,
+    pub value: f64,
+}
+
+#[derive(Debug, Error)]
+pub enum MetricError {
+    #[error("{0:?} aggregation requires a field")]
+    MissingField(AggregationType),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// SQL aggregate over `r.data->>$5` for the metric's aggregation
+fn aggregate_sql(metric: &MetricDef) -> Result<&'static str, MetricError> {
+    if metric.aggregation != AggregationType::Count && metric.field.is_none() {
+        return Err(MetricError::MissingField(metric.aggregation.clone()));
+    }
+
+    Ok(match metric.aggregation {
+        AggregationType::Count => "COUNT(*)::float8",
+        AggregationType::Sum => "SUM(CASE WHEN jsonb_typeof(r.data->$5) = 'number' THEN (r.data->>$5)::float8 END)",
+        AggregationType::Avg => "AVG(CASE WHEN jsonb_typeof(r.data->$5) = 'number' THEN (r.data->>$5)::float8 END)",
+        AggregationType::Min => "MIN(CASE WHEN jsonb_typeof(r.data->$5) = 'number' THEN (r.data->>$5)::float8 END)",
+        AggregationType::Max => "MAX(CASE WHEN jsonb_typeof(r.data->$5) = 'number' THEN (r.data->>$5)::float8 END)",
+        AggregationType::CountDistinct => "COUNT(DISTINCT r.data->>$5)::float8",
+    })
+}
+
+/// Time-bucketed values of `metric` over `range`, one point per bucket.
+///
+/// Buckets follow the tenant's `settings.timezone` (UTC if unset), so a day
+/// bucket is a local calendar day even across DST changes. Hours skipped by
+/// a spring-forward transition produce no bucket. Empty buckets are zero.
+pub async fn metric_series(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    metric: &MetricDef,
+    interval: Interval,
+    range: &DateRange,
+) -> Result<Vec<MetricPoint>, MetricError> {
+    let sql = format!(
+        r#"
+        WITH tz AS (
+            SELECT COALESCE((SELECT settings->>'timezone' FROM tenants WHERE id = $1), 'UTC') AS name
+        ),
+        buckets AS (
+            SELECT b.bucket
+            FROM tz, generate_series(
+                date_trunc($2, $3::date::timestamp),
+                date_trunc($2, ($4::date + 1)::timestamp - interval '1 microsecond'),
+                ('1 ' || $2)::interval
+            ) AS b(bucket)
+            WHERE (b.bucket AT TIME ZONE tz.name) AT TIME ZONE tz.name = b.bucket
+        ),
+        agg AS (
+            SELECT date_trunc($2, r.created_at AT TIME ZONE tz.name) AS bucket, {aggregate} AS value
+            FROM entity_records r
+            JOIN entity_types et ON et.id = r.entity_type_id AND et.tenant_id = r.tenant_id
+            CROSS JOIN tz
+            WHERE r.tenant_id = $1
+              AND et.name = $6
+              AND r.deleted_at IS NULL
+              AND r.created_at AT TIME ZONE tz.name >= $3::date
+              AND r.created_at AT TIME ZONE tz.name < $4::date + 1
+              AND ($7::jsonb IS NULL OR r.data @> $7)
+            GROUP BY 1
+        )
+        SELECT b.bucket, COALESCE(a.value, 0)::float8 AS value
+        FROM buckets b
+        LEFT JOIN agg a ON a.bucket = b.bucket
+        ORDER BY b.bucket
+        "#,
+        aggregate = aggregate_sql(metric)?,
+    );
+
+    let filters = metric
+        .filters
+        .as_object()
+        .filter(|f| !f.is_empty())
+        .map(|_| metric.filters.clone());
+
+    let rows = sqlx::query(&sql)
+        .bind(tenant_id)
+        .bind(interval.as_str())
+        .bind(range.from)
+        .bind(range.to)
+        .bind(metric.field.as_deref())
+        .bind(&metric.entity_type)
+        .bind(filters)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| MetricPoint {
+            bucket: row.get("bucket"),
+            value: row.get("value"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+    use serde_json::json;
+    use test_support::TestTenant;
+
+    /// Tenant in America/New_York with a `deal` entity type; removed by `cleanup`
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Metrics Test").await;
+            sqlx::query("UPDATE tenants SET settings = $1 WHERE id = $2")
+                .bind(json!({ "timezone": "America/New_York" }))
+                .bind(tenant.id)
+                .execute(&tenant.pool)
+                .await
+                .unwrap();
+            let entity_type_id = tenant.entity_type("crm", "deal", "Deal", "Deals").await;
+            Self { tenant, entity_type_id }
+        }
+
+        async fn insert(&self, created_at: DateTime<Utc>, data: serde_json::Value) {
+            sqlx::query(
+                "INSERT INTO entity_records (tenant_id, entity_type_id, data, created_at) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(self.tenant.id)
+            .bind(self.entity_type_id)
+            .bind(data)
+            .bind(created_at)
+            .execute(&self.tenant.pool)
+            .await
+            .unwrap();
+        }
+
+        fn metric(&self, aggregation: AggregationType, field: Option<&str>) -> MetricDef {
+            MetricDef {
+                id: Uuid::new_v4(),
+                tenant_id: self.tenant.id,
+                name: "deals".to_string(),
+                label: "Deals".to_string(),
+                description: None,
+                entity_type: "deal".to_string(),
+                aggregation,
+                field: field.map(str::to_string),
+                filters: json!({}),
+                format: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    fn range(from: (i32, u32, u32), to: (i32, u32, u32)) -> DateRange {
+        DateRange {
+            from: NaiveDate::from_ymd_opt(from.0, from.1, from.2).unwrap(),
+            to: NaiveDate::from_ymd_opt(to.0, to.1, to.2).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_field_required_for_value_aggregations() {
+        let metric = MetricDef {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "revenue".to_string(),
+            label: "Revenue".to_string(),
+            description: None,
+            entity_type: "deal".to_string(),
+            aggregation: AggregationType::Sum,
+            field: None,
+            filters: json!({}),
+            format: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert!(matches!(aggregate_sql(&metric), Err(MetricError::MissingField(AggregationType::Sum))));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_daily_buckets_use_tenant_timezone_across_dst() {
+        let fixture = Fixture::new().await;
+        // US clocks sprang forward at 2024-03-10 02:00 local (07:00 UTC)
+        fixture.insert(utc(2024, 3, 10, 4, 30), json!({ "amount": 100 })).await; // Mar 9, 23:30 EST
+        fixture.insert(utc(2024, 3, 10, 6, 30), json!({ "amount": 200 })).await; // Mar 10, 01:30 EST
+        fixture.insert(utc(2024, 3, 11, 3, 30), json!({ "amount": 300 })).await; // Mar 10, 23:30 EDT
+        fixture.insert(utc(2024, 3, 11, 4, 30), json!({ "amount": 400 })).await; // Mar 11, 00:30 EDT
+
+        let count = metric_series(
+            &fixture.tenant.pool,
+            fixture.tenant.id,
+            &fixture.metric(AggregationType::Count, None),
+            Interval::Day,
+            &range((2024, 3, 8), (2024, 3, 11)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            count,
+            vec![
+                MetricPoint { bucket: day(2024, 3, 8), value: 0.0 },
+                MetricPoint { bucket: day(2024, 3, 9), value: 1.0 },
+                MetricPoint { bucket: day(2024, 3, 10), value: 2.0 },
+                MetricPoint { bucket: day(2024, 3, 11), value: 1.0 },
+            ]
+        );
+
+        let avg = metric_series(
+            &fixture.tenant.pool,
+            fixture.tenant.id,
+            &fixture.metric(AggregationType::Avg, Some("amount")),
+            Interval::Day,
+            &range((2024, 3, 9), (2024, 3, 10)),
+        )
+        .await
+        .unwrap();
+        let values: Vec<f64> = avg.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![100.0, 250.0]);
+
+        fixture.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_hourly_buckets_skip_missing_dst_hour() {
+        let fixture = Fixture::new().await;
+        fixture.insert(utc(2024, 3, 10, 6, 59), json!({ "amount": 5 })).await; // 01:59 EST
+        fixture.insert(utc(2024, 3, 10, 7, 0), json!({ "amount": 7 })).await; // 03:00 EDT
+
+        let series = metric_series(
+            &fixture.tenant.pool,
+            fixture.tenant.id,
+            &fixture.metric(AggregationType::Sum, Some("amount")),
+            Interval::Hour,
+            &range((2024, 3, 10), (2024, 3, 10)),
+        )
+        .await
+        .unwrap();
+
+        // 23 local hours: 02:00 never happened
+        assert_eq!(series.len(), 23);
+        assert!(series.iter().all(|p| p.bucket != day(2024, 3, 10) + chrono::Duration::hours(2)));
+        let value_at = |hour: i64| {
+            series
+                .iter()
+                .find(|p| p.bucket == day(2024, 3, 10) + chrono::Duration::hours(hour))
+                .map(|p| p.value)
+        };
+        assert_eq!(value_at(1), Some(5.0));
+        assert_eq!(value_at(3), Some(7.0));
+        assert_eq!(series.iter().map(|p| p.value).sum::<f64>(), 12.0);
+
+        fixture.cleanup().await;
+    }
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// A search result item
@@ -15,6 +16,152 @@ pub struct SearchResult {
     pub score: f32,
 }
 
+/// Postgres tsvector weight class; `A` ranks highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldWeight {
+    A,
+    B,
+    C,
+    D,
+}
+
+/// Which `data` fields are searchable per entity type, and how much each counts
+///
+/// Entity types without an entry fall back to `default`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchWeights {
+    pub default: HashMap<String, FieldWeight>,
+    pub by_entity_type: HashMap<String, HashMap<String, FieldWeight>>,
+}
+
+impl Default for SearchWeights {
+    fn default() -> Self {
+        let default = [
+            ("name", FieldWeight::A),
+            ("title", FieldWeight::A),
+            ("email", FieldWeight::B),
+            ("description", FieldWeight::C),
+            ("notes", FieldWeight::C),
+        ]
+        .into_iter()
+        .map(|(field, weight)| (field.to_string(), weight))
+        .collect();
+
+        Self { default, by_entity_type: HashMap::new() }
+    }
+}
+
+impl SearchWeights {
+    pub fn with_entity_type(mut self, entity_type: &str, fields: &[(&str, FieldWeight)]) -> Self {
+        let fields = fields.iter().map(|(field, weight)| (field.to_string(), *weight)).collect();
+        self.by_entity_type.insert(entity_type.to_string(), fields);
+        self
+    }
+
+    /// `{"<entity_type>": {"<field>": "A"}, "*": {...}}`, consumed by the ranking query
+    fn to_json(&self) -> serde_json::Value {
+        let mut map = serde_json::Map::new();
+        map.insert("*".to_string(), serde_json::json!(self.default));
+        for (entity_type, fields) in &self.by_entity_type {
+            map.insert(entity_type.clone(), serde_json::json!(fields));
+        }
+        serde_json::Value::Object(map)
+    }
+}
+
+/// Options for [`SearchService::search_ranked`] and [`SearchService::search_with_facets`]
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub entity_types: Option<Vec<String>>,
+    pub min_rank: f32,
+    pub limit: i64,
+    pub weights: SearchWeights,
+    /// `data` field -> accepted values; a record must match every field
+    pub filters: HashMap<String, Vec<String>>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            entity_types: None,
+            min_rank: 0.0,
+            limit: 20,
+            weights: SearchWeights::default(),
+            filters: HashMap::new(),
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn with_entity_types(mut self, entity_types: Vec<String>) -> Self {
+        self.entity_types = Some(entity_types);
+        self
+    }
+
+    pub fn with_min_rank(mut self, min_rank: f32) -> Self {
+        self.min_rank = min_rank;
+        self
+    }
+
+    pub fn with_limit(mut self, limit: i64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: SearchWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_filter(mut self, field: &str, values: &[&str]) -> Self {
+        self.filters
+            .insert(field.to_string(), values.iter().map(|v| v.to_string()).collect());
+        self
+    }
+}
+
+/// A ranked full-text match with a highlighted snippet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub entity_id: Uuid,
+    pub entity_type: String,
+    pub rank: f32,
+    /// Matched text with terms wrapped in `<mark>...</mark>`
+    pub snippet: String,
+}
+
+/// Number of matching records sharing one value of a facet field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetBucket {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Weighted document built from the fields listed in `$3` for the record's entity type
+const WEIGHTED_DOCUMENT_SQL: &str = r#"
+    setweight(to_tsvector('simple', COALESCE((
+        SELECT string_agg(r.data->>f.key, ' ') FROM jsonb_each_text(COALESCE($3::jsonb -> et.name, $3::jsonb -> '*')) f WHERE f.value = 'A'
+    ), '')), 'A') ||
+    setweight(to_tsvector('simple', COALESCE((
+        SELECT string_agg(r.data->>f.key, ' ') FROM jsonb_each_text(COALESCE($3::jsonb -> et.name, $3::jsonb -> '*')) f WHERE f.value = 'B'
+    ), '')), 'B') ||
+    setweight(to_tsvector('simple', COALESCE((
+        SELECT string_agg(r.data->>f.key, ' ') FROM jsonb_each_text(COALESCE($3::jsonb -> et.name, $3::jsonb -> '*')) f WHERE f.value = 'C'
+    ), '')), 'C') ||
+    setweight(to_tsvector('simple', COALESCE((
+        SELECT string_agg(r.data->>f.key, ' ') FROM jsonb_each_text(COALESCE($3::jsonb -> et.name, $3::jsonb -> '*')) f WHERE f.value = 'D'
+    ), '')), 'D')
+"#;
+
+/// Plain text of the same fields, highest weight first, for `ts_headline`
+const HEADLINE_TEXT_SQL: &str = r#"
+    COALESCE((
+        SELECT string_agg(r.data->>f.key, ' … ' ORDER BY f.value, f.key)
+        FROM jsonb_each_text(COALESCE($3::jsonb -> et.name, $3::jsonb -> '*')) f
+        WHERE r.data->>f.key IS NOT NULL
+    ), '')
+"#;
+
 /// Search service for full-text search across entities
 pub struct SearchService {
     pool: PgPool,
@@ -73,4 +220,306 @@ impl SearchService {
 
         Ok(results)
     }
+
+    /// Ranked full-text search over entity records using `ts_rank_cd`,
+    /// returning `<mark>`-highlighted snippets via `ts_headline`
+    pub async fn search_ranked(
+        &self,
+        tenant_id: Uuid,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        use sqlx::Row;
+
+        let sql = format!(
+            r#"
+            {matches}
+            SELECT
+                m.id,
+                m.entity_type,
+                m.rank,
+                ts_headline('simple', m.body, m.query, 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2') AS snippet
+            FROM matches m
+            ORDER BY m.rank DESC, m.id
+            LIMIT $7
+            "#,
+            matches = matches_cte(),
+        );
+
+        let rows = bind_matches(sqlx::query(&sql), tenant_id, query, options, &options.filters)
+            .bind(options.limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let hits = rows
+            .iter()
+            .map(|row| SearchHit {
+                entity_id: row.try_get("id").unwrap_or_default(),
+                entity_type: row.try_get("entity_type").unwrap_or_default(),
+                rank: row.try_get("rank").unwrap_or_default(),
+                snippet: row.try_get("snippet").unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(hits)
+    }
+
+    /// Ranked search plus value counts for each of `facet_fields`
+    ///
+    /// Each facet is counted with every filter applied except its own, so
+    /// selecting `status = active` still reports how many `sold` records match.
+    pub async fn search_with_facets(
+        &self,
+        tenant_id: Uuid,
+        query: &str,
+        options: &SearchOptions,
+        facet_fields: &[String],
+    ) -> Result<(Vec<SearchHit>, HashMap<String, Vec<FacetBucket>>), sqlx::Error> {
+        use sqlx::Row;
+
+        let hits = self.search_ranked(tenant_id, query, options).await?;
+
+        let sql = format!(
+            r#"
+            {matches}
+            SELECT m.data->>$7 AS value, COUNT(*) AS count
+            FROM matches m
+            WHERE m.data->>$7 IS NOT NULL
+            GROUP BY 1
+            ORDER BY count DESC, value
+            "#,
+            matches = matches_cte(),
+        );
+
+        let mut facets = HashMap::new();
+        for field in facet_fields {
+            let mut filters = options.filters.clone();
+            filters.remove(field);
+
+            let rows = bind_matches(sqlx::query(&sql), tenant_id, query, options, &filters)
+                .bind(field)
+                .fetch_all(&self.pool)
+                .await?;
+
+            let buckets = rows
+                .iter()
+                .map(|row| FacetBucket {
+                    value: row.try_get("value").unwrap_or_default(),
+                    count: row.try_get("count").unwrap_or_default(),
+                })
+                .collect();
+            facets.insert(field.clone(), buckets);
+        }
+
+        Ok((hits, facets))
+    }
+}
+
+/// `matches` CTE shared by ranked search and facet counts.
+///
+/// Binds: $1 tenant, $2 query, $3 weights, $4 entity types, $5 filters, $6 min rank.
+fn matches_cte() -> String {
+    format!(
+        r#"
+        WITH q AS (SELECT websearch_to_tsquery('simple', $2) AS query),
+        docs AS (
+            SELECT
+                r.id,
+                r.data,
+                et.name AS entity_type,
+                {document} AS document,
+                {headline_text} AS body
+            FROM entity_records r
+            JOIN entity_types et ON et.id = r.entity_type_id AND et.tenant_id = r.tenant_id
+            WHERE r.tenant_id = $1
+              AND r.deleted_at IS NULL
+              AND ($4::text[] IS NULL OR et.name = ANY($4))
+              AND NOT EXISTS (
+                  SELECT 1 FROM jsonb_each($5::jsonb) f
+                  WHERE NOT COALESCE(f.value ? (r.data->>f.key), false)
+              )
+        ),
+        matches AS (
+            SELECT d.*, q.query, ts_rank_cd(d.document, q.query) AS rank
+            FROM docs d, q
+            WHERE d.document @@ q.query
+              AND ts_rank_cd(d.document, q.query) >= $6
+        )
+        "#,
+        document = WEIGHTED_DOCUMENT_SQL,
+        headline_text = HEADLINE_TEXT_SQL,
+    )
+}
+
+fn bind_matches<'q>(
+    query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
+    tenant_id: Uuid,
+    search: &'q str,
+    options: &'q SearchOptions,
+    filters: &HashMap<String, Vec<String>>,
+) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    query
+        .bind(tenant_id)
+        .bind(search)
+        .bind(options.weights.to_json())
+        .bind(options.entity_types.as_deref())
+        .bind(serde_json::json!(filters))
+        .bind(options.min_rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_support::TestTenant;
+
+    #[test]
+    fn test_weights_json_falls_back_to_default() {
+        let weights = SearchWeights::default()
+            .with_entity_type("deal", &[("name", FieldWeight::A), ("summary", FieldWeight::B)]);
+
+        let json = weights.to_json();
+
+        assert_eq!(json["*"]["title"], json!("A"));
+        assert_eq!(json["*"]["description"], json!("C"));
+        assert_eq!(json["deal"], json!({ "name": "A", "summary": "B" }));
+    }
+
+    /// Tenant with one `listing` entity type; removed again by `cleanup`
+    struct Fixture {
+        tenant: TestTenant,
+        entity_type_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let tenant = TestTenant::new("Search Test").await;
+            let entity_type_id = tenant.entity_type("crm", "listing", "Listing", "Listings").await;
+            Self { tenant, entity_type_id }
+        }
+
+        async fn insert(&self, tenant_id: Uuid, data: serde_json::Value) -> Uuid {
+            sqlx::query_scalar(
+                "INSERT INTO entity_records (tenant_id, entity_type_id, data) VALUES ($1, $2, $3) RETURNING id",
+            )
+            .bind(tenant_id)
+            .bind(self.entity_type_id)
+            .bind(data)
+            .fetch_one(&self.tenant.pool)
+            .await
+            .unwrap()
+        }
+
+        async fn cleanup(self) {
+            self.tenant.cleanup().await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_title_hit_outranks_body_hit() {
+        let fixture = Fixture::new().await;
+        let body_hit = fixture
+            .insert(fixture.tenant.id, json!({ "title": "Townhouse", "description": "Quiet street near the marina" }))
+            .await;
+        let title_hit = fixture
+            .insert(fixture.tenant.id, json!({ "title": "Marina penthouse", "description": "Top floor" }))
+            .await;
+        fixture.insert(fixture.tenant.id, json!({ "title": "Desert villa" })).await;
+
+        let service = SearchService::new(fixture.tenant.pool.clone());
+        let hits = service
+            .search_ranked(fixture.tenant.id, "marina", &SearchOptions::default())
+            .await
+            .unwrap();
+
+        let ids: Vec<Uuid> = hits.iter().map(|h| h.entity_id).collect();
+        assert_eq!(ids, vec![title_hit, body_hit]);
+        assert!(hits[0].rank > hits[1].rank);
+        assert_eq!(hits[0].entity_type, "listing");
+        assert!(hits[0].snippet.contains("<mark>Marina</mark>"), "{}", hits[0].snippet);
+        assert!(hits[1].snippet.contains("<mark>marina</mark>"), "{}", hits[1].snippet);
+
+        // min_rank drops the weaker description-only match
+        let strong = service
+            .search_ranked(fixture.tenant.id, "marina", &SearchOptions::default().with_min_rank(hits[0].rank))
+            .await
+            .unwrap();
+        assert_eq!(strong.len(), 1);
+        assert_eq!(strong[0].entity_id, title_hit);
+
+        fixture.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_entity_type_weights_and_tenant_isolation() {
+        let fixture = Fixture::new().await;
+        let other = Fixture::new().await;
+        let foreign = other.insert(other.tenant.id, json!({ "title": "Marina loft" })).await;
+        let notes_hit = fixture.insert(fixture.tenant.id, json!({ "title": "Loft", "notes": "marina view" })).await;
+        let title_hit = fixture.insert(fixture.tenant.id, json!({ "title": "Marina loft" })).await;
+
+        // Flip the weights for listings so notes outrank titles
+        let weights = SearchWeights::default()
+            .with_entity_type("listing", &[("title", FieldWeight::D), ("notes", FieldWeight::A)]);
+        let service = SearchService::new(fixture.tenant.pool.clone());
+        let hits = service
+            .search_ranked(fixture.tenant.id, "marina", &SearchOptions::default().with_weights(weights))
+            .await
+            .unwrap();
+
+        let ids: Vec<Uuid> = hits.iter().map(|h| h.entity_id).collect();
+        assert_eq!(ids, vec![notes_hit, title_hit]);
+        assert!(!ids.contains(&foreign));
+
+        other.cleanup().await;
+        fixture.cleanup().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_facets_exclude_their_own_filter() {
+        let fixture = Fixture::new().await;
+        let mut matching = Vec::new();
+        for (status, city) in [
+            ("active", "Dubai"),
+            ("active", "Abu Dhabi"),
+            ("sold", "Dubai"),
+            ("sold", "Dubai"),
+            ("active", "Dubai"),
+        ] {
+            let id = fixture
+                .insert(fixture.tenant.id, json!({ "title": "Beach villa", "status": status, "city": city }))
+                .await;
+            matching.push(id);
+        }
+        // Outside the query's matching set, so never counted
+        fixture
+            .insert(fixture.tenant.id, json!({ "title": "Office", "status": "sold", "city": "Dubai" }))
+            .await;
+
+        let options = SearchOptions::default()
+            .with_filter("status", &["active"])
+            .with_filter("city", &["Dubai"]);
+        let facet_fields = vec!["status".to_string(), "city".to_string()];
+        let (hits, facets) = SearchService::new(fixture.tenant.pool.clone())
+            .search_with_facets(fixture.tenant.id, "villa", &options, &facet_fields)
+            .await
+            .unwrap();
+
+        let mut ids: Vec<Uuid> = hits.iter().map(|h| h.entity_id).collect();
+        ids.sort();
+        let mut expected = vec![matching[0], matching[4]];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let bucket = |value: &str, count: i64| FacetBucket { value: value.to_string(), count };
+        // status ignores the status filter but honours city = Dubai
+        assert_eq!(facets["status"], vec![bucket("active", 2), bucket("sold", 2)]);
+        // city ignores the city filter but honours status = active
+        assert_eq!(facets["city"], vec![bucket("Dubai", 2), bucket("Abu Dhabi", 1)]);
+
+        fixture.cleanup().await;
+    }
 }
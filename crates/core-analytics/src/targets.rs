@@ -4,6 +4,8 @@
 use sqlx::{PgPool, Row};
 use serde::{Deserialize, Serialize};
 use chrono::{NaiveDate, Utc, Datelike};
+use thiserror::Error;
+use uuid::Uuid;
 
 /// A resolved target with calculated progress
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -35,6 +37,170 @@ impl MetricType {
             MetricType::ListingsAdded => "listings_added",
         }
     }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "revenue" => Some(MetricType::Revenue),
+            "deals_won" => Some(MetricType::DealsWon),
+            "leads_created" => Some(MetricType::LeadsCreated),
+            "calls_made" => Some(MetricType::CallsMade),
+            "viewings_completed" => Some(MetricType::ViewingsCompleted),
+            "listings_added" => Some(MetricType::ListingsAdded),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a target is beaten by going over it (revenue) or staying under it (costs)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetDirection {
+    #[default]
+    HigherIsBetter,
+    LowerIsBetter,
+}
+
+/// A stored target row
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Target {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub metric_type: String,
+    pub target_value: f64,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub direction: TargetDirection,
+}
+
+/// Pacing of a target as of a given date
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TargetProgress {
+    pub actual: f64,
+    /// Target prorated linearly over the days elapsed in the period
+    pub expected_to_date: f64,
+    /// `actual` as a percentage of the full target
+    pub attainment_pct: f64,
+    /// End-of-period value at the current run-rate; `None` before the period starts
+    pub projected_end: Option<f64>,
+    pub on_pace: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum TargetError {
+    #[error("Target not found: {0}")]
+    NotFound(Uuid),
+
+    #[error("Unsupported target metric: {0}")]
+    UnsupportedMetric(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Pace `actual` against `target` on `as_of`; both period dates are inclusive
+pub fn calculate_target_progress(target: &Target, actual: f64, as_of: NaiveDate) -> TargetProgress {
+    let total_days = ((target.end_date - target.start_date).num_days() + 1) as f64;
+    let elapsed_days = ((as_of - target.start_date).num_days() + 1).clamp(0, total_days as i64) as f64;
+
+    let expected_to_date = target.target_value * elapsed_days / total_days;
+    let attainment_pct = if target.target_value > 0.0 {
+        actual / target.target_value * 100.0
+    } else {
+        0.0
+    };
+    let projected_end = (elapsed_days > 0.0).then(|| actual / elapsed_days * total_days);
+    let on_pace = match target.direction {
+        TargetDirection::HigherIsBetter => actual >= expected_to_date,
+        TargetDirection::LowerIsBetter => actual <= expected_to_date,
+    };
+
+    TargetProgress { actual, expected_to_date, attainment_pct, projected_end, on_pace }
+}
+
+/// Load a target, measure its metric from the period start up to `as_of`, and pace it
+pub async fn target_progress(
+    pool: &PgPool,
+    target_id: Uuid,
+    as_of: NaiveDate,
+) -> Result<TargetProgress, TargetError> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, tenant_id, user_id, metric_type, target_value::float8 AS target_value,
+               start_date, end_date, direction
+        FROM agent_targets
+        WHERE id = $1
+        "#
+    )
+    .bind(target_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(TargetError::NotFound(target_id))?;
+
+    let direction: String = row.get("direction");
+    let target = Target {
+        id: row.get("id"),
+        tenant_id: row.get("tenant_id"),
+        user_id: row.get("user_id"),
+        metric_type: row.get("metric_type"),
+        target_value: row.get("target_value"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        direction: serde_json::from_str(&format!("\"{}\"", direction)).unwrap_or_default(),
+    };
+
+    let actual = if as_of < target.start_date {
+        0.0
+    } else {
+        let metric = MetricType::parse(&target.metric_type)
+            .ok_or_else(|| TargetError::UnsupportedMetric(target.metric_type.clone()))?;
+        measure_metric(pool, &target, metric, as_of.min(target.end_date)).await?
+    };
+
+    Ok(calculate_target_progress(&target, actual, as_of))
+}
+
+/// Metric value for the target's tenant (and owner, if set) from its start date through `until`
+async fn measure_metric(
+    pool: &PgPool,
+    target: &Target,
+    metric: MetricType,
+    until: NaiveDate,
+) -> Result<f64, TargetError> {
+    let query = match metric {
+        MetricType::Revenue => r#"
+            SELECT COALESCE(SUM(amount), 0)::float8 AS actual
+            FROM deals
+            WHERE tenant_id = $1 AND ($2::uuid IS NULL OR owner_id = $2)
+              AND stage IN ('Won', 'Closed Won')
+              AND COALESCE(actual_close_date, created_at::date) BETWEEN $3 AND $4
+            "#,
+        MetricType::DealsWon => r#"
+            SELECT COUNT(*)::float8 AS actual
+            FROM deals
+            WHERE tenant_id = $1 AND ($2::uuid IS NULL OR owner_id = $2)
+              AND stage IN ('Won', 'Closed Won')
+              AND COALESCE(actual_close_date, created_at::date) BETWEEN $3 AND $4
+            "#,
+        MetricType::LeadsCreated => r#"
+            SELECT COUNT(*)::float8 AS actual
+            FROM contacts
+            WHERE tenant_id = $1 AND ($2::uuid IS NULL OR owner_id = $2)
+              AND lifecycle_stage = 'lead'
+              AND created_at::date BETWEEN $3 AND $4
+            "#,
+        other => return Err(TargetError::UnsupportedMetric(other.as_str().to_string())),
+    };
+
+    let row = sqlx::query(query)
+        .bind(target.tenant_id)
+        .bind(target.user_id)
+        .bind(target.start_date)
+        .bind(until)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.get("actual"))
 }
 
 /// Get target for a specific user, metric, and date range
@@ -261,3 +427,75 @@ pub fn calculate_progress(current: f64, target: f64) -> f64 {
         (current / target * 100.0).min(200.0) // Cap at 200%
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(direction: TargetDirection) -> Target {
+        // 30-day period, 3000 target => 100/day expected
+        Target {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            user_id: None,
+            metric_type: "revenue".to_string(),
+            target_value: 3000.0,
+            start_date: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(),
+            direction,
+        }
+    }
+
+    fn june(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_mid_period_ahead_of_pace() {
+        let progress = calculate_target_progress(&target(TargetDirection::HigherIsBetter), 1800.0, june(15));
+
+        assert_eq!(progress.expected_to_date, 1500.0);
+        assert_eq!(progress.attainment_pct, 60.0);
+        assert_eq!(progress.projected_end, Some(3600.0));
+        assert!(progress.on_pace);
+    }
+
+    #[test]
+    fn test_mid_period_behind_pace() {
+        let progress = calculate_target_progress(&target(TargetDirection::HigherIsBetter), 900.0, june(15));
+
+        assert_eq!(progress.expected_to_date, 1500.0);
+        assert_eq!(progress.attainment_pct, 30.0);
+        assert_eq!(progress.projected_end, Some(1800.0));
+        assert!(!progress.on_pace);
+    }
+
+    #[test]
+    fn test_lower_is_better_flips_pace() {
+        let under = calculate_target_progress(&target(TargetDirection::LowerIsBetter), 900.0, june(15));
+        let over = calculate_target_progress(&target(TargetDirection::LowerIsBetter), 1800.0, june(15));
+
+        assert!(under.on_pace);
+        assert!(!over.on_pace);
+        assert_eq!(over.projected_end, Some(3600.0));
+    }
+
+    #[test]
+    fn test_period_not_started() {
+        let progress = calculate_target_progress(&target(TargetDirection::HigherIsBetter), 0.0, june(1).pred_opt().unwrap());
+
+        assert_eq!(progress.expected_to_date, 0.0);
+        assert_eq!(progress.attainment_pct, 0.0);
+        assert_eq!(progress.projected_end, None);
+        assert!(progress.on_pace);
+    }
+
+    #[test]
+    fn test_period_finished_caps_elapsed_days() {
+        let progress = calculate_target_progress(&target(TargetDirection::HigherIsBetter), 3300.0, june(30) + chrono::Duration::days(10));
+
+        assert_eq!(progress.expected_to_date, 3000.0);
+        assert_eq!(progress.projected_end, Some(3300.0));
+        assert!(progress.on_pace);
+    }
+}
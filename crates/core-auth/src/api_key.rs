@@ -0,0 +1,257 @@
+//! API key management
+//!
+//! Keys are shown once, when created; afterwards only their prefix is
+//! known. Each key carries `resource:action` scopes and, optionally, the
+//! entity types its record access is limited to.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::session::sha256_hash;
+use crate::AuthError;
+
+/// Start of every API key secret
+pub const KEY_PREFIX: &str = "jk_";
+
+/// Characters of the secret kept to tell keys apart
+const SHOWN_LENGTH: usize = 11;
+
+/// Actions a scope can grant
+const ACTIONS: [&str; 4] = ["read", "write", "delete", "*"];
+
+/// An API key, without its secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    /// The secret's first characters, e.g. `jk_1a2b3c4d`
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    /// Entity types the key may touch records of; `None` for all
+    pub entity_types: Option<Vec<String>>,
+    pub created_by: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    /// Whether a scope grants `action` on `resource`
+    pub fn allows(&self, resource: &str, action: &str) -> bool {
+        self.scopes.iter().any(|scope| {
+            let (scope_resource, scope_action) = scope.split_once(':').unwrap_or((scope, "*"));
+            (scope_resource == "*" || scope_resource == resource) && (scope_action == "*" || scope_action == action)
+        })
+    }
+
+    /// Whether the key may touch records of `entity_type`
+    pub fn covers_entity(&self, entity_type: &str) -> bool {
+        self.entity_types
+            .as_ref()
+            .is_none_or(|types| types.iter().any(|t| t == entity_type))
+    }
+}
+
+/// What a key is for and what it may do
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewApiKey {
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub entity_types: Option<Vec<String>>,
+}
+
+/// Check scopes are `*` or `resource:action` with a known action
+pub fn validate_scopes(scopes: &[String]) -> Result<(), AuthError> {
+    for scope in scopes {
+        let valid = match scope.split_once(':') {
+            Some((resource, action)) => !resource.is_empty() && ACTIONS.contains(&action),
+            None => scope == "*",
+        };
+        if !valid {
+            return Err(AuthError::InvalidScope(scope.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// API key service
+#[derive(Clone)]
+pub struct ApiKeyService {
+    pool: PgPool,
+}
+
+impl ApiKeyService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a key; the secret is returned here and never again
+    pub async fn create(
+        &self,
+        tenant_id: Uuid,
+        created_by: Option<Uuid>,
+        input: NewApiKey,
+    ) -> Result<(ApiKey, String), AuthError> {
+        validate_scopes(&input.scopes)?;
+        let secret = format!("{}{}{}", KEY_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO api_keys (tenant_id, name, key_prefix, key_hash, scopes, entity_types, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, tenant_id, name, key_prefix, scopes, entity_types,
+                      created_by, last_used_at, revoked_at, created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&input.name)
+        .bind(&secret[..SHOWN_LENGTH])
+        .bind(sha256_hash(&secret))
+        .bind(&input.scopes)
+        .bind(&input.entity_types)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((api_key_from_row(&row)?, secret))
+    }
+
+    /// A tenant's keys, newest first, revoked ones included
+    pub async fn list(&self, tenant_id: Uuid) -> Result<Vec<ApiKey>, AuthError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tenant_id, name, key_prefix, scopes, entity_types,
+                   created_by, last_used_at, revoked_at, created_at
+            FROM api_keys
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(api_key_from_row).collect()
+    }
+
+    /// Change what a live key may do
+    pub async fn update_scopes(
+        &self,
+        tenant_id: Uuid,
+        key_id: Uuid,
+        scopes: &[String],
+        entity_types: Option<&[String]>,
+    ) -> Result<Option<ApiKey>, AuthError> {
+        validate_scopes(scopes)?;
+        let row = sqlx::query(
+            r#"
+            UPDATE api_keys SET scopes = $3, entity_types = $4
+            WHERE tenant_id = $1 AND id = $2 AND revoked_at IS NULL
+            RETURNING id, tenant_id, name, key_prefix, scopes, entity_types,
+                      created_by, last_used_at, revoked_at, created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(key_id)
+        .bind(scopes)
+        .bind(entity_types)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(api_key_from_row).transpose()
+    }
+
+    /// Revoke a key; false if the tenant has no such live key
+    pub async fn revoke(&self, tenant_id: Uuid, key_id: Uuid) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            r#"UPDATE api_keys SET revoked_at = NOW() WHERE tenant_id = $1 AND id = $2 AND revoked_at IS NULL"#,
+        )
+        .bind(tenant_id)
+        .bind(key_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The live key `secret` belongs to, noting that it was used
+    pub async fn authenticate(&self, secret: &str) -> Result<ApiKey, AuthError> {
+        if !secret.starts_with(KEY_PREFIX) {
+            return Err(AuthError::InvalidToken);
+        }
+        let row = sqlx::query(
+            r#"
+            UPDATE api_keys SET last_used_at = NOW()
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            RETURNING id, tenant_id, name, key_prefix, scopes, entity_types,
+                      created_by, last_used_at, revoked_at, created_at
+            "#,
+        )
+        .bind(sha256_hash(secret))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+
+        api_key_from_row(&row)
+    }
+}
+
+fn api_key_from_row(row: &sqlx::postgres::PgRow) -> Result<ApiKey, AuthError> {
+    use sqlx::Row;
+
+    Ok(ApiKey {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        name: row.try_get("name")?,
+        key_prefix: row.try_get("key_prefix")?,
+        scopes: row.try_get("scopes")?,
+        entity_types: row.try_get("entity_types")?,
+        created_by: row.try_get("created_by")?,
+        last_used_at: row.try_get("last_used_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(scopes: &[&str], entity_types: Option<&[&str]>) -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Zapier".to_string(),
+            key_prefix: "jk_1a2b3c4d".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            entity_types: entity_types.map(|types| types.iter().map(|t| t.to_string()).collect()),
+            created_by: None,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_scopes() {
+        let reader = key(&["records:read", "tasks:*"], Some(&["contact"]));
+        assert!(reader.allows("records", "read"));
+        assert!(!reader.allows("records", "write"));
+        assert!(reader.allows("tasks", "delete"));
+        assert!(!reader.allows("voice", "read"));
+        assert!(reader.covers_entity("contact"));
+        assert!(!reader.covers_entity("deal"));
+
+        let admin = key(&["*"], None);
+        assert!(admin.allows("voice", "write"));
+        assert!(admin.covers_entity("deal"));
+
+        assert!(validate_scopes(&["*:read".to_string(), "records:delete".to_string()]).is_ok());
+        assert!(validate_scopes(&["records".to_string()]).is_err());
+        assert!(validate_scopes(&["records:admin".to_string()]).is_err());
+    }
+}
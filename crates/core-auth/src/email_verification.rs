@@ -0,0 +1,144 @@
+//! Email verification
+//!
+//! New users confirm their address with a token mailed to them. A token
+//! works once and only until it expires, and issuing a new one invalidates
+//! any the user hasn't used. Only the token's hash is stored.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::session::sha256_hash;
+use crate::AuthError;
+
+/// A verification token issued for an account
+#[derive(Debug, Clone)]
+pub struct IssuedVerification {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Email verification service
+#[derive(Clone)]
+pub struct EmailVerificationService {
+    pool: PgPool,
+    /// Token lifetime (default: 24 hours)
+    token_ttl: Duration,
+}
+
+impl EmailVerificationService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            token_ttl: Duration::hours(24),
+        }
+    }
+
+    pub fn with_ttl(pool: PgPool, ttl: Duration) -> Self {
+        Self { pool, token_ttl: ttl }
+    }
+
+    /// Issue a verification token for the user, replacing any earlier unused
+    /// one; `None` when their email is already verified
+    pub async fn issue(&self, user_id: Uuid) -> Result<Option<IssuedVerification>, AuthError> {
+        let row = sqlx::query(r#"SELECT tenant_id, email, name, email_verified FROM users WHERE id = $1"#)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        if row.try_get::<bool, _>("email_verified")? {
+            return Ok(None);
+        }
+        let tenant_id: Uuid = row.try_get("tenant_id")?;
+
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let expires_at = Utc::now() + self.token_ttl;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(r#"DELETE FROM email_verification_tokens WHERE user_id = $1 AND used_at IS NULL"#)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO email_verification_tokens (tenant_id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(sha256_hash(&token))
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(IssuedVerification {
+            tenant_id,
+            user_id,
+            email: row.try_get("email")?,
+            name: row.try_get("name")?,
+            token,
+            expires_at,
+        }))
+    }
+
+    /// Mark the email `token` was sent to as verified; returns the user's id
+    pub async fn verify_email(&self, token: &str) -> Result<Uuid, AuthError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, expires_at, used_at
+            FROM email_verification_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(sha256_hash(token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+        let token_id: Uuid = row.try_get("id")?;
+        let user_id: Uuid = row.try_get("user_id")?;
+        let expires_at: DateTime<Utc> = row.try_get("expires_at")?;
+        let used_at: Option<DateTime<Utc>> = row.try_get("used_at")?;
+        if used_at.is_some() {
+            return Err(AuthError::InvalidToken);
+        }
+        if expires_at <= Utc::now() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        sqlx::query(r#"UPDATE email_verification_tokens SET used_at = NOW() WHERE id = $1"#)
+            .bind(token_id)
+            .execute(&mut *tx)
+            .await?;
+        // Level 1 is "email verified"; don't lower anyone already past it
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET email_verified = TRUE, email_verified_at = NOW(),
+                verification_level = GREATEST(COALESCE(verification_level, 0), 1), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    pub async fn is_verified(&self, user_id: Uuid) -> Result<bool, AuthError> {
+        let verified: Option<bool> = sqlx::query_scalar(r#"SELECT email_verified FROM users WHERE id = $1"#)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        verified.ok_or(AuthError::UserNotFound)
+    }
+}
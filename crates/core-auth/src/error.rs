@@ -1,5 +1,16 @@
 //! Auth errors
+//!
+//! Every variant maps to an HTTP status and a stable `code` that clients can
+//! branch on; the message may change, the code won't. Responses all have
+//! the shape `{"error": <message>, "code": <code>, "status": <status>}`.
+//! Credential failures are reported as one `invalid_credentials`, never as
+//! an unknown user or a wrong password.
 
+use axum::{
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,8 +27,11 @@ pub enum AuthError {
     #[error("Tenant not found")]
     TenantNotFound,
 
-    #[error("Tenant not active")]
-    TenantNotActive,
+    #[error("Tenant is suspended")]
+    TenantSuspended,
+
+    #[error("Tenant trial has expired")]
+    TrialExpired,
 
     #[error("Session expired")]
     SessionExpired,
@@ -28,15 +42,30 @@ pub enum AuthError {
     #[error("Invalid token")]
     InvalidToken,
 
-    #[error("Permission denied")]
-    PermissionDenied,
+    #[error("Token expired")]
+    TokenExpired,
+
+    #[error("Multi-factor authentication required")]
+    MfaRequired,
+
+    #[error("Forbidden")]
+    Forbidden,
 
     #[error("Account disabled")]
     AccountDisabled,
 
+    #[error("Email not verified")]
+    EmailNotVerified,
+
+    #[error("Too many attempts, try again later")]
+    RateLimited { retry_after_secs: i64 },
+
     #[error("Invalid invitation")]
     InvalidInvitation,
 
+    #[error("Invalid scope: {0}")]
+    InvalidScope(String),
+
     #[error("Password too weak: {0}")]
     WeakPassword(String),
 
@@ -46,3 +75,150 @@ pub enum AuthError {
     #[error("Password hash error")]
     PasswordHash,
 }
+
+impl AuthError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AuthError::InvalidCredentials
+            | AuthError::SessionExpired
+            | AuthError::SessionNotFound
+            | AuthError::MfaRequired => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden
+            | AuthError::TenantSuspended
+            | AuthError::AccountDisabled
+            | AuthError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AuthError::UserNotFound | AuthError::TenantNotFound => StatusCode::NOT_FOUND,
+            AuthError::UserExists(_) => StatusCode::CONFLICT,
+            AuthError::TrialExpired => StatusCode::PAYMENT_REQUIRED,
+            AuthError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AuthError::InvalidToken
+            | AuthError::TokenExpired
+            | AuthError::InvalidInvitation
+            | AuthError::InvalidScope(_)
+            | AuthError::WeakPassword(_) => StatusCode::BAD_REQUEST,
+            AuthError::Database(_) | AuthError::PasswordHash => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable, machine-readable identifier for the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthError::InvalidCredentials => "invalid_credentials",
+            AuthError::UserNotFound => "user_not_found",
+            AuthError::UserExists(_) => "user_exists",
+            AuthError::TenantNotFound => "tenant_not_found",
+            AuthError::TenantSuspended => "tenant_suspended",
+            AuthError::TrialExpired => "trial_expired",
+            AuthError::SessionExpired => "session_expired",
+            AuthError::SessionNotFound => "session_not_found",
+            AuthError::InvalidToken => "invalid_token",
+            AuthError::TokenExpired => "token_expired",
+            AuthError::MfaRequired => "mfa_required",
+            AuthError::Forbidden => "forbidden",
+            AuthError::AccountDisabled => "account_disabled",
+            AuthError::EmailNotVerified => "email_not_verified",
+            AuthError::RateLimited { .. } => "rate_limited",
+            AuthError::InvalidInvitation => "invalid_invitation",
+            AuthError::InvalidScope(_) => "invalid_scope",
+            AuthError::WeakPassword(_) => "weak_password",
+            AuthError::Database(_) | AuthError::PasswordHash => "internal_error",
+        }
+    }
+
+    /// The message shown to clients; internal failures stay vague
+    pub fn public_message(&self) -> String {
+        match self {
+            AuthError::Database(_) | AuthError::PasswordHash => "Internal error".to_string(),
+            e => e.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        if self.status().is_server_error() {
+            tracing::error!("Auth error: {}", self);
+        }
+        let status = self.status();
+        let body = Json(serde_json::json!({
+            "error": self.public_message(),
+            "code": self.code(),
+            "status": status.as_u16(),
+        }));
+
+        let mut response = (status, body).into_response();
+        if let AuthError::RateLimited { retry_after_secs } = self {
+            if let Ok(value) = HeaderValue::from_str(&retry_after_secs.max(1).to_string()) {
+                response.headers_mut().insert(RETRY_AFTER, value);
+            }
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_each_variant_maps_to_status_and_code() {
+        let cases = [
+            (AuthError::InvalidCredentials, StatusCode::UNAUTHORIZED, "invalid_credentials"),
+            (AuthError::UserNotFound, StatusCode::NOT_FOUND, "user_not_found"),
+            (AuthError::UserExists("a@example.com".to_string()), StatusCode::CONFLICT, "user_exists"),
+            (AuthError::TenantNotFound, StatusCode::NOT_FOUND, "tenant_not_found"),
+            (AuthError::TenantSuspended, StatusCode::FORBIDDEN, "tenant_suspended"),
+            (AuthError::TrialExpired, StatusCode::PAYMENT_REQUIRED, "trial_expired"),
+            (AuthError::SessionExpired, StatusCode::UNAUTHORIZED, "session_expired"),
+            (AuthError::SessionNotFound, StatusCode::UNAUTHORIZED, "session_not_found"),
+            (AuthError::InvalidToken, StatusCode::BAD_REQUEST, "invalid_token"),
+            (AuthError::TokenExpired, StatusCode::BAD_REQUEST, "token_expired"),
+            (AuthError::MfaRequired, StatusCode::UNAUTHORIZED, "mfa_required"),
+            (AuthError::Forbidden, StatusCode::FORBIDDEN, "forbidden"),
+            (AuthError::AccountDisabled, StatusCode::FORBIDDEN, "account_disabled"),
+            (AuthError::EmailNotVerified, StatusCode::FORBIDDEN, "email_not_verified"),
+            (AuthError::RateLimited { retry_after_secs: 30 }, StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+            (AuthError::InvalidInvitation, StatusCode::BAD_REQUEST, "invalid_invitation"),
+            (AuthError::InvalidScope("x".to_string()), StatusCode::BAD_REQUEST, "invalid_scope"),
+            (AuthError::WeakPassword("too short".to_string()), StatusCode::BAD_REQUEST, "weak_password"),
+            (AuthError::Database(sqlx::Error::RowNotFound), StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+            (AuthError::PasswordHash, StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        ];
+
+        for (error, status, code) in cases {
+            let message = error.public_message();
+            let response = error.into_response();
+            assert_eq!(response.status(), status, "{}", code);
+            let body = body(response).await;
+            // Same three fields, every time
+            assert_eq!(body.as_object().unwrap().len(), 3, "{}", code);
+            assert_eq!(body["code"], code);
+            assert_eq!(body["status"], status.as_u16());
+            assert_eq!(body["error"], message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_internal_details_and_credential_parts_stay_hidden() {
+        let body = body(AuthError::Database(sqlx::Error::PoolTimedOut).into_response()).await;
+        assert_eq!(body["error"], "Internal error");
+
+        let body = body_text(AuthError::InvalidCredentials).await;
+        assert!(!body.contains("password") && !body.contains("user") && !body.contains("email"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_says_when_to_retry() {
+        let response = AuthError::RateLimited { retry_after_secs: 42 }.into_response();
+        assert_eq!(response.headers()[RETRY_AFTER], "42");
+    }
+
+    async fn body_text(error: AuthError) -> String {
+        body(error.into_response()).await.to_string().to_lowercase()
+    }
+}
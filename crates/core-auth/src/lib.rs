@@ -3,8 +3,12 @@
 //! Provides tenant management, user authentication, sessions, and role-based access control.
 
 pub mod error;
+pub mod email_verification;
+pub mod login_throttle;
 pub mod password;
+pub mod password_reset;
 pub mod session;
+pub mod api_key;
 pub mod tenant;
 pub mod user;
 pub mod middleware;
@@ -0,0 +1,232 @@
+//! Login throttling
+//!
+//! Failed logins are counted per account (tenant + email) and per client
+//! IP. Each failure in the current window makes the next attempt wait
+//! longer, and `max_*_failures` inside the window locks the key until the
+//! lockout passes — even the right password is refused meanwhile. Unknown
+//! emails are counted like real ones, so a lockout says nothing about
+//! whether an account exists.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use crate::AuthError;
+
+const ACCOUNT: &str = "account";
+const IP: &str = "ip";
+
+/// Thresholds for `LoginThrottle`
+#[derive(Debug, Clone)]
+pub struct ThrottlePolicy {
+    /// Failures per account inside `window` before it locks
+    pub max_account_failures: i32,
+    /// Failures per IP inside `window` before it locks
+    pub max_ip_failures: i32,
+    pub window: Duration,
+    pub lockout: Duration,
+    /// Wait after the first failure; doubles with each one after
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            max_account_failures: 5,
+            max_ip_failures: 20,
+            window: Duration::minutes(15),
+            lockout: Duration::minutes(15),
+            base_delay: Duration::milliseconds(250),
+            max_delay: Duration::seconds(4),
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// How long to hold an attempt after `failures` recent failures
+    pub fn delay_after(&self, failures: i32) -> Duration {
+        if failures <= 0 {
+            return Duration::zero();
+        }
+        let doublings = (failures - 1).min(16) as u32;
+        (self.base_delay * 2i32.pow(doublings)).min(self.max_delay)
+    }
+}
+
+/// Failed login counters, shared by every server through the database
+#[derive(Clone)]
+pub struct LoginThrottle {
+    pool: PgPool,
+    policy: ThrottlePolicy,
+}
+
+impl LoginThrottle {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, policy: ThrottlePolicy::default() }
+    }
+
+    pub fn with_policy(pool: PgPool, policy: ThrottlePolicy) -> Self {
+        Self { pool, policy }
+    }
+
+    /// Before checking a password: `Err(RateLimited)` while the account or
+    /// IP is locked, otherwise how long to wait first
+    pub async fn check(&self, tenant_id: Uuid, email: &str, ip: Option<IpAddr>) -> Result<Duration, AuthError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT failures, window_started_at, locked_until
+            FROM login_attempts
+            WHERE (scope = $1 AND key = $2) OR (scope = $3 AND key = $4)
+            "#,
+        )
+        .bind(ACCOUNT)
+        .bind(account_key(tenant_id, email))
+        .bind(IP)
+        .bind(ip.map(|ip| ip.to_string()).unwrap_or_default())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = Utc::now();
+        let mut failures = 0;
+        for row in &rows {
+            let locked_until: Option<DateTime<Utc>> = row.try_get("locked_until")?;
+            if let Some(until) = locked_until.filter(|until| *until > now) {
+                return Err(AuthError::RateLimited {
+                    retry_after_secs: (until - now).num_seconds().max(1),
+                });
+            }
+            let window_started_at: DateTime<Utc> = row.try_get("window_started_at")?;
+            if locked_until.is_none() && window_started_at + self.policy.window > now {
+                failures = failures.max(row.try_get("failures")?);
+            }
+        }
+        Ok(self.policy.delay_after(failures))
+    }
+
+    /// Count a failed login against the account and IP
+    pub async fn record_failure(&self, tenant_id: Uuid, email: &str, ip: Option<IpAddr>) -> Result<(), AuthError> {
+        self.count_failure(ACCOUNT, &account_key(tenant_id, email), self.policy.max_account_failures)
+            .await?;
+        if let Some(ip) = ip {
+            self.count_failure(IP, &ip.to_string(), self.policy.max_ip_failures).await?;
+        }
+        Ok(())
+    }
+
+    async fn count_failure(&self, scope: &str, key: &str, max_failures: i32) -> Result<(), AuthError> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(r#"INSERT INTO login_attempts (scope, key) VALUES ($1, $2) ON CONFLICT DO NOTHING"#)
+            .bind(scope)
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+        let row = sqlx::query(
+            r#"SELECT failures, window_started_at, locked_until FROM login_attempts WHERE scope = $1 AND key = $2 FOR UPDATE"#,
+        )
+        .bind(scope)
+        .bind(key)
+        .fetch_one(&mut *tx)
+        .await?;
+        let failures: i32 = row.try_get("failures")?;
+        let window_started_at: DateTime<Utc> = row.try_get("window_started_at")?;
+        let locked_until: Option<DateTime<Utc>> = row.try_get("locked_until")?;
+
+        // A lapsed window or served lockout starts the count over
+        let (failures, window_started_at) =
+            if window_started_at + self.policy.window <= now || locked_until.is_some_and(|until| until <= now) {
+                (1, now)
+            } else {
+                (failures + 1, window_started_at)
+            };
+        let locked_until = (failures >= max_failures).then(|| now + self.policy.lockout);
+        if locked_until.is_some() {
+            tracing::warn!(scope, key, failures, "Login locked after repeated failures");
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE login_attempts
+            SET failures = $3, window_started_at = $4, locked_until = $5, updated_at = $6
+            WHERE scope = $1 AND key = $2
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(failures)
+        .bind(window_started_at)
+        .bind(locked_until)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// A successful login clears the account's failures. The IP's are kept,
+    /// so knowing one password doesn't buy more guesses at others.
+    pub async fn record_success(&self, tenant_id: Uuid, email: &str) -> Result<(), AuthError> {
+        self.unlock(tenant_id, email).await
+    }
+
+    /// Lift an account's lockout and clear its failures
+    pub async fn unlock(&self, tenant_id: Uuid, email: &str) -> Result<(), AuthError> {
+        sqlx::query(r#"DELETE FROM login_attempts WHERE scope = $1 AND key = $2"#)
+            .bind(ACCOUNT)
+            .bind(account_key(tenant_id, email))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop counters that no longer affect a login: windows that lapsed
+    /// without a lockout, and lockouts that have been served
+    pub async fn purge_expired(&self) -> Result<u64, AuthError> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            DELETE FROM login_attempts
+            WHERE CASE WHEN locked_until IS NULL THEN window_started_at <= $2 ELSE locked_until <= $1 END
+            "#,
+        )
+        .bind(now)
+        .bind(now - self.policy.window)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Purge expired counters every `period`, forever
+    pub async fn run_purge(self, period: std::time::Duration) {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match self.purge_expired().await {
+                Ok(removed) if removed > 0 => tracing::info!(removed, "Purged expired login attempts"),
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Failed to purge login attempts"),
+            }
+        }
+    }
+}
+
+fn account_key(tenant_id: Uuid, email: &str) -> String {
+    format!("{}:{}", tenant_id, email.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_then_caps() {
+        let policy = ThrottlePolicy::default();
+        assert_eq!(policy.delay_after(0), Duration::zero());
+        assert_eq!(policy.delay_after(1), Duration::milliseconds(250));
+        assert_eq!(policy.delay_after(3), Duration::seconds(1));
+        assert_eq!(policy.delay_after(10), Duration::seconds(4));
+        assert_eq!(policy.delay_after(i32::MAX), Duration::seconds(4));
+    }
+}
@@ -0,0 +1,155 @@
+//! Password reset
+//!
+//! A reset token works once and only until it expires. Only its hash is
+//! stored; the caller mails the token itself. Resetting signs the user out
+//! of every session.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::password::{hash_password, validate_password_strength};
+use crate::session::sha256_hash;
+use crate::AuthError;
+
+/// A reset token issued for an account
+#[derive(Debug, Clone)]
+pub struct IssuedReset {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Password reset service
+#[derive(Clone)]
+pub struct PasswordResetService {
+    pool: PgPool,
+    /// Token lifetime (default: 30 minutes)
+    token_ttl: Duration,
+}
+
+impl PasswordResetService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            token_ttl: Duration::minutes(30),
+        }
+    }
+
+    pub fn with_ttl(pool: PgPool, ttl: Duration) -> Self {
+        Self { pool, token_ttl: ttl }
+    }
+
+    /// Issue a reset token for the account with `email` (in any case),
+    /// replacing any earlier unused one; `None` when there's no such enabled
+    /// account
+    pub async fn request_password_reset(
+        &self,
+        tenant_id: Uuid,
+        email: &str,
+    ) -> Result<Option<IssuedReset>, AuthError> {
+        let user: Option<(Uuid, String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT id, email, name, status FROM users
+            WHERE tenant_id = $1 AND lower(email) = lower($2)
+            ORDER BY email = $2 DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((user_id, email, name, status)) = user else { return Ok(None) };
+        if is_disabled(&status) {
+            return Ok(None);
+        }
+
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let expires_at = Utc::now() + self.token_ttl;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(r#"DELETE FROM password_reset_tokens WHERE user_id = $1 AND used_at IS NULL"#)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (tenant_id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(sha256_hash(&token))
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(IssuedReset { tenant_id, user_id, email, name, token, expires_at }))
+    }
+
+    /// Use `token` to set a new password, signing the user out everywhere;
+    /// returns the user's id
+    ///
+    /// A weak password is refused before the token is spent.
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<Uuid, AuthError> {
+        validate_password_strength(new_password)?;
+        let password_hash = hash_password(new_password)?;
+
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, expires_at, used_at
+            FROM password_reset_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(sha256_hash(token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+        let reset_id: Uuid = row.try_get("id")?;
+        let user_id: Uuid = row.try_get("user_id")?;
+        let expires_at: DateTime<Utc> = row.try_get("expires_at")?;
+        let used_at: Option<DateTime<Utc>> = row.try_get("used_at")?;
+        if used_at.is_some() {
+            return Err(AuthError::InvalidToken);
+        }
+        if expires_at <= Utc::now() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        sqlx::query(r#"UPDATE password_reset_tokens SET used_at = NOW() WHERE id = $1"#)
+            .bind(reset_id)
+            .execute(&mut *tx)
+            .await?;
+        let status: String = sqlx::query_scalar(
+            r#"UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2 RETURNING status"#,
+        )
+        .bind(&password_hash)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if is_disabled(&status) {
+            return Err(AuthError::AccountDisabled);
+        }
+        sqlx::query(r#"DELETE FROM sessions WHERE user_id = $1"#)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+}
+
+/// `UserService::disable` stores the status JSON-quoted
+fn is_disabled(status: &str) -> bool {
+    status.trim_matches('"') == "disabled"
+}
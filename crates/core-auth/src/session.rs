@@ -1,12 +1,19 @@
 //! Session management
 
-use chrono::{Duration, Utc};
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
 use core_models::{Session, User, UserInfo, UserRole, AuthContext};
+use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::AuthError;
 
+/// How stale `last_seen_at` may get before a request refreshes it
+const LAST_SEEN_RESOLUTION_SECS: i64 = 60;
+
 /// Session service for managing user sessions
 #[derive(Clone)]
 pub struct SessionService {
@@ -88,10 +95,11 @@ impl SessionService {
                 u.email,
                 u.name,
                 u.role,
-                u.avatar_url
+                u.avatar_url,
+                s.last_seen_at
             FROM sessions s
             JOIN users u ON s.user_id = u.id
-            WHERE s.token_hash = $1 AND s.expires_at > $2
+            WHERE s.token_hash = $1 AND s.expires_at > $2 AND s.revoked_at IS NULL
             "#,
         )
         .bind(&token_hash)
@@ -102,6 +110,15 @@ impl SessionService {
 
         use sqlx::Row;
         let role_str: String = row.try_get("role").unwrap_or_default();
+        let session_id: Uuid = row.try_get("session_id")?;
+        let last_seen_at: Option<DateTime<Utc>> = row.try_get("last_seen_at")?;
+        if last_seen_at.is_none_or(|seen| now - seen > Duration::seconds(LAST_SEEN_RESOLUTION_SECS)) {
+            sqlx::query(r#"UPDATE sessions SET last_seen_at = $1 WHERE id = $2"#)
+                .bind(now)
+                .bind(session_id)
+                .execute(&self.pool)
+                .await?;
+        }
         
         Ok(AuthContext {
             user: UserInfo {
@@ -113,7 +130,7 @@ impl SessionService {
                 avatar_url: row.try_get("avatar_url")?,
             },
             tenant_id: row.try_get("tenant_id")?,
-            session_id: row.try_get("session_id")?,
+            session_id,
         })
     }
 
@@ -156,6 +173,65 @@ impl SessionService {
         Ok(())
     }
 
+    /// The user's live sessions, most recently used first
+    pub async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<SessionInfo>, AuthError> {
+        use sqlx::Row;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_agent, ip_address, created_at, COALESCE(last_seen_at, created_at) AS last_seen_at, expires_at
+            FROM sessions
+            WHERE user_id = $1 AND expires_at > NOW() AND revoked_at IS NULL
+            ORDER BY COALESCE(last_seen_at, created_at) DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let user_agent: Option<String> = row.try_get("user_agent")?;
+                Ok(SessionInfo {
+                    id: row.try_get("id")?,
+                    device: user_agent.as_deref().map(describe_device),
+                    user_agent,
+                    ip_address: row.try_get("ip_address")?,
+                    created_at: row.try_get("created_at")?,
+                    last_seen_at: row.try_get("last_seen_at")?,
+                    expires_at: row.try_get("expires_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Revoke one of the user's sessions; its next request is
+    /// unauthenticated. Returns whether there was a live session to revoke.
+    pub async fn revoke(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, AuthError> {
+        let result = sqlx::query(
+            r#"UPDATE sessions SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL"#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every session of the user's but `current` ("log out other
+    /// devices"); returns how many were revoked
+    pub async fn revoke_all_except(&self, user_id: Uuid, current: Uuid) -> Result<u64, AuthError> {
+        let result = sqlx::query(
+            r#"UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND id <> $2 AND revoked_at IS NULL"#,
+        )
+        .bind(user_id)
+        .bind(current)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Clean up expired sessions
     pub async fn cleanup_expired(&self) -> Result<u64, AuthError> {
         let result = sqlx::query(
@@ -169,8 +245,48 @@ impl SessionService {
     }
 }
 
+/// A live session as its user sees it
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    /// Browser and OS read from the user agent, e.g. "Firefox on Windows"
+    pub device: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A short "browser on OS" label for a user agent
+pub fn describe_device(user_agent: &str) -> String {
+    // Order matters: Edge and Opera also claim Chrome, Chrome claims Safari
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map_or("Unknown browser", |(_, name)| name);
+    let os = [
+        ("iPhone", "iOS"),
+        ("iPad", "iOS"),
+        ("Android", "Android"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map_or("unknown OS", |(_, name)| name);
+    format!("{} on {}", browser, os)
+}
+
 /// Cryptographically secure SHA256 hash for session tokens
-fn sha256_hash(input: &str) -> String {
+pub(crate) fn sha256_hash(input: &str) -> String {
     use sha2::{Sha256, Digest};
     
     let mut hasher = Sha256::new();
@@ -178,3 +294,250 @@ fn sha256_hash(input: &str) -> String {
     let result = hasher.finalize();
     hex::encode(result)
 }
+
+// ============================================================================
+// BEARER TOKENS
+// ============================================================================
+
+/// Claims carried by an API bearer token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// The user's id
+    pub sub: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub roles: Vec<String>,
+    /// Unique per token; stands in for the session id
+    pub jti: Uuid,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+impl JwtClaims {
+    /// The auth context a request carrying this token runs as
+    pub fn auth_context(&self) -> AuthContext {
+        let role = self
+            .roles
+            .first()
+            .and_then(|role| serde_json::from_value(serde_json::Value::String(role.clone())).ok())
+            .unwrap_or(UserRole::Member);
+        AuthContext {
+            user: UserInfo {
+                id: self.sub,
+                tenant_id: self.tenant_id,
+                email: self.email.clone(),
+                name: self.name.clone(),
+                role,
+                avatar_url: None,
+                verification_level: 0,
+                phone: None,
+            },
+            tenant_id: self.tenant_id,
+            session_id: self.jti,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct JwtKey {
+    kid: String,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl JwtKey {
+    fn new(kid: String, secret: &[u8]) -> Self {
+        Self {
+            kid,
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+        }
+    }
+}
+
+/// The deployment's bearer token keys, by `kid`
+///
+/// The first key signs new tokens; the others only verify. Rotating puts
+/// the new key first and keeps the old one listed until the tokens it
+/// signed have expired.
+#[derive(Clone)]
+pub struct JwtKeySet {
+    keys: Vec<JwtKey>,
+}
+
+impl JwtKeySet {
+    /// A key set with one HMAC key
+    pub fn new(kid: impl Into<String>, secret: &[u8]) -> Self {
+        Self { keys: vec![JwtKey::new(kid.into(), secret)] }
+    }
+
+    /// Add a key that verifies but doesn't sign
+    pub fn with_key(mut self, kid: impl Into<String>, secret: &[u8]) -> Self {
+        self.keys.push(JwtKey::new(kid.into(), secret));
+        self
+    }
+
+    /// Parse a JWKS document of `oct` keys, each with a `kid`
+    pub fn from_jwks(json: &str) -> Result<Self, AuthError> {
+        let set: JwkSet = serde_json::from_str(json).map_err(|_| AuthError::InvalidToken)?;
+        let mut keys = Vec::new();
+        for jwk in set.keys {
+            let (Some(kid), AlgorithmParameters::OctetKey(params)) = (jwk.common.key_id, jwk.algorithm) else {
+                return Err(AuthError::InvalidToken);
+            };
+            let secret = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(params.value.trim_end_matches('='))
+                .map_err(|_| AuthError::InvalidToken)?;
+            keys.push(JwtKey::new(kid, &secret));
+        }
+        if keys.is_empty() {
+            return Err(AuthError::InvalidToken);
+        }
+        Ok(Self { keys })
+    }
+
+    /// Keys from `JWT_JWKS`, or the single key `JWT_SECRET` (id `JWT_KEY_ID`,
+    /// default `default`); `None` when neither is set, leaving bearer auth off
+    pub fn from_env() -> Option<Self> {
+        if let Ok(jwks) = std::env::var("JWT_JWKS") {
+            return match Self::from_jwks(&jwks) {
+                Ok(keys) => Some(keys),
+                Err(_) => {
+                    tracing::error!("JWT_JWKS is not a set of oct keys with ids; bearer auth is off");
+                    None
+                }
+            };
+        }
+        let secret = std::env::var("JWT_SECRET").ok()?;
+        let kid = std::env::var("JWT_KEY_ID").unwrap_or_else(|_| "default".to_string());
+        Some(Self::new(kid, secret.as_bytes()))
+    }
+
+    fn signing_key(&self) -> &JwtKey {
+        &self.keys[0]
+    }
+
+    fn find(&self, kid: &str) -> Option<&JwtKey> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+}
+
+/// Issue a bearer token for `user`, valid for `ttl`
+pub fn issue_jwt(keys: &JwtKeySet, user: &UserInfo, ttl: Duration) -> Result<String, AuthError> {
+    let now = Utc::now();
+    let claims = JwtClaims {
+        sub: user.id,
+        tenant_id: user.tenant_id,
+        email: user.email.clone(),
+        name: user.name.clone(),
+        roles: vec![user.role.as_str().to_string()],
+        jti: Uuid::new_v4(),
+        iat: now.timestamp(),
+        exp: (now + ttl).timestamp(),
+    };
+
+    let key = keys.signing_key();
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some(key.kid.clone());
+    jsonwebtoken::encode(&header, &claims, &key.encoding).map_err(|_| AuthError::InvalidToken)
+}
+
+/// Check a bearer token's signature and expiry against the key named by its
+/// `kid`
+pub fn validate_jwt(keys: &JwtKeySet, token: &str) -> Result<JwtClaims, AuthError> {
+    let header = jsonwebtoken::decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+    let key = header
+        .kid
+        .as_deref()
+        .and_then(|kid| keys.find(kid))
+        .ok_or(AuthError::InvalidToken)?;
+
+    jsonwebtoken::decode::<JwtClaims>(token, &key.decoding, &Validation::new(Algorithm::HS256))
+        .map(|data| data.claims)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::SessionExpired,
+            _ => AuthError::InvalidToken,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user() -> UserInfo {
+        UserInfo {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            email: "agent@example.com".to_string(),
+            name: "Agent".to_string(),
+            role: UserRole::Manager,
+            avatar_url: None,
+            verification_level: 0,
+            phone: None,
+        }
+    }
+
+    #[test]
+    fn test_describe_device() {
+        let chrome_mac = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+        assert_eq!(describe_device(chrome_mac), "Chrome on macOS");
+        let safari_iphone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+        assert_eq!(describe_device(safari_iphone), "Safari on iOS");
+        let edge = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0";
+        assert_eq!(describe_device(edge), "Edge on Windows");
+        assert_eq!(describe_device("curl/8.0"), "Unknown browser on unknown OS");
+    }
+
+    #[test]
+    fn test_jwt_round_trip() {
+        let keys = JwtKeySet::new("k1", b"first-secret");
+        let user = user();
+        let token = issue_jwt(&keys, &user, Duration::minutes(15)).unwrap();
+
+        let claims = validate_jwt(&keys, &token).unwrap();
+        assert_eq!(claims.sub, user.id);
+        assert_eq!(claims.tenant_id, user.tenant_id);
+        assert_eq!(claims.roles, vec!["manager".to_string()]);
+        let auth = claims.auth_context();
+        assert_eq!(auth.user.role, UserRole::Manager);
+        assert_eq!(auth.tenant_id, user.tenant_id);
+    }
+
+    #[test]
+    fn test_expired_jwt_is_rejected() {
+        let keys = JwtKeySet::new("k1", b"first-secret");
+        let token = issue_jwt(&keys, &user(), Duration::minutes(-5)).unwrap();
+        assert!(matches!(validate_jwt(&keys, &token), Err(AuthError::SessionExpired)));
+    }
+
+    #[test]
+    fn test_old_key_validates_during_rotation() {
+        let old = JwtKeySet::new("k1", b"first-secret");
+        let old_token = issue_jwt(&old, &user(), Duration::minutes(15)).unwrap();
+
+        let rotated = JwtKeySet::new("k2", b"second-secret").with_key("k1", b"first-secret");
+        assert!(validate_jwt(&rotated, &old_token).is_ok());
+        let new_token = issue_jwt(&rotated, &user(), Duration::minutes(15)).unwrap();
+        assert_eq!(jsonwebtoken::decode_header(&new_token).unwrap().kid.as_deref(), Some("k2"));
+
+        // Once the old key is dropped its tokens stop working
+        let retired = JwtKeySet::new("k2", b"second-secret");
+        assert!(matches!(validate_jwt(&retired, &old_token), Err(AuthError::InvalidToken)));
+        assert!(validate_jwt(&retired, &new_token).is_ok());
+    }
+
+    #[test]
+    fn test_jwks_key_set() {
+        let jwks = r#"{"keys": [
+            {"kty": "oct", "kid": "k2", "k": "c2Vjb25kLXNlY3JldA"},
+            {"kty": "oct", "kid": "k1", "k": "Zmlyc3Qtc2VjcmV0"}
+        ]}"#;
+        let keys = JwtKeySet::from_jwks(jwks).unwrap();
+        let old_token = issue_jwt(&JwtKeySet::new("k1", b"first-secret"), &user(), Duration::minutes(15)).unwrap();
+        assert!(validate_jwt(&keys, &old_token).is_ok());
+
+        let token = issue_jwt(&keys, &user(), Duration::minutes(15)).unwrap();
+        assert!(validate_jwt(&JwtKeySet::new("k2", b"second-secret"), &token).is_ok());
+    }
+}
@@ -101,7 +101,7 @@ impl TenantService {
 
         // Check tenant is active
         if !tenant.is_active() {
-            return Err(AuthError::TenantNotActive);
+            return Err(AuthError::TenantSuspended);
         }
 
         Ok(TenantContext {
@@ -133,18 +133,27 @@ impl UserService {
         email: &str,
         password: &str,
     ) -> Result<User, AuthError> {
-        let user = self.get_by_email(tenant_id, email).await?;
+        let user = match self.get_by_email(tenant_id, email).await {
+            Ok(user) => user,
+            // Answer an unknown email exactly like a wrong password, hash
+            // check included, so neither the error nor the timing tells them apart
+            Err(AuthError::UserNotFound) => {
+                let _ = verify_password(password, unknown_user_hash());
+                return Err(AuthError::InvalidCredentials);
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Verify password before saying anything about the account
+        if !verify_password(password, &user.password_hash)? {
+            return Err(AuthError::InvalidCredentials);
+        }
 
         // Check account status
         if user.status == UserStatus::Disabled {
             return Err(AuthError::AccountDisabled);
         }
 
-        // Verify password
-        if !verify_password(password, &user.password_hash)? {
-            return Err(AuthError::InvalidCredentials);
-        }
-
         // Update last login
         sqlx::query(
             r#"UPDATE users SET last_login_at = $1 WHERE id = $2"#,
@@ -248,3 +257,9 @@ fn user_info_from_row(row: &sqlx::postgres::PgRow) -> Result<UserInfo, AuthError
         avatar_url: row.try_get("avatar_url")?,
     })
 }
+
+/// A hash no password matches, checked against when the email is unknown
+fn unknown_user_hash() -> &'static str {
+    static HASH: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    HASH.get_or_init(|| hash_password(&Uuid::new_v4().to_string()).unwrap_or_default())
+}
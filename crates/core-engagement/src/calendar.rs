@@ -2,8 +2,11 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use uuid::Uuid;
 
+use crate::recurrence::{parse_timezone, RecurrenceError, RecurrenceRule};
+
 /// Calendar event status
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -43,13 +46,49 @@ pub struct CalendarEvent {
     /// Linked record ID
     pub linked_entity_id: Option<Uuid>,
     /// Recurrence rule (iCal RRULE format)
+    #[serde(alias = "rrule")]
     pub recurrence_rule: Option<String>,
+    /// IANA timezone the recurrence is expanded in (UTC if unset)
+    pub timezone: Option<String>,
+    /// Original start times of occurrences removed from the series (EXDATE)
+    #[serde(default)]
+    pub exdates: Vec<DateTime<Utc>>,
+    /// Single occurrences that were moved or edited
+    #[serde(default)]
+    pub overrides: Vec<OccurrenceOverride>,
     /// External ID (for sync with Google/Microsoft)
     pub external_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Changes to one occurrence of a recurring event, keyed by its original start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OccurrenceOverride {
+    /// Start the occurrence would have had without the override (RECURRENCE-ID)
+    pub original_start: DateTime<Utc>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub title: Option<String>,
+    pub location: Option<String>,
+    pub status: Option<EventStatus>,
+}
+
+/// One materialized instance of a (possibly recurring) event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Occurrence {
+    pub event_id: Uuid,
+    /// Start from the recurrence rule, identifying the instance
+    pub original_start: DateTime<Utc>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub title: String,
+    pub location: Option<String>,
+    pub status: EventStatus,
+    /// Whether an override changed this instance
+    pub is_override: bool,
+}
+
 /// Attendee response status
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -124,6 +163,9 @@ impl CalendarEvent {
             linked_entity_type: None,
             linked_entity_id: None,
             recurrence_rule: None,
+            timezone: None,
+            exdates: Vec::new(),
+            overrides: Vec::new(),
             external_id: None,
             created_at: now,
             updated_at: now,
@@ -140,4 +182,376 @@ impl CalendarEvent {
         self.meeting_url = Some(url.to_string());
         self
     }
+
+    /// Make the event recur, validating the rule and timezone
+    pub fn with_recurrence(mut self, rrule: &str, timezone: Option<&str>) -> Result<Self, RecurrenceError> {
+        rrule.parse::<RecurrenceRule>()?;
+        parse_timezone(timezone)?;
+        self.recurrence_rule = Some(rrule.to_string());
+        self.timezone = timezone.map(str::to_string);
+        Ok(self)
+    }
+
+    /// Remove the occurrence that starts at `original_start`
+    pub fn with_exdate(mut self, original_start: DateTime<Utc>) -> Self {
+        self.exdates.push(original_start);
+        self
+    }
+
+    /// Replace a single occurrence
+    pub fn with_override(mut self, occurrence: OccurrenceOverride) -> Self {
+        self.overrides.retain(|o| o.original_start != occurrence.original_start);
+        self.overrides.push(occurrence);
+        self
+    }
+}
+
+/// Materialize the occurrences of `event` that overlap `range`, in start order
+///
+/// Nothing is persisted: recurring events are expanded from their rule on
+/// every call, with EXDATEs dropped and overrides applied. An event whose
+/// rule or timezone no longer parses is treated as a single event.
+pub fn expand_occurrences(event: &CalendarEvent, range: Range<DateTime<Utc>>) -> Vec<Occurrence> {
+    let duration = event.end_at - event.start_at;
+    let starts = match recurrence_starts(event, &range) {
+        Ok(starts) => starts,
+        Err(e) => {
+            tracing::warn!(event_id = %event.id, error = %e, "Ignoring invalid recurrence");
+            vec![event.start_at]
+        }
+    };
+
+    let mut occurrences: Vec<Occurrence> = starts
+        .into_iter()
+        .filter(|start| !event.exdates.contains(start))
+        .map(|original_start| match event.overrides.iter().find(|o| o.original_start == original_start) {
+            Some(o) => Occurrence {
+                event_id: event.id,
+                original_start,
+                start_at: o.start_at,
+                end_at: o.end_at,
+                title: o.title.clone().unwrap_or_else(|| event.title.clone()),
+                location: o.location.clone().or_else(|| event.location.clone()),
+                status: o.status.clone().unwrap_or_else(|| event.status.clone()),
+                is_override: true,
+            },
+            None => Occurrence {
+                event_id: event.id,
+                original_start,
+                start_at: original_start,
+                end_at: original_start + duration,
+                title: event.title.clone(),
+                location: event.location.clone(),
+                status: event.status.clone(),
+                is_override: false,
+            },
+        })
+        .filter(|o| o.start_at < range.end && (o.end_at > range.start || o.start_at >= range.start))
+        .collect();
+
+    occurrences.sort_by_key(|o| o.start_at);
+    occurrences
+}
+
+/// Rule-generated start instants that could land in `range`, including
+/// ones an override moves into it
+fn recurrence_starts(event: &CalendarEvent, range: &Range<DateTime<Utc>>) -> Result<Vec<DateTime<Utc>>, RecurrenceError> {
+    let Some(rrule) = &event.recurrence_rule else {
+        return Ok(vec![event.start_at]);
+    };
+    let rule: RecurrenceRule = rrule.parse()?;
+    let tz = parse_timezone(event.timezone.as_deref())?;
+
+    let bound = event.overrides.iter().map(|o| o.original_start).fold(range.end, DateTime::max);
+    let dtstart = event.start_at.with_timezone(&tz).naive_local();
+
+    Ok(rule.starts(dtstart, tz, bound))
+}
+
+// ============================================================================
+// iCalendar export
+// ============================================================================
+
+/// Product identifier written to exported calendars
+const ICS_PRODID: &str = "-//Jirsi//Calendar Feed//EN";
+
+/// Render events as an iCalendar (RFC 5545) document
+///
+/// Recurring events carry their RRULE and EXDATEs, and each override becomes
+/// a VEVENT with the same UID and a RECURRENCE-ID. Events with a timezone are
+/// written in local time with an IANA TZID so clients expand the rule across
+/// DST the same way [`expand_occurrences`] does.
+pub fn to_ics(events: &[CalendarEvent]) -> String {
+    let mut ics = IcsWriter::default();
+    ics.line("BEGIN:VCALENDAR");
+    ics.line("VERSION:2.0");
+    ics.line(&format!("PRODID:{}", ICS_PRODID));
+    ics.line("CALSCALE:GREGORIAN");
+    ics.line("METHOD:PUBLISH");
+
+    for event in events {
+        let tz = event
+            .timezone
+            .as_deref()
+            .filter(|_| event.recurrence_rule.is_some())
+            .and_then(|name| parse_timezone(Some(name)).ok().map(|tz| (name, tz)));
+        let time = |name: &str, at: DateTime<Utc>| match tz {
+            _ if event.all_day => format!("{};VALUE=DATE:{}", name, at.format("%Y%m%d")),
+            Some((tzid, tz)) => format!("{};TZID={}:{}", name, tzid, at.with_timezone(&tz).format("%Y%m%dT%H%M%S")),
+            None => format!("{}:{}", name, at.format("%Y%m%dT%H%M%SZ")),
+        };
+
+        ics.line("BEGIN:VEVENT");
+        ics.line(&format!("UID:{}", event.id));
+        ics.line(&format!("DTSTAMP:{}", event.updated_at.format("%Y%m%dT%H%M%SZ")));
+        ics.line(&time("DTSTART", event.start_at));
+        ics.line(&time("DTEND", event.end_at));
+        ics.text("SUMMARY", &event.title);
+        ics.event_details(event.description.as_deref(), event.location.as_deref(), event.meeting_url.as_deref());
+        ics.line(&format!("STATUS:{}", ics_status(&event.status)));
+        if let Some(rrule) = &event.recurrence_rule {
+            ics.line(&format!("RRULE:{}", rrule.trim().trim_start_matches("RRULE:")));
+            for exdate in &event.exdates {
+                ics.line(&time("EXDATE", *exdate));
+            }
+        }
+        ics.line("END:VEVENT");
+
+        for o in event.recurrence_rule.as_ref().map(|_| event.overrides.as_slice()).unwrap_or_default() {
+            ics.line("BEGIN:VEVENT");
+            ics.line(&format!("UID:{}", event.id));
+            ics.line(&format!("DTSTAMP:{}", event.updated_at.format("%Y%m%dT%H%M%SZ")));
+            ics.line(&time("RECURRENCE-ID", o.original_start));
+            ics.line(&time("DTSTART", o.start_at));
+            ics.line(&time("DTEND", o.end_at));
+            ics.text("SUMMARY", o.title.as_deref().unwrap_or(&event.title));
+            ics.event_details(
+                event.description.as_deref(),
+                o.location.as_deref().or(event.location.as_deref()),
+                event.meeting_url.as_deref(),
+            );
+            ics.line(&format!("STATUS:{}", ics_status(o.status.as_ref().unwrap_or(&event.status))));
+            ics.line("END:VEVENT");
+        }
+    }
+
+    ics.line("END:VCALENDAR");
+    ics.out
+}
+
+fn ics_status(status: &EventStatus) -> &'static str {
+    match status {
+        EventStatus::Tentative => "TENTATIVE",
+        EventStatus::Confirmed => "CONFIRMED",
+        EventStatus::Cancelled => "CANCELLED",
+    }
+}
+
+/// Builds CRLF-terminated content lines, folded at 75 octets
+#[derive(Default)]
+struct IcsWriter {
+    out: String,
+}
+
+impl IcsWriter {
+    fn line(&mut self, line: &str) {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > 75 {
+                self.out.push_str("\r\n ");
+                width = 1;
+            }
+            self.out.push(c);
+            width += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+    }
+
+    /// A TEXT property, escaped per RFC 5545 section 3.3.11
+    fn text(&mut self, name: &str, value: &str) {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                ';' => escaped.push_str("\\;"),
+                ',' => escaped.push_str("\\,"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => {}
+                c => escaped.push(c),
+            }
+        }
+        self.line(&format!("{}:{}", name, escaped));
+    }
+
+    fn event_details(&mut self, description: Option<&str>, location: Option<&str>, meeting_url: Option<&str>) {
+        if let Some(description) = description {
+            self.text("DESCRIPTION", description);
+        }
+        if let Some(location) = location {
+            self.text("LOCATION", location);
+        }
+        if let Some(url) = meeting_url {
+            self.line(&format!("URL:{}", url));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDateTime, TimeZone};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap().and_utc()
+    }
+
+    fn viewing(start: &str, rrule: &str, timezone: Option<&str>) -> CalendarEvent {
+        let start = utc(start);
+        CalendarEvent::new(Uuid::new_v4(), Uuid::new_v4(), "Viewing", start, start + chrono::Duration::hours(1))
+            .with_recurrence(rrule, timezone)
+            .unwrap()
+    }
+
+    fn starts(occurrences: &[Occurrence]) -> Vec<String> {
+        occurrences.iter().map(|o| o.start_at.format("%Y-%m-%d %H:%M").to_string()).collect()
+    }
+
+    #[test]
+    fn test_weekly_recurrence_crosses_month_boundary() {
+        // Tuesdays and Thursdays at 14:00 Dubai time (UTC+4, no DST)
+        let event = viewing("2025-01-21 10:00", "FREQ=WEEKLY;BYDAY=TU,TH", Some("Asia/Dubai"));
+
+        let occurrences = expand_occurrences(&event, utc("2025-01-25 00:00")..utc("2025-02-08 00:00"));
+
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2025-01-28 10:00", "2025-01-30 10:00", "2025-02-04 10:00", "2025-02-06 10:00"]
+        );
+        assert!(occurrences.iter().all(|o| o.end_at - o.start_at == chrono::Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_excluded_date_is_skipped_and_counts_toward_count() {
+        let event = viewing("2025-03-03 09:00", "FREQ=WEEKLY;COUNT=4", None).with_exdate(utc("2025-03-10 09:00"));
+
+        let occurrences = expand_occurrences(&event, utc("2025-01-01 00:00")..utc("2026-01-01 00:00"));
+
+        assert_eq!(starts(&occurrences), vec!["2025-03-03 09:00", "2025-03-17 09:00", "2025-03-24 09:00"]);
+    }
+
+    #[test]
+    fn test_override_moves_single_occurrence() {
+        let event = viewing("2025-03-03 09:00", "FREQ=DAILY;COUNT=5", None).with_override(OccurrenceOverride {
+            original_start: utc("2025-03-07 09:00"),
+            start_at: utc("2025-03-04 15:00"),
+            end_at: utc("2025-03-04 16:00"),
+            title: Some("Viewing (rescheduled)".to_string()),
+            location: None,
+            status: None,
+        });
+
+        // The moved instance lands in range even though its original slot does not
+        let occurrences = expand_occurrences(&event, utc("2025-03-04 00:00")..utc("2025-03-05 00:00"));
+
+        assert_eq!(starts(&occurrences), vec!["2025-03-04 09:00", "2025-03-04 15:00"]);
+        assert!(occurrences[1].is_override);
+        assert_eq!(occurrences[1].original_start, utc("2025-03-07 09:00"));
+        assert_eq!(occurrences[1].title, "Viewing (rescheduled)");
+    }
+
+    #[test]
+    fn test_weekly_recurrence_keeps_local_time_over_dst() {
+        // 18:00 in New York; DST starts 9 March 2025
+        let start = chrono_tz::America::New_York.with_ymd_and_hms(2025, 3, 2, 18, 0, 0).unwrap().with_timezone(&Utc);
+        let event = viewing(&start.format("%Y-%m-%d %H:%M").to_string(), "FREQ=WEEKLY;COUNT=2", Some("America/New_York"));
+
+        let occurrences = expand_occurrences(&event, utc("2025-03-01 00:00")..utc("2025-04-01 00:00"));
+
+        assert_eq!(starts(&occurrences), vec!["2025-03-02 23:00", "2025-03-09 22:00"]);
+    }
+
+    #[test]
+    fn test_invalid_timezone_is_rejected() {
+        let event = CalendarEvent::new(Uuid::new_v4(), Uuid::new_v4(), "Call", utc("2025-03-03 09:00"), utc("2025-03-03 10:00"));
+
+        let err = event.with_recurrence("FREQ=DAILY", Some("Mars/Olympus")).unwrap_err();
+
+        assert_eq!(err, RecurrenceError::UnknownTimezone("Mars/Olympus".to_string()));
+    }
+
+    /// Unfold an iCalendar document and collect each VEVENT's properties,
+    /// failing on unbalanced components or malformed lines
+    fn parse_ics(ics: &str) -> Vec<Vec<(String, String)>> {
+        assert!(ics.ends_with("\r\n"));
+        let unfolded = ics.replace("\r\n ", "");
+        let mut stack = Vec::new();
+        let mut events = Vec::new();
+        for line in unfolded.split_terminator("\r\n") {
+            let (name, value) = line.split_once(':').expect("content line without ':'");
+            match name {
+                "BEGIN" => {
+                    stack.push(value.to_string());
+                    if value == "VEVENT" {
+                        events.push(Vec::new());
+                    }
+                }
+                "END" => assert_eq!(stack.pop().as_deref(), Some(value)),
+                _ if stack.last().map(String::as_str) == Some("VEVENT") => {
+                    events.last_mut().unwrap().push((name.to_string(), value.to_string()))
+                }
+                _ => assert_eq!(stack, vec!["VCALENDAR".to_string()]),
+            }
+        }
+        assert!(stack.is_empty());
+        events
+    }
+
+    fn prop<'a>(event: &'a [(String, String)], name: &str) -> Option<&'a str> {
+        event.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_ics_contains_single_and_recurring_events() {
+        let mut single = CalendarEvent::new(Uuid::new_v4(), Uuid::new_v4(), "Call, then; notes", utc("2025-03-03 09:00"), utc("2025-03-03 09:30"));
+        single.location = Some("Office\nFloor 3".to_string());
+        single.description = Some("x".repeat(200));
+        let recurring = viewing("2025-01-21 10:00", "FREQ=WEEKLY;BYDAY=TU,TH", Some("Asia/Dubai"))
+            .with_exdate(utc("2025-01-23 10:00"))
+            .with_override(OccurrenceOverride {
+                original_start: utc("2025-01-28 10:00"),
+                start_at: utc("2025-01-28 12:00"),
+                end_at: utc("2025-01-28 13:00"),
+                title: None,
+                location: Some("Marina Tower".to_string()),
+                status: None,
+            });
+
+        let ics = to_ics(&[single.clone(), recurring.clone()]);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.lines().all(|l| l.trim_end_matches('\r').len() <= 75));
+        let events = parse_ics(&ics);
+        assert_eq!(events.len(), 3);
+
+        let first = &events[0];
+        assert_eq!(prop(first, "UID"), Some(single.id.to_string().as_str()));
+        assert_eq!(prop(first, "DTSTART"), Some("20250303T090000Z"));
+        assert_eq!(prop(first, "DTEND"), Some("20250303T093000Z"));
+        assert_eq!(prop(first, "SUMMARY"), Some(r"Call\, then\; notes"));
+        assert_eq!(prop(first, "LOCATION"), Some(r"Office\nFloor 3"));
+        assert_eq!(prop(first, "DESCRIPTION").map(str::len), Some(200));
+        assert_eq!(prop(first, "RRULE"), None);
+
+        let series = &events[1];
+        assert_eq!(prop(series, "UID"), Some(recurring.id.to_string().as_str()));
+        assert_eq!(prop(series, "DTSTART;TZID=Asia/Dubai"), Some("20250121T140000"));
+        assert_eq!(prop(series, "RRULE"), Some("FREQ=WEEKLY;BYDAY=TU,TH"));
+        assert_eq!(prop(series, "EXDATE;TZID=Asia/Dubai"), Some("20250123T140000"));
+
+        let moved = &events[2];
+        assert_eq!(prop(moved, "UID"), Some(recurring.id.to_string().as_str()));
+        assert_eq!(prop(moved, "RECURRENCE-ID;TZID=Asia/Dubai"), Some("20250128T140000"));
+        assert_eq!(prop(moved, "DTSTART;TZID=Asia/Dubai"), Some("20250128T160000"));
+        assert_eq!(prop(moved, "LOCATION"), Some("Marina Tower"));
+    }
 }
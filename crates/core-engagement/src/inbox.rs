@@ -1,14 +1,34 @@
 //! Inbox Service - Thread aggregation for unified messaging
 //!
 //! Groups interactions into conversation threads by entity (Contact, Deal, etc.)
+//! and channel. Threads that belong together can be merged, and a mis-grouped
+//! thread split in two.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use uuid::Uuid;
 
+/// Inbox errors
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InboxError {
+    #[error("Message {0} is not in the thread")]
+    MessageNotInThread(Uuid),
+
+    #[error("Splitting at the first message would leave the thread empty")]
+    EmptySplit,
+
+    #[error("At least two threads are needed to merge")]
+    NothingToMerge,
+}
+
 /// A conversation thread in the inbox
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InboxThread {
+    /// Thread identity; one per (entity, channel) unless merged or split
+    pub thread_id: Uuid,
+    /// Channel the thread started on (the interaction type)
+    pub channel: String,
     /// Entity ID this thread is about (e.g., Contact ID)
     pub entity_id: Uuid,
     /// Entity type (contact, deal, company, etc.)
@@ -76,6 +96,8 @@ impl InboxThread {
 impl Default for InboxThread {
     fn default() -> Self {
         Self {
+            thread_id: Uuid::nil(),
+            channel: String::new(),
             entity_id: Uuid::nil(),
             entity_type: String::new(),
             entity_name: String::new(),
@@ -86,3 +108,94 @@ impl Default for InboxThread {
         }
     }
 }
+
+/// Whether a message came from the customer ("inbound") or the team ("outbound")
+///
+/// An explicit direction recorded with the message wins; otherwise emails
+/// and messages are treated as inbound and everything else as outbound.
+pub fn message_direction(interaction_type: &str, declared: Option<&str>) -> &'static str {
+    match declared {
+        Some("inbound") => "inbound",
+        Some("outbound") => "outbound",
+        _ => match interaction_type.to_lowercase().as_str() {
+            "email" | "message" => "inbound",
+            _ => "outbound",
+        },
+    }
+}
+
+/// Channel used for thread identity: the interaction type, lowercased
+pub fn thread_channel(interaction_type: &str) -> String {
+    interaction_type.to_lowercase()
+}
+
+/// Combine the messages of several threads into one conversation, oldest first
+///
+/// Messages at the same instant are ordered by id so the result is stable.
+pub fn merge_messages(threads: Vec<Vec<ThreadMessage>>) -> Vec<ThreadMessage> {
+    let mut messages: Vec<ThreadMessage> = threads.into_iter().flatten().collect();
+    messages.sort_by_key(|m| (m.occurred_at, m.id));
+    messages
+}
+
+/// Split a conversation at `from_message_id`: the messages before it stay,
+/// it and everything after move to a new thread
+pub fn split_messages(
+    messages: Vec<ThreadMessage>,
+    from_message_id: Uuid,
+) -> Result<(Vec<ThreadMessage>, Vec<ThreadMessage>), InboxError> {
+    let mut messages = merge_messages(vec![messages]);
+    let at = messages
+        .iter()
+        .position(|m| m.id == from_message_id)
+        .ok_or(InboxError::MessageNotInThread(from_message_id))?;
+    if at == 0 {
+        return Err(InboxError::EmptySplit);
+    }
+
+    let moved = messages.split_off(at);
+    Ok((messages, moved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn message(minute: u32, interaction_type: &str) -> ThreadMessage {
+        ThreadMessage {
+            id: Uuid::new_v4(),
+            interaction_type: interaction_type.to_string(),
+            title: format!("{} at {}", interaction_type, minute),
+            content: None,
+            created_by: Uuid::nil(),
+            occurred_at: Utc.with_ymd_and_hms(2025, 3, 1, 9, minute, 0).unwrap(),
+            direction: "inbound".to_string(),
+            duration_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_interleaves_by_time() {
+        let email = vec![message(0, "email"), message(20, "email")];
+        let whatsapp = vec![message(10, "message"), message(30, "message")];
+
+        let merged = merge_messages(vec![email, whatsapp]);
+
+        let titles: Vec<&str> = merged.iter().map(|m| m.title.as_str()).collect();
+        assert_eq!(titles, vec!["email at 0", "message at 10", "email at 20", "message at 30"]);
+    }
+
+    #[test]
+    fn test_split_partitions_at_message() {
+        let messages = vec![message(30, "email"), message(0, "email"), message(10, "email")];
+        let from = messages[2].id;
+
+        let (kept, moved) = split_messages(messages.clone(), from).unwrap();
+
+        assert_eq!(kept.iter().map(|m| m.id).collect::<Vec<_>>(), vec![messages[1].id]);
+        assert_eq!(moved.iter().map(|m| m.id).collect::<Vec<_>>(), vec![messages[2].id, messages[0].id]);
+        assert_eq!(split_messages(messages.clone(), messages[1].id).unwrap_err(), InboxError::EmptySplit);
+        assert!(matches!(split_messages(messages, Uuid::new_v4()), Err(InboxError::MessageNotInThread(_))));
+    }
+}
@@ -4,8 +4,12 @@
 
 pub mod interaction;
 pub mod calendar;
+pub mod recurrence;
+pub mod timeline;
 pub mod inbox;
 
 pub use interaction::*;
 pub use calendar::*;
+pub use recurrence::*;
+pub use timeline::*;
 pub use inbox::*;
@@ -0,0 +1,335 @@
+//! Recurrence rules - the iCalendar RRULE subset used by calendar events
+//!
+//! Supported parts: FREQ (DAILY, WEEKLY, MONTHLY, YEARLY), INTERVAL, COUNT,
+//! UNTIL and BYDAY. Occurrences are generated in the event's local time and
+//! converted to UTC per instance, so a 10:00 viewing stays at 10:00 across
+//! DST changes.
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Recurrence errors
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RecurrenceError {
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRule(String),
+
+    #[error("Unknown timezone: {0}")]
+    UnknownTimezone(String),
+}
+
+/// How often a rule repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A BYDAY entry, e.g. `MO`, `2TU` or `-1FR`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekdayNum {
+    /// Nth weekday of the month (negative counts from the end); monthly rules only
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+/// End of a recurrence (inclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    /// `UNTIL=20250131` - last local date
+    Date(NaiveDate),
+    /// `UNTIL=20250131T090000Z` - last instant
+    DateTime(DateTime<Utc>),
+}
+
+/// A parsed RRULE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub by_day: Vec<WeekdayNum>,
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RecurrenceError;
+
+    /// Parse `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`, with or without an `RRULE:` prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |msg: String| RecurrenceError::InvalidRule(msg);
+        let body = s.trim();
+        let body = body.strip_prefix("RRULE:").unwrap_or(body);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = Vec::new();
+
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected KEY=VALUE, got '{}'", part)))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(invalid(format!("unsupported FREQ '{}'", other))),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| invalid(format!("INTERVAL must be a positive integer, got '{}'", value)))?
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|n| *n > 0)
+                            .ok_or_else(|| invalid(format!("COUNT must be a positive integer, got '{}'", value)))?,
+                    )
+                }
+                "UNTIL" => until = Some(parse_until(value).ok_or_else(|| invalid(format!("invalid UNTIL '{}'", value)))?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(|d| parse_weekday_num(d).ok_or_else(|| invalid(format!("invalid BYDAY '{}'", d))))
+                        .collect::<Result<_, _>>()?
+                }
+                other => return Err(invalid(format!("unsupported rule part '{}'", other))),
+            }
+        }
+
+        let freq = freq.ok_or_else(|| invalid("FREQ is required".to_string()))?;
+        if count.is_some() && until.is_some() {
+            return Err(invalid("COUNT and UNTIL cannot both be set".to_string()));
+        }
+        if freq == Frequency::Yearly && !by_day.is_empty() {
+            return Err(invalid("BYDAY is not supported for YEARLY rules".to_string()));
+        }
+        if freq != Frequency::Monthly && by_day.iter().any(|d| d.ordinal.is_some()) {
+            return Err(invalid("BYDAY ordinals are only supported for MONTHLY rules".to_string()));
+        }
+
+        Ok(Self { freq, interval, count, until, by_day })
+    }
+}
+
+fn parse_until(value: &str) -> Option<Until> {
+    if let Some(instant) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(instant, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Until::DateTime(naive.and_utc()));
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(Until::Date)
+}
+
+fn parse_weekday_num(value: &str) -> Option<WeekdayNum> {
+    let value = value.trim().to_ascii_uppercase();
+    if value.len() < 2 {
+        return None;
+    }
+    let (ordinal, day) = value.split_at(value.len() - 2);
+    let weekday = match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        n => Some(n.parse::<i8>().ok().filter(|n| *n != 0 && n.abs() <= 5)?),
+    };
+    Some(WeekdayNum { ordinal, weekday })
+}
+
+/// Resolve an IANA timezone name; `None` means UTC
+pub fn parse_timezone(name: Option<&str>) -> Result<Tz, RecurrenceError> {
+    match name {
+        None => Ok(Tz::UTC),
+        Some(name) => name.parse().map_err(|_| RecurrenceError::UnknownTimezone(name.to_string())),
+    }
+}
+
+/// Convert a local wall-clock time to UTC. Ambiguous times (DST fall-back)
+/// take the first instance; times in a DST gap use the offset in force
+/// before the gap, as RFC 5545 prescribes.
+pub fn local_to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) => dt.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        LocalResult::None => {
+            let before = tz.offset_from_utc_datetime(&(local - Duration::days(1))).fix();
+            (local - Duration::seconds(before.local_minus_utc() as i64)).and_utc()
+        }
+    }
+}
+
+impl RecurrenceRule {
+    /// Start instants of every occurrence beginning at `dtstart` (local time
+    /// in `tz`) up to and including `bound`. COUNT is applied from the first
+    /// occurrence, so results are the same whatever the bound.
+    pub fn starts(&self, dtstart: NaiveDateTime, tz: Tz, bound: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut starts = Vec::new();
+        let time = dtstart.time();
+        let start_date = dtstart.date();
+        let last_date = bound.with_timezone(&tz).date_naive() + Duration::days(1);
+
+        for period in 0u32.. {
+            let Some((period_start, dates)) = self.period_dates(start_date, period) else {
+                break;
+            };
+            if period_start > last_date {
+                break;
+            }
+
+            for date in dates {
+                if date < start_date {
+                    continue;
+                }
+                let local = date.and_time(time);
+                let instant = local_to_utc(tz, local);
+                let past_until = match self.until {
+                    Some(Until::Date(until)) => date > until,
+                    Some(Until::DateTime(until)) => instant > until,
+                    None => false,
+                };
+                if past_until || instant > bound {
+                    return starts;
+                }
+                starts.push(instant);
+                if self.count.is_some_and(|count| starts.len() >= count as usize) {
+                    return starts;
+                }
+            }
+        }
+
+        starts
+    }
+
+    /// First day of the `period`-th repetition and the candidate dates in it, in order
+    fn period_dates(&self, start: NaiveDate, period: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = period.checked_mul(self.interval)?;
+        match self.freq {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(Duration::days(step as i64))?;
+                Some((date, self.matches_weekday(date).then_some(date).into_iter().collect()))
+            }
+            Frequency::Weekly => {
+                let week_start = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+                let week = week_start.checked_add_signed(Duration::weeks(step as i64))?;
+                let mut days: Vec<u32> = if self.by_day.is_empty() {
+                    vec![start.weekday().num_days_from_monday()]
+                } else {
+                    self.by_day.iter().map(|d| d.weekday.num_days_from_monday()).collect()
+                };
+                days.sort_unstable();
+                days.dedup();
+                Some((week, days.into_iter().map(|d| week + Duration::days(d as i64)).collect()))
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + step as i64;
+                let (year, month) = (i32::try_from(months / 12).ok()?, (months % 12) as u32 + 1);
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                if self.by_day.is_empty() {
+                    // Months without the start's day (e.g. the 31st) are skipped
+                    return Some((first, NaiveDate::from_ymd_opt(year, month, start.day()).into_iter().collect()));
+                }
+                let days_in_month = first.checked_add_months(chrono::Months::new(1))?.pred_opt()?.day();
+                let dates = (1..=days_in_month)
+                    .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+                    .filter(|date| self.by_day.iter().any(|d| matches_monthly(d, *date, days_in_month)))
+                    .collect();
+                Some((first, dates))
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(step).ok()?)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                // Feb 29 only recurs in leap years
+                Some((first, NaiveDate::from_ymd_opt(year, start.month(), start.day()).into_iter().collect()))
+            }
+        }
+    }
+
+    fn matches_weekday(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == date.weekday())
+    }
+}
+
+fn matches_monthly(by_day: &WeekdayNum, date: NaiveDate, days_in_month: u32) -> bool {
+    if by_day.weekday != date.weekday() {
+        return false;
+    }
+    match by_day.ordinal {
+        None => true,
+        Some(n) if n > 0 => (date.day() - 1) / 7 + 1 == n as u32,
+        Some(n) => (days_in_month - date.day()) / 7 + 1 == n.unsigned_abs() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule: RecurrenceRule = "RRULE:FREQ=MONTHLY;INTERVAL=2;COUNT=6;BYDAY=-1FR".parse().unwrap();
+
+        assert_eq!(rule.freq, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.count, Some(6));
+        assert_eq!(rule.by_day, vec![WeekdayNum { ordinal: Some(-1), weekday: Weekday::Fri }]);
+        assert!("FREQ=HOURLY".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=WEEKLY;BYDAY=1MO".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;COUNT=2;UNTIL=20250101".parse::<RecurrenceRule>().is_err());
+    }
+
+    #[test]
+    fn test_last_friday_of_month() {
+        let rule: RecurrenceRule = "FREQ=MONTHLY;COUNT=3;BYDAY=-1FR".parse().unwrap();
+
+        let starts = rule.starts(local("2025-01-01 09:00"), Tz::UTC, local("2026-01-01 00:00").and_utc());
+
+        let days: Vec<String> = starts.iter().map(|s| s.format("%Y-%m-%d").to_string()).collect();
+        assert_eq!(days, vec!["2025-01-31", "2025-02-28", "2025-03-28"]);
+    }
+
+    #[test]
+    fn test_local_time_kept_across_dst() {
+        // Europe/London moves to BST on 30 March 2025
+        let tz: Tz = "Europe/London".parse().unwrap();
+        let rule: RecurrenceRule = "FREQ=DAILY;UNTIL=20250331".parse().unwrap();
+
+        let starts = rule.starts(local("2025-03-29 10:00"), tz, local("2025-12-31 00:00").and_utc());
+
+        assert_eq!(
+            starts,
+            vec![
+                local("2025-03-29 10:00").and_utc(),
+                local("2025-03-30 09:00").and_utc(),
+                local("2025-03-31 09:00").and_utc(),
+            ]
+        );
+        // 01:30 does not exist on the 30th; the pre-gap offset moves it to 02:30 BST
+        assert_eq!(local_to_utc(tz, local("2025-03-30 01:30")), local("2025-03-30 01:30").and_utc());
+    }
+}
@@ -0,0 +1,195 @@
+//! Timeline - a record's interactions, field changes and tasks merged into
+//! one newest-first feed, optionally including activity on associated records
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Default and maximum page sizes
+pub const DEFAULT_TIMELINE_LIMIT: usize = 25;
+pub const MAX_TIMELINE_LIMIT: usize = 100;
+
+/// What a timeline item was built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineSource {
+    Interaction,
+    FieldChange,
+    Task,
+}
+
+/// One entry in a record's timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineItem {
+    /// Id of the interaction, audit entry or task
+    pub id: Uuid,
+    pub source: TimelineSource,
+    /// Interaction type for interactions; "field_change" or "task" otherwise
+    pub item_type: String,
+    pub occurred_at: DateTime<Utc>,
+    /// Record the item belongs to (the timeline's record or an associated one)
+    pub entity_type: String,
+    pub record_id: Uuid,
+    pub title: String,
+    pub summary: Option<String>,
+    pub actor_id: Option<Uuid>,
+    /// Association the item was pulled in through, if not the record's own
+    pub via_association: Option<String>,
+}
+
+impl TimelineItem {
+    pub fn cursor(&self) -> TimelineCursor {
+        TimelineCursor { occurred_at: self.occurred_at, id: self.id }
+    }
+}
+
+/// Position in a timeline: items strictly older than this come next
+///
+/// Serialized as `<unix micros>_<id>` so it can travel in a query string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineCursor {
+    pub occurred_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl fmt::Display for TimelineCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.occurred_at.timestamp_micros(), self.id)
+    }
+}
+
+impl FromStr for TimelineCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid timeline cursor '{}'", s);
+        let (micros, id) = s.split_once('_').ok_or_else(invalid)?;
+        let occurred_at = micros
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        Ok(Self { occurred_at, id })
+    }
+}
+
+/// Options for building a timeline
+#[derive(Debug, Clone, Default)]
+pub struct TimelineOptions {
+    /// Also include interactions on directly associated records
+    pub include_associated: bool,
+    /// Only items of these types (interaction types, "field_change", "task"); all if empty
+    pub item_types: Vec<String>,
+    /// Continue after this cursor
+    pub cursor: Option<TimelineCursor>,
+    /// Page size; defaults to [`DEFAULT_TIMELINE_LIMIT`], capped at [`MAX_TIMELINE_LIMIT`]
+    pub limit: Option<usize>,
+}
+
+impl TimelineOptions {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_TIMELINE_LIMIT).clamp(1, MAX_TIMELINE_LIMIT)
+    }
+
+    /// Whether items of `item_type` are wanted
+    pub fn includes(&self, item_type: &str) -> bool {
+        self.item_types.is_empty() || self.item_types.iter().any(|t| t.eq_ignore_ascii_case(item_type))
+    }
+}
+
+/// A page of timeline items, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelinePage {
+    pub items: Vec<TimelineItem>,
+    /// Pass back as `cursor` for the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Merge items from every source into one page
+///
+/// Each source must already be limited to items after the cursor; fetching
+/// `limit + 1` per source is enough to tell whether another page exists.
+pub fn merge_timeline(sources: Vec<Vec<TimelineItem>>, options: &TimelineOptions) -> TimelinePage {
+    let limit = options.limit();
+    let mut items: Vec<TimelineItem> = sources
+        .into_iter()
+        .flatten()
+        .filter(|item| options.includes(&item.item_type))
+        .filter(|item| options.cursor.is_none_or(|c| (item.occurred_at, item.id) < (c.occurred_at, c.id)))
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse((item.occurred_at, item.id)));
+
+    let has_more = items.len() > limit;
+    items.truncate(limit);
+    let next_cursor = has_more.then(|| items.last().map(|item| item.cursor().to_string())).flatten();
+
+    TimelinePage { items, next_cursor }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn item(minute: u32, item_type: &str) -> TimelineItem {
+        let source = match item_type {
+            "task" => TimelineSource::Task,
+            "field_change" => TimelineSource::FieldChange,
+            _ => TimelineSource::Interaction,
+        };
+        TimelineItem {
+            id: Uuid::new_v4(),
+            source,
+            item_type: item_type.to_string(),
+            occurred_at: Utc.with_ymd_and_hms(2025, 3, 1, 9, minute, 0).unwrap(),
+            entity_type: "deal".to_string(),
+            record_id: Uuid::nil(),
+            title: format!("{} at {}", item_type, minute),
+            summary: None,
+            actor_id: None,
+            via_association: None,
+        }
+    }
+
+    fn titles(page: &TimelinePage) -> Vec<&str> {
+        page.items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_merge_sorts_newest_first_and_paginates() {
+        let sources = vec![
+            vec![item(30, "call"), item(0, "email")],
+            vec![item(20, "field_change")],
+            vec![item(10, "task")],
+        ];
+        let mut options = TimelineOptions { limit: Some(3), ..Default::default() };
+
+        let first = merge_timeline(sources.clone(), &options);
+        assert_eq!(titles(&first), vec!["call at 30", "field_change at 20", "task at 10"]);
+
+        options.cursor = Some(first.next_cursor.unwrap().parse().unwrap());
+        let second = merge_timeline(sources, &options);
+        assert_eq!(titles(&second), vec!["email at 0"]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn test_filter_by_item_type() {
+        let options = TimelineOptions { item_types: vec!["Call".to_string(), "task".to_string()], ..Default::default() };
+
+        let page = merge_timeline(vec![vec![item(30, "call"), item(20, "email"), item(10, "task")]], &options);
+
+        assert_eq!(titles(&page), vec!["call at 30", "task at 10"]);
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = item(5, "call").cursor();
+
+        assert_eq!(cursor.to_string().parse::<TimelineCursor>().unwrap(), cursor);
+        assert!("garbage".parse::<TimelineCursor>().is_err());
+    }
+}
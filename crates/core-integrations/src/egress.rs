@@ -0,0 +1,185 @@
+//! Egress - outbound requests to tenant-supplied URLs
+//!
+//! Webhook subscriptions and workflow HTTP nodes call URLs a tenant typed in,
+//! so the host is resolved and refused if any address is loopback, private,
+//! link-local or otherwise not publicly routable. Check at save time to
+//! reject bad URLs early and again before every request; the client built
+//! from the checked target connects only to the vetted addresses (so a DNS
+//! answer can't change in between) and never follows redirects.
+
+use reqwest::redirect::Policy;
+use reqwest::{ClientBuilder, Url};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EgressError {
+    /// Not an absolute http(s) URL with a host
+    InvalidUrl(String),
+    /// The host didn't resolve
+    Unresolvable(String),
+    /// The host resolves to an address that isn't publicly routable
+    Blocked(String),
+}
+
+impl std::fmt::Display for EgressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EgressError::InvalidUrl(url) => write!(f, "Not an http(s) URL: {}", url),
+            EgressError::Unresolvable(host) => write!(f, "Could not resolve host {}", host),
+            EgressError::Blocked(host) => write!(f, "Host {} is not a public address", host),
+        }
+    }
+}
+
+impl std::error::Error for EgressError {}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        // Carrier-grade NAT 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking 198.18.0.0/15
+        || (a == 198 && (b == 18 || b == 19))
+        // Reserved 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+    let segments = ip.segments();
+    // NAT64 64:ff9b::/96 reaches the embedded IPv4 address
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let o = ip.octets();
+        return is_public_v4(Ipv4Addr::new(o[12], o[13], o[14], o[15]));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local fe80::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+        // Documentation 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+/// Whether `ip` is publicly routable
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+/// Which destinations outbound requests may reach
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EgressPolicy {
+    allow_private: bool,
+}
+
+impl EgressPolicy {
+    /// Public addresses only
+    pub fn public_only() -> Self {
+        Self { allow_private: false }
+    }
+
+    /// Any address; for tests against local mock servers
+    pub fn allow_private() -> Self {
+        Self { allow_private: true }
+    }
+
+    /// Resolve `url`'s host and check every address it resolves to
+    pub async fn check(&self, url: &str) -> Result<CheckedTarget, EgressError> {
+        let parsed = Url::parse(url).map_err(|_| EgressError::InvalidUrl(url.to_string()))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(EgressError::InvalidUrl(url.to_string()));
+        }
+        let host = parsed.host_str().ok_or_else(|| EgressError::InvalidUrl(url.to_string()))?.to_string();
+        let port = parsed.port_or_known_default().unwrap_or(443);
+
+        // IPv6 literals come back bracketed
+        let bare = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs: Vec<SocketAddr> = match bare.parse::<IpAddr>() {
+            Ok(ip) => vec![SocketAddr::new(ip, port)],
+            Err(_) => tokio::net::lookup_host((bare, port))
+                .await
+                .map_err(|_| EgressError::Unresolvable(host.clone()))?
+                .collect(),
+        };
+        if addrs.is_empty() {
+            return Err(EgressError::Unresolvable(host));
+        }
+        if !self.allow_private && !addrs.iter().all(|addr| is_public(addr.ip())) {
+            return Err(EgressError::Blocked(host));
+        }
+
+        Ok(CheckedTarget { url: parsed, host: bare.to_string(), addrs })
+    }
+}
+
+/// A URL whose host resolved only to allowed addresses
+#[derive(Debug, Clone)]
+pub struct CheckedTarget {
+    pub url: Url,
+    host: String,
+    addrs: Vec<SocketAddr>,
+}
+
+impl CheckedTarget {
+    /// A client that connects only to the checked addresses and doesn't
+    /// follow redirects
+    pub fn client_builder(&self) -> ClientBuilder {
+        reqwest::Client::builder()
+            .redirect(Policy::none())
+            .resolve_to_addrs(&self.host, &self.addrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0",
+            "100.64.0.1", "255.255.255.255", "::1", "::", "fd00::1", "fe80::1", "::ffff:10.0.0.1",
+            "::ffff:169.254.169.254", "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{} should be blocked", ip);
+        }
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{} should be allowed", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_refuses_internal_and_non_http_targets() {
+        let policy = EgressPolicy::public_only();
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://169.254.169.254/latest/meta-data/",
+            "https://[::1]/hook",
+            "http://localhost/hook",
+            "http://10.0.0.5/admin",
+        ] {
+            assert!(matches!(policy.check(url).await, Err(EgressError::Blocked(_))), "{}", url);
+        }
+        assert!(matches!(policy.check("ftp://example.com/x").await, Err(EgressError::InvalidUrl(_))));
+        assert!(matches!(policy.check("not a url").await, Err(EgressError::InvalidUrl(_))));
+        assert!(policy.check("https://93.184.216.34/hook").await.is_ok());
+
+        assert!(EgressPolicy::allow_private().check("http://127.0.0.1:8080/hook").await.is_ok());
+    }
+}
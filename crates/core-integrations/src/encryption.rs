@@ -1,22 +1,209 @@
 //! Encryption utilities for secure credential storage
 //!
 //! Uses AES-256-GCM for authenticated encryption of API credentials.
+//! Ciphertext is prefixed with the version of the key that produced it, so
+//! master keys can be rotated without losing access to stored credentials.
+//! Ciphertext written before versioning (`nonce || ciphertext`) is still
+//! read with the legacy key, and rotation upgrades it to the versioned form.
 
 use aes_gcm::{
     aead::{Aead, KeyInit},
     Aes256Gcm, Nonce,
 };
 use rand::Rng;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use tracing::info;
+use uuid::Uuid;
 
 /// Encryption key length (256 bits = 32 bytes)
 const KEY_LENGTH: usize = 32;
 /// Nonce length for AES-GCM (96 bits = 12 bytes)
 const NONCE_LENGTH: usize = 12;
+/// Rows re-encrypted per batch by `rotate_credentials`
+const ROTATION_BATCH_SIZE: i64 = 100;
+/// Version of the key that wrote unversioned ciphertext, unless configured
+const DEFAULT_LEGACY_KEY_ID: KeyId = 1;
+
+/// Columns holding `KeyRing` ciphertext as (table, column); each table has
+/// `id` and `updated_at`
+const ENCRYPTED_COLUMNS: &[(&str, &str)] = &[
+    ("integrations", "credentials_encrypted"),
+    ("webhook_subscriptions", "secret_encrypted"),
+];
+
+/// Identifies a master key; stored as the first byte of every ciphertext
+pub type KeyId = u8;
+
+/// Errors from decrypting stored credentials
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecryptionError {
+    /// Ciphertext was produced under a key that is not in the key ring
+    UnknownKeyVersion(KeyId),
+    /// Ciphertext shorter than version byte + nonce
+    TooShort,
+    /// Authentication failed (wrong key or tampered data)
+    Failed(String),
+    /// Decrypted bytes are not the expected JSON
+    InvalidJson(String),
+}
+
+impl std::fmt::Display for DecryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptionError::UnknownKeyVersion(id) => write!(f, "Unknown encryption key version: {}", id),
+            DecryptionError::TooShort => write!(f, "Encrypted data too short"),
+            DecryptionError::Failed(msg) => write!(f, "Decryption failed: {}", msg),
+            DecryptionError::InvalidJson(msg) => write!(f, "JSON deserialization failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DecryptionError {}
+
+/// Master keys by version; new data is always encrypted with the current key
+#[derive(Clone)]
+pub struct KeyRing {
+    keys: HashMap<KeyId, [u8; KEY_LENGTH]>,
+    current: KeyId,
+    /// Key for ciphertext from before versioning
+    legacy: KeyId,
+}
+
+impl KeyRing {
+    pub fn new(current: KeyId, key: [u8; KEY_LENGTH]) -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(current, key);
+        Self { keys, current, legacy: DEFAULT_LEGACY_KEY_ID }
+    }
+
+    /// Add a key that can still decrypt older ciphertext
+    pub fn with_key(mut self, key_id: KeyId, key: [u8; KEY_LENGTH]) -> Self {
+        self.keys.insert(key_id, key);
+        self
+    }
+
+    /// Read unversioned ciphertext with the key of this version
+    pub fn with_legacy_key_id(mut self, key_id: KeyId) -> Self {
+        self.legacy = key_id;
+        self
+    }
+
+    /// Load from the environment:
+    /// - `INTEGRATION_ENCRYPTION_KEY`: current key (hex)
+    /// - `INTEGRATION_ENCRYPTION_KEY_ID`: its version (default 1)
+    /// - `INTEGRATION_ENCRYPTION_OLD_KEYS`: retired keys as `id:hex,id:hex`
+    /// - `INTEGRATION_ENCRYPTION_LEGACY_KEY_ID`: version of the key that
+    ///   wrote unversioned ciphertext (default 1)
+    pub fn from_env() -> Self {
+        // TODO: Load from secrets manager
+        // Fallback key is for development only (CHANGE IN PRODUCTION!)
+        let key_hex = std::env::var("INTEGRATION_ENCRYPTION_KEY")
+            .unwrap_or_else(|_| "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef".to_string());
+        let current = std::env::var("INTEGRATION_ENCRYPTION_KEY_ID")
+            .ok()
+            .and_then(|id| id.parse().ok())
+            .unwrap_or(1);
+
+        let mut ring = Self::new(current, parse_key(&key_hex).unwrap_or_default());
+
+        for entry in std::env::var("INTEGRATION_ENCRYPTION_OLD_KEYS").unwrap_or_default().split(',') {
+            let parsed = entry
+                .split_once(':')
+                .and_then(|(id, hex)| Some((id.trim().parse::<KeyId>().ok()?, parse_key(hex.trim())?)));
+            if let Some((id, key)) = parsed {
+                ring.keys.entry(id).or_insert(key);
+            }
+        }
+
+        if let Some(legacy) = std::env::var("INTEGRATION_ENCRYPTION_LEGACY_KEY_ID").ok().and_then(|id| id.parse().ok()) {
+            ring.legacy = legacy;
+        }
+
+        ring
+    }
+
+    pub fn current_key_id(&self) -> KeyId {
+        self.current
+    }
+
+    pub fn has_key(&self, key_id: KeyId) -> bool {
+        self.keys.contains_key(&key_id)
+    }
+
+    /// Encrypt with the current key
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        self.encrypt_with(self.current, plaintext)
+    }
+
+    /// Encrypt with a specific key version
+    ///
+    /// The output format is: key_id (1 byte) || nonce (12 bytes) || ciphertext
+    pub fn encrypt_with(&self, key_id: KeyId, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let key = self.keys.get(&key_id)
+            .ok_or_else(|| format!("Unknown encryption key version: {}", key_id))?;
+        let encrypted = encrypt(plaintext, key)?;
+
+        let mut result = Vec::with_capacity(1 + encrypted.len());
+        result.push(key_id);
+        result.extend(encrypted);
+        Ok(result)
+    }
+
+    /// Decrypt with the key named by the embedded version byte, or as
+    /// unversioned ciphertext with the legacy key
+    pub fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>, DecryptionError> {
+        self.decrypt_versioned(encrypted).map(|(_, plaintext)| plaintext)
+    }
+
+    /// Decrypt, also returning the key version the data was under; `None`
+    /// means unversioned ciphertext read with the legacy key
+    pub fn decrypt_versioned(&self, encrypted: &[u8]) -> Result<(Option<KeyId>, Vec<u8>), DecryptionError> {
+        let (&key_id, rest) = encrypted.split_first().ok_or(DecryptionError::TooShort)?;
+        let versioned = match self.keys.get(&key_id) {
+            Some(_) if rest.len() < NONCE_LENGTH => Err(DecryptionError::TooShort),
+            Some(key) => decrypt(rest, key).map_err(DecryptionError::Failed),
+            None => Err(DecryptionError::UnknownKeyVersion(key_id)),
+        };
+        let error = match versioned {
+            Ok(plaintext) => return Ok((Some(key_id), plaintext)),
+            Err(error) => error,
+        };
+
+        // Unversioned: the first byte was part of the nonce. GCM
+        // authentication rules out reading one format as the other.
+        if let Some(key) = self.keys.get(&self.legacy) {
+            if let Ok(plaintext) = decrypt(encrypted, key) {
+                return Ok((None, plaintext));
+            }
+        }
+        Err(error)
+    }
+
+    /// Encrypt a JSON-serializable value with the current key
+    pub fn encrypt_json<T: serde::Serialize>(&self, value: &T) -> Result<Vec<u8>, String> {
+        let json = serde_json::to_vec(value)
+            .map_err(|e| format!("JSON serialization failed: {}", e))?;
+        self.encrypt(&json)
+    }
+
+    /// Decrypt and deserialize JSON
+    pub fn decrypt_json<T: serde::de::DeserializeOwned>(&self, encrypted: &[u8]) -> Result<T, DecryptionError> {
+        let plaintext = self.decrypt(encrypted)?;
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| DecryptionError::InvalidJson(e.to_string()))
+    }
+}
+
+fn parse_key(key_hex: &str) -> Option<[u8; KEY_LENGTH]> {
+    let bytes = hex::decode(key_hex).ok()?;
+    bytes.get(..KEY_LENGTH)?.try_into().ok()
+}
 
 /// Encrypt data using AES-256-GCM
 /// 
 /// The output format is: nonce (12 bytes) || ciphertext
-pub fn encrypt(plaintext: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, String> {
+fn encrypt(plaintext: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, String> {
     let cipher = Aes256Gcm::new_from_slice(key)
         .map_err(|e| format!("Failed to create cipher: {}", e))?;
     
@@ -39,7 +226,7 @@ pub fn encrypt(plaintext: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, Stri
 }
 
 /// Decrypt data encrypted with AES-256-GCM
-pub fn decrypt(encrypted: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, String> {
+fn decrypt(encrypted: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, String> {
     if encrypted.len() < NONCE_LENGTH {
         return Err("Encrypted data too short".to_string());
     }
@@ -57,18 +244,88 @@ pub fn decrypt(encrypted: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, Stri
         .map_err(|e| format!("Decryption failed: {}", e))
 }
 
-/// Encrypt a JSON-serializable value
-pub fn encrypt_json<T: serde::Serialize>(value: &T, key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, String> {
-    let json = serde_json::to_vec(value)
-        .map_err(|e| format!("JSON serialization failed: {}", e))?;
-    encrypt(&json, key)
+/// Re-encrypt stored credentials from `old_key_id` to `new_key_id`
+///
+/// Unversioned ciphertext from before key versioning is re-encrypted too.
+/// Runs in a single transaction, processing rows in batches. Returns the
+/// number of rows re-encrypted.
+pub async fn rotate_credentials(
+    pool: &PgPool,
+    keys: &KeyRing,
+    old_key_id: KeyId,
+    new_key_id: KeyId,
+) -> Result<u64, String> {
+    if old_key_id == new_key_id {
+        return Ok(0);
+    }
+    for key_id in [old_key_id, new_key_id] {
+        if !keys.has_key(key_id) {
+            return Err(DecryptionError::UnknownKeyVersion(key_id).to_string());
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Transaction failed: {}", e))?;
+    let mut rotated = 0;
+    for (table, column) in ENCRYPTED_COLUMNS {
+        rotated += rotate_column(&mut tx, keys, table, column, old_key_id, new_key_id).await?;
+    }
+
+    tx.commit().await.map_err(|e| format!("Commit failed: {}", e))?;
+    info!(old_key_id, new_key_id, rotated, "Encrypted credentials rotated");
+
+    Ok(rotated)
 }
 
-/// Decrypt and deserialize JSON
-pub fn decrypt_json<T: serde::de::DeserializeOwned>(encrypted: &[u8], key: &[u8; KEY_LENGTH]) -> Result<T, String> {
-    let plaintext = decrypt(encrypted, key)?;
-    serde_json::from_slice(&plaintext)
-        .map_err(|e| format!("JSON deserialization failed: {}", e))
+async fn rotate_column(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    keys: &KeyRing,
+    table: &str,
+    column: &str,
+    old_key_id: KeyId,
+    new_key_id: KeyId,
+) -> Result<u64, String> {
+    let mut rotated = 0;
+    let mut after = Uuid::nil();
+
+    loop {
+        // The version byte can't be filtered on in SQL: unversioned rows
+        // start with a random nonce byte
+        let batch: Vec<(Uuid, Vec<u8>)> = sqlx::query_as(&format!(
+            "SELECT id, {column} FROM {table}
+             WHERE {column} IS NOT NULL AND id > $1
+             ORDER BY id
+             LIMIT $2
+             FOR UPDATE"
+        ))
+        .bind(after)
+        .bind(ROTATION_BATCH_SIZE)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| format!("Query failed: {}", e))?;
+
+        let Some(&(last, _)) = batch.last() else { break };
+        after = last;
+
+        for (id, encrypted) in batch {
+            let (version, plaintext) = keys
+                .decrypt_versioned(&encrypted)
+                .map_err(|e| format!("{} {}: {}", table, id, e))?;
+            if version.is_some_and(|version| version != old_key_id) {
+                continue;
+            }
+            let reencrypted = keys.encrypt_with(new_key_id, &plaintext)?;
+
+            sqlx::query(&format!("UPDATE {table} SET {column} = $1, updated_at = NOW() WHERE id = $2"))
+                .bind(&reencrypted)
+                .bind(id)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| format!("Update failed: {}", e))?;
+            rotated += 1;
+        }
+    }
+
+    Ok(rotated)
 }
 
 /// Generate a new random encryption key
@@ -91,26 +348,88 @@ mod tests {
 
     #[test]
     fn test_encrypt_decrypt_roundtrip() {
-        let key = generate_key();
+        let keys = KeyRing::new(1, generate_key());
         let plaintext = b"Hello, World!";
         
-        let encrypted = encrypt(plaintext, &key).unwrap();
-        let decrypted = decrypt(&encrypted, &key).unwrap();
+        let encrypted = keys.encrypt(plaintext).unwrap();
+        let decrypted = keys.decrypt(&encrypted).unwrap();
         
+        assert_eq!(encrypted[0], 1);
         assert_eq!(plaintext.to_vec(), decrypted);
     }
 
     #[test]
     fn test_encrypt_json_roundtrip() {
-        let key = generate_key();
+        let keys = KeyRing::new(1, generate_key());
         let value = serde_json::json!({
             "username": "test",
             "password": "secret123"
         });
         
-        let encrypted = encrypt_json(&value, &key).unwrap();
-        let decrypted: serde_json::Value = decrypt_json(&encrypted, &key).unwrap();
+        let encrypted = keys.encrypt_json(&value).unwrap();
+        let decrypted: serde_json::Value = keys.decrypt_json(&encrypted).unwrap();
         
         assert_eq!(value, decrypted);
     }
+
+    #[test]
+    fn test_roundtrip_across_key_versions() {
+        let (old_key, new_key) = (generate_key(), generate_key());
+        let before = KeyRing::new(1, old_key);
+        let legacy = before.encrypt(b"twilio-auth-token").unwrap();
+
+        // After rotation the new key is current and the old one is kept for reads
+        let after = KeyRing::new(2, new_key).with_key(1, old_key);
+        let fresh = after.encrypt(b"twilio-auth-token").unwrap();
+
+        assert_eq!((legacy[0], fresh[0]), (1, 2));
+        assert_eq!(after.decrypt(&legacy).unwrap(), b"twilio-auth-token");
+        assert_eq!(after.decrypt(&fresh).unwrap(), b"twilio-auth-token");
+
+        // Re-encrypting moves data to the new version
+        let rotated = after.encrypt_with(2, &after.decrypt(&legacy).unwrap()).unwrap();
+        assert_eq!(KeyRing::new(2, new_key).decrypt(&rotated).unwrap(), b"twilio-auth-token");
+    }
+
+    #[test]
+    fn test_unversioned_ciphertext_from_before_versioning_still_decrypts() {
+        // What the key ring wrote before ciphertext carried a key version
+        let key = generate_key();
+        let legacy = encrypt(b"twilio-auth-token", &key).unwrap();
+
+        assert_eq!(KeyRing::new(1, key).decrypt(&legacy).unwrap(), b"twilio-auth-token");
+        assert_eq!(KeyRing::new(1, key).decrypt_versioned(&legacy).unwrap().0, None);
+
+        // Still readable once a newer key is current, and rotation upgrades it
+        let after = KeyRing::new(2, generate_key()).with_key(1, key);
+        assert_eq!(after.decrypt(&legacy).unwrap(), b"twilio-auth-token");
+        let rotated = after.encrypt_with(2, &after.decrypt(&legacy).unwrap()).unwrap();
+        assert_eq!(after.decrypt_versioned(&rotated).unwrap(), (Some(2), b"twilio-auth-token".to_vec()));
+
+        // A configured legacy version is used instead of 1
+        let ring = KeyRing::new(3, generate_key()).with_key(2, key).with_legacy_key_id(2);
+        assert_eq!(ring.decrypt(&legacy).unwrap(), b"twilio-auth-token");
+        assert!(KeyRing::new(1, generate_key()).decrypt(&legacy).is_err());
+    }
+
+    #[test]
+    fn test_missing_key_version_is_reported() {
+        let encrypted = KeyRing::new(7, generate_key()).encrypt(b"secret").unwrap();
+
+        assert_eq!(
+            KeyRing::new(1, generate_key()).decrypt(&encrypted),
+            Err(DecryptionError::UnknownKeyVersion(7))
+        );
+        assert_eq!(KeyRing::new(1, generate_key()).decrypt(&[]), Err(DecryptionError::TooShort));
+    }
+
+    #[test]
+    fn test_wrong_key_for_version_fails_authentication() {
+        let encrypted = KeyRing::new(1, generate_key()).encrypt(b"secret").unwrap();
+
+        assert!(matches!(
+            KeyRing::new(1, generate_key()).decrypt(&encrypted),
+            Err(DecryptionError::Failed(_))
+        ));
+    }
 }
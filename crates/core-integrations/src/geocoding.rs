@@ -0,0 +1,404 @@
+//! Geocoding - addresses to coordinates
+//!
+//! `Geocoder` is implemented by Nominatim (OpenStreetMap, no key) and the
+//! Google Geocoding API. `CachedGeocoder` wraps either one: it caches answers
+//! by normalized address, misses included, and spaces out upstream requests
+//! so a burst of saves stays within the provider's rate limit.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A point on the map
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+/// One candidate a provider returned for an address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeocodeMatch {
+    pub point: GeoPoint,
+    /// The provider's formatted address
+    pub label: String,
+    /// 0.0 - 1.0, higher is a better match
+    pub confidence: f64,
+}
+
+/// What an address resolved to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GeocodeOutcome {
+    Found { point: GeoPoint, label: String },
+    /// Several places match about equally well; nothing is picked
+    Ambiguous { candidates: Vec<GeocodeMatch> },
+    NotFound,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeocodeError {
+    Http(String),
+    /// The provider refused the request (bad key, quota, ...)
+    Provider(String),
+}
+
+impl std::fmt::Display for GeocodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeocodeError::Http(e) => write!(f, "Geocoding request failed: {}", e),
+            GeocodeError::Provider(e) => write!(f, "Geocoding provider error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GeocodeError {}
+
+/// A geocoding provider
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+    /// Candidates for `address`, best first; empty when nothing matches
+    async fn geocode(&self, address: &str) -> Result<Vec<GeocodeMatch>, GeocodeError>;
+}
+
+/// Build the provider named by `GEOCODER` ("nominatim" or "google"), if any
+pub fn geocoder_from_env() -> Option<CachedGeocoder> {
+    let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+    let (provider, interval): (Arc<dyn Geocoder>, Duration) = match env("GEOCODER")?.to_lowercase().as_str() {
+        "nominatim" => {
+            let base_url = env("NOMINATIM_URL").unwrap_or_else(|| NominatimGeocoder::PUBLIC_URL.to_string());
+            // The public server allows one request per second
+            (Arc::new(NominatimGeocoder::new(base_url)), Duration::from_secs(1))
+        }
+        "google" => match env("GOOGLE_MAPS_API_KEY") {
+            Some(key) => (Arc::new(GoogleGeocoder::new(key)), Duration::from_millis(50)),
+            None => {
+                tracing::warn!("GEOCODER=google but GOOGLE_MAPS_API_KEY is not set; geocoding is off");
+                return None;
+            }
+        },
+        other => {
+            tracing::warn!("Unknown GEOCODER '{}'; geocoding is off", other);
+            return None;
+        }
+    };
+    Some(CachedGeocoder::new(provider, interval))
+}
+
+/// Cache key for an address: case, punctuation and spacing don't matter
+pub fn normalize_address(address: &str) -> String {
+    address
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '.')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pick the answer from a provider's candidates
+///
+/// A single candidate, or a best one clearly ahead of the next, is found;
+/// close runners-up make the address ambiguous.
+pub fn resolve_matches(mut matches: Vec<GeocodeMatch>) -> GeocodeOutcome {
+    const CLEAR_LEAD: f64 = 0.15;
+
+    matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    match matches.as_slice() {
+        [] => GeocodeOutcome::NotFound,
+        [best] => GeocodeOutcome::Found { point: best.point, label: best.label.clone() },
+        [best, next, ..] if best.confidence - next.confidence >= CLEAR_LEAD => {
+            GeocodeOutcome::Found { point: best.point, label: best.label.clone() }
+        }
+        _ => {
+            matches.truncate(5);
+            GeocodeOutcome::Ambiguous { candidates: matches }
+        }
+    }
+}
+
+// ============================================================================
+// Cache and rate limit
+// ============================================================================
+
+/// A `Geocoder` behind a cache and a minimum interval between requests
+pub struct CachedGeocoder {
+    provider: Arc<dyn Geocoder>,
+    cache: Mutex<HashMap<String, GeocodeOutcome>>,
+    /// Earliest time the next upstream request may start
+    next_request: tokio::sync::Mutex<Instant>,
+    interval: Duration,
+}
+
+impl CachedGeocoder {
+    /// Entries kept before the cache starts over
+    const MAX_ENTRIES: usize = 10_000;
+
+    pub fn new(provider: Arc<dyn Geocoder>, interval: Duration) -> Self {
+        Self {
+            provider,
+            cache: Mutex::new(HashMap::new()),
+            next_request: tokio::sync::Mutex::new(Instant::now()),
+            interval,
+        }
+    }
+
+    /// Resolve `address`, asking the provider only for addresses not seen before
+    ///
+    /// Provider errors are not cached, so the address is retried next time.
+    pub async fn geocode(&self, address: &str) -> Result<GeocodeOutcome, GeocodeError> {
+        let key = normalize_address(address);
+        if key.is_empty() {
+            return Ok(GeocodeOutcome::NotFound);
+        }
+        if let Some(hit) = self.cache.lock().unwrap().get(&key) {
+            return Ok(hit.clone());
+        }
+
+        {
+            // Holding the lock while waiting queues concurrent callers
+            let mut next_request = self.next_request.lock().await;
+            tokio::time::sleep_until((*next_request).into()).await;
+            *next_request = Instant::now() + self.interval;
+        }
+        let outcome = resolve_matches(self.provider.geocode(address).await?);
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= Self::MAX_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(key, outcome.clone());
+        Ok(outcome)
+    }
+}
+
+// ============================================================================
+// Providers
+// ============================================================================
+
+/// OpenStreetMap's Nominatim, public or self-hosted
+pub struct NominatimGeocoder {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl NominatimGeocoder {
+    pub const PUBLIC_URL: &'static str = "https://nominatim.openstreetmap.org";
+
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                // Nominatim's usage policy requires an identifying User-Agent
+                .user_agent(concat!("jirsi-platform/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NominatimPlace {
+    lat: String,
+    lon: String,
+    display_name: String,
+    #[serde(default)]
+    importance: Option<f64>,
+}
+
+#[async_trait]
+impl Geocoder for NominatimGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Vec<GeocodeMatch>, GeocodeError> {
+        let response = self
+            .client
+            .get(format!("{}/search", self.base_url))
+            .query(&[("q", address), ("format", "jsonv2"), ("limit", "5")])
+            .send()
+            .await
+            .map_err(|e| GeocodeError::Http(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(GeocodeError::Provider(format!("Nominatim returned {}", response.status())));
+        }
+        let places: Vec<NominatimPlace> = response.json().await.map_err(|e| GeocodeError::Http(e.to_string()))?;
+
+        Ok(places
+            .into_iter()
+            .filter_map(|place| {
+                Some(GeocodeMatch {
+                    point: GeoPoint { lat: place.lat.parse().ok()?, lng: place.lon.parse().ok()? },
+                    label: place.display_name,
+                    confidence: place.importance.unwrap_or(0.5).clamp(0.0, 1.0),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Google Maps Geocoding API
+pub struct GoogleGeocoder {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl GoogleGeocoder {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self { client: reqwest::Client::new(), api_key: api_key.into() }
+    }
+}
+
+#[derive(Deserialize)]
+struct GoogleResponse {
+    status: String,
+    #[serde(default)]
+    error_message: Option<String>,
+    #[serde(default)]
+    results: Vec<GoogleResult>,
+}
+
+#[derive(Deserialize)]
+struct GoogleResult {
+    formatted_address: String,
+    geometry: GoogleGeometry,
+    #[serde(default)]
+    partial_match: bool,
+}
+
+#[derive(Deserialize)]
+struct GoogleGeometry {
+    location: GoogleLocation,
+    location_type: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleLocation {
+    lat: f64,
+    lng: f64,
+}
+
+#[async_trait]
+impl Geocoder for GoogleGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Vec<GeocodeMatch>, GeocodeError> {
+        let response: GoogleResponse = self
+            .client
+            .get("https://maps.googleapis.com/maps/api/geocode/json")
+            .query(&[("address", address), ("key", self.api_key.as_str())])
+            .send()
+            .await
+            .map_err(|e| GeocodeError::Http(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| GeocodeError::Http(e.to_string()))?;
+
+        match response.status.as_str() {
+            "OK" | "ZERO_RESULTS" => {}
+            status => {
+                return Err(GeocodeError::Provider(response.error_message.unwrap_or_else(|| status.to_string())));
+            }
+        }
+
+        Ok(response
+            .results
+            .into_iter()
+            .map(|result| {
+                // Rooftop hits beat interpolated ones, which beat areas
+                let precision = match result.geometry.location_type.as_str() {
+                    "ROOFTOP" => 1.0,
+                    "RANGE_INTERPOLATED" => 0.8,
+                    "GEOMETRIC_CENTER" => 0.6,
+                    _ => 0.4,
+                };
+                GeocodeMatch {
+                    point: GeoPoint { lat: result.geometry.location.lat, lng: result.geometry.location.lng },
+                    label: result.formatted_address,
+                    confidence: if result.partial_match { precision * 0.5 } else { precision },
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers from a fixed table and counts the requests it gets
+    struct MockGeocoder {
+        places: HashMap<&'static str, Vec<GeocodeMatch>>,
+        calls: AtomicUsize,
+    }
+
+    impl MockGeocoder {
+        fn new() -> Self {
+            let place = |lat, lng, label: &str, confidence| GeocodeMatch {
+                point: GeoPoint { lat, lng },
+                label: label.to_string(),
+                confidence,
+            };
+            let places = HashMap::from([
+                ("Burj Khalifa, Dubai", vec![place(25.1972, 55.2744, "Burj Khalifa, Dubai, UAE", 0.9)]),
+                (
+                    "Springfield",
+                    vec![
+                        place(39.7817, -89.6501, "Springfield, Illinois", 0.62),
+                        place(37.2090, -93.2923, "Springfield, Missouri", 0.58),
+                    ],
+                ),
+            ]);
+            Self { places, calls: AtomicUsize::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl Geocoder for MockGeocoder {
+        async fn geocode(&self, address: &str) -> Result<Vec<GeocodeMatch>, GeocodeError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.places.get(address).cloned().unwrap_or_default())
+        }
+    }
+
+    fn cached() -> (CachedGeocoder, Arc<MockGeocoder>) {
+        let mock = Arc::new(MockGeocoder::new());
+        (CachedGeocoder::new(mock.clone(), Duration::ZERO), mock)
+    }
+
+    #[tokio::test]
+    async fn test_address_is_geocoded() {
+        let (geocoder, mock) = cached();
+
+        let outcome = geocoder.geocode("Burj Khalifa, Dubai").await.unwrap();
+        assert_eq!(
+            outcome,
+            GeocodeOutcome::Found { point: GeoPoint { lat: 25.1972, lng: 55.2744 }, label: "Burj Khalifa, Dubai, UAE".to_string() }
+        );
+        assert_eq!(mock.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_skips_the_provider() {
+        let (geocoder, mock) = cached();
+
+        let first = geocoder.geocode("Burj Khalifa, Dubai").await.unwrap();
+        // Same address, typed differently
+        let second = geocoder.geocode("  burj khalifa  dubai. ").await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(mock.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_ambiguous_addresses_are_not_placed() {
+        let (geocoder, mock) = cached();
+
+        assert_eq!(geocoder.geocode("Nowhere Lane 99").await.unwrap(), GeocodeOutcome::NotFound);
+        // Misses are cached too
+        assert_eq!(geocoder.geocode("nowhere lane 99").await.unwrap(), GeocodeOutcome::NotFound);
+        assert_eq!(mock.calls.load(Ordering::SeqCst), 1);
+
+        match geocoder.geocode("Springfield").await.unwrap() {
+            GeocodeOutcome::Ambiguous { candidates } => assert_eq!(candidates.len(), 2),
+            other => panic!("expected an ambiguous result, got {:?}", other),
+        }
+        assert_eq!(geocoder.geocode("   ").await.unwrap(), GeocodeOutcome::NotFound);
+    }
+}
@@ -1,14 +1,18 @@
 //! Core Integrations - Third-party API integrations and Webhook Gateway
 //!
 //! Provides secure credential storage and webhook handling for external services
-//! like Twilio, Facebook, WhatsApp, and Email.
+//! like Twilio, Facebook, WhatsApp, and Email, plus address geocoding and a
+//! guard for requests to tenant-supplied URLs.
 
 pub mod models;
 pub mod service;
 pub mod webhook;
 pub mod providers;
 pub mod encryption;
+pub mod geocoding;
+pub mod egress;
 
 pub use models::{IntegrationConfig, Provider, ProviderStatus};
 pub use service::IntegrationService;
-pub use webhook::WebhookHandler;
+pub use webhook::{WebhookHandler, WebhookReplayStore, WhatsAppStatusWebhook};
+pub use geocoding::{CachedGeocoder, GeoPoint, GeocodeOutcome, Geocoder};
@@ -169,6 +169,16 @@ pub enum SystemEvent {
         external_id: String,
         raw_data: serde_json::Value,
     },
+    /// Delivery status callback for an outbound message
+    MessageStatusUpdated {
+        tenant_id: Uuid,
+        provider: Provider,
+        /// Provider message ID (e.g. `wamid...`)
+        external_id: String,
+        /// "sent", "delivered", "read" or "failed"
+        status: String,
+        error: Option<String>,
+    },
     /// Webhook received but not processed
     WebhookReceived {
         tenant_id: Uuid,
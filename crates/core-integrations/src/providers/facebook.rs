@@ -1,11 +1,9 @@
 //! Facebook Lead Ads webhook handler
 
 use bytes::Bytes;
-use hmac::{Hmac, Mac};
 use http::HeaderMap;
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
-use tracing::{info, warn};
+use tracing::info;
 use uuid::Uuid;
 
 use crate::models::{Provider, SystemEvent};
@@ -64,52 +62,19 @@ impl WebhookHandler for FacebookHandler {
         Provider::Facebook
     }
 
-    fn verify_signature(
-        &self,
-        payload: &[u8],
-        headers: &HeaderMap,
-        secret: &str,
-    ) -> bool {
-        // Get signature from header (X-Hub-Signature-256)
-        let signature = match headers.get("X-Hub-Signature-256") {
-            Some(sig) => match sig.to_str() {
-                Ok(s) => s.to_string(),
-                Err(_) => return false,
-            },
-            None => {
-                // Try legacy header
-                match headers.get("X-Hub-Signature") {
-                    Some(sig) => match sig.to_str() {
-                        Ok(s) => s.to_string(),
-                        Err(_) => return false,
-                    },
-                    None => {
-                        warn!("Missing X-Hub-Signature header");
-                        return false;
-                    }
-                }
-            }
-        };
-
-        // Signature format: sha256=<hex>
-        let expected_prefix = "sha256=";
-        if !signature.starts_with(expected_prefix) {
-            warn!("Invalid signature format");
-            return false;
-        }
-        let signature_hex = &signature[expected_prefix.len()..];
-
-        // Compute HMAC-SHA256
-        type HmacSha256 = Hmac<Sha256>;
-        let mut mac = match HmacSha256::new_from_slice(secret.as_bytes()) {
-            Ok(m) => m,
-            Err(_) => return false,
-        };
-        mac.update(payload);
-        let expected = hex::encode(mac.finalize().into_bytes());
-
-        // Constant-time comparison
-        signature_hex == expected
+    /// The leads' `leadgen_id`s; a retried delivery carries the same ones
+    fn event_id(&self, payload: &[u8], _headers: &HeaderMap) -> Option<String> {
+        let webhook: FacebookWebhook = serde_json::from_slice(payload).ok()?;
+        let mut ids: Vec<String> = webhook
+            .entry
+            .into_iter()
+            .flat_map(|entry| entry.changes.unwrap_or_default())
+            .filter(|change| change.field == "leadgen")
+            .filter_map(|change| change.value.leadgen_id)
+            .collect();
+        ids.sort();
+        ids.dedup();
+        (!ids.is_empty()).then(|| format!("leadgen:{}", ids.join(",")))
     }
 
     fn handle_webhook(
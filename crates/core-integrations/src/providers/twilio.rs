@@ -1,11 +1,9 @@
 //! Twilio webhook handler for SMS and Voice
 
 use bytes::Bytes;
-use hmac::{Hmac, Mac};
 use http::HeaderMap;
-use sha2::Sha256;
 use std::collections::HashMap;
-use tracing::{info, warn};
+use tracing::info;
 use uuid::Uuid;
 
 use crate::models::{Provider, SystemEvent};
@@ -33,28 +31,6 @@ impl TwilioHandler {
         
         Ok(params)
     }
-
-    /// Compute Twilio signature for validation
-    /// https://www.twilio.com/docs/usage/security#validating-requests
-    fn compute_signature(url: &str, params: &HashMap<String, String>, auth_token: &str) -> String {
-        // Sort parameters by key and concatenate
-        let mut sorted_keys: Vec<&String> = params.keys().collect();
-        sorted_keys.sort();
-        
-        let mut data = url.to_string();
-        for key in sorted_keys {
-            data.push_str(key);
-            data.push_str(params.get(key).unwrap_or(&String::new()));
-        }
-        
-        // HMAC-SHA1 (Twilio uses SHA1)
-        type HmacSha1 = hmac::Hmac<sha1::Sha1>;
-        let mut mac = HmacSha1::new_from_slice(auth_token.as_bytes())
-            .expect("HMAC can take key of any size");
-        mac.update(data.as_bytes());
-        
-        base64::encode(mac.finalize().into_bytes())
-    }
 }
 
 impl WebhookHandler for TwilioHandler {
@@ -62,42 +38,27 @@ impl WebhookHandler for TwilioHandler {
         Provider::Twilio
     }
 
-    fn verify_signature(
-        &self,
-        payload: &[u8],
-        headers: &HeaderMap,
-        secret: &str,
-    ) -> bool {
-        // Get Twilio signature from header
-        let signature = match headers.get("X-Twilio-Signature") {
-            Some(sig) => match sig.to_str() {
-                Ok(s) => s.to_string(),
-                Err(_) => return false,
-            },
-            None => {
-                warn!("Missing X-Twilio-Signature header");
-                return false;
-            }
-        };
-
-        // Parse form data to compute signature
-        let params = match Self::parse_form_data(payload) {
-            Ok(p) => p,
-            Err(_) => return false,
-        };
-
-        // Get webhook URL from headers or construct it
-        let url = headers
+    /// Twilio signs the public URL; fall back to our configured base URL
+    fn signature_url(&self, headers: &HeaderMap) -> String {
+        headers
             .get("X-Original-URL")
             .and_then(|v| v.to_str().ok())
             .map(|s| s.to_string())
-            .unwrap_or_else(|| self.base_url.clone());
+            .unwrap_or_else(|| self.base_url.clone())
+    }
 
-        // Compute expected signature
-        let expected = Self::compute_signature(&url, &params, secret);
-        
-        // Constant-time comparison
-        signature == expected
+    /// `MessageSid` or `CallSid`, with the status for status callbacks,
+    /// which Twilio sends once per status change of the same message or call
+    fn event_id(&self, payload: &[u8], _headers: &HeaderMap) -> Option<String> {
+        let params = Self::parse_form_data(payload).ok()?;
+        let (sid, status) = match params.get("MessageSid") {
+            Some(sid) => (sid, params.get("MessageStatus").or_else(|| params.get("SmsStatus"))),
+            None => (params.get("CallSid")?, params.get("CallStatus")),
+        };
+        Some(match status {
+            Some(status) => format!("{}:{}", sid, status),
+            None => sid.clone(),
+        })
     }
 
     fn handle_webhook(
@@ -174,6 +135,3 @@ impl WebhookHandler for TwilioHandler {
         }
     }
 }
-
-// Need sha1 for Twilio signature
-use sha1::Sha1;
@@ -4,26 +4,9 @@ use sqlx::PgPool;
 use tracing::{info, warn};
 use uuid::Uuid;
 
-use crate::encryption::{encrypt_json, decrypt_json, generate_webhook_secret};
+use crate::encryption::{generate_webhook_secret, KeyRing};
 use crate::models::{IntegrationConfig, Provider, ProviderCredentials, ProviderStatus};
 
-/// Master encryption key - in production, load from environment/secrets manager
-/// This should be 32 bytes for AES-256
-fn get_encryption_key() -> [u8; 32] {
-    // TODO: Load from environment variable INTEGRATION_ENCRYPTION_KEY
-    // For now, use a fixed key (CHANGE IN PRODUCTION!)
-    let key_hex = std::env::var("INTEGRATION_ENCRYPTION_KEY")
-        .unwrap_or_else(|_| "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef".to_string());
-    
-    let mut key = [0u8; 32];
-    if let Ok(bytes) = hex::decode(&key_hex) {
-        if bytes.len() >= 32 {
-            key.copy_from_slice(&bytes[..32]);
-        }
-    }
-    key
-}
-
 /// Service for managing integrations
 pub struct IntegrationService;
 
@@ -96,8 +79,9 @@ impl IntegrationService {
 
         match row {
             Some((encrypted,)) => {
-                let key = get_encryption_key();
-                let credentials: T = decrypt_json(&encrypted, &key)?;
+                let credentials: T = KeyRing::from_env()
+                    .decrypt_json(&encrypted)
+                    .map_err(|e| e.to_string())?;
                 Ok(Some(credentials))
             }
             None => Ok(None),
@@ -134,10 +118,9 @@ impl IntegrationService {
         base_webhook_url: &str,
     ) -> Result<IntegrationConfig, String> {
         let provider = credentials.provider();
-        let key = get_encryption_key();
         
-        // Encrypt credentials
-        let encrypted = encrypt_json(&credentials, &key)?;
+        // Encrypt credentials with the current master key
+        let encrypted = KeyRing::from_env().encrypt_json(&credentials)?;
         
         // Generate webhook secret if new
         let webhook_secret = generate_webhook_secret();
@@ -1,7 +1,15 @@
 //! Webhook handler trait and dispatcher
 
+use base64::Engine;
 use bytes::Bytes;
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
 use http::HeaderMap;
+use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::models::{Provider, SystemEvent};
@@ -11,13 +19,44 @@ pub trait WebhookHandler: Send + Sync {
     /// Get the provider type
     fn provider(&self) -> Provider;
 
-    /// Verify the webhook signature
-    fn verify_signature(
+    /// Signature scheme used by this provider
+    fn signature_scheme(&self) -> SignatureScheme {
+        SignatureScheme::for_provider(self.provider())
+    }
+
+    /// Public URL the provider posted to (only signed by Twilio)
+    fn signature_url(&self, headers: &HeaderMap) -> String {
+        headers
+            .get("X-Original-URL")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Verify the webhook signature against the raw request body
+    fn verify(
         &self,
-        payload: &[u8],
         headers: &HeaderMap,
+        raw_body: &[u8],
         secret: &str,
-    ) -> bool;
+    ) -> Result<(), SignatureError> {
+        self.signature_scheme()
+            .verify(&self.signature_url(headers), headers, raw_body, secret)
+    }
+
+    /// The provider's own id for the event(s) in a delivery, if it sends one
+    fn event_id(&self, _payload: &[u8], _headers: &HeaderMap) -> Option<String> {
+        None
+    }
+
+    /// Key a redelivery of the same event shares: the provider's event id,
+    /// or a hash of the raw body when there isn't one
+    fn replay_key(&self, payload: &[u8], headers: &HeaderMap) -> String {
+        match self.event_id(payload, headers) {
+            Some(id) => format!("id:{}", id),
+            None => format!("sha256:{}", hex::encode(Sha256::digest(payload))),
+        }
+    }
 
     /// Parse the webhook payload and convert to system event
     fn handle_webhook(
@@ -54,6 +93,213 @@ impl std::fmt::Display for WebhookError {
 
 impl std::error::Error for WebhookError {}
 
+// ============================================================================
+// Signature verification
+// ============================================================================
+
+type HmacSha1 = Hmac<Sha1>;
+type HmacSha256 = Hmac<Sha256>;
+
+/// Maximum age of a Stripe signature timestamp
+pub const STRIPE_TOLERANCE_SECS: i64 = 300;
+
+/// How a provider signs its webhook requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// `X-Twilio-Signature`: base64 HMAC-SHA1 of the URL followed by the
+    /// form params sorted by key
+    TwilioX,
+    /// `X-Hub-Signature-256: sha256=<hex>` HMAC-SHA256 of the body (Facebook, WhatsApp)
+    FacebookSha256,
+    /// `Stripe-Signature: t=<ts>,v1=<hex>` HMAC-SHA256 of `"{ts}.{body}"`
+    StripeV1,
+    /// `X-Signature: <hex>` HMAC-SHA256 of the body
+    HmacSha256Hex,
+}
+
+impl SignatureScheme {
+    pub fn for_provider(provider: Provider) -> Self {
+        match provider {
+            Provider::Twilio => SignatureScheme::TwilioX,
+            Provider::Facebook | Provider::WhatsApp => SignatureScheme::FacebookSha256,
+            Provider::Email => SignatureScheme::HmacSha256Hex,
+        }
+    }
+
+    /// Header carrying the signature
+    pub fn header(&self) -> &'static str {
+        match self {
+            SignatureScheme::TwilioX => "X-Twilio-Signature",
+            SignatureScheme::FacebookSha256 => "X-Hub-Signature-256",
+            SignatureScheme::StripeV1 => "Stripe-Signature",
+            SignatureScheme::HmacSha256Hex => "X-Signature",
+        }
+    }
+
+    /// Verify a request; `url` is only used by `TwilioX`
+    pub fn verify(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        raw_body: &[u8],
+        secret: &str,
+    ) -> Result<(), SignatureError> {
+        self.verify_at(url, headers, raw_body, secret, chrono::Utc::now().timestamp())
+    }
+
+    fn verify_at(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        raw_body: &[u8],
+        secret: &str,
+        now: i64,
+    ) -> Result<(), SignatureError> {
+        let header = self.header();
+        let signature = headers
+            .get(header)
+            .ok_or(SignatureError::MissingSignature { header })?
+            .to_str()
+            .map_err(|_| SignatureError::Malformed("signature header is not ASCII".to_string()))?
+            .trim();
+        if signature.is_empty() {
+            return Err(SignatureError::MissingSignature { header });
+        }
+
+        match self {
+            SignatureScheme::TwilioX => {
+                let expected = base64::engine::general_purpose::STANDARD.decode(signature)
+                    .map_err(|_| SignatureError::Malformed("signature is not base64".to_string()))?;
+                let mut mac = HmacSha1::new_from_slice(secret.as_bytes())
+                    .expect("HMAC can take key of any size");
+                mac.update(twilio_signing_string(url, raw_body).as_bytes());
+                mac.verify_slice(&expected).map_err(|_| SignatureError::InvalidSignature)
+            }
+            SignatureScheme::FacebookSha256 => {
+                let hex_sig = signature
+                    .strip_prefix("sha256=")
+                    .ok_or_else(|| SignatureError::Malformed("expected sha256=<hex>".to_string()))?;
+                verify_hmac_sha256_hex(secret, &[raw_body], hex_sig)
+            }
+            SignatureScheme::StripeV1 => {
+                let mut timestamp = None;
+                let mut candidates = Vec::new();
+                for part in signature.split(',') {
+                    match part.trim().split_once('=') {
+                        Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                        Some(("v1", v)) => candidates.push(v),
+                        _ => {}
+                    }
+                }
+                let timestamp = timestamp
+                    .ok_or_else(|| SignatureError::Malformed("missing t= timestamp".to_string()))?;
+                if candidates.is_empty() {
+                    return Err(SignatureError::MissingSignature { header });
+                }
+                if (now - timestamp).abs() > STRIPE_TOLERANCE_SECS {
+                    return Err(SignatureError::TimestampOutOfTolerance);
+                }
+
+                let prefix = format!("{}.", timestamp);
+                // Stripe sends one v1 per active secret during rotation
+                if candidates
+                    .iter()
+                    .any(|c| verify_hmac_sha256_hex(secret, &[prefix.as_bytes(), raw_body], c).is_ok())
+                {
+                    Ok(())
+                } else {
+                    Err(SignatureError::InvalidSignature)
+                }
+            }
+            SignatureScheme::HmacSha256Hex => {
+                let hex_sig = signature.strip_prefix("sha256=").unwrap_or(signature);
+                verify_hmac_sha256_hex(secret, &[raw_body], hex_sig)
+            }
+        }
+    }
+}
+
+/// Why a webhook signature was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The signature header is absent or empty
+    MissingSignature { header: &'static str },
+    /// A signature was present but does not match
+    InvalidSignature,
+    /// The signature header could not be parsed
+    Malformed(String),
+    /// Signed timestamp is too old or in the future (replay protection)
+    TimestampOutOfTolerance,
+}
+
+impl std::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::MissingSignature { header } => write!(f, "Missing signature header: {}", header),
+            SignatureError::InvalidSignature => write!(f, "Invalid webhook signature"),
+            SignatureError::Malformed(msg) => write!(f, "Malformed signature: {}", msg),
+            SignatureError::TimestampOutOfTolerance => write!(f, "Signature timestamp outside tolerance"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+impl From<SignatureError> for WebhookError {
+    fn from(err: SignatureError) -> Self {
+        match err {
+            SignatureError::InvalidSignature => WebhookError::InvalidSignature,
+            other => WebhookError::ProviderError(other.to_string()),
+        }
+    }
+}
+
+/// Constant-time check of a hex HMAC-SHA256 over the concatenated `parts`
+fn verify_hmac_sha256_hex(secret: &str, parts: &[&[u8]], hex_sig: &str) -> Result<(), SignatureError> {
+    let expected = hex::decode(hex_sig)
+        .map_err(|_| SignatureError::Malformed("signature is not hex".to_string()))?;
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take key of any size");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(&expected).map_err(|_| SignatureError::InvalidSignature)
+}
+
+/// URL followed by each form param key and value, sorted by key
+/// https://www.twilio.com/docs/usage/security#validating-requests
+fn twilio_signing_string(url: &str, raw_body: &[u8]) -> String {
+    let mut params: Vec<(String, String)> = url::form_urlencoded::parse(raw_body).into_owned().collect();
+    params.sort();
+
+    let mut data = url.to_string();
+    for (key, value) in params {
+        data.push_str(&key);
+        data.push_str(&value);
+    }
+    data
+}
+
+// ============================================================================
+// Outbound signing
+// ============================================================================
+
+/// Header carrying the signature on webhooks we send to tenant endpoints
+pub const OUTBOUND_SIGNATURE_HEADER: &str = "X-Jirsi-Signature";
+
+/// Signature for a webhook we send: `t=<ts>,v1=<hex>`, the HMAC-SHA256 of
+/// `"{ts}.{body}"`
+///
+/// Same format as Stripe's, so receivers can check it the way
+/// `SignatureScheme::StripeV1` does, timestamp tolerance included.
+pub fn sign_outbound(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()))
+}
+
 /// Webhook dispatcher that routes to appropriate handler
 pub struct WebhookDispatcher {
     handlers: Vec<Box<dyn WebhookHandler>>,
@@ -82,3 +328,515 @@ impl Default for WebhookDispatcher {
         Self::new()
     }
 }
+
+// ============================================================================
+// Replay protection
+// ============================================================================
+
+/// How long a processed event is remembered (default for `WebhookReplayStore`)
+///
+/// Facebook keeps retrying a failed delivery for up to 36 hours.
+pub const WEBHOOK_REPLAY_TTL_HOURS: i64 = 72;
+
+/// Events already processed, so provider redeliveries run only once
+#[derive(Clone)]
+pub struct WebhookReplayStore {
+    pool: PgPool,
+    ttl: Duration,
+}
+
+impl WebhookReplayStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, ttl: Duration::hours(WEBHOOK_REPLAY_TTL_HOURS) }
+    }
+
+    pub fn with_ttl(pool: PgPool, ttl: Duration) -> Self {
+        Self { pool, ttl }
+    }
+
+    /// Claim `key` for processing; `false` if it was already claimed and
+    /// hasn't expired, i.e. the delivery is a duplicate
+    pub async fn claim(&self, tenant_id: Uuid, provider: Provider, key: &str) -> Result<bool, sqlx::Error> {
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO processed_webhook_events (tenant_id, provider, event_key, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, provider, event_key) DO UPDATE
+            SET processed_at = NOW(), expires_at = EXCLUDED.expires_at
+            WHERE processed_webhook_events.expires_at <= NOW()
+            RETURNING event_key
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(provider.as_str())
+        .bind(key)
+        .bind(Utc::now() + self.ttl)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(claimed.is_some())
+    }
+
+    /// Give up a claim after processing failed, so the provider's retry runs
+    pub async fn release(&self, tenant_id: Uuid, provider: Provider, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(r#"DELETE FROM processed_webhook_events WHERE tenant_id = $1 AND provider = $2 AND event_key = $3"#)
+            .bind(tenant_id)
+            .bind(provider.as_str())
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Forget expired events; returns how many were removed
+    pub async fn purge_expired(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM processed_webhook_events WHERE expires_at <= NOW()"#)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Purge expired events every `period`, forever
+    pub async fn run_purge(self, period: std::time::Duration) {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            match self.purge_expired().await {
+                Ok(removed) if removed > 0 => info!(removed, "Purged expired webhook replay keys"),
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "Failed to purge webhook replay keys"),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// WhatsApp delivery status callbacks
+// ============================================================================
+
+/// Status change reported by the provider for one outbound message
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageStatusUpdate {
+    pub provider_message_id: String,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusCallback {
+    #[serde(default)]
+    entry: Vec<StatusEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusEntry {
+    #[serde(default)]
+    changes: Vec<StatusChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusChange {
+    value: StatusValue,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusValue {
+    #[serde(default)]
+    statuses: Vec<StatusItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusItem {
+    id: String,
+    status: String,
+    #[serde(default)]
+    errors: Vec<StatusErrorItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusErrorItem {
+    code: Option<i64>,
+    title: Option<String>,
+}
+
+/// Extract message status updates from a Meta WhatsApp webhook payload
+pub fn parse_whatsapp_statuses(payload: &[u8]) -> Result<Vec<MessageStatusUpdate>, WebhookError> {
+    let callback: StatusCallback = serde_json::from_slice(payload)
+        .map_err(|e| WebhookError::ParseError(format!("Invalid JSON: {}", e)))?;
+
+    Ok(callback
+        .entry
+        .into_iter()
+        .flat_map(|entry| entry.changes)
+        .flat_map(|change| change.value.statuses)
+        .map(|item| MessageStatusUpdate {
+            provider_message_id: item.id,
+            status: item.status,
+            error: item.errors.first().map(|e| {
+                format!("{}: {}", e.code.unwrap_or_default(), e.title.as_deref().unwrap_or("Unknown error"))
+            }),
+        })
+        .collect())
+}
+
+/// Statuses a message may be in for `status` to apply
+///
+/// Callbacks can arrive out of order, so statuses only move forward
+/// (sent -> delivered -> read); a late "delivered" never overwrites "read".
+pub fn status_predecessors(status: &str) -> &'static [&'static str] {
+    match status {
+        "sent" => &["pending"],
+        "delivered" => &["pending", "sent"],
+        "read" => &["pending", "sent", "delivered"],
+        "failed" => &["pending", "sent"],
+        _ => &[],
+    }
+}
+
+/// Webhook handler for WhatsApp delivery status callbacks
+pub struct WhatsAppStatusWebhook {
+    pool: PgPool,
+}
+
+impl WhatsAppStatusWebhook {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Apply status updates to `whatsapp_messages`; returns how many rows changed
+    pub async fn apply_updates(
+        &self,
+        tenant_id: Uuid,
+        updates: &[MessageStatusUpdate],
+    ) -> Result<u64, sqlx::Error> {
+        let mut applied = 0;
+
+        for update in updates {
+            let predecessors: Vec<String> = status_predecessors(&update.status)
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            if predecessors.is_empty() {
+                warn!(status = %update.status, "Ignoring unknown WhatsApp status");
+                continue;
+            }
+
+            let result = sqlx::query(
+                r#"
+                UPDATE whatsapp_messages
+                SET status = $3, last_error = COALESCE($4, last_error), updated_at = NOW()
+                WHERE tenant_id = $1 AND provider_message_id = $2 AND status = ANY($5)
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&update.provider_message_id)
+            .bind(&update.status)
+            .bind(&update.error)
+            .bind(&predecessors)
+            .execute(&self.pool)
+            .await?;
+
+            applied += result.rows_affected();
+        }
+
+        Ok(applied)
+    }
+}
+
+impl WebhookHandler for WhatsAppStatusWebhook {
+    fn provider(&self) -> Provider {
+        Provider::WhatsApp
+    }
+
+    /// Each status carries the message id; the same message reaches each
+    /// status once
+    fn event_id(&self, payload: &[u8], _headers: &HeaderMap) -> Option<String> {
+        let mut ids: Vec<String> = parse_whatsapp_statuses(payload)
+            .ok()?
+            .into_iter()
+            .map(|update| format!("{}:{}", update.provider_message_id, update.status))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        (!ids.is_empty()).then(|| ids.join(","))
+    }
+
+    fn handle_webhook(
+        &self,
+        tenant_id: Uuid,
+        payload: Bytes,
+        _headers: &HeaderMap,
+    ) -> Result<Vec<SystemEvent>, WebhookError> {
+        let updates = parse_whatsapp_statuses(&payload)?;
+
+        info!(tenant_id = %tenant_id, count = updates.len(), "Processing WhatsApp status callback");
+
+        Ok(updates
+            .into_iter()
+            .map(|update| SystemEvent::MessageStatusUpdated {
+                tenant_id,
+                provider: Provider::WhatsApp,
+                external_id: update.provider_message_id,
+                status: update.status,
+                error: update.error,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_twilio_signature_vector() {
+        // Example from Twilio's request validation docs
+        let url = "https://mycompany.com/myapp.php?foo=1&bar=2";
+        let body = b"CallSid=CA1234567890ABCDE&Caller=%2B12349013030&Digits=1234&From=%2B12349013030&To=%2B18005551212";
+        let valid = headers(&[("X-Twilio-Signature", "0/KCTR6DLpKmkAf8muzZqo1nDgQ=")]);
+
+        assert_eq!(SignatureScheme::TwilioX.verify(url, &valid, body, "12345"), Ok(()));
+        assert_eq!(
+            SignatureScheme::TwilioX.verify(url, &valid, body, "wrong"),
+            Err(SignatureError::InvalidSignature)
+        );
+        // The URL is part of the signed data
+        assert_eq!(
+            SignatureScheme::TwilioX.verify("https://mycompany.com/other", &valid, body, "12345"),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_facebook_signature_vector() {
+        let body = br#"{"object":"page","entry":[]}"#;
+        let valid = headers(&[(
+            "X-Hub-Signature-256",
+            "sha256=5d0e5788f775eb342b54c3614d555983d0245e3125a99cee765575fc6f2446c3",
+        )]);
+
+        assert_eq!(SignatureScheme::FacebookSha256.verify("", &valid, body, "fb_app_secret"), Ok(()));
+        assert_eq!(
+            SignatureScheme::FacebookSha256.verify("", &valid, b"{}", "fb_app_secret"),
+            Err(SignatureError::InvalidSignature)
+        );
+        assert!(matches!(
+            SignatureScheme::FacebookSha256.verify("", &headers(&[("X-Hub-Signature-256", "md5=abc")]), body, "fb_app_secret"),
+            Err(SignatureError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_stripe_signature_vector() {
+        let body = br#"{"id":"evt_1","type":"payment_intent.succeeded"}"#;
+        let valid = headers(&[(
+            "Stripe-Signature",
+            "t=1700000000,v1=deadbeef,v1=001ce3ef73e456cedaab328328720d3ad59defb8bbd0f1518f46c04ad4ac0bb7",
+        )]);
+
+        assert_eq!(SignatureScheme::StripeV1.verify_at("", &valid, body, "whsec_test", 1700000100), Ok(()));
+        assert_eq!(
+            SignatureScheme::StripeV1.verify_at("", &valid, body, "whsec_other", 1700000100),
+            Err(SignatureError::InvalidSignature)
+        );
+        assert_eq!(
+            SignatureScheme::StripeV1.verify_at("", &valid, body, "whsec_test", 1700000000 + STRIPE_TOLERANCE_SECS + 1),
+            Err(SignatureError::TimestampOutOfTolerance)
+        );
+    }
+
+    #[test]
+    fn test_outbound_signature_verifies_as_stripe_v1() {
+        let body = br#"{"id":"evt_1","type":"payment_intent.succeeded"}"#;
+        let signature = sign_outbound("whsec_test", 1700000000, body);
+        assert_eq!(
+            signature,
+            "t=1700000000,v1=001ce3ef73e456cedaab328328720d3ad59defb8bbd0f1518f46c04ad4ac0bb7"
+        );
+
+        let signed = headers(&[("Stripe-Signature", &signature)]);
+        assert_eq!(SignatureScheme::StripeV1.verify_at("", &signed, body, "whsec_test", 1700000060), Ok(()));
+        assert_eq!(
+            SignatureScheme::StripeV1.verify_at("", &signed, b"{}", "whsec_test", 1700000060),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256_hex_signature_vector() {
+        let body = br#"{"event":"ping"}"#;
+        let sig = "7799ad8e8f5d2230414eda9ccaafabe02225247ad219fa4243c27fe8323244be";
+
+        assert_eq!(SignatureScheme::HmacSha256Hex.verify("", &headers(&[("X-Signature", sig)]), body, "shared_secret"), Ok(()));
+        assert_eq!(
+            SignatureScheme::HmacSha256Hex.verify("", &headers(&[("X-Signature", &sig.replace('7', "8"))]), body, "shared_secret"),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_missing_signature_is_distinguished_from_invalid() {
+        for scheme in [
+            SignatureScheme::TwilioX,
+            SignatureScheme::FacebookSha256,
+            SignatureScheme::StripeV1,
+            SignatureScheme::HmacSha256Hex,
+        ] {
+            assert_eq!(
+                scheme.verify("", &HeaderMap::new(), b"{}", "secret"),
+                Err(SignatureError::MissingSignature { header: scheme.header() })
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_verify_dispatches_on_provider() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let handler = WhatsAppStatusWebhook::new(pool);
+        let body = br#"{"object":"page","entry":[]}"#;
+        let valid = headers(&[(
+            "X-Hub-Signature-256",
+            "sha256=5d0e5788f775eb342b54c3614d555983d0245e3125a99cee765575fc6f2446c3",
+        )]);
+
+        assert_eq!(handler.signature_scheme(), SignatureScheme::FacebookSha256);
+        assert_eq!(handler.verify(&valid, body, "fb_app_secret"), Ok(()));
+        assert_eq!(handler.verify(&valid, body, "other"), Err(SignatureError::InvalidSignature));
+    }
+
+    fn callback(id: &str, status: &str) -> Vec<u8> {
+        serde_json::json!({
+            "object": "whatsapp_business_account",
+            "entry": [{
+                "id": "102290129340398",
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "messaging_product": "whatsapp",
+                        "statuses": [{
+                            "id": id,
+                            "status": status,
+                            "timestamp": "1700000000",
+                            "recipient_id": "971500000000"
+                        }]
+                    }
+                }]
+            }]
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Apply updates the same way the UPDATE ... WHERE status = ANY(..) does
+    fn apply(current: &str, updates: &[MessageStatusUpdate]) -> String {
+        updates.iter().fold(current.to_string(), |status, update| {
+            if status_predecessors(&update.status).contains(&status.as_str()) {
+                update.status.clone()
+            } else {
+                status
+            }
+        })
+    }
+
+    #[test]
+    fn test_delivered_then_read_sequence() {
+        let mut status = "sent".to_string();
+
+        let delivered = parse_whatsapp_statuses(&callback("wamid.1", "delivered")).unwrap();
+        assert_eq!(delivered[0].provider_message_id, "wamid.1");
+        status = apply(&status, &delivered);
+        assert_eq!(status, "delivered");
+
+        let read = parse_whatsapp_statuses(&callback("wamid.1", "read")).unwrap();
+        status = apply(&status, &read);
+        assert_eq!(status, "read");
+
+        // A late duplicate "delivered" does not move the message backwards
+        status = apply(&status, &delivered);
+        assert_eq!(status, "read");
+    }
+
+    #[test]
+    fn test_failed_callback_carries_error() {
+        let payload = serde_json::json!({
+            "entry": [{ "changes": [{ "value": { "statuses": [{
+                "id": "wamid.2",
+                "status": "failed",
+                "errors": [{ "code": 131026, "title": "Message undeliverable" }]
+            }]}}]}]
+        });
+
+        let updates = parse_whatsapp_statuses(payload.to_string().as_bytes()).unwrap();
+
+        assert_eq!(updates[0].error.as_deref(), Some("131026: Message undeliverable"));
+        assert_eq!(apply("sent", &updates), "failed");
+        assert_eq!(apply("read", &updates), "read");
+    }
+
+    #[tokio::test]
+    async fn test_handle_webhook_emits_status_events() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let handler = WhatsAppStatusWebhook::new(pool);
+        let tenant_id = Uuid::new_v4();
+
+        let events = handler
+            .handle_webhook(tenant_id, Bytes::from(callback("wamid.3", "read")), &HeaderMap::new())
+            .unwrap();
+
+        assert!(matches!(
+            &events[..],
+            [SystemEvent::MessageStatusUpdated { external_id, status, .. }] if external_id == "wamid.3" && status == "read"
+        ));
+    }
+
+    fn lead(leadgen_id: &str) -> Vec<u8> {
+        serde_json::json!({
+            "object": "page",
+            "entry": [{
+                "id": "153125381133",
+                "time": 1700000000,
+                "changes": [{ "field": "leadgen", "value": { "form_id": "444", "leadgen_id": leadgen_id, "page_id": "153125381133" } }]
+            }]
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_replay_key_uses_the_provider_event_id() {
+        let facebook = crate::providers::facebook::FacebookHandler::new("verify".to_string());
+        let first = facebook.replay_key(&lead("9001"), &HeaderMap::new());
+        assert_eq!(first, "id:leadgen:9001");
+
+        // A redelivery is the same event even when re-serialized differently
+        let redelivered = String::from_utf8(lead("9001")).unwrap().replace(',', ", ");
+        assert_eq!(facebook.replay_key(redelivered.as_bytes(), &HeaderMap::new()), first);
+        assert_ne!(facebook.replay_key(&lead("9002"), &HeaderMap::new()), first);
+
+        let twilio = crate::providers::twilio::TwilioHandler::new("https://api.example.com".to_string());
+        let sms = b"MessageSid=SM123&From=%2B15550001&To=%2B15550002&Body=hi";
+        assert_eq!(twilio.replay_key(sms, &HeaderMap::new()), "id:SM123");
+        // Each status callback for one call is its own event
+        let ringing = twilio.replay_key(b"CallSid=CA1&CallStatus=ringing", &HeaderMap::new());
+        let completed = twilio.replay_key(b"CallSid=CA1&CallStatus=completed", &HeaderMap::new());
+        assert_ne!(ringing, completed);
+    }
+
+    #[test]
+    fn test_replay_key_falls_back_to_a_body_hash() {
+        let facebook = crate::providers::facebook::FacebookHandler::new("verify".to_string());
+        let body = br#"{"object":"instagram","entry":[]}"#;
+
+        let key = facebook.replay_key(body, &HeaderMap::new());
+        assert!(key.starts_with("sha256:"));
+        assert_eq!(facebook.replay_key(body, &HeaderMap::new()), key);
+        assert_ne!(facebook.replay_key(br#"{"object":"instagram","entry":[{}]}"#, &HeaderMap::new()), key);
+    }
+}
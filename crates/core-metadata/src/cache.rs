@@ -1,10 +1,20 @@
 //! Metadata cache - in-memory caching for frequently accessed metadata
+//!
+//! Each map is a [`LoadingCache`]: concurrent misses for the same key share
+//! one database load, and entries past their soft TTL are served stale while
+//! a single background refresh runs.
 
 use core_models::{EntityType, FieldDef, ViewDef, AppDef};
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
+use crate::MetadataError;
+
 /// Cache key for tenant-scoped items
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 struct TenantKey {
@@ -21,131 +31,317 @@ impl TenantKey {
     }
 }
 
+/// Entry lifetimes for a [`LoadingCache`]
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    /// After this an entry is still served, but a background refresh starts
+    pub soft_ttl: Duration,
+    /// After this an entry is never served; callers wait for a fresh load
+    pub hard_ttl: Duration,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            soft_ttl: Duration::from_secs(60),
+            hard_ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+struct CacheEntry<V> {
+    value: V,
+    loaded_at: Instant,
+}
+
+/// Key/value cache with single-flight loading and stale-while-revalidate
+pub struct LoadingCache<K, V> {
+    inner: Arc<LoadingCacheInner<K, V>>,
+}
+
+struct LoadingCacheInner<K, V> {
+    policy: CachePolicy,
+    entries: RwLock<HashMap<K, CacheEntry<V>>>,
+    /// One cell per key with a load in progress; waiters share its result
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K, V> LoadingCache<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    pub fn new(policy: CachePolicy) -> Self {
+        Self {
+            inner: Arc::new(LoadingCacheInner {
+                policy,
+                entries: RwLock::new(HashMap::new()),
+                in_flight: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Cached value unless it is past the hard TTL
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.inner.lookup(key).map(|(value, _)| value)
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.inner.store(key, value);
+    }
+
+    /// Drop `key`; a load already in flight for it will not store its result
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut in_flight = self.inner.in_flight.lock().unwrap();
+        in_flight.remove(key);
+        self.inner.entries.write().unwrap().remove(key).map(|e| e.value)
+    }
+
+    pub fn retain(&self, mut keep: impl FnMut(&K, &V) -> bool) {
+        self.inner.entries.write().unwrap().retain(|k, e| keep(k, &e.value));
+    }
+
+    pub fn clear(&self) {
+        let mut in_flight = self.inner.in_flight.lock().unwrap();
+        in_flight.clear();
+        self.inner.entries.write().unwrap().clear();
+    }
+
+    /// Return the cached value, or run `loader` once for all concurrent callers.
+    ///
+    /// A value past the soft TTL is returned immediately and refreshed in the
+    /// background; a failed refresh keeps serving it until the hard TTL.
+    pub async fn get_or_load<F, Fut>(&self, key: K, loader: F) -> Result<V, MetadataError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<V, MetadataError>> + Send + 'static,
+    {
+        if let Some((value, stale)) = self.inner.lookup(&key) {
+            if stale && !self.inner.is_loading(&key) {
+                let inner = self.inner.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = inner.load(key, &loader).await {
+                        tracing::warn!(error = %e, "Background metadata refresh failed");
+                    }
+                });
+            }
+            return Ok(value);
+        }
+
+        self.inner.load(key, &loader).await
+    }
+}
+
+impl<K, V> LoadingCacheInner<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    /// `(value, past soft TTL)` for entries within the hard TTL
+    fn lookup(&self, key: &K) -> Option<(V, bool)> {
+        let entries = self.entries.read().unwrap();
+        let entry = entries.get(key)?;
+        let age = entry.loaded_at.elapsed();
+        (age < self.policy.hard_ttl).then(|| (entry.value.clone(), age >= self.policy.soft_ttl))
+    }
+
+    fn store(&self, key: K, value: V) {
+        let entry = CacheEntry { value, loaded_at: Instant::now() };
+        self.entries.write().unwrap().insert(key, entry);
+    }
+
+    fn is_loading(&self, key: &K) -> bool {
+        self.in_flight.lock().unwrap().contains_key(key)
+    }
+
+    async fn load<F, Fut>(&self, key: K, loader: &F) -> Result<V, MetadataError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<V, MetadataError>>,
+    {
+        let cell = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            // A load may have finished between the caller's miss and taking the lock
+            if let Some((value, false)) = self.lookup(&key) {
+                return Ok(value);
+            }
+            in_flight.entry(key.clone()).or_default().clone()
+        };
+
+        // If the load fails, the next waiter on this cell retries it
+        let result = cell.get_or_try_init(loader).await.cloned();
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        // Only the first finisher stores; later waiters see the cell already gone
+        if in_flight.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+            if let Ok(value) = &result {
+                self.store(key.clone(), value.clone());
+            }
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+}
+
 /// In-memory metadata cache
-/// 
+///
 /// Caches EntityTypes, FieldDefs, and ViewDefs per tenant.
 /// Cache is invalidated on metadata changes.
 pub struct MetadataCache {
-    entity_types: RwLock<HashMap<TenantKey, EntityType>>,
-    entity_types_by_id: RwLock<HashMap<Uuid, EntityType>>,
-    fields: RwLock<HashMap<Uuid, Vec<FieldDef>>>, // entity_type_id -> fields
-    views: RwLock<HashMap<Uuid, Vec<ViewDef>>>,   // entity_type_id -> views
-    apps: RwLock<HashMap<Uuid, Vec<AppDef>>>,     // tenant_id -> apps
+    entity_types: LoadingCache<TenantKey, EntityType>,
+    entity_types_by_id: LoadingCache<Uuid, EntityType>,
+    fields: LoadingCache<Uuid, Vec<FieldDef>>, // entity_type_id -> fields
+    views: LoadingCache<Uuid, Vec<ViewDef>>,   // entity_type_id -> views
+    apps: LoadingCache<Uuid, Vec<AppDef>>,     // tenant_id -> apps
 }
 
 impl MetadataCache {
     pub fn new() -> Self {
+        Self::with_policy(CachePolicy::default())
+    }
+
+    pub fn with_policy(policy: CachePolicy) -> Self {
         Self {
-            entity_types: RwLock::new(HashMap::new()),
-            entity_types_by_id: RwLock::new(HashMap::new()),
-            fields: RwLock::new(HashMap::new()),
-            views: RwLock::new(HashMap::new()),
-            apps: RwLock::new(HashMap::new()),
+            entity_types: LoadingCache::new(policy),
+            entity_types_by_id: LoadingCache::new(policy),
+            fields: LoadingCache::new(policy),
+            views: LoadingCache::new(policy),
+            apps: LoadingCache::new(policy),
         }
     }
 
     // ============ EntityType ============
 
     pub fn get_entity_type(&self, tenant_id: Uuid, name: &str) -> Option<EntityType> {
-        let cache = self.entity_types.read().unwrap();
-        cache.get(&TenantKey::new(tenant_id, name)).cloned()
+        self.entity_types.get(&TenantKey::new(tenant_id, name))
     }
 
     pub fn get_entity_type_by_id(&self, id: Uuid) -> Option<EntityType> {
-        let cache = self.entity_types_by_id.read().unwrap();
-        cache.get(&id).cloned()
+        self.entity_types_by_id.get(&id)
+    }
+
+    pub async fn load_entity_type<F, Fut>(
+        &self,
+        tenant_id: Uuid,
+        name: &str,
+        loader: F,
+    ) -> Result<EntityType, MetadataError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<EntityType, MetadataError>> + Send + 'static,
+    {
+        let entity = self.entity_types.get_or_load(TenantKey::new(tenant_id, name), loader).await?;
+        self.entity_types_by_id.insert(entity.id, entity.clone());
+        Ok(entity)
+    }
+
+    pub async fn load_entity_type_by_id<F, Fut>(&self, id: Uuid, loader: F) -> Result<EntityType, MetadataError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<EntityType, MetadataError>> + Send + 'static,
+    {
+        let entity = self.entity_types_by_id.get_or_load(id, loader).await?;
+        self.entity_types
+            .insert(TenantKey::new(entity.tenant_id, &entity.name), entity.clone());
+        Ok(entity)
     }
 
     pub fn set_entity_type(&self, entity: EntityType) {
         let key = TenantKey::new(entity.tenant_id, &entity.name);
-        {
-            let mut cache = self.entity_types.write().unwrap();
-            cache.insert(key, entity.clone());
-        }
-        {
-            let mut cache = self.entity_types_by_id.write().unwrap();
-            cache.insert(entity.id, entity);
-        }
+        self.entity_types.insert(key, entity.clone());
+        self.entity_types_by_id.insert(entity.id, entity);
     }
 
     pub fn invalidate_entity_type(&self, tenant_id: Uuid, name: &str) {
         let key = TenantKey::new(tenant_id, name);
-        let mut cache = self.entity_types.write().unwrap();
-        if let Some(entity) = cache.remove(&key) {
-            let mut by_id = self.entity_types_by_id.write().unwrap();
-            by_id.remove(&entity.id);
+        if let Some(entity) = self.entity_types.remove(&key) {
+            self.entity_types_by_id.remove(&entity.id);
         }
     }
 
     // ============ FieldDef ============
 
     pub fn get_fields(&self, entity_type_id: Uuid) -> Option<Vec<FieldDef>> {
-        let cache = self.fields.read().unwrap();
-        cache.get(&entity_type_id).cloned()
+        self.fields.get(&entity_type_id)
+    }
+
+    pub async fn load_fields<F, Fut>(&self, entity_type_id: Uuid, loader: F) -> Result<Vec<FieldDef>, MetadataError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<FieldDef>, MetadataError>> + Send + 'static,
+    {
+        self.fields.get_or_load(entity_type_id, loader).await
     }
 
     pub fn set_fields(&self, entity_type_id: Uuid, fields: Vec<FieldDef>) {
-        let mut cache = self.fields.write().unwrap();
-        cache.insert(entity_type_id, fields);
+        self.fields.insert(entity_type_id, fields);
     }
 
     pub fn invalidate_fields(&self, entity_type_id: Uuid) {
-        let mut cache = self.fields.write().unwrap();
-        cache.remove(&entity_type_id);
+        self.fields.remove(&entity_type_id);
     }
 
     // ============ ViewDef ============
 
     pub fn get_views(&self, entity_type_id: Uuid) -> Option<Vec<ViewDef>> {
-        let cache = self.views.read().unwrap();
-        cache.get(&entity_type_id).cloned()
+        self.views.get(&entity_type_id)
+    }
+
+    pub async fn load_views<F, Fut>(&self, entity_type_id: Uuid, loader: F) -> Result<Vec<ViewDef>, MetadataError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<ViewDef>, MetadataError>> + Send + 'static,
+    {
+        self.views.get_or_load(entity_type_id, loader).await
     }
 
     pub fn set_views(&self, entity_type_id: Uuid, views: Vec<ViewDef>) {
-        let mut cache = self.views.write().unwrap();
-        cache.insert(entity_type_id, views);
+        self.views.insert(entity_type_id, views);
     }
 
     pub fn invalidate_views(&self, entity_type_id: Uuid) {
-        let mut cache = self.views.write().unwrap();
-        cache.remove(&entity_type_id);
+        self.views.remove(&entity_type_id);
     }
 
     // ============ Apps ============
 
     pub fn get_apps(&self, tenant_id: Uuid) -> Option<Vec<AppDef>> {
-        let cache = self.apps.read().unwrap();
-        cache.get(&tenant_id).cloned()
+        self.apps.get(&tenant_id)
+    }
+
+    pub async fn load_apps<F, Fut>(&self, tenant_id: Uuid, loader: F) -> Result<Vec<AppDef>, MetadataError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<AppDef>, MetadataError>> + Send + 'static,
+    {
+        self.apps.get_or_load(tenant_id, loader).await
     }
 
     pub fn set_apps(&self, tenant_id: Uuid, apps: Vec<AppDef>) {
-        let mut cache = self.apps.write().unwrap();
-        cache.insert(tenant_id, apps);
+        self.apps.insert(tenant_id, apps);
     }
 
     pub fn invalidate_apps(&self, tenant_id: Uuid) {
-        let mut cache = self.apps.write().unwrap();
-        cache.remove(&tenant_id);
+        self.apps.remove(&tenant_id);
     }
 
     // ============ Global ============
 
     pub fn invalidate_tenant(&self, tenant_id: Uuid) {
         // Clear all caches for a tenant
-        {
-            let mut cache = self.entity_types.write().unwrap();
-            cache.retain(|k, _| k.tenant_id != tenant_id);
-        }
+        self.entity_types.retain(|k, _| k.tenant_id != tenant_id);
+        self.entity_types_by_id.retain(|_, e| e.tenant_id != tenant_id);
         self.invalidate_apps(tenant_id);
         // Note: fields/views are keyed by entity_type_id, would need reverse lookup
     }
 
     pub fn clear(&self) {
-        self.entity_types.write().unwrap().clear();
-        self.entity_types_by_id.write().unwrap().clear();
-        self.fields.write().unwrap().clear();
-        self.views.write().unwrap().clear();
-        self.apps.write().unwrap().clear();
+        self.entity_types.clear();
+        self.entity_types_by_id.clear();
+        self.fields.clear();
+        self.views.clear();
+        self.apps.clear();
     }
 }
 
@@ -154,3 +350,86 @@ impl Default for MetadataCache {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Loader that counts calls and returns the call number after `delay`
+    fn counting_loader(
+        calls: Arc<AtomicUsize>,
+        delay: Duration,
+    ) -> impl Fn() -> std::pin::Pin<Box<dyn Future<Output = Result<usize, MetadataError>> + Send>> + Send + Sync + 'static
+    {
+        move || {
+            let calls = calls.clone();
+            Box::pin(async move {
+                let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(delay).await;
+                Ok(call)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_cold_gets_load_once() {
+        let cache = Arc::new(LoadingCache::<String, usize>::new(CachePolicy::default()));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let cache = cache.clone();
+                let loader = counting_loader(calls.clone(), Duration::from_millis(50));
+                tokio::spawn(async move { cache.get_or_load("contact".to_string(), loader).await })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap().unwrap(), 1);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get(&"contact".to_string()), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_stale_entry_served_while_refreshing() {
+        let policy = CachePolicy {
+            soft_ttl: Duration::from_millis(20),
+            hard_ttl: Duration::from_secs(60),
+        };
+        let cache = LoadingCache::<u8, usize>::new(policy);
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        assert_eq!(cache.get_or_load(1, counting_loader(calls.clone(), Duration::ZERO)).await.unwrap(), 1);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        // Stale: both readers get the old value without waiting; one refresh starts
+        let slow = Duration::from_millis(50);
+        assert_eq!(cache.get_or_load(1, counting_loader(calls.clone(), slow)).await.unwrap(), 1);
+        assert_eq!(cache.get_or_load(1, counting_loader(calls.clone(), slow)).await.unwrap(), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.get_or_load(1, counting_loader(calls.clone(), slow)).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_hard_expired_entry_is_reloaded_and_errors_not_cached() {
+        let policy = CachePolicy {
+            soft_ttl: Duration::from_millis(5),
+            hard_ttl: Duration::from_millis(10),
+        };
+        let cache = LoadingCache::<u8, usize>::new(policy);
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let failing = || async { Err::<usize, _>(MetadataError::ViewNotFound("x".to_string())) };
+        assert!(cache.get_or_load(1, failing).await.is_err());
+        assert_eq!(cache.get(&1), None);
+
+        assert_eq!(cache.get_or_load(1, counting_loader(calls.clone(), Duration::ZERO)).await.unwrap(), 1);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get_or_load(1, counting_loader(calls.clone(), Duration::ZERO)).await.unwrap(), 2);
+    }
+}
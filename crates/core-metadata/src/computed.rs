@@ -0,0 +1,150 @@
+//! Computed fields - values derived on read from other fields via the logic engine
+
+use core_models::{EvalContext, FieldDef, FieldType, LogicExpr};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::MetadataError;
+
+/// Computed fields of an entity type, ordered so each comes after any
+/// computed field its expression reads
+pub fn evaluation_order(fields: &[FieldDef]) -> Result<Vec<(&str, &LogicExpr)>, MetadataError> {
+    let computed: Vec<(&str, &LogicExpr)> = fields
+        .iter()
+        .filter_map(|f| match &f.field_type {
+            FieldType::Computed { expression } => Some((f.name.as_str(), expression)),
+            _ => None,
+        })
+        .collect();
+    let names: HashSet<&str> = computed.iter().map(|(name, _)| *name).collect();
+
+    let mut ordered = Vec::with_capacity(computed.len());
+    let mut done: HashSet<&str> = HashSet::new();
+
+    // Repeatedly take every field whose computed dependencies are resolved,
+    // keeping definition order among independent fields
+    while ordered.len() < computed.len() {
+        let ready: Vec<(&str, &LogicExpr)> = computed
+            .iter()
+            .filter(|(name, expr)| {
+                !done.contains(name)
+                    && expr
+                        .referenced_fields()
+                        .iter()
+                        .all(|dep| !names.contains(dep) || done.contains(dep))
+            })
+            .copied()
+            .collect();
+
+        if ready.is_empty() {
+            let mut cycle: Vec<&str> = computed
+                .iter()
+                .map(|(name, _)| *name)
+                .filter(|name| !done.contains(name))
+                .collect();
+            cycle.sort_unstable();
+            return Err(MetadataError::Validation(format!(
+                "Computed fields have a circular dependency: {}",
+                cycle.join(", ")
+            )));
+        }
+
+        for (name, expr) in ready {
+            done.insert(name);
+            ordered.push((name, expr));
+        }
+    }
+
+    Ok(ordered)
+}
+
+/// Evaluate every computed field and write the results into `record`,
+/// replacing any stored value
+pub fn apply_computed_fields(fields: &[FieldDef], record: &mut Value) -> Result<(), MetadataError> {
+    let order = evaluation_order(fields)?;
+    let Some(object) = record.as_object_mut() else {
+        return Ok(());
+    };
+    if order.is_empty() {
+        return Ok(());
+    }
+
+    let mut data: HashMap<String, Value> = object.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    for (name, expr) in order {
+        let value = expr.evaluate(&EvalContext::with_data(&data));
+        data.insert(name.to_string(), value.clone());
+        object.insert(name.to_string(), value);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn field(name: &str, field_type: FieldType) -> FieldDef {
+        FieldDef::new(Uuid::nil(), Uuid::nil(), name, name, field_type)
+    }
+
+    fn computed(name: &str, expression: LogicExpr) -> FieldDef {
+        field(name, FieldType::Computed { expression })
+    }
+
+    fn var(name: &str) -> LogicExpr {
+        LogicExpr::Field(name.to_string())
+    }
+
+    #[test]
+    fn test_full_name_concatenation() {
+        let fields = vec![
+            field("first_name", FieldType::Text),
+            field("last_name", FieldType::Text),
+            computed(
+                "full_name",
+                LogicExpr::Concat(vec![var("first_name"), LogicExpr::Literal(json!(" ")), var("last_name")]),
+            ),
+        ];
+        let mut record = json!({ "first_name": "Layla", "last_name": "Haddad", "full_name": "stale" });
+
+        apply_computed_fields(&fields, &mut record).unwrap();
+
+        assert_eq!(record["full_name"], json!("Layla Haddad"));
+    }
+
+    #[test]
+    fn test_numeric_derivation_follows_dependencies() {
+        // `total` reads `subtotal`, which is itself computed and defined later
+        let fields = vec![
+            computed(
+                "total",
+                LogicExpr::Subtract(Box::new(var("subtotal")), Box::new(var("discount"))),
+            ),
+            computed("subtotal", LogicExpr::Multiply(vec![var("price"), var("quantity")])),
+            field("price", FieldType::Money { currency_code: None }),
+            field("quantity", FieldType::Number { decimals: None }),
+            field("discount", FieldType::Money { currency_code: None }),
+        ];
+        let mut record = json!({ "price": 120.5, "quantity": 4, "discount": 82 });
+
+        apply_computed_fields(&fields, &mut record).unwrap();
+
+        assert_eq!(record["subtotal"], json!(482));
+        assert_eq!(record["total"], json!(400));
+        let order: Vec<&str> = evaluation_order(&fields).unwrap().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(order, vec!["subtotal", "total"]);
+    }
+
+    #[test]
+    fn test_circular_dependency_is_rejected() {
+        let fields = vec![
+            computed("a", LogicExpr::Add(vec![var("b"), LogicExpr::Literal(json!(1))])),
+            computed("b", LogicExpr::Add(vec![var("a"), LogicExpr::Literal(json!(1))])),
+        ];
+
+        let err = apply_computed_fields(&fields, &mut json!({})).unwrap_err();
+        assert!(matches!(err, MetadataError::Validation(msg) if msg.contains("a, b")));
+    }
+}
@@ -17,6 +17,9 @@ pub enum MetadataError {
     #[error("View not found: {0}")]
     ViewNotFound(String),
 
+    #[error("Schema version {version} not found for entity type {entity}")]
+    SchemaVersionNotFound { entity: String, version: i32 },
+
     #[error("Association not found: {0}")]
     AssociationNotFound(String),
 
@@ -7,6 +7,10 @@ pub mod repository;
 pub mod cache;
 pub mod service;
 pub mod error;
+pub mod versioning;
+pub mod computed;
 
 pub use error::MetadataError;
 pub use service::MetadataService;
+pub use computed::apply_computed_fields;
+pub use versioning::{FieldChange, SchemaDiff, SchemaSnapshot};
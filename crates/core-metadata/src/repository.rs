@@ -4,6 +4,7 @@ use core_models::{EntityType, FieldDef, ViewDef, AppDef, EntityFlags, FieldType,
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::versioning::SchemaSnapshot;
 use crate::MetadataError;
 
 /// Repository for metadata CRUD operations
@@ -29,7 +30,7 @@ impl MetadataRepository {
             SELECT 
                 id, tenant_id, app_id, module_id, name, label, label_plural,
                 icon, description, flags,
-                default_sort_field, default_sort_desc, soft_delete,
+                default_sort_field, default_sort_desc, soft_delete, display_field,
                 created_at, updated_at
             FROM entity_types
             WHERE tenant_id = $1 AND name = $2
@@ -54,7 +55,7 @@ impl MetadataRepository {
             SELECT 
                 id, tenant_id, app_id, module_id, name, label, label_plural,
                 icon, description, flags,
-                default_sort_field, default_sort_desc, soft_delete,
+                default_sort_field, default_sort_desc, soft_delete, display_field,
                 created_at, updated_at
             FROM entity_types
             WHERE tenant_id = $1 AND id = $2
@@ -80,7 +81,7 @@ impl MetadataRepository {
                 SELECT 
                     id, tenant_id, app_id, module_id, name, label, label_plural,
                     icon, description, flags,
-                    default_sort_field, default_sort_desc, soft_delete,
+                    default_sort_field, default_sort_desc, soft_delete, display_field,
                     created_at, updated_at
                 FROM entity_types
                 WHERE tenant_id = $1 AND app_id = $2
@@ -97,7 +98,7 @@ impl MetadataRepository {
                 SELECT 
                     id, tenant_id, app_id, module_id, name, label, label_plural,
                     icon, description, flags,
-                    default_sort_field, default_sort_desc, soft_delete,
+                    default_sort_field, default_sort_desc, soft_delete, display_field,
                     created_at, updated_at
                 FROM entity_types
                 WHERE tenant_id = $1
@@ -203,6 +204,92 @@ impl MetadataRepository {
         }
     }
 
+    // ============ Schema Versions ============
+
+    /// Store `entity_type` and `fields` as the entity type's next schema version
+    pub async fn insert_schema_version(
+        &self,
+        entity_type: &EntityType,
+        fields: &[FieldDef],
+    ) -> Result<i32, MetadataError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Serialize concurrent bumps for the same entity type
+        sqlx::query("SELECT id FROM entity_types WHERE id = $1 FOR UPDATE")
+            .bind(entity_type.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let version: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO metadata_versions (tenant_id, entity_type_id, version, snapshot)
+            SELECT $1, $2, COALESCE(MAX(version), 0) + 1, $3
+            FROM metadata_versions
+            WHERE entity_type_id = $2
+            RETURNING version
+            "#,
+        )
+        .bind(entity_type.tenant_id)
+        .bind(entity_type.id)
+        .bind(serde_json::json!({ "entity_type": entity_type, "fields": fields }))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(version)
+    }
+
+    /// Latest schema version, or 0 if the entity type has never been versioned
+    pub async fn latest_schema_version(
+        &self,
+        tenant_id: Uuid,
+        entity_type_id: Uuid,
+    ) -> Result<i32, MetadataError> {
+        let version: Option<i32> = sqlx::query_scalar(
+            "SELECT MAX(version) FROM metadata_versions WHERE tenant_id = $1 AND entity_type_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(entity_type_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(version.unwrap_or(0))
+    }
+
+    pub async fn get_schema_snapshot(
+        &self,
+        tenant_id: Uuid,
+        entity_type_id: Uuid,
+        version: i32,
+    ) -> Result<Option<SchemaSnapshot>, MetadataError> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            r#"
+            SELECT version, snapshot
+            FROM metadata_versions
+            WHERE tenant_id = $1 AND entity_type_id = $2 AND version = $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(entity_type_id)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let snapshot: serde_json::Value = row.try_get("snapshot")?;
+        let invalid = |e: serde_json::Error| MetadataError::Validation(format!("Corrupt schema snapshot: {}", e));
+        Ok(Some(SchemaSnapshot {
+            version: row.try_get("version")?,
+            entity_type: serde_json::from_value(snapshot["entity_type"].clone()).map_err(invalid)?,
+            fields: serde_json::from_value(snapshot["fields"].clone()).map_err(invalid)?,
+        }))
+    }
+
     // ============ Apps ============
 
     pub async fn list_apps(&self, tenant_id: Uuid) -> Result<Vec<AppDef>, MetadataError> {
@@ -247,6 +334,7 @@ fn entity_type_from_row(row: &sqlx::postgres::PgRow) -> Result<EntityType, Metad
         default_sort_field: row.try_get("default_sort_field")?,
         default_sort_desc: row.try_get("default_sort_desc")?,
         soft_delete: row.try_get("soft_delete")?,
+        display_field: row.try_get("display_field")?,
         created_at: row.try_get("created_at")?,
         updated_at: row.try_get("updated_at")?,
     })
@@ -257,6 +345,7 @@ fn field_def_from_row(row: &sqlx::postgres::PgRow) -> Result<FieldDef, MetadataE
     
     // Get field_type as string - it might be JSON or simple string
     let field_type_str: String = row.try_get("field_type")?;
+    let options: Option<serde_json::Value> = row.try_get("options")?;
     
     // Try parsing as JSON first (new format: {"type": "Select", ...})
     let field_type: FieldType = serde_json::from_str(&field_type_str)
@@ -283,13 +372,30 @@ fn field_def_from_row(row: &sqlx::postgres::PgRow) -> Result<FieldDef, MetadataE
                 "attachment" | "file" | "file_array" => FieldType::Attachment,
                 "score" => FieldType::Score { max_value: Some(100) },
                 "json" => FieldType::Json,
+                // Geocoding targets are in `options`: {"lat_field": "lat", "lng_field": "lng"}
+                "location" | "address" => {
+                    let option = |key: &str| {
+                        options.as_ref().and_then(|o| o.get(key)).and_then(|v| v.as_str()).map(String::from)
+                    };
+                    FieldType::Location {
+                        show_map: options.as_ref().and_then(|o| o.get("show_map")).and_then(|v| v.as_bool()).unwrap_or(false),
+                        lat_field: option("lat_field"),
+                        lng_field: option("lng_field"),
+                    }
+                }
+                // Type config is stored in `options`: {"expression": {...}}
+                "computed" => options
+                    .as_ref()
+                    .and_then(|o| o.get("expression"))
+                    .and_then(|e| serde_json::from_value(e.clone()).ok())
+                    .map(|expression| FieldType::Computed { expression })
+                    .unwrap_or(FieldType::Text),
                 _ => FieldType::Text,
             }
         });
     
     // Get optional JSON fields
     let validation: Option<serde_json::Value> = row.try_get("validation")?;
-    let options: Option<serde_json::Value> = row.try_get("options")?;
     let ui_hints: Option<serde_json::Value> = row.try_get("ui_hints")?;
     
     Ok(FieldDef {
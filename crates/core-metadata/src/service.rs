@@ -6,7 +6,9 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::cache::MetadataCache;
+use crate::computed::apply_computed_fields;
 use crate::repository::MetadataRepository;
+use crate::versioning::SchemaDiff;
 use crate::MetadataError;
 
 /// High-level service for accessing metadata with caching
@@ -37,15 +39,15 @@ impl MetadataService {
         tenant_id: Uuid,
         name: &str,
     ) -> Result<EntityType, MetadataError> {
-        // Check cache first
-        if let Some(entity) = self.cache.get_entity_type(tenant_id, name) {
-            return Ok(entity);
-        }
-
-        // Fetch from database
-        let entity = self.repo.get_entity_type_by_name(tenant_id, name).await?;
-        self.cache.set_entity_type(entity.clone());
-        Ok(entity)
+        let repo = self.repo.clone();
+        let name_owned = name.to_string();
+        self.cache
+            .load_entity_type(tenant_id, name, move || {
+                let repo = repo.clone();
+                let name = name_owned.clone();
+                async move { repo.get_entity_type_by_name(tenant_id, &name).await }
+            })
+            .await
     }
 
     /// Get an EntityType by ID, using cache
@@ -54,15 +56,13 @@ impl MetadataService {
         tenant_id: Uuid,
         id: Uuid,
     ) -> Result<EntityType, MetadataError> {
-        // Check cache first
-        if let Some(entity) = self.cache.get_entity_type_by_id(id) {
-            return Ok(entity);
-        }
-
-        // Fetch from database
-        let entity = self.repo.get_entity_type_by_id(tenant_id, id).await?;
-        self.cache.set_entity_type(entity.clone());
-        Ok(entity)
+        let repo = self.repo.clone();
+        self.cache
+            .load_entity_type_by_id(id, move || {
+                let repo = repo.clone();
+                async move { repo.get_entity_type_by_id(tenant_id, id).await }
+            })
+            .await
     }
 
     /// List all EntityTypes for a tenant, optionally filtered by app
@@ -85,15 +85,13 @@ impl MetadataService {
         tenant_id: Uuid,
         entity_type_id: Uuid,
     ) -> Result<Vec<FieldDef>, MetadataError> {
-        // Check cache
-        if let Some(fields) = self.cache.get_fields(entity_type_id) {
-            return Ok(fields);
-        }
-
-        // Fetch from database
-        let fields = self.repo.get_fields_for_entity(tenant_id, entity_type_id).await?;
-        self.cache.set_fields(entity_type_id, fields.clone());
-        Ok(fields)
+        let repo = self.repo.clone();
+        self.cache
+            .load_fields(entity_type_id, move || {
+                let repo = repo.clone();
+                async move { repo.get_fields_for_entity(tenant_id, entity_type_id).await }
+            })
+            .await
     }
 
     /// Get fields for an EntityType by name
@@ -112,15 +110,13 @@ impl MetadataService {
         tenant_id: Uuid,
         entity_type_id: Uuid,
     ) -> Result<Vec<ViewDef>, MetadataError> {
-        // Check cache
-        if let Some(views) = self.cache.get_views(entity_type_id) {
-            return Ok(views);
-        }
-
-        // Fetch from database
-        let views = self.repo.get_views_for_entity(tenant_id, entity_type_id).await?;
-        self.cache.set_views(entity_type_id, views.clone());
-        Ok(views)
+        let repo = self.repo.clone();
+        self.cache
+            .load_views(entity_type_id, move || {
+                let repo = repo.clone();
+                async move { repo.get_views_for_entity(tenant_id, entity_type_id).await }
+            })
+            .await
     }
 
     /// Get the default view for an EntityType
@@ -140,15 +136,13 @@ impl MetadataService {
 
     /// List all apps for a tenant
     pub async fn list_apps(&self, tenant_id: Uuid) -> Result<Vec<AppDef>, MetadataError> {
-        // Check cache
-        if let Some(apps) = self.cache.get_apps(tenant_id) {
-            return Ok(apps);
-        }
-
-        // Fetch from database
-        let apps = self.repo.list_apps(tenant_id).await?;
-        self.cache.set_apps(tenant_id, apps.clone());
-        Ok(apps)
+        let repo = self.repo.clone();
+        self.cache
+            .load_apps(tenant_id, move || {
+                let repo = repo.clone();
+                async move { repo.list_apps(tenant_id).await }
+            })
+            .await
     }
 
     /// Get complete metadata for an entity: EntityType + Fields + Views
@@ -168,6 +162,77 @@ impl MetadataService {
         })
     }
 
+    // ============ Computed fields ============
+
+    /// Fill in `record`'s computed fields from its stored values
+    pub async fn evaluate_computed_fields(
+        &self,
+        entity_type: &EntityType,
+        record: &mut serde_json::Value,
+    ) -> Result<(), MetadataError> {
+        let fields = self.get_fields(entity_type.tenant_id, entity_type.id).await?;
+        apply_computed_fields(&fields, record)
+    }
+
+    // ============ Schema versions ============
+
+    /// Record an EntityType/FieldDef change: drop cached metadata and
+    /// snapshot the current schema as the next version, which is returned
+    pub async fn schema_changed(
+        &self,
+        tenant_id: Uuid,
+        entity_name: &str,
+    ) -> Result<i32, MetadataError> {
+        self.invalidate_entity(tenant_id, entity_name);
+        let entity = self.repo.get_entity_type_by_name(tenant_id, entity_name).await?;
+        self.invalidate_fields(entity.id);
+
+        let fields = self.repo.get_fields_for_entity(tenant_id, entity.id).await?;
+        let version = self.repo.insert_schema_version(&entity, &fields).await?;
+
+        tracing::info!(
+            tenant_id = %tenant_id,
+            entity_type = %entity_name,
+            version,
+            "Recorded schema version"
+        );
+        Ok(version)
+    }
+
+    /// Current schema version of an EntityType (0 if never changed)
+    pub async fn schema_version(
+        &self,
+        tenant_id: Uuid,
+        entity_name: &str,
+    ) -> Result<i32, MetadataError> {
+        let entity = self.get_entity_type(tenant_id, entity_name).await?;
+        self.repo.latest_schema_version(tenant_id, entity.id).await
+    }
+
+    /// Fields added, removed, and changed between two schema versions
+    pub async fn diff_schema(
+        &self,
+        tenant_id: Uuid,
+        entity_name: &str,
+        from_version: i32,
+        to_version: i32,
+    ) -> Result<SchemaDiff, MetadataError> {
+        let entity = self.get_entity_type(tenant_id, entity_name).await?;
+        let load = |version: i32| async move {
+            self.repo
+                .get_schema_snapshot(tenant_id, entity.id, version)
+                .await?
+                .ok_or_else(|| MetadataError::SchemaVersionNotFound {
+                    entity: entity_name.to_string(),
+                    version,
+                })
+        };
+
+        let from = load(from_version).await?;
+        let to = load(to_version).await?;
+        Ok(from.diff(&to))
+    }
+
     // ============ Cache invalidation ============
 
     pub fn invalidate_entity(&self, tenant_id: Uuid, entity_name: &str) {
@@ -194,3 +259,71 @@ pub struct EntityMetadata {
     pub fields: Vec<FieldDef>,
     pub views: Vec<ViewDef>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::get_test_pool;
+
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_adding_field_bumps_schema_version() {
+        let pool = get_test_pool().await;
+        let tenant_id = Uuid::new_v4();
+        let entity_type_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, 'Schema Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("schema-{}", tenant_id.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural)
+             VALUES ($1, $2, 'crm', 'contact', 'Contact', 'Contacts')",
+        )
+        .bind(entity_type_id)
+        .bind(tenant_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let add_field = |name: &'static str| {
+            sqlx::query(
+                "INSERT INTO field_defs (id, tenant_id, entity_type_id, name, label, field_type)
+                 VALUES (gen_random_uuid(), $1, $2, $3, $3, 'text')",
+            )
+            .bind(tenant_id)
+            .bind(entity_type_id)
+            .bind(name)
+            .execute(&pool)
+        };
+
+        let service = MetadataService::new(pool.clone());
+        add_field("first_name").await.unwrap();
+        assert_eq!(service.schema_version(tenant_id, "contact").await.unwrap(), 0);
+        assert_eq!(service.schema_changed(tenant_id, "contact").await.unwrap(), 1);
+
+        // Warm the cache so the bump has to invalidate it
+        assert_eq!(service.get_fields(tenant_id, entity_type_id).await.unwrap().len(), 1);
+        add_field("phone").await.unwrap();
+        assert_eq!(service.schema_changed(tenant_id, "contact").await.unwrap(), 2);
+        assert_eq!(service.schema_version(tenant_id, "contact").await.unwrap(), 2);
+        assert_eq!(service.get_fields(tenant_id, entity_type_id).await.unwrap().len(), 2);
+
+        let diff = service.diff_schema(tenant_id, "contact", 1, 2).await.unwrap();
+        assert_eq!(diff.added.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["phone"]);
+        assert!(diff.removed.is_empty() && diff.changed.is_empty() && !diff.entity_type_changed);
+
+        assert!(matches!(
+            service.diff_schema(tenant_id, "contact", 1, 3).await,
+            Err(MetadataError::SchemaVersionNotFound { version: 3, .. })
+        ));
+
+        for sql in [
+            "DELETE FROM field_defs WHERE entity_type_id = $1",
+            "DELETE FROM entity_types WHERE id = $1",
+        ] {
+            sqlx::query(sql).bind(entity_type_id).execute(&pool).await.unwrap();
+        }
+        sqlx::query("DELETE FROM tenants WHERE id = $1").bind(tenant_id).execute(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+//! Schema versioning - snapshots of an entity type's schema and diffs between them
+
+use core_models::{EntityType, FieldDef};
+use serde::{Deserialize, Serialize};
+
+/// An entity type and its fields as they were at one schema version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub version: i32,
+    pub entity_type: EntityType,
+    pub fields: Vec<FieldDef>,
+}
+
+/// A field present in both versions whose definition differs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub name: String,
+    pub before: FieldDef,
+    pub after: FieldDef,
+}
+
+/// Field-level differences between two schema versions, matched by field name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    pub from_version: i32,
+    pub to_version: i32,
+    pub added: Vec<FieldDef>,
+    pub removed: Vec<FieldDef>,
+    pub changed: Vec<FieldChange>,
+    /// The entity type's own definition (label, flags, ...) changed
+    pub entity_type_changed: bool,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty() && !self.entity_type_changed
+    }
+}
+
+impl SchemaSnapshot {
+    /// Diff `self` (the older version) against `to`
+    pub fn diff(&self, to: &SchemaSnapshot) -> SchemaDiff {
+        let added = to
+            .fields
+            .iter()
+            .filter(|f| !self.fields.iter().any(|old| old.name == f.name))
+            .cloned()
+            .collect();
+        let removed = self
+            .fields
+            .iter()
+            .filter(|f| !to.fields.iter().any(|new| new.name == f.name))
+            .cloned()
+            .collect();
+        let changed = self
+            .fields
+            .iter()
+            .filter_map(|before| {
+                let after = to.fields.iter().find(|f| f.name == before.name)?;
+                (definition(before) != definition(after)).then(|| FieldChange {
+                    name: before.name.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                })
+            })
+            .collect();
+
+        SchemaDiff {
+            from_version: self.version,
+            to_version: to.version,
+            added,
+            removed,
+            changed,
+            entity_type_changed: definition(&self.entity_type) != definition(&to.entity_type),
+        }
+    }
+}
+
+/// Serialized form without timestamps, which change on every save
+fn definition<T: Serialize>(value: &T) -> serde_json::Value {
+    let mut json = serde_json::to_value(value).unwrap_or_default();
+    if let Some(map) = json.as_object_mut() {
+        for key in ["created_at", "updated_at", "createdAt", "updatedAt"] {
+            map.remove(key);
+        }
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::FieldType;
+    use uuid::Uuid;
+
+    fn snapshot(version: i32, entity_type: &EntityType, fields: Vec<FieldDef>) -> SchemaSnapshot {
+        SchemaSnapshot { version, entity_type: entity_type.clone(), fields }
+    }
+
+    fn field(entity_type: &EntityType, name: &str, field_type: FieldType) -> FieldDef {
+        FieldDef::new(entity_type.tenant_id, entity_type.id, name, name, field_type)
+    }
+
+    #[test]
+    fn test_diff_reports_only_added_field() {
+        let contact = EntityType::new(Uuid::new_v4(), "crm", "contact", "Contact");
+        let name = field(&contact, "first_name", FieldType::Text);
+        let email = field(&contact, "email", FieldType::Email);
+        let v1 = snapshot(1, &contact, vec![name.clone(), email.clone()]);
+
+        // Re-saving bumps timestamps, which must not count as a change
+        let mut touched = email.clone();
+        touched.updated_at += chrono::Duration::minutes(5);
+        let phone = field(&contact, "phone", FieldType::Phone);
+        let v2 = snapshot(2, &contact, vec![name, touched, phone.clone()]);
+
+        let diff = v1.diff(&v2);
+
+        assert_eq!((diff.from_version, diff.to_version), (1, 2));
+        assert_eq!(diff.added.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["phone"]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed.is_empty());
+        assert!(!diff.entity_type_changed);
+    }
+
+    #[test]
+    fn test_diff_reports_removed_and_changed_fields() {
+        let contact = EntityType::new(Uuid::new_v4(), "crm", "contact", "Contact");
+        let email = field(&contact, "email", FieldType::Email);
+        let fax = field(&contact, "fax", FieldType::Phone);
+        let v1 = snapshot(1, &contact, vec![email.clone(), fax]);
+
+        let mut renamed = contact.clone();
+        renamed.label = "Person".to_string();
+        let mut required_email = email.clone();
+        required_email.is_required = true;
+        let v2 = snapshot(2, &renamed, vec![required_email]);
+
+        let diff = v1.diff(&v2);
+
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "fax");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "email");
+        assert!(!diff.changed[0].before.is_required && diff.changed[0].after.is_required);
+        assert!(diff.entity_type_changed);
+        assert!(!diff.is_empty());
+        assert!(v2.diff(&v2).is_empty());
+    }
+}
@@ -32,6 +32,9 @@ pub struct EntityType {
     pub default_sort_desc: bool,
     /// Soft delete support
     pub soft_delete: bool,
+    /// Field whose value labels a record in lookups; `None` for the built-in label
+    #[serde(default)]
+    pub display_field: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -90,6 +93,7 @@ impl EntityType {
             default_sort_field: None,
             default_sort_desc: false,
             soft_delete: true,
+            display_field: None,
             created_at: now,
             updated_at: now,
         }
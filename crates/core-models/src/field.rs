@@ -16,6 +16,7 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 // Import Antigravity layers
+use crate::logic::LogicExpr;
 use crate::metadata::{AiMetadata, LayoutConfig, MergeStrategy};
 use crate::validation::ValidationRule;
 
@@ -101,6 +102,8 @@ pub enum FieldType {
         target_field: String, 
         operation: String,  // sum, count, avg, min, max
     },
+    /// Derived on read from other fields via the logic engine; never stored
+    Computed { expression: LogicExpr },
     
     // ==================
     // Jirsi Enhanced Types
@@ -121,8 +124,15 @@ pub enum FieldType {
     ColorPicker,
     /// JsonLogic formula for calculated fields
     JsonLogic { formula: String },
-    /// Location/address with optional map display
-    Location { show_map: bool },
+    /// Location/address with optional map display. With `lat_field` and
+    /// `lng_field` set, saving an address geocodes it into those fields.
+    Location {
+        show_map: bool,
+        #[serde(default)]
+        lat_field: Option<String>,
+        #[serde(default)]
+        lng_field: Option<String>,
+    },
     /// Progress indicator (0-100)
     Progress { max_value: i32 },
     /// Star rating (1-5 stars)
@@ -414,4 +424,9 @@ impl FieldDef {
         self.is_system = true;
         self
     }
+
+    /// Computed fields are derived on read and never accept writes
+    pub fn is_computed(&self) -> bool {
+        matches!(self.field_type, FieldType::Computed { .. })
+    }
 }
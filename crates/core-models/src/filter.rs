@@ -0,0 +1,143 @@
+//! Filter Expressions
+//!
+//! Record filters as built by the frontend filter builder: conditions on
+//! fields, combined into nested AND / OR groups. The backend translates them
+//! to SQL, so both sides agree on what a filter matches.
+//!
+//! ```json
+//! {"and": [
+//!     {"field": "stage", "op": "eq", "value": "qualified"},
+//!     {"or": [
+//!         {"field": "amount", "op": "gt", "value": 10000},
+//!         {"field": "tags", "op": "contains", "value": "vip"}
+//!     ]}
+//! ]}
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Deepest group nesting accepted from clients
+pub const MAX_FILTER_DEPTH: usize = 8;
+
+/// A filter: a single condition or an AND / OR group of filters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterExpr {
+    And { and: Vec<FilterExpr> },
+    Or { or: Vec<FilterExpr> },
+    Condition(FilterCondition),
+}
+
+/// `field <op> value`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterCondition {
+    pub field: String,
+    pub op: FilterOp,
+    /// Unused by `is_empty` / `is_true` style operators; a two-element
+    /// array for `between`, an array for `in`
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+/// Condition operators; the aliases are the filter builder's names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    #[serde(alias = "equals")]
+    Eq,
+    #[serde(alias = "not_equals")]
+    Neq,
+    Contains,
+    NotContains,
+    #[serde(alias = "after")]
+    Gt,
+    Gte,
+    #[serde(alias = "before")]
+    Lt,
+    Lte,
+    In,
+    NotIn,
+    Between,
+    IsEmpty,
+    IsNotEmpty,
+    IsTrue,
+    IsFalse,
+}
+
+impl FilterOp {
+    /// Whether the operator compares against `value`
+    pub fn takes_value(&self) -> bool {
+        !matches!(self, Self::IsEmpty | Self::IsNotEmpty | Self::IsTrue | Self::IsFalse)
+    }
+}
+
+impl FilterExpr {
+    pub fn condition(field: impl Into<String>, op: FilterOp, value: serde_json::Value) -> Self {
+        Self::Condition(FilterCondition { field: field.into(), op, value })
+    }
+
+    /// Group nesting depth; a lone condition is 0
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::And { and: children } | Self::Or { or: children } => {
+                1 + children.iter().map(Self::depth).max().unwrap_or(0)
+            }
+            Self::Condition(_) => 0,
+        }
+    }
+
+    /// Every condition in the expression, depth first
+    pub fn conditions(&self) -> Vec<&FilterCondition> {
+        match self {
+            Self::And { and: children } | Self::Or { or: children } => {
+                children.iter().flat_map(Self::conditions).collect()
+            }
+            Self::Condition(condition) => vec![condition],
+        }
+    }
+}
+
+/// A named, saved filter over one entity type, e.g. "Hot Leads"
+///
+/// Only the filter is stored, so membership follows the data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Entity type name, e.g. `lead`
+    pub entity_type: String,
+    pub name: String,
+    pub filter: FilterExpr,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_nested_groups() {
+        let expr: FilterExpr = serde_json::from_value(json!({"and": [
+            {"field": "stage", "op": "equals", "value": "won"},
+            {"or": [
+                {"field": "amount", "op": "gt", "value": 100},
+                {"field": "notes", "op": "is_empty"}
+            ]}
+        ]}))
+        .unwrap();
+
+        assert_eq!(expr.depth(), 2);
+        let ops: Vec<FilterOp> = expr.conditions().iter().map(|c| c.op).collect();
+        assert_eq!(ops, vec![FilterOp::Eq, FilterOp::Gt, FilterOp::IsEmpty]);
+        assert_eq!(expr.conditions()[2].value, serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_unknown_operator_is_rejected() {
+        assert!(serde_json::from_value::<FilterExpr>(json!({"field": "stage", "op": "like", "value": "x"})).is_err());
+    }
+}
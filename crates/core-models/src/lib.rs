@@ -36,10 +36,13 @@ pub mod entity;
 pub mod field;
 pub mod association;
 pub mod view;
+pub mod filter;
 pub mod node;
 pub mod crdt;
 pub mod sync;
 pub mod event;
+pub mod money;
+pub mod phone;
 
 // ============================================================================
 // RE-EXPORTS
@@ -57,5 +60,8 @@ pub use entity::*;
 pub use field::*;
 pub use association::*;
 pub use view::*;
+pub use filter::*;
 pub use event::*;
 pub use node::*;
+pub use money::*;
+pub use phone::*;
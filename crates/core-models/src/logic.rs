@@ -7,6 +7,7 @@
 //! - Role-based access control
 //! - Feature flag gating
 //! - Adaptive UI based on device type
+//! - Computed field values (`LogicExpr`)
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -53,6 +54,15 @@ pub enum LogicOp {
     /// Field value is less than
     Lt { field: String, value: f64 },
     
+    /// Field equals another field or context variable
+    EqualsField { field: String, other: String },
+    
+    /// Field value is greater than another field's
+    GtField { field: String, other: String },
+    
+    /// Field value is less than another field's
+    LtField { field: String, other: String },
+    
     // ==================
     // Context Logic
     // ==================
@@ -142,6 +152,29 @@ impl<'a> EvalContext<'a> {
     }
 }
 
+impl EvalContext<'_> {
+    /// Value of `field` in the record data
+    ///
+    /// A key not present as such is read as a dotted path into nested
+    /// values, so workflow conditions can address `$trigger.new_values.stage`
+    /// or `items.0.sku`.
+    pub fn lookup(&self, field: &str) -> Option<&Value> {
+        if let Some(value) = self.record_data.get(field) {
+            return Some(value);
+        }
+        let mut segments = field.split('.');
+        let mut current = self.record_data.get(segments.next()?)?;
+        for segment in segments {
+            current = match current {
+                Value::Object(map) => map.get(segment)?,
+                Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+}
+
 impl LogicOp {
     /// Evaluate this logic operation against the given context
     /// 
@@ -154,16 +187,16 @@ impl LogicOp {
             
             // Data Logic
             LogicOp::Equals { field, value } => {
-                ctx.record_data.get(field).map_or(false, |v| v == value)
+                ctx.lookup(field).map_or(false, |v| v == value)
             }
             LogicOp::NotEquals { field, value } => {
-                ctx.record_data.get(field).map_or(true, |v| v != value)
+                ctx.lookup(field).map_or(true, |v| v != value)
             }
             LogicOp::Empty { field } => {
-                ctx.record_data.get(field).map_or(true, |v| v.is_null())
+                ctx.lookup(field).map_or(true, |v| v.is_null())
             }
             LogicOp::Contains { field, value } => {
-                ctx.record_data.get(field).map_or(false, |v| {
+                ctx.lookup(field).map_or(false, |v| {
                     if let Some(s) = v.as_str() {
                         value.as_str().map_or(false, |needle| s.contains(needle))
                     } else if let Some(arr) = v.as_array() {
@@ -174,15 +207,24 @@ impl LogicOp {
                 })
             }
             LogicOp::Gt { field, value } => {
-                ctx.record_data.get(field).map_or(false, |v| {
+                ctx.lookup(field).map_or(false, |v| {
                     v.as_f64().map_or(false, |n| n > *value)
                 })
             }
             LogicOp::Lt { field, value } => {
-                ctx.record_data.get(field).map_or(false, |v| {
+                ctx.lookup(field).map_or(false, |v| {
                     v.as_f64().map_or(false, |n| n < *value)
                 })
             }
+            LogicOp::EqualsField { field, other } => {
+                matches!((ctx.lookup(field), ctx.lookup(other)), (Some(a), Some(b)) if a == b)
+            }
+            LogicOp::GtField { field, other } => {
+                matches!(numbers(ctx, field, other), Some((a, b)) if a > b)
+            }
+            LogicOp::LtField { field, other } => {
+                matches!(numbers(ctx, field, other), Some((a, b)) if a < b)
+            }
             
             // Context Logic
             LogicOp::HasRole { role } => ctx.user_roles.contains(role),
@@ -195,7 +237,7 @@ impl LogicOp {
             LogicOp::FeatureEnabled { flag } => ctx.feature_flags.contains(flag),
             LogicOp::DeviceType { device } => ctx.device_type == device,
             
-            // Combinators
+            // Combinators (short-circuit: later operands are not evaluated)
             LogicOp::And(ops) => ops.iter().all(|op| op.evaluate(ctx)),
             LogicOp::Or(ops) => ops.iter().any(|op| op.evaluate(ctx)),
             LogicOp::Not(op) => !op.evaluate(ctx),
@@ -203,6 +245,150 @@ impl LogicOp {
     }
 }
 
+impl LogicOp {
+    /// Record fields this condition reads
+    pub fn referenced_fields(&self) -> Vec<&str> {
+        match self {
+            LogicOp::Equals { field, .. }
+            | LogicOp::NotEquals { field, .. }
+            | LogicOp::Empty { field }
+            | LogicOp::Contains { field, .. }
+            | LogicOp::Gt { field, .. }
+            | LogicOp::Lt { field, .. } => vec![field.as_str()],
+            LogicOp::EqualsField { field, other }
+            | LogicOp::GtField { field, other }
+            | LogicOp::LtField { field, other } => vec![field.as_str(), other.as_str()],
+            LogicOp::And(ops) | LogicOp::Or(ops) => ops.iter().flat_map(|op| op.referenced_fields()).collect(),
+            LogicOp::Not(op) => op.referenced_fields(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Numeric values of two fields, if both are numbers
+fn numbers(ctx: &EvalContext, field: &str, other: &str) -> Option<(f64, f64)> {
+    Some((ctx.lookup(field)?.as_f64()?, ctx.lookup(other)?.as_f64()?))
+}
+
+// ============================================================================
+// VALUE EXPRESSIONS
+// ============================================================================
+
+/// Value-producing expressions for computed fields
+///
+/// ```json
+/// { "op": "concat", "args": [{ "op": "field", "args": "first_name" }, { "op": "literal", "args": " " }] }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "op", content = "args")]
+pub enum LogicExpr {
+    /// Value of a record field (null if missing)
+    Field(String),
+
+    /// Constant value
+    Literal(Value),
+
+    /// Join values as text; null/missing values become empty strings
+    Concat(Vec<LogicExpr>),
+
+    /// Sum of numeric values
+    Add(Vec<LogicExpr>),
+
+    /// First value minus the second
+    Subtract(Box<LogicExpr>, Box<LogicExpr>),
+
+    /// Product of numeric values
+    Multiply(Vec<LogicExpr>),
+
+    /// First value divided by the second (null when dividing by zero)
+    Divide(Box<LogicExpr>, Box<LogicExpr>),
+
+    /// `then` when `condition` holds, otherwise `otherwise`
+    If {
+        condition: LogicOp,
+        then: Box<LogicExpr>,
+        otherwise: Box<LogicExpr>,
+    },
+}
+
+impl LogicExpr {
+    /// Evaluate against the record in `ctx`
+    ///
+    /// Arithmetic yields null if any operand is missing or not a number.
+    pub fn evaluate(&self, ctx: &EvalContext) -> Value {
+        match self {
+            LogicExpr::Field(name) => ctx.lookup(name).cloned().unwrap_or(Value::Null),
+            LogicExpr::Literal(value) => value.clone(),
+            LogicExpr::Concat(parts) => {
+                Value::String(parts.iter().map(|p| value_to_text(&p.evaluate(ctx))).collect())
+            }
+            LogicExpr::Add(terms) => fold_numbers(terms, ctx, 0.0, |a, b| a + b),
+            LogicExpr::Multiply(terms) => fold_numbers(terms, ctx, 1.0, |a, b| a * b),
+            LogicExpr::Subtract(a, b) => match (a.evaluate(ctx).as_f64(), b.evaluate(ctx).as_f64()) {
+                (Some(a), Some(b)) => number_value(a - b),
+                _ => Value::Null,
+            },
+            LogicExpr::Divide(a, b) => match (a.evaluate(ctx).as_f64(), b.evaluate(ctx).as_f64()) {
+                (Some(a), Some(b)) if b != 0.0 => number_value(a / b),
+                _ => Value::Null,
+            },
+            LogicExpr::If { condition, then, otherwise } => {
+                if condition.evaluate(ctx) {
+                    then.evaluate(ctx)
+                } else {
+                    otherwise.evaluate(ctx)
+                }
+            }
+        }
+    }
+
+    /// Record fields this expression reads, including those in `If` conditions
+    pub fn referenced_fields(&self) -> Vec<&str> {
+        match self {
+            LogicExpr::Field(name) => vec![name.as_str()],
+            LogicExpr::Literal(_) => vec![],
+            LogicExpr::Concat(parts) | LogicExpr::Add(parts) | LogicExpr::Multiply(parts) => {
+                parts.iter().flat_map(|p| p.referenced_fields()).collect()
+            }
+            LogicExpr::Subtract(a, b) | LogicExpr::Divide(a, b) => {
+                let mut fields = a.referenced_fields();
+                fields.extend(b.referenced_fields());
+                fields
+            }
+            LogicExpr::If { condition, then, otherwise } => {
+                let mut fields = condition.referenced_fields();
+                fields.extend(then.referenced_fields());
+                fields.extend(otherwise.referenced_fields());
+                fields
+            }
+        }
+    }
+}
+
+fn fold_numbers(terms: &[LogicExpr], ctx: &EvalContext, init: f64, op: impl Fn(f64, f64) -> f64) -> Value {
+    terms
+        .iter()
+        .try_fold(init, |acc, term| term.evaluate(ctx).as_f64().map(|n| op(acc, n)))
+        .map_or(Value::Null, number_value)
+}
+
+/// Whole results stay integers so `2 * 3` renders as `6`, not `6.0`
+fn number_value(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,6 +444,29 @@ mod tests {
         assert!(op.evaluate(&ctx));
     }
     
+    #[test]
+    fn test_paths_and_field_comparisons() {
+        let mut data = HashMap::new();
+        data.insert("$trigger".to_string(), json!({"new_values": {"amount": 5000, "budget": 4000, "owner": "u1"}}));
+        data.insert("assignee".to_string(), json!("u1"));
+        data.insert("tags".to_string(), json!(["vip", "hot"]));
+        let ctx = EvalContext::with_data(&data);
+
+        assert_eq!(ctx.lookup("$trigger.new_values.amount"), Some(&json!(5000)));
+        assert_eq!(ctx.lookup("tags.1"), Some(&json!("hot")));
+        assert_eq!(ctx.lookup("$trigger.missing.amount"), None);
+
+        let over_budget: LogicOp = serde_json::from_value(json!({
+            "op": "gtField",
+            "args": { "field": "$trigger.new_values.amount", "other": "$trigger.new_values.budget" }
+        }))
+        .unwrap();
+        assert!(over_budget.evaluate(&ctx));
+        assert!(LogicOp::EqualsField { field: "assignee".into(), other: "$trigger.new_values.owner".into() }.evaluate(&ctx));
+        assert!(!LogicOp::LtField { field: "assignee".into(), other: "tags.0".into() }.evaluate(&ctx));
+        assert_eq!(over_budget.referenced_fields(), vec!["$trigger.new_values.amount", "$trigger.new_values.budget"]);
+    }
+    
     #[test]
     fn test_device_type() {
         let base = EvalContext::new();
@@ -266,4 +475,53 @@ mod tests {
         assert!(LogicOp::DeviceType { device: "mobile".into() }.evaluate(&ctx));
         assert!(!LogicOp::DeviceType { device: "desktop".into() }.evaluate(&ctx));
     }
+
+    #[test]
+    fn test_expr_concat_and_arithmetic() {
+        let mut data = HashMap::new();
+        data.insert("first_name".to_string(), json!("Layla"));
+        data.insert("last_name".to_string(), json!("Haddad"));
+        data.insert("price".to_string(), json!(1250.5));
+        data.insert("quantity".to_string(), json!(2));
+        let ctx = EvalContext::with_data(&data);
+
+        let full_name = LogicExpr::Concat(vec![
+            LogicExpr::Field("first_name".into()),
+            LogicExpr::Literal(json!(" ")),
+            LogicExpr::Field("last_name".into()),
+        ]);
+        assert_eq!(full_name.evaluate(&ctx), json!("Layla Haddad"));
+
+        let total = LogicExpr::Multiply(vec![LogicExpr::Field("price".into()), LogicExpr::Field("quantity".into())]);
+        assert_eq!(total.evaluate(&ctx), json!(2501));
+
+        let half = LogicExpr::Divide(Box::new(LogicExpr::Field("price".into())), Box::new(LogicExpr::Literal(json!(2))));
+        assert_eq!(half.evaluate(&ctx), json!(625.25));
+
+        // Missing operands and division by zero yield null
+        let missing = LogicExpr::Add(vec![LogicExpr::Field("price".into()), LogicExpr::Field("discount".into())]);
+        assert_eq!(missing.evaluate(&ctx), Value::Null);
+        let by_zero = LogicExpr::Divide(Box::new(LogicExpr::Literal(json!(1))), Box::new(LogicExpr::Literal(json!(0))));
+        assert_eq!(by_zero.evaluate(&ctx), Value::Null);
+    }
+
+    #[test]
+    fn test_expr_conditional_and_serde() {
+        let expr: LogicExpr = serde_json::from_value(json!({
+            "op": "if",
+            "args": {
+                "condition": { "op": "gt", "args": { "field": "score", "value": 80.0 } },
+                "then": { "op": "literal", "args": "hot" },
+                "otherwise": { "op": "literal", "args": "cold" }
+            }
+        }))
+        .unwrap();
+
+        let mut data = HashMap::new();
+        data.insert("score".to_string(), json!(95));
+        assert_eq!(expr.evaluate(&EvalContext::with_data(&data)), json!("hot"));
+        data.insert("score".to_string(), json!(10));
+        assert_eq!(expr.evaluate(&EvalContext::with_data(&data)), json!("cold"));
+        assert_eq!(expr.referenced_fields(), vec!["score"]);
+    }
 }
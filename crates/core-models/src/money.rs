@@ -0,0 +1,284 @@
+//! Money - currency amounts in integer minor units
+//!
+//! Amounts are stored as `{"amount_minor": 123456, "currency": "USD"}` so
+//! cents never pass through a float. Plain numbers from before this format
+//! are still read, as major units. Formatting and parsing follow a locale's
+//! digit grouping, decimal mark, and symbol placement.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Currency used when neither the field nor the tenant sets one
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Locale used when the tenant doesn't set one
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// An amount of money in a currency's minor units (cents, fils, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Money {
+    pub amount_minor: i64,
+    /// ISO 4217 code
+    pub currency: String,
+}
+
+/// How a currency is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyInfo {
+    pub code: &'static str,
+    pub symbol: &'static str,
+    /// Digits after the decimal mark
+    pub decimals: u32,
+}
+
+const CURRENCIES: &[CurrencyInfo] = &[
+    CurrencyInfo { code: "USD", symbol: "$", decimals: 2 },
+    CurrencyInfo { code: "EUR", symbol: "€", decimals: 2 },
+    CurrencyInfo { code: "GBP", symbol: "£", decimals: 2 },
+    CurrencyInfo { code: "AED", symbol: "د.إ", decimals: 2 },
+    CurrencyInfo { code: "SAR", symbol: "ر.س", decimals: 2 },
+    CurrencyInfo { code: "JPY", symbol: "¥", decimals: 0 },
+    CurrencyInfo { code: "KWD", symbol: "د.ك", decimals: 3 },
+];
+
+/// Formatting info for `code`; unknown currencies use the code and two decimals
+pub fn currency_info(code: &str) -> CurrencyInfo {
+    let code = code.trim().to_uppercase();
+    CURRENCIES.iter().copied().find(|c| c.code == code).unwrap_or(CurrencyInfo {
+        code: "",
+        symbol: "",
+        decimals: 2,
+    })
+}
+
+/// Number conventions of a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocaleFormat {
+    pub group: char,
+    pub decimal: char,
+    /// "1.234,56 €" rather than "€1,234.56"
+    pub symbol_after: bool,
+    /// Arabic-script symbols are written out; other locales show the code
+    pub arabic_symbols: bool,
+}
+
+impl LocaleFormat {
+    /// Conventions for a BCP 47 tag such as "de-DE"; unknown languages use English ones
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['-', '_']).next().unwrap_or("").to_lowercase();
+        match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "tr" | "id" => {
+                Self { group: '.', decimal: ',', symbol_after: true, arabic_symbols: false }
+            }
+            "fr" => Self { group: '\u{202F}', decimal: ',', symbol_after: true, arabic_symbols: false },
+            "ar" => Self { group: ',', decimal: '.', symbol_after: true, arabic_symbols: true },
+            _ => Self { group: ',', decimal: '.', symbol_after: false, arabic_symbols: false },
+        }
+    }
+}
+
+impl Money {
+    pub fn new(amount_minor: i64, currency: &str) -> Self {
+        Self { amount_minor, currency: currency.trim().to_uppercase() }
+    }
+
+    /// Convert a major-unit amount (e.g. 1234.56), rounding to the currency's minor unit
+    pub fn from_major(amount: f64, currency: &str) -> Self {
+        let scale = 10f64.powi(currency_info(currency).decimals as i32);
+        Self::new((amount * scale).round() as i64, currency)
+    }
+
+    /// Amount in major units, for display and arithmetic that tolerates floats
+    pub fn to_major(&self) -> f64 {
+        self.amount_minor as f64 / 10f64.powi(self.info().decimals as i32)
+    }
+
+    pub fn info(&self) -> CurrencyInfo {
+        currency_info(&self.currency)
+    }
+
+    /// Read a stored value: the minor-units object, or a legacy number (or
+    /// numeric string) in major units of `default_currency`
+    pub fn from_value(value: &Value, default_currency: &str) -> Option<Self> {
+        match value {
+            Value::Object(map) => {
+                let amount_minor = map.get("amount_minor")?.as_i64()?;
+                let currency = map.get("currency").and_then(|c| c.as_str()).unwrap_or(default_currency);
+                Some(Self::new(amount_minor, currency))
+            }
+            Value::Number(n) => n.as_f64().map(|amount| Self::from_major(amount, default_currency)),
+            Value::String(s) => s.trim().parse::<f64>().ok().map(|amount| Self::from_major(amount, default_currency)),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::json!({ "amount_minor": self.amount_minor, "currency": self.currency })
+    }
+
+    /// The bare number with the locale's grouping and decimal mark, e.g. "1.234,56"
+    pub fn format_number(&self, locale: &str) -> String {
+        let format = LocaleFormat::for_locale(locale);
+        let decimals = self.info().decimals;
+        let scale = 10u64.pow(decimals);
+        let abs = self.amount_minor.unsigned_abs();
+
+        let whole = (abs / scale).to_string();
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i).is_multiple_of(3) {
+                grouped.push(format.group);
+            }
+            grouped.push(digit);
+        }
+        if decimals > 0 {
+            grouped.push(format.decimal);
+            grouped.push_str(&format!("{:0width$}", abs % scale, width = decimals as usize));
+        }
+        if self.amount_minor < 0 {
+            grouped.insert(0, '-');
+        }
+        grouped
+    }
+
+    /// The amount with its symbol for `locale`, e.g. "$1,234.56" or "1.234,56 €"
+    pub fn format(&self, locale: &str) -> String {
+        let format = LocaleFormat::for_locale(locale);
+        let info = self.info();
+        let number = self.format_number(locale);
+
+        let arabic = info.symbol.chars().any(|c| ('\u{0600}'..='\u{06FF}').contains(&c));
+        if format.symbol_after {
+            let symbol = if info.symbol.is_empty() || (arabic && !format.arabic_symbols) { &self.currency } else { info.symbol };
+            format!("{} {}", number, symbol)
+        } else if info.symbol.is_empty() || arabic {
+            // Codes read best set apart: "AED 1,234.56"
+            format!("{} {}", self.currency, number)
+        } else if let Some(positive) = number.strip_prefix('-') {
+            format!("-{}{}", info.symbol, positive)
+        } else {
+            format!("{}{}", info.symbol, number)
+        }
+    }
+
+    /// Parse user input such as "1,234.56" (en) or "1.234,56" (de) in `currency`
+    ///
+    /// Symbols, codes and spaces are ignored. Group separators must split
+    /// the whole part into threes, so "1234.56" is rejected in a locale where
+    /// "." groups digits rather than read as 123456.
+    pub fn parse(input: &str, currency: &str, locale: &str) -> Result<Self, String> {
+        let format = LocaleFormat::for_locale(locale);
+        let decimals = currency_info(currency).decimals;
+
+        // Keep digits, a leading minus, and separators between digits (symbols
+        // such as "د.إ" contain the decimal mark too)
+        let chars: Vec<char> = input.chars().collect();
+        let mut cleaned = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let between_digits = i > 0
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|next| next.is_ascii_digit());
+            if c.is_ascii_digit()
+                || (c == '-' && cleaned.is_empty())
+                || ((c == format.group || c == format.decimal) && between_digits)
+            {
+                cleaned.push(c);
+            }
+        }
+        let (negative, digits) = match cleaned.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, cleaned.as_str()),
+        };
+        if digits.is_empty() {
+            return Err("Please enter an amount".to_string());
+        }
+
+        let (whole, fraction) = match digits.split_once(format.decimal) {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (digits, ""),
+        };
+        if fraction.contains(format.group) || fraction.contains(format.decimal) {
+            return Err("Please enter a valid amount".to_string());
+        }
+        if fraction.len() > decimals as usize {
+            return Err(format!("Use at most {} decimal places", decimals));
+        }
+
+        let groups: Vec<&str> = whole.split(format.group).collect();
+        let valid_groups = groups.len() == 1
+            || (!groups[0].is_empty() && groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3));
+        if !valid_groups {
+            return Err("Please enter a valid amount".to_string());
+        }
+
+        let whole: String = groups.concat();
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| "Amount is too large".to_string())? };
+        let fraction: i64 = format!("{:0<width$}", fraction, width = decimals as usize).parse().unwrap_or(0);
+        let minor = whole
+            .checked_mul(10i64.pow(decimals))
+            .and_then(|m| m.checked_add(fraction))
+            .ok_or_else(|| "Amount is too large".to_string())?;
+
+        Ok(Self::new(if negative { -minor } else { minor }, currency))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip_through_minor_units() {
+        let money = Money::from_major(1234.56, "USD");
+        assert_eq!(money.amount_minor, 123_456);
+        assert_eq!(money.to_major(), 1234.56);
+
+        let stored = money.to_value();
+        assert_eq!(stored, json!({ "amount_minor": 123_456, "currency": "USD" }));
+        assert_eq!(Money::from_value(&stored, "EUR"), Some(money.clone()));
+
+        // Legacy float values read as major units
+        assert_eq!(Money::from_value(&json!(1234.56), "USD"), Some(money));
+        assert_eq!(Money::from_value(&json!("0.10"), "USD").unwrap().amount_minor, 10);
+        assert_eq!(Money::from_value(&json!(null), "USD"), None);
+    }
+
+    #[test]
+    fn test_format_usd_eur_aed() {
+        let usd = Money::new(123_456, "USD");
+        assert_eq!(usd.format("en-US"), "$1,234.56");
+        assert_eq!(Money::new(-5, "USD").format("en-US"), "-$0.05");
+
+        let eur = Money::new(123_456, "EUR");
+        assert_eq!(eur.format("de-DE"), "1.234,56 €");
+        assert_eq!(eur.format("en-GB"), "€1,234.56");
+        assert_eq!(eur.format("fr-FR"), "1\u{202F}234,56 €");
+
+        let aed = Money::new(123_456_789, "AED");
+        assert_eq!(aed.format("en-US"), "AED 1,234,567.89");
+        assert_eq!(aed.format("ar-AE"), "1,234,567.89 د.إ");
+
+        assert_eq!(Money::new(1_500, "JPY").format("en-US"), "¥1,500");
+        assert_eq!(Money::new(1_500, "XYZ").format("en-US"), "XYZ 15.00");
+    }
+
+    #[test]
+    fn test_parse_per_locale() {
+        assert_eq!(Money::parse("1,234.56", "USD", "en-US"), Ok(Money::new(123_456, "USD")));
+        assert_eq!(Money::parse("1.234,56", "EUR", "de-DE"), Ok(Money::new(123_456, "EUR")));
+        assert_eq!(Money::parse("€ 1.234,5", "EUR", "de-DE"), Ok(Money::new(123_450, "EUR")));
+        assert_eq!(Money::parse("1234", "USD", "en-US"), Ok(Money::new(123_400, "USD")));
+        assert_eq!(Money::parse("-12.30", "USD", "en-US"), Ok(Money::new(-1_230, "USD")));
+
+        // A group separator with the wrong digit count is not misread
+        assert!(Money::parse("1234.56", "EUR", "de-DE").is_err());
+        assert!(Money::parse("12,34.56", "USD", "en-US").is_err());
+        assert!(Money::parse("1.005", "USD", "en-US").is_err());
+        assert!(Money::parse("", "USD", "en-US").is_err());
+
+        // Formatting and parsing agree
+        let aed = Money::new(123_456_789, "AED");
+        assert_eq!(Money::parse(&aed.format("ar-AE"), "AED", "ar-AE"), Ok(aed));
+    }
+}
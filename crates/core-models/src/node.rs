@@ -78,6 +78,8 @@ pub enum NodeType {
 
     // Conditions
     ConditionIf,
+    /// Evaluates a `LogicOp` and continues along its `true` or `false` port
+    Branch,
     ConditionSwitch,
     ConditionFilter,
 
@@ -95,10 +97,14 @@ pub enum NodeType {
     /// WhatsApp Business API integration (Meta Cloud API)
     ActionWhatsapp,
     ActionSendWebhook,
+    /// Call an external API and map its response into the context
+    HttpRequest,
     ActionCreateInteraction,
     ActionCreateTask,
     ActionScheduleMeeting,
     ActionDelay,
+    /// Pause until a date field of the triggering record arrives
+    WaitUntilField,
     /// Collect payment via Stripe/PaymentProvider
     ActionCollectPayment,
 
@@ -123,6 +129,8 @@ pub enum NodeType {
     FlowMerge,
     FlowSplit,
     FlowLoop,
+    /// Runs the nodes on its `item` port once per element of a context array
+    ForEach,
     FlowSubGraph,
 
     // UI (for UI graphs)
@@ -213,6 +221,8 @@ pub struct GraphExecution {
 pub enum ExecutionStatus {
     Pending,
     Running,
+    /// Waiting on a delay; resumable from its checkpoint
+    Suspended,
     Completed,
     Failed,
     Cancelled,
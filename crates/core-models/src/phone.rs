@@ -0,0 +1,192 @@
+//! Phone - E.164 normalization and localized display
+//!
+//! Phone numbers are stored in E.164 ("+971501234567") so the same number
+//! typed as "050 123 4567" or "+971 50 123 4567" dedups and dials the same.
+//! National input is read against a default country, normally the tenant's.
+
+/// Country used when the tenant doesn't set one
+pub const DEFAULT_PHONE_REGION: &str = "US";
+
+/// Numbering plan of a country
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberingPlan {
+    /// ISO 3166-1 alpha-2
+    pub region: &'static str,
+    pub calling_code: &'static str,
+    /// Dialed before national numbers, dropped in E.164 ("0" in "050 ...")
+    pub trunk_prefix: Option<&'static str>,
+    /// Digits after the calling code
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+const PLANS: &[NumberingPlan] = &[
+    NumberingPlan { region: "US", calling_code: "1", trunk_prefix: Some("1"), min_len: 10, max_len: 10 },
+    NumberingPlan { region: "CA", calling_code: "1", trunk_prefix: Some("1"), min_len: 10, max_len: 10 },
+    NumberingPlan { region: "GB", calling_code: "44", trunk_prefix: Some("0"), min_len: 9, max_len: 10 },
+    NumberingPlan { region: "AE", calling_code: "971", trunk_prefix: Some("0"), min_len: 8, max_len: 9 },
+    NumberingPlan { region: "SA", calling_code: "966", trunk_prefix: Some("0"), min_len: 8, max_len: 9 },
+    NumberingPlan { region: "KW", calling_code: "965", trunk_prefix: None, min_len: 8, max_len: 8 },
+    NumberingPlan { region: "QA", calling_code: "974", trunk_prefix: None, min_len: 8, max_len: 8 },
+    NumberingPlan { region: "EG", calling_code: "20", trunk_prefix: Some("0"), min_len: 8, max_len: 10 },
+    NumberingPlan { region: "DE", calling_code: "49", trunk_prefix: Some("0"), min_len: 6, max_len: 13 },
+    NumberingPlan { region: "FR", calling_code: "33", trunk_prefix: Some("0"), min_len: 9, max_len: 9 },
+    NumberingPlan { region: "IN", calling_code: "91", trunk_prefix: Some("0"), min_len: 10, max_len: 10 },
+    NumberingPlan { region: "JP", calling_code: "81", trunk_prefix: Some("0"), min_len: 9, max_len: 10 },
+];
+
+/// Plan for a country code such as "AE"
+pub fn numbering_plan(region: &str) -> Option<NumberingPlan> {
+    let region = region.trim().to_uppercase();
+    PLANS.iter().copied().find(|p| p.region == region)
+}
+
+/// Plan whose calling code starts `digits` (an E.164 number without '+')
+fn plan_for_number(digits: &str) -> Option<NumberingPlan> {
+    // Calling codes are prefix-free, so at most one length matches
+    (1..=3).rev().find_map(|len| {
+        let code = digits.get(..len)?;
+        PLANS.iter().copied().find(|p| p.calling_code == code)
+    })
+}
+
+/// Country of a BCP 47 locale such as "ar-AE"
+pub fn region_from_locale(locale: &str) -> Option<String> {
+    locale
+        .split(['-', '_'])
+        .skip(1)
+        .find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(|part| part.to_uppercase())
+}
+
+/// Normalize `input` to E.164; numbers without a '+' or "00" prefix are
+/// national numbers of `default_region`
+pub fn normalize_phone(input: &str, default_region: &str) -> Result<String, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err("Please enter a phone number".to_string());
+    }
+    if trimmed.chars().any(|c| !c.is_ascii_digit() && !" -.()/+".contains(c)) || trimmed.rfind('+').is_some_and(|i| i > 0) {
+        return Err("Phone numbers may only contain digits, spaces and - . ( ) +".to_string());
+    }
+
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+    let international = if trimmed.starts_with('+') {
+        digits
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else {
+        let plan = numbering_plan(default_region)
+            .ok_or_else(|| "Add the country code, e.g. +44 20 7946 0958".to_string())?;
+        let national = plan
+            .trunk_prefix
+            .and_then(|trunk| digits.strip_prefix(trunk))
+            .filter(|rest| rest.len() >= plan.min_len)
+            .unwrap_or(&digits);
+        format!("{}{}", plan.calling_code, national)
+    };
+
+    if international.starts_with('0') || !(7..=15).contains(&international.len()) {
+        return Err("Please enter a valid phone number".to_string());
+    }
+    if let Some(plan) = plan_for_number(&international) {
+        let national = &international[plan.calling_code.len()..];
+        if !(plan.min_len..=plan.max_len).contains(&national.len()) {
+            return Err(format!("Phone number has the wrong number of digits for +{}", plan.calling_code));
+        }
+        // North American area codes never start with 0 or 1
+        if plan.calling_code == "1" && national.starts_with(['0', '1']) {
+            return Err("Please enter a valid phone number".to_string());
+        }
+    }
+    Ok(format!("+{}", international))
+}
+
+/// Whether `value` is already a valid E.164 number
+pub fn is_e164(value: &str) -> bool {
+    value.starts_with('+') && normalize_phone(value, DEFAULT_PHONE_REGION).as_deref() == Ok(value)
+}
+
+/// Display an E.164 number: national format for numbers of `region`,
+/// international otherwise. Other values are returned unchanged.
+pub fn format_phone(e164: &str, region: &str) -> String {
+    let Some(plan) = e164.strip_prefix('+').and_then(plan_for_number) else {
+        return e164.to_string();
+    };
+    let national = &e164[1 + plan.calling_code.len()..];
+    let domestic = numbering_plan(region).is_some_and(|r| r.calling_code == plan.calling_code);
+
+    if plan.calling_code == "1" && national.len() == 10 {
+        let (area, exchange, line) = (&national[..3], &national[3..6], &national[6..]);
+        return if domestic {
+            format!("({}) {}-{}", area, exchange, line)
+        } else {
+            format!("+1 {} {} {}", area, exchange, line)
+        };
+    }
+
+    let grouped = group_digits(national);
+    match (domestic, plan.trunk_prefix) {
+        (true, Some(trunk)) => format!("{}{}", trunk, grouped),
+        (true, None) => grouped,
+        (false, _) => format!("+{} {}", plan.calling_code, grouped),
+    }
+}
+
+/// "501234567" -> "50 123 4567"; eight digits split in half
+fn group_digits(digits: &str) -> String {
+    let sizes: Vec<usize> = match digits.len() {
+        8 => vec![4, 4],
+        len if len > 7 => vec![len - 7, 3, 4],
+        len if len > 3 => vec![3, len - 3],
+        len => vec![len],
+    };
+    let mut groups = Vec::with_capacity(sizes.len());
+    let mut start = 0;
+    for size in sizes {
+        groups.push(&digits[start..start + size]);
+        start += size;
+    }
+    groups.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_formats_normalize_to_one_number() {
+        for input in ["+1 (555) 123-4567", "555.123.4567", "1-555-123-4567", "00 1 555 123 4567", "(555) 123 4567"] {
+            assert_eq!(normalize_phone(input, "US").as_deref(), Ok("+15551234567"), "{}", input);
+        }
+        for input in ["050 123 4567", "+971 50 123 4567", "00971501234567", "50-123-4567"] {
+            assert_eq!(normalize_phone(input, "AE").as_deref(), Ok("+971501234567"), "{}", input);
+        }
+        // International input ignores the default country
+        assert_eq!(normalize_phone("+971 50 123 4567", "US").as_deref(), Ok("+971501234567"));
+        assert_eq!(normalize_phone("020 7946 0958", "gb").as_deref(), Ok("+442079460958"));
+        assert!(is_e164("+442079460958"));
+        assert!(!is_e164("02079460958"));
+    }
+
+    #[test]
+    fn test_invalid_numbers_are_rejected() {
+        for input in ["", "123", "555-CALL-NOW", "+1 055 123 4567", "+971 50 123", "1234567890123456", "+0 123 456 789", "5+55 123 4567"] {
+            assert!(normalize_phone(input, "US").is_err(), "{}", input);
+        }
+        // National numbers need a known default country
+        assert!(normalize_phone("050 123 4567", "ZZ").is_err());
+    }
+
+    #[test]
+    fn test_display_is_localized() {
+        assert_eq!(format_phone("+15551234567", "US"), "(555) 123-4567");
+        assert_eq!(format_phone("+15551234567", "AE"), "+1 555 123 4567");
+        assert_eq!(format_phone("+971501234567", "AE"), "050 123 4567");
+        assert_eq!(format_phone("+971501234567", "US"), "+971 50 123 4567");
+        assert_eq!(format_phone("+96551234567", "KW"), "5123 4567");
+        assert_eq!(format_phone("not a number", "US"), "not a number");
+        assert_eq!(region_from_locale("ar-AE").as_deref(), Some("AE"));
+        assert_eq!(region_from_locale("en"), None);
+    }
+}
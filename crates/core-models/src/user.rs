@@ -35,6 +35,34 @@ impl Default for UserRole {
 }
 
 impl UserRole {
+    /// Every built-in role
+    pub const ALL: [UserRole; 9] = [
+        Self::Admin,
+        Self::Manager,
+        Self::Member,
+        Self::Agent,
+        Self::Broker,
+        Self::Landlord,
+        Self::Tenant,
+        Self::Vendor,
+        Self::Viewer,
+    ];
+
+    /// The role's name as stored in `users.role`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Manager => "manager",
+            Self::Member => "member",
+            Self::Agent => "agent",
+            Self::Broker => "broker",
+            Self::Landlord => "landlord",
+            Self::Tenant => "tenant",
+            Self::Vendor => "vendor",
+            Self::Viewer => "viewer",
+        }
+    }
+
     /// Returns the entities this role can access
     pub fn accessible_entities(&self) -> Vec<&'static str> {
         match self {
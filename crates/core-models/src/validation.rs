@@ -3,8 +3,17 @@
 //! This module provides a dual-layer validation system:
 //! - **Portable**: Sync validators that work in WASM (frontend) and native (backend)
 //! - **Async**: Database-dependent validators that only run on the backend
+//!
+//! Record-level rules (`RequiredIf`, `Compare`) are portable too but need the whole
+//! record, so they run through `validate_record` instead of `validate_portable`.
 
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::logic::{EvalContext, LogicOp};
 
 /// Validation rules that can be attached to fields
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +41,19 @@ pub enum ValidationRule {
     /// Must be a valid URL format
     Url,
     
+    // ==================
+    // Record-level Rules (portable, need the whole record)
+    // ==================
+    
+    /// `field` must have a non-empty value whenever `op` holds for the record,
+    /// e.g. `close_date` when `stage` equals `closed_won`
+    RequiredIf { field: String, op: LogicOp },
+    
+    /// `left_field` must compare to `right_field` as `op` says, e.g.
+    /// `end_date` Gt `start_date`. Numbers, dates, and datetimes are supported;
+    /// skipped while either side is empty.
+    Compare { left_field: String, op: CompareOp, right_field: String },
+    
     // ==================
     // Backend-Only Rules (require DB)
     // ==================
@@ -40,11 +62,52 @@ pub enum ValidationRule {
     Unique { table: String, column: String },
 }
 
+/// Comparison operator for `ValidationRule::Compare`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CompareOp {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Gte => ordering != Ordering::Less,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Lte => ordering != Ordering::Greater,
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            CompareOp::Gt => "greater than",
+            CompareOp::Gte => "greater than or equal to",
+            CompareOp::Lt => "less than",
+            CompareOp::Lte => "less than or equal to",
+            CompareOp::Eq => "equal to",
+            CompareOp::Ne => "different from",
+        }
+    }
+}
+
 impl ValidationRule {
     /// Check if this rule can be evaluated in WASM (no DB required)
     pub fn is_portable(&self) -> bool {
         !matches!(self, ValidationRule::Unique { .. })
     }
+    
+    /// Check if this rule reads other fields and must go through `validate_record`
+    pub fn is_record_level(&self) -> bool {
+        matches!(self, ValidationRule::RequiredIf { .. } | ValidationRule::Compare { .. })
+    }
 }
 
 /// Trait for validating field values
@@ -61,6 +124,25 @@ pub trait Validator {
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>>;
 }
 
+/// A validation failure attributed to a specific field
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn is_empty_value(value: &Value) -> bool {
+    value.is_null()
+        || value.as_str().is_some_and(|s| s.trim().is_empty())
+        || value.as_array().is_some_and(|a| a.is_empty())
+}
+
 /// Validate a value against a portable rule
 pub fn validate_portable(
     field_name: &str,
@@ -69,11 +151,7 @@ pub fn validate_portable(
 ) -> Result<(), String> {
     match rule {
         ValidationRule::Required => {
-            let is_empty = value.is_null()
-                || value.as_str().map_or(false, |s| s.trim().is_empty())
-                || value.as_array().map_or(false, |a| a.is_empty());
-            
-            if is_empty {
+            if is_empty_value(value) {
                 return Err(format!("{} is required", field_name));
             }
         }
@@ -116,6 +194,9 @@ pub fn validate_portable(
             let _ = message;
         }
         
+        // Record-level rules need the whole record; see `validate_record`
+        ValidationRule::RequiredIf { .. } | ValidationRule::Compare { .. } => {}
+        
         // Backend-only rules skip in portable context
         ValidationRule::Unique { .. } => {}
     }
@@ -123,6 +204,89 @@ pub fn validate_portable(
     Ok(())
 }
 
+/// Validate a rule attached to `field_name` against the whole record
+///
+/// Handles record-level rules and falls back to `validate_portable` on the
+/// field's own value for the rest. Works in WASM and native.
+pub fn validate_record(
+    field_name: &str,
+    record: &HashMap<String, Value>,
+    rule: &ValidationRule,
+) -> Result<(), FieldError> {
+    match rule {
+        ValidationRule::RequiredIf { field, op } => {
+            let value = record.get(field).unwrap_or(&Value::Null);
+            if is_empty_value(value) && op.evaluate(&EvalContext::with_data(record)) {
+                return Err(FieldError {
+                    field: field.clone(),
+                    message: format!("{} is required", field),
+                });
+            }
+            Ok(())
+        }
+        ValidationRule::Compare { left_field, op, right_field } => {
+            let left = record.get(left_field).unwrap_or(&Value::Null);
+            let right = record.get(right_field).unwrap_or(&Value::Null);
+            if is_empty_value(left) || is_empty_value(right) {
+                return Ok(());
+            }
+            
+            let error = |message: String| FieldError { field: left_field.clone(), message };
+            match compare_values(left, right) {
+                Some(ordering) if op.holds(ordering) => Ok(()),
+                Some(_) => Err(error(format!("{} must be {} {}", left_field, op.describe(), right_field))),
+                None => Err(error(format!("{} cannot be compared with {}", left_field, right_field))),
+            }
+        }
+        _ => {
+            let value = record.get(field_name).unwrap_or(&Value::Null);
+            validate_portable(field_name, value, rule).map_err(|message| FieldError {
+                field: field_name.to_string(),
+                message,
+            })
+        }
+    }
+}
+
+/// A value coerced for comparison
+enum Comparable {
+    Number(f64),
+    DateTime(NaiveDateTime),
+}
+
+impl Comparable {
+    /// Numbers (or numeric strings), ISO dates, and ISO/RFC 3339 datetimes.
+    /// Dates compare as midnight so they can be ordered against datetimes.
+    fn from_value(value: &Value) -> Option<Self> {
+        if let Some(n) = value.as_f64() {
+            return Some(Comparable::Number(n));
+        }
+        let s = value.as_str()?.trim();
+        if let Ok(n) = s.parse::<f64>() {
+            return Some(Comparable::Number(n));
+        }
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Some(Comparable::DateTime(dt.naive_utc()));
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+            return Some(Comparable::DateTime(dt));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(Comparable::DateTime)
+    }
+}
+
+/// Order two record values; `None` if they aren't the same kind
+fn compare_values(left: &Value, right: &Value) -> Option<Ordering> {
+    match (Comparable::from_value(left)?, Comparable::from_value(right)?) {
+        (Comparable::Number(a), Comparable::Number(b)) => a.partial_cmp(&b),
+        (Comparable::DateTime(a), Comparable::DateTime(b)) => Some(a.cmp(&b)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,4 +328,104 @@ mod tests {
             column: "email".into(),
         }.is_portable());
     }
+    
+    #[test]
+    fn test_required_if_triggered() {
+        let rule = ValidationRule::RequiredIf {
+            field: "close_date".into(),
+            op: LogicOp::Equals { field: "stage".into(), value: json!("closed_won") },
+        };
+        let mut record = HashMap::new();
+        record.insert("stage".to_string(), json!("closed_won"));
+        record.insert("close_date".to_string(), json!(""));
+        
+        let err = validate_record("close_date", &record, &rule).unwrap_err();
+        assert_eq!(err.field, "close_date");
+        assert_eq!(err.message, "close_date is required");
+        
+        record.insert("close_date".to_string(), json!("2025-01-31"));
+        assert!(validate_record("close_date", &record, &rule).is_ok());
+    }
+    
+    #[test]
+    fn test_required_if_not_triggered() {
+        let rule = ValidationRule::RequiredIf {
+            field: "close_date".into(),
+            op: LogicOp::Equals { field: "stage".into(), value: json!("closed_won") },
+        };
+        let mut record = HashMap::new();
+        record.insert("stage".to_string(), json!("negotiation"));
+        
+        assert!(validate_record("close_date", &record, &rule).is_ok());
+        // Value-only validation cannot see the condition and never fails
+        assert!(validate_portable("close_date", &Value::Null, &rule).is_ok());
+        assert!(rule.is_portable());
+    }
+    
+    #[test]
+    fn test_validate_record_falls_back_to_field_value() {
+        let mut record = HashMap::new();
+        record.insert("email".to_string(), json!("invalid"));
+        
+        let err = validate_record("email", &record, &ValidationRule::Email).unwrap_err();
+        assert_eq!(err.field, "email");
+    }
+    
+    fn compare(left_field: &str, op: CompareOp, right_field: &str) -> ValidationRule {
+        ValidationRule::Compare { left_field: left_field.into(), op, right_field: right_field.into() }
+    }
+    
+    #[test]
+    fn test_compare_date_ordering() {
+        let rule = compare("end_date", CompareOp::Gt, "start_date");
+        let mut record = HashMap::new();
+        record.insert("start_date".to_string(), json!("2025-03-01"));
+        record.insert("end_date".to_string(), json!("2025-02-15"));
+        
+        let err = validate_record("end_date", &record, &rule).unwrap_err();
+        assert_eq!(err.field, "end_date");
+        assert_eq!(err.message, "end_date must be greater than start_date");
+        
+        record.insert("end_date".to_string(), json!("2025-03-02"));
+        assert!(validate_record("end_date", &record, &rule).is_ok());
+        
+        // Dates compare against datetimes as midnight
+        record.insert("end_date".to_string(), json!("2025-03-01T09:30:00Z"));
+        assert!(validate_record("end_date", &record, &rule).is_ok());
+        record.insert("end_date".to_string(), json!("2025-03-01"));
+        assert!(validate_record("end_date", &record, &compare("end_date", CompareOp::Gte, "start_date")).is_ok());
+    }
+    
+    #[test]
+    fn test_compare_numbers() {
+        let rule = compare("max_price", CompareOp::Gte, "min_price");
+        let mut record = HashMap::new();
+        record.insert("min_price".to_string(), json!(500000));
+        record.insert("max_price".to_string(), json!("450000.50"));
+        
+        assert!(validate_record("max_price", &record, &rule).is_err());
+        
+        record.insert("max_price".to_string(), json!(500000.0));
+        assert!(validate_record("max_price", &record, &rule).is_ok());
+        assert!(validate_record("max_price", &record, &compare("max_price", CompareOp::Ne, "min_price")).is_err());
+    }
+    
+    #[test]
+    fn test_compare_mismatched_and_empty_values() {
+        let rule = compare("end_date", CompareOp::Gt, "start_date");
+        let mut record = HashMap::new();
+        record.insert("start_date".to_string(), json!("2025-03-01"));
+        record.insert("end_date".to_string(), json!(42));
+        
+        let err = validate_record("end_date", &record, &rule).unwrap_err();
+        assert_eq!(err.message, "end_date cannot be compared with start_date");
+        
+        record.insert("end_date".to_string(), json!("soon"));
+        assert!(validate_record("end_date", &record, &rule).is_err());
+        
+        // Missing values are left to Required/RequiredIf
+        record.remove("end_date");
+        assert!(validate_record("end_date", &record, &rule).is_ok());
+        assert!(rule.is_portable() && rule.is_record_level());
+    }
 }
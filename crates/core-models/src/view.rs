@@ -30,6 +30,38 @@ impl Default for ViewType {
     }
 }
 
+/// Who besides its creator can see a saved view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewVisibility {
+    /// Only the creator (and users it is explicitly shared with)
+    #[default]
+    Private,
+    /// Members of any team the creator belongs to
+    Team,
+    /// Everyone in the tenant
+    Tenant,
+}
+
+impl ViewVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Team => "team",
+            Self::Tenant => "tenant",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "private" => Some(Self::Private),
+            "team" => Some(Self::Team),
+            "tenant" => Some(Self::Tenant),
+            _ => None,
+        }
+    }
+}
+
 /// Sort direction
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
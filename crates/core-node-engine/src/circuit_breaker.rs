@@ -26,10 +26,12 @@ pub enum CircuitState {
 pub struct CircuitBreakerConfig {
     /// Number of failures before opening circuit
     pub failure_threshold: u32,
-    /// Number of successes in half-open to close
+    /// Number of successful probes in half-open to close
     pub success_threshold: u32,
-    /// Time before attempting recovery
-    pub timeout: Duration,
+    /// Backoff after the first trip; doubles with every failed probe
+    pub base_backoff: Duration,
+    /// Upper bound for the backoff before jitter
+    pub max_backoff: Duration,
     /// Maximum requests per minute
     pub rate_limit: u32,
 }
@@ -39,15 +41,28 @@ impl Default for CircuitBreakerConfig {
         Self {
             failure_threshold: 5,
             success_threshold: 2,
-            timeout: Duration::from_secs(60),
+            base_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(30 * 60),
             rate_limit: 60,
         }
     }
 }
 
+/// Point-in-time view of a circuit for observability
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    /// When the next half-open probe is allowed (only while open)
+    pub next_probe_at: Option<Instant>,
+    /// Current backoff ceiling before jitter
+    pub backoff: Duration,
+    /// Consecutive trips without a successful recovery
+    pub trips: u32,
+}
+
 /// Circuit breaker instance
 #[derive(Debug)]
-struct CircuitBreaker {
+pub struct CircuitBreaker {
     state: CircuitState,
     failure_count: u32,
     success_count: u32,
@@ -55,13 +70,19 @@ struct CircuitBreaker {
     last_success: Option<Instant>,
     config: CircuitBreakerConfig,
     
+    // Recovery backoff
+    trips: u32,
+    backoff: Duration,
+    next_probe_at: Option<Instant>,
+    probe_in_flight: bool,
+    
     // Rate limiting
     request_count: u32,
     window_start: Instant,
 }
 
 impl CircuitBreaker {
-    fn new(config: CircuitBreakerConfig) -> Self {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
         Self {
             state: CircuitState::Closed,
             failure_count: 0,
@@ -69,14 +90,39 @@ impl CircuitBreaker {
             last_failure: None,
             last_success: None,
             config,
+            trips: 0,
+            backoff: Duration::ZERO,
+            next_probe_at: None,
+            probe_in_flight: false,
             request_count: 0,
             window_start: Instant::now(),
         }
     }
     
-    fn can_execute(&mut self) -> Result<(), CircuitBreakerError> {
+    /// Current state and next probe time
+    pub fn state(&self) -> CircuitStatus {
+        CircuitStatus {
+            state: self.state,
+            next_probe_at: self.next_probe_at,
+            backoff: self.backoff,
+            trips: self.trips,
+        }
+    }
+    
+    pub fn can_execute(&mut self) -> Result<(), CircuitBreakerError> {
+        self.can_execute_at(Instant::now())
+    }
+    
+    pub fn record_success(&mut self) {
+        self.record_success_at(Instant::now())
+    }
+    
+    pub fn record_failure(&mut self) {
+        self.record_failure_at(Instant::now())
+    }
+    
+    fn can_execute_at(&mut self, now: Instant) -> Result<(), CircuitBreakerError> {
         // Check rate limit first
-        let now = Instant::now();
         if now.duration_since(self.window_start) >= Duration::from_secs(60) {
             // Reset window
             self.window_start = now;
@@ -94,41 +140,41 @@ impl CircuitBreaker {
                 Ok(())
             }
             CircuitState::Open => {
-                // Check if timeout has passed
-                if let Some(last_failure) = self.last_failure {
-                    if now.duration_since(last_failure) >= self.config.timeout {
-                        // Transition to half-open
+                // Allow a single probe once the backoff has elapsed
+                match self.next_probe_at {
+                    Some(next_probe_at) if now < next_probe_at => Err(CircuitBreakerError::CircuitOpen),
+                    _ => {
                         self.state = CircuitState::HalfOpen;
                         self.success_count = 0;
+                        self.next_probe_at = None;
+                        self.probe_in_flight = true;
                         self.request_count += 1;
                         Ok(())
-                    } else {
-                        Err(CircuitBreakerError::CircuitOpen)
                     }
-                } else {
-                    // No last failure recorded, close circuit
-                    self.state = CircuitState::Closed;
-                    self.request_count += 1;
-                    Ok(())
                 }
             }
             CircuitState::HalfOpen => {
+                // Only one probe at a time while recovering
+                if self.probe_in_flight {
+                    return Err(CircuitBreakerError::CircuitOpen);
+                }
+                self.probe_in_flight = true;
                 self.request_count += 1;
                 Ok(())
             }
         }
     }
     
-    fn record_success(&mut self) {
-        self.last_success = Some(Instant::now());
+    fn record_success_at(&mut self, now: Instant) {
+        self.last_success = Some(now);
         
         match self.state {
             CircuitState::HalfOpen => {
+                self.probe_in_flight = false;
                 self.success_count += 1;
                 if self.success_count >= self.config.success_threshold {
-                    // Enough successes, close the circuit
-                    self.state = CircuitState::Closed;
-                    self.failure_count = 0;
+                    // Recovered: close and reset the backoff
+                    self.close();
                 }
             }
             CircuitState::Closed => {
@@ -139,32 +185,63 @@ impl CircuitBreaker {
             }
             CircuitState::Open => {
                 // Shouldn't happen, but reset just in case
-                self.state = CircuitState::Closed;
-                self.failure_count = 0;
+                self.close();
             }
         }
     }
     
-    fn record_failure(&mut self) {
-        self.last_failure = Some(Instant::now());
+    fn record_failure_at(&mut self, now: Instant) {
+        self.last_failure = Some(now);
         self.failure_count += 1;
         
         match self.state {
             CircuitState::Closed => {
                 if self.failure_count >= self.config.failure_threshold {
-                    self.state = CircuitState::Open;
+                    self.trip(now);
                 }
             }
             CircuitState::HalfOpen => {
-                // Any failure in half-open reopens the circuit
-                self.state = CircuitState::Open;
-                self.success_count = 0;
+                // A failed probe reopens the circuit with a longer backoff
+                self.trip(now);
             }
             CircuitState::Open => {
                 // Already open, just update last_failure
             }
         }
     }
+    
+    /// Open the circuit and schedule the next probe using
+    /// `base * 2^(trips - 1)` capped at `max_backoff`, with full jitter
+    fn trip(&mut self, now: Instant) {
+        self.trips += 1;
+        self.state = CircuitState::Open;
+        self.success_count = 0;
+        self.probe_in_flight = false;
+        
+        let exponent = (self.trips - 1).min(31);
+        self.backoff = self
+            .config
+            .base_backoff
+            .saturating_mul(1u32 << exponent)
+            .min(self.config.max_backoff);
+        self.next_probe_at = Some(now + full_jitter(self.backoff));
+    }
+    
+    fn close(&mut self) {
+        self.state = CircuitState::Closed;
+        self.failure_count = 0;
+        self.success_count = 0;
+        self.trips = 0;
+        self.backoff = Duration::ZERO;
+        self.next_probe_at = None;
+        self.probe_in_flight = false;
+    }
+}
+
+/// Random duration in `[0, ceiling]` ("full jitter")
+fn full_jitter(ceiling: Duration) -> Duration {
+    let fraction = (uuid::Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0;
+    ceiling.mul_f64(fraction)
 }
 
 /// Circuit breaker errors
@@ -247,6 +324,12 @@ impl CircuitBreakerManager {
         }
     }
     
+    /// Get circuit state and next probe time
+    pub async fn get_status(&self, key: &str) -> Option<CircuitStatus> {
+        let circuits = self.circuits.read().await;
+        circuits.get(key).map(|cb| cb.state())
+    }
+    
     /// Get circuit state
     pub async fn get_state(&self, key: &str) -> Option<CircuitState> {
         let circuits = self.circuits.read().await;
@@ -321,6 +404,77 @@ mod tests {
         );
     }
     
+    fn flapping_config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            success_threshold: 1,
+            base_backoff: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(60),
+            rate_limit: 1000,
+        }
+    }
+    
+    #[test]
+    fn test_backoff_grows_on_failed_probes_and_caps() {
+        let mut cb = CircuitBreaker::new(flapping_config());
+        let mut now = Instant::now();
+        
+        cb.record_failure_at(now);
+        cb.record_failure_at(now);
+        assert_eq!(cb.state().state, CircuitState::Open);
+        
+        // Each failed probe doubles the backoff: 10s, 20s, 40s, then capped at 60s
+        let mut observed = vec![cb.state().backoff];
+        for _ in 0..4 {
+            let status = cb.state();
+            let next_probe_at = status.next_probe_at.unwrap();
+            assert!(next_probe_at <= now + status.backoff);
+            if next_probe_at > now {
+                assert!(cb.can_execute_at(now).is_err());
+            }
+            
+            now += status.backoff;
+            assert!(cb.can_execute_at(now).is_ok());
+            assert_eq!(cb.state().state, CircuitState::HalfOpen);
+            
+            cb.record_failure_at(now);
+            observed.push(cb.state().backoff);
+        }
+        
+        let secs: Vec<u64> = observed.iter().map(|d| d.as_secs()).collect();
+        assert_eq!(secs, vec![10, 20, 40, 60, 60]);
+    }
+    
+    #[test]
+    fn test_half_open_allows_single_probe_and_success_resets_backoff() {
+        let mut cb = CircuitBreaker::new(flapping_config());
+        let mut now = Instant::now();
+        
+        // Trip twice so the backoff has grown
+        cb.record_failure_at(now);
+        cb.record_failure_at(now);
+        now += cb.state().backoff;
+        cb.can_execute_at(now).unwrap();
+        cb.record_failure_at(now);
+        assert_eq!(cb.state().trips, 2);
+        
+        now += cb.state().backoff;
+        assert!(cb.can_execute_at(now).is_ok());
+        // Second concurrent request is rejected while the probe is in flight
+        assert!(matches!(cb.can_execute_at(now), Err(CircuitBreakerError::CircuitOpen)));
+        
+        cb.record_success_at(now);
+        let status = cb.state();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.trips, 0);
+        assert_eq!(status.next_probe_at, None);
+        
+        // Next trip starts again from the base backoff
+        cb.record_failure_at(now);
+        cb.record_failure_at(now);
+        assert_eq!(cb.state().backoff, Duration::from_secs(10));
+    }
+    
     #[test]
     fn test_workflow_guard_prevents_infinite_loops() {
         let mut guard = WorkflowExecutionGuard::new(uuid::Uuid::new_v4(), 5);
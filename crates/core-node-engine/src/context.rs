@@ -1,18 +1,32 @@
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use uuid::Uuid;
 
 use std::sync::Arc;
 use crate::ai::AiService;
 
 /// Execution context - holds values during graph execution
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionContext {
     pub values: HashMap<String, Value>,
     pub logs: Vec<Value>,
+    #[serde(skip)]
     pub ai_service: Option<Arc<dyn AiService>>,
     /// Data from the trigger that started this execution
+    #[serde(default)]
     pub trigger_data: Value,
+    /// Tenant that owns the running graph
+    #[serde(default)]
+    pub tenant_id: Uuid,
+    /// ID of the `graph_executions` row for this run
+    #[serde(default)]
+    pub execution_id: Uuid,
+    /// Correlation id of the request (or job) that started this run
+    #[serde(default)]
+    pub trace_id: Option<String>,
 }
 
 impl ExecutionContext {
@@ -29,5 +43,30 @@ impl ExecutionContext {
         self.trigger_data = data;
         self
     }
+    
+    pub fn with_execution(mut self, tenant_id: Uuid, execution_id: Uuid) -> Self {
+        self.tenant_id = tenant_id;
+        self.execution_id = execution_id;
+        self
+    }
+    
+    pub fn with_trace_id(mut self, trace_id: Option<String>) -> Self {
+        self.trace_id = trace_id;
+        self
+    }
 }
 
+/// Persisted state of a suspended graph run, stored on its `graph_executions` row
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionCheckpoint {
+    /// Accumulated values (node outputs keyed by node ID) and logs
+    pub context: ExecutionContext,
+    /// Nodes that already ran or were skipped
+    pub completed_node_ids: Vec<Uuid>,
+    /// Nodes still to run, in topological order
+    pub pending_node_ids: Vec<Uuid>,
+    /// Steps already counted against the max-step guard
+    pub steps: u32,
+    /// When the run should be picked up again
+    pub resume_at: Option<DateTime<Utc>>,
+}
@@ -11,8 +11,17 @@ pub enum NodeEngineError {
     #[error("Node not found: {0}")]
     NodeNotFound(Uuid),
 
-    #[error("Cycle detected in graph")]
-    CycleDetected,
+    #[error("Execution not found: {0}")]
+    ExecutionNotFound(Uuid),
+
+    #[error("Graph definition changed since execution {execution_id} was suspended: {node_ids:?}")]
+    GraphDefinitionChanged { execution_id: Uuid, node_ids: Vec<Uuid> },
+
+    #[error("Cycle detected in graph involving nodes: {node_ids:?}")]
+    GraphHasCycle { node_ids: Vec<Uuid> },
+
+    #[error("Graph execution exceeded maximum of {max_steps} steps")]
+    MaxStepsExceeded { max_steps: u32 },
 
     #[error("Node execution failed: {node_id} - {message}")]
     NodeExecutionFailed { node_id: Uuid, message: String },
@@ -20,6 +29,13 @@ pub enum NodeEngineError {
     #[error("Invalid port connection: {0}")]
     InvalidPortConnection(String),
 
+    #[error("Edge {edge_id} connects a {from_type} output to a {to_type} input")]
+    IncompatiblePorts {
+        edge_id: Uuid,
+        from_type: crate::ports::PortType,
+        to_type: crate::ports::PortType,
+    },
+
     #[error("Missing required input: {node_id}.{port}")]
     MissingInput { node_id: Uuid, port: String },
 
@@ -32,9 +48,15 @@ pub enum NodeEngineError {
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    #[error("Template error: {0}")]
+    Template(#[from] crate::template::TemplateError),
+
     #[error("Execution timeout")]
     Timeout,
 
+    #[error("Payment idempotency conflict: key {idempotency_key} was already used for a different amount")]
+    PaymentIdempotencyConflict { idempotency_key: String },
+
     #[error("Max retries exceeded")]
     MaxRetriesExceeded,
     
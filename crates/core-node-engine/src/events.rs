@@ -14,6 +14,8 @@ pub enum EventType {
     Update,
     /// Record was deleted
     Delete,
+    /// Soft-deleted record was restored
+    Restore,
     /// Custom event (user-defined)
     Custom(String),
 }
@@ -24,6 +26,7 @@ impl ToString for EventType {
             Self::Create => "create".to_string(),
             Self::Update => "update".to_string(),
             Self::Delete => "delete".to_string(),
+            Self::Restore => "restore".to_string(),
             Self::Custom(name) => format!("custom:{}", name),
         }
     }
@@ -50,6 +53,9 @@ pub struct EntityEvent {
     pub changed_fields: Option<Vec<String>>,
     /// When the event occurred
     pub occurred_at: DateTime<Utc>,
+    /// Correlation id of the request that caused the event
+    #[serde(default)]
+    pub trace_id: Option<String>,
 }
 
 impl EntityEvent {
@@ -72,6 +78,7 @@ impl EntityEvent {
             new_values: Some(new_values),
             changed_fields: None,
             occurred_at: Utc::now(),
+            trace_id: None,
         }
     }
 
@@ -96,6 +103,7 @@ impl EntityEvent {
             new_values: Some(new_values),
             changed_fields: Some(changed_fields),
             occurred_at: Utc::now(),
+            trace_id: None,
         }
     }
 
@@ -118,6 +126,30 @@ impl EntityEvent {
             new_values: None,
             changed_fields: None,
             occurred_at: Utc::now(),
+            trace_id: None,
+        }
+    }
+
+    /// Create a new restore event
+    pub fn restore(
+        tenant_id: Uuid,
+        entity_type: &str,
+        record_id: Uuid,
+        new_values: serde_json::Value,
+        triggered_by: Option<Uuid>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            entity_type: entity_type.to_string(),
+            record_id,
+            event_type: EventType::Restore,
+            triggered_by,
+            old_values: None,
+            new_values: Some(new_values),
+            changed_fields: None,
+            occurred_at: Utc::now(),
+            trace_id: None,
         }
     }
 
@@ -141,9 +173,16 @@ impl EntityEvent {
             new_values: Some(data),
             changed_fields: None,
             occurred_at: Utc::now(),
+            trace_id: None,
         }
     }
 
+    /// Tag the event with the correlation id of the request that caused it
+    pub fn with_trace_id(mut self, trace_id: Option<String>) -> Self {
+        self.trace_id = trace_id;
+        self
+    }
+
     /// Convert to trigger data for graph execution
     pub fn to_trigger_data(&self) -> serde_json::Value {
         serde_json::json!({
@@ -227,6 +266,7 @@ impl EventPublisher {
                 "create" => EventType::Create,
                 "update" => EventType::Update,
                 "delete" => EventType::Delete,
+                "restore" => EventType::Restore,
                 s if s.starts_with("custom:") => EventType::Custom(s[7..].to_string()),
                 _ => EventType::Create,
             };
@@ -245,6 +285,7 @@ impl EventPublisher {
                     .flatten()
                     .and_then(|v| serde_json::from_value(v).ok()),
                 occurred_at: row.try_get("occurred_at").unwrap_or_else(|_| Utc::now()),
+                trace_id: None,
             }
         }).collect();
 
@@ -3,24 +3,38 @@
 //! ## Antigravity Integration
 //! Now supports LogicOp for evaluating node visibility and enabled conditions.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use futures::stream::{self, StreamExt};
 use core_models::{
-    EdgeDef, ExecutionStatus, GraphExecution, NodeDef, NodeGraphDef,
+    EdgeDef, ExecutionStatus, GraphExecution, NodeDef, NodeGraphDef, NodeType,
     logic::{LogicOp, EvalContext},
 };
 use serde_json::Value;
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
-use crate::nodes::NodeRegistry;
+use crate::nodes::{NodeHandler, NodeRegistry};
 use crate::NodeEngineError;
-use crate::context::ExecutionContext;
+use crate::context::{ExecutionCheckpoint, ExecutionContext};
+use crate::for_each::{self, ForEachConfig};
+use crate::repository::NodeGraphRepository;
+use crate::http_request::HttpRequestHandler;
+use crate::wait_until_field::WaitUntilFieldHandler;
 
 use crate::ai::AiService;
 use std::sync::Arc;
 
+/// Default upper bound on node executions per graph run
+pub const DEFAULT_MAX_STEPS: u32 = 1000;
+
+/// Default number of nodes allowed to run at the same time
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
 /// Graph executor - runs node graphs
 /// Now with Antigravity LogicOp support for conditional node execution
 #[allow(dead_code)]
@@ -28,14 +42,24 @@ pub struct GraphExecutor {
     pool: PgPool,
     registry: NodeRegistry,
     ai_service: Option<Arc<dyn AiService>>,
+    /// Maximum node executions before a run is aborted
+    max_steps: u32,
+    /// Maximum nodes executed concurrently within a wave
+    max_concurrency: usize,
 }
 
 impl GraphExecutor {
     pub fn new(pool: PgPool) -> Self {
+        let mut registry = NodeRegistry::new();
+        registry.register(NodeType::WaitUntilField, Arc::new(WaitUntilFieldHandler::new(pool.clone())));
+        registry.register(NodeType::HttpRequest, Arc::new(HttpRequestHandler::new(pool.clone())));
+
         Self {
             pool,
-            registry: NodeRegistry::new(),
+            registry,
             ai_service: None,
+            max_steps: DEFAULT_MAX_STEPS,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
         }
     }
 
@@ -44,14 +68,59 @@ impl GraphExecutor {
         self
     }
 
+    /// Override the runtime step guard (defaults to `DEFAULT_MAX_STEPS`)
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Limit how many independent nodes run at once (defaults to `DEFAULT_MAX_CONCURRENCY`)
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Register or replace the handler for a node type
+    pub fn with_handler(mut self, node_type: NodeType, handler: Arc<dyn NodeHandler>) -> Self {
+        self.registry.register(node_type, handler);
+        self
+    }
+
+    /// Validate that a graph is acyclic and its edges connect compatible ports.
+    ///
+    /// Returns `GraphHasCycle` with the participating node IDs, or
+    /// `IncompatiblePorts` with the offending edge, so the editor can
+    /// highlight them.
+    pub fn validate_graph(nodes: &[NodeDef], edges: &[EdgeDef]) -> Result<(), NodeEngineError> {
+        Self::topological_sort(nodes, edges)?;
+        crate::ports::validate_port_types(nodes, edges)
+    }
+
     /// Execute a graph for a given trigger event
-    #[instrument(skip(self, trigger_data))]
+    ///
+    /// If a node suspends the run (e.g. a long delay), the checkpoint is
+    /// persisted and the returned execution has status `Suspended`.
     pub async fn execute(
         &self,
         graph: &NodeGraphDef,
         nodes: &[NodeDef],
         edges: &[EdgeDef],
         trigger_data: Value,
+    ) -> Result<GraphExecution, NodeEngineError> {
+        self.execute_traced(graph, nodes, edges, trigger_data, None).await
+    }
+
+    /// `execute`, carrying the correlation id of the operation that
+    /// triggered the run into its context, so jobs and plugins it starts
+    /// can be traced back to it
+    #[instrument(skip(self, trigger_data))]
+    pub async fn execute_traced(
+        &self,
+        graph: &NodeGraphDef,
+        nodes: &[NodeDef],
+        edges: &[EdgeDef],
+        trigger_data: Value,
+        trace_id: Option<String>,
     ) -> Result<GraphExecution, NodeEngineError> {
         info!(graph_id = %graph.id, "Starting graph execution");
 
@@ -72,60 +141,306 @@ impl GraphExecutor {
             log: serde_json::json!({ "steps": [] }),
         };
 
-        // Build execution context
-        let mut context = ExecutionContext {
-            values: HashMap::new(),
-            logs: Vec::new(),
-            ai_service: self.ai_service.clone(),
+        // Build execution context, initialized with trigger data
+        let mut context = ExecutionContext::new()
+            .with_trigger_data(trigger_data.clone())
+            .with_execution(graph.tenant_id, execution_id)
+            .with_trace_id(trace_id);
+        context.values.insert("$trigger".to_string(), trigger_data);
+
+        // Topological sort (rejects cyclic graphs at load time)
+        let sorted_nodes = Self::topological_sort(nodes, edges)?;
+        let checkpoint = ExecutionCheckpoint {
+            context,
+            pending_node_ids: sorted_nodes.iter().map(|n| n.id).collect(),
+            ..Default::default()
         };
 
-        // Initialize with trigger data
-        context.values.insert("$trigger".to_string(), trigger_data.clone());
+        if let Some(checkpoint) = self.run(graph, nodes, edges, &mut execution, checkpoint).await? {
+            self.save_execution(&execution, Some(&checkpoint)).await?;
+        }
+
+        Ok(execution)
+    }
+
+    /// Resume a suspended execution from its persisted checkpoint
+    ///
+    /// Fails with `GraphDefinitionChanged` if nodes were added or removed
+    /// since the run was suspended.
+    #[instrument(skip(self))]
+    pub async fn resume(&self, execution_id: Uuid) -> Result<GraphExecution, NodeEngineError> {
+        use sqlx::Row;
 
-        // Topological sort
-        let sorted_nodes = self.topological_sort(nodes, edges)?;
+        let row = sqlx::query(
+            r#"
+            SELECT
+                id, graph_id, tenant_id, trigger_event_id, trigger_record_id,
+                status, started_at, log, checkpoint
+            FROM graph_executions
+            WHERE id = $1
+            "#,
+        )
+        .bind(execution_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(NodeEngineError::ExecutionNotFound(execution_id))?;
 
-        // Execute nodes in order
-        for node in sorted_nodes {
-            // Check legacy is_enabled flag
-            if !node.is_enabled {
-                debug!(node_id = %node.id, "Skipping disabled node (legacy flag)");
-                continue;
+        let status_str: String = row.try_get("status")?;
+        let checkpoint: Option<Value> = row.try_get("checkpoint")?;
+        let checkpoint = match (status_str.as_str(), checkpoint) {
+            ("suspended", Some(checkpoint)) => serde_json::from_value::<ExecutionCheckpoint>(checkpoint)
+                .map_err(|e| NodeEngineError::InvalidInput(format!("Corrupt checkpoint: {}", e)))?,
+            _ => {
+                return Err(NodeEngineError::InvalidInput(format!(
+                    "Execution {} is not suspended",
+                    execution_id
+                )))
             }
-            
-            // Antigravity: Check LogicOp enabled_if condition from node config
-            if !self.evaluate_node_condition(&node, &trigger_data, &context) {
-                debug!(node_id = %node.id, "Skipping node (LogicOp enabled_if = false)");
-                context.logs.push(serde_json::json!({
-                    "node_id": node.id,
-                    "label": node.label,
-                    "status": "skipped",
-                    "reason": "enabled_if condition evaluated to false",
-                }));
-                continue;
+        };
+
+        let mut execution = GraphExecution {
+            id: row.try_get("id")?,
+            graph_id: row.try_get("graph_id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            trigger_event_id: row.try_get("trigger_event_id")?,
+            trigger_record_id: row.try_get("trigger_record_id")?,
+            status: ExecutionStatus::Running,
+            started_at: row.try_get("started_at")?,
+            completed_at: None,
+            error: None,
+            log: row.try_get("log")?,
+        };
+
+        let (graph, nodes, edges) = NodeGraphRepository::new(self.pool.clone())
+            .get_graph_complete(execution.tenant_id, execution.graph_id)
+            .await?;
+
+        info!(execution_id = %execution_id, graph_id = %graph.id, "Resuming graph execution");
+
+        match self.resume_checkpoint(&graph, &nodes, &edges, &mut execution, checkpoint).await {
+            Ok(next_checkpoint) => {
+                self.save_execution(&execution, next_checkpoint.as_ref()).await?;
+                Ok(execution)
+            }
+            Err(e) => {
+                execution.status = ExecutionStatus::Failed;
+                execution.error = Some(e.to_string());
+                execution.completed_at = Some(Utc::now());
+                self.save_execution(&execution, None).await?;
+                Err(e)
             }
+        }
+    }
 
-            let result = self.execute_node(&node, edges, &mut context).await;
+    /// Continue a run from a checkpoint after checking the graph still matches it
+    async fn resume_checkpoint(
+        &self,
+        graph: &NodeGraphDef,
+        nodes: &[NodeDef],
+        edges: &[EdgeDef],
+        execution: &mut GraphExecution,
+        checkpoint: ExecutionCheckpoint,
+    ) -> Result<Option<ExecutionCheckpoint>, NodeEngineError> {
+        let current: HashSet<Uuid> = nodes.iter().map(|n| n.id).collect();
+        let recorded: HashSet<Uuid> = checkpoint
+            .completed_node_ids
+            .iter()
+            .chain(checkpoint.pending_node_ids.iter())
+            .copied()
+            .collect();
 
-            match result {
-                Ok(output) => {
-                    context.values.insert(node.id.to_string(), output.clone());
+        let mut changed: Vec<Uuid> = current.symmetric_difference(&recorded).copied().collect();
+        if !changed.is_empty() {
+            changed.sort();
+            warn!(execution_id = %execution.id, changed = ?changed, "Graph definition changed since suspension");
+            return Err(NodeEngineError::GraphDefinitionChanged {
+                execution_id: execution.id,
+                node_ids: changed,
+            });
+        }
+
+        execution.status = ExecutionStatus::Running;
+        self.run(graph, nodes, edges, execution, checkpoint).await
+    }
+
+    /// Run pending nodes until the graph completes, fails or suspends.
+    ///
+    /// Returns the checkpoint to persist when a node suspended the run.
+    async fn run(
+        &self,
+        graph: &NodeGraphDef,
+        nodes: &[NodeDef],
+        edges: &[EdgeDef],
+        execution: &mut GraphExecution,
+        checkpoint: ExecutionCheckpoint,
+    ) -> Result<Option<ExecutionCheckpoint>, NodeEngineError> {
+        let ExecutionCheckpoint {
+            mut context,
+            completed_node_ids,
+            pending_node_ids,
+            mut steps,
+            ..
+        } = checkpoint;
+        context.ai_service = self.ai_service.clone();
+        let trigger_data = context.values.get("$trigger").cloned().unwrap_or(Value::Null);
+
+        let node_map: HashMap<Uuid, &NodeDef> = nodes.iter().map(|n| (n.id, n)).collect();
+        let node_ids: HashSet<Uuid> = node_map.keys().copied().collect();
+        let mut completed: HashSet<Uuid> = completed_node_ids.into_iter().collect();
+        let mut pending: Vec<NodeDef> = pending_node_ids
+            .iter()
+            .filter_map(|id| node_map.get(id).map(|n| (*n).clone()))
+            .collect();
+
+        // Execute in waves: every node whose upstream nodes have finished runs
+        // concurrently, then results are merged back in topological order.
+        while !pending.is_empty() {
+            let (ready, rest): (Vec<NodeDef>, Vec<NodeDef>) = pending.into_iter().partition(|node| {
+                edges
+                    .iter()
+                    .filter(|e| e.target_node_id == node.id)
+                    .all(|e| completed.contains(&e.source_node_id) || !node_ids.contains(&e.source_node_id))
+            });
+            pending = rest;
+
+            let mut wave = Vec::with_capacity(ready.len());
+            for node in ready {
+                // Runtime guard against loops introduced by dynamic edges
+                steps += 1;
+                if steps > self.max_steps {
+                    error!(graph_id = %graph.id, max_steps = self.max_steps, "Graph execution exceeded max steps");
+                    return Err(NodeEngineError::MaxStepsExceeded { max_steps: self.max_steps });
+                }
+
+                // Only reachable through branch ports that were not taken
+                if Self::branch_not_taken(&node, edges, &context) {
+                    debug!(node_id = %node.id, "Skipping node (branch not taken)");
+                    context.values.insert(node.id.to_string(), serde_json::json!({ "branch_not_taken": true }));
                     context.logs.push(serde_json::json!({
                         "node_id": node.id,
                         "label": node.label,
-                        "status": "success",
-                        "output": output,
+                        "status": "skipped",
+                        "reason": "branch not taken",
                     }));
+                    completed.insert(node.id);
+                    continue;
                 }
-                Err(e) => {
-                    error!(node_id = %node.id, error = %e, "Node execution failed");
-                    execution.status = ExecutionStatus::Failed;
-                    execution.error = Some(e.to_string());
-                    execution.completed_at = Some(Utc::now());
-                    execution.log = serde_json::json!({ "steps": context.logs });
-                    return Ok(execution);
+
+                // Check legacy is_enabled flag
+                if !node.is_enabled {
+                    debug!(node_id = %node.id, "Skipping disabled node (legacy flag)");
+                    completed.insert(node.id);
+                    continue;
+                }
+
+                // Antigravity: Check LogicOp enabled_if condition from node config
+                if !self.evaluate_node_condition(&node, &trigger_data, &context) {
+                    debug!(node_id = %node.id, "Skipping node (LogicOp enabled_if = false)");
+                    context.logs.push(serde_json::json!({
+                        "node_id": node.id,
+                        "label": node.label,
+                        "status": "skipped",
+                        "reason": "enabled_if condition evaluated to false",
+                    }));
+                    completed.insert(node.id);
+                    continue;
+                }
+
+                // Loops run their body per item in place of a handler
+                if node.node_type == NodeType::ForEach {
+                    let body = for_each::loop_body(node.id, edges);
+                    match self.run_for_each(graph, &node, nodes, edges, &body, &context).await {
+                        Ok(output) => {
+                            context.values.insert(node.id.to_string(), output.clone());
+                            context.logs.push(serde_json::json!({
+                                "node_id": node.id,
+                                "label": node.label,
+                                "status": "success",
+                                "output": output,
+                            }));
+                            completed.insert(node.id);
+                            pending.retain(|n| !body.contains(&n.id));
+                            for id in body {
+                                context.values.insert(id.to_string(), serde_json::json!({ "loop_body": node.id }));
+                                completed.insert(id);
+                            }
+                        }
+                        Err(e) => {
+                            error!(node_id = %node.id, error = %e, "Loop execution failed");
+                            execution.status = ExecutionStatus::Failed;
+                            execution.error = Some(e.to_string());
+                            execution.completed_at = Some(Utc::now());
+                            execution.log = serde_json::json!({ "steps": context.logs });
+                            return Ok(None);
+                        }
+                    }
+                    continue;
+                }
+
+                wave.push(node);
+            }
+
+            let outcomes = self.execute_wave(&wave, edges, &context).await?;
+            let snapshot = context.clone();
+            let mut writers: HashMap<String, Uuid> = HashMap::new();
+
+            let mut resume_at = None;
+            // Suspended nodes that run again on resume, e.g. to re-check what they wait for
+            let mut rerun: Vec<NodeDef> = Vec::new();
+
+            for (node, (result, node_context)) in wave.iter().zip(outcomes) {
+                match result {
+                    Ok(output) => {
+                        let suspends = output.get("suspend").and_then(|v| v.as_bool()).unwrap_or(false);
+                        if suspends {
+                            resume_at = Some(
+                                output
+                                    .get("resume_at")
+                                    .and_then(|v| v.as_str())
+                                    .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                                    .map(|v| v.with_timezone(&Utc))
+                                    .unwrap_or_else(Utc::now),
+                            );
+                        }
+                        let reruns = suspends && output.get("rerun_on_resume").and_then(|v| v.as_bool()).unwrap_or(false);
+                        self.merge_node_context(node, &snapshot, &node_context, &mut context, &mut writers);
+                        context.values.insert(node.id.to_string(), output.clone());
+                        context.logs.push(serde_json::json!({
+                            "node_id": node.id,
+                            "label": node.label,
+                            "status": if reruns { "waiting" } else { "success" },
+                            "output": output,
+                        }));
+                        if reruns {
+                            rerun.push(node.clone());
+                        } else {
+                            completed.insert(node.id);
+                        }
+                    }
+                    Err(e) => {
+                        error!(node_id = %node.id, error = %e, "Node execution failed");
+                        execution.status = ExecutionStatus::Failed;
+                        execution.error = Some(e.to_string());
+                        execution.completed_at = Some(Utc::now());
+                        execution.log = serde_json::json!({ "steps": context.logs });
+                        return Ok(None);
+                    }
                 }
             }
+
+            // A node asked to wait: checkpoint everything not yet run
+            if resume_at.is_some() {
+                info!(graph_id = %graph.id, execution_id = %execution.id, resume_at = ?resume_at, "Graph execution suspended");
+                execution.status = ExecutionStatus::Suspended;
+                execution.log = serde_json::json!({ "steps": context.logs });
+                return Ok(Some(ExecutionCheckpoint {
+                    context,
+                    completed_node_ids: completed.into_iter().collect(),
+                    pending_node_ids: rerun.iter().chain(pending.iter()).map(|n| n.id).collect(),
+                    steps,
+                    resume_at,
+                }));
+            }
         }
 
         execution.status = ExecutionStatus::Completed;
@@ -138,7 +453,207 @@ impl GraphExecutor {
             "Graph execution completed"
         );
 
-        Ok(execution)
+        Ok(None)
+    }
+
+    /// Run the body of a `for_each` node once per item and collect the results
+    ///
+    /// Each item's result is the output of the body's last node (or an object
+    /// keyed by node ID when the body ends in several nodes). A failed item
+    /// aborts the loop unless `continue_on_error` is set, in which case its
+    /// result is `null` and the error is listed under `errors`.
+    async fn run_for_each(
+        &self,
+        graph: &NodeGraphDef,
+        node: &NodeDef,
+        nodes: &[NodeDef],
+        edges: &[EdgeDef],
+        body: &HashSet<Uuid>,
+        context: &ExecutionContext,
+    ) -> Result<Value, NodeEngineError> {
+        let config = ForEachConfig::from_node(node)?;
+        let items = for_each::resolve_items(context, &config.items)?;
+        if items.len() > config.max_iterations {
+            return Err(NodeEngineError::NodeExecutionFailed {
+                node_id: node.id,
+                message: format!("{} items exceed max_iterations of {}", items.len(), config.max_iterations),
+            });
+        }
+
+        let body_nodes: Vec<NodeDef> = Self::topological_sort(nodes, edges)?
+            .into_iter()
+            .filter(|n| body.contains(&n.id))
+            .collect();
+        let body_edges: Vec<EdgeDef> = edges.iter().filter(|e| body.contains(&e.target_node_id)).cloned().collect();
+        let sinks: Vec<Uuid> = body_nodes
+            .iter()
+            .filter(|n| !body_edges.iter().any(|e| e.source_node_id == n.id))
+            .map(|n| n.id)
+            .collect();
+
+        let passes: Vec<BoxFuture<'_, Result<Value, String>>> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| self.run_iteration(graph, node, &body_nodes, &body_edges, &sinks, context, index, item))
+            .collect();
+        let mut iterations = stream::iter(passes).buffered(config.concurrency.unwrap_or(1).max(1));
+
+        let mut results = Vec::with_capacity(items.len());
+        let mut errors = Vec::new();
+        let mut index = 0;
+        while let Some(result) = iterations.next().await {
+            match result {
+                Ok(output) => results.push(output),
+                Err(message) if config.continue_on_error => {
+                    warn!(node_id = %node.id, index, error = %message, "Loop item failed, continuing");
+                    results.push(Value::Null);
+                    errors.push(serde_json::json!({ "index": index, "item": items[index], "error": message }));
+                }
+                Err(message) => {
+                    return Err(NodeEngineError::NodeExecutionFailed {
+                        node_id: node.id,
+                        message: format!("Item {} failed: {}", index, message),
+                    });
+                }
+            }
+            index += 1;
+        }
+
+        Ok(serde_json::json!({
+            "count": items.len(),
+            "results": results,
+            "errors": errors,
+        }))
+    }
+
+    /// One pass over a loop body with `$item` and `$index` bound
+    #[allow(clippy::too_many_arguments)]
+    fn run_iteration<'a>(
+        &'a self,
+        graph: &'a NodeGraphDef,
+        node: &'a NodeDef,
+        body_nodes: &'a [NodeDef],
+        body_edges: &'a [EdgeDef],
+        sinks: &'a [Uuid],
+        context: &'a ExecutionContext,
+        index: usize,
+        item: &'a Value,
+    ) -> BoxFuture<'a, Result<Value, String>> {
+        Box::pin(async move {
+            let mut iteration_context = context.clone();
+            iteration_context.logs.clear();
+            iteration_context.values.insert("$item".to_string(), item.clone());
+            iteration_context.values.insert("$index".to_string(), serde_json::json!(index));
+            // Body nodes wired to the `item` port receive the item as input
+            iteration_context
+                .values
+                .insert(node.id.to_string(), serde_json::json!({ "item": item, "index": index }));
+
+            let mut execution = GraphExecution {
+                id: context.execution_id,
+                graph_id: graph.id,
+                tenant_id: graph.tenant_id,
+                trigger_event_id: None,
+                trigger_record_id: None,
+                status: ExecutionStatus::Running,
+                started_at: Utc::now(),
+                completed_at: None,
+                error: None,
+                log: serde_json::json!({ "steps": [] }),
+            };
+            let checkpoint = ExecutionCheckpoint {
+                context: iteration_context,
+                pending_node_ids: body_nodes.iter().map(|n| n.id).collect(),
+                ..Default::default()
+            };
+
+            match self.run(graph, body_nodes, body_edges, &mut execution, checkpoint).await {
+                Err(e) => return Err(e.to_string()),
+                Ok(Some(_)) => return Err("nodes in a for_each body cannot suspend the run".to_string()),
+                Ok(None) if execution.status == ExecutionStatus::Failed => {
+                    return Err(execution.error.unwrap_or_default());
+                }
+                Ok(None) => {}
+            }
+
+            let steps = execution.log["steps"].as_array().cloned().unwrap_or_default();
+            let output_of = |id: &Uuid| {
+                steps
+                    .iter()
+                    .rev()
+                    .find(|s| s["node_id"] == serde_json::json!(id) && s["status"] == "success")
+                    .map(|s| s["output"].clone())
+                    .unwrap_or(Value::Null)
+            };
+            Ok(match sinks {
+                [sink] => output_of(sink),
+                _ => Value::Object(sinks.iter().map(|id| (id.to_string(), output_of(id))).collect()),
+            })
+        })
+    }
+
+    /// Upsert the execution row, storing the checkpoint while suspended
+    async fn save_execution(
+        &self,
+        execution: &GraphExecution,
+        checkpoint: Option<&ExecutionCheckpoint>,
+    ) -> Result<(), NodeEngineError> {
+        let status = serde_json::to_value(&execution.status)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default();
+        let checkpoint_json = checkpoint
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| NodeEngineError::InvalidInput(format!("Unserializable checkpoint: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO graph_executions (
+                id, graph_id, tenant_id, trigger_event_id, trigger_record_id,
+                status, started_at, completed_at, error, log, checkpoint, resume_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                completed_at = EXCLUDED.completed_at,
+                error = EXCLUDED.error,
+                log = EXCLUDED.log,
+                checkpoint = EXCLUDED.checkpoint,
+                resume_at = EXCLUDED.resume_at
+            "#,
+        )
+        .bind(execution.id)
+        .bind(execution.graph_id)
+        .bind(execution.tenant_id)
+        .bind(execution.trigger_event_id)
+        .bind(execution.trigger_record_id)
+        .bind(status)
+        .bind(execution.started_at)
+        .bind(execution.completed_at)
+        .bind(&execution.error)
+        .bind(&execution.log)
+        .bind(checkpoint_json)
+        .bind(checkpoint.and_then(|c| c.resume_at))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether every edge into `node` is dead: it leaves a branch node by
+    /// the port not taken, or leaves a node that was itself skipped that way
+    fn branch_not_taken(node: &NodeDef, edges: &[EdgeDef], context: &ExecutionContext) -> bool {
+        let mut incoming = edges.iter().filter(|e| e.target_node_id == node.id).peekable();
+        incoming.peek().is_some()
+            && incoming.all(|edge| match context.values.get(&edge.source_node_id.to_string()) {
+                Some(output) if output.get("branch_not_taken").and_then(|v| v.as_bool()) == Some(true) => true,
+                Some(output) => output
+                    .get("branch")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|port| port != edge.source_port),
+                None => false,
+            })
     }
 
     /// Evaluate node's enabled_if condition using Antigravity Logic Engine
@@ -181,19 +696,92 @@ impl GraphExecutor {
         enabled_if.evaluate(&eval_ctx)
     }
 
-    /// Execute a single node
-    async fn execute_node(
+    /// Execute a wave of independent nodes concurrently.
+    ///
+    /// Each node runs against its own snapshot of the context; outcomes are
+    /// returned in the same order as `wave` so merging stays deterministic.
+    async fn execute_wave(
         &self,
-        node: &NodeDef,
+        wave: &[NodeDef],
         edges: &[EdgeDef],
+        context: &ExecutionContext,
+    ) -> Result<Vec<(Result<Value, NodeEngineError>, ExecutionContext)>, NodeEngineError> {
+        let mut outcomes: Vec<Option<(Result<Value, NodeEngineError>, ExecutionContext)>> =
+            wave.iter().map(|_| None).collect();
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrency.max(1)));
+        let mut join_set = JoinSet::new();
+
+        for (index, node) in wave.iter().enumerate() {
+            // Gather inputs from connected nodes
+            let prepared = self
+                .gather_inputs(node, edges, context)
+                .and_then(|inputs| Ok((inputs, self.registry.get_handler(&node.node_type)?)));
+
+            let (inputs, handler) = match prepared {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    outcomes[index] = Some((Err(e), context.clone()));
+                    continue;
+                }
+            };
+
+            let node = node.clone();
+            let mut node_context = context.clone();
+            let semaphore = semaphore.clone();
+            join_set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let result = handler.execute(&node, inputs, &mut node_context).await;
+                (index, result, node_context)
+            });
+        }
+
+        while let Some(joined) = join_set.join_next().await {
+            let (index, result, node_context) = joined.map_err(|e| NodeEngineError::NodeExecutionFailed {
+                node_id: Uuid::nil(),
+                message: format!("Node task aborted: {}", e),
+            })?;
+            outcomes[index] = Some((result, node_context));
+        }
+
+        Ok(outcomes.into_iter().flatten().collect())
+    }
+
+    /// Merge values and logs a node wrote into its context snapshot.
+    ///
+    /// Keys written by more than one node in the same wave are reported as a
+    /// warning; the later node in topological order wins.
+    fn merge_node_context(
+        &self,
+        node: &NodeDef,
+        snapshot: &ExecutionContext,
+        node_context: &ExecutionContext,
         context: &mut ExecutionContext,
-    ) -> Result<Value, NodeEngineError> {
-        // Gather inputs from connected nodes
-        let inputs = self.gather_inputs(node, edges, context)?;
+        writers: &mut HashMap<String, Uuid>,
+    ) {
+        let mut written: Vec<(&String, &Value)> = node_context
+            .values
+            .iter()
+            .filter(|(key, value)| snapshot.values.get(*key) != Some(*value))
+            .collect();
+        written.sort_by(|a, b| a.0.cmp(b.0));
 
-        // Execute the node
-        let handler = self.registry.get_handler(&node.node_type)?;
-        handler.execute(node, inputs, context).await
+        for (key, value) in written {
+            if let Some(previous) = writers.insert(key.clone(), node.id) {
+                warn!(key = %key, first = %previous, second = %node.id, "Parallel nodes wrote the same context key");
+                context.logs.push(serde_json::json!({
+                    "node_id": node.id,
+                    "status": "warning",
+                    "reason": "context key written by multiple parallel nodes",
+                    "key": key,
+                    "node_ids": [previous, node.id],
+                }));
+            }
+            context.values.insert(key.clone(), value.clone());
+        }
+
+        context
+            .logs
+            .extend(node_context.logs.iter().skip(snapshot.logs.len()).cloned());
     }
 
     /// Gather inputs for a node from connected edges
@@ -207,9 +795,11 @@ impl GraphExecutor {
 
         // Find all edges targeting this node
         for edge in edges.iter().filter(|e| e.target_node_id == node.id) {
-            let source_value = context
-                .values
-                .get(&edge.source_node_id.to_string())
+            let source_output = context.values.get(&edge.source_node_id.to_string());
+            // A port named after a field of the output carries just that field
+            let source_value = source_output
+                .and_then(|output| output.get(&edge.source_port))
+                .or(source_output)
                 .cloned()
                 .unwrap_or(Value::Null);
 
@@ -221,24 +811,25 @@ impl GraphExecutor {
 
     /// Topological sort of nodes
     fn topological_sort(
-        &self,
         nodes: &[NodeDef],
         edges: &[EdgeDef],
     ) -> Result<Vec<NodeDef>, NodeEngineError> {
         let mut result = Vec::new();
         let mut visited = HashMap::new();
         let mut temp_visited = HashMap::new();
+        let mut path = Vec::new();
 
         let node_map: HashMap<_, _> = nodes.iter().map(|n| (n.id, n.clone())).collect();
 
         for node in nodes {
             if !visited.contains_key(&node.id) {
-                self.visit_node(
+                Self::visit_node(
                     &node.id,
                     &node_map,
                     edges,
                     &mut visited,
                     &mut temp_visited,
+                    &mut path,
                     &mut result,
                 )?;
             }
@@ -248,16 +839,20 @@ impl GraphExecutor {
     }
 
     fn visit_node(
-        &self,
         node_id: &Uuid,
         node_map: &HashMap<Uuid, NodeDef>,
         edges: &[EdgeDef],
         visited: &mut HashMap<Uuid, bool>,
         temp_visited: &mut HashMap<Uuid, bool>,
+        path: &mut Vec<Uuid>,
         result: &mut Vec<NodeDef>,
     ) -> Result<(), NodeEngineError> {
         if temp_visited.get(node_id).copied().unwrap_or(false) {
-            return Err(NodeEngineError::CycleDetected);
+            // Back-edge: everything on the path from the first visit is in the cycle
+            let start = path.iter().position(|id| id == node_id).unwrap_or(0);
+            return Err(NodeEngineError::GraphHasCycle {
+                node_ids: path[start..].to_vec(),
+            });
         }
 
         if visited.get(node_id).copied().unwrap_or(false) {
@@ -265,19 +860,22 @@ impl GraphExecutor {
         }
 
         temp_visited.insert(*node_id, true);
+        path.push(*node_id);
 
         // Visit all dependencies (nodes that feed into this one)
         for edge in edges.iter().filter(|e| e.target_node_id == *node_id) {
-            self.visit_node(
+            Self::visit_node(
                 &edge.source_node_id,
                 node_map,
                 edges,
                 visited,
                 temp_visited,
+                path,
                 result,
             )?;
         }
 
+        path.pop();
         temp_visited.insert(*node_id, false);
         visited.insert(*node_id, true);
 
@@ -289,4 +887,497 @@ impl GraphExecutor {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::time::{Duration, Instant};
+
+    fn node(id: Uuid) -> NodeDef {
+        NodeDef {
+            id,
+            graph_id: Uuid::nil(),
+            node_type: NodeType::TriggerOnCreate,
+            label: "Test".to_string(),
+            x: 0.0,
+            y: 0.0,
+            config: serde_json::json!({}),
+            is_enabled: true,
+        }
+    }
+
+    fn edge(source: Uuid, target: Uuid) -> EdgeDef {
+        EdgeDef {
+            id: Uuid::new_v4(),
+            graph_id: Uuid::nil(),
+            source_node_id: source,
+            source_port: "out".to_string(),
+            target_node_id: target,
+            target_port: "in".to_string(),
+            label: None,
+        }
+    }
+
+    fn test_graph() -> NodeGraphDef {
+        NodeGraphDef {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "test".to_string(),
+            label: "Test".to_string(),
+            description: None,
+            scope: core_models::GraphScope::Global,
+            graph_type: core_models::GraphType::Logic,
+            entity_type_id: None,
+            app_id: None,
+            is_enabled: true,
+            version: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_two_node_cycle_detected() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let nodes = vec![node(a), node(b)];
+        let edges = vec![edge(a, b), edge(b, a)];
+
+        match GraphExecutor::validate_graph(&nodes, &edges) {
+            Err(NodeEngineError::GraphHasCycle { mut node_ids }) => {
+                node_ids.sort();
+                let mut expected = vec![a, b];
+                expected.sort();
+                assert_eq!(node_ids, expected);
+            }
+            other => panic!("Expected GraphHasCycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_self_loop_detected() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let nodes = vec![node(a), node(b)];
+        let edges = vec![edge(a, b), edge(b, b)];
+
+        match GraphExecutor::validate_graph(&nodes, &edges) {
+            Err(NodeEngineError::GraphHasCycle { node_ids }) => assert_eq!(node_ids, vec![b]),
+            other => panic!("Expected GraphHasCycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_acyclic_graph_is_valid() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let nodes = vec![node(a), node(b), node(c)];
+        let edges = vec![edge(a, b), edge(a, c), edge(b, c)];
+
+        assert!(GraphExecutor::validate_graph(&nodes, &edges).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_max_steps_guard_aborts_run() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool).with_max_steps(2);
+        let nodes = vec![node(Uuid::new_v4()), node(Uuid::new_v4()), node(Uuid::new_v4())];
+
+        let result = executor.execute(&test_graph(), &nodes, &[], serde_json::json!({})).await;
+
+        assert!(matches!(result, Err(NodeEngineError::MaxStepsExceeded { max_steps: 2 })));
+    }
+
+    /// Test handler that sleeps before echoing its label
+    struct SleepHandler;
+
+    #[async_trait]
+    impl NodeHandler for SleepHandler {
+        async fn execute(
+            &self,
+            node: &NodeDef,
+            _inputs: HashMap<String, Value>,
+            context: &mut ExecutionContext,
+        ) -> Result<Value, NodeEngineError> {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            context.values.insert("shared".to_string(), serde_json::json!(node.label));
+            Ok(serde_json::json!(node.label))
+        }
+    }
+
+    fn sleep_node(label: &str) -> NodeDef {
+        NodeDef {
+            node_type: NodeType::ActionDelay,
+            label: label.to_string(),
+            ..node(Uuid::new_v4())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_independent_branches_run_in_parallel() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool).with_handler(NodeType::ActionDelay, Arc::new(SleepHandler));
+        let trigger = node(Uuid::new_v4());
+        let (left, right) = (sleep_node("left"), sleep_node("right"));
+        let edges = vec![edge(trigger.id, left.id), edge(trigger.id, right.id)];
+        let nodes = vec![trigger, left, right];
+
+        let started = Instant::now();
+        let execution = executor
+            .execute(&test_graph(), &nodes, &edges, serde_json::json!({}))
+            .await
+            .unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(execution.status, ExecutionStatus::Completed);
+        assert!(elapsed < Duration::from_millis(180), "took {:?}", elapsed);
+
+        // Both branches wrote "shared", which must be surfaced as a warning
+        let steps = execution.log["steps"].as_array().unwrap();
+        assert!(steps.iter().any(|s| s["status"] == "warning" && s["key"] == "shared"));
+    }
+
+    fn delay_node(minutes: u64) -> NodeDef {
+        NodeDef {
+            node_type: NodeType::ActionDelay,
+            label: "Wait".to_string(),
+            config: serde_json::json!({ "minutes": minutes }),
+            ..node(Uuid::new_v4())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_suspend_at_delay_and_resume() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool).with_handler(NodeType::ActionDelay, Arc::new(crate::nodes::DelayHandler));
+        let graph = test_graph();
+        let (trigger, wait, follow_up) = (node(Uuid::new_v4()), delay_node(30), node(Uuid::new_v4()));
+        let edges = vec![edge(trigger.id, wait.id), edge(wait.id, follow_up.id)];
+        let nodes = vec![trigger.clone(), wait.clone(), follow_up.clone()];
+
+        let mut execution = GraphExecution {
+            id: Uuid::new_v4(),
+            graph_id: graph.id,
+            tenant_id: graph.tenant_id,
+            trigger_event_id: None,
+            trigger_record_id: None,
+            status: ExecutionStatus::Running,
+            started_at: Utc::now(),
+            completed_at: None,
+            error: None,
+            log: serde_json::json!({ "steps": [] }),
+        };
+        let mut context = ExecutionContext::new();
+        context.values.insert("$trigger".to_string(), serde_json::json!({ "id": 1 }));
+        let checkpoint = ExecutionCheckpoint {
+            context,
+            pending_node_ids: vec![trigger.id, wait.id, follow_up.id],
+            ..Default::default()
+        };
+
+        let checkpoint = executor
+            .run(&graph, &nodes, &edges, &mut execution, checkpoint)
+            .await
+            .unwrap()
+            .expect("delay node should suspend the run");
+
+        assert_eq!(execution.status, ExecutionStatus::Suspended);
+        assert_eq!(checkpoint.pending_node_ids, vec![follow_up.id]);
+        assert!(checkpoint.resume_at.unwrap() > Utc::now());
+
+        // Round-trip through JSON as the graph_executions row would
+        let stored = serde_json::to_value(&checkpoint).unwrap();
+        let restored: ExecutionCheckpoint = serde_json::from_value(stored).unwrap();
+
+        let next = executor
+            .resume_checkpoint(&graph, &nodes, &edges, &mut execution, restored)
+            .await
+            .unwrap();
+
+        assert!(next.is_none());
+        assert_eq!(execution.status, ExecutionStatus::Completed);
+        let steps = execution.log["steps"].as_array().unwrap();
+        assert!(steps.iter().any(|s| s["node_id"] == serde_json::json!(follow_up.id) && s["status"] == "success"));
+        // The delay node is not executed again
+        assert_eq!(steps.iter().filter(|s| s["node_id"] == serde_json::json!(wait.id)).count(), 1);
+    }
+
+    /// trigger → branch(stage == "won") → true: `won`, false: `lost`, both → `done`
+    async fn run_stage_branch(stage: &str) -> Vec<(String, String)> {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool);
+        let trigger = node(Uuid::new_v4());
+        let branch = NodeDef {
+            node_type: NodeType::Branch,
+            label: "Won?".to_string(),
+            config: serde_json::json!({
+                "condition": {"op": "and", "args": [
+                    {"op": "equals", "args": {"field": "stage", "value": "won"}},
+                    {"op": "gt", "args": {"field": "$trigger.new_values.amount", "value": 0.0}}
+                ]}
+            }),
+            ..node(Uuid::new_v4())
+        };
+        let labelled = |label: &str| NodeDef { label: label.to_string(), ..node(Uuid::new_v4()) };
+        let (won, won_follow_up, lost, done) = (labelled("won"), labelled("won_follow_up"), labelled("lost"), labelled("done"));
+        let port = |source: &NodeDef, port: &str, target: &NodeDef| EdgeDef {
+            source_port: port.to_string(),
+            ..edge(source.id, target.id)
+        };
+        let edges = vec![
+            edge(trigger.id, branch.id),
+            port(&branch, "true", &won),
+            edge(won.id, won_follow_up.id),
+            port(&branch, "false", &lost),
+            edge(won_follow_up.id, done.id),
+            edge(lost.id, done.id),
+        ];
+        let nodes = vec![trigger, branch, won, won_follow_up, lost, done];
+        let trigger_data = serde_json::json!({ "new_values": { "stage": stage, "amount": 1200 } });
+
+        let execution = executor.execute(&test_graph(), &nodes, &edges, trigger_data).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Completed);
+        execution.log["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|s| s["label"] != "Test" && s["label"] != "Won?")
+            .map(|s| (s["label"].as_str().unwrap().to_string(), s["status"].as_str().unwrap().to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_branch_takes_true_path() {
+        let steps = run_stage_branch("won").await;
+        let status = |label: &str| steps.iter().find(|(l, _)| l == label).map(|(_, s)| s.as_str());
+        assert_eq!(status("won"), Some("success"));
+        assert_eq!(status("won_follow_up"), Some("success"));
+        assert_eq!(status("lost"), Some("skipped"));
+        // Paths join again: one live edge is enough
+        assert_eq!(status("done"), Some("success"));
+    }
+
+    #[tokio::test]
+    async fn test_branch_takes_false_path() {
+        let steps = run_stage_branch("lost").await;
+        let status = |label: &str| steps.iter().find(|(l, _)| l == label).map(|(_, s)| s.as_str());
+        assert_eq!(status("won"), Some("skipped"));
+        // Skipping carries through nodes only reachable from the untaken port
+        assert_eq!(status("won_follow_up"), Some("skipped"));
+        assert_eq!(status("lost"), Some("success"));
+        assert_eq!(status("done"), Some("success"));
+    }
+
+    fn wait_until_node() -> NodeDef {
+        NodeDef {
+            node_type: NodeType::WaitUntilField,
+            label: "Until close date".to_string(),
+            config: serde_json::json!({ "field": "expected_close_date" }),
+            ..node(Uuid::new_v4())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_field_suspends_until_future_date() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool);
+        let graph = test_graph();
+        let (trigger, wait, follow_up) = (node(Uuid::new_v4()), wait_until_node(), node(Uuid::new_v4()));
+        let edges = vec![edge(trigger.id, wait.id), edge(wait.id, follow_up.id)];
+        let nodes = vec![trigger.clone(), wait.clone(), follow_up.clone()];
+        let close = Utc::now() + chrono::Duration::days(3);
+
+        let mut execution = GraphExecution {
+            id: Uuid::new_v4(),
+            graph_id: graph.id,
+            tenant_id: graph.tenant_id,
+            trigger_event_id: None,
+            trigger_record_id: None,
+            status: ExecutionStatus::Running,
+            started_at: Utc::now(),
+            completed_at: None,
+            error: None,
+            log: serde_json::json!({ "steps": [] }),
+        };
+        let mut context = ExecutionContext::new();
+        context.values.insert(
+            "$trigger".to_string(),
+            serde_json::json!({ "record_id": Uuid::new_v4(), "new_values": { "expected_close_date": close.to_rfc3339() } }),
+        );
+        let checkpoint = ExecutionCheckpoint {
+            context,
+            pending_node_ids: vec![trigger.id, wait.id, follow_up.id],
+            ..Default::default()
+        };
+
+        let checkpoint = executor
+            .run(&graph, &nodes, &edges, &mut execution, checkpoint)
+            .await
+            .unwrap()
+            .expect("a future date should suspend the run");
+
+        assert_eq!(execution.status, ExecutionStatus::Suspended);
+        assert_eq!(checkpoint.resume_at.map(|r| r.timestamp()), Some(close.timestamp()));
+        // The wait node runs again on resume to re-read the field
+        assert_eq!(checkpoint.pending_node_ids, vec![wait.id, follow_up.id]);
+        assert_eq!(checkpoint.completed_node_ids, vec![trigger.id]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_field_proceeds_for_past_date() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool);
+        let (trigger, wait, follow_up) = (node(Uuid::new_v4()), wait_until_node(), node(Uuid::new_v4()));
+        let edges = vec![edge(trigger.id, wait.id), edge(wait.id, follow_up.id)];
+        let nodes = vec![trigger, wait, follow_up.clone()];
+        let trigger_data = serde_json::json!({ "new_values": { "expected_close_date": "2020-01-15" } });
+
+        let execution = executor.execute(&test_graph(), &nodes, &edges, trigger_data).await.unwrap();
+
+        assert_eq!(execution.status, ExecutionStatus::Completed);
+        let steps = execution.log["steps"].as_array().unwrap();
+        assert!(steps.iter().any(|s| s["node_id"] == serde_json::json!(follow_up.id) && s["status"] == "success"));
+    }
+
+    /// Greets `$item`, failing for items marked `fail`
+    struct GreetItemHandler;
+
+    #[async_trait]
+    impl NodeHandler for GreetItemHandler {
+        async fn execute(
+            &self,
+            node: &NodeDef,
+            inputs: HashMap<String, Value>,
+            context: &mut ExecutionContext,
+        ) -> Result<Value, NodeEngineError> {
+            let item = context.values.get("$item").cloned().unwrap_or(Value::Null);
+            if item["fail"] == true {
+                return Err(NodeEngineError::NodeExecutionFailed {
+                    node_id: node.id,
+                    message: format!("cannot greet {}", item["name"]),
+                });
+            }
+            assert_eq!(inputs["in"], item);
+            Ok(serde_json::json!({ "greeting": format!("Hi {}", item["name"].as_str().unwrap_or_default()) }))
+        }
+    }
+
+    /// trigger → for_each(contacts) → item: `greet`, done: `after`
+    async fn run_contact_loop(contacts: Value, config: Value) -> GraphExecution {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool).with_handler(NodeType::ActionSendSms, Arc::new(GreetItemHandler));
+        let trigger = node(Uuid::new_v4());
+        let mut loop_config = serde_json::json!({ "items": "$trigger.new_values.contacts" });
+        loop_config.as_object_mut().unwrap().extend(config.as_object().unwrap().clone());
+        let each = NodeDef {
+            node_type: NodeType::ForEach,
+            label: "Each contact".to_string(),
+            config: loop_config,
+            ..node(Uuid::new_v4())
+        };
+        let greet = NodeDef {
+            node_type: NodeType::ActionSendSms,
+            label: "Greet".to_string(),
+            ..node(Uuid::new_v4())
+        };
+        let after = NodeDef { label: "After".to_string(), ..node(Uuid::new_v4()) };
+        let port = |source: &NodeDef, port: &str, target: &NodeDef| EdgeDef {
+            source_port: port.to_string(),
+            ..edge(source.id, target.id)
+        };
+        let edges = vec![edge(trigger.id, each.id), port(&each, "item", &greet), port(&each, "done", &after)];
+        let nodes = vec![trigger, each, greet, after];
+        let trigger_data = serde_json::json!({ "new_values": { "contacts": contacts } });
+
+        executor.execute(&test_graph(), &nodes, &edges, trigger_data).await.unwrap()
+    }
+
+    fn loop_output(execution: &GraphExecution) -> Value {
+        let steps = execution.log["steps"].as_array().unwrap();
+        steps.iter().find(|s| s["label"] == "Each contact").map(|s| s["output"].clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_for_each_runs_body_per_item() {
+        let contacts = serde_json::json!([{ "name": "Amal" }, { "name": "Badr" }, { "name": "Hana" }]);
+
+        let execution = run_contact_loop(contacts, serde_json::json!({ "concurrency": 2 })).await;
+
+        assert_eq!(execution.status, ExecutionStatus::Completed);
+        let output = loop_output(&execution);
+        assert_eq!(output["count"], 3);
+        let greetings: Vec<&str> = output["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["greeting"].as_str().unwrap())
+            .collect();
+        assert_eq!(greetings, vec!["Hi Amal", "Hi Badr", "Hi Hana"]);
+        // The done port runs once, after the loop; the body is not run again
+        let steps = execution.log["steps"].as_array().unwrap();
+        assert_eq!(steps.iter().filter(|s| s["label"] == "After" && s["status"] == "success").count(), 1);
+        assert!(!steps.iter().any(|s| s["label"] == "Greet"));
+    }
+
+    #[tokio::test]
+    async fn test_for_each_max_iterations_guard() {
+        let contacts = serde_json::json!([{ "name": "Amal" }, { "name": "Badr" }, { "name": "Hana" }]);
+
+        let execution = run_contact_loop(contacts, serde_json::json!({ "max_iterations": 2 })).await;
+
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert!(execution.error.unwrap().contains("3 items exceed max_iterations of 2"));
+    }
+
+    #[tokio::test]
+    async fn test_for_each_continue_on_error_collects_partial_results() {
+        let contacts = serde_json::json!([{ "name": "Amal" }, { "name": "Badr", "fail": true }, { "name": "Hana" }]);
+
+        let execution = run_contact_loop(contacts.clone(), serde_json::json!({ "continue_on_error": true })).await;
 
+        assert_eq!(execution.status, ExecutionStatus::Completed);
+        let output = loop_output(&execution);
+        assert_eq!(output["results"][0]["greeting"], "Hi Amal");
+        assert_eq!(output["results"][1], Value::Null);
+        assert_eq!(output["results"][2]["greeting"], "Hi Hana");
+        assert_eq!(output["errors"].as_array().unwrap().len(), 1);
+        assert_eq!(output["errors"][0]["index"], 1);
+
+        // Without the flag the first failure aborts the run
+        let execution = run_contact_loop(contacts, serde_json::json!({})).await;
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert!(execution.error.unwrap().contains("Item 1 failed"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_rejects_changed_graph() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = GraphExecutor::new(pool);
+        let graph = test_graph();
+        let (done, removed) = (Uuid::new_v4(), Uuid::new_v4());
+        let nodes = vec![node(done)];
+        let checkpoint = ExecutionCheckpoint {
+            completed_node_ids: vec![done],
+            pending_node_ids: vec![removed],
+            ..Default::default()
+        };
+        let mut execution = GraphExecution {
+            id: Uuid::new_v4(),
+            graph_id: graph.id,
+            tenant_id: graph.tenant_id,
+            trigger_event_id: None,
+            trigger_record_id: None,
+            status: ExecutionStatus::Suspended,
+            started_at: Utc::now(),
+            completed_at: None,
+            error: None,
+            log: serde_json::json!({ "steps": [] }),
+        };
+
+        let result = executor
+            .resume_checkpoint(&graph, &nodes, &[], &mut execution, checkpoint)
+            .await;
+
+        match result {
+            Err(NodeEngineError::GraphDefinitionChanged { node_ids, .. }) => assert_eq!(node_ids, vec![removed]),
+            other => panic!("Expected GraphDefinitionChanged, got {:?}", other),
+        }
+    }
+}
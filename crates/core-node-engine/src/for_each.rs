@@ -0,0 +1,148 @@
+//! For-each node - run part of a graph once per item of a collection
+//!
+//! Node config:
+//! - `items`: context path of the array, e.g. `$trigger.new_values.contacts`
+//! - `max_iterations`: more items than this fails the node (default 100)
+//! - `concurrency`: items processed at the same time (default 1)
+//! - `continue_on_error`: collect failed items instead of aborting the loop
+//!
+//! The loop body is every node reachable from the node's `item` port. The
+//! executor runs the body per item with `$item` and `$index` bound in the
+//! context, and the for-each output collects the body's results in item
+//! order. Nodes on other ports run once, after the loop.
+
+use core_models::{EdgeDef, NodeDef};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::context::ExecutionContext;
+use crate::NodeEngineError;
+
+/// Port whose downstream nodes form the loop body
+pub const ITEM_PORT: &str = "item";
+
+/// Default cap on the number of items a for-each node accepts
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ForEachConfig {
+    pub items: String,
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: usize,
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+fn default_max_iterations() -> usize {
+    DEFAULT_MAX_ITERATIONS
+}
+
+impl ForEachConfig {
+    pub fn from_node(node: &NodeDef) -> Result<Self, NodeEngineError> {
+        serde_json::from_value(node.config.clone())
+            .map_err(|e| NodeEngineError::InvalidConfig(format!("Invalid for_each config: {}", e)))
+    }
+}
+
+/// Nodes reachable from the `item` port of `node_id`
+pub fn loop_body(node_id: Uuid, edges: &[EdgeDef]) -> HashSet<Uuid> {
+    let mut body = HashSet::new();
+    let mut frontier: Vec<Uuid> = edges
+        .iter()
+        .filter(|e| e.source_node_id == node_id && e.source_port == ITEM_PORT)
+        .map(|e| e.target_node_id)
+        .collect();
+
+    while let Some(id) = frontier.pop() {
+        if id != node_id && body.insert(id) {
+            frontier.extend(edges.iter().filter(|e| e.source_node_id == id).map(|e| e.target_node_id));
+        }
+    }
+    body
+}
+
+/// The array at `path` in the context: a value key, then a dotted path into it.
+/// A missing or `null` value is an empty collection.
+pub fn resolve_items(context: &ExecutionContext, path: &str) -> Result<Vec<Value>, NodeEngineError> {
+    let value = match context.values.get(path) {
+        Some(value) => Some(value),
+        None => {
+            let mut segments = path.split('.');
+            let first = segments.next().and_then(|key| context.values.get(key));
+            segments.try_fold(first, |current, key| {
+                Some(match current? {
+                    Value::Object(map) => map.get(key),
+                    Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                    _ => None,
+                })
+            })
+            .flatten()
+        }
+    };
+
+    match value {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Array(items)) => Ok(items.clone()),
+        Some(other) => Err(NodeEngineError::TypeMismatch {
+            expected: "array".to_string(),
+            actual: type_name(other).to_string(),
+        }),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edge(source: Uuid, port: &str, target: Uuid) -> EdgeDef {
+        EdgeDef {
+            id: Uuid::new_v4(),
+            graph_id: Uuid::nil(),
+            source_node_id: source,
+            source_port: port.to_string(),
+            target_node_id: target,
+            target_port: "in".to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_loop_body_follows_item_port_only() {
+        let (each, a, b, done) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let edges = vec![edge(each, ITEM_PORT, a), edge(a, "out", b), edge(each, "done", done)];
+
+        assert_eq!(loop_body(each, &edges), HashSet::from([a, b]));
+    }
+
+    #[test]
+    fn test_resolve_items_by_path() {
+        let mut context = ExecutionContext::new();
+        context.values.insert(
+            "$trigger".to_string(),
+            json!({ "new_values": { "contacts": [{ "id": 1 }, { "id": 2 }], "name": "Deal" } }),
+        );
+
+        assert_eq!(resolve_items(&context, "$trigger.new_values.contacts").unwrap().len(), 2);
+        assert!(resolve_items(&context, "$trigger.new_values.missing").unwrap().is_empty());
+        assert!(matches!(
+            resolve_items(&context, "$trigger.new_values.name"),
+            Err(NodeEngineError::TypeMismatch { .. })
+        ));
+    }
+}
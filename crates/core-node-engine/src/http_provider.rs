@@ -0,0 +1,602 @@
+//! Generic HTTP Provider - call arbitrary third-party APIs from workflows
+//!
+//! Node config:
+//! - `method`, `url`, `headers`, `body`: `{{...}}` templates rendered against
+//!   the node inputs, trigger data and execution values
+//! - `signing`: optional HMAC-SHA256 signature of the request body
+//! - `oauth2`: optional client-credentials token, cached until expiry
+//! - `timeout_ms`, `max_retries`, `retry_backoff_ms`, `max_response_bytes`
+//!
+//! The URL and any token URL must resolve to public addresses (see
+//! `core_integrations::egress`); redirects are never followed.
+//!
+//! 5xx responses and network errors are retried with exponential backoff.
+//! The response JSON is returned as the node output's `body`. The
+//! `http_request` node builds on the same request handling.
+
+use async_trait::async_trait;
+use core_integrations::egress::EgressPolicy;
+use core_models::NodeDef;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::context::ExecutionContext;
+use crate::nodes::NodeHandler;
+use crate::template::{render_template, template_context};
+use crate::NodeEngineError;
+
+/// Refresh tokens this long before the provider says they expire
+const TOKEN_EXPIRY_SKEW: Duration = Duration::from_secs(30);
+
+/// Request settings read from node config
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpRequestConfig {
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// String template, or JSON whose string leaves are templates
+    #[serde(default)]
+    pub body: Option<Value>,
+    #[serde(default)]
+    pub signing: Option<SigningConfig>,
+    #[serde(default)]
+    pub oauth2: Option<OAuth2Config>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    #[serde(default = "default_max_response_bytes")]
+    pub max_response_bytes: usize,
+}
+
+/// HMAC request signing: `<header>: sha256=<hex of HMAC-SHA256(secret, body)>`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SigningConfig {
+    pub secret: String,
+    #[serde(default = "default_signature_header")]
+    pub header: String,
+}
+
+/// OAuth2 client-credentials grant
+#[derive(Debug, Clone, Deserialize)]
+pub struct OAuth2Config {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_response_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_signature_header() -> String {
+    "X-Signature".to_string()
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// Workflow node that calls an arbitrary HTTP API
+pub struct GenericHttpProvider {
+    egress: EgressPolicy,
+    /// Access tokens keyed by token URL, client and scope
+    tokens: Mutex<HashMap<String, CachedToken>>,
+}
+
+impl GenericHttpProvider {
+    pub fn new() -> Self {
+        Self {
+            egress: EgressPolicy::public_only(),
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Call URLs whatever they resolve to; for tests against local mock
+    /// servers
+    pub fn with_egress(mut self, egress: EgressPolicy) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    /// A client pinned to `url`'s checked addresses
+    async fn client_for(&self, url: &str) -> Result<reqwest::Client, String> {
+        let target = self.egress.check(url).await.map_err(|e| e.to_string())?;
+        target.client_builder().build().map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+
+    /// Get a cached access token, fetching a new one if missing, expired or `force_refresh`
+    async fn access_token(&self, oauth: &OAuth2Config, timeout: Duration, force_refresh: bool) -> Result<String, String> {
+        let cache_key = format!("{}|{}|{}", oauth.token_url, oauth.client_id, oauth.scope.as_deref().unwrap_or(""));
+        let mut tokens = self.tokens.lock().await;
+
+        if !force_refresh {
+            if let Some(token) = tokens.get(&cache_key) {
+                if Instant::now() < token.expires_at {
+                    return Ok(token.access_token.clone());
+                }
+            }
+        }
+
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", oauth.client_id.as_str()),
+            ("client_secret", oauth.client_secret.as_str()),
+        ];
+        if let Some(scope) = &oauth.scope {
+            form.push(("scope", scope.as_str()));
+        }
+
+        let client = self.client_for(&oauth.token_url).await?;
+        let response = client
+            .post(&oauth.token_url)
+            .form(&form)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| format!("Token request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Token endpoint returned {}", response.status()));
+        }
+
+        let json: Value = response.json().await
+            .map_err(|e| format!("Invalid token response: {}", e))?;
+        let access_token = json["access_token"].as_str()
+            .ok_or("Token response missing access_token")?
+            .to_string();
+        let expires_in = Duration::from_secs(json["expires_in"].as_u64().unwrap_or(3600));
+
+        info!(token_url = %oauth.token_url, "Acquired OAuth2 access token");
+        tokens.insert(cache_key, CachedToken {
+            access_token: access_token.clone(),
+            expires_at: Instant::now() + expires_in.saturating_sub(TOKEN_EXPIRY_SKEW),
+        });
+
+        Ok(access_token)
+    }
+}
+
+impl Default for GenericHttpProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl NodeHandler for GenericHttpProvider {
+    async fn execute(
+        &self,
+        node: &NodeDef,
+        inputs: HashMap<String, Value>,
+        context: &mut ExecutionContext,
+    ) -> Result<Value, NodeEngineError> {
+        let config = HttpRequestConfig::from_node(node)?;
+        let request = PreparedRequest::render(&config, &inputs, context)?;
+        let response = self.send(node, &config, &request).await?;
+
+        if !response.is_success() {
+            return Err(NodeEngineError::NodeExecutionFailed {
+                node_id: node.id,
+                message: format!(
+                    "HTTP {} from {} after {} attempts: {}",
+                    response.status, request.url, response.attempts, response.body
+                ),
+            });
+        }
+
+        Ok(json!({
+            "success": true,
+            "status": response.status,
+            "body": response.body,
+        }))
+    }
+}
+
+impl HttpRequestConfig {
+    pub fn from_node(node: &NodeDef) -> Result<Self, NodeEngineError> {
+        serde_json::from_value(node.config.clone())
+            .map_err(|e| NodeEngineError::InvalidConfig(format!("Invalid HTTP request config: {}", e)))
+    }
+}
+
+/// A request with its templates rendered, ready to send
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub method: reqwest::Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    is_json: bool,
+    signature: Option<(String, String)>,
+}
+
+impl PreparedRequest {
+    /// Render the config's templates against the node inputs and context
+    pub fn render(
+        config: &HttpRequestConfig,
+        inputs: &HashMap<String, Value>,
+        context: &ExecutionContext,
+    ) -> Result<Self, NodeEngineError> {
+        let method = reqwest::Method::from_bytes(config.method.to_uppercase().as_bytes())
+            .map_err(|_| NodeEngineError::InvalidConfig(format!("Invalid HTTP method: {}", config.method)))?;
+
+        let variables = render_context(inputs, context);
+        let url = render_template(&config.url, &variables)?;
+        let mut headers = Vec::with_capacity(config.headers.len());
+        for (name, value) in &config.headers {
+            headers.push((name.clone(), render_template(value, &variables)?));
+        }
+        let (body, is_json) = match &config.body {
+            None | Some(Value::Null) => (None, false),
+            Some(Value::String(template)) => (Some(render_template(template, &variables)?), false),
+            Some(value) => (Some(render_json(value, &variables)?.to_string()), true),
+        };
+
+        let signature = config.signing.as_ref().map(|signing| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(signing.secret.as_bytes())
+                .expect("HMAC can take key of any size");
+            mac.update(body.as_deref().unwrap_or_default().as_bytes());
+            (signing.header.clone(), format!("sha256={}", hex::encode(mac.finalize().into_bytes())))
+        });
+
+        Ok(Self { method, url, headers, body, is_json, signature })
+    }
+}
+
+/// Final response of a request, after any retries
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Parsed JSON, or the raw text if the body is not JSON
+    pub body: Value,
+    /// Attempts made, including the first
+    pub attempts: u32,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+impl GenericHttpProvider {
+    /// Send `request`, retrying 5xx responses and network errors.
+    ///
+    /// A 5xx that is still failing when the retries run out is returned like
+    /// any other response; only network errors fail the call.
+    pub async fn send(
+        &self,
+        node: &NodeDef,
+        config: &HttpRequestConfig,
+        request: &PreparedRequest,
+    ) -> Result<HttpResponse, NodeEngineError> {
+        let failed = |message: String| NodeEngineError::NodeExecutionFailed { node_id: node.id, message };
+
+        let client = self.client_for(&request.url).await.map_err(failed)?;
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let mut token = match &config.oauth2 {
+            Some(oauth) => Some(self.access_token(oauth, timeout, false).await.map_err(failed)?),
+            None => None,
+        };
+        let mut refreshed = false;
+        let mut attempt = 0;
+
+        let response = loop {
+            let mut builder = client.request(request.method.clone(), &request.url).timeout(timeout);
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(body) = &request.body {
+                if request.is_json {
+                    builder = builder.header("Content-Type", "application/json");
+                }
+                builder = builder.body(body.clone());
+            }
+            if let Some((name, value)) = &request.signature {
+                builder = builder.header(name, value);
+            }
+            if let Some(token) = &token {
+                builder = builder.bearer_auth(token);
+            }
+
+            let retryable = match builder.send().await {
+                // Token revoked or expired early: refresh once
+                Ok(response) if response.status() == reqwest::StatusCode::UNAUTHORIZED && !refreshed => {
+                    match &config.oauth2 {
+                        Some(oauth) => {
+                            refreshed = true;
+                            token = Some(self.access_token(oauth, timeout, true).await.map_err(failed)?);
+                            continue;
+                        }
+                        None => break response,
+                    }
+                }
+                Ok(response) if response.status().is_server_error() && attempt >= config.max_retries => break response,
+                Ok(response) if response.status().is_server_error() => format!("server returned {}", response.status()),
+                Ok(response) => break response,
+                Err(e) if (e.is_timeout() || e.is_connect()) && attempt < config.max_retries => e.to_string(),
+                Err(e) if e.is_timeout() || e.is_connect() => {
+                    return Err(failed(format!("Request failed after {} attempts: {}", attempt + 1, e)));
+                }
+                Err(e) => return Err(failed(format!("Request failed: {}", e))),
+            };
+
+            let delay = Duration::from_millis(config.retry_backoff_ms).saturating_mul(1u32 << attempt.min(16));
+            warn!(node_id = %node.id, attempt = attempt + 1, error = %retryable, "HTTP request failed, retrying in {:?}", delay);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+
+        let status = response.status().as_u16();
+        let bytes = read_capped(response, config.max_response_bytes).await.map_err(failed)?;
+        let body = serde_json::from_slice::<Value>(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+
+        Ok(HttpResponse { status, body, attempts: attempt + 1 })
+    }
+}
+
+/// Template variables: inputs, trigger/record, then execution values
+fn render_context(inputs: &HashMap<String, Value>, context: &ExecutionContext) -> Value {
+    let mut variables = template_context(inputs, &context.trigger_data);
+    if let Value::Object(map) = &mut variables {
+        for (key, value) in &context.values {
+            map.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    variables
+}
+
+/// Render every string leaf of a JSON body template
+fn render_json(value: &Value, variables: &Value) -> Result<Value, NodeEngineError> {
+    Ok(match value {
+        Value::String(template) => Value::String(render_template(template, variables)?),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_json(v, variables)).collect::<Result<_, _>>()?),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), render_json(v, variables)?)))
+                .collect::<Result<_, NodeEngineError>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Read the response body, failing once it exceeds `max_bytes`
+async fn read_capped(mut response: reqwest::Response, max_bytes: usize) -> Result<Vec<u8>, String> {
+    if response.content_length().is_some_and(|len| len as usize > max_bytes) {
+        return Err(format!("Response exceeds {} bytes", max_bytes));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read response: {}", e))? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(format!("Response exceeds {} bytes", max_bytes));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct MockApi {
+        tokens_issued: AtomicU32,
+        /// Tokens older than this are rejected (simulates revocation)
+        min_valid_token: AtomicU32,
+        failures_before_success: AtomicU32,
+        hits: AtomicU32,
+    }
+
+    async fn issue_token(State(api): State<Arc<MockApi>>, body: String) -> Json<Value> {
+        assert!(body.contains("grant_type=client_credentials"));
+        assert!(body.contains("client_id=jirsi"));
+        let n = api.tokens_issued.fetch_add(1, Ordering::SeqCst) + 1;
+        Json(json!({ "access_token": format!("token-{}", n), "expires_in": 3600 }))
+    }
+
+    async fn echo(State(api): State<Arc<MockApi>>, headers: HeaderMap, body: String) -> (StatusCode, Json<Value>) {
+        api.hits.fetch_add(1, Ordering::SeqCst);
+        if api.failures_before_success.load(Ordering::SeqCst) > 0 {
+            api.failures_before_success.fetch_sub(1, Ordering::SeqCst);
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "error": "busy" })));
+        }
+
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+        if let Some(auth) = header("authorization") {
+            let n: u32 = auth.trim_start_matches("Bearer token-").parse().unwrap();
+            if n < api.min_valid_token.load(Ordering::SeqCst) {
+                return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "invalid_token" })));
+            }
+        }
+
+        (StatusCode::OK, Json(json!({
+            "received": serde_json::from_str::<Value>(&body).unwrap_or(Value::String(body)),
+            "signature": header("x-signature"),
+            "authorization": header("authorization"),
+            "tenant": header("x-tenant"),
+        })))
+    }
+
+    async fn mock_server() -> (String, Arc<MockApi>) {
+        let api = Arc::new(MockApi::default());
+        let app = Router::new()
+            .route("/oauth/token", post(issue_token))
+            .route("/deals", post(echo))
+            .with_state(api.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), api)
+    }
+
+    fn provider() -> GenericHttpProvider {
+        GenericHttpProvider::new().with_egress(EgressPolicy::allow_private())
+    }
+
+    fn http_node(config: Value) -> NodeDef {
+        NodeDef {
+            id: Uuid::new_v4(),
+            graph_id: Uuid::new_v4(),
+            node_type: core_models::NodeType::ActionSendWebhook,
+            label: "Call API".to_string(),
+            x: 0.0,
+            y: 0.0,
+            config,
+            is_enabled: true,
+        }
+    }
+
+    fn deal_context() -> ExecutionContext {
+        ExecutionContext::new().with_trigger_data(json!({ "record": { "id": "d-1", "name": "Marina Villa", "amount": 2500000 } }))
+    }
+
+    #[tokio::test]
+    async fn test_signed_post_interpolates_context() {
+        let (base, _api) = mock_server().await;
+        let provider = provider();
+        let node = http_node(json!({
+            "method": "POST",
+            "url": format!("{}/deals", base),
+            "headers": { "X-Tenant": "{{tenant_slug}}" },
+            "body": { "deal": "{{record.name}}", "ref": "{{record.id}}" },
+            "signing": { "secret": "s3cret" }
+        }));
+        let mut inputs = HashMap::new();
+        inputs.insert("tenant_slug".to_string(), json!("acme"));
+
+        let output = provider.execute(&node, inputs, &mut deal_context()).await.unwrap();
+
+        let sent = json!({ "deal": "Marina Villa", "ref": "d-1" });
+        assert_eq!(output["status"], 200);
+        assert_eq!(output["body"]["received"], sent);
+        assert_eq!(output["body"]["tenant"], "acme");
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(sent.to_string().as_bytes());
+        assert_eq!(output["body"]["signature"], format!("sha256={}", hex::encode(mac.finalize().into_bytes())));
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_is_cached_and_refreshed_on_401() {
+        let (base, api) = mock_server().await;
+        let provider = provider();
+        let node = http_node(json!({
+            "url": format!("{}/deals", base),
+            "body": { "deal": "{{record.name}}" },
+            "oauth2": {
+                "token_url": format!("{}/oauth/token", base),
+                "client_id": "jirsi",
+                "client_secret": "secret"
+            }
+        }));
+
+        let first = provider.execute(&node, HashMap::new(), &mut deal_context()).await.unwrap();
+        let second = provider.execute(&node, HashMap::new(), &mut deal_context()).await.unwrap();
+        assert_eq!(first["body"]["authorization"], "Bearer token-1");
+        assert_eq!(second["body"]["authorization"], "Bearer token-1");
+        assert_eq!(api.tokens_issued.load(Ordering::SeqCst), 1);
+
+        // Server revokes token-1: the call is retried once with a fresh token
+        api.min_valid_token.store(2, Ordering::SeqCst);
+        let third = provider.execute(&node, HashMap::new(), &mut deal_context()).await.unwrap();
+        assert_eq!(third["body"]["authorization"], "Bearer token-2");
+        assert_eq!(api.tokens_issued.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_5xx_with_backoff() {
+        let (base, api) = mock_server().await;
+        let provider = provider();
+        let node = http_node(json!({
+            "url": format!("{}/deals", base),
+            "body": "{}",
+            "max_retries": 2,
+            "retry_backoff_ms": 1
+        }));
+
+        api.failures_before_success.store(2, Ordering::SeqCst);
+        let output = provider.execute(&node, HashMap::new(), &mut deal_context()).await.unwrap();
+        assert_eq!(output["status"], 200);
+        assert_eq!(api.hits.load(Ordering::SeqCst), 3);
+
+        api.failures_before_success.store(5, Ordering::SeqCst);
+        let result = provider.execute(&node, HashMap::new(), &mut deal_context()).await;
+        assert!(matches!(result, Err(NodeEngineError::NodeExecutionFailed { message, .. }) if message.contains("after 3 attempts")));
+    }
+
+    #[tokio::test]
+    async fn test_response_size_cap() {
+        let (base, _api) = mock_server().await;
+        let provider = provider();
+        let node = http_node(json!({
+            "url": format!("{}/deals", base),
+            "body": { "padding": "x".repeat(512) },
+            "max_response_bytes": 64
+        }));
+
+        let result = provider.execute(&node, HashMap::new(), &mut deal_context()).await;
+
+        assert!(matches!(result, Err(NodeEngineError::NodeExecutionFailed { message, .. }) if message.contains("exceeds 64 bytes")));
+    }
+
+    #[tokio::test]
+    async fn test_internal_targets_are_refused() {
+        let (base, api) = mock_server().await;
+        let node = http_node(json!({ "url": format!("{}/deals", base), "body": "{}" }));
+
+        let result = GenericHttpProvider::new().execute(&node, HashMap::new(), &mut deal_context()).await;
+        assert!(matches!(result, Err(NodeEngineError::NodeExecutionFailed { message, .. }) if message.contains("not a public address")));
+
+        // Token URLs are tenant-supplied too
+        let node = http_node(json!({
+            "url": "https://93.184.216.34/deals",
+            "oauth2": { "token_url": "http://169.254.169.254/token", "client_id": "jirsi", "client_secret": "secret" }
+        }));
+        let result = GenericHttpProvider::new().execute(&node, HashMap::new(), &mut deal_context()).await;
+        assert!(matches!(result, Err(NodeEngineError::NodeExecutionFailed { message, .. }) if message.contains("not a public address")));
+        assert_eq!(api.hits.load(Ordering::SeqCst), 0);
+    }
+}
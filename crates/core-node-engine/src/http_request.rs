@@ -0,0 +1,261 @@
+//! HTTP request node - call an external API inline and use its response
+//!
+//! Node config: everything the generic HTTP provider takes (`method`, `url`,
+//! `headers`, `body`, `timeout_ms`, `max_retries`, ...) plus
+//! - `outputs`: context variable name -> dotted path into the response JSON,
+//!   e.g. `{ "deal_id": "data.items.0.id" }`
+//!
+//! Requests only go to hosts on the tenant's allowlist (`outbound_hosts` in
+//! tenant settings; `*.example.com` allows subdomains) that resolve to
+//! public addresses. Redirects are not followed, so an allowed host cannot
+//! bounce the call elsewhere.
+//!
+//! A 2xx response continues along the normal port with the mapped values
+//! set in the context. Any other status, once the 5xx retries are used up,
+//! continues along the `error` port instead.
+
+use async_trait::async_trait;
+use core_models::NodeDef;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::context::ExecutionContext;
+use crate::http_provider::{GenericHttpProvider, HttpRequestConfig, PreparedRequest};
+use crate::nodes::NodeHandler;
+use crate::NodeEngineError;
+
+/// Port taken when the request does not succeed
+pub const ERROR_PORT: &str = "error";
+
+#[derive(Debug, Clone, Deserialize)]
+struct ResponseMapping {
+    #[serde(default)]
+    outputs: HashMap<String, String>,
+}
+
+pub struct HttpRequestHandler {
+    pool: PgPool,
+    provider: GenericHttpProvider,
+}
+
+impl HttpRequestHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            provider: GenericHttpProvider::new(),
+        }
+    }
+
+    /// Hosts the tenant allows workflows to call
+    async fn allowed_hosts(&self, tenant_id: Uuid) -> Result<Vec<String>, NodeEngineError> {
+        let hosts: Option<Option<Value>> =
+            sqlx::query_scalar("SELECT settings->'outbound_hosts' FROM tenants WHERE id = $1")
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(hosts
+            .flatten()
+            .and_then(|hosts| serde_json::from_value(hosts).ok())
+            .unwrap_or_default())
+    }
+
+    /// Render, check and send the request, mapping the response into `context`
+    async fn run(
+        &self,
+        node: &NodeDef,
+        inputs: HashMap<String, Value>,
+        context: &mut ExecutionContext,
+        allowed_hosts: &[String],
+    ) -> Result<Value, NodeEngineError> {
+        let config = HttpRequestConfig::from_node(node)?;
+        let mapping: ResponseMapping = serde_json::from_value(node.config.clone())
+            .map_err(|e| NodeEngineError::InvalidConfig(format!("Invalid response mapping: {}", e)))?;
+        let request = PreparedRequest::render(&config, &inputs, context)?;
+
+        let host = reqwest::Url::parse(&request.url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+            .ok_or_else(|| NodeEngineError::InvalidConfig(format!("Invalid request URL: {}", request.url)))?;
+        if !host_allowed(&host, allowed_hosts) {
+            return Err(NodeEngineError::NodeExecutionFailed {
+                node_id: node.id,
+                message: format!("Host {} is not on the tenant's outbound allowlist", host),
+            });
+        }
+
+        let response = self.provider.send(node, &config, &request).await?;
+
+        if !response.is_success() {
+            warn!(node_id = %node.id, status = response.status, url = %request.url, "HTTP request failed");
+            return Ok(json!({
+                "success": false,
+                "branch": ERROR_PORT,
+                "status": response.status,
+                "body": response.body,
+                "attempts": response.attempts,
+            }));
+        }
+
+        let mut mapped = serde_json::Map::new();
+        for (variable, path) in &mapping.outputs {
+            let value = json_path(&response.body, path).cloned().unwrap_or(Value::Null);
+            context.values.insert(variable.clone(), value.clone());
+            mapped.insert(variable.clone(), value);
+        }
+
+        Ok(json!({
+            "success": true,
+            "status": response.status,
+            "body": response.body,
+            "mapped": mapped,
+        }))
+    }
+}
+
+#[async_trait]
+impl NodeHandler for HttpRequestHandler {
+    async fn execute(
+        &self,
+        node: &NodeDef,
+        inputs: HashMap<String, Value>,
+        context: &mut ExecutionContext,
+    ) -> Result<Value, NodeEngineError> {
+        let allowed_hosts = self.allowed_hosts(context.tenant_id).await?;
+        self.run(node, inputs, context, &allowed_hosts).await
+    }
+}
+
+/// Whether `host` matches an allowlist entry; `*.example.com` matches subdomains only
+fn host_allowed(host: &str, allowed_hosts: &[String]) -> bool {
+    allowed_hosts.iter().any(|entry| {
+        let entry = entry.trim().to_lowercase();
+        match entry.strip_prefix("*.") {
+            Some(domain) => host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.')),
+            None => host == entry,
+        }
+    })
+}
+
+/// Resolve a dotted path (`data.items.0.id`) into a JSON value
+fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|key| !key.is_empty()).try_fold(value, |current, key| match current {
+        Value::Object(map) => map.get(key),
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+    use core_integrations::egress::EgressPolicy;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    async fn deal(State(hits): State<Arc<AtomicU32>>) -> Json<Value> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({ "data": { "items": [{ "id": "deal-42", "stage": "won" }] } }))
+    }
+
+    async fn broken(State(hits): State<Arc<AtomicU32>>) -> (StatusCode, Json<Value>) {
+        hits.fetch_add(1, Ordering::SeqCst);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "boom" })))
+    }
+
+    async fn mock_server() -> (String, Arc<AtomicU32>) {
+        let hits = Arc::new(AtomicU32::new(0));
+        let app = Router::new()
+            .route("/deals/:id", get(deal))
+            .route("/broken", get(broken))
+            .with_state(hits.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), hits)
+    }
+
+    fn handler() -> HttpRequestHandler {
+        HttpRequestHandler {
+            pool: PgPool::connect_lazy("postgres://localhost/unused").unwrap(),
+            provider: GenericHttpProvider::new().with_egress(EgressPolicy::allow_private()),
+        }
+    }
+
+    fn request_node(config: Value) -> NodeDef {
+        NodeDef {
+            id: Uuid::new_v4(),
+            graph_id: Uuid::new_v4(),
+            node_type: core_models::NodeType::HttpRequest,
+            label: "Fetch deal".to_string(),
+            x: 0.0,
+            y: 0.0,
+            config,
+            is_enabled: true,
+        }
+    }
+
+    fn localhost() -> Vec<String> {
+        vec!["127.0.0.1".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_get_maps_response_field_into_context() {
+        let (base, _hits) = mock_server().await;
+        let node = request_node(json!({
+            "method": "GET",
+            "url": format!("{}/deals/{{{{record.id}}}}", base),
+            "outputs": { "deal_id": "data.items.0.id", "missing": "data.nope" }
+        }));
+        let mut context = ExecutionContext::new().with_trigger_data(json!({ "record": { "id": "42" } }));
+
+        let output = handler().run(&node, HashMap::new(), &mut context, &localhost()).await.unwrap();
+
+        assert_eq!(output["status"], 200);
+        assert!(output.get("branch").is_none());
+        assert_eq!(context.values["deal_id"], json!("deal-42"));
+        assert_eq!(context.values["missing"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_500_exhausting_retries_routes_to_error_port() {
+        let (base, hits) = mock_server().await;
+        let node = request_node(json!({
+            "method": "GET",
+            "url": format!("{}/broken", base),
+            "max_retries": 2,
+            "retry_backoff_ms": 1,
+            "outputs": { "deal_id": "data.id" }
+        }));
+        let mut context = ExecutionContext::new();
+
+        let output = handler().run(&node, HashMap::new(), &mut context, &localhost()).await.unwrap();
+
+        assert_eq!(output["branch"], ERROR_PORT);
+        assert_eq!(output["status"], 500);
+        assert_eq!(output["attempts"], 3);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        assert!(!context.values.contains_key("deal_id"));
+    }
+
+    #[tokio::test]
+    async fn test_host_outside_allowlist_is_refused() {
+        let (base, hits) = mock_server().await;
+        let node = request_node(json!({ "method": "GET", "url": format!("{}/deals/1", base) }));
+
+        let result = handler().run(&node, HashMap::new(), &mut ExecutionContext::new(), &[]).await;
+
+        assert!(matches!(result, Err(NodeEngineError::NodeExecutionFailed { message, .. }) if message.contains("allowlist")));
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let allowed = vec!["*.example.com".to_string()];
+        assert!(host_allowed("api.example.com", &allowed));
+        assert!(!host_allowed("example.com", &allowed));
+        assert!(!host_allowed("evilexample.com", &allowed));
+    }
+}
@@ -7,6 +7,11 @@ pub mod circuit_breaker;
 pub mod context;
 pub mod error;
 pub mod events;
+pub mod for_each;
+#[cfg(feature = "backend")]
+pub mod http_provider;
+#[cfg(feature = "backend")]
+pub mod http_request;
 pub mod matching;
 pub mod nodes;
 #[cfg(feature = "backend")]
@@ -15,8 +20,12 @@ pub mod notifications;
 pub mod payments;
 #[cfg(feature = "backend")]
 pub mod plugin_sandbox;
+pub mod ports;
 pub mod state_machine;
 pub mod strategies;
+pub mod template;
+#[cfg(feature = "backend")]
+pub mod wait_until_field;
 #[cfg(feature = "backend")]
 pub mod whatsapp;
 
@@ -32,7 +41,7 @@ pub mod executor;
 pub mod repository;
 
 pub mod ai;
-pub use context::ExecutionContext;
+pub use context::{ExecutionCheckpoint, ExecutionContext};
 pub use error::NodeEngineError;
 pub use events::{EntityEvent, EventType};
 #[cfg(feature = "backend")]
@@ -44,6 +53,9 @@ pub use nodes::*; // Re-export node types
 pub use executor::GraphExecutor;
 
 pub use strategies::{AssignmentStrategy, AgentStats};
+pub use matching::{filter_eligible_agents, MatchCriteria};
+pub use template::{render_template, TemplateError};
+pub use ports::PortType;
 #[cfg(feature = "backend")]
 pub use strategies::AssignmentService;
 
@@ -51,6 +63,12 @@ pub use strategies::AssignmentService;
 pub use wasm_executor::{WasmExecutor, PluginSource, WasmPluginConfig, HostFunctions};
 #[cfg(feature = "backend")]
 pub use script_node::ScriptNodeHandler;
+#[cfg(feature = "backend")]
+pub use http_provider::GenericHttpProvider;
+#[cfg(feature = "backend")]
+pub use http_request::HttpRequestHandler;
+#[cfg(feature = "backend")]
+pub use wait_until_field::WaitUntilFieldHandler;
 
 // Stub types for WASM frontend builds
 #[cfg(not(feature = "backend"))]
@@ -6,15 +6,50 @@
 //! - Property type requirements
 //! - Bedroom/bathroom counts
 //! - Amenity preferences
+//!
+//! Also provides agent eligibility filtering (skills, territory) that runs
+//! before an `AssignmentStrategy` picks the agent.
 
 use async_trait::async_trait;
 use core_models::NodeDef;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
 use crate::context::ExecutionContext;
 use crate::NodeEngineError;
 use crate::nodes::NodeHandler;
+use crate::strategies::AgentStats;
+
+/// Requirements a record places on the agent it is assigned to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchCriteria {
+    /// Every skill listed here must be held by the agent
+    #[serde(default)]
+    pub required_skills: Vec<String>,
+    /// Territory the agent must cover, if any
+    #[serde(default)]
+    pub territory: Option<String>,
+}
+
+/// Narrow candidates to agents holding all required skills and covering the territory
+///
+/// Comparisons are case-insensitive. Order of the input is preserved.
+pub fn filter_eligible_agents(agents: &[AgentStats], criteria: &MatchCriteria) -> Vec<AgentStats> {
+    agents
+        .iter()
+        .filter(|agent| {
+            criteria.required_skills.iter().all(|required| {
+                agent.skills.iter().any(|skill| skill.eq_ignore_ascii_case(required))
+            })
+        })
+        .filter(|agent| match &criteria.territory {
+            Some(territory) => agent.territories.iter().any(|t| t.eq_ignore_ascii_case(territory)),
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
 
 /// Smart Match Handler - matches leads with suitable properties
 pub struct SmartMatchHandler;
@@ -392,6 +427,70 @@ fn haversine_distance(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use uuid::Uuid;
+
+    fn agent(id: u128, skills: &[&str], territories: &[&str]) -> AgentStats {
+        AgentStats {
+            id: Uuid::from_u128(id),
+            name: format!("Agent {}", id),
+            active_deals: 0,
+            total_assignments: 0,
+            capacity: 10,
+            open_count: 0,
+            skills: skills.iter().map(|s| s.to_string()).collect(),
+            territories: territories.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_filter_requires_all_skills() {
+        let agents = vec![
+            agent(1, &["english"], &["dubai"]),
+            agent(2, &["Arabic", "english"], &["dubai"]),
+            agent(3, &["arabic"], &["abu dhabi"]),
+        ];
+        let criteria = MatchCriteria {
+            required_skills: vec!["arabic".to_string()],
+            territory: None,
+        };
+
+        let eligible: Vec<Uuid> = filter_eligible_agents(&agents, &criteria).iter().map(|a| a.id).collect();
+        assert_eq!(eligible, vec![Uuid::from_u128(2), Uuid::from_u128(3)]);
+    }
+
+    #[test]
+    fn test_filter_by_territory() {
+        let agents = vec![agent(1, &["arabic"], &["Dubai"]), agent(2, &["arabic"], &[])];
+        let criteria = MatchCriteria {
+            required_skills: vec!["arabic".to_string()],
+            territory: Some("dubai".to_string()),
+        };
+
+        let eligible = filter_eligible_agents(&agents, &criteria);
+        assert_eq!(eligible.len(), 1);
+        assert_eq!(eligible[0].id, Uuid::from_u128(1));
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn test_arabic_record_routes_only_to_arabic_agents() {
+        use crate::strategies::{AssignmentService, AssignmentStrategy};
+
+        // The english-only agent is the least loaded but must never be picked
+        let mut agents = vec![agent(1, &["english"], &[]), agent(2, &["arabic"], &[]), agent(3, &["arabic", "english"], &[])];
+        agents[1].open_count = 3;
+        agents[2].open_count = 1;
+        let criteria = MatchCriteria {
+            required_skills: vec!["arabic".to_string()],
+            territory: None,
+        };
+
+        for _ in 0..10 {
+            let chosen = AssignmentService::assign_matching(&agents, &criteria, &AssignmentStrategy::WeightedRoundRobin).unwrap();
+            assert_ne!(chosen, Uuid::from_u128(1));
+            agents.iter_mut().find(|a| a.id == chosen).unwrap().open_count += 1;
+        }
+    }
     
     #[test]
     fn test_haversine_distance() {
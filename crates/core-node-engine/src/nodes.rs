@@ -1,6 +1,7 @@
 //! Node handlers and registry
 
 use async_trait::async_trait;
+use core_models::logic::{EvalContext, LogicOp};
 use core_models::{NodeDef, NodeType};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -39,7 +40,10 @@ impl NodeRegistry {
         registry.register(NodeType::DataSetField, Arc::new(SetFieldHandler));
         registry.register(NodeType::ActionSendEmail, Arc::new(SendEmailHandler));
         registry.register(NodeType::ConditionIf, Arc::new(ConditionIfHandler));
+        registry.register(NodeType::Branch, Arc::new(BranchHandler));
         registry.register(NodeType::AiGenerate, Arc::new(AiGenerateHandler));
+        #[cfg(feature = "backend")]
+        registry.register(NodeType::ActionSendWebhook, Arc::new(crate::http_provider::GenericHttpProvider::new()));
         
         registry
     }
@@ -94,10 +98,7 @@ impl Default for NodeRegistry {
         NodeType::ActionSendSms,
         Arc::new(SendSmsHandler),
     );
-    registry.register(
-        NodeType::ActionSendWebhook,
-        Arc::new(WebhookHandler),
-    );
+    // ActionSendWebhook keeps the HTTP provider `new` registers
     registry.register(
         NodeType::ActionDelay,
         Arc::new(DelayHandler),
@@ -374,6 +375,51 @@ impl NodeHandler for ConditionIfHandler {
     }
 }
 
+/// Branch handler - evaluates `config.condition` (a `LogicOp`) and names
+/// the output port to follow, `true` or `false`
+///
+/// Field paths resolve against the execution values (`$trigger.new_values.stage`,
+/// a node's output by its ID) or, as a shorthand, the triggering record's
+/// fields (`stage`). The executor skips everything reachable only through
+/// the other port.
+pub struct BranchHandler;
+
+#[async_trait]
+impl NodeHandler for BranchHandler {
+    async fn execute(
+        &self,
+        node: &NodeDef,
+        _inputs: HashMap<String, Value>,
+        context: &mut ExecutionContext,
+    ) -> Result<Value, NodeEngineError> {
+        let condition: LogicOp = node
+            .config
+            .get("condition")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| NodeEngineError::InvalidConfig(format!("Invalid branch condition: {}", e)))?
+            .ok_or_else(|| NodeEngineError::InvalidConfig("branch requires `condition`".to_string()))?;
+
+        let mut data: HashMap<String, Value> = context
+            .values
+            .get("$trigger")
+            .and_then(|t| t.get("new_values"))
+            .and_then(|v| v.as_object())
+            .map(|record| record.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        data.extend(context.values.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        let taken = condition.evaluate(&EvalContext::with_data(&data));
+        tracing::debug!(node_id = %node.id, taken = taken, "Branch evaluated");
+
+        Ok(serde_json::json!({
+            "branch": if taken { "true" } else { "false" },
+            "condition": taken,
+        }))
+    }
+}
+
 /// Helper function for numeric comparisons
 fn compare_numeric<F>(a: &Value, b: &Value, cmp: F) -> bool 
 where
@@ -636,69 +682,6 @@ impl NodeHandler for SendSmsHandler {
     }
 }
 
-/// Webhook handler - sends data to external URLs
-pub struct WebhookHandler;
-
-#[async_trait]
-impl NodeHandler for WebhookHandler {
-    async fn execute(
-        &self,
-        node: &NodeDef,
-        inputs: HashMap<String, Value>,
-        _context: &mut ExecutionContext,
-    ) -> Result<Value, NodeEngineError> {
-        let url = node.config.get("url")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| NodeEngineError::NodeExecutionFailed {
-                node_id: node.id,
-                message: "Webhook URL is required".to_string(),
-            })?;
-        
-        let method = node.config.get("method")
-            .and_then(|v| v.as_str())
-            .unwrap_or("POST")
-            .to_uppercase();
-        
-        let headers: HashMap<String, String> = node.config.get("headers")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default();
-        
-        // Build payload from inputs or config
-        let payload = node.config.get("payload")
-            .cloned()
-            .or_else(|| inputs.get("data").cloned())
-            .unwrap_or_else(|| serde_json::json!(inputs));
-        
-        let timeout_seconds = node.config.get("timeout")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(30);
-        
-        tracing::info!(
-            url = url,
-            method = method.as_str(),
-            payload_size = payload.to_string().len(),
-            "Executing webhook"
-        );
-        
-        // In production, this would use reqwest or similar HTTP client
-        // For now, we log and return mock success
-        
-        Ok(serde_json::json!({
-            "action": "webhook",
-            "url": url,
-            "method": method,
-            "headers": headers,
-            "payload": payload,
-            "timeout": timeout_seconds,
-            "executed": true,
-            "response": {
-                "status": 200,
-                "body": "Mock response"
-            }
-        }))
-    }
-}
-
 // ============ AI-Powered Nodes ============
 
 /// AI Summarization handler - summarizes text or entity data
@@ -903,10 +886,22 @@ impl NodeHandler for DelayHandler {
         
         tracing::info!(delay_seconds = total_seconds, "Delaying workflow execution");
         
-        // In production, this would schedule a delayed continuation
+        // Long delays suspend the run: the executor checkpoints it and the
+        // scheduled trigger runner enqueues a resume at `resume_at`
+        if total_seconds > 60 {
+            let resume_at = chrono::Utc::now() + chrono::Duration::seconds(total_seconds as i64);
+            return Ok(serde_json::json!({
+                "action": "delay",
+                "delayed_seconds": total_seconds,
+                "suspend": true,
+                "resume_at": resume_at.to_rfc3339(),
+                "completed": false
+            }));
+        }
+        
         // For short delays on backend, use tokio sleep
         #[cfg(feature = "backend")]
-        if total_seconds > 0 && total_seconds <= 60 {
+        if total_seconds > 0 {
             tokio::time::sleep(std::time::Duration::from_secs(total_seconds)).await;
         }
         
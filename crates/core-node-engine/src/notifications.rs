@@ -14,6 +14,7 @@ use std::collections::HashMap;
 use crate::context::ExecutionContext;
 use crate::NodeEngineError;
 use crate::nodes::NodeHandler;
+use crate::template::{render_template, template_context};
 
 // ============================================================================
 // SMS - Twilio Integration
@@ -127,7 +128,8 @@ impl NodeHandler for SendSmsHandler {
             .ok_or_else(|| NodeEngineError::InvalidConfig("Missing message body".into()))?;
         
         // Template variable substitution
-        let body = substitute_variables(body, &inputs, &context.trigger_data);
+        let variables = template_context(&inputs, &context.trigger_data);
+        let body = render_template(body, &variables)?;
         
         // Send SMS
         let result = self.send_sms(phone, &body).await
@@ -288,8 +290,9 @@ impl NodeHandler for SendEmailHandler {
             .and_then(|v| v.as_str());
         
         // Template variable substitution
-        let subject = substitute_variables(subject, &inputs, &context.trigger_data);
-        let body_html = substitute_variables(body_html, &inputs, &context.trigger_data);
+        let variables = template_context(&inputs, &context.trigger_data);
+        let subject = render_template(subject, &variables)?;
+        let body_html = render_template(body_html, &variables)?;
         
         // Send email
         let result = self.send_email(to, &subject, &body_html, body_text.as_deref()).await
@@ -306,38 +309,6 @@ impl NodeHandler for SendEmailHandler {
     }
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
-
-/// Substitute {{variable}} placeholders in text
-fn substitute_variables(template: &str, inputs: &HashMap<String, Value>, trigger_data: &Value) -> String {
-    let mut result = template.to_string();
-    
-    // Find all {{variable}} patterns
-    let re = regex::Regex::new(r"\{\{(\w+)\}\}").unwrap();
-    
-    for cap in re.captures_iter(template) {
-        let full_match = &cap[0];
-        let var_name = &cap[1];
-        
-        // Try inputs first, then trigger_data
-        let value = inputs.get(var_name)
-            .or_else(|| trigger_data.get(var_name))
-            .map(|v| match v {
-                Value::String(s) => s.clone(),
-                Value::Number(n) => n.to_string(),
-                Value::Bool(b) => b.to_string(),
-                _ => v.to_string(),
-            })
-            .unwrap_or_else(|| full_match.to_string());
-        
-        result = result.replace(full_match, &value);
-    }
-    
-    result
-}
-
 /// Mock SMS handler for testing (doesn't send real SMS)
 pub struct MockSmsHandler;
 
@@ -4,6 +4,7 @@
 //! - PaymentProvider trait for payment processing
 //! - StripeConnectProvider implementation
 //! - ActionCollectPayment workflow node
+//! - Idempotent charging backed by the `payment_attempts` table
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -30,6 +31,8 @@ pub struct CreatePaymentRequest {
     pub application_fee_amount: Option<i64>,
     /// Metadata
     pub metadata: std::collections::HashMap<String, String>,
+    /// Sent to the provider so a retried request never charges twice
+    pub idempotency_key: Option<String>,
 }
 
 /// Payment intent result
@@ -87,6 +90,8 @@ pub enum PaymentError {
     Declined(String),
     #[error("Network error: {0}")]
     NetworkError(String),
+    #[error("Idempotency key reused with different parameters: {0}")]
+    IdempotencyConflict(String),
 }
 
 /// Trait for payment providers
@@ -157,10 +162,16 @@ impl PaymentProvider for StripeConnectProvider {
             form.push((format!("metadata[{}]", key), value));
         }
         
-        let response = self.client
+        let mut builder = self.client
             .post(format!("{}/payment_intents", self.api_base()))
             .basic_auth(&self.api_key, Option::<&str>::None)
-            .form(&form)
+            .form(&form);
+        
+        if let Some(key) = &request.idempotency_key {
+            builder = builder.header("Idempotency-Key", key);
+        }
+        
+        let response = builder
             .send()
             .await
             .map_err(|e| PaymentError::NetworkError(e.to_string()))?;
@@ -168,6 +179,11 @@ impl PaymentProvider for StripeConnectProvider {
         if !response.status().is_success() {
             let error: serde_json::Value = response.json().await
                 .unwrap_or(serde_json::json!({"error": {"message": "Unknown error"}}));
+            if error["error"]["type"].as_str() == Some("idempotency_error") {
+                return Err(PaymentError::IdempotencyConflict(
+                    request.idempotency_key.unwrap_or_default()
+                ));
+            }
             return Err(PaymentError::ApiError(
                 error["error"]["message"].as_str().unwrap_or("Unknown error").to_string()
             ));
@@ -352,15 +368,127 @@ use crate::NodeEngineError;
 use crate::nodes::NodeHandler;
 use std::collections::HashMap;
 use serde_json::{json, Value};
+use sqlx::PgPool;
+
+// ============================================================================
+// Payment Attempts (idempotency)
+// ============================================================================
+
+/// A charge already sent to the provider for an idempotency key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentAttempt {
+    pub tenant_id: Uuid,
+    pub idempotency_key: String,
+    pub graph_execution_id: Uuid,
+    pub node_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub payment_intent_id: String,
+    /// Node output returned for the original charge
+    pub result: Value,
+}
+
+/// Storage for payment attempts, keyed by (tenant_id, idempotency_key)
+#[async_trait]
+pub trait PaymentAttemptStore: Send + Sync {
+    async fn find(&self, tenant_id: Uuid, idempotency_key: &str) -> Result<Option<PaymentAttempt>, NodeEngineError>;
+    
+    async fn record(&self, attempt: &PaymentAttempt) -> Result<(), NodeEngineError>;
+}
+
+/// Postgres-backed `payment_attempts` store
+pub struct PgPaymentAttemptStore {
+    pool: PgPool,
+}
+
+impl PgPaymentAttemptStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PaymentAttemptStore for PgPaymentAttemptStore {
+    async fn find(&self, tenant_id: Uuid, idempotency_key: &str) -> Result<Option<PaymentAttempt>, NodeEngineError> {
+        use sqlx::Row;
+        
+        let row = sqlx::query(
+            r#"
+            SELECT tenant_id, idempotency_key, graph_execution_id, node_id,
+                   amount, currency, payment_intent_id, result
+            FROM payment_attempts
+            WHERE tenant_id = $1 AND idempotency_key = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(idempotency_key)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        row.map(|row| {
+            Ok(PaymentAttempt {
+                tenant_id: row.try_get("tenant_id")?,
+                idempotency_key: row.try_get("idempotency_key")?,
+                graph_execution_id: row.try_get("graph_execution_id")?,
+                node_id: row.try_get("node_id")?,
+                amount: row.try_get("amount")?,
+                currency: row.try_get("currency")?,
+                payment_intent_id: row.try_get("payment_intent_id")?,
+                result: row.try_get("result")?,
+            })
+        })
+        .transpose()
+    }
+    
+    async fn record(&self, attempt: &PaymentAttempt) -> Result<(), NodeEngineError> {
+        sqlx::query(
+            r#"
+            INSERT INTO payment_attempts (
+                tenant_id, idempotency_key, graph_execution_id, node_id,
+                amount, currency, payment_intent_id, result
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(attempt.tenant_id)
+        .bind(&attempt.idempotency_key)
+        .bind(attempt.graph_execution_id)
+        .bind(attempt.node_id)
+        .bind(attempt.amount)
+        .bind(&attempt.currency)
+        .bind(&attempt.payment_intent_id)
+        .bind(&attempt.result)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+}
+
+/// Default idempotency key for a payment node within one graph execution
+pub fn payment_idempotency_key(tenant_id: Uuid, graph_execution_id: Uuid, node_id: Uuid) -> String {
+    format!("{}:{}:{}", tenant_id, graph_execution_id, node_id)
+}
+
+// ============================================================================
+// Node Handler
+// ============================================================================
+
 
 /// Action Collect Payment - workflow node for collecting payments
+///
+/// Each charge carries an idempotency key derived from
+/// (tenant_id, graph_execution_id, node_id), overridable with the
+/// `idempotency_key` config. A retried node replays the stored result
+/// instead of charging again.
 pub struct ActionCollectPaymentHandler {
     provider: std::sync::Arc<dyn PaymentProvider>,
+    attempts: std::sync::Arc<dyn PaymentAttemptStore>,
 }
 
 impl ActionCollectPaymentHandler {
-    pub fn new(provider: std::sync::Arc<dyn PaymentProvider>) -> Self {
-        Self { provider }
+    pub fn new(provider: std::sync::Arc<dyn PaymentProvider>, attempts: std::sync::Arc<dyn PaymentAttemptStore>) -> Self {
+        Self { provider, attempts }
     }
 }
 
@@ -411,6 +539,27 @@ impl NodeHandler for ActionCollectPaymentHandler {
             None
         };
         
+        let amount_cents = (amount * 100.0) as i64; // Convert to cents
+        
+        // Idempotency key: config override, else derived from the execution
+        let idempotency_key = node.config.get("idempotency_key")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| payment_idempotency_key(context.tenant_id, context.execution_id, node.id));
+        
+        // Replay a previous charge for the same key
+        if let Some(attempt) = self.attempts.find(context.tenant_id, &idempotency_key).await? {
+            if attempt.amount != amount_cents || !attempt.currency.eq_ignore_ascii_case(&currency) {
+                return Err(NodeEngineError::PaymentIdempotencyConflict { idempotency_key });
+            }
+            tracing::info!(
+                node_id = %node.id,
+                payment_intent_id = %attempt.payment_intent_id,
+                "Payment already collected, returning original result"
+            );
+            return Ok(attempt.result);
+        }
+        
         // Build metadata
         let mut metadata = HashMap::new();
         if let Some(record) = inputs.get("record") {
@@ -418,25 +567,31 @@ impl NodeHandler for ActionCollectPaymentHandler {
                 metadata.insert("record_id".to_string(), id.to_string());
             }
         }
-        metadata.insert("workflow_execution_id".to_string(), context.request_id.to_string());
+        metadata.insert("workflow_execution_id".to_string(), context.execution_id.to_string());
         
         // Create payment intent
         let request = CreatePaymentRequest {
             amount: PaymentAmount {
-                amount: (amount * 100.0) as i64, // Convert to cents
-                currency,
+                amount: amount_cents,
+                currency: currency.clone(),
             },
             customer_email: email.to_string(),
             description,
             destination_account_id: destination,
             application_fee_amount: commission_amount,
             metadata,
+            idempotency_key: Some(idempotency_key.clone()),
         };
         
         let result = self.provider.create_payment_intent(request).await
-            .map_err(|e| NodeEngineError::ExecutionFailed(e.to_string()))?;
-        
-        Ok(json!({
+            .map_err(|e| match e {
+                PaymentError::IdempotencyConflict(_) => NodeEngineError::PaymentIdempotencyConflict {
+                    idempotency_key: idempotency_key.clone(),
+                },
+                e => NodeEngineError::NodeExecutionFailed { node_id: node.id, message: e.to_string() },
+            })?;
+        
+        let output = json!({
             "success": true,
             "payment_intent_id": result.id,
             "client_secret": result.client_secret,
@@ -444,6 +599,136 @@ impl NodeHandler for ActionCollectPaymentHandler {
             "amount": result.amount,
             "currency": result.currency,
             "commission_amount": commission_amount,
-        }))
+            "idempotency_key": idempotency_key,
+        });
+        
+        self.attempts.record(&PaymentAttempt {
+            tenant_id: context.tenant_id,
+            idempotency_key,
+            graph_execution_id: context.execution_id,
+            node_id: node.id,
+            amount: amount_cents,
+            currency,
+            payment_intent_id: result.id,
+            result: output.clone(),
+        }).await?;
+        
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    
+    /// Provider that counts charges
+    #[derive(Default)]
+    struct CountingProvider {
+        charges: AtomicU32,
+    }
+    
+    #[async_trait]
+    impl PaymentProvider for CountingProvider {
+        async fn create_payment_intent(&self, request: CreatePaymentRequest) -> Result<PaymentIntent, PaymentError> {
+            let n = self.charges.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(PaymentIntent {
+                id: format!("pi_{}", n),
+                client_secret: format!("pi_{}_secret", n),
+                status: PaymentStatus::Succeeded,
+                amount: request.amount.amount,
+                currency: request.amount.currency,
+            })
+        }
+        
+        async fn get_payment_intent(&self, _payment_intent_id: &str) -> Result<PaymentIntent, PaymentError> {
+            unimplemented!()
+        }
+        
+        async fn cancel_payment_intent(&self, _payment_intent_id: &str) -> Result<(), PaymentError> {
+            unimplemented!()
+        }
+        
+        async fn create_connected_account(&self, _request: CreateMerchantRequest) -> Result<MerchantAccount, PaymentError> {
+            unimplemented!()
+        }
+        
+        async fn create_onboarding_link(&self, _account_id: &str, _return_url: &str, _refresh_url: &str) -> Result<String, PaymentError> {
+            unimplemented!()
+        }
+        
+        async fn get_account_status(&self, _account_id: &str) -> Result<MerchantAccount, PaymentError> {
+            unimplemented!()
+        }
+    }
+    
+    #[derive(Default)]
+    struct MemoryAttemptStore {
+        attempts: Mutex<HashMap<(Uuid, String), PaymentAttempt>>,
+    }
+    
+    #[async_trait]
+    impl PaymentAttemptStore for MemoryAttemptStore {
+        async fn find(&self, tenant_id: Uuid, idempotency_key: &str) -> Result<Option<PaymentAttempt>, NodeEngineError> {
+            Ok(self.attempts.lock().unwrap().get(&(tenant_id, idempotency_key.to_string())).cloned())
+        }
+        
+        async fn record(&self, attempt: &PaymentAttempt) -> Result<(), NodeEngineError> {
+            self.attempts.lock().unwrap()
+                .entry((attempt.tenant_id, attempt.idempotency_key.clone()))
+                .or_insert_with(|| attempt.clone());
+            Ok(())
+        }
+    }
+    
+    fn payment_node(config: Value) -> NodeDef {
+        NodeDef {
+            id: Uuid::new_v4(),
+            graph_id: Uuid::new_v4(),
+            node_type: core_models::NodeType::ActionCollectPayment,
+            label: "Collect".to_string(),
+            x: 0.0,
+            y: 0.0,
+            config,
+            is_enabled: true,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_retried_node_charges_once() {
+        let provider = Arc::new(CountingProvider::default());
+        let handler = ActionCollectPaymentHandler::new(provider.clone(), Arc::new(MemoryAttemptStore::default()));
+        let node = payment_node(json!({ "amount": 49.5, "email": "buyer@example.com" }));
+        let mut context = ExecutionContext::new().with_execution(Uuid::new_v4(), Uuid::new_v4());
+        
+        let first = handler.execute(&node, HashMap::new(), &mut context).await.unwrap();
+        let second = handler.execute(&node, HashMap::new(), &mut context).await.unwrap();
+        
+        assert_eq!(provider.charges.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+        assert_eq!(
+            first["idempotency_key"],
+            json!(payment_idempotency_key(context.tenant_id, context.execution_id, node.id))
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_same_key_different_amount_conflicts() {
+        let provider = Arc::new(CountingProvider::default());
+        let handler = ActionCollectPaymentHandler::new(provider.clone(), Arc::new(MemoryAttemptStore::default()));
+        let mut context = ExecutionContext::new().with_execution(Uuid::new_v4(), Uuid::new_v4());
+        
+        let node = payment_node(json!({ "amount": 10, "email": "a@example.com", "idempotency_key": "order-42" }));
+        handler.execute(&node, HashMap::new(), &mut context).await.unwrap();
+        
+        let changed = payment_node(json!({ "amount": 12, "email": "a@example.com", "idempotency_key": "order-42" }));
+        let result = handler.execute(&changed, HashMap::new(), &mut context).await;
+        
+        assert!(matches!(
+            result,
+            Err(NodeEngineError::PaymentIdempotencyConflict { ref idempotency_key }) if idempotency_key == "order-42"
+        ));
+        assert_eq!(provider.charges.load(Ordering::SeqCst), 1);
     }
 }
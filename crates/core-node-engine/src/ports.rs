@@ -0,0 +1,149 @@
+//! Port types - what flows along each edge, checked when a graph is saved
+//!
+//! Every node kind declares the types of the ports it reads and writes.
+//! Most nodes output a JSON object on their main `output` port; a port named
+//! after a field of that object (e.g. a geo-fence's `distance_km`) carries
+//! just that field. Ports a node kind does not declare are `any`.
+
+use core_models::{EdgeDef, NodeDef, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::NodeEngineError;
+
+/// Type of the value carried by a port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortType {
+    String,
+    Number,
+    /// A JSON object, e.g. a record or a node's full output
+    Record,
+    Array,
+    Boolean,
+    /// Accepts or produces anything
+    Any,
+}
+
+impl PortType {
+    /// Whether a value of type `self` may flow into a port of type `to`
+    pub fn is_compatible_with(self, to: PortType) -> bool {
+        self == PortType::Any || to == PortType::Any || self == to
+    }
+}
+
+impl fmt::Display for PortType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PortType::String => "string",
+            PortType::Number => "number",
+            PortType::Record => "record",
+            PortType::Array => "array",
+            PortType::Boolean => "boolean",
+            PortType::Any => "any",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Declared type of an input port
+pub fn input_type(node_type: &NodeType, port: &str) -> PortType {
+    use NodeType::*;
+    use PortType::*;
+
+    match (node_type, port) {
+        (ActionSendEmail, "to") => String,
+        (ActionSendSms | ActionWhatsapp, "phone" | "to" | "message") => String,
+        (ConditionIf | DataCreateRecord, "data") => Record,
+        (DataUpdateRecord | DataDeleteRecord, "record_id") => String,
+        (AiSummarize | AiClassify | AiExtract, "text") => String,
+        (AiContextAware, "query") => String,
+        (ActionDelay, "seconds") => Number,
+        (LogicMatch, "criteria") => Record,
+        (LogicGeoFence, "latitude" | "longitude") => Number,
+        (ForEach, "items") => Array,
+        _ => Any,
+    }
+}
+
+/// Declared type of an output port
+pub fn output_type(node_type: &NodeType, port: &str) -> PortType {
+    use NodeType::*;
+    use PortType::*;
+
+    match (node_type, port) {
+        // The loop body receives each item, whatever it is
+        (ForEach, "item") => Any,
+        (LogicGeoFence, "is_within") => Boolean,
+        (LogicGeoFence, "distance_km" | "radius_km") => Number,
+        (AiGenerate, "text") => String,
+        (AiSummarize, "summary") => String,
+        (AiClassify, "category") => String,
+        (AiClassify, "confidence") => Number,
+        (AiExtract, "extracted") => Record,
+        (HttpRequest, "status") => Number,
+        (DataSetField, "value") => Any,
+        (ScriptNode | FlowMerge | FlowSplit | FlowLoop | FlowSubGraph, _) => Any,
+        (UiShowField | UiHideField | UiSetRequired | UiSetReadonly, _) => Any,
+        (_, "output") => Record,
+        _ => Any,
+    }
+}
+
+/// Check that every edge connects an output to a compatible input
+pub fn validate_port_types(nodes: &[NodeDef], edges: &[EdgeDef]) -> Result<(), NodeEngineError> {
+    let node_types: HashMap<_, _> = nodes.iter().map(|n| (n.id, &n.node_type)).collect();
+
+    for edge in edges {
+        let (Some(source), Some(target)) = (node_types.get(&edge.source_node_id), node_types.get(&edge.target_node_id))
+        else {
+            continue;
+        };
+        let from_type = output_type(source, &edge.source_port);
+        let to_type = input_type(target, &edge.target_port);
+        if !from_type.is_compatible_with(to_type) {
+            return Err(NodeEngineError::IncompatiblePorts { edge_id: edge.id, from_type, to_type });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn node(node_type: NodeType) -> NodeDef {
+        NodeDef::new(Uuid::nil(), node_type, "Test", 0.0, 0.0)
+    }
+
+    fn edge(source: &NodeDef, source_port: &str, target: &NodeDef, target_port: &str) -> EdgeDef {
+        EdgeDef::new(Uuid::nil(), source.id, source_port, target.id, target_port)
+    }
+
+    #[test]
+    fn test_number_output_into_string_input_is_rejected() {
+        let (fence, email) = (node(NodeType::LogicGeoFence), node(NodeType::ActionSendEmail));
+        let bad = edge(&fence, "distance_km", &email, "to");
+        let nodes = vec![fence, email];
+
+        match validate_port_types(&nodes, std::slice::from_ref(&bad)) {
+            Err(NodeEngineError::IncompatiblePorts { edge_id, from_type, to_type }) => {
+                assert_eq!(edge_id, bad.id);
+                assert_eq!(from_type, PortType::Number);
+                assert_eq!(to_type, PortType::String);
+            }
+            other => panic!("Expected IncompatiblePorts, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_number_output_into_any_input_is_accepted() {
+        let (fence, webhook) = (node(NodeType::LogicGeoFence), node(NodeType::ActionSendWebhook));
+        let edges = vec![edge(&fence, "distance_km", &webhook, "data")];
+
+        assert_eq!(input_type(&NodeType::ActionSendWebhook, "data"), PortType::Any);
+        assert!(validate_port_types(&[fence, webhook], &edges).is_ok());
+    }
+}
@@ -46,7 +46,7 @@ impl NodeHandler for ScriptNodeHandler {
         &self,
         node: &NodeDef,
         inputs: HashMap<String, Value>,
-        _context: &mut ExecutionContext,
+        context: &mut ExecutionContext,
     ) -> Result<Value, NodeEngineError> {
         tracing::info!(node_id = %node.id, "Executing ScriptNode");
         
@@ -134,7 +134,7 @@ impl NodeHandler for ScriptNodeHandler {
         
         // Execute WASM plugin
         let output = self.executor
-            .execute(&plugin_source, function_name, wasm_input, &allowed_host_functions)
+            .execute(&plugin_source, function_name, wasm_input, &allowed_host_functions, context.trace_id.as_deref())
             .await
             .map_err(|e| NodeEngineError::NodeExecutionFailed {
                 node_id: node.id,
@@ -8,6 +8,9 @@ use sqlx::PgPool;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+#[cfg(feature = "backend")]
+use crate::matching::{filter_eligible_agents, MatchCriteria};
+
 /// Assignment strategy types
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -16,6 +19,8 @@ pub enum AssignmentStrategy {
     RoundRobin,
     /// Assign to agent with fewest active deals
     LoadBalanced,
+    /// Assign proportionally to each agent's spare capacity
+    WeightedRoundRobin,
     /// Manual assignment (no auto-assignment)
     Manual,
 }
@@ -46,6 +51,10 @@ impl AssignmentService {
             AssignmentStrategy::LoadBalanced => {
                 Self::find_agent_load_balanced(pool, tenant_id, pool_id).await
             }
+            AssignmentStrategy::WeightedRoundRobin => {
+                let stats = Self::get_agent_stats(pool, tenant_id).await?;
+                Ok(Self::assign_weighted(&stats))
+            }
             AssignmentStrategy::Manual => Ok(None),
         }
     }
@@ -153,19 +162,99 @@ impl AssignmentService {
         }
     }
 
+    /// Find an agent for a record: match criteria, filter, then apply the strategy
+    pub async fn find_agent_matching(
+        pool: &PgPool,
+        tenant_id: Uuid,
+        strategy: AssignmentStrategy,
+        criteria: &MatchCriteria,
+    ) -> Result<Option<Uuid>, String> {
+        let stats = Self::get_agent_stats(pool, tenant_id).await?;
+        Ok(Self::assign_matching(&stats, criteria, &strategy))
+    }
+
+    /// Apply a strategy to the agents eligible under `criteria`
+    ///
+    /// Works on in-memory stats: round robin picks the fewest total
+    /// assignments, load balanced the fewest active deals. Ties break on
+    /// lowest agent ID.
+    pub fn assign_matching(
+        candidates: &[AgentStats],
+        criteria: &MatchCriteria,
+        strategy: &AssignmentStrategy,
+    ) -> Option<Uuid> {
+        let eligible = filter_eligible_agents(candidates, criteria);
+        if eligible.is_empty() {
+            warn!(criteria = ?criteria, "No agents match assignment criteria");
+            return None;
+        }
+
+        match strategy {
+            AssignmentStrategy::RoundRobin => eligible
+                .iter()
+                .min_by(|a, b| a.total_assignments.cmp(&b.total_assignments).then(a.id.cmp(&b.id)))
+                .map(|agent| agent.id),
+            AssignmentStrategy::LoadBalanced => eligible
+                .iter()
+                .min_by(|a, b| a.active_deals.cmp(&b.active_deals).then(a.id.cmp(&b.id)))
+                .map(|agent| agent.id),
+            AssignmentStrategy::WeightedRoundRobin => Self::assign_weighted(&eligible),
+            AssignmentStrategy::Manual => None,
+        }
+    }
+
+    /// Pick an agent in proportion to capacity, ignoring agents that are full
+    ///
+    /// The agent with the lowest `open_count / capacity` ratio wins, so
+    /// repeated assignments fill agents proportionally to their capacity.
+    /// When every agent is at or above capacity, falls back to the least
+    /// loaded agent. Ties break on lowest `open_count`, then lowest ID.
+    pub fn assign_weighted(candidates: &[AgentStats]) -> Option<Uuid> {
+        let by_load = |a: &&AgentStats, b: &&AgentStats| {
+            a.open_count.cmp(&b.open_count).then(a.id.cmp(&b.id))
+        };
+
+        let available = candidates
+            .iter()
+            .filter(|agent| agent.capacity > 0 && agent.open_count < agent.capacity)
+            .min_by(|a, b| {
+                // Compare open/capacity ratios without floating point
+                (a.open_count as i128 * b.capacity as i128)
+                    .cmp(&(b.open_count as i128 * a.capacity as i128))
+                    .then_with(|| by_load(a, b))
+            });
+
+        match available {
+            Some(agent) => {
+                info!(agent_id = %agent.id, open = agent.open_count, capacity = agent.capacity, "Weighted round robin assigned agent");
+                Some(agent.id)
+            }
+            None => {
+                let fallback = candidates.iter().min_by(by_load).map(|agent| agent.id);
+                if fallback.is_some() {
+                    warn!("All agents at capacity, falling back to least loaded");
+                }
+                fallback
+            }
+        }
+    }
+
     /// Get assignment statistics for all agents
     pub async fn get_agent_stats(
         pool: &PgPool,
         tenant_id: Uuid,
     ) -> Result<Vec<AgentStats>, String> {
-        let rows: Vec<(Uuid, String, String, i64, i32)> = sqlx::query_as(
+        let rows: Vec<(Uuid, String, String, i64, i32, i64, serde_json::Value, serde_json::Value)> = sqlx::query_as(
             r#"
             SELECT 
                 u.id,
                 u.first_name,
                 u.last_name,
                 COUNT(d.id) as active_deals,
-                COALESCE(ars.assignment_count, 0) as total_assignments
+                COALESCE(ars.assignment_count, 0) as total_assignments,
+                COALESCE((u.preferences->>'capacity')::bigint, $2) as capacity,
+                COALESCE(u.preferences->'skills', '[]'::jsonb) as skills,
+                COALESCE(u.preferences->'territories', '[]'::jsonb) as territories
             FROM users u
             LEFT JOIN deals d ON d.owner_id = u.id 
                 AND d.tenant_id = $1 
@@ -176,26 +265,34 @@ impl AssignmentService {
             WHERE u.tenant_id = $1 
               AND u.role = 'agent' 
               AND u.is_active = true
-            GROUP BY u.id, u.first_name, u.last_name, ars.assignment_count
+            GROUP BY u.id, u.first_name, u.last_name, ars.assignment_count, u.preferences
             ORDER BY u.first_name, u.last_name
             "#
         )
         .bind(tenant_id)
+        .bind(DEFAULT_AGENT_CAPACITY)
         .fetch_all(pool)
         .await
         .map_err(|e| format!("Query failed: {}", e))?;
 
-        Ok(rows.into_iter().map(|(id, first_name, last_name, active_deals, total_assignments)| {
+        Ok(rows.into_iter().map(|(id, first_name, last_name, active_deals, total_assignments, capacity, skills, territories)| {
             AgentStats {
                 id,
                 name: format!("{} {}", first_name, last_name),
                 active_deals,
                 total_assignments,
+                capacity,
+                open_count: active_deals,
+                skills: serde_json::from_value(skills).unwrap_or_default(),
+                territories: serde_json::from_value(territories).unwrap_or_default(),
             }
         }).collect())
     }
 }
 
+/// Capacity used when an agent has none set in their preferences
+pub const DEFAULT_AGENT_CAPACITY: i64 = 20;
+
 /// Statistics for an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentStats {
@@ -203,6 +300,18 @@ pub struct AgentStats {
     pub name: String,
     pub active_deals: i64,
     pub total_assignments: i32,
+    /// Maximum open records the agent should hold
+    #[serde(default)]
+    pub capacity: i64,
+    /// Open records currently assigned to the agent
+    #[serde(default)]
+    pub open_count: i64,
+    /// Skills used by `MatchCriteria` (e.g. languages)
+    #[serde(default)]
+    pub skills: Vec<String>,
+    /// Territories the agent covers
+    #[serde(default)]
+    pub territories: Vec<String>,
 }
 
 #[cfg(test)]
@@ -220,4 +329,47 @@ mod tests {
         let json = serde_json::to_string(&strategy).unwrap();
         assert_eq!(json, "\"load_balanced\"");
     }
+
+    #[cfg(feature = "backend")]
+    fn agent(id: u128, capacity: i64, open_count: i64) -> AgentStats {
+        AgentStats {
+            id: Uuid::from_u128(id),
+            name: format!("Agent {}", id),
+            active_deals: open_count,
+            total_assignments: 0,
+            capacity,
+            open_count,
+            skills: Vec::new(),
+            territories: Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn test_weighted_distribution_follows_capacity() {
+        let mut agents = vec![agent(1, 20, 0), agent(2, 60, 0), agent(3, 120, 0)];
+
+        for _ in 0..100 {
+            let chosen = AssignmentService::assign_weighted(&agents).unwrap();
+            let assigned = agents.iter_mut().find(|a| a.id == chosen).unwrap();
+            assigned.open_count += 1;
+        }
+
+        let counts: Vec<i64> = agents.iter().map(|a| a.open_count).collect();
+        assert_eq!(counts, vec![10, 30, 60]);
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn test_weighted_skips_full_agents_and_falls_back() {
+        // Agent 1 is full, so agent 2 wins despite a higher open count
+        let agents = vec![agent(1, 5, 5), agent(2, 10, 8)];
+        assert_eq!(AssignmentService::assign_weighted(&agents), Some(Uuid::from_u128(2)));
+
+        // Everyone full: least loaded, then lowest ID
+        let agents = vec![agent(3, 4, 6), agent(2, 4, 4), agent(1, 2, 4)];
+        assert_eq!(AssignmentService::assign_weighted(&agents), Some(Uuid::from_u128(1)));
+
+        assert_eq!(AssignmentService::assign_weighted(&[]), None);
+    }
 }
@@ -0,0 +1,274 @@
+//! Message Templates - `{{record.field}}` interpolation
+//!
+//! Shared by the notification and WhatsApp handlers. Supports:
+//! - Dotted paths into the context (`{{record.owner.name}}`)
+//! - `default` filter for missing values (`{{record.phone | default: "N/A"}}`)
+//! - `url_encode` filter for click-to-chat links (`{{message | url_encode}}`)
+//!
+//! Nothing is escaped unless a filter asks for it.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("Unknown template variable: {path}")]
+    UnknownVariable { path: String },
+
+    #[error("Unclosed template tag at byte {position}")]
+    UnclosedTag { position: usize },
+
+    #[error("Empty template tag at byte {position}")]
+    EmptyTag { position: usize },
+
+    #[error("Unknown template filter: {name}")]
+    UnknownFilter { name: String },
+
+    #[error("Invalid argument for filter {name}: {message}")]
+    InvalidFilterArgument { name: String, message: String },
+}
+
+/// Render `template`, resolving each `{{ path | filter }}` tag against `context`
+pub fn render_template(template: &str, context: &Value) -> Result<String, TemplateError> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    let mut offset = 0;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let position = offset + start;
+        let after_open = &rest[start + 2..];
+        let end = after_open
+            .find("}}")
+            .ok_or(TemplateError::UnclosedTag { position })?;
+
+        output.push_str(&render_tag(&after_open[..end], context, position)?);
+
+        let consumed = start + 2 + end + 2;
+        rest = &rest[consumed..];
+        offset += consumed;
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Build the render context for a node: node inputs, `trigger`, `record`,
+/// and the trigger's top-level fields. Inputs win on key collisions.
+pub fn template_context(inputs: &HashMap<String, Value>, trigger_data: &Value) -> Value {
+    let mut context = Map::new();
+
+    if let Value::Object(fields) = trigger_data {
+        for (key, value) in fields {
+            context.insert(key.clone(), value.clone());
+        }
+    }
+
+    let record = trigger_data.get("record").cloned().unwrap_or_else(|| trigger_data.clone());
+    context.insert("record".to_string(), record);
+    context.insert("trigger".to_string(), trigger_data.clone());
+
+    for (key, value) in inputs {
+        context.insert(key.clone(), value.clone());
+    }
+
+    Value::Object(context)
+}
+
+fn render_tag(tag: &str, context: &Value, position: usize) -> Result<String, TemplateError> {
+    let mut segments = split_filters(tag).into_iter();
+    let path = segments.next().unwrap_or_default();
+    if path.is_empty() {
+        return Err(TemplateError::EmptyTag { position });
+    }
+
+    let mut value = lookup(context, path).map(value_to_string);
+
+    for filter in segments {
+        let (name, arg) = match filter.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (filter, None),
+        };
+
+        value = match name {
+            "default" => {
+                let fallback = parse_string_literal(name, arg)?;
+                Some(value.unwrap_or(fallback))
+            }
+            "url_encode" => value.map(|v| url_encode(&v)),
+            _ => return Err(TemplateError::UnknownFilter { name: name.to_string() }),
+        };
+    }
+
+    value.ok_or_else(|| TemplateError::UnknownVariable { path: path.to_string() })
+}
+
+/// Split `path | filter: "a|b" | filter` on pipes outside quotes
+fn split_filters(tag: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (i, c) in tag.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '|' if !in_quotes => {
+                parts.push(tag[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(tag[start..].trim());
+    parts
+}
+
+/// Resolve a dotted path; `null` counts as missing so `default` applies
+fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut current = context;
+    for key in path.split('.') {
+        current = match current {
+            Value::Object(map) => map.get(key)?,
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    (!current.is_null()).then_some(current)
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn parse_string_literal(name: &str, arg: Option<&str>) -> Result<String, TemplateError> {
+    let invalid = |message: &str| TemplateError::InvalidFilterArgument {
+        name: name.to_string(),
+        message: message.to_string(),
+    };
+    let arg = arg.ok_or_else(|| invalid("expected a quoted string"))?;
+
+    arg.strip_prefix('"')
+        .and_then(|a| a.strip_suffix('"'))
+        .map(|a| a.to_string())
+        .ok_or_else(|| invalid("expected a quoted string"))
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn url_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn context() -> Value {
+        json!({
+            "record": {
+                "first_name": "Layla",
+                "amount": 1250.5,
+                "phone": null,
+                "owner": { "name": "Omar", "email": "omar@example.com" },
+                "tags": ["vip", "investor"]
+            },
+            "message": "Hi Layla, is 5pm ok? (villa #12)"
+        })
+    }
+
+    #[test]
+    fn test_renders_nested_paths() {
+        let rendered = render_template(
+            "{{record.first_name}} ({{ record.amount }}) is owned by {{record.owner.name}}, tag {{record.tags.0}}",
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "Layla (1250.5) is owned by Omar, tag vip");
+    }
+
+    #[test]
+    fn test_missing_value_errors_without_default() {
+        assert_eq!(
+            render_template("Call {{record.owner.phone}}", &context()),
+            Err(TemplateError::UnknownVariable { path: "record.owner.phone".to_string() })
+        );
+        // null is treated as missing
+        assert!(render_template("{{record.phone}}", &context()).is_err());
+    }
+
+    #[test]
+    fn test_default_filter() {
+        let rendered = render_template(
+            r#"{{record.phone | default: "N/A"}} / {{record.first_name | default: "there"}} / {{x | default: "a|b"}}"#,
+            &context(),
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "N/A / Layla / a|b");
+    }
+
+    #[test]
+    fn test_url_encode_filter() {
+        let rendered = render_template("https://wa.me/971500000000?text={{message | url_encode}}", &context()).unwrap();
+
+        assert_eq!(
+            rendered,
+            "https://wa.me/971500000000?text=Hi%20Layla%2C%20is%205pm%20ok%3F%20%28villa%20%2312%29"
+        );
+        // Filters chain left to right
+        assert_eq!(
+            render_template(r#"{{record.phone | default: "a b" | url_encode}}"#, &context()).unwrap(),
+            "a%20b"
+        );
+    }
+
+    #[test]
+    fn test_no_escaping_by_default() {
+        let rendered = render_template("<b>{{message}}</b>", &context()).unwrap();
+        assert_eq!(rendered, "<b>Hi Layla, is 5pm ok? (villa #12)</b>");
+    }
+
+    #[test]
+    fn test_malformed_templates() {
+        assert_eq!(
+            render_template("Hello {{record.first_name", &context()),
+            Err(TemplateError::UnclosedTag { position: 6 })
+        );
+        assert_eq!(
+            render_template("{{record.first_name | upper}}", &context()),
+            Err(TemplateError::UnknownFilter { name: "upper".to_string() })
+        );
+        assert!(matches!(
+            render_template("{{record.phone | default: N/A}}", &context()),
+            Err(TemplateError::InvalidFilterArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_template_context_exposes_record_and_inputs() {
+        let mut inputs = HashMap::new();
+        inputs.insert("first_name".to_string(), json!("Input"));
+        let trigger = json!({ "record": { "first_name": "Layla" }, "first_name": "Trigger", "event": "created" });
+
+        let ctx = template_context(&inputs, &trigger);
+
+        assert_eq!(
+            render_template("{{record.first_name}} {{first_name}} {{trigger.event}} {{event}}", &ctx).unwrap(),
+            "Layla Input created created"
+        );
+    }
+}
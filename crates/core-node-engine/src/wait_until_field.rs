@@ -0,0 +1,155 @@
+//! Wait-until-field node - pause a run until a record's date arrives
+//!
+//! Node config:
+//! - `field`: date (`2025-06-30`, midnight UTC) or datetime field of the
+//!   triggering record, e.g. `expected_close_date`
+//!
+//! A future value suspends the run until then; the executor stores the
+//! resume time on the execution and the scheduled trigger runner picks it
+//! up with the other delayed actions. On resume the field is read again
+//! from the database, so a moved date waits for the new one. An empty,
+//! unparseable or past value proceeds immediately.
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use core_models::NodeDef;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::context::ExecutionContext;
+use crate::nodes::NodeHandler;
+use crate::NodeEngineError;
+
+pub struct WaitUntilFieldHandler {
+    pool: PgPool,
+}
+
+impl WaitUntilFieldHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Current value of `field` on the stored record; `Null` if it is gone
+    async fn reload_field(&self, context: &ExecutionContext, field: &str) -> Result<Value, NodeEngineError> {
+        let Some(record_id) = trigger_record_id(context) else {
+            return Ok(Value::Null);
+        };
+        let value: Option<Option<Value>> = sqlx::query_scalar(
+            "SELECT data->$3 FROM entity_records WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(record_id)
+        .bind(context.tenant_id)
+        .bind(field)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(value.flatten().unwrap_or(Value::Null))
+    }
+}
+
+#[async_trait]
+impl NodeHandler for WaitUntilFieldHandler {
+    async fn execute(
+        &self,
+        node: &NodeDef,
+        _inputs: HashMap<String, Value>,
+        context: &mut ExecutionContext,
+    ) -> Result<Value, NodeEngineError> {
+        let field = node
+            .config
+            .get("field")
+            .and_then(Value::as_str)
+            .ok_or_else(|| NodeEngineError::InvalidConfig("wait_until_field requires `field`".to_string()))?;
+
+        // The run was suspended here before: the record may have changed since
+        let resuming = context
+            .values
+            .get(&node.id.to_string())
+            .and_then(|output| output.get("suspend"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let value = if resuming {
+            self.reload_field(context, field).await?
+        } else {
+            trigger_record(context).and_then(|record| record.get(field)).cloned().unwrap_or(Value::Null)
+        };
+
+        Ok(wait_output(field, &value, Utc::now()))
+    }
+}
+
+/// Node output for waiting on `value` at `now`
+fn wait_output(field: &str, value: &Value, now: DateTime<Utc>) -> Value {
+    match parse_instant(value) {
+        Some(until) if until > now => {
+            tracing::info!(field = field, resume_at = %until, "Waiting for record date");
+            json!({
+                "action": "wait_until_field",
+                "field": field,
+                "suspend": true,
+                "rerun_on_resume": true,
+                "resume_at": until.to_rfc3339(),
+                "completed": false
+            })
+        }
+        until => json!({
+            "action": "wait_until_field",
+            "field": field,
+            "waited_until": until.map(|u| u.to_rfc3339()),
+            "completed": true
+        }),
+    }
+}
+
+/// Record data the run was triggered with
+fn trigger_record(context: &ExecutionContext) -> Option<&Value> {
+    let trigger = context.values.get("$trigger")?;
+    trigger.get("new_values").filter(|v| v.is_object()).or_else(|| trigger.get("data"))
+}
+
+fn trigger_record_id(context: &ExecutionContext) -> Option<Uuid> {
+    context.values.get("$trigger")?.get("record_id")?.as_str()?.parse().ok()
+}
+
+/// A datetime (RFC 3339, or naive as UTC) or a date (midnight UTC)
+fn parse_instant(value: &Value) -> Option<DateTime<Utc>> {
+    let s = value.as_str()?.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Some(dt.and_utc());
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_future_date_suspends_until_it() {
+        let now = utc("2025-06-01T12:00:00Z");
+        let output = wait_output("expected_close_date", &json!("2025-06-30"), now);
+        assert_eq!(output["suspend"], json!(true));
+        assert_eq!(output["resume_at"], json!("2025-06-30T00:00:00+00:00"));
+
+        let output = wait_output("follow_up_at", &json!("2025-06-01T14:30:00+02:00"), now);
+        assert_eq!(output["resume_at"], json!("2025-06-01T12:30:00+00:00"));
+    }
+
+    #[test]
+    fn test_past_or_empty_date_proceeds() {
+        let now = utc("2025-06-01T12:00:00Z");
+        for value in [json!("2025-05-31"), json!(null), json!(""), json!("next week")] {
+            let output = wait_output("expected_close_date", &value, now);
+            assert_eq!(output["completed"], json!(true), "{}", value);
+            assert!(output.get("suspend").is_none());
+        }
+    }
+}
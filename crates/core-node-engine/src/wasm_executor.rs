@@ -3,7 +3,7 @@
 //! Provides sandboxed execution of user-defined logic in WASM.
 //! Supports plugins written in Rust, JavaScript, Python, Go, etc.
 
-use extism::{Manifest, Plugin, Wasm};
+use extism::{CurrentPlugin, Function, Manifest, Plugin, UserData, Val, Wasm, PTR};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -50,6 +50,8 @@ impl WasmExecutor {
     /// * `function_name` - Name of the function to call in the WASM module
     /// * `input` - JSON input to pass to the function
     /// * `allowed_host_functions` - List of host functions this plugin can call
+    /// * `trace_id` - Correlation id of the operation running the plugin; tags
+    ///   its log entries and is readable as the `trace_id` config key
     ///
     /// # Returns
     /// JSON output from the WASM function
@@ -59,14 +61,16 @@ impl WasmExecutor {
         function_name: &str,
         input: JsonValue,
         allowed_host_functions: &[String],
+        trace_id: Option<&str>,
     ) -> Result<JsonValue, NodeEngineError> {
         tracing::debug!(
             function = function_name,
+            trace_id = trace_id.unwrap_or_default(),
             "Executing WASM plugin"
         );
         
         // Get or load plugin
-        let mut plugin = self.get_or_load_plugin(plugin_source, allowed_host_functions).await?;
+        let mut plugin = self.get_or_load_plugin(plugin_source, allowed_host_functions, trace_id).await?;
         
         // Serialize input to JSON bytes
         let input_bytes = serde_json::to_vec(&input)
@@ -95,6 +99,7 @@ impl WasmExecutor {
         &self,
         source: &PluginSource,
         _allowed_host_functions: &[String],
+        trace_id: Option<&str>,
     ) -> Result<Plugin, NodeEngineError> {
         let cache_key = source.cache_key();
         
@@ -148,10 +153,13 @@ impl WasmExecutor {
         
         // Create Extism manifest
         let wasm = Wasm::data(wasm_bytes);
-        let manifest = Manifest::new([wasm]);
+        let mut manifest = Manifest::new([wasm]);
+        if let Some(trace_id) = trace_id {
+            manifest = manifest.with_config_key("trace_id", trace_id);
+        }
         
         // Create plugin with configuration
-        let plugin = Plugin::new(&manifest, [], true)
+        let plugin = Plugin::new(&manifest, [log_function(trace_id)], true)
             .map_err(|e| NodeEngineError::WasmError(format!("Failed to create plugin: {}", e)))?;
         
         // Set memory limit if configured
@@ -181,6 +189,24 @@ impl WasmExecutor {
     }
 }
 
+/// The `log(level, message)` host function, tagging entries with `trace_id`
+fn log_function(trace_id: Option<&str>) -> Function {
+    Function::new(
+        "log",
+        [PTR, PTR],
+        [],
+        UserData::new(trace_id.map(String::from)),
+        |plugin: &mut CurrentPlugin, inputs: &[Val], _outputs: &mut [Val], trace_id: UserData<Option<String>>| {
+            let level: String = plugin.memory_get_val(&inputs[0])?;
+            let message: String = plugin.memory_get_val(&inputs[1])?;
+            let trace_id = trace_id.get()?;
+            let trace_id = trace_id.lock().map_err(|_| extism::Error::msg("trace id lock poisoned"))?;
+            HostFunctions::log(&level, &message, trace_id.as_deref());
+            Ok(())
+        },
+    )
+}
+
 impl Default for WasmExecutor {
     fn default() -> Self {
         Self::new()
@@ -245,14 +271,16 @@ pub struct WasmPluginConfig {
 pub struct HostFunctions;
 
 impl HostFunctions {
-    /// Log a message from WASM (always allowed)
-    pub fn log(level: &str, message: &str) {
+    /// Log a message from WASM (always allowed), tagged with the trace id
+    /// of the operation running the plugin
+    pub fn log(level: &str, message: &str, trace_id: Option<&str>) {
+        let trace_id = trace_id.unwrap_or_default();
         match level {
-            "error" => tracing::error!("[WASM Plugin] {}", message),
-            "warn" => tracing::warn!("[WASM Plugin] {}", message),
-            "info" => tracing::info!("[WASM Plugin] {}", message),
-            "debug" => tracing::debug!("[WASM Plugin] {}", message),
-            _ => tracing::trace!("[WASM Plugin] {}", message),
+            "error" => tracing::error!(trace_id, "[WASM Plugin] {}", message),
+            "warn" => tracing::warn!(trace_id, "[WASM Plugin] {}", message),
+            "info" => tracing::info!(trace_id, "[WASM Plugin] {}", message),
+            "debug" => tracing::debug!(trace_id, "[WASM Plugin] {}", message),
+            _ => tracing::trace!(trace_id, "[WASM Plugin] {}", message),
         }
     }
     
@@ -6,6 +6,7 @@
 //! - Interactive messages
 //! - Media messages
 //! - Message status webhooks
+//! - Delivery tracking with retry (`send_with_tracking`)
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -106,6 +107,30 @@ pub struct WhatsAppSendResult {
     pub status: String,
 }
 
+/// Delivery status of a tracked outbound message
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageStatus {
+    /// Queued or waiting for a retry
+    Pending,
+    Sent,
+    Delivered,
+    Read,
+    Failed,
+}
+
+impl MessageStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageStatus::Pending => "pending",
+            MessageStatus::Sent => "sent",
+            MessageStatus::Delivered => "delivered",
+            MessageStatus::Read => "read",
+            MessageStatus::Failed => "failed",
+        }
+    }
+}
+
 /// WhatsApp API errors
 #[derive(Debug, thiserror::Error)]
 pub enum WhatsAppError {
@@ -315,6 +340,7 @@ use serde_json::{json, Value};
 use crate::context::ExecutionContext;
 use crate::NodeEngineError;
 use crate::nodes::NodeHandler;
+use crate::template::{render_template, template_context, TemplateError};
 
 /// WhatsApp Action Handler for workflow engine
 pub struct ActionWhatsAppHandler {
@@ -342,6 +368,9 @@ impl NodeHandler for ActionWhatsAppHandler {
             .and_then(|v| v.as_str())
             .ok_or_else(|| NodeEngineError::InvalidConfig("Missing phone number".into()))?;
         
+        // Values available to {{record.field}} placeholders
+        let variables = template_context(&inputs, &context.trigger_data);
+        
         // Get message type
         let message_type = node.config.get("message_type")
             .and_then(|v| v.as_str())
@@ -366,13 +395,14 @@ impl NodeHandler for ActionWhatsAppHandler {
                     .and_then(|v| v.as_array()) 
                 {
                     let parameters: Vec<TemplateParameter> = body_params.iter()
-                        .filter_map(|p| p.as_str().map(|s| TemplateParameter {
+                        .filter_map(|p| p.as_str())
+                        .map(|s| Ok(TemplateParameter {
                             param_type: "text".to_string(),
-                            text: Some(s.to_string()),
+                            text: Some(render_template(s, &variables)?),
                             currency: None,
                             image: None,
                         }))
-                        .collect();
+                        .collect::<Result<_, TemplateError>>()?;
                     
                     if !parameters.is_empty() {
                         components.push(TemplateComponent {
@@ -395,7 +425,7 @@ impl NodeHandler for ActionWhatsAppHandler {
                     .ok_or_else(|| NodeEngineError::InvalidConfig("Missing message body".into()))?;
                 
                 WhatsAppMessage::Text {
-                    body: body.to_string(),
+                    body: render_template(body, &variables)?,
                     preview_url: false,
                 }
             }
@@ -419,6 +449,270 @@ impl NodeHandler for ActionWhatsAppHandler {
     }
 }
 
+// ============================================================================
+// Delivery Tracking
+// ============================================================================
+
+use crate::events::EntityEvent;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Outbound message persisted in `whatsapp_messages`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedMessage {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub recipient: String,
+    pub message: WhatsAppMessage,
+    /// Meta message ID (`wamid...`), set once the send succeeds
+    pub provider_message_id: Option<String>,
+    pub status: MessageStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Retry policy for tracked sends
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles per attempt
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Storage for tracked messages
+#[async_trait]
+pub trait MessageStore: Send + Sync {
+    async fn insert(&self, message: &TrackedMessage) -> Result<(), NodeEngineError>;
+    
+    async fn get(&self, id: Uuid) -> Result<Option<TrackedMessage>, NodeEngineError>;
+    
+    async fn save(&self, message: &TrackedMessage) -> Result<(), NodeEngineError>;
+    
+    /// Publish an entity event (permanent delivery failures)
+    async fn publish(&self, event: &EntityEvent) -> Result<(), NodeEngineError>;
+}
+
+/// Postgres-backed `whatsapp_messages` store
+pub struct PgMessageStore {
+    pool: PgPool,
+}
+
+impl PgMessageStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MessageStore for PgMessageStore {
+    async fn insert(&self, message: &TrackedMessage) -> Result<(), NodeEngineError> {
+        sqlx::query(
+            r#"
+            INSERT INTO whatsapp_messages (
+                id, tenant_id, recipient, message, provider_message_id,
+                status, attempts, last_error, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(message.id)
+        .bind(message.tenant_id)
+        .bind(&message.recipient)
+        .bind(serde_json::to_value(&message.message).unwrap_or_default())
+        .bind(&message.provider_message_id)
+        .bind(message.status.as_str())
+        .bind(message.attempts as i32)
+        .bind(&message.last_error)
+        .bind(message.created_at)
+        .bind(message.updated_at)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn get(&self, id: Uuid) -> Result<Option<TrackedMessage>, NodeEngineError> {
+        use sqlx::Row;
+        
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, recipient, message, provider_message_id,
+                   status, attempts, last_error, created_at, updated_at
+            FROM whatsapp_messages
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        
+        let message: serde_json::Value = row.try_get("message")?;
+        let status: String = row.try_get("status")?;
+        let attempts: i32 = row.try_get("attempts")?;
+        
+        Ok(Some(TrackedMessage {
+            id: row.try_get("id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            recipient: row.try_get("recipient")?,
+            message: serde_json::from_value(message)
+                .map_err(|e| NodeEngineError::InvalidInput(format!("Invalid stored message: {}", e)))?,
+            provider_message_id: row.try_get("provider_message_id")?,
+            status: serde_json::from_str(&format!("\"{}\"", status))
+                .map_err(|_| NodeEngineError::InvalidInput(format!("Unknown message status: {}", status)))?,
+            attempts: attempts as u32,
+            last_error: row.try_get("last_error")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        }))
+    }
+    
+    async fn save(&self, message: &TrackedMessage) -> Result<(), NodeEngineError> {
+        sqlx::query(
+            r#"
+            UPDATE whatsapp_messages
+            SET provider_message_id = $2, status = $3, attempts = $4,
+                last_error = $5, updated_at = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(message.id)
+        .bind(&message.provider_message_id)
+        .bind(message.status.as_str())
+        .bind(message.attempts as i32)
+        .bind(&message.last_error)
+        .bind(message.updated_at)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn publish(&self, event: &EntityEvent) -> Result<(), NodeEngineError> {
+        crate::events::EventPublisher::new(self.pool.clone()).publish(event).await?;
+        Ok(())
+    }
+}
+
+/// Send a message and track its delivery
+///
+/// Persists the message as `Pending` and delivers it in the background,
+/// retrying with exponential backoff. Returns the tracking ID, which
+/// callers poll via `MessageStore::get`. Status callbacks from Meta move
+/// it on to `Delivered` / `Read`.
+pub async fn send_with_tracking(
+    provider: Arc<dyn WhatsAppProvider>,
+    store: Arc<dyn MessageStore>,
+    tenant_id: Uuid,
+    to: &str,
+    message: WhatsAppMessage,
+    policy: RetryPolicy,
+) -> Result<Uuid, NodeEngineError> {
+    let now = Utc::now();
+    let tracked = TrackedMessage {
+        id: Uuid::new_v4(),
+        tenant_id,
+        recipient: to.to_string(),
+        message,
+        provider_message_id: None,
+        status: MessageStatus::Pending,
+        attempts: 0,
+        last_error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    store.insert(&tracked).await?;
+    
+    let id = tracked.id;
+    tokio::spawn(async move {
+        if let Err(e) = deliver(provider.as_ref(), store.as_ref(), tracked, &policy).await {
+            tracing::error!(message_id = %id, error = %e, "Failed to update WhatsApp delivery status");
+        }
+    });
+    
+    Ok(id)
+}
+
+async fn deliver(
+    provider: &dyn WhatsAppProvider,
+    store: &dyn MessageStore,
+    mut tracked: TrackedMessage,
+    policy: &RetryPolicy,
+) -> Result<(), NodeEngineError> {
+    loop {
+        tracked.attempts += 1;
+        let result = provider.send_message(&tracked.recipient, tracked.message.clone()).await;
+        tracked.updated_at = Utc::now();
+        
+        let error = match result {
+            Ok(sent) => {
+                tracked.status = MessageStatus::Sent;
+                tracked.provider_message_id = Some(sent.message_id);
+                tracked.last_error = None;
+                return store.save(&tracked).await;
+            }
+            Err(e) => e,
+        };
+        
+        // Bad numbers and unknown templates will not succeed on retry
+        let retryable = !matches!(error, WhatsAppError::InvalidPhone(_) | WhatsAppError::TemplateNotFound(_));
+        tracked.last_error = Some(error.to_string());
+        
+        if retryable && tracked.attempts < policy.max_attempts {
+            let delay = policy.base_delay.saturating_mul(1u32 << (tracked.attempts - 1).min(16));
+            tracing::warn!(
+                message_id = %tracked.id,
+                attempt = tracked.attempts,
+                error = %error,
+                "WhatsApp send failed, retrying in {:?}", delay
+            );
+            store.save(&tracked).await?;
+            tokio::time::sleep(delay).await;
+            continue;
+        }
+        
+        tracing::error!(
+            message_id = %tracked.id,
+            attempts = tracked.attempts,
+            error = %error,
+            "WhatsApp send permanently failed"
+        );
+        tracked.status = MessageStatus::Failed;
+        store.save(&tracked).await?;
+        
+        let event = EntityEvent::custom(
+            tracked.tenant_id,
+            "whatsapp_message",
+            tracked.id,
+            "delivery_failed",
+            json!({
+                "recipient": tracked.recipient,
+                "attempts": tracked.attempts,
+                "error": tracked.last_error,
+            }),
+            None,
+        );
+        return store.publish(&event).await;
+    }
+}
+
 /// Pre-approved WhatsApp templates for common use cases
 pub mod templates {
     /// Template for viewing confirmation
@@ -436,3 +730,129 @@ pub mod templates {
     /// Template for lead follow-up
     pub const LEAD_FOLLOWUP: &str = "lead_followup";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventType;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+    
+    /// Provider that fails the first `failures` sends
+    struct FlakyProvider {
+        failures: u32,
+        calls: AtomicU32,
+    }
+    
+    #[async_trait]
+    impl WhatsAppProvider for FlakyProvider {
+        async fn send_message(&self, _to: &str, _message: WhatsAppMessage) -> Result<WhatsAppSendResult, WhatsAppError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call <= self.failures {
+                return Err(WhatsAppError::NetworkError(format!("timeout on attempt {}", call)));
+            }
+            Ok(WhatsAppSendResult {
+                message_id: format!("wamid.{}", call),
+                status: "sent".to_string(),
+            })
+        }
+        
+        async fn get_message_status(&self, _message_id: &str) -> Result<String, WhatsAppError> {
+            unimplemented!()
+        }
+        
+        async fn mark_as_read(&self, _message_id: &str) -> Result<(), WhatsAppError> {
+            unimplemented!()
+        }
+    }
+    
+    #[derive(Default)]
+    struct MemoryStore {
+        messages: Mutex<HashMap<Uuid, TrackedMessage>>,
+        events: Mutex<Vec<EntityEvent>>,
+    }
+    
+    #[async_trait]
+    impl MessageStore for MemoryStore {
+        async fn insert(&self, message: &TrackedMessage) -> Result<(), NodeEngineError> {
+            self.messages.lock().unwrap().insert(message.id, message.clone());
+            Ok(())
+        }
+        
+        async fn get(&self, id: Uuid) -> Result<Option<TrackedMessage>, NodeEngineError> {
+            Ok(self.messages.lock().unwrap().get(&id).cloned())
+        }
+        
+        async fn save(&self, message: &TrackedMessage) -> Result<(), NodeEngineError> {
+            self.messages.lock().unwrap().insert(message.id, message.clone());
+            Ok(())
+        }
+        
+        async fn publish(&self, event: &EntityEvent) -> Result<(), NodeEngineError> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+    
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+    
+    fn text() -> WhatsAppMessage {
+        WhatsAppMessage::Text { body: "Your viewing is confirmed".to_string(), preview_url: false }
+    }
+    
+    /// Poll until the message leaves `Pending`
+    async fn wait_for_outcome(store: &MemoryStore, id: Uuid) -> TrackedMessage {
+        for _ in 0..200 {
+            let message = store.get(id).await.unwrap().unwrap();
+            if message.status != MessageStatus::Pending {
+                return message;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("message {} never left pending", id);
+    }
+    
+    #[tokio::test]
+    async fn test_send_with_tracking_retries_then_succeeds() {
+        let provider = Arc::new(FlakyProvider { failures: 2, calls: AtomicU32::new(0) });
+        let store = Arc::new(MemoryStore::default());
+        
+        let id = send_with_tracking(provider.clone(), store.clone(), Uuid::new_v4(), "+971500000000", text(), policy())
+            .await
+            .unwrap();
+        let message = wait_for_outcome(&store, id).await;
+        
+        assert_eq!(message.status, MessageStatus::Sent);
+        assert_eq!(message.attempts, 3);
+        assert_eq!(message.provider_message_id.as_deref(), Some("wamid.3"));
+        assert!(store.events.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_permanent_failure_after_max_retries() {
+        let provider = Arc::new(FlakyProvider { failures: u32::MAX, calls: AtomicU32::new(0) });
+        let store = Arc::new(MemoryStore::default());
+        let tenant_id = Uuid::new_v4();
+        
+        let id = send_with_tracking(provider.clone(), store.clone(), tenant_id, "+971500000000", text(), policy())
+            .await
+            .unwrap();
+        let message = wait_for_outcome(&store, id).await;
+        
+        assert_eq!(message.status, MessageStatus::Failed);
+        assert_eq!(message.attempts, 3);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+        assert!(message.last_error.unwrap().contains("attempt 3"));
+        
+        let events = store.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].tenant_id, tenant_id);
+        assert_eq!(events[0].record_id, id);
+        assert_eq!(events[0].event_type, EventType::Custom("delivery_failed".to_string()));
+    }
+}
@@ -486,6 +486,16 @@ pub async fn fetch_field_defs(entity_name: &str) -> Result<Vec<FieldDef>, String
     fetch_json(&url).await
 }
 
+/// Field definitions for a form: the display shape, plus the full models
+/// (with validation rules) of those that parse
+pub async fn fetch_form_fields(entity_name: &str) -> Result<(Vec<FieldDef>, Vec<core_models::field::FieldDef>), String> {
+    let url = format!("{}/metadata/entities/{}/fields", API_BASE, entity_name);
+    let raw: Vec<serde_json::Value> = fetch_json(&url).await?;
+    let display = raw.iter().cloned().map(serde_json::from_value).collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+    let models = raw.into_iter().filter_map(|f| serde_json::from_value(f).ok()).collect();
+    Ok((display, models))
+}
+
 /// Add a new option to a select/status field's options list
 /// This persists the option permanently in the database
 pub async fn add_field_option(
@@ -552,6 +562,117 @@ pub async fn fetch_entity_lookup(
     fetch_json::<Vec<LookupResult>>(&url).await
 }
 
+/// A record as a picker option, labelled by its entity's display field
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LookupOption {
+    pub value: String,
+    pub label: String,
+}
+
+/// Records of `entity_type` whose label starts with `query`, or with `ids`
+/// set, the labels of those records
+pub async fn lookup_entity_options(
+    entity_type: &str,
+    query: &str,
+    ids: &[String],
+) -> Result<Vec<LookupOption>, String> {
+    let params = if ids.is_empty() {
+        format!("q={}", urlencoding::encode(query))
+    } else {
+        format!("ids={}", urlencoding::encode(&ids.join(",")))
+    };
+    let url = format!("{}/entities/{}/lookup?tenant_id={}&{}", API_BASE, entity_type, TENANT_ID, params);
+    fetch_json::<Vec<LookupOption>>(&url).await
+}
+
+// ============================================================================
+// UPLOADS
+// ============================================================================
+
+/// A file stored by `POST /uploads`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UploadedFile {
+    pub url: String,
+    pub file_name: String,
+    pub size: u64,
+    pub content_type: String,
+    pub checksum: String,
+}
+
+/// Upload `file` for an Image or Attachment field; the returned URL is what
+/// the record stores
+pub async fn upload_file(file: &web_sys::File) -> Result<UploadedFile, String> {
+    let form = web_sys::FormData::new().map_err(|e| format!("Form error: {:?}", e))?;
+    form.append_with_blob_and_filename("file", file, &file.name())
+        .map_err(|e| format!("Form error: {:?}", e))?;
+    post_upload(&form).await
+}
+
+/// Upload a signature captured as a PNG data URL. The server keeps
+/// signatures for audit; the record stores the returned URL.
+pub async fn upload_signature(data_url: &str) -> Result<UploadedFile, String> {
+    let (content_type, bytes) = crate::components::signature_pad::decode_data_url(data_url)?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes.as_slice()));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(&content_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(|e| format!("Blob error: {:?}", e))?;
+
+    let form = web_sys::FormData::new().map_err(|e| format!("Form error: {:?}", e))?;
+    // The purpose must come before the file
+    form.append_with_str("purpose", "signature")
+        .map_err(|e| format!("Form error: {:?}", e))?;
+    form.append_with_blob_and_filename("file", &blob, "signature.png")
+        .map_err(|e| format!("Form error: {:?}", e))?;
+    post_upload(&form).await
+}
+
+async fn post_upload(form: &web_sys::FormData) -> Result<UploadedFile, String> {
+    let window = web_sys::window().ok_or("no window")?;
+
+    let opts = RequestInit::new();
+    opts.set_method("POST");
+    opts.set_mode(RequestMode::Cors);
+    // No Content-Type: the browser sets the multipart boundary
+    opts.set_body(form);
+
+    let request = Request::new_with_str_and_init(&format!("{}/uploads", get_api_base()), &opts)
+        .map_err(|e| format!("Request error: {:?}", e))?;
+    let headers = request.headers();
+    let _ = headers.set("X-Tenant-Id", TENANT_ID);
+    let _ = headers.set("X-Tenant-Slug", "demo");
+
+    let resp_value = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|e| format!("Fetch error: {:?}", e))?;
+    let resp: Response = resp_value.dyn_into()
+        .map_err(|_| "response conversion error")?;
+
+    let json = JsFuture::from(resp.json().map_err(|e| format!("JSON parse error: {:?}", e))?)
+        .await
+        .map_err(|e| format!("JSON await error: {:?}", e))?;
+    if !resp.ok() {
+        // Size and type rejections explain themselves in `error`
+        let body: serde_json::Value = serde_wasm_bindgen::from_value(json).unwrap_or_default();
+        return Err(body["error"].as_str().map(String::from).unwrap_or_else(|| format!("HTTP error: {}", resp.status())));
+    }
+
+    serde_wasm_bindgen::from_value(json)
+        .map_err(|e| format!("Deserialize error: {:?}", e))
+}
+
+/// Address to fetch a stored upload from. Files kept by the API come back
+/// as paths, which are resolved against the API host for this tenant.
+pub fn upload_src(url: &str) -> String {
+    if !url.starts_with('/') {
+        return url.to_string();
+    }
+    let base = get_api_base();
+    let origin = base.strip_suffix("/api/v1").unwrap_or(&base);
+    format!("{}{}?tenant_id={}", origin, url, TENANT_ID)
+}
+
 // ============================================================================
 // GENERIC ENTITY CRUD FUNCTIONS
 // ============================================================================
@@ -569,6 +690,23 @@ pub async fn fetch_entity_list(entity_type: &str) -> Result<GenericListResponse,
     fetch_json(&url).await
 }
 
+/// One page of records in cursor mode
+#[derive(Debug, Clone, Deserialize)]
+pub struct CursorPage {
+    pub items: Vec<serde_json::Value>,
+    /// Pass as `after` for the next page; None on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Fetch up to `limit` records (newest first), continuing after `after`
+pub async fn fetch_entity_page(entity_type: &str, after: Option<&str>, limit: u32) -> Result<CursorPage, String> {
+    let mut url = format!("{}/records/{}?tenant_id={}&limit={}", API_BASE, entity_type, TENANT_ID, limit);
+    if let Some(cursor) = after {
+        url.push_str(&format!("&after={}", String::from(js_sys::encode_uri_component(cursor))));
+    }
+    fetch_json(&url).await
+}
+
 /// Fetch a single record by ID
 pub async fn fetch_entity(entity_type: &str, id: &str) -> Result<serde_json::Value, String> {
     let url = format!("{}/records/{}/{}?tenant_id={}", API_BASE, entity_type, id, TENANT_ID);
@@ -587,6 +725,61 @@ pub async fn update_entity(entity_type: &str, id: &str, data: serde_json::Value)
     put_json(&url, &data).await
 }
 
+/// Why a record save did not go through
+#[derive(Debug, Clone)]
+pub enum SaveError {
+    /// The record changed on the server since it was read; holds its current state
+    Conflict(serde_json::Value),
+    /// Cancelled through the abort signal, e.g. by a newer save
+    Aborted,
+    Failed(String),
+}
+
+/// Save only the given fields of a record (with `_version`) via PATCH
+///
+/// `signal` lets a newer save abort this one.
+pub async fn patch_entity(
+    entity_type: &str,
+    id: &str,
+    fields: &serde_json::Value,
+    signal: Option<&web_sys::AbortSignal>,
+) -> Result<serde_json::Value, SaveError> {
+    let window = web_sys::window().ok_or_else(|| SaveError::Failed("no window".to_string()))?;
+    let url = format!("{}/records/{}/{}?tenant_id={}", get_api_base(), entity_type, id, TENANT_ID);
+
+    let opts = RequestInit::new();
+    opts.set_method("PATCH");
+    opts.set_mode(RequestMode::Cors);
+    opts.set_body(&JsValue::from_str(&fields.to_string()));
+    opts.set_signal(signal);
+
+    let request = Request::new_with_str_and_init(&url, &opts)
+        .map_err(|e| SaveError::Failed(format!("Request error: {:?}", e)))?;
+    let headers = request.headers();
+    let _ = headers.set("Content-Type", "application/json");
+    let _ = headers.set("X-Tenant-Id", TENANT_ID);
+    let _ = headers.set("X-Tenant-Slug", "demo");
+    let _ = headers.set("X-Request-Id", &Uuid::new_v4().to_string());
+
+    let resp: Response = match JsFuture::from(window.fetch_with_request(&request)).await {
+        Ok(value) => value.dyn_into().map_err(|_| SaveError::Failed("response conversion error".to_string()))?,
+        Err(_) if signal.is_some_and(|s| s.aborted()) => return Err(SaveError::Aborted),
+        Err(e) => return Err(SaveError::Failed(format!("Fetch error: {:?}", e))),
+    };
+
+    let json = match resp.json() {
+        Ok(promise) => JsFuture::from(promise).await.ok().and_then(|v| serde_wasm_bindgen::from_value(v).ok()),
+        Err(_) => None,
+    }
+    .unwrap_or(serde_json::Value::Null);
+
+    match resp.status() {
+        200..=299 => Ok(json),
+        409 => Err(SaveError::Conflict(json.get("current").cloned().unwrap_or_default())),
+        status => Err(SaveError::Failed(format!("HTTP error: {}", status))),
+    }
+}
+
 /// Delete a record (soft delete)
 pub async fn delete_entity(entity_type: &str, id: &str) -> Result<serde_json::Value, String> {
     let url = format!("{}/records/{}/{}?tenant_id={}", API_BASE, entity_type, id, TENANT_ID);
@@ -1190,3 +1383,11 @@ pub async fn fetch_views(entity_name: &str) -> Result<Vec<ViewDef>, String> {
     let resp: ViewListResponse = fetch_json(&url).await?;
     Ok(resp.data)
 }
+
+/// Save a view's column layout (`{"columns": [...]}`), returning the id of the
+/// view written: a personal override when the view is shared or a system view
+pub async fn save_view_columns(view_id: &str, payload: &serde_json::Value) -> Result<String, String> {
+    let url = format!("{}/views/{}/columns?tenant_id={}", API_BASE, view_id, TENANT_ID);
+    let view: serde_json::Value = put_json(&url, payload).await?;
+    view.get("id").and_then(|v| v.as_str()).map(String::from).ok_or_else(|| "Missing view id".to_string())
+}
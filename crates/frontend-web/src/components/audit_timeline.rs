@@ -301,12 +301,26 @@ pub fn AuditTimeline(
     }
 }
 
+/// Audit log row as returned by the audit API
+#[derive(Debug, Deserialize)]
+struct AuditLogRecord {
+    id: Uuid,
+    user_id: Option<String>,
+    action: String,
+    resource_type: String,
+    #[serde(default)]
+    details: JsonValue,
+    #[serde(default)]
+    changes: JsonValue,
+    created_at: DateTime<Utc>,
+}
+
 /// Fetch audit events from API
 async fn fetch_audit_events(entity_id: Uuid, entity_type: &str) -> Result<Vec<AuditEvent>, String> {
     use gloo_net::http::Request;
     use crate::api::get_api_base;
     
-    let url = format!("{}/entities/{}/{}/audit", get_api_base(), entity_type, entity_id);
+    let url = format!("{}/audit/{}/{}", get_api_base(), entity_type, entity_id);
     
     let response = Request::get(&url)
         .header("Accept", "application/json")
@@ -318,8 +332,28 @@ async fn fetch_audit_events(entity_id: Uuid, entity_type: &str) -> Result<Vec<Au
         return Err(format!("HTTP {}", response.status()));
     }
     
-    let events: Vec<AuditEvent> = response.json().await.map_err(|e| e.to_string())?;
-    Ok(events)
+    let records: Vec<AuditLogRecord> = response.json().await.map_err(|e| e.to_string())?;
+    Ok(records.into_iter().map(|r| {
+        let user_id = r.user_id.as_deref().and_then(|u| Uuid::parse_str(u).ok());
+        AuditEvent {
+            id: r.id,
+            entity_id,
+            entity_type: r.resource_type,
+            event_type: match r.action.as_str() {
+                "create" => "Created".to_string(),
+                "update" => "Updated".to_string(),
+                "delete" => "Deleted".to_string(),
+                "association_linked" => "Linked".to_string(),
+                "association_unlinked" => "Unlinked".to_string(),
+                other => other.to_string(),
+            },
+            user_id: user_id.unwrap_or_default(),
+            user_name: if user_id.is_some() { "User" } else { "System" }.to_string(),
+            occurred_at: r.created_at,
+            version: r.details.get("version").and_then(|v| v.as_u64()).unwrap_or_default(),
+            changes: r.changes,
+        }
+    }).collect())
 }
 
 /// Get CSS class for event type
@@ -353,6 +387,8 @@ fn event_action_text(event_type: &str) -> &'static str {
         s if s.contains("StageChanged") => "changed the stage",
         s if s.contains("Assigned") => "assigned",
         s if s.contains("ValueAdded") => "added value",
+        "Linked" => "linked a related record",
+        "Unlinked" => "removed a related record",
         _ => "made changes",
     }
 }
@@ -378,6 +414,43 @@ fn format_relative_time(dt: DateTime<Utc>) -> String {
 /// Render field changes
 fn render_changes(changes: &JsonValue) -> impl IntoView {
     match changes {
+        // Field-level diffs: [{field, label, old, new, redacted}]
+        JsonValue::Array(list) => {
+            let items: Vec<_> = list.iter().map(|change| {
+                let label = change.get("label")
+                    .or_else(|| change.get("field"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let redacted = change.get("redacted").and_then(|v| v.as_bool()).unwrap_or(false);
+                
+                if redacted {
+                    view! {
+                        <div class="change-item redacted">
+                            <span class="field-name">{label}":"</span>
+                            <span class="old-value">"changed (hidden)"</span>
+                        </div>
+                    }
+                } else {
+                    let old_val = change.get("old").map(format_value).unwrap_or_else(|| "—".to_string());
+                    let new_val = change.get("new").map(format_value).unwrap_or_else(|| "—".to_string());
+                    view! {
+                        <div class="change-item">
+                            <span class="field-name">{label}":"</span>
+                            <span class="old-value">{old_val}</span>
+                            <span class="arrow">"→"</span>
+                            <span class="new-value">{new_val}</span>
+                        </div>
+                    }
+                }
+            }).collect();
+            
+            view! {
+                <div class="changes-list">
+                    {items}
+                </div>
+            }.into_view()
+        }
         JsonValue::Object(obj) => {
             let items: Vec<_> = obj.iter().map(|(field, change)| {
                 let old_val = change.get("old").map(|v| format_value(v)).unwrap_or_else(|| "—".to_string());
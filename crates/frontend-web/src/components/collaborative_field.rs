@@ -3,12 +3,17 @@
 //! ## Antigravity Integration
 //! This component uses CrdtText for fields marked with `physics: TextMerge`.
 //! Enables Google Docs-style multi-user editing via the yrs CRDT library.
+//! Other users' cursors are drawn over the text from their awareness
+//! updates; ours are published, throttled, as the selection moves.
 
 use leptos::*;
 use uuid::Uuid;
+use std::rc::Rc;
 use std::sync::Arc;
+use wasm_bindgen::JsCast;
 
-use crate::offline::crdt::CrdtText;
+use crate::offline::crdt::{AwarenessState, CrdtText};
+use crate::offline::presence::{user_color, utf16_to_byte_offset, CursorThrottle, RemoteCursors, CURSOR_STALE_MS};
 use crate::context::websocket::{use_websocket, WsConnectionState};
 
 /// Props for CollaborativeTextField
@@ -55,11 +60,18 @@ pub fn CollaborativeTextField(
     // Local state for reactive UI updates
     let (text_content, set_text_content) = create_signal(initial_value.clone());
     let (is_syncing, set_is_syncing) = create_signal(false);
-    let (collaborators, set_collaborators) = create_signal(Vec::<String>::new());
+    let remote_cursors = create_rw_signal(RemoteCursors::default());
+    let collaborators = Signal::derive(move || {
+        remote_cursors.with(|c| c.states().into_iter().map(|s| s.user_name).collect::<Vec<_>>())
+    });
+    let textarea_ref = create_node_ref::<html::Textarea>();
+    let overlay_ref = create_node_ref::<html::Div>();
     
     // WebSocket for sync
     let ws = use_websocket();
     let ws_state = ws.connection_state;
+    let local_user = ws.user_id;
+    let document_id = format!("{}:{}", entity_id, field_name);
     
     let crdt_for_sync = crdt_text.clone();
     let crdt_for_input = crdt_text.clone();
@@ -81,6 +93,128 @@ pub fn CollaborativeTextField(
         }
     });
     
+    // Publish our cursor, throttled; a held position is flushed when the
+    // interval ends so the last one always goes out
+    let throttle = store_value(CursorThrottle::default());
+    let flush_scheduled = store_value(false);
+    let ws_for_cursor = ws.clone();
+    let doc_for_cursor = document_id.clone();
+    let send_cursor = Rc::new(move |position: u32| {
+        let Some(user_id) = local_user.get_untracked() else { return };
+        let send = {
+            let ws = ws_for_cursor.clone();
+            let doc = doc_for_cursor.clone();
+            move |position: u32| ws.send_awareness(&doc, user_id, &local_user_name(), user_color(user_id), Some(position))
+        };
+        let now = js_sys::Date::now();
+        let mut state = throttle.get_value();
+        let ready = state.offer(position, now);
+        let wait = state.wait_ms(now);
+        throttle.set_value(state);
+
+        if let Some(position) = ready {
+            send(position);
+        } else if let (Some(wait), false) = (wait, flush_scheduled.get_value()) {
+            flush_scheduled.set_value(true);
+            set_timeout(
+                move || {
+                    flush_scheduled.set_value(false);
+                    let mut state = throttle.get_value();
+                    let ready = state.flush(js_sys::Date::now());
+                    throttle.set_value(state);
+                    if let Some(position) = ready {
+                        send(position);
+                    }
+                },
+                std::time::Duration::from_millis(wait.ceil() as u64),
+            );
+        }
+    });
+    let on_selection = {
+        let send_cursor = send_cursor.clone();
+        move || {
+            if let Some(position) = textarea_ref.get_untracked().and_then(|t| t.selection_start().ok().flatten()) {
+                send_cursor(position);
+            }
+        }
+    };
+    let on_select = {
+        let on_selection = on_selection.clone();
+        move |_: leptos::ev::Event| on_selection()
+    };
+    let on_keyup = {
+        let on_selection = on_selection.clone();
+        move |_: leptos::ev::KeyboardEvent| on_selection()
+    };
+    let on_click = {
+        let on_selection = on_selection.clone();
+        move |_: leptos::ev::MouseEvent| on_selection()
+    };
+    let on_scroll = move |_: leptos::ev::Event| {
+        if let (Some(textarea), Some(overlay)) = (textarea_ref.get_untracked(), overlay_ref.get_untracked()) {
+            overlay.set_scroll_top(textarea.scroll_top());
+        }
+    };
+
+    // Track remote cursors for this document
+    let doc_for_update = document_id.clone();
+    let update_listener = window_event_listener_untyped("awareness-update", move |ev| {
+        let Some(detail) = awareness_detail(&ev, &doc_for_update) else { return };
+        let Some(user_id) = detail_str(&detail, "userId").and_then(|id| Uuid::parse_str(&id).ok()) else { return };
+        if Some(user_id) == local_user.get_untracked() {
+            return;
+        }
+        let name = detail_str(&detail, "userName").unwrap_or_else(|| "Guest".to_string());
+        let color = detail_str(&detail, "userColor").unwrap_or_else(|| user_color(user_id).to_string());
+        let mut state = AwarenessState::new(user_id, &name, &color);
+        state.cursor_position = js_sys::Reflect::get(&detail, &"cursorPosition".into())
+            .ok()
+            .and_then(|v| v.as_f64())
+            .map(|p| p as u32);
+        remote_cursors.update(|c| c.upsert(state, js_sys::Date::now()));
+    });
+    let doc_for_remove = document_id.clone();
+    let remove_listener = window_event_listener_untyped("awareness-remove", move |ev| {
+        let Some(detail) = awareness_detail(&ev, &doc_for_remove) else { return };
+        if let Some(user_id) = detail_str(&detail, "userId").and_then(|id| Uuid::parse_str(&id).ok()) {
+            remote_cursors.update(|c| {
+                c.remove(user_id);
+            });
+        }
+    });
+    let evict_interval = set_interval_with_handle(
+        move || {
+            let now = js_sys::Date::now();
+            if remote_cursors.with_untracked(|c| !c.states().is_empty()) {
+                remote_cursors.update(|c| {
+                    c.evict_stale(now);
+                });
+            }
+        },
+        std::time::Duration::from_millis((CURSOR_STALE_MS / 6.0) as u64),
+    )
+    .ok();
+
+    // Cursors of a lost connection cannot be trusted
+    create_effect(move |_| {
+        if !matches!(ws_state.get(), WsConnectionState::Connected) {
+            remote_cursors.update(|c| c.clear());
+        }
+    });
+
+    let ws_for_cleanup = ws.clone();
+    let doc_for_cleanup = document_id.clone();
+    on_cleanup(move || {
+        update_listener.remove();
+        remove_listener.remove();
+        if let Some(interval) = evict_interval {
+            interval.clear();
+        }
+        if let Some(user_id) = local_user.get_untracked() {
+            ws_for_cleanup.send_awareness_remove(&doc_for_cleanup, user_id);
+        }
+    });
+
     // Handle text input
     let on_input = move |ev: leptos::ev::Event| {
         if readonly {
@@ -105,6 +239,7 @@ pub fn CollaborativeTextField(
         if let Some(callback) = on_change.as_ref() {
             callback.call(new_text);
         }
+        on_selection();
     };
     
     // Apply remote updates (would be called from WebSocket message handler)
@@ -139,14 +274,24 @@ pub fn CollaborativeTextField(
                 </Show>
             </div>
             
-            // Text area with CRDT backing
-            <textarea
-                class="collaborative-field__input"
-                placeholder=placeholder.unwrap_or_default()
-                prop:value=text_content
-                on:input=on_input
-                readonly=readonly
-            ></textarea>
+            // Text area with CRDT backing, remote cursors drawn over it
+            <div class="collaborative-field__editor">
+                <textarea
+                    class="collaborative-field__input"
+                    placeholder=placeholder.unwrap_or_default()
+                    prop:value=text_content
+                    on:input=on_input
+                    on:select=on_select
+                    on:keyup=on_keyup
+                    on:click=on_click
+                    on:scroll=on_scroll
+                    readonly=readonly
+                    node_ref=textarea_ref
+                ></textarea>
+                <div class="collaborative-field__cursors" aria-hidden="true" node_ref=overlay_ref>
+                    {move || cursor_overlay(&text_content.get(), remote_cursors.with(|c| c.states()))}
+                </div>
+            </div>
             
             // Footer with collaboration info
             <div class="collaborative-field__footer">
@@ -190,6 +335,44 @@ pub fn CollaborativeTextField(
     border-radius: 1rem;
 }
 
+.collaborative-field__editor {
+    position: relative;
+}
+
+.collaborative-field__cursors {
+    position: absolute;
+    inset: 0;
+    padding: 0.75rem;
+    border: 1px solid transparent;
+    font-size: 0.875rem;
+    line-height: 1.5;
+    font-family: inherit;
+    white-space: pre-wrap;
+    overflow-wrap: break-word;
+    overflow: hidden;
+    color: transparent;
+    pointer-events: none;
+}
+
+.remote-cursor {
+    position: relative;
+    border-left: 2px solid var(--cursor-color);
+    margin-left: -1px;
+}
+
+.remote-cursor__label {
+    position: absolute;
+    bottom: 100%;
+    left: -2px;
+    padding: 0 0.25rem;
+    border-radius: 0.25rem 0.25rem 0.25rem 0;
+    background: var(--cursor-color);
+    color: #fff;
+    font-size: 0.625rem;
+    line-height: 1.4;
+    white-space: nowrap;
+}
+
 .collaborative-field__input {
     width: 100%;
     min-height: 120px;
@@ -228,6 +411,48 @@ pub fn CollaborativeTextField(
     }
 }
 
+/// Text with a marker at each remote cursor, laid over the textarea
+fn cursor_overlay(text: &str, cursors: Vec<AwarenessState>) -> impl IntoView {
+    let mut cursors: Vec<(usize, AwarenessState)> = cursors
+        .into_iter()
+        .filter_map(|c| Some((utf16_to_byte_offset(text, c.cursor_position?), c)))
+        .collect();
+    cursors.sort_by_key(|(offset, _)| *offset);
+
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (offset, cursor) in cursors {
+        parts.push(text[start..offset].to_string().into_view());
+        parts.push(view! {
+            <span class="remote-cursor" style=format!("--cursor-color: {}", cursor.user_color)>
+                <span class="remote-cursor__label">{cursor.user_name}</span>
+            </span>
+        }.into_view());
+        start = offset;
+    }
+    parts.push(text[start..].to_string().into_view());
+    parts
+}
+
+/// Detail of an awareness event for `document_id`
+fn awareness_detail(event: &web_sys::Event, document_id: &str) -> Option<js_sys::Object> {
+    let detail = event.dyn_ref::<web_sys::CustomEvent>()?.detail().dyn_into::<js_sys::Object>().ok()?;
+    (detail_str(&detail, "documentId").as_deref() == Some(document_id)).then_some(detail)
+}
+
+fn detail_str(detail: &js_sys::Object, key: &str) -> Option<String> {
+    js_sys::Reflect::get(detail, &key.into()).ok()?.as_string()
+}
+
+/// Name shown on our cursor for other users
+fn local_user_name() -> String {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|s| s.get_item("user_email").ok().flatten())
+        .and_then(|email| email.split('@').next().map(str::to_string))
+        .unwrap_or_else(|| "Guest".to_string())
+}
+
 /// Helper to check if a field should use collaborative editing
 pub fn should_use_crdt(physics: &str) -> bool {
     physics == "textMerge" || physics == "\"textMerge\""
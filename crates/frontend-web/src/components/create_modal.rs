@@ -1,9 +1,13 @@
 //! Generic Create Modal - Metadata-driven form in a modal for creating new records
+//! Validates against the fields' rules on submit and shows errors inline
 
 use leptos::*;
-use crate::api::{fetch_field_defs, post_json, add_field_option, delete_field_option, FieldDef, API_BASE, TENANT_ID};
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use crate::api::{fetch_form_fields, post_json, add_field_option, delete_field_option, FieldDef, API_BASE, TENANT_ID};
 use crate::components::field_renderer::LinkInput;
 use crate::components::smart_select::{SmartSelect, MultiSelect, SelectOption};
+use crate::components::smart_field::{validate_submission, ValidationMessage, ValidationResult};
 
 /// Record info returned from CreateModal when a new record is created
 #[derive(Clone, Debug)]
@@ -32,6 +36,9 @@ pub fn CreateModal(
     
     // State
     let (fields, set_fields) = create_signal::<Vec<FieldDef>>(Vec::new());
+    // Full field models, for their validation rules
+    let field_models = store_value(Vec::<core_models::field::FieldDef>::new());
+    let field_errors = create_rw_signal(HashMap::<String, ValidationResult>::new());
     let (form_data, set_form_data) = create_signal::<std::collections::HashMap<String, String>>(std::collections::HashMap::new());
     let (loading, set_loading) = create_signal(true);
     let (saving, set_saving) = create_signal(false);
@@ -45,8 +52,9 @@ pub fn CreateModal(
         spawn_local(async move {
             set_loading.set(true);
             
-            match fetch_field_defs(&et).await {
-                Ok(field_defs) => {
+            match fetch_form_fields(&et).await {
+                Ok((field_defs, models)) => {
+                    field_models.set_value(models);
                     set_fields.set(field_defs);
                     set_loading.set(false);
                 }
@@ -65,6 +73,34 @@ pub fn CreateModal(
         let et = entity_type_stored.get_value();
         let data = form_data.get();
         
+        // Check the fields in form order; block and show what's wrong
+        let values: HashMap<String, serde_json::Value> = data.iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(k, v)| (k.clone(), serde_json::json!(v)))
+            .collect();
+        let form_fields: Vec<_> = fields.with_untracked(|defs| {
+            field_models.with_value(|models| {
+                defs.iter()
+                    .filter(|f| !f.is_readonly)
+                    .filter_map(|f| models.iter().find(|m| m.name == f.name).cloned())
+                    .collect()
+            })
+        });
+        let validation = validate_submission(&form_fields, &values);
+        if !validation.is_valid() {
+            let shown = fields.with_untracked(|defs| {
+                validation.errors.keys().filter(|name| defs.iter().any(|f| &f.name == *name && !f.is_readonly)).count()
+            });
+            // Rules can flag fields this form doesn't show
+            set_error.set((shown < validation.errors.len()).then(|| "Some fields need attention".to_string()));
+            field_errors.set(validation.errors);
+            if let Some(first) = validation.first_error {
+                focus_field(&first);
+            }
+            return;
+        }
+        field_errors.set(HashMap::new());
+        
         set_saving.set(true);
         set_error.set(None);
         
@@ -125,6 +161,9 @@ pub fn CreateModal(
     
     // Handle field change
     let update_field = move |name: String, value: String| {
+        if field_errors.with_untracked(|e| e.contains_key(&name)) {
+            field_errors.update(|e| { e.remove(&name); });
+        }
         set_form_data.update(|d| {
             d.insert(name, value);
         });
@@ -147,7 +186,7 @@ pub fn CreateModal(
                         view! { <div class="modal-loading">"Loading form..."</div> }.into_view()
                     } else {
                         view! {
-                            <form class="modal-form" on:submit=handle_submit>
+                            <form class="modal-form" novalidate=true on:submit=handle_submit>
                                 {move || error.get().map(|e| view! { <div class="form-error">{e}</div> })}
                                 
                                 <div class="ui-modal-body">
@@ -162,9 +201,13 @@ pub fn CreateModal(
                                             let field_type = field.get_field_type();
                                             let is_required = field.is_required;
                                             let placeholder = field.placeholder.clone().unwrap_or_default();
+                                            let field_name_error = field.name.clone();
+                                            let field_error = Signal::derive(move || {
+                                                field_errors.with(|e| e.get(&field_name_error).cloned().unwrap_or_else(ValidationResult::valid))
+                                            });
                                             
                                             view! {
-                                                <div class="form-field">
+                                                <div class="form-field" data-field=field.name.clone() class:has-error=move || !field_error.get().is_valid>
                                                     <label class="field-label">
                                                         {field_label}
                                                         {if is_required { " *" } else { "" }}
@@ -379,6 +422,7 @@ pub fn CreateModal(
                                                             />
                                                         }.into_view(),
                                                     }}
+                                                    <ValidationMessage result=field_error />
                                                 </div>
                                             }
                                         }
@@ -410,3 +454,18 @@ pub fn CreateModal(
         </div>
     }
 }
+
+/// Focus the input of a form field, e.g. the first one with an error
+fn focus_field(name: &str) {
+    let selector = format!(
+        "[data-field=\"{0}\"] input, [data-field=\"{0}\"] textarea, [data-field=\"{0}\"] select, [data-field=\"{0}\"] button",
+        name
+    );
+    let input = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.query_selector(&selector).ok().flatten())
+        .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok());
+    if let Some(input) = input {
+        let _ = input.focus();
+    }
+}
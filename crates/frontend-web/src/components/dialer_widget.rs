@@ -8,6 +8,9 @@
 
 use leptos::*;
 use uuid::Uuid;
+use core_models::normalize_phone;
+
+use crate::utils::tenant_phone_region;
 
 /// Dialer state
 #[derive(Clone, Copy, PartialEq)]
@@ -109,10 +112,14 @@ pub fn DialerWidget() -> impl IntoView {
     };
     
     let initiate_call = move |phone: String| {
-        if phone.is_empty() {
-            set_error.set(Some("Please enter a phone number".to_string()));
-            return;
-        }
+        // Dial the canonical number, whatever format it was typed or stored in
+        let phone = match normalize_phone(&phone, &tenant_phone_region()) {
+            Ok(phone) => phone,
+            Err(e) => {
+                set_error.set(Some(e));
+                return;
+            }
+        };
         
         set_state.set(DialerState::Dialing);
         set_error.set(None);
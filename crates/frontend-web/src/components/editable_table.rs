@@ -1,11 +1,88 @@
 //! Editable Table Component - Inline editing with auto-save
 //!
-//! Click any cell to edit, changes save on blur via PATCH API
+//! Click any cell to edit, changes save on blur via PATCH API.
+//! Only the rows in view (plus a buffer) are mounted, so large lists scroll
+//! smoothly; `on_end_reached` asks for the next page as the end comes near.
+//! Arrow keys, Page Up/Down, Home and End move the active row, scrolling it
+//! into view; Enter opens it. Dragging a header's right edge resizes the
+//! column and reports the final width through `on_column_resize`.
 
 
 use leptos::*;
+use wasm_bindgen::JsCast;
 use crate::api::{FieldDef, patch_json, API_BASE, TENANT_ID};
 use crate::components::field_renderer::EditableFieldValue;
+use crate::core::view_layout::MIN_COLUMN_WIDTH;
+use crate::utils::{format_money, tenant_phone_region};
+use core_models::format_phone;
+use std::collections::HashMap;
+
+/// Rows mounted beyond each edge of the viewport
+pub const OVERSCAN_ROWS: usize = 8;
+
+/// Ask for more rows once the mounted rows come this close to the end
+pub const LOAD_MORE_THRESHOLD: usize = 20;
+
+/// Rows to mount for a scroll position, and the spacer heights around them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisibleRange {
+    pub start: usize,
+    /// Exclusive
+    pub end: usize,
+    pub offset_top: f64,
+    pub offset_bottom: f64,
+}
+
+/// Rows visible at `scroll_top` in a viewport of `viewport_height`, widened by `overscan` rows each side
+pub fn visible_range(scroll_top: f64, viewport_height: f64, row_height: f64, total: usize, overscan: usize) -> VisibleRange {
+    let row_height = row_height.max(1.0);
+    let visible = (viewport_height.max(0.0) / row_height).ceil() as usize;
+    // After the data shrinks the scroll offset can point past the end
+    let first = ((scroll_top.max(0.0) / row_height).floor() as usize).min(total.saturating_sub(visible));
+
+    let start = first.saturating_sub(overscan);
+    let end = (first + visible + overscan).min(total);
+    VisibleRange {
+        start,
+        end,
+        offset_top: start as f64 * row_height,
+        offset_bottom: (total - end) as f64 * row_height,
+    }
+}
+
+/// Whether the mounted rows are close enough to the end to fetch the next page
+pub fn near_end(range: &VisibleRange, total: usize) -> bool {
+    total - range.end <= LOAD_MORE_THRESHOLD
+}
+
+/// Active row after a navigation key, or None if the key doesn't navigate
+pub fn next_row(current: Option<usize>, key: &str, page: usize, total: usize) -> Option<usize> {
+    let last = total.checked_sub(1)?;
+    let next = match (key, current) {
+        ("ArrowDown" | "ArrowUp" | "PageDown" | "PageUp", None) => 0,
+        ("ArrowDown", Some(i)) => i + 1,
+        ("ArrowUp", Some(i)) => i.saturating_sub(1),
+        ("PageDown", Some(i)) => i + page,
+        ("PageUp", Some(i)) => i.saturating_sub(page),
+        ("Home", _) => 0,
+        ("End", _) => last,
+        _ => return None,
+    };
+    Some(next.min(last))
+}
+
+/// Scroll offset that brings row `index` into view, if it is not already
+pub fn scroll_to_reveal(index: usize, row_height: f64, scroll_top: f64, viewport_height: f64) -> Option<f64> {
+    let top = index as f64 * row_height;
+    let bottom = top + row_height;
+    if top < scroll_top {
+        Some(top)
+    } else if bottom > scroll_top + viewport_height {
+        Some(bottom - viewport_height)
+    } else {
+        None
+    }
+}
 
 /// Editable data table with inline editing
 #[component]
@@ -22,6 +99,15 @@ pub fn EditableTable(
     /// Callback when row is clicked (for navigation)
     #[prop(optional, into)]
     on_row_click: Option<Callback<String>>,
+    /// Callback when scrolling nears the last loaded row (for fetching the next page)
+    #[prop(optional, into)]
+    on_end_reached: Option<Callback<()>>,
+    /// Saved column widths (px) by field name
+    #[prop(optional, into)]
+    column_widths: MaybeSignal<HashMap<String, u32>>,
+    /// Callback with (field, width) when a column resize ends
+    #[prop(optional, into)]
+    on_column_resize: Option<Callback<(String, u32)>>,
 ) -> impl IntoView {
     let entity_type_stored = store_value(entity_type);
     
@@ -45,21 +131,164 @@ pub fn EditableTable(
         });
     };
     
-    let padding_y = match density.as_str() {
-        "compact" => "py-1",
-        "spacious" => "py-5",
-        _ => "py-3",
+    // Padding and the row height it gives, until a row is measured
+    let (padding_y, estimated_row_height) = match density.as_str() {
+        "compact" => ("py-1", 33.0),
+        "spacious" => ("py-5", 73.0),
+        _ => ("py-3", 49.0),
+    };
+    
+    // Virtualization state
+    let scroll_ref = create_node_ref::<html::Div>();
+    let head_ref = create_node_ref::<html::Thead>();
+    let scroll_top = create_rw_signal(0.0);
+    let viewport_height = create_rw_signal(600.0);
+    let row_height = create_rw_signal(estimated_row_height);
+    let active_row = create_rw_signal(None::<usize>);
+    
+    // Viewport is the scroll area below the sticky header
+    let measure_viewport = move || {
+        if let Some(el) = scroll_ref.get_untracked() {
+            let header = head_ref.get_untracked().map_or(0.0, |h| h.offset_height() as f64);
+            viewport_height.set((el.client_height() as f64 - header).max(0.0));
+        }
     };
+    scroll_ref.on_load(move |_| measure_viewport());
+    let resize = window_event_listener(ev::resize, move |_| measure_viewport());
+    on_cleanup(move || resize.remove());
+    
+    let range = create_memo(move |_| {
+        visible_range(scroll_top.get(), viewport_height.get(), row_height.get(), data.with(Vec::len), OVERSCAN_ROWS)
+    });
+    
+    // Measure a mounted row once the range renders
+    create_effect(move |_| {
+        range.track();
+        request_animation_frame(move || {
+            let row = scroll_ref.get_untracked()
+                .and_then(|el| el.query_selector("tr[data-row]").ok().flatten())
+                .and_then(|row| row.dyn_into::<web_sys::HtmlElement>().ok());
+            if let Some(row) = row {
+                let height = row.offset_height() as f64;
+                if height > 0.0 && (height - row_height.get_untracked()).abs() >= 1.0 {
+                    row_height.set(height);
+                }
+            }
+        });
+    });
+    
+    // Fetch more as the end comes into range
+    create_effect(move |_| {
+        let total = data.with(Vec::len);
+        if total > 0 && near_end(&range.get(), total) {
+            if let Some(cb) = on_end_reached {
+                cb.call(());
+            }
+        }
+    });
+    
+    // Keyboard navigation works on row indices, so it reaches rows that aren't mounted
+    let on_keydown = move |ev: web_sys::KeyboardEvent| {
+        // Leave keys to a cell editor that has focus
+        let tag = event_target::<web_sys::Element>(&ev).tag_name();
+        if matches!(tag.as_str(), "INPUT" | "SELECT" | "TEXTAREA") {
+            return;
+        }
+        
+        let height = row_height.get_untracked();
+        let viewport = viewport_height.get_untracked();
+        if ev.key() == "Enter" {
+            let id = active_row.get_untracked().and_then(|i| data.with_untracked(|rows| {
+                rows.get(i).and_then(|r| r.get("id")).and_then(|v| v.as_str()).map(|s| s.to_string())
+            }));
+            if let (Some(id), Some(cb)) = (id, on_row_click) {
+                cb.call(id);
+            }
+            return;
+        }
+        
+        let page = ((viewport / height).floor() as usize).max(1);
+        let Some(next) = next_row(active_row.get_untracked(), &ev.key(), page, data.with_untracked(Vec::len)) else {
+            return;
+        };
+        ev.prevent_default();
+        active_row.set(Some(next));
+        if let Some(top) = scroll_to_reveal(next, height, scroll_top.get_untracked(), viewport) {
+            if let Some(el) = scroll_ref.get_untracked() {
+                el.set_scroll_top(top as i32);
+            }
+            scroll_top.set(top);
+        }
+    };
+    
+    // Column resizing: live widths while dragging, seeded from the saved ones
+    let widths = create_rw_signal(HashMap::<String, u32>::new());
+    create_effect(move |_| widths.set(column_widths.get()));
+    // (field, pointer x at start, width at start)
+    let dragging = store_value(None::<(String, i32, u32)>);
+    let drag_move = window_event_listener(ev::mousemove, move |ev| {
+        if let Some((field, start_x, start_width)) = dragging.get_value() {
+            let width = (start_width as i32 + ev.client_x() - start_x).max(MIN_COLUMN_WIDTH as i32) as u32;
+            widths.update(|w| { w.insert(field, width); });
+        }
+    });
+    let drag_end = window_event_listener(ev::mouseup, move |_| {
+        if let Some((field, _, _)) = dragging.get_value() {
+            dragging.set_value(None);
+            let width = widths.with_untracked(|w| w.get(&field).copied());
+            if let (Some(width), Some(cb)) = (width, on_column_resize) {
+                cb.call((field, width));
+            }
+        }
+    });
+    on_cleanup(move || {
+        drag_move.remove();
+        drag_end.remove();
+    });
+    
+    let columns_stored = store_value(columns.clone());
     
     view! {
-        <div class="w-full overflow-x-auto rounded-xl border border-white/10 bg-surface/30 backdrop-blur-md">
+        <div
+            node_ref=scroll_ref
+            class="w-full h-full overflow-auto rounded-xl border border-white/10 bg-surface/30 backdrop-blur-md focus:outline-none"
+            tabindex="0"
+            on:scroll=move |_| {
+                if let Some(el) = scroll_ref.get_untracked() {
+                    scroll_top.set(el.scroll_top() as f64);
+                }
+            }
+            on:keydown=on_keydown
+        >
             <table class="w-full border-collapse text-left">
-                <thead>
+                <thead node_ref=head_ref class="sticky top-0 z-10 bg-surface/90 backdrop-blur-md">
                     <tr>
                         {columns.iter().map(|col| {
+                            let field = col.name.clone();
+                            let width = {
+                                let field = field.clone();
+                                move || widths.with(|w| w.get(&field).map(|px| format!("{}px", px)))
+                            };
                             view! {
-                                <th class="px-6 py-4 text-xs font-bold uppercase tracking-wider text-slate-500 border-b border-white/10 whitespace-nowrap" title=col.name.clone()>
+                                <th
+                                    class="relative px-6 py-4 text-xs font-bold uppercase tracking-wider text-slate-500 border-b border-white/10 whitespace-nowrap"
+                                    title=col.name.clone()
+                                    style:width=width.clone()
+                                    style:min-width=width
+                                >
                                     <span>{col.label.clone()}</span>
+                                    <span
+                                        class="absolute right-0 top-0 h-full w-1.5 cursor-col-resize hover:bg-indigo-500/50"
+                                        on:mousedown=move |ev: web_sys::MouseEvent| {
+                                            ev.prevent_default();
+                                            ev.stop_propagation();
+                                            let start_width = event_target::<web_sys::HtmlElement>(&ev)
+                                                .parent_element()
+                                                .and_then(|th| th.dyn_into::<web_sys::HtmlElement>().ok())
+                                                .map_or(MIN_COLUMN_WIDTH, |th| th.offset_width() as u32);
+                                            dragging.set_value(Some((field.clone(), ev.client_x(), start_width)));
+                                        }
+                                    ></span>
                                 </th>
                             }
                         }).collect_view()}
@@ -67,12 +296,26 @@ pub fn EditableTable(
                     </tr>
                 </thead>
                 <tbody class="divide-y divide-white/5">
-                    {move || {
-                        let cols = columns.clone();
-                        let on_click = on_row_click.clone();
-                        let py_class = padding_y.clone();
-                        
-                        data.get().into_iter().map(|row| {
+                    // Stands in for the rows scrolled past
+                    <tr aria-hidden="true" style=move || format!("height: {}px", range.get().offset_top)></tr>
+                    <For
+                        each=move || {
+                            let r = range.get();
+                            data.with(|rows| {
+                                rows.get(r.start..r.end)
+                                    .unwrap_or_default()
+                                    .iter()
+                                    .cloned()
+                                    .enumerate()
+                                    .map(|(i, row)| (r.start + i, row))
+                                    .collect::<Vec<_>>()
+                            })
+                        }
+                        key=|(index, row)| (*index, row.to_string())
+                        children=move |(index, row)| {
+                            let cols = columns_stored.get_value();
+                            let on_click = on_row_click;
+                            
                             let record_id = row.get("id")
                                 .and_then(|v| v.as_str())
                                 .unwrap_or_default()
@@ -80,10 +323,14 @@ pub fn EditableTable(
                             
                             let row_id = record_id.clone();
                             let nav_id = record_id.clone();
-                            let py = py_class.clone();
                             
                             view! {
-                                <tr class="group transition-colors hover:bg-white/5">
+                                <tr
+                                    data-row=index
+                                    class="group transition-colors hover:bg-white/5"
+                                    class:bg-white_10=move || active_row.get() == Some(index)
+                                    on:click=move |_| active_row.set(Some(index))
+                                >
                                     {cols.iter().map(|col| {
                                         let field = col.clone();
                                         let value = row.get(&col.name)
@@ -91,13 +338,12 @@ pub fn EditableTable(
                                             .unwrap_or(serde_json::Value::Null);
                                         let rid = row_id.clone();
                                         let fname = col.name.clone();
-                                        let py_cell = py.clone();
                                         
                                         // Determine if field is editable (not readonly, not id)
                                         let is_editable = !col.is_readonly && col.name != "id" && col.name != "created_at" && col.name != "updated_at";
                                         
                                         view! {
-                                            <td class=format!("px-6 {} text-sm text-slate-300 whitespace-nowrap transition-colors", py_cell) class:bg-white_5=is_editable class:hover:bg-white_10=is_editable>
+                                            <td class=format!("px-6 {} text-sm text-slate-300 whitespace-nowrap transition-colors", padding_y) class:bg-white_5=is_editable class:hover:bg-white_10=is_editable>
                                                 {if is_editable {
                                                     view! {
                                                         <EditableFieldValue 
@@ -134,8 +380,9 @@ pub fn EditableTable(
                                     </td>
                                 </tr>
                             }
-                        }).collect_view()
-                    }}
+                        }
+                    />
+                    <tr aria-hidden="true" style=move || format!("height: {}px", range.get().offset_bottom)></tr>
                 </tbody>
             </table>
             
@@ -151,24 +398,87 @@ pub fn EditableTable(
 }
 
 fn format_display_value(value: &serde_json::Value, field_type: &str) -> String {
+    if matches!(field_type, "money" | "currency") {
+        if let Some(formatted) = format_money(value, None) {
+            return formatted;
+        }
+    }
     match value {
         serde_json::Value::Null => "—".to_string(),
         serde_json::Value::String(s) => {
             if field_type == "date" && s.len() >= 10 {
                 s[..10].to_string()
+            } else if field_type == "phone" {
+                format_phone(s, &tenant_phone_region())
             } else {
                 s.clone()
             }
         }
-        serde_json::Value::Number(n) => {
-            if field_type == "currency" {
-                format!("${:.2}", n.as_f64().unwrap_or(0.0))
-            } else {
-                n.to_string()
-            }
-        }
+        serde_json::Value::Number(n) => n.to_string(),
         serde_json::Value::Bool(b) => if *b { "Yes" } else { "No" }.to_string(),
         serde_json::Value::Array(arr) => format!("{} items", arr.len()),
         serde_json::Value::Object(_) => "[object]".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_range_follows_scroll() {
+        // 10_000 rows of 40px in a 400px viewport
+        let top = visible_range(0.0, 400.0, 40.0, 10_000, 5);
+        assert_eq!((top.start, top.end), (0, 15));
+        assert_eq!(top.offset_top, 0.0);
+        assert_eq!(top.offset_bottom, (10_000 - 15) as f64 * 40.0);
+
+        // Scrolled to row 500, partway into it
+        let middle = visible_range(20_010.0, 400.0, 40.0, 10_000, 5);
+        assert_eq!((middle.start, middle.end), (495, 515));
+        assert_eq!(middle.offset_top, 495.0 * 40.0);
+        assert_eq!(middle.offset_top + (middle.end - middle.start) as f64 * 40.0 + middle.offset_bottom, 400_000.0);
+
+        let bottom = visible_range(399_600.0, 400.0, 40.0, 10_000, 5);
+        assert_eq!((bottom.start, bottom.end), (9_985, 10_000));
+        assert_eq!(bottom.offset_bottom, 0.0);
+    }
+
+    #[test]
+    fn test_visible_range_edge_cases() {
+        assert_eq!(visible_range(0.0, 400.0, 40.0, 0, 5), VisibleRange { start: 0, end: 0, offset_top: 0.0, offset_bottom: 0.0 });
+        // Fewer rows than fit
+        let short = visible_range(0.0, 400.0, 40.0, 3, 5);
+        assert_eq!((short.start, short.end), (0, 3));
+        // Scroll offset left over after the list shrank
+        let shrunk = visible_range(40_000.0, 400.0, 40.0, 50, 5);
+        assert_eq!((shrunk.start, shrunk.end), (35, 50));
+        // Unmeasured row height doesn't divide by zero
+        assert_eq!(visible_range(0.0, 400.0, 0.0, 1_000, 0).end, 400);
+    }
+
+    #[test]
+    fn test_near_end_requests_next_page() {
+        let range = visible_range(0.0, 400.0, 40.0, 100, 5);
+        assert!(!near_end(&range, 100));
+        let range = visible_range(2_800.0, 400.0, 40.0, 100, 5);
+        assert!(near_end(&range, 100));
+    }
+
+    #[test]
+    fn test_keyboard_navigation_crosses_virtual_boundary() {
+        assert_eq!(next_row(None, "ArrowDown", 10, 1_000), Some(0));
+        assert_eq!(next_row(Some(0), "ArrowUp", 10, 1_000), Some(0));
+        assert_eq!(next_row(Some(5), "PageDown", 10, 1_000), Some(15));
+        assert_eq!(next_row(Some(995), "PageDown", 10, 1_000), Some(999));
+        assert_eq!(next_row(Some(3), "End", 10, 1_000), Some(999));
+        assert_eq!(next_row(Some(3), "Tab", 10, 1_000), None);
+        assert_eq!(next_row(None, "ArrowDown", 10, 0), None);
+
+        // Moving past the viewport scrolls just enough to show the row
+        assert_eq!(scroll_to_reveal(10, 40.0, 0.0, 400.0), Some(40.0));
+        assert_eq!(scroll_to_reveal(999, 40.0, 0.0, 400.0), Some(39_600.0));
+        assert_eq!(scroll_to_reveal(2, 40.0, 200.0, 400.0), Some(80.0));
+        assert_eq!(scroll_to_reveal(5, 40.0, 0.0, 400.0), None);
+    }
+}
@@ -7,6 +7,8 @@ use leptos::*;
 use crate::api::FieldDef;
 use crate::components::smart_select::{SmartSelect, SelectOption};
 use crate::components::async_entity_select::{AsyncEntitySelect, AsyncEntityLabel};
+use crate::utils::{format_money, tenant_money_settings, tenant_phone_region};
+use core_models::{format_phone, normalize_phone, Money};
 
 // ============================================================================
 // FIELD MODE - Defines rendering modes for fields
@@ -53,7 +55,7 @@ fn render_field_value(field: &FieldDef, value: &serde_json::Value, _editable: bo
     match field_type.as_str() {
         // Image - render as img tag
         "image" => {
-            let url = value.as_str().unwrap_or("").to_string();
+            let url = crate::api::upload_src(value.as_str().unwrap_or(""));
             view! {
                 <img 
                     src=url.clone() 
@@ -66,8 +68,7 @@ fn render_field_value(field: &FieldDef, value: &serde_json::Value, _editable: bo
         
         // Money - format with currency
         "money" => {
-            let amount = value.as_f64().unwrap_or(0.0);
-            let formatted = format!("${:.2}", amount);
+            let formatted = format_money(value, None).unwrap_or_default();
             view! {
                 <span class="field-money">{formatted}</span>
             }.into_view()
@@ -187,8 +188,9 @@ fn render_field_value(field: &FieldDef, value: &serde_json::Value, _editable: bo
         // Phone - render as tel link
         "phone" => {
             let phone = value.as_str().unwrap_or("").to_string();
+            let display = format_phone(&phone, &tenant_phone_region());
             view! {
-                <a href=format!("tel:{}", phone) class="field-phone">{phone.clone()}</a>
+                <a href=format!("tel:{}", phone) class="field-phone">{display}</a>
             }.into_view()
         }
         
@@ -349,8 +351,37 @@ fn render_edit_input(
             }.into_view()
         }
         
-        // Number/Money - render as number input
-        "number" | "money" | "integer" | "decimal" | "score" => {
+        // Money - text input in the tenant locale, stored as minor units
+        "money" => {
+            let (locale, tenant_currency) = tenant_money_settings();
+            let money = Money::from_value(&current_value, &tenant_currency);
+            let currency = money.as_ref().map(|m| m.currency.clone()).unwrap_or(tenant_currency);
+            let text = money.map(|m| m.format_number(&locale)).unwrap_or_default();
+            view! {
+                <input
+                    type="text"
+                    inputmode="decimal"
+                    value=text
+                    class="field-input-number"
+                    on:blur=move |ev| {
+                        set_editing.set(false);
+                        if let Ok(money) = Money::parse(&event_target_value(&ev), &currency, &locale) {
+                            let json_val = money.to_value();
+                            set_current_value.set(json_val.clone());
+                            on_change.call(json_val);
+                        }
+                    }
+                    on:keydown=move |ev| {
+                        if ev.key() == "Enter" || ev.key() == "Escape" {
+                            set_editing.set(false);
+                        }
+                    }
+                />
+            }.into_view()
+        }
+
+        // Number - render as number input
+        "number" | "integer" | "decimal" | "score" => {
             let num = current_value.as_f64().unwrap_or(0.0);
             view! {
                 <input 
@@ -471,7 +502,8 @@ fn render_edit_input(
         }
         
         "phone" => {
-            let val = current_value.as_str().unwrap_or("").to_string();
+            let region = tenant_phone_region();
+            let val = format_phone(current_value.as_str().unwrap_or(""), &region);
             view! {
                 <input 
                     type="tel"
@@ -481,6 +513,8 @@ fn render_edit_input(
                     on:blur=move |ev| {
                         set_editing.set(false);
                         let new_val = event_target_value(&ev);
+                        // Unparseable input is sent as typed for the server to reject
+                        let new_val = normalize_phone(&new_val, &region).unwrap_or(new_val);
                         let json_val = serde_json::Value::String(new_val);
                         set_current_value.set(json_val.clone());
                         on_change.call(json_val);
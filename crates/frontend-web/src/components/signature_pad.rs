@@ -6,6 +6,7 @@
 //! - Export to base64 PNG
 //! - Customizable pen color and width
 
+use base64::{engine::general_purpose::STANDARD as Base64, Engine};
 use leptos::*;
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
@@ -28,6 +29,8 @@ pub fn SignaturePad(
     let (is_drawing, set_is_drawing) = create_signal(false);
     let (last_x, set_last_x) = create_signal(0.0);
     let (last_y, set_last_y) = create_signal(0.0);
+    // A blank canvas is not a signature
+    let (has_ink, set_has_ink) = create_signal(false);
     
     let get_context = move || {
         canvas_ref.get().and_then(|canvas| {
@@ -60,6 +63,7 @@ pub fn SignaturePad(
             
             set_last_x.set(x);
             set_last_y.set(y);
+            set_has_ink.set(true);
         }
     };
     
@@ -71,6 +75,7 @@ pub fn SignaturePad(
         if let Some(ctx) = get_context() {
             ctx.clear_rect(0.0, 0.0, width as f64, height as f64);
         }
+        set_has_ink.set(false);
     };
     
     let save = move |_| {
@@ -146,6 +151,7 @@ pub fn SignaturePad(
                 <button
                     type="button"
                     class="ui-btn ui-btn-primary"
+                    disabled=move || !has_ink.get()
                     on:click=save
                 >
                     "Save Signature"
@@ -154,3 +160,31 @@ pub fn SignaturePad(
         </div>
     }
 }
+
+/// Split a `data:image/png;base64,...` URL into its content type and bytes
+pub fn decode_data_url(data_url: &str) -> Result<(String, Vec<u8>), String> {
+    let rest = data_url.strip_prefix("data:").ok_or("Not a data URL")?;
+    let (meta, data) = rest.split_once(',').ok_or("Data URL has no data")?;
+    let content_type = meta
+        .strip_suffix(";base64")
+        .ok_or("Only base64 data URLs are supported")?;
+    let content_type = if content_type.is_empty() { "text/plain" } else { content_type };
+    let bytes = Base64.decode(data).map_err(|e| format!("Invalid base64 data: {}", e))?;
+    Ok((content_type.to_string(), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_url_decodes_to_png_bytes() {
+        let (content_type, bytes) = decode_data_url("data:image/png;base64,iVBORw0KGgo=").unwrap();
+        assert_eq!(content_type, "image/png");
+        assert_eq!(bytes, b"\x89PNG\r\n\x1a\n");
+
+        assert!(decode_data_url("https://example.com/signature.png").is_err());
+        assert!(decode_data_url("data:image/png,rawdata").is_err());
+        assert!(decode_data_url("data:image/png;base64,not base64!").is_err());
+    }
+}
@@ -14,7 +14,11 @@ use std::collections::HashMap;
 
 use core_models::field::{FieldDef, FieldType, FieldContext};
 use core_models::logic::{LogicOp, EvalContext};
+use core_models::{format_phone, is_e164, normalize_phone, Money, DEFAULT_CURRENCY};
+use crate::utils::{format_money, tenant_money_settings, tenant_phone_region};
+use crate::api;
 use crate::components::async_select::{AsyncSelect, SelectOption};
+use crate::components::signature_pad::SignaturePad;
 
 /// SmartField - Intelligently renders fields based on type and context
 /// 
@@ -45,8 +49,8 @@ pub fn SmartField(
     
     // Evaluate readonly using Logic Engine
     let is_readonly = create_memo(move |_| {
-        // Legacy support: check field.is_readonly first
-        if field_for_readonly.is_readonly || disabled {
+        // Legacy support: check field.is_readonly first; computed fields are never editable
+        if field_for_readonly.is_readonly || field_for_readonly.is_computed() || disabled {
             return true;
         }
         // New: evaluate layout.readonly_if condition
@@ -283,19 +287,30 @@ fn render_input_control(
                 FieldType::Url => "url",
                 _ => "text",
             };
+            // Phones show in the local format and are emitted in E.164 once
+            // they parse; other input is kept for validation to flag
+            let phone_region = matches!(field.field_type, FieldType::Phone).then(tenant_phone_region);
+            let display = match &phone_region {
+                Some(region) => format_phone(&val_str, region),
+                None => val_str,
+            };
             
             view! {
                 <input
                     type=input_type
                     id=field_id
                     class="form-input"
-                    value=val_str
+                    value=display
                     placeholder=field.placeholder.clone().unwrap_or_default()
                     required=field.is_required
                     disabled=is_readonly
                     on:input=move |ev| {
                         if let Some(cb) = on_change {
                             let new_val = event_target_value(&ev);
+                            let new_val = match &phone_region {
+                                Some(region) => normalize_phone(&new_val, region).unwrap_or(new_val),
+                                None => new_val,
+                            };
                             cb.call(JsonValue::String(new_val));
                         }
                     }
@@ -343,25 +358,45 @@ fn render_input_control(
                 />
             }.into_view()
         },
-        FieldType::Money { currency_code: _ } => {
+        FieldType::Money { currency_code } => {
+            let (locale, tenant_currency) = tenant_money_settings();
+            let money = Money::from_value(&value, currency_code.as_deref().unwrap_or(&tenant_currency));
+            let currency = money
+                .as_ref()
+                .map(|m| m.currency.clone())
+                .or_else(|| currency_code.clone())
+                .unwrap_or(tenant_currency);
+            let text = money.map(|m| m.format_number(&locale)).unwrap_or(val_str);
             view! {
-                <input
-                    type="number"
-                    id=field_id
-                    class="form-input"
-                    value=val_str
-                    required=field.is_required
-                    disabled=is_readonly
-                    step="0.01"
-                    on:input=move |ev| {
-                        if let Some(cb) = on_change {
-                            let new_val = event_target_value(&ev);
-                            if let Ok(num) = new_val.parse::<f64>() {
-                                 cb.call(JsonValue::Number(serde_json::Number::from_f64(num).unwrap()));
+                <div class="money-input">
+                    <input
+                        type="text"
+                        inputmode="decimal"
+                        id=field_id
+                        class="form-input"
+                        value=text
+                        required=field.is_required
+                        disabled=is_readonly
+                        on:input={
+                            let currency = currency.clone();
+                            move |ev| {
+                                if let Some(cb) = on_change {
+                                    let new_val = event_target_value(&ev);
+                                    if new_val.trim().is_empty() {
+                                        cb.call(JsonValue::Null);
+                                    } else {
+                                        // Unparseable text is kept so validation can flag it
+                                        match Money::parse(&new_val, &currency, &locale) {
+                                            Ok(money) => cb.call(money.to_value()),
+                                            Err(_) => cb.call(JsonValue::String(new_val)),
+                                        }
+                                    }
+                                }
                             }
                         }
-                    }
-                />
+                    />
+                    <span class="money-input-currency">{currency}</span>
+                </div>
             }.into_view()
         },
         FieldType::Boolean => {
@@ -575,25 +610,17 @@ fn render_input_control(
                 }
             );
             
-            // Mock options - in real implementation, these would be loaded from API
-            let mock_options = vec![
-                SelectOption::new("1", format!("{} 1", target)),
-                SelectOption::new("2", format!("{} 2", target)),
-                SelectOption::new("3", format!("{} 3", target)),
-            ];
-            let options_signal = create_rw_signal(mock_options);
+            let lookup = EntityLookup::new(target.clone(), selected_ids.get_untracked());
             
             view! {
                 <div class="multi-link-field space-y-2">
                     <div class="flex flex-wrap gap-2 mb-2 min-h-[40px] p-2 border border-gray-300 dark:border-gray-700 rounded">
-                        {{
-                        let target_for_display = target.clone();
-                        move || selected_ids.get().into_iter().map(|id| {
+                        {move || selected_ids.get().into_iter().map(|id| {
                             let id_clone = id.clone();
-                            let target_clone = target_for_display.clone();
+                            let id_for_label = id.clone();
                             view! {
                                 <span class="inline-flex items-center gap-1 px-3 py-1 bg-purple-100 dark:bg-purple-900 text-purple-800 dark:text-purple-200 rounded-full text-sm">
-                                    {format!("{} {}", target_clone, id.clone())}
+                                    {move || lookup.label(&id_for_label)}
                                     {if !is_readonly {
                                         view! {
                                             <button
@@ -618,7 +645,6 @@ fn render_input_control(
                                 </span>
                             }
                         }).collect_view()}
-                        }
                     </div>
                     {if !is_readonly {
                         let target_for_select = target.clone();
@@ -627,6 +653,7 @@ fn render_input_control(
                                 value=Signal::from(create_rw_signal(None::<String>))
                                 on_change=Callback::new(move |new_val: Option<String>| {
                                     if let Some(id) = new_val {
+                                        lookup.remember(&id);
                                         selected_ids.update(|ids| {
                                             if !ids.contains(&id) {
                                                 ids.push(id);
@@ -640,13 +667,11 @@ fn render_input_control(
                                         }
                                     }
                                 })
-                                options=Signal::from(options_signal)
+                                options=Signal::from(lookup.options)
+                                on_search=Callback::new(move |query: String| lookup.search(query))
                                 placeholder=format!("Select {}...", target_for_select)
                                 allow_create=false
                             />
-                            <small class="text-xs text-gray-500 mt-1 block">
-                                "API integration for async entity lookup needed"
-                            </small>
                         }.into_view()
                     } else {
                         view! {}.into_view()
@@ -757,13 +782,15 @@ fn render_input_control(
         FieldType::Image => {
             // Image upload with preview
             let image_url = create_rw_signal(val_str.clone());
+            let uploading = create_rw_signal(false);
+            let upload_error = create_rw_signal(None::<String>);
             
             view! {
                 <div class="image-upload-field space-y-2">
                     {move || if !image_url.get().is_empty() {
                         view! {
                             <div class="relative inline-block">
-                                <img src=image_url.get() alt="Uploaded image" class="max-w-xs max-h-64 rounded border shadow-sm"/>
+                                <img src=api::upload_src(&image_url.get()) alt="Uploaded image" class="max-w-xs max-h-64 rounded border shadow-sm"/>
                                 {if !is_readonly {
                                     view! {
                                         <button
@@ -795,28 +822,41 @@ fn render_input_control(
                                     id=field_id
                                     class="form-input"
                                     accept="image/*"
+                                    disabled=move || uploading.get()
                                     on:change=move |ev| {
-                                        // TODO: Upload to server and get URL
-                                        // For now, create a local object URL for preview
-                                        if let Some(input) = ev.target() {
-                                            let input = input.unchecked_into::<web_sys::HtmlInputElement>();
-                                            if let Some(files) = input.files() {
-                                                if let Some(file) = files.get(0) {
-                                                    // Create object URL for preview
-                                                    let url = web_sys::Url::create_object_url_with_blob(&file).unwrap_or_default();
-                                                    image_url.set(url.clone());
-                                                   if let Some(cb) = on_change {
-                                                        // TODO: Actually upload file and get permanent URL
-                                                        cb.call(JsonValue::String(url));
+                                        let input = ev.target().unwrap().unchecked_into::<web_sys::HtmlInputElement>();
+                                        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                                            return;
+                                        };
+                                        input.set_value("");
+                                        uploading.set(true);
+                                        upload_error.set(None);
+                                        spawn_local(async move {
+                                            // Only the stored file's URL goes into the record
+                                            match api::upload_file(&file).await {
+                                                Ok(uploaded) => {
+                                                    image_url.set(uploaded.url.clone());
+                                                    if let Some(cb) = on_change {
+                                                        cb.call(JsonValue::String(uploaded.url));
                                                     }
                                                 }
+                                                Err(e) => upload_error.set(Some(e)),
                                             }
-                                        }
+                                            uploading.set(false);
+                                        });
                                     }
                                 />
-                                <small class="text-xs text-gray-500 mt-1 block">
-                                    "Supported: JPG, PNG, GIF, WebP | Upload API integration needed"
-                                </small>
+                                {move || match (uploading.get(), upload_error.get()) {
+                                    (true, _) => view! {
+                                        <small class="text-xs text-gray-500 mt-1 block">"Uploading..."</small>
+                                    }.into_view(),
+                                    (false, Some(error)) => view! {
+                                        <small class="text-xs text-red-600 mt-1 block">{error}</small>
+                                    }.into_view(),
+                                    (false, None) => view! {
+                                        <small class="text-xs text-gray-500 mt-1 block">"Supported: JPG, PNG, GIF, WebP"</small>
+                                    }.into_view(),
+                                }}
                             </div>
                         }.into_view()
                     } else {
@@ -836,6 +876,8 @@ fn render_input_control(
                     vec![]
                 }
             );
+            let uploading = create_rw_signal(false);
+            let upload_error = create_rw_signal(None::<String>);
             
             view! {
                 <div class="attachment-field space-y-2">
@@ -848,7 +890,7 @@ fn render_input_control(
                                         <div class="flex items-center justify-between gap-2 p-2 hover:bg-gray-100 dark:hover:bg-gray-700 rounded">
                                             <div class="flex items-center gap-2 flex-1 min-w-0">
                                                 <span class="text-xl">"\u{1f4ce}"</span>
-                                                <a href=file_url.clone() class="text-blue-600 hover:underline truncate" target="_blank">
+                                                <a href=api::upload_src(&file_url) class="text-blue-600 hover:underline truncate" target="_blank">
                                                     {file_name}
                                                 </a>
                                             </div>
@@ -892,33 +934,47 @@ fn render_input_control(
                                     id=field_id
                                     class="form-input"
                                     multiple=true
+                                    disabled=move || uploading.get()
                                     on:change=move |ev| {
-                                        // TODO: Upload files to server
-                                        if let Some(input) = ev.target() {
-                                            let input = input.unchecked_into::<web_sys::HtmlInputElement>();
-                                            if let Some(files) = input.files() {
-                                                let mut new_files = Vec::new();
-                                                for i in 0..files.length() {
-                                                    if let Some(file) = files.get(i) {
-                                                        // TODO: Upload and get permanent URL
-                                                        let file_name = file.name();
-                                                        new_files.push(format!("/uploads/{}", file_name));
-                                                    }
-                                                }
-                                                attachments.update(|atts| atts.extend(new_files));
-                                                if let Some(cb) = on_change {
-                                                    let json_array: Vec<JsonValue> = attachments.get().into_iter()
-                                                        .map(JsonValue::String)
-                                                        .collect();
-                                                    cb.call(JsonValue::Array(json_array));
+                                        let input = ev.target().unwrap().unchecked_into::<web_sys::HtmlInputElement>();
+                                        let Some(files) = input.files() else {
+                                            return;
+                                        };
+                                        let files: Vec<web_sys::File> = (0..files.length()).filter_map(|i| files.get(i)).collect();
+                                        input.set_value("");
+                                        uploading.set(true);
+                                        upload_error.set(None);
+                                        spawn_local(async move {
+                                            // Files that fail are reported; the rest are still attached
+                                            let mut errors = Vec::new();
+                                            for file in files {
+                                                match api::upload_file(&file).await {
+                                                    Ok(uploaded) => attachments.update(|atts| atts.push(uploaded.url)),
+                                                    Err(e) => errors.push(format!("{}: {}", file.name(), e)),
                                                 }
                                             }
-                                        }
+                                            if let Some(cb) = on_change {
+                                                let json_array: Vec<JsonValue> = attachments.get_untracked().into_iter()
+                                                    .map(JsonValue::String)
+                                                    .collect();
+                                                cb.call(JsonValue::Array(json_array));
+                                            }
+                                            upload_error.set((!errors.is_empty()).then(|| errors.join("; ")));
+                                            uploading.set(false);
+                                        });
                                     }
                                 />
-                                <small class="text-xs text-gray-500 mt-1 block">
-                                    "Multiple files supported | Upload API integration needed"
-                                </small>
+                                {move || match (uploading.get(), upload_error.get()) {
+                                    (true, _) => view! {
+                                        <small class="text-xs text-gray-500 mt-1 block">"Uploading..."</small>
+                                    }.into_view(),
+                                    (false, Some(error)) => view! {
+                                        <small class="text-xs text-red-600 mt-1 block">{error}</small>
+                                    }.into_view(),
+                                    (false, None) => view! {
+                                        <small class="text-xs text-gray-500 mt-1 block">"Multiple files supported"</small>
+                                    }.into_view(),
+                                }}
                             </div>
                         }.into_view()
                     } else {
@@ -1004,7 +1060,7 @@ fn render_input_control(
                 </div>
             }.into_view()
         },
-        FieldType::Location { show_map: _ } => {
+        FieldType::Location { .. } => {
             // Location/Address field
             view! {
                 <div class="location-field space-y-2">
@@ -1022,7 +1078,7 @@ fn render_input_control(
                         }
                     />
                     <small class="text-xs text-gray-500">
-                        "Map integration and geocoding coming soon"
+                        "Coordinates are filled in from the address when saved"
                     </small>
                 </div>
             }.into_view()
@@ -1048,13 +1104,42 @@ fn render_input_control(
             }.into_view()
         },
         FieldType::Signature => {
-            // Digital signature field
+            // Drawn on a canvas, uploaded (and retained) by the server; the value is its URL
+            let signature_url = create_rw_signal(val_str.clone());
+            let capturing = create_rw_signal(false);
+            let uploading = create_rw_signal(false);
+            let upload_error = create_rw_signal(None::<String>);
+
+            let on_save = Callback::new(move |data_url: String| {
+                uploading.set(true);
+                upload_error.set(None);
+                spawn_local(async move {
+                    match api::upload_signature(&data_url).await {
+                        Ok(uploaded) => {
+                            signature_url.set(uploaded.url.clone());
+                            capturing.set(false);
+                            if let Some(cb) = on_change {
+                                cb.call(JsonValue::String(uploaded.url));
+                            }
+                        }
+                        Err(e) => upload_error.set(Some(e)),
+                    }
+                    uploading.set(false);
+                });
+            });
+
             view! {
                 <div class="signature-field p-4 border border-dashed border-purple-300 dark:border-purple-700 rounded bg-purple-50 dark:bg-purple-900/20">
-                    {if !val_str.is_empty() {
+                    {move || if capturing.get() {
                         view! {
                             <div class="mb-2">
-                                <img src=val_str.clone() alt="Signature" class="max-w-xs border rounded"/>
+                                <SignaturePad on_save=on_save/>
+                            </div>
+                        }.into_view()
+                    } else if !signature_url.get().is_empty() {
+                        view! {
+                            <div class="mb-2">
+                                <img src=api::upload_src(&signature_url.get()) alt="Signature" class="max-w-xs border rounded"/>
                             </div>
                         }.into_view()
                     } else {
@@ -1064,64 +1149,88 @@ fn render_input_control(
                             </div>
                         }.into_view()
                     }}
-                    <button
-                        type="button"
-                        class="btn btn-secondary text-sm"
-                        disabled=is_readonly
-                    >
-                        "Capture Signature"
-                    </button>
-                    <small class="text-xs text-gray-600 dark:text-gray-400 mt-2 block">
-                        "Canvas signature capture coming soon"
-                    </small>
+                    {if !is_readonly {
+                        view! {
+                            <div class="flex gap-2">
+                                {move || if capturing.get() {
+                                    view! {
+                                        <button
+                                            type="button"
+                                            class="btn btn-secondary text-sm"
+                                            disabled=move || uploading.get()
+                                            on:click=move |_| capturing.set(false)
+                                        >
+                                            "Cancel"
+                                        </button>
+                                    }.into_view()
+                                } else {
+                                    let has_signature = !signature_url.get().is_empty();
+                                    view! {
+                                        <button
+                                            type="button"
+                                            class="btn btn-secondary text-sm"
+                                            on:click=move |_| capturing.set(true)
+                                        >
+                                            {if has_signature { "Redo Signature" } else { "Capture Signature" }}
+                                        </button>
+                                        <Show when=move || has_signature>
+                                            <button
+                                                type="button"
+                                                class="btn btn-secondary text-sm"
+                                                on:click=move |_| {
+                                                    // The uploaded image is kept for audit; only the link goes
+                                                    signature_url.set(String::new());
+                                                    if let Some(cb) = on_change {
+                                                        cb.call(JsonValue::String(String::new()));
+                                                    }
+                                                }
+                                            >
+                                                "Clear"
+                                            </button>
+                                        </Show>
+                                    }.into_view()
+                                }}
+                            </div>
+                        }.into_view()
+                    } else {
+                        view! {}.into_view()
+                    }}
+                    {move || match (uploading.get(), upload_error.get()) {
+                        (true, _) => view! {
+                            <small class="text-xs text-gray-500 mt-2 block">"Saving signature..."</small>
+                        }.into_view(),
+                        (false, Some(error)) => view! {
+                            <small class="text-xs text-red-600 mt-2 block">{error}</small>
+                        }.into_view(),
+                        (false, None) => view! {}.into_view(),
+                    }}
                 </div>
             }.into_view()
         },
-        FieldType::Association { target_entity, display_field, allow_inline_create } => {
-            // Association field with AsyncSelect for entity lookup
+        FieldType::Association { target_entity, display_field: _, allow_inline_create } => {
+            // Options come from the entity lookup, labelled by the target's display field
             let target = target_entity.clone();
-            let display = display_field.clone();
             let allow_create = *allow_inline_create;
             
             let selected_id = create_rw_signal(value.as_str().map(|s| s.to_string()));
-            
-            // Mock entity options - in real implementation, these would be loaded from API
-            let mock_options = vec![
-                SelectOption {
-                    value: "1".to_string(),
-                    label: format!("{} Record 1", target),
-                    description: Some(format!("Sample {} #1", target)),
-                    color: None,
-                    icon: None,
-                },
-                SelectOption {
-                    value: "2".to_string(),
-                    label: format!("{} Record 2", target),
-                    description: Some(format!("Sample {} #2", target)),
-                    color: None,
-                    icon: None,
-                },
-                SelectOption {
-                    value: "3".to_string(),
-                    label: format!("{} Record 3", target),
-                    description: Some(format!("Sample {} #3", target)),
-                    color: None,
-                    icon: None,
-                },
-            ];
-            let options_signal = create_rw_signal(mock_options);
+            let lookup = EntityLookup::new(target.clone(), selected_id.get_untracked().into_iter().collect());
+            let options_signal = lookup.options;
             
             view! {
                 <div class="association-field space-y-2">
                     <AsyncSelect
                         value=Signal::from(selected_id)
                         on_change=Callback::new(move |new_val: Option<String>| {
+                            if let Some(id) = &new_val {
+                                lookup.remember(id);
+                            }
                             selected_id.set(new_val.clone());
                             if let Some(cb) = on_change {
                                 cb.call(new_val.map(JsonValue::String).unwrap_or(JsonValue::Null));
                             }
                         })
                         options=Signal::from(options_signal)
+                        on_search=Callback::new(move |query: String| lookup.search(query))
                         placeholder=format!("Select {}...", target)
                         allow_create=allow_create
                         on_create={
@@ -1151,9 +1260,6 @@ fn render_input_control(
                         disabled=is_readonly
                         required=field.is_required
                     />
-                    <small class="text-xs text-gray-500">
-                        "API endpoint: GET /api/v1/entities/" {target} " | Field: " {display}
-                    </small>
                 </div>
             }.into_view()
         },
@@ -1173,6 +1279,89 @@ fn render_input_control(
     }
 }
 
+// ==================
+// Entity Lookup
+// ==================
+
+/// Options of a link field, loaded from the target entity's lookup endpoint
+///
+/// Labels of the selected records are resolved by id, so they show even when
+/// a search doesn't return them. A newer search supersedes one in flight.
+#[derive(Clone, Copy)]
+struct EntityLookup {
+    target: StoredValue<String>,
+    options: RwSignal<Vec<SelectOption>>,
+    /// Selected records, kept across searches
+    selected: RwSignal<Vec<SelectOption>>,
+    generation: StoredValue<u64>,
+}
+
+impl EntityLookup {
+    fn new(target: String, selected_ids: Vec<String>) -> Self {
+        let lookup = Self {
+            target: store_value(target),
+            options: create_rw_signal(Vec::new()),
+            selected: create_rw_signal(Vec::new()),
+            generation: store_value(0),
+        };
+        if !selected_ids.is_empty() {
+            spawn_local(async move {
+                let target = lookup.target.get_value();
+                if let Ok(found) = api::lookup_entity_options(&target, "", &selected_ids).await {
+                    let found: Vec<SelectOption> = found.into_iter().map(|o| SelectOption::new(o.value, o.label)).collect();
+                    lookup.options.update(|options| *options = merge_options(std::mem::take(options), &found));
+                    lookup.selected.set(found);
+                }
+            });
+        }
+        lookup.search(String::new());
+        lookup
+    }
+
+    fn search(self, query: String) {
+        self.generation.update_value(|g| *g += 1);
+        let generation = self.generation.get_value();
+        spawn_local(async move {
+            let target = self.target.get_value();
+            let Ok(found) = api::lookup_entity_options(&target, query.trim(), &[]).await else { return };
+            if self.generation.get_value() != generation {
+                return;
+            }
+            let found = found.into_iter().map(|o| SelectOption::new(o.value, o.label)).collect();
+            self.options.set(merge_options(found, &self.selected.get_untracked()));
+        });
+    }
+
+    /// Keep a newly selected option's label for later searches
+    fn remember(self, id: &str) {
+        let Some(option) = self.options.with_untracked(|options| options.iter().find(|o| o.value == id).cloned()) else {
+            return;
+        };
+        self.selected.update(|selected| {
+            if !selected.iter().any(|o| o.value == id) {
+                selected.push(option);
+            }
+        });
+    }
+
+    /// Label of a record, or its id until resolved
+    fn label(self, id: &str) -> String {
+        self.selected
+            .with(|selected| selected.iter().find(|o| o.value == id).map(|o| o.label.clone()))
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// Search results followed by the pinned options they don't already contain
+fn merge_options(mut options: Vec<SelectOption>, pinned: &[SelectOption]) -> Vec<SelectOption> {
+    for option in pinned {
+        if !options.iter().any(|o| o.value == option.value) {
+            options.push(option.clone());
+        }
+    }
+    options
+}
+
 // ==================
 // Filter Builder Rendering
 // ==================
@@ -1197,9 +1386,10 @@ fn render_filter_input(
 fn format_display_value(field: &FieldDef, value: &JsonValue) -> String {
     match &field.field_type {
         FieldType::Money { currency_code } => {
-            let amount = value.as_f64().unwrap_or(0.0);
-            let currency = currency_code.as_deref().unwrap_or("USD");
-            format!("{} {:.2}", currency, amount)
+            format_money(value, currency_code.as_deref()).unwrap_or_default()
+        },
+        FieldType::Phone => {
+            format_phone(value.as_str().unwrap_or(""), &tenant_phone_region())
         },
         FieldType::Date | FieldType::DateTime => {
             value.as_str().unwrap_or("").to_string()
@@ -1247,7 +1437,6 @@ impl ValidationResult {
 
 // Compiled regex patterns (cached for performance)
 static EMAIL_REGEX: OnceLock<Regex> = OnceLock::new();
-static PHONE_REGEX: OnceLock<Regex> = OnceLock::new();
 static URL_REGEX: OnceLock<Regex> = OnceLock::new();
 
 fn email_regex() -> &'static Regex {
@@ -1256,13 +1445,6 @@ fn email_regex() -> &'static Regex {
     })
 }
 
-fn phone_regex() -> &'static Regex {
-    PHONE_REGEX.get_or_init(|| {
-        // Matches: +1234567890, (123) 456-7890, 123-456-7890, 123.456.7890, 1234567890
-        Regex::new(r"^[\+]?[(]?[0-9]{1,4}[)]?[-\s\.]?[0-9]{1,4}[-\s\.]?[0-9]{1,9}$").unwrap()
-    })
-}
-
 fn url_regex() -> &'static Regex {
     URL_REGEX.get_or_init(|| {
         Regex::new(r"^https?://[^\s/$.?#].[^\s]*$").unwrap()
@@ -1306,16 +1488,16 @@ pub fn validate_field(field: &FieldDef, value: &JsonValue) -> ValidationResult {
             }
         }
         FieldType::Phone => {
+            // The input emits E.164 once the number parses
             let phone = value.as_str().unwrap_or("");
-            // Remove common formatting
-            let cleaned: String = phone.chars().filter(|c| c.is_numeric() || *c == '+').collect();
-            if cleaned.len() < 7 || cleaned.len() > 15 {
-                return ValidationResult::invalid("Phone number should be 7-15 digits");
-            }
-            if !phone_regex().is_match(phone) {
+            if !is_e164(phone) {
                 return ValidationResult::invalid("Please enter a valid phone number");
             }
         }
+        // Set to the uploaded image's URL once the signature is saved
+        FieldType::Signature if value.as_str().is_none_or(|s| s.starts_with("data:")) => {
+            return ValidationResult::invalid("Please save the signature");
+        }
         FieldType::Url => {
             let url = value.as_str().unwrap_or("");
             if !url_regex().is_match(url) {
@@ -1331,14 +1513,8 @@ pub fn validate_field(field: &FieldDef, value: &JsonValue) -> ValidationResult {
                 return ValidationResult::invalid("Please enter a valid number");
             }
         }
-        FieldType::Money { .. } => {
-            if let Some(num_str) = value.as_str() {
-                if num_str.parse::<f64>().is_err() {
-                    return ValidationResult::invalid("Please enter a valid amount");
-                }
-            } else if value.as_f64().is_none() && value.as_i64().is_none() {
-                return ValidationResult::invalid("Please enter a valid amount");
-            }
+        FieldType::Money { .. } if Money::from_value(value, DEFAULT_CURRENCY).is_none() => {
+            return ValidationResult::invalid("Please enter a valid amount");
         }
         FieldType::Date => {
             let date_str = value.as_str().unwrap_or("");
@@ -1410,6 +1586,8 @@ fn validate_portable_rule(
             // For now, skip (let the backend handle complex regex)
             let _ = message;
         }
+        // Record-level rules need the whole record; checked in validate_form
+        ValidationRule::RequiredIf { .. } | ValidationRule::Compare { .. } => {}
         // Backend-only rules are skipped in frontend
         ValidationRule::Unique { .. } => {}
     }
@@ -1504,7 +1682,7 @@ pub fn ValidatedSmartField(
 /// Form-level validation helper
 /// Takes field definitions and a map of field_name -> value
 pub fn validate_form(fields: &[FieldDef], values: &std::collections::HashMap<String, JsonValue>) -> Vec<(String, ValidationResult)> {
-    fields.iter()
+    let mut errors: Vec<(String, ValidationResult)> = fields.iter()
         .filter_map(|field| {
             // Use field.name for lookup since it's a String
             let value = values.get(&field.name).cloned().unwrap_or(JsonValue::Null);
@@ -1515,7 +1693,159 @@ pub fn validate_form(fields: &[FieldDef], values: &std::collections::HashMap<Str
                 None
             }
         })
-        .collect()
+        .collect();
+
+    // Record-level rules (e.g. RequiredIf) may fail a field other than the one they're attached to
+    for field in fields {
+        for rule in field.rules.iter().filter(|r| r.is_record_level()) {
+            if let Err(err) = core_models::validation::validate_record(&field.name, values, rule) {
+                if !errors.iter().any(|(name, _)| *name == err.field) {
+                    errors.push((err.field, ValidationResult::invalid(err.message)));
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Result of validating a form on submit
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FormValidation {
+    /// Error per invalid field
+    pub errors: HashMap<String, ValidationResult>,
+    /// Invalid field that comes first in the form, to focus
+    pub first_error: Option<String>,
+}
+
+impl FormValidation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Validate a form before submitting it; `fields` are in form order
+pub fn validate_submission(fields: &[FieldDef], values: &HashMap<String, JsonValue>) -> FormValidation {
+    let errors: HashMap<String, ValidationResult> = validate_form(fields, values).into_iter().collect();
+    let first_error = fields
+        .iter()
+        .map(|f| &f.name)
+        .find(|name| errors.contains_key(*name))
+        .or_else(|| errors.keys().min())
+        .cloned();
+    FormValidation { errors, first_error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_models::validation::{CompareOp, ValidationRule};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn field(name: &str, field_type: FieldType) -> FieldDef {
+        FieldDef::new(Uuid::nil(), Uuid::nil(), name, name, field_type)
+    }
+
+    fn values(pairs: &[(&str, JsonValue)]) -> HashMap<String, JsonValue> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    fn deal_fields() -> Vec<FieldDef> {
+        let mut stage = field("stage", FieldType::Text);
+        stage.rules.push(ValidationRule::RequiredIf {
+            field: "close_date".into(),
+            op: LogicOp::Equals { field: "stage".into(), value: json!("closed_won") },
+        });
+        let mut end_date = field("end_date", FieldType::Date);
+        end_date.rules.push(ValidationRule::Compare {
+            left_field: "end_date".into(),
+            op: CompareOp::Gt,
+            right_field: "start_date".into(),
+        });
+        vec![
+            field("name", FieldType::Text).required(),
+            field("email", FieldType::Email).required(),
+            stage,
+            field("close_date", FieldType::Date),
+            field("start_date", FieldType::Date),
+            end_date,
+        ]
+    }
+
+    #[test]
+    fn test_missing_required_field_blocks_submit() {
+        let fields = deal_fields();
+        let form = values(&[("name", json!("Marina Villa")), ("email", json!(""))]);
+
+        let result = validate_submission(&fields, &form);
+        assert!(!result.is_valid());
+        assert_eq!(result.first_error.as_deref(), Some("email"));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors["email"].error_message.as_deref(), Some("email is required"));
+    }
+
+    #[test]
+    fn test_valid_form_passes() {
+        let fields = deal_fields();
+        let form = values(&[
+            ("name", json!("Marina Villa")),
+            ("email", json!("sales@example.com")),
+            ("stage", json!("closed_won")),
+            ("close_date", json!("2025-01-31")),
+            ("start_date", json!("2025-01-01")),
+            ("end_date", json!("2025-02-01")),
+        ]);
+
+        assert_eq!(validate_submission(&fields, &form), FormValidation::default());
+    }
+
+    #[test]
+    fn test_record_rules_report_their_field() {
+        let fields = deal_fields();
+        let form = values(&[
+            ("name", json!("Marina Villa")),
+            ("email", json!("sales@example.com")),
+            ("stage", json!("closed_won")),
+            ("start_date", json!("2025-02-01")),
+            ("end_date", json!("2025-01-01")),
+        ]);
+
+        let result = validate_submission(&fields, &form);
+        assert_eq!(result.first_error.as_deref(), Some("close_date"));
+        assert!(result.errors.contains_key("close_date"));
+        assert_eq!(
+            result.errors["end_date"].error_message.as_deref(),
+            Some("end_date must be greater than start_date")
+        );
+    }
+
+    #[test]
+    fn test_phone_must_be_e164() {
+        let phone = field("phone", FieldType::Phone);
+        assert!(validate_field(&phone, &json!("+971501234567")).is_valid);
+        assert!(!validate_field(&phone, &json!("050 123 4567")).is_valid);
+        assert!(!validate_field(&phone, &json!("+1 055 123")).is_valid);
+    }
+
+    #[test]
+    fn test_signature_must_be_an_uploaded_url() {
+        let signature = field("signature", FieldType::Signature);
+        assert!(validate_field(&signature, &json!("/api/v1/uploads/files/t/signatures/s.png")).is_valid);
+        assert!(!validate_field(&signature, &json!("data:image/png;base64,iVBORw0KGgo=")).is_valid);
+    }
+
+    #[test]
+    fn test_selected_options_survive_a_search() {
+        let selected = vec![SelectOption::new("7", "Marina Villa")];
+        let found = vec![SelectOption::new("3", "Palm Tower"), SelectOption::new("7", "Marina Villa")];
+
+        let merged = merge_options(found, &selected);
+        assert_eq!(merged.iter().map(|o| o.value.as_str()).collect::<Vec<_>>(), vec!["3", "7"]);
+
+        let merged = merge_options(vec![SelectOption::new("3", "Palm Tower")], &selected);
+        assert_eq!(merged.iter().map(|o| o.label.as_str()).collect::<Vec<_>>(), vec!["Palm Tower", "Marina Villa"]);
+    }
 }
 
 
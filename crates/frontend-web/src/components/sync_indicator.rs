@@ -5,10 +5,8 @@
 
 use leptos::*;
 use gloo_timers::future::TimeoutFuture;
-use crate::offline::db::LocalDatabase;
-use crate::offline::sync::SyncManager;
-use crate::api::TENANT_ID;
-use uuid::Uuid;
+use crate::context::network_status::{use_network_status, NetworkStatus};
+use crate::offline::background::{use_background_sync, SyncPhase};
 
 /// Network sync status enum
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -76,30 +74,29 @@ pub fn use_sync() -> Option<SyncContext> {
 }
 
 /// Sync Indicator - Floating badge for network status
+///
+/// Follows the background sync (see `offline::background`); clicking it
+/// syncs right away.
 #[component]
 pub fn SyncIndicator() -> impl IntoView {
     let status = create_rw_signal(SyncStatus::Synced);
-    let (is_syncing, set_is_syncing) = create_signal(false);
-    let (db_ready, set_db_ready) = create_signal(false);
     let (visible, set_visible) = create_signal(false);
+    let background = use_background_sync();
+    let sync_state = move || background.map(|b| b.state.get()).unwrap_or_default();
+    let is_syncing = Signal::derive(move || sync_state().phase == SyncPhase::Syncing);
     
     // Provide global sync context
     provide_context(SyncContext { status });
     
-    // Initialize DB (optional - don't show error if OPFS unavailable)
+    // Mirror the background sync state
     create_effect(move |_| {
-        spawn_local(async move {
-            let db = LocalDatabase::new().await;
-            match db {
-                Ok(_) => {
-                    set_db_ready.set(true);
-                    gloo_console::log!("✓ Offline DB Initialized");
-                },
-                Err(e) => {
-                    // OPFS not available is OK - we're just online-only mode
-                    gloo_console::warn!("Offline DB not available (online-only mode):", e);
-                }
-            }
+        let state = sync_state();
+        let offline = use_network_status().is_some_and(|n| n.status.get() == NetworkStatus::Offline);
+        status.set(match state.phase {
+            SyncPhase::Syncing => SyncStatus::Syncing,
+            SyncPhase::Error => SyncStatus::Error,
+            SyncPhase::Idle if offline && state.pending > 0 => SyncStatus::Offline,
+            SyncPhase::Idle => SyncStatus::Synced,
         });
     });
     
@@ -121,35 +118,11 @@ pub fn SyncIndicator() -> impl IntoView {
 
     let on_sync = move |_| {
         if is_syncing.get() { return; }
-        
-        set_is_syncing.set(true);
-        status.set(SyncStatus::Syncing);
-        
-        spawn_local(async move {
-            if db_ready.get() {
-                if let Ok(db) = LocalDatabase::new().await {
-                    let manager = SyncManager::new(db);
-                    let tenant_id = Uuid::parse_str(TENANT_ID).unwrap_or_default();
-                    
-                    // Pull key entities
-                    let _ = manager.pull_entities("contact", &tenant_id).await;
-                    let _ = manager.pull_entities("deal", &tenant_id).await;
-                    let _ = manager.pull_entities("property", &tenant_id).await;
-                    
-                    // Push changes
-                    let _ = manager.push_changes(&tenant_id).await;
-                    
-                    status.set(SyncStatus::Synced);
-                } else {
-                    status.set(SyncStatus::Error);
-                }
-            } else {
-                // Online-only mode - just show success
-                TimeoutFuture::new(500).await;
-                status.set(SyncStatus::Synced);
-            }
-            set_is_syncing.set(false);
-        });
+        match background {
+            Some(background) => background.sync_now(),
+            // Online-only mode - nothing queued locally
+            None => status.set(SyncStatus::Synced),
+        }
     };
 
     view! {
@@ -159,7 +132,7 @@ pub fn SyncIndicator() -> impl IntoView {
                 class="btn-sync-header" 
                 class:syncing=is_syncing
                 on:click=on_sync
-                title="Sync Status"
+                title=move || format!("Sync Status - {} pending", sync_state().pending)
             >
                 <span class="icon">
                     {move || if is_syncing.get() { "↻" } else { "☁" }}
@@ -188,7 +161,7 @@ pub fn SyncIndicator() -> impl IntoView {
                         SyncStatus::Synced => "sync-badge synced",
                     };
                     view! {
-                        <span class=badge_class on:click=on_sync.clone()>
+                        <span class=badge_class on:click=on_sync>
                             <span class="badge-icon">{s.icon()}</span>
                             <span class="badge-text">{s.label()}</span>
                         </span>
@@ -28,6 +28,19 @@ pub enum WsEvent {
         entity_id: Uuid,
         interaction_type: String,
     },
+    ThreadsMerged {
+        thread_id: Uuid,
+        merged_thread_ids: Vec<Uuid>,
+    },
+    ThreadSplit {
+        thread_id: Uuid,
+        new_thread_id: Uuid,
+    },
+    UnreadCountChanged {
+        thread_id: Uuid,
+        user_id: Option<Uuid>,
+        unread_count: Option<i64>,
+    },
     WebhookReceived {
         provider: String,
         message: String,
@@ -163,6 +163,9 @@ impl WebSocketService {
                                 } => {
                                     dispatch_awareness_update(&document_id, user_id, &user_name, &user_color, cursor_position);
                                 }
+                                WsEvent::AwarenessRemove { document_id, user_id } => {
+                                    dispatch_awareness_remove(&document_id, user_id);
+                                }
                                 WsEvent::Notification { title, message, level } => {
                                     gloo_console::log!("Notification:", &title, &message, &level);
                                 }
@@ -243,6 +246,14 @@ impl WebSocketService {
         });
     }
     
+    /// Tell other users we left a document
+    pub fn send_awareness_remove(&self, document_id: &str, user_id: Uuid) {
+        self.send_message(&WsEvent::AwarenessRemove {
+            document_id: document_id.to_string(),
+            user_id,
+        });
+    }
+    
     /// Send a message to WebSocket
     fn send_message(&self, event: &WsEvent) {
         if let Some(ws) = self.ws.borrow().as_ref() {
@@ -318,6 +329,21 @@ fn dispatch_awareness_update(document_id: &str, user_id: Uuid, user_name: &str,
     }
 }
 
+/// Dispatch awareness removal event (user left the document)
+fn dispatch_awareness_remove(document_id: &str, user_id: Uuid) {
+    if let Some(window) = web_sys::window() {
+        let detail = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&detail, &"documentId".into(), &document_id.into());
+        let _ = js_sys::Reflect::set(&detail, &"userId".into(), &user_id.to_string().into());
+        
+        let init = web_sys::CustomEventInit::new();
+        init.set_detail(&detail);
+        if let Ok(event) = web_sys::CustomEvent::new_with_event_init_dict("awareness-remove", &init) {
+            let _ = window.dispatch_event(&event);
+        }
+    }
+}
+
 /// Create WebSocket context provider
 #[component]
 pub fn WebSocketProvider(children: Children) -> impl IntoView {
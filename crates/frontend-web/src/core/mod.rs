@@ -2,11 +2,13 @@
 //! Handles all State, Synchronization, Connectivity, and Data Modeling.
 
 pub mod api;
+pub mod autosave;
 pub mod context;
 pub mod entities;
 pub mod models;
 pub mod sync_engine;
 pub mod shortcuts;
+pub mod view_layout;
 
 // Re-export core primitives
 pub use entities::*;
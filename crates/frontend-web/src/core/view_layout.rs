@@ -0,0 +1,112 @@
+//! View Layout: column widths of a view, persisted after resizing
+//!
+//! Widths live in the view's `columns` JSON as CSS pixel strings ("240px").
+//! A drag emits one resize per column; resizes are coalesced and the whole
+//! column list is saved once the user stops for `COLUMN_PERSIST_DEBOUNCE_MS`.
+
+use std::collections::HashMap;
+use serde_json::Value;
+
+use crate::api::ViewColumn;
+
+/// Quiet period before a resized layout is saved
+pub const COLUMN_PERSIST_DEBOUNCE_MS: u32 = 750;
+
+/// Narrowest a column can be dragged
+pub const MIN_COLUMN_WIDTH: u32 = 60;
+
+/// Pixel width of a stored width like "240px" or "240"
+pub fn parse_width(width: &str) -> Option<u32> {
+    width.trim().trim_end_matches("px").trim().parse::<f64>().ok().map(|w| w.round() as u32)
+}
+
+/// Column layout of the active view and its unsaved changes
+#[derive(Debug, Clone, Default)]
+pub struct ColumnLayout {
+    columns: Vec<ViewColumn>,
+    /// Bumped per resize; a debounce timer only saves if it is still current
+    generation: u64,
+    dirty: bool,
+}
+
+impl ColumnLayout {
+    pub fn new(columns: Vec<ViewColumn>) -> Self {
+        Self { columns, ..Default::default() }
+    }
+
+    /// Widths (px) of the columns that have one
+    pub fn widths(&self) -> HashMap<String, u32> {
+        self.columns
+            .iter()
+            .filter_map(|c| Some((c.field.clone(), parse_width(c.width.as_deref()?)?)))
+            .collect()
+    }
+
+    /// Set a column's width, returning the generation to save after the debounce
+    pub fn resize(&mut self, field: &str, width: u32) -> u64 {
+        let width = Some(format!("{}px", width.max(MIN_COLUMN_WIDTH)));
+        match self.columns.iter_mut().find(|c| c.field == field) {
+            Some(column) => column.width = width,
+            // Shown by default but not listed in the view yet
+            None => self.columns.push(ViewColumn {
+                field: field.to_string(),
+                width,
+                visible: true,
+                sort_order: self.columns.len() as i32,
+            }),
+        }
+        self.dirty = true;
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Body for the views API, if `generation` is the latest resize and unsaved
+    pub fn take(&mut self, generation: u64) -> Option<Value> {
+        if generation != self.generation || !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        Some(serde_json::json!({ "columns": self.columns }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(field: &str, width: Option<&str>) -> ViewColumn {
+        ViewColumn { field: field.to_string(), width: width.map(String::from), visible: true, sort_order: 0 }
+    }
+
+    #[test]
+    fn test_widths_restore_from_view() {
+        let layout = ColumnLayout::new(vec![column("name", Some("240px")), column("stage", None), column("price", Some("96"))]);
+        assert_eq!(layout.widths(), HashMap::from([("name".to_string(), 240), ("price".to_string(), 96)]));
+    }
+
+    #[test]
+    fn test_resizes_coalesce_into_one_payload() {
+        let mut layout = ColumnLayout::new(vec![column("name", Some("200px")), column("stage", None)]);
+        let first = layout.resize("name", 220);
+        let second = layout.resize("name", 260);
+        let third = layout.resize("email", 10);
+
+        // Earlier timers find a newer resize and send nothing
+        assert_eq!(layout.take(first), None);
+        assert_eq!(layout.take(second), None);
+
+        let payload = layout.take(third).unwrap();
+        assert_eq!(
+            payload,
+            json!({ "columns": [
+                { "field": "name", "width": "260px", "visible": true, "sort_order": 0 },
+                { "field": "stage", "width": null, "visible": true, "sort_order": 0 },
+                { "field": "email", "width": "60px", "visible": true, "sort_order": 2 },
+            ]})
+        );
+
+        // Already saved
+        assert_eq!(layout.take(third), None);
+    }
+}
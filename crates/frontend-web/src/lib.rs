@@ -39,6 +39,7 @@ pub fn hydrate() {
         context::provide_jirsi_theme();
         context::provide_mobile_context();
         context::network_status::provide_network_status();
+        offline::provide_background_sync();
         
         view! { <App/> }
     });
@@ -0,0 +1,254 @@
+//! Background Sync - flush the offline queue when the app can reach the server
+//!
+//! Sync runs when the network comes back (browser `online` events and the
+//! `NetworkStatusContext` going from offline to online), when the tab
+//! becomes visible again, or when the user asks for it. Automatic triggers
+//! are debounced, and after a failed run they wait out an exponential
+//! backoff; a manual "sync now" runs straight away.
+
+use leptos::*;
+use gloo_timers::future::TimeoutFuture;
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use super::db::LocalDatabase;
+use super::sync::{retry_delay, SyncManager, SyncResult};
+use crate::api::TENANT_ID;
+use crate::context::network_status::{use_network_status, NetworkStatus};
+
+/// Wait after an automatic trigger, so a burst of events syncs once
+pub const DEBOUNCE_MS: u32 = 1_000;
+
+/// Entities refreshed by a manual sync
+const PULLED_ENTITIES: &[&str] = &["contact", "deal", "property"];
+
+/// What set off a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncTrigger {
+    /// The network came back
+    Online,
+    /// The tab became visible
+    Visible,
+    /// Retry after a failed run
+    Retry,
+    /// The user pressed "sync now"
+    Manual,
+}
+
+/// Phase of the background sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPhase {
+    #[default]
+    Idle,
+    Syncing,
+    /// The last run failed; a retry is scheduled
+    Error,
+}
+
+/// Sync state for the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SyncState {
+    pub phase: SyncPhase,
+    /// Local changes not yet on the server
+    pub pending: u32,
+}
+
+/// When the next sync may run, given past failures
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncSchedule {
+    failures: u32,
+    /// Time (ms) before which automatic triggers wait
+    retry_at_ms: Option<f64>,
+}
+
+impl SyncSchedule {
+    /// Delay (ms) before running a sync set off by `trigger` at `now_ms`
+    pub fn delay(&self, trigger: SyncTrigger, now_ms: f64) -> u32 {
+        if trigger == SyncTrigger::Manual {
+            return 0;
+        }
+        let backoff = self.retry_at_ms.map_or(0.0, |at| (at - now_ms).max(0.0)) as u32;
+        backoff.max(DEBOUNCE_MS)
+    }
+
+    /// Record a successful run
+    pub fn succeeded(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record a failed run at `now_ms`, returning the delay before the retry
+    pub fn failed(&mut self, now_ms: f64) -> u32 {
+        self.failures += 1;
+        let delay = retry_delay(self.failures).num_milliseconds() as u32;
+        self.retry_at_ms = Some(now_ms + delay as f64);
+        delay
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+/// Whether a network status change means the connection came back
+pub fn came_online(previous: Option<NetworkStatus>, current: NetworkStatus) -> bool {
+    current == NetworkStatus::Online && previous == Some(NetworkStatus::Offline)
+}
+
+/// Background sync context
+#[derive(Clone, Copy)]
+pub struct BackgroundSync {
+    pub state: RwSignal<SyncState>,
+    schedule: StoredValue<SyncSchedule>,
+    /// Bumped per scheduled run; a timer only fires if it is still current
+    generation: StoredValue<u64>,
+}
+
+impl BackgroundSync {
+    /// Sync immediately, ignoring any backoff
+    pub fn sync_now(&self) {
+        self.trigger(SyncTrigger::Manual);
+    }
+
+    /// Schedule a sync, replacing any one already waiting
+    pub fn trigger(&self, trigger: SyncTrigger) {
+        let delay = self.schedule.with_value(|s| s.delay(trigger, js_sys::Date::now()));
+        let generation = self.generation.with_value(|g| g + 1);
+        self.generation.set_value(generation);
+
+        let this = *self;
+        spawn_local(async move {
+            if delay > 0 {
+                TimeoutFuture::new(delay).await;
+            }
+            if this.generation.get_value() != generation
+                || this.state.get_untracked().phase == SyncPhase::Syncing
+            {
+                return;
+            }
+            this.run(trigger == SyncTrigger::Manual).await;
+        });
+    }
+
+    async fn run(&self, pull: bool) {
+        self.state.update(|s| s.phase = SyncPhase::Syncing);
+
+        let db = match LocalDatabase::new().await {
+            Ok(db) => db,
+            Err(e) => {
+                // No offline store (online-only mode): nothing to flush
+                gloo_console::warn!("Background sync skipped:", e);
+                self.state.set(SyncState::default());
+                return;
+            }
+        };
+        let manager = SyncManager::new(db.clone());
+        let tenant_id = Uuid::parse_str(TENANT_ID).unwrap_or_default();
+
+        let result = if pull {
+            manager.sync_all(&tenant_id, PULLED_ENTITIES).await.unwrap_or_else(SyncResult::Error)
+        } else {
+            manager.sync(&tenant_id).await
+        };
+        let pending = db.get_pending_count().unwrap_or(0);
+
+        match result {
+            SyncResult::Success { .. } | SyncResult::Conflict { .. } | SyncResult::Offline => {
+                self.schedule.update_value(|s| s.succeeded());
+                self.state.set(SyncState { phase: SyncPhase::Idle, pending });
+            }
+            SyncResult::PartialSuccess { .. } | SyncResult::Error(_) => {
+                let mut schedule = self.schedule.get_value();
+                let delay = schedule.failed(js_sys::Date::now());
+                self.schedule.set_value(schedule);
+                self.state.set(SyncState { phase: SyncPhase::Error, pending });
+                gloo_console::warn!("Sync failed", schedule.failures(), "time(s) - retrying in", delay, "ms");
+                self.trigger(SyncTrigger::Retry);
+            }
+        }
+    }
+}
+
+/// Provide background sync at app root; needs `provide_network_status` first
+pub fn provide_background_sync() {
+    let sync = BackgroundSync {
+        state: create_rw_signal(SyncState::default()),
+        schedule: store_value(SyncSchedule::default()),
+        generation: store_value(0),
+    };
+    provide_context(sync);
+
+    if let Some(network) = use_network_status() {
+        // Keep the network status in step with the browser's view
+        if let Some(window) = web_sys::window() {
+            for (event, online) in [("online", true), ("offline", false)] {
+                let closure = Closure::<dyn Fn()>::new(move || {
+                    if online { network.set_online() } else { network.set_offline() }
+                });
+                let _ = window.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref());
+                closure.forget();
+            }
+        }
+
+        create_effect(move |previous: Option<NetworkStatus>| {
+            let current = network.status.get();
+            if came_online(previous, current) {
+                sync.trigger(SyncTrigger::Online);
+            }
+            current
+        });
+    }
+
+    if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+        let visible_document = document.clone();
+        let closure = Closure::<dyn Fn()>::new(move || {
+            if !visible_document.hidden() {
+                sync.trigger(SyncTrigger::Visible);
+            }
+        });
+        let _ = document.add_event_listener_with_callback("visibilitychange", closure.as_ref().unchecked_ref());
+        closure.forget();
+    }
+
+    // Flush whatever was queued in an earlier session
+    sync.trigger(SyncTrigger::Visible);
+}
+
+/// Get the background sync context
+pub fn use_background_sync() -> Option<BackgroundSync> {
+    use_context::<BackgroundSync>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_going_online_fires_debounced_sync() {
+        assert!(came_online(Some(NetworkStatus::Offline), NetworkStatus::Online));
+        assert!(!came_online(Some(NetworkStatus::Online), NetworkStatus::Online));
+        assert!(!came_online(None, NetworkStatus::Online));
+        assert!(!came_online(Some(NetworkStatus::Online), NetworkStatus::Offline));
+
+        let schedule = SyncSchedule::default();
+        assert_eq!(schedule.delay(SyncTrigger::Online, 0.0), DEBOUNCE_MS);
+        assert_eq!(schedule.delay(SyncTrigger::Manual, 0.0), 0);
+    }
+
+    #[test]
+    fn test_repeated_failures_back_off() {
+        let mut schedule = SyncSchedule::default();
+
+        let delays: Vec<u32> = (0..3).map(|_| schedule.failed(0.0)).collect();
+        assert_eq!(delays, vec![5_000, 10_000, 20_000]);
+
+        // Automatic triggers wait for the backoff, manual ones do not
+        assert_eq!(schedule.delay(SyncTrigger::Online, 5_000.0), 15_000);
+        assert_eq!(schedule.delay(SyncTrigger::Visible, 19_500.0), DEBOUNCE_MS);
+        assert_eq!(schedule.delay(SyncTrigger::Manual, 5_000.0), 0);
+
+        schedule.succeeded();
+        assert_eq!(schedule.failures(), 0);
+        assert_eq!(schedule.delay(SyncTrigger::Online, 0.0), DEBOUNCE_MS);
+    }
+}
@@ -195,6 +195,59 @@ impl CrdtText {
     }
 }
 
+/// Three-way merge of two concurrent edits of `base`, through the CRDT
+///
+/// Each side's edit is replayed as a replacement of the range it changed on
+/// its own replica of `base`; exchanging the updates lets the CRDT
+/// interleave both, so edits to different parts of the text are both kept.
+pub fn merge_text(base: &str, local: &str, server: &str) -> String {
+    let entity_id = Uuid::nil();
+    let origin = CrdtText::new(entity_id, "merge");
+    origin.set(base);
+    let initial = origin.get_update();
+
+    let replica = |edited: &str| -> Result<CrdtText, String> {
+        let text = CrdtText::new(entity_id, "merge");
+        text.apply_update(&initial)?;
+        let (start, removed, inserted) = changed_range(base, edited);
+        if removed > 0 {
+            text.delete(start, removed);
+        }
+        if !inserted.is_empty() {
+            text.insert(start, inserted);
+        }
+        Ok(text)
+    };
+
+    let merged = (|| {
+        let (ours, theirs) = (replica(local)?, replica(server)?);
+        ours.apply_update(&theirs.get_update())?;
+        Ok::<_, String>(ours.get())
+    })();
+    // Replicas built from the same update always decode; keep the server
+    // text if that ever fails
+    merged.unwrap_or_else(|_| server.to_string())
+}
+
+/// Byte range of `base` replaced in `edited`: (start, removed length, inserted text)
+fn changed_range<'a>(base: &str, edited: &'a str) -> (u32, u32, &'a str) {
+    let prefix = base
+        .char_indices()
+        .zip(edited.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(base.len().min(edited.len()), |((i, _), _)| i);
+    let suffix = base[prefix..]
+        .chars()
+        .rev()
+        .zip(edited[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+
+    let removed = base.len() - prefix - suffix;
+    (prefix as u32, removed as u32, &edited[prefix..edited.len() - suffix])
+}
+
 /// Document manager for tracking multiple collaborative documents
 pub struct CrdtManager {
     documents: Mutex<HashMap<String, CrdtDocument>>,
@@ -343,4 +396,14 @@ mod tests {
         // Both should converge to same state
         assert_eq!(text_a.get(), text_b.get());
     }
+
+    #[test]
+    fn test_merge_text_keeps_both_edits() {
+        let base = "Villa with sea view.";
+        let local = "Renovated villa with sea view.";
+        let server = "Villa with sea view. Pool included.";
+
+        assert_eq!(merge_text(base, local, server), "Renovated villa with sea view. Pool included.");
+        assert_eq!(merge_text(base, base, server), server);
+    }
 }
@@ -1,8 +1,10 @@
 //! Local Database - OPFS-backed SQLite for offline-first functionality
 //!
 //! Provides a JavaScript-bridged interface to SQLite WASM running on OPFS.
-//! Supports dirty record tracking for sync queue management.
+//! Supports dirty record tracking for sync queue management. The schema is
+//! upgraded on open by the versioned steps in `migrations`.
 
+use super::migrations;
 use wasm_bindgen::prelude::*;
 use serde_wasm_bindgen::to_value;
 use uuid::Uuid;
@@ -41,44 +43,28 @@ impl LocalDatabase {
         }
     }
 
-    /// Ensure required tables exist
+    /// Bring the schema up to date, rebuilding the store if a migration fails
     fn ensure_schema(&self) -> Result<(), String> {
-        // Main records table with dirty tracking
-        self.execute(r#"
-            CREATE TABLE IF NOT EXISTS local_entity_records (
-                id TEXT PRIMARY KEY,
-                tenant_id TEXT NOT NULL,
-                entity_type TEXT NOT NULL,
-                data TEXT NOT NULL,
-                is_dirty INTEGER DEFAULT 0,
-                is_deleted INTEGER DEFAULT 0,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                last_synced_at TEXT,
-                server_version INTEGER DEFAULT 0
-            )
-        "#)?;
-
-        // Create indexes for efficient querying
-        self.execute("CREATE INDEX IF NOT EXISTS idx_dirty ON local_entity_records(is_dirty)")?;
-        self.execute("CREATE INDEX IF NOT EXISTS idx_entity_type ON local_entity_records(entity_type, tenant_id)")?;
-
-        // Pending operations queue for offline actions
-        self.execute(r#"
-            CREATE TABLE IF NOT EXISTS pending_operations (
-                id TEXT PRIMARY KEY,
-                operation_type TEXT NOT NULL,
-                entity_type TEXT NOT NULL,
-                entity_id TEXT NOT NULL,
-                payload TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                retry_count INTEGER DEFAULT 0
-            )
-        "#)?;
-
+        if let Err(e) = migrations::migrate(self) {
+            gloo_console::error!("Offline DB", e.to_string(), "- resetting local store");
+            self.force_reset()?;
+        }
         Ok(())
     }
 
+    /// Schema version of the local store
+    pub fn schema_version(&self) -> Result<u32, String> {
+        migrations::user_version(self)
+    }
+
+    /// Drop all local data and recreate the schema at the latest version
+    ///
+    /// Unsynced changes are lost; used when a migration cannot be applied.
+    pub fn force_reset(&self) -> Result<(), String> {
+        gloo_console::warn!("Resetting offline DB to schema version", migrations::latest_version());
+        migrations::reset(self).map(|_| ()).map_err(|e| e.to_string())
+    }
+
     /// Execute SQL without parameters
     pub fn execute(&self, sql: &str) -> Result<(), String> {
         executeSql(sql, JsValue::NULL)
@@ -104,6 +90,9 @@ impl LocalDatabase {
     }
 
     /// Save a record locally (marks as dirty for sync)
+    ///
+    /// Remembers the server version the edit was based on and which fields
+    /// it changed, so the sync can send just those with the version.
     pub fn save_record(
         &self,
         entity_type: &str,
@@ -114,10 +103,21 @@ impl LocalDatabase {
         let now = chrono::Utc::now().to_rfc3339();
         let data_str = serde_json::to_string(data).map_err(|e| e.to_string())?;
 
+        let previous = self.get_record_state(entity_id)?;
+        let edit = record_edit(previous.as_ref(), data);
+        let changed_str = serde_json::to_string(&edit.changed_fields).map_err(|e| e.to_string())?;
+        // The server copy the edits started from, for merging on conflict
+        let base_data = previous.as_ref().map(|p| if p.is_dirty { &p.base_data } else { &p.data });
+        let base_str = match base_data {
+            Some(base) if !base.is_null() => Some(serde_json::to_string(base).map_err(|e| e.to_string())?),
+            _ => None,
+        };
+
         let sql = r#"
             INSERT OR REPLACE INTO local_entity_records 
-            (id, tenant_id, entity_type, data, is_dirty, created_at, updated_at)
-            VALUES (?, ?, ?, ?, 1, COALESCE((SELECT created_at FROM local_entity_records WHERE id = ?), ?), ?)
+            (id, tenant_id, entity_type, data, is_dirty, created_at, updated_at,
+             server_version, base_version, changed_fields, base_data, sync_attempts, next_sync_at)
+            VALUES (?, ?, ?, ?, 1, COALESCE((SELECT created_at FROM local_entity_records WHERE id = ?), ?), ?, ?, ?, ?, ?, 0, NULL)
         "#;
 
         let params = serde_json::json!([
@@ -127,12 +127,25 @@ impl LocalDatabase {
             data_str,
             entity_id.to_string(),
             now.clone(),
-            now
+            now,
+            previous.map(|p| p.server_version).unwrap_or(0),
+            edit.base_version,
+            changed_str,
+            base_str
         ]);
 
         self.execute_with_params(sql, params)
     }
 
+    /// Current local state of a record, if it is stored
+    pub fn get_record_state(&self, entity_id: &Uuid) -> Result<Option<LocalRecordState>, String> {
+        let rows = self.query(&format!(
+            "SELECT data, is_dirty, server_version, base_version, changed_fields, base_data FROM local_entity_records WHERE id = '{}'",
+            entity_id
+        ))?;
+        Ok(rows.first().map(LocalRecordState::from_row))
+    }
+
     /// Mark a record as deleted (soft delete)
     pub fn delete_record(&self, entity_id: &Uuid) -> Result<(), String> {
         let now = chrono::Utc::now().to_rfc3339();
@@ -147,13 +160,19 @@ impl LocalDatabase {
         
         rows.into_iter()
             .map(|row| {
+                let int = |key: &str| row.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
                 Ok(DirtyRecord {
                     id: row.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
                     tenant_id: row.get("tenant_id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
                     entity_type: row.get("entity_type").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
-                    data: row.get("data").cloned().unwrap_or(serde_json::Value::Null),
-                    is_deleted: row.get("is_deleted").and_then(|v| v.as_i64()).unwrap_or(0) == 1,
-                    server_version: row.get("server_version").and_then(|v| v.as_i64()).unwrap_or(0) as u64,
+                    data: json_column(&row, "data"),
+                    is_deleted: int("is_deleted") == 1,
+                    server_version: int("server_version") as u64,
+                    base_version: int("base_version") as u64,
+                    changed_fields: serde_json::from_value(json_column(&row, "changed_fields")).unwrap_or_default(),
+                    base_data: json_column(&row, "base_data"),
+                    sync_attempts: int("sync_attempts") as u32,
+                    next_sync_at: row.get("next_sync_at").and_then(|v| v.as_str()).map(String::from),
                 })
             })
             .collect()
@@ -162,8 +181,41 @@ impl LocalDatabase {
     /// Mark record as synced (clear dirty flag)
     pub fn mark_synced(&self, entity_id: &Uuid, server_version: u64) -> Result<(), String> {
         let now = chrono::Utc::now().to_rfc3339();
-        let sql = "UPDATE local_entity_records SET is_dirty = 0, last_synced_at = ?, server_version = ? WHERE id = ?";
-        let params = serde_json::json!([now, server_version, entity_id.to_string()]);
+        let sql = r#"
+            UPDATE local_entity_records
+            SET is_dirty = 0, last_synced_at = ?, server_version = ?, base_version = ?,
+                changed_fields = '[]', base_data = NULL, sync_attempts = 0, next_sync_at = NULL
+            WHERE id = ?
+        "#;
+        let params = serde_json::json!([now, server_version, server_version, entity_id.to_string()]);
+        self.execute_with_params(sql, params)
+    }
+
+    /// Record a failed push; the record is skipped until `next_sync_at`
+    pub fn record_sync_failure(&self, entity_id: &Uuid, attempts: u32, next_sync_at: &str) -> Result<(), String> {
+        let sql = "UPDATE local_entity_records SET sync_attempts = ?, next_sync_at = ? WHERE id = ?";
+        let params = serde_json::json!([attempts, next_sync_at, entity_id.to_string()]);
+        self.execute_with_params(sql, params)
+    }
+
+    /// Re-base a dirty record on a newer server copy, e.g. after the user
+    /// resolved a conflict, so the next sync sends `changed_fields` against it
+    pub fn rebase_record(
+        &self,
+        entity_id: &Uuid,
+        server_version: u64,
+        server_data: &serde_json::Value,
+        changed_fields: &[String],
+    ) -> Result<(), String> {
+        let changed_str = serde_json::to_string(changed_fields).map_err(|e| e.to_string())?;
+        let base_str = serde_json::to_string(server_data).map_err(|e| e.to_string())?;
+        let sql = r#"
+            UPDATE local_entity_records
+            SET is_dirty = 1, server_version = ?, base_version = ?, changed_fields = ?, base_data = ?,
+                sync_attempts = 0, next_sync_at = NULL
+            WHERE id = ?
+        "#;
+        let params = serde_json::json!([server_version, server_version, changed_str, base_str, entity_id.to_string()]);
         self.execute_with_params(sql, params)
     }
 
@@ -245,14 +297,6 @@ impl LocalDatabase {
 
     /// Mark operation as failed with error message
     pub fn mark_operation_failed(&self, operation_id: &str, error: &str) -> Result<(), String> {
-        // Add failed_at and last_error columns if not exist (safe to call multiple times)
-        let _ = self.execute(
-            "ALTER TABLE pending_operations ADD COLUMN failed_at TEXT"
-        );
-        let _ = self.execute(
-            "ALTER TABLE pending_operations ADD COLUMN last_error TEXT"
-        );
-
         let now = chrono::Utc::now().to_rfc3339();
         let sql = "UPDATE pending_operations SET failed_at = ?, last_error = ?, retry_count = retry_count + 1 WHERE id = ?";
         let params = serde_json::json!([now, error, operation_id]);
@@ -280,6 +324,16 @@ impl LocalDatabase {
     }
 }
 
+impl migrations::SqlConnection for LocalDatabase {
+    fn execute(&self, sql: &str) -> Result<(), String> {
+        LocalDatabase::execute(self, sql)
+    }
+
+    fn query(&self, sql: &str) -> Result<Vec<serde_json::Value>, String> {
+        LocalDatabase::query(self, sql)
+    }
+}
+
 /// Represents a dirty record that needs to be synced
 #[derive(Debug, Clone)]
 pub struct DirtyRecord {
@@ -289,6 +343,122 @@ pub struct DirtyRecord {
     pub data: serde_json::Value,
     pub is_deleted: bool,
     pub server_version: u64,
+    /// Server version the local edits were made from (0 for new records)
+    pub base_version: u64,
+    /// Fields edited locally since the last sync
+    pub changed_fields: Vec<String>,
+    /// Server copy at `base_version` (null if unknown)
+    pub base_data: serde_json::Value,
+    /// Failed pushes since the last successful sync
+    pub sync_attempts: u32,
+    /// Earliest time (RFC 3339) to retry after a failure
+    pub next_sync_at: Option<String>,
+}
+
+impl DirtyRecord {
+    /// Whether the record was created offline and never reached the server
+    pub fn is_new(&self) -> bool {
+        self.base_version == 0 && self.server_version == 0
+    }
+
+    /// Update body: the changed fields plus the version they were edited
+    /// from, for the server's optimistic-concurrency check. Records queued
+    /// before changes were tracked send all their fields.
+    pub fn patch(&self, expected_version: u64) -> serde_json::Value {
+        let mut patch = match (&self.data, self.changed_fields.is_empty()) {
+            (serde_json::Value::Object(fields), true) => fields.clone(),
+            _ => serde_json::Map::new(),
+        };
+        for field in &self.changed_fields {
+            patch.insert(field.clone(), self.data.get(field).cloned().unwrap_or(serde_json::Value::Null));
+        }
+        patch.insert(VERSION_KEY.to_string(), serde_json::json!(expected_version));
+        serde_json::Value::Object(patch)
+    }
+
+    /// Whether a failed push may be retried at `now`
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.next_sync_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .is_none_or(|at| at <= now)
+    }
+}
+
+/// Record version field shared with the server's optimistic-concurrency check
+pub const VERSION_KEY: &str = "_version";
+
+/// Stored state of a local record, as needed to track a new edit
+#[derive(Debug, Clone, Default)]
+pub struct LocalRecordState {
+    pub data: serde_json::Value,
+    pub is_dirty: bool,
+    pub server_version: u64,
+    pub base_version: u64,
+    pub changed_fields: Vec<String>,
+    pub base_data: serde_json::Value,
+}
+
+impl LocalRecordState {
+    fn from_row(row: &serde_json::Value) -> Self {
+        let int = |key: &str| row.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+        Self {
+            data: json_column(row, "data"),
+            is_dirty: int("is_dirty") == 1,
+            server_version: int("server_version") as u64,
+            base_version: int("base_version") as u64,
+            changed_fields: serde_json::from_value(json_column(row, "changed_fields")).unwrap_or_default(),
+            base_data: json_column(row, "base_data"),
+        }
+    }
+}
+
+/// Version and changed fields recorded for a local edit
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordEdit {
+    pub base_version: u64,
+    pub changed_fields: Vec<String>,
+}
+
+/// Track an edit of `previous` to `data`
+///
+/// Edits made while the record is already dirty keep the original base
+/// version and add to its changed fields, so several offline edits reach
+/// the server as one update against the version they started from.
+pub fn record_edit(previous: Option<&LocalRecordState>, data: &serde_json::Value) -> RecordEdit {
+    let empty = serde_json::Map::new();
+    let new_fields = data.as_object().unwrap_or(&empty);
+    let Some(previous) = previous else {
+        let mut changed_fields: Vec<String> = new_fields.keys().cloned().collect();
+        changed_fields.sort();
+        return RecordEdit { base_version: 0, changed_fields };
+    };
+
+    let old_fields = previous.data.as_object().unwrap_or(&empty);
+    let mut changed_fields: Vec<String> = if previous.is_dirty { previous.changed_fields.clone() } else { Vec::new() };
+    changed_fields.extend(
+        new_fields
+            .keys()
+            .chain(old_fields.keys())
+            .filter(|key| new_fields.get(*key) != old_fields.get(*key))
+            .cloned(),
+    );
+    changed_fields.sort();
+    changed_fields.dedup();
+
+    RecordEdit {
+        base_version: if previous.is_dirty { previous.base_version } else { previous.server_version },
+        changed_fields,
+    }
+}
+
+/// A JSON column, which SQLite hands back as text
+fn json_column(row: &serde_json::Value, key: &str) -> serde_json::Value {
+    match row.get(key) {
+        Some(serde_json::Value::String(text)) => serde_json::from_str(text).unwrap_or(serde_json::Value::Null),
+        Some(value) => value.clone(),
+        None => serde_json::Value::Null,
+    }
 }
 
 /// Sync status for the UI indicator
@@ -320,3 +490,57 @@ impl SyncStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn synced(data: serde_json::Value, server_version: u64) -> LocalRecordState {
+        LocalRecordState { data, server_version, base_version: server_version, ..Default::default() }
+    }
+
+    #[test]
+    fn test_offline_edits_queue_changed_fields_against_base_version() {
+        let stored = synced(json!({ "name": "Marina Villa", "stage": "lead", "amount": 100 }), 4);
+
+        let first = record_edit(Some(&stored), &json!({ "name": "Marina Villa", "stage": "qualified", "amount": 100 }));
+        assert_eq!(first, RecordEdit { base_version: 4, changed_fields: vec!["stage".to_string()] });
+
+        // A second offline edit keeps the base and adds to the changed fields
+        let dirty = LocalRecordState {
+            data: json!({ "name": "Marina Villa", "stage": "qualified", "amount": 100 }),
+            is_dirty: true,
+            server_version: 4,
+            base_version: first.base_version,
+            changed_fields: first.changed_fields,
+            ..Default::default()
+        };
+        let second = record_edit(Some(&dirty), &json!({ "name": "Marina Villa", "stage": "qualified", "amount": 250 }));
+        assert_eq!(second.base_version, 4);
+        assert_eq!(second.changed_fields, vec!["amount".to_string(), "stage".to_string()]);
+
+        let record = DirtyRecord {
+            id: "r-1".to_string(),
+            tenant_id: "t-1".to_string(),
+            entity_type: "deal".to_string(),
+            data: json!({ "name": "Marina Villa", "stage": "qualified", "amount": 250 }),
+            is_deleted: false,
+            server_version: 4,
+            base_version: second.base_version,
+            changed_fields: second.changed_fields,
+            base_data: stored.data.clone(),
+            sync_attempts: 0,
+            next_sync_at: None,
+        };
+        assert!(!record.is_new());
+        assert_eq!(record.patch(record.base_version), json!({ "stage": "qualified", "amount": 250, "_version": 4 }));
+    }
+
+    #[test]
+    fn test_new_record_is_created_with_all_fields() {
+        let edit = record_edit(None, &json!({ "name": "Amal", "email": "amal@example.com" }));
+        assert_eq!(edit.base_version, 0);
+        assert_eq!(edit.changed_fields, vec!["email".to_string(), "name".to_string()]);
+    }
+}
@@ -0,0 +1,244 @@
+//! Local Schema Migrations - versioned upgrades of the offline SQLite store
+//!
+//! The store's schema version lives in SQLite's `PRAGMA user_version`. On
+//! open, every migration newer than it runs in order, each inside a
+//! savepoint together with the version bump, so a failed step leaves the
+//! store at the last good version. Steps only add tables and columns, so
+//! existing records survive an upgrade.
+
+use serde_json::Value;
+
+/// One schema upgrade step
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub statements: &'static [&'static str],
+}
+
+/// All migrations, oldest first. Append new steps; never edit shipped ones.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Entity records and pending operations",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS local_entity_records (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                entity_type TEXT NOT NULL,
+                data TEXT NOT NULL,
+                is_dirty INTEGER DEFAULT 0,
+                is_deleted INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                last_synced_at TEXT,
+                server_version INTEGER DEFAULT 0
+            )
+            "#,
+            // Tables created by the JS adapter before versioning lack it
+            "ALTER TABLE local_entity_records ADD COLUMN server_version INTEGER DEFAULT 0",
+            "CREATE INDEX IF NOT EXISTS idx_dirty ON local_entity_records(is_dirty)",
+            "CREATE INDEX IF NOT EXISTS idx_entity_type ON local_entity_records(entity_type, tenant_id)",
+            r#"
+            CREATE TABLE IF NOT EXISTS pending_operations (
+                id TEXT PRIMARY KEY,
+                operation_type TEXT NOT NULL,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                retry_count INTEGER DEFAULT 0
+            )
+            "#,
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "Offline edit tracking",
+        statements: &[
+            "ALTER TABLE local_entity_records ADD COLUMN base_version INTEGER DEFAULT 0",
+            "ALTER TABLE local_entity_records ADD COLUMN changed_fields TEXT DEFAULT '[]'",
+            "ALTER TABLE local_entity_records ADD COLUMN sync_attempts INTEGER DEFAULT 0",
+            "ALTER TABLE local_entity_records ADD COLUMN next_sync_at TEXT",
+            "ALTER TABLE local_entity_records ADD COLUMN base_data TEXT",
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "Failed operation details",
+        statements: &[
+            "ALTER TABLE pending_operations ADD COLUMN failed_at TEXT",
+            "ALTER TABLE pending_operations ADD COLUMN last_error TEXT",
+        ],
+    },
+];
+
+/// Tables owned by the migrations, dropped by a reset
+const TABLES: &[&str] = &["local_entity_records", "pending_operations"];
+
+/// Schema version the current build expects
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// SQL access needed to migrate a store
+pub trait SqlConnection {
+    fn execute(&self, sql: &str) -> Result<(), String>;
+    fn query(&self, sql: &str) -> Result<Vec<Value>, String>;
+}
+
+/// A migration step that failed; the store stays at the previous version
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationError {
+    pub version: u32,
+    pub description: &'static str,
+    pub error: String,
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "migration {} ({}) failed: {}", self.version, self.description, self.error)
+    }
+}
+
+/// Schema version of the store (0 for a new or pre-versioning store)
+pub fn user_version(conn: &impl SqlConnection) -> Result<u32, String> {
+    let rows = conn.query("PRAGMA user_version")?;
+    Ok(rows
+        .first()
+        .and_then(|r| r.get("user_version"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32)
+}
+
+/// Apply every migration newer than the store's version, returning the new version
+pub fn migrate(conn: &impl SqlConnection) -> Result<u32, MigrationError> {
+    let current = user_version(conn).map_err(|error| MigrationError { version: 0, description: "read version", error })?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let failed = |error: String| MigrationError {
+            version: migration.version,
+            description: migration.description,
+            error,
+        };
+
+        conn.execute("SAVEPOINT migration").map_err(failed)?;
+        let applied = migration
+            .statements
+            .iter()
+            .try_for_each(|sql| run_statement(conn, sql))
+            .and_then(|_| conn.execute(&format!("PRAGMA user_version = {}", migration.version)));
+
+        if let Err(error) = applied {
+            let _ = conn.execute("ROLLBACK TO migration");
+            let _ = conn.execute("RELEASE migration");
+            return Err(failed(error));
+        }
+        conn.execute("RELEASE migration").map_err(failed)?;
+    }
+
+    Ok(current.max(latest_version()))
+}
+
+/// Drop all local tables and rebuild them at the latest version
+///
+/// Loses local data, including changes not yet synced.
+pub fn reset(conn: &impl SqlConnection) -> Result<u32, MigrationError> {
+    let failed = |error: String| MigrationError { version: 0, description: "reset", error };
+    for table in TABLES {
+        conn.execute(&format!("DROP TABLE IF EXISTS {}", table)).map_err(failed)?;
+    }
+    conn.execute("PRAGMA user_version = 0").map_err(failed)?;
+    migrate(conn)
+}
+
+/// Run one statement; adding a column that already exists is not an error,
+/// since stores from before versioning may have some of them
+fn run_statement(conn: &impl SqlConnection, sql: &str) -> Result<(), String> {
+    match conn.execute(sql) {
+        Err(e) if sql.contains("ADD COLUMN") && e.contains("duplicate column name") => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::{Cell, RefCell};
+
+    /// Records statements and tracks `user_version`, including rollback
+    #[derive(Default)]
+    struct FakeStore {
+        version: Cell<u32>,
+        saved_version: Cell<u32>,
+        existing_columns: Vec<&'static str>,
+        failing: Option<&'static str>,
+        executed: RefCell<Vec<String>>,
+    }
+
+    impl SqlConnection for FakeStore {
+        fn execute(&self, sql: &str) -> Result<(), String> {
+            self.executed.borrow_mut().push(sql.trim().to_string());
+            if let Some(version) = sql.strip_prefix("PRAGMA user_version = ") {
+                self.version.set(version.parse().unwrap());
+            } else if sql == "SAVEPOINT migration" {
+                self.saved_version.set(self.version.get());
+            } else if sql == "ROLLBACK TO migration" {
+                self.version.set(self.saved_version.get());
+            } else if self.failing.is_some_and(|f| sql.contains(f)) {
+                return Err("disk I/O error".to_string());
+            } else if self.existing_columns.iter().any(|c| sql.contains(&format!("ADD COLUMN {} ", c))) {
+                return Err("duplicate column name".to_string());
+            }
+            Ok(())
+        }
+
+        fn query(&self, _sql: &str) -> Result<Vec<Value>, String> {
+            Ok(vec![json!({ "user_version": self.version.get() })])
+        }
+    }
+
+    #[test]
+    fn test_old_store_runs_newer_migrations_and_keeps_data() {
+        let store = FakeStore { version: Cell::new(1), ..Default::default() };
+
+        assert_eq!(migrate(&store), Ok(latest_version()));
+        assert_eq!(store.version.get(), latest_version());
+
+        let executed = store.executed.borrow();
+        assert!(executed.iter().all(|sql| !sql.contains("CREATE TABLE") && !sql.contains("DROP")));
+        let base_version = executed.iter().position(|sql| sql.contains("ADD COLUMN base_version")).unwrap();
+        let last_error = executed.iter().position(|sql| sql.contains("ADD COLUMN last_error")).unwrap();
+        assert!(base_version < last_error);
+        drop(executed);
+
+        // Opening again is a no-op
+        store.executed.borrow_mut().clear();
+        assert_eq!(migrate(&store), Ok(latest_version()));
+        assert!(store.executed.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_pre_versioning_store_with_some_columns_migrates() {
+        let store = FakeStore { existing_columns: vec!["server_version", "base_version"], ..Default::default() };
+
+        assert_eq!(migrate(&store), Ok(latest_version()));
+    }
+
+    #[test]
+    fn test_failed_step_rolls_back_to_last_good_version() {
+        let store = FakeStore { version: Cell::new(1), failing: Some("base_data"), ..Default::default() };
+
+        let err = migrate(&store).unwrap_err();
+        assert_eq!(err.version, 2);
+        assert_eq!(store.version.get(), 1);
+        assert!(store.executed.borrow().iter().any(|sql| sql == "ROLLBACK TO migration"));
+
+        // A reset rebuilds from scratch once the failure is gone
+        let store = FakeStore { version: Cell::new(1), failing: None, ..store };
+        assert_eq!(reset(&store), Ok(latest_version()));
+        assert!(store.executed.borrow().iter().any(|sql| sql == "DROP TABLE IF EXISTS local_entity_records"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Cursor Presence - remote users' cursors in a collaborative field
+//!
+//! Holds the non-DOM parts of cursor awareness: throttling our own cursor
+//! updates, and a store of remote cursors keyed by user that drops users
+//! who leave or stop sending updates.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::crdt::AwarenessState;
+
+/// Minimum time between cursor updates we send
+pub const CURSOR_THROTTLE_MS: f64 = 150.0;
+
+/// A remote cursor not updated for this long is removed
+pub const CURSOR_STALE_MS: f64 = 30_000.0;
+
+/// Colors assigned to collaborators
+const CURSOR_COLORS: &[&str] = &[
+    "#FF6B6B", "#4ECDC4", "#45B7D1", "#96CEB4",
+    "#FFEAA7", "#DDA0DD", "#98D8C8", "#F7DC6F",
+    "#BB8FCE", "#85C1E9", "#F8B500", "#00CED1",
+];
+
+/// Stable color for a user, so everyone sees the same one
+pub fn user_color(user_id: Uuid) -> &'static str {
+    let index = user_id.as_bytes().iter().fold(0usize, |acc, b| acc.wrapping_add(*b as usize));
+    CURSOR_COLORS[index % CURSOR_COLORS.len()]
+}
+
+/// Throttle for our cursor updates
+///
+/// The first position goes out at once; positions within the interval are
+/// held and the latest is sent when it ends, so the final cursor position
+/// is never lost.
+#[derive(Debug, Clone, Default)]
+pub struct CursorThrottle {
+    last_sent_ms: Option<f64>,
+    pending: Option<u32>,
+}
+
+impl CursorThrottle {
+    /// Offer a new cursor position; returns it if it may be sent now
+    pub fn offer(&mut self, position: u32, now_ms: f64) -> Option<u32> {
+        if self.last_sent_ms.is_some_and(|at| now_ms - at < CURSOR_THROTTLE_MS) {
+            self.pending = Some(position);
+            return None;
+        }
+        self.last_sent_ms = Some(now_ms);
+        self.pending = None;
+        Some(position)
+    }
+
+    /// The held position, once the interval since the last send has passed
+    pub fn flush(&mut self, now_ms: f64) -> Option<u32> {
+        let position = self.pending?;
+        self.offer(position, now_ms)
+    }
+
+    /// Time (ms) until a held position can be flushed
+    pub fn wait_ms(&self, now_ms: f64) -> Option<f64> {
+        self.pending?;
+        let at = self.last_sent_ms?;
+        Some((at + CURSOR_THROTTLE_MS - now_ms).max(0.0))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RemoteCursor {
+    state: AwarenessState,
+    last_seen_ms: f64,
+}
+
+/// Remote users' cursors, keyed by user id
+#[derive(Debug, Clone, Default)]
+pub struct RemoteCursors {
+    cursors: HashMap<Uuid, RemoteCursor>,
+}
+
+impl RemoteCursors {
+    /// Add or refresh a user's cursor
+    pub fn upsert(&mut self, state: AwarenessState, now_ms: f64) {
+        self.cursors.insert(state.user_id, RemoteCursor { state, last_seen_ms: now_ms });
+    }
+
+    /// Remove a user who left the document
+    pub fn remove(&mut self, user_id: Uuid) -> bool {
+        self.cursors.remove(&user_id).is_some()
+    }
+
+    /// Drop cursors not updated within `CURSOR_STALE_MS`, returning who was dropped
+    pub fn evict_stale(&mut self, now_ms: f64) -> Vec<Uuid> {
+        let stale: Vec<Uuid> = self
+            .cursors
+            .iter()
+            .filter(|(_, c)| now_ms - c.last_seen_ms > CURSOR_STALE_MS)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &stale {
+            self.cursors.remove(id);
+        }
+        stale
+    }
+
+    pub fn clear(&mut self) {
+        self.cursors.clear();
+    }
+
+    /// Current cursors, ordered by user name
+    pub fn states(&self) -> Vec<AwarenessState> {
+        let mut states: Vec<AwarenessState> = self.cursors.values().map(|c| c.state.clone()).collect();
+        states.sort_by(|a, b| a.user_name.cmp(&b.user_name));
+        states
+    }
+}
+
+/// Byte offset in `text` of a textarea position, which counts UTF-16 units
+pub fn utf16_to_byte_offset(text: &str, position: u32) -> usize {
+    let mut units = 0;
+    for (offset, c) in text.char_indices() {
+        if units >= position as usize {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(user_id: Uuid, name: &str, position: u32) -> AwarenessState {
+        AwarenessState::new(user_id, name, user_color(user_id)).with_cursor(position)
+    }
+
+    #[test]
+    fn test_cursor_updates_are_throttled() {
+        let mut throttle = CursorThrottle::default();
+
+        assert_eq!(throttle.offer(1, 0.0), Some(1));
+        assert_eq!(throttle.offer(2, 50.0), None);
+        assert_eq!(throttle.offer(3, 100.0), None);
+        assert_eq!(throttle.wait_ms(100.0), Some(CURSOR_THROTTLE_MS - 100.0));
+        assert_eq!(throttle.flush(120.0), None);
+
+        // The latest held position goes out once the interval has passed
+        assert_eq!(throttle.flush(CURSOR_THROTTLE_MS), Some(3));
+        assert_eq!(throttle.flush(CURSOR_THROTTLE_MS + 1.0), None);
+        assert_eq!(throttle.offer(4, 2.0 * CURSOR_THROTTLE_MS), Some(4));
+    }
+
+    #[test]
+    fn test_stale_cursors_are_evicted() {
+        let (amal, omar) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut cursors = RemoteCursors::default();
+        cursors.upsert(cursor(amal, "Amal", 3), 0.0);
+        cursors.upsert(cursor(omar, "Omar", 8), 0.0);
+
+        // Omar keeps typing; Amal goes quiet
+        cursors.upsert(cursor(omar, "Omar", 12), 20_000.0);
+        assert!(cursors.evict_stale(CURSOR_STALE_MS).is_empty());
+        assert_eq!(cursors.evict_stale(CURSOR_STALE_MS + 1.0), vec![amal]);
+
+        let states = cursors.states();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].cursor_position, Some(12));
+
+        assert!(cursors.remove(omar));
+        assert!(cursors.states().is_empty());
+    }
+
+    #[test]
+    fn test_textarea_positions_map_to_byte_offsets() {
+        assert_eq!(utf16_to_byte_offset("abc", 2), 2);
+        assert_eq!(utf16_to_byte_offset("مرحبا", 2), 4);
+        assert_eq!(utf16_to_byte_offset("ab", 10), 2);
+    }
+}
@@ -3,12 +3,19 @@
 //! Handles bidirectional sync between local SQLite and remote API.
 //! Features:
 //! - Queue-based dirty record pushing
-//! - Conflict detection via server_version (CQRS aggregate_version)
-//! - Last-Write-Wins (LWW) conflict resolution
+//! - Conflict detection: edits are sent with the version they were made
+//!   from, and the server answers 409 with its current record
+//! - Per-field merge on conflict, following each field's `physics`:
+//!   `TextMerge` text is merged through the CRDT, other fields are
+//!   Last-Write-Wins (LWW) or left for manual resolution
 //! - Exponential backoff retry
 //! - Online status detection
 
-use super::db::{LocalDatabase, DirtyRecord};
+use super::crdt::merge_text;
+use super::db::{LocalDatabase, DirtyRecord, VERSION_KEY};
+use core_models::{FieldDef, MergeStrategy};
+use std::collections::HashMap;
+use crate::components::conflict_resolver::{ConflictData, ResolutionChoice, ResolutionResult};
 use uuid::Uuid;
 use gloo_console;
 use crate::api::get_api_base;
@@ -20,19 +27,156 @@ use gloo_net::http::Request;
 pub enum SyncResult {
     Success { synced_count: usize },
     PartialSuccess { synced: usize, failed: usize },
-    Conflict { record_id: String, server_version: u64 },
+    /// Records the user has to resolve; everything else was pushed
+    Conflict { synced: usize, conflicts: Vec<ConflictResolution> },
     Offline,
     Error(String),
 }
 
 /// Conflict resolution strategy
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub enum ConflictResolution {
     #[default]
     LastWriteWins,
     ServerWins,
     ClientWins,
     Manual,
+    /// A conflict left for the user, with both versions for the `ConflictResolver`
+    ManualRequired(Box<ConflictData>),
+}
+
+/// First retry delay after a failed push; doubles per failure
+const RETRY_BASE_SECS: i64 = 5;
+
+/// Longest wait between retries
+const RETRY_MAX_SECS: i64 = 600;
+
+/// Wait before retrying a record that failed `attempts` times in a row
+pub fn retry_delay(attempts: u32) -> chrono::Duration {
+    let secs = RETRY_BASE_SECS.saturating_mul(1i64 << attempts.saturating_sub(1).min(16));
+    chrono::Duration::seconds(secs.min(RETRY_MAX_SECS))
+}
+
+/// Both sides of a rejected push, as shown by the conflict resolver
+pub fn conflict_data(record: &DirtyRecord, current: &serde_json::Value) -> ConflictData {
+    let server_version = current.get(VERSION_KEY).and_then(|v| v.as_u64()).unwrap_or(0);
+    let mut server_data = current.clone();
+    if let Some(fields) = server_data.as_object_mut() {
+        fields.remove(VERSION_KEY);
+    }
+    let updated_at = |data: &serde_json::Value| {
+        data.get("updated_at").and_then(|v| v.as_str()).unwrap_or_default().to_string()
+    };
+
+    ConflictData {
+        entity_id: Uuid::parse_str(&record.id).unwrap_or_default(),
+        entity_type: record.entity_type.clone(),
+        field: None,
+        local_updated_at: updated_at(&record.data),
+        server_updated_at: updated_at(&server_data),
+        local_data: record.data.clone(),
+        local_version: record.base_version,
+        server_data,
+        server_version,
+    }
+}
+
+/// What to store locally once the user has resolved a conflict
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolutionPlan {
+    /// Take the server's record as it is
+    AcceptServer { data: serde_json::Value, version: u64 },
+    /// Keep `data` as a local edit of the server's version, pushing the
+    /// fields that differ from it on the next sync
+    Rebase { data: serde_json::Value, version: u64, changed_fields: Vec<String> },
+}
+
+/// Plan for applying the user's choice; `None` leaves the conflict pending
+pub fn resolution_plan(conflict: &ConflictData, result: &ResolutionResult) -> Option<ResolutionPlan> {
+    let rebase = |data: &serde_json::Value| {
+        let empty = serde_json::Map::new();
+        let (ours, theirs) = (data.as_object().unwrap_or(&empty), conflict.server_data.as_object().unwrap_or(&empty));
+        let mut changed_fields: Vec<String> = ours
+            .keys()
+            .filter(|key| key.as_str() != "updated_at" && ours.get(*key) != theirs.get(*key))
+            .cloned()
+            .collect();
+        changed_fields.sort();
+        ResolutionPlan::Rebase { data: data.clone(), version: conflict.server_version, changed_fields }
+    };
+
+    match result.choice {
+        ResolutionChoice::KeepTheirs => Some(ResolutionPlan::AcceptServer {
+            data: conflict.server_data.clone(),
+            version: conflict.server_version,
+        }),
+        ResolutionChoice::KeepMine => Some(rebase(&conflict.local_data)),
+        ResolutionChoice::Merge => Some(rebase(result.merged_data.as_ref().unwrap_or(&conflict.local_data))),
+        ResolutionChoice::Cancel => None,
+    }
+}
+
+/// Outcome of merging a rejected edit into the server's current record
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMerge {
+    /// Server record with the local changes applied
+    pub data: serde_json::Value,
+    /// Fields where `data` differs from the server record
+    pub changed_fields: Vec<String>,
+    /// Last-write-wins fields both sides changed; `data` holds the local value
+    pub conflicts: Vec<String>,
+}
+
+/// Merge the fields `record` changed into the server's `current` data
+///
+/// A field only one side changed takes that side's value. A field both
+/// changed is merged when its strategy is `TextMerge`, and is a conflict
+/// otherwise (or when the record's base copy is unknown).
+pub fn merge_fields(
+    record: &DirtyRecord,
+    current: &serde_json::Value,
+    strategies: Option<&HashMap<String, MergeStrategy>>,
+) -> FieldMerge {
+    let mut data = current.as_object().cloned().unwrap_or_default();
+    let fields: Vec<String> = if record.changed_fields.is_empty() {
+        record.data.as_object().map(|o| o.keys().cloned().collect()).unwrap_or_default()
+    } else {
+        record.changed_fields.clone()
+    };
+
+    let mut changed_fields = Vec::new();
+    let mut conflicts = Vec::new();
+    for field in fields {
+        let ours = record.data.get(&field).cloned().unwrap_or(serde_json::Value::Null);
+        let theirs = current.get(&field).cloned().unwrap_or(serde_json::Value::Null);
+        if ours == theirs {
+            continue;
+        }
+        let base = record.base_data.as_object().map(|o| o.get(&field).cloned().unwrap_or_default());
+        let strategy = strategies.and_then(|s| s.get(&field)).cloned().unwrap_or_default();
+
+        let value = match (base, strategy) {
+            // The server did not touch the field
+            (Some(base), _) if base == theirs => ours,
+            (Some(serde_json::Value::String(base)), MergeStrategy::TextMerge) => match (ours.as_str(), theirs.as_str()) {
+                (Some(ours), Some(theirs)) => serde_json::json!(merge_text(&base, ours, theirs)),
+                _ => {
+                    conflicts.push(field.clone());
+                    ours
+                }
+            },
+            _ => {
+                conflicts.push(field.clone());
+                ours
+            }
+        };
+        if value != theirs {
+            changed_fields.push(field.clone());
+        }
+        data.insert(field, value);
+    }
+
+    FieldMerge { data: serde_json::Value::Object(data), changed_fields, conflicts }
 }
 
 /// Sync Manager for offline-first data synchronization
@@ -40,6 +184,8 @@ pub enum ConflictResolution {
 pub struct SyncManager {
     local_db: LocalDatabase,
     conflict_resolution: ConflictResolution,
+    /// Merge strategy per entity type and field, from field `physics`
+    field_strategies: HashMap<String, HashMap<String, MergeStrategy>>,
 }
 
 impl SyncManager {
@@ -48,6 +194,7 @@ impl SyncManager {
         Self { 
             local_db,
             conflict_resolution: ConflictResolution::default(),
+            field_strategies: HashMap::new(),
         }
     }
 
@@ -56,9 +203,17 @@ impl SyncManager {
         Self {
             local_db,
             conflict_resolution: resolution,
+            field_strategies: HashMap::new(),
         }
     }
 
+    /// Use the `physics` of an entity type's fields when merging conflicts
+    pub fn with_field_physics(mut self, entity_type: &str, fields: &[FieldDef]) -> Self {
+        let strategies = fields.iter().map(|f| (f.name.clone(), f.physics.clone())).collect();
+        self.field_strategies.insert(entity_type.to_string(), strategies);
+        self
+    }
+
     /// Check if we're online
     pub fn is_online() -> bool {
         web_sys::window()
@@ -144,16 +299,21 @@ impl SyncManager {
         Ok(count)
     }
 
-    /// Push all dirty records to server
-    pub async fn push_changes(&self, tenant_id: &Uuid) -> SyncResult {
+    /// Push all dirty records that are due to the server
+    ///
+    /// Each edit goes out with the version it was made from. A conflict is
+    /// settled by the configured strategy, or returned for the user under
+    /// `Manual`; failed pushes stay dirty and back off before the next try.
+    pub async fn sync(&self, tenant_id: &Uuid) -> SyncResult {
         if !Self::is_online() {
             gloo_console::warn!("Offline - changes queued for later sync");
             return SyncResult::Offline;
         }
 
         // Get dirty records
-        let dirty_records = match self.local_db.get_dirty_records() {
-            Ok(records) => records,
+        let now = chrono::Utc::now();
+        let dirty_records: Vec<DirtyRecord> = match self.local_db.get_dirty_records() {
+            Ok(records) => records.into_iter().filter(|r| r.is_due(now)).collect(),
             Err(e) => return SyncResult::Error(e),
         };
 
@@ -165,99 +325,150 @@ impl SyncManager {
 
         let mut synced = 0;
         let mut failed = 0;
+        let mut conflicts = Vec::new();
 
         for record in dirty_records {
-            match self.push_single_record(&record, tenant_id).await {
-                Ok(new_version) => {
-                    // Mark as synced
-                    if let Ok(id) = Uuid::parse_str(&record.id) {
-                        let _ = self.local_db.mark_synced(&id, new_version);
+            let Ok(id) = Uuid::parse_str(&record.id) else {
+                failed += 1;
+                continue;
+            };
+
+            let mut outcome = self.push_single_record(&record, tenant_id, record.base_version).await;
+            if let Err(PushError::Conflict { current }) = &outcome {
+                let mut conflict = conflict_data(&record, current);
+                let merge = merge_fields(&record, &conflict.server_data, self.field_strategies.get(&record.entity_type));
+                match &self.conflict_resolution {
+                    _ if merge.conflicts.is_empty() => {
+                        // Merged cleanly - re-send against the server's version
+                        outcome = self.push_merged(&record, &id, tenant_id, &conflict, merge).await;
                     }
-                    synced += 1;
-                }
-                Err(PushError::Conflict { server_version }) => {
-                    // Handle conflict based on strategy
-                    match self.conflict_resolution {
-                        ConflictResolution::LastWriteWins | ConflictResolution::ClientWins => {
-                            // Force push with server version
-                            if let Ok(_) = self.force_push_record(&record, tenant_id, server_version).await {
-                                synced += 1;
-                            } else {
-                                failed += 1;
-                            }
-                        }
-                        ConflictResolution::ServerWins => {
-                            // Discard local changes, pull server version
-                            if let Ok(id) = Uuid::parse_str(&record.id) {
-                                let _ = self.local_db.mark_synced(&id, server_version);
-                            }
-                            synced += 1;
-                        }
-                        ConflictResolution::Manual => {
-                            // Return conflict for user resolution
-                            return SyncResult::Conflict {
-                                record_id: record.id,
-                                server_version,
-                            };
+                    ConflictResolution::LastWriteWins | ConflictResolution::ClientWins => {
+                        // Local values win the conflicting fields; text still merges
+                        outcome = self.push_merged(&record, &id, tenant_id, &conflict, merge).await;
+                    }
+                    ConflictResolution::ServerWins => {
+                        // Discard local changes, keep the server version
+                        outcome = self
+                            .apply_plan(&record.entity_type, &id, tenant_id, &conflict.server_data, ResolutionPlan::AcceptServer {
+                                data: conflict.server_data.clone(),
+                                version: conflict.server_version,
+                            })
+                            .map(|_| conflict.server_version)
+                            .map_err(PushError::Server);
+                    }
+                    ConflictResolution::Manual | ConflictResolution::ManualRequired(_) => {
+                        // Stays dirty until the user picks a version
+                        if let [field] = merge.conflicts.as_slice() {
+                            conflict.field = Some(field.clone());
                         }
+                        conflicts.push(ConflictResolution::ManualRequired(Box::new(conflict)));
+                        continue;
                     }
                 }
-                Err(PushError::Network(e)) => {
-                    gloo_console::error!("Network error:", &e);
-                    failed += 1;
+            }
+
+            match outcome {
+                Ok(new_version) => {
+                    let _ = self.local_db.mark_synced(&id, new_version);
+                    synced += 1;
                 }
-                Err(PushError::Server(e)) => {
-                    gloo_console::error!("Server error:", &e);
+                Err(e) => {
+                    let attempts = record.sync_attempts + 1;
+                    let next_sync_at = (now + retry_delay(attempts)).to_rfc3339();
+                    gloo_console::error!("Sync failed for", &record.id, ":", e.to_string(), "- retrying at", &next_sync_at);
+                    let _ = self.local_db.record_sync_failure(&id, attempts, &next_sync_at);
                     failed += 1;
                 }
             }
         }
 
-        if failed == 0 {
+        if !conflicts.is_empty() {
+            SyncResult::Conflict { synced, conflicts }
+        } else if failed == 0 {
             SyncResult::Success { synced_count: synced }
         } else {
             SyncResult::PartialSuccess { synced, failed }
         }
     }
 
-    /// Push a single record to server
-    async fn push_single_record(&self, record: &DirtyRecord, tenant_id: &Uuid) -> Result<u64, PushError> {
-        let url = if record.is_deleted {
-            format!("{}/entities/{}/{}", get_api_base(), record.entity_type, record.id)
-        } else {
-            format!("{}/entities/{}", get_api_base(), record.entity_type)
-        };
-
-        let method = if record.is_deleted {
-            "DELETE"
-        } else if record.server_version == 0 {
-            "POST" // New record
-        } else {
-            "PUT" // Update
-        };
+    /// Store the user's answer to a `ManualRequired` conflict
+    ///
+    /// Keeping the server's record clears the local edit; keeping or merging
+    /// local changes re-bases them on the server version for the next sync.
+    pub fn resolve_conflict(
+        &self,
+        conflict: &ConflictData,
+        result: &ResolutionResult,
+        tenant_id: &Uuid,
+    ) -> Result<(), String> {
+        match resolution_plan(conflict, result) {
+            Some(plan) => {
+                self.apply_plan(&conflict.entity_type, &conflict.entity_id, tenant_id, &conflict.server_data, plan)
+            }
+            None => Ok(()),
+        }
+    }
 
-        // Build request body with version for optimistic locking
-        let body = if !record.is_deleted {
-            let mut data = record.data.clone();
-            if let Some(obj) = data.as_object_mut() {
-                obj.insert("id".to_string(), serde_json::json!(record.id));
-                obj.insert("expected_version".to_string(), serde_json::json!(record.server_version));
+    /// Store `plan` locally; `server_data` becomes the base of a re-based edit
+    fn apply_plan(
+        &self,
+        entity_type: &str,
+        id: &Uuid,
+        tenant_id: &Uuid,
+        server_data: &serde_json::Value,
+        plan: ResolutionPlan,
+    ) -> Result<(), String> {
+        match plan {
+            ResolutionPlan::AcceptServer { data, version } => {
+                self.local_db.save_record(entity_type, id, tenant_id, &data)?;
+                self.local_db.mark_synced(id, version)
             }
-            Some(serde_json::to_string(&data).map_err(|e| PushError::Network(e.to_string()))?)
-        } else {
-            None
-        };
+            ResolutionPlan::Rebase { data, version, changed_fields } => {
+                self.local_db.save_record(entity_type, id, tenant_id, &data)?;
+                self.local_db.rebase_record(id, version, server_data, &changed_fields)
+            }
+        }
+    }
 
-        let url_with_id = match method {
-            "PUT" | "DELETE" => format!("{}/{}", url, record.id),
-            _ => url.clone(),
+    /// Store a merged record as an edit of the server's version and push it
+    async fn push_merged(
+        &self,
+        record: &DirtyRecord,
+        id: &Uuid,
+        tenant_id: &Uuid,
+        conflict: &ConflictData,
+        merge: FieldMerge,
+    ) -> Result<u64, PushError> {
+        let plan = ResolutionPlan::Rebase {
+            data: merge.data.clone(),
+            version: conflict.server_version,
+            changed_fields: merge.changed_fields.clone(),
         };
-        
-        let request_builder = match method {
-            "POST" => Request::post(&url),
-            "PUT" => Request::put(&url_with_id),
-            "DELETE" => Request::delete(&url_with_id),
-            _ => Request::post(&url),
+        self.apply_plan(&record.entity_type, id, tenant_id, &conflict.server_data, plan)
+            .map_err(PushError::Server)?;
+
+        let rebased = DirtyRecord {
+            data: merge.data,
+            server_version: conflict.server_version,
+            base_version: conflict.server_version,
+            changed_fields: merge.changed_fields,
+            base_data: conflict.server_data.clone(),
+            ..record.clone()
+        };
+        self.push_single_record(&rebased, tenant_id, conflict.server_version).await
+    }
+
+    /// Push a single record, as an edit of `expected_version`
+    async fn push_single_record(&self, record: &DirtyRecord, tenant_id: &Uuid, expected_version: u64) -> Result<u64, PushError> {
+        let collection = format!("{}/entities/{}", get_api_base(), record.entity_type);
+        let item = format!("{}/{}", collection, record.id);
+
+        let request_builder = if record.is_deleted {
+            Request::delete(&item)
+        } else if record.is_new() {
+            Request::post(&collection)
+        } else {
+            Request::put(&item)
         };
 
         let request_builder = request_builder
@@ -266,8 +477,22 @@ impl SyncManager {
             .header("X-Tenant-Slug", "demo")
             .header("X-Request-Id", &Uuid::new_v4().to_string());
 
+        // New records send everything; updates only the changed fields
+        let body = if record.is_deleted {
+            None
+        } else if record.is_new() {
+            let mut data = record.data.clone();
+            if let Some(obj) = data.as_object_mut() {
+                obj.insert("id".to_string(), serde_json::json!(record.id));
+            }
+            Some(data)
+        } else {
+            Some(record.patch(expected_version))
+        };
+
         // Build the final request - with or without body
         let resp = if let Some(body) = body {
+            let body = serde_json::to_string(&body).map_err(|e| PushError::Network(e.to_string()))?;
             request_builder
                 .body(body)
                 .map_err(|e| PushError::Network(format!("{:?}", e)))?
@@ -286,23 +511,20 @@ impl SyncManager {
             200 | 201 => {
                 // Success - get new version from response
                 let json: serde_json::Value = resp.json().await.unwrap_or(serde_json::json!({}));
-                let new_version = json.get("aggregate_version")
-                    .or_else(|| json.get("version"))
+                let new_version = json.get("version")
+                    .or_else(|| json.get("aggregate_version"))
                     .and_then(|v| v.as_u64())
-                    .unwrap_or(record.server_version + 1);
+                    .unwrap_or(expected_version + 1);
                 Ok(new_version)
             }
             204 => {
                 // Deleted successfully
-                Ok(record.server_version)
+                Ok(expected_version)
             }
             409 => {
-                // Conflict - get server version
+                // Conflict - the server sends its current record
                 let json: serde_json::Value = resp.json().await.unwrap_or(serde_json::json!({}));
-                let server_version = json.get("current_version")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(record.server_version + 1);
-                Err(PushError::Conflict { server_version })
+                Err(PushError::Conflict { current: json.get("current").cloned().unwrap_or_default() })
             }
             status => {
                 let text = resp.text().await.unwrap_or_default();
@@ -311,36 +533,10 @@ impl SyncManager {
         }
     }
 
-    /// Force push a record, overwriting server version
-    async fn force_push_record(&self, record: &DirtyRecord, tenant_id: &Uuid, _server_version: u64) -> Result<u64, PushError> {
-        // In LWW mode, we just push again without version check
-        let url = format!("{}/entities/{}/{}", get_api_base(), record.entity_type, record.id);
-        
-        let body = serde_json::to_string(&record.data).map_err(|e| PushError::Network(e.to_string()))?;
-
-        let resp = Request::put(&url)
-            .header("Content-Type", "application/json")
-            .header("X-Tenant-Id", &tenant_id.to_string())
-            .header("X-Tenant-Slug", "demo")
-            .header("X-Force-Overwrite", "true")
-            .body(body)
-            .map_err(|e| PushError::Network(format!("{:?}", e)))?
-            .send()
-            .await
-            .map_err(|e| PushError::Network(e.to_string()))?;
-
-        if resp.ok() {
-            let json: serde_json::Value = resp.json().await.unwrap_or(serde_json::json!({}));
-            Ok(json.get("aggregate_version").and_then(|v| v.as_u64()).unwrap_or(1))
-        } else {
-            Err(PushError::Server(format!("Force push failed: {}", resp.status())))
-        }
-    }
-
-    /// Sync all - pull then push
+    /// Sync all - push then pull
     pub async fn sync_all(&self, tenant_id: &Uuid, entity_types: &[&str]) -> Result<SyncResult, String> {
         // First push local changes
-        let push_result = self.push_changes(tenant_id).await;
+        let push_result = self.sync(tenant_id).await;
         
         // Then pull updates for each entity type
         for entity_type in entity_types {
@@ -356,11 +552,22 @@ impl SyncManager {
 /// Errors that can occur during push
 #[derive(Debug)]
 enum PushError {
-    Conflict { server_version: u64 },
+    /// Holds the server's current record, including its `_version`
+    Conflict { current: serde_json::Value },
     Network(String),
     Server(String),
 }
 
+impl std::fmt::Display for PushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PushError::Conflict { .. } => write!(f, "version conflict"),
+            PushError::Network(e) => write!(f, "network error: {}", e),
+            PushError::Server(e) => write!(f, "server error: {}", e),
+        }
+    }
+}
+
 // ============================================================================
 // OPTIMISTIC UI SUPPORT
 // ============================================================================
@@ -550,15 +757,20 @@ impl SyncManager {
             data: operation.new_data.clone(),
             server_version: 0, // Will be set by server
             is_deleted: matches!(operation.operation, OptimisticOpType::Delete),
+            base_version: 0,
+            changed_fields: Vec::new(),
+            base_data: serde_json::Value::Null,
+            sync_attempts: 0,
+            next_sync_at: None,
         };
         
-        match self.push_single_record(&record, tenant_id).await {
+        match self.push_single_record(&record, tenant_id, 0).await {
             Ok(new_version) => {
                 // Update local version
                 let _ = self.local_db.mark_synced(&operation.entity_id, new_version);
                 OptimisticResult::Success { new_version }
             }
-            Err(PushError::Conflict { server_version: _ }) => {
+            Err(PushError::Conflict { .. }) => {
                 // Conflict - caller should handle rollback or merge
                 OptimisticResult::Failed { error: "Version conflict".to_string() }
             }
@@ -575,3 +787,134 @@ impl SyncManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edited_contact() -> DirtyRecord {
+        DirtyRecord {
+            id: Uuid::new_v4().to_string(),
+            tenant_id: Uuid::new_v4().to_string(),
+            entity_type: "contact".to_string(),
+            data: json!({ "name": "Sara", "phone": "+971 50 111", "updated_at": "2026-10-01T09:00:00Z" }),
+            is_deleted: false,
+            server_version: 3,
+            base_version: 3,
+            changed_fields: vec!["phone".to_string()],
+            base_data: json!({ "name": "Sara", "phone": "+971 50 000", "updated_at": "2026-09-30T09:00:00Z" }),
+            sync_attempts: 0,
+            next_sync_at: None,
+        }
+    }
+
+    #[test]
+    fn test_conflict_carries_both_versions() {
+        let record = edited_contact();
+        let current = json!({
+            "id": record.id,
+            "name": "Sara K.",
+            "phone": "+971 50 999",
+            "updated_at": "2026-10-01T10:00:00Z",
+            "_version": 5
+        });
+
+        let conflict = conflict_data(&record, &current);
+
+        assert_eq!(conflict.local_version, 3);
+        assert_eq!(conflict.server_version, 5);
+        assert_eq!(conflict.local_data["phone"], "+971 50 111");
+        assert_eq!(conflict.server_data["phone"], "+971 50 999");
+        assert!(conflict.server_data.get(VERSION_KEY).is_none());
+        assert_eq!(conflict.server_updated_at, "2026-10-01T10:00:00Z");
+    }
+
+    #[test]
+    fn test_resolution_rebases_local_changes_on_server_version() {
+        let record = edited_contact();
+        let current = json!({ "name": "Sara K.", "phone": "+971 50 999", "_version": 5 });
+        let conflict = conflict_data(&record, &current);
+        let resolve = |choice, merged_data| ResolutionResult { entity_id: conflict.entity_id, choice, merged_data };
+
+        assert_eq!(
+            resolution_plan(&conflict, &resolve(ResolutionChoice::KeepMine, None)),
+            Some(ResolutionPlan::Rebase {
+                data: record.data.clone(),
+                version: 5,
+                changed_fields: vec!["name".to_string(), "phone".to_string()],
+            })
+        );
+
+        let merged = json!({ "name": "Sara K.", "phone": "+971 50 111" });
+        assert_eq!(
+            resolution_plan(&conflict, &resolve(ResolutionChoice::Merge, Some(merged.clone()))),
+            Some(ResolutionPlan::Rebase { data: merged, version: 5, changed_fields: vec!["phone".to_string()] })
+        );
+
+        assert!(matches!(
+            resolution_plan(&conflict, &resolve(ResolutionChoice::KeepTheirs, None)),
+            Some(ResolutionPlan::AcceptServer { version: 5, .. })
+        ));
+        assert_eq!(resolution_plan(&conflict, &resolve(ResolutionChoice::Cancel, None)), None);
+    }
+
+    fn deal_strategies() -> HashMap<String, MergeStrategy> {
+        HashMap::from([
+            ("notes".to_string(), MergeStrategy::TextMerge),
+            ("amount".to_string(), MergeStrategy::LastWriteWins),
+        ])
+    }
+
+    fn edited_deal(data: serde_json::Value, changed_fields: &[&str]) -> DirtyRecord {
+        DirtyRecord {
+            entity_type: "deal".to_string(),
+            data,
+            changed_fields: changed_fields.iter().map(|f| f.to_string()).collect(),
+            base_data: json!({ "notes": "Client wants a sea view.", "amount": 100, "stage": "lead" }),
+            ..edited_contact()
+        }
+    }
+
+    #[test]
+    fn test_concurrent_text_merge_edit_merges() {
+        let record = edited_deal(json!({ "notes": "Client wants a sea view. Budget flexible.", "amount": 100, "stage": "lead" }), &["notes"]);
+        let current = json!({ "notes": "VIP: Client wants a sea view.", "amount": 100, "stage": "viewing" });
+
+        let merge = merge_fields(&record, &current, Some(&deal_strategies()));
+
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.data["notes"], "VIP: Client wants a sea view. Budget flexible.");
+        assert_eq!(merge.data["stage"], "viewing");
+        assert_eq!(merge.changed_fields, vec!["notes".to_string()]);
+    }
+
+    #[test]
+    fn test_concurrent_lww_edit_conflicts_in_mixed_record() {
+        let record = edited_deal(
+            json!({ "notes": "Client wants a sea view. Budget flexible.", "amount": 150, "stage": "lead" }),
+            &["amount", "notes"],
+        );
+        let current = json!({ "notes": "VIP: Client wants a sea view.", "amount": 120, "stage": "lead" });
+
+        let merge = merge_fields(&record, &current, Some(&deal_strategies()));
+
+        assert_eq!(merge.conflicts, vec!["amount".to_string()]);
+        assert_eq!(merge.data["amount"], 150);
+        assert_eq!(merge.data["notes"], "VIP: Client wants a sea view. Budget flexible.");
+        assert_eq!(merge.changed_fields, vec!["amount".to_string(), "notes".to_string()]);
+
+        // An LWW field only we changed goes through
+        let record = edited_deal(json!({ "notes": "Client wants a sea view.", "amount": 150, "stage": "lead" }), &["amount"]);
+        let current = json!({ "notes": "Client wants a sea view.", "amount": 100, "stage": "viewing" });
+        assert!(merge_fields(&record, &current, Some(&deal_strategies())).conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_up_to_cap() {
+        assert_eq!(retry_delay(1).num_seconds(), 5);
+        assert_eq!(retry_delay(2).num_seconds(), 10);
+        assert_eq!(retry_delay(4).num_seconds(), 40);
+        assert_eq!(retry_delay(30).num_seconds(), 600);
+    }
+}
@@ -1,12 +1,16 @@
 //! Cinematic Entity Detail - Full Feature Edition
 //! Metadata driven + Glassmorphism + Activity (Composer + Timeline) + Audit Timeline + Tabs
+//! Field edits autosave after a pause; conflicting saves prompt through the ConflictResolver
 
 use leptos::*;
 use leptos_router::*;
 use uuid::Uuid;
 use crate::api::{
-    fetch_field_defs, fetch_entity, update_entity, FieldDef
+    fetch_field_defs, fetch_entity, patch_entity, update_entity, FieldDef, SaveError
 };
+use crate::components::conflict_resolver::{ConflictData, ConflictResolver, ResolutionChoice, ResolutionResult};
+use crate::core::autosave::{AutosaveQueue, SaveState, AUTOSAVE_DEBOUNCE_MS};
+use gloo_timers::future::TimeoutFuture;
 use crate::design_system::inputs::smart_field::SmartField;
 use crate::components::audit_timeline::AuditTimeline;
 use crate::components::timeline::{UnifiedComposer, UnifiedTimeline};
@@ -20,6 +24,8 @@ pub fn EntityDetailPage() -> impl IntoView {
     // Signals
     let (fields, set_fields) = create_signal(Vec::<FieldDef>::new());
     let (record, set_record) = create_signal(serde_json::Value::Null);
+    // Version the record was read at; sent with every update (optimistic concurrency)
+    let version = create_rw_signal(serde_json::Value::Null);
     let (loading, set_loading) = create_signal(true);
     let (error, set_error) = create_signal(Option::<String>::None);
     
@@ -29,6 +35,17 @@ pub fn EntityDetailPage() -> impl IntoView {
     // Activity reload trigger
     let reload_activity = create_rw_signal(0u32);
     
+    // Autosave
+    let autosave = Autosave {
+        queue: store_value(AutosaveQueue::default()),
+        controller: store_value(None),
+        state: create_rw_signal(SaveState::Idle),
+        conflict: create_rw_signal(None),
+        record,
+        set_record,
+        version,
+    };
+    
     // Load Data
     let entity_for_load = entity_type.clone();
     let id_for_load = record_id.clone();
@@ -49,7 +66,10 @@ pub fn EntityDetailPage() -> impl IntoView {
             
             // Fetch Record
             match fetch_entity(&etype, &id).await {
-                Ok(r) => set_record.set(r),
+                Ok(r) => {
+                    version.set(r.get("_version").cloned().unwrap_or_default());
+                    set_record.set(r);
+                }
                 Err(e) => set_error.set(Some(e)),
             }
             
@@ -94,7 +114,17 @@ pub fn EntityDetailPage() -> impl IntoView {
                     </div>
                     
                     // Action buttons
-                    <div class="flex gap-2">
+                    <div class="flex gap-2 items-center">
+                        <span class="text-xs text-zinc-400 mr-2">
+                            {move || match autosave.state.get() {
+                                SaveState::Idle => "",
+                                SaveState::Pending => "Unsaved changes",
+                                SaveState::Saving => "Saving...",
+                                SaveState::Saved => "All changes saved",
+                                SaveState::Conflict => "Changed by someone else",
+                                SaveState::Error => "Save failed",
+                            }}
+                        </span>
                         <button class="ui-btn ui-btn-secondary">
                             <i class="fa-solid fa-share-nodes mr-2"></i>
                             "Share"
@@ -147,6 +177,22 @@ pub fn EntityDetailPage() -> impl IntoView {
                 </button>
             </div>
             
+            // Prompt when an autosave hit a newer server version
+            {move || autosave.conflict.get().map(|conflict| {
+                let etype = entity_type();
+                let id = record_id();
+                view! {
+                    <ConflictResolver
+                        conflict=conflict.clone()
+                        on_resolve=Callback::new(move |result: ResolutionResult| autosave.resolve(&etype, &id, &conflict, result))
+                        on_cancel=Callback::new(move |_| {
+                            autosave.conflict.set(None);
+                            autosave.state.set(SaveState::Pending);
+                        })
+                    />
+                }
+            })}
+            
             // Tab Content
             <div class="flex-1">
                 {move || match active_tab.get().as_str() {
@@ -170,13 +216,7 @@ pub fn EntityDetailPage() -> impl IntoView {
                                             let fname = field.name.clone();
                                             
                                             let on_change = Callback::new(move |new_val: serde_json::Value| {
-                                                let etype = etype.clone();
-                                                let id = id.clone();
-                                                let fname = fname.clone();
-                                                spawn_local(async move {
-                                                    let body = serde_json::json!({ fname: new_val });
-                                                    let _ = update_entity(&etype, &id, body).await;
-                                                });
+                                                autosave.edit(&etype, &id, &fname, new_val);
                                             });
 
                                             view! {
@@ -316,6 +356,143 @@ pub fn EntityDetailPage() -> impl IntoView {
     }
 }
 
+/// Autosave wiring for the page: edits are queued, saved after a pause,
+/// and a 409 opens the conflict prompt
+#[derive(Clone, Copy)]
+struct Autosave {
+    queue: StoredValue<AutosaveQueue>,
+    /// Aborts the save in flight
+    controller: StoredValue<Option<web_sys::AbortController>>,
+    state: RwSignal<SaveState>,
+    conflict: RwSignal<Option<ConflictData>>,
+    record: ReadSignal<serde_json::Value>,
+    set_record: WriteSignal<serde_json::Value>,
+    version: RwSignal<serde_json::Value>,
+}
+
+impl Autosave {
+    /// Queue an edit and save once the user pauses
+    fn edit(self, entity_type: &str, id: &str, field: &str, value: serde_json::Value) {
+        let Some(edit) = self.queue.try_update_value(|q| q.edit(field, value)) else { return };
+        if edit.abort.is_some() {
+            self.controller.with_value(|c| c.as_ref().map(|c| c.abort()));
+        }
+        self.state.set(SaveState::Pending);
+
+        let (entity_type, id) = (entity_type.to_string(), id.to_string());
+        spawn_local(async move {
+            TimeoutFuture::new(AUTOSAVE_DEBOUNCE_MS).await;
+            self.save(&entity_type, &id, edit.generation).await;
+        });
+    }
+
+    /// Send the queued fields if `generation` is still the latest edit
+    async fn save(self, entity_type: &str, id: &str, generation: u64) {
+        let Some(Some(save)) = self.queue.try_update_value(|q| q.take(generation)) else { return };
+        let controller = web_sys::AbortController::new().ok();
+        self.controller.set_value(controller.clone());
+        self.state.set(SaveState::Saving);
+
+        let mut body = save.fields.clone();
+        body.insert("_version".to_string(), self.version.get_untracked());
+        let signal = controller.as_ref().map(|c| c.signal());
+        let result = patch_entity(entity_type, id, &serde_json::Value::Object(body), signal.as_ref()).await;
+
+        match result {
+            Ok(resp) => {
+                // Even a superseded save may have landed; later saves build on it
+                self.version.set(resp.get("version").cloned().unwrap_or_default());
+                if self.queue.try_update_value(|q| q.finish(save.id)) == Some(true) {
+                    self.state.set(SaveState::Saved);
+                }
+            }
+            Err(SaveError::Aborted) => {}
+            Err(SaveError::Conflict(current)) => {
+                if self.queue.try_update_value(|q| q.fail(save.id)).flatten().is_none() {
+                    return;
+                }
+                let mut local = self.record.get_untracked();
+                if let Some(fields) = local.as_object_mut() {
+                    fields.extend(save.fields);
+                }
+                self.conflict.set(Some(conflict_data(entity_type, id, local, current, &self.version.get_untracked())));
+                self.state.set(SaveState::Conflict);
+            }
+            Err(SaveError::Failed(e)) => {
+                if self.queue.try_update_value(|q| q.fail(save.id)).flatten().is_some() {
+                    gloo_console::error!("Autosave failed:", e);
+                    self.state.set(SaveState::Error);
+                }
+            }
+        }
+    }
+
+    /// Apply the user's choice for a conflicting save
+    fn resolve(self, entity_type: &str, id: &str, conflict: &ConflictData, result: ResolutionResult) {
+        self.conflict.set(None);
+        if result.choice == ResolutionChoice::Cancel {
+            self.state.set(SaveState::Pending);
+            return;
+        }
+
+        // Continue from the server's version either way
+        let mut server = conflict.server_data.clone();
+        if let Some(fields) = server.as_object_mut() {
+            fields.insert("_version".to_string(), serde_json::json!(conflict.server_version));
+        }
+        self.version.set(serde_json::json!(conflict.server_version));
+        self.set_record.set(server);
+        self.queue.update_value(|q| q.discard());
+
+        if result.choice == ResolutionChoice::KeepTheirs {
+            self.state.set(SaveState::Idle);
+            return;
+        }
+        let wanted = result.merged_data.unwrap_or_else(|| conflict.local_data.clone());
+        let changed: Vec<(String, serde_json::Value)> = wanted
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter(|(name, value)| name.as_str() != "_version" && conflict.server_data.get(name.as_str()) != Some(*value))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (name, value) in changed {
+            self.edit(entity_type, id, &name, value);
+        }
+    }
+}
+
+/// Both sides of a rejected save, for the ConflictResolver
+fn conflict_data(
+    entity_type: &str,
+    id: &str,
+    local: serde_json::Value,
+    mut current: serde_json::Value,
+    local_version: &serde_json::Value,
+) -> ConflictData {
+    let server_version = current.get("_version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if let Some(fields) = current.as_object_mut() {
+        fields.remove("_version");
+    }
+    let updated_at = |data: &serde_json::Value| {
+        data.get("updated_at").and_then(|v| v.as_str()).unwrap_or_default().to_string()
+    };
+    ConflictData {
+        entity_id: Uuid::parse_str(id).unwrap_or_default(),
+        entity_type: entity_type.to_string(),
+        field: None,
+        local_updated_at: updated_at(&local),
+        server_updated_at: updated_at(&current),
+        local_data: local,
+        server_data: current,
+        local_version: local_version.as_u64().unwrap_or(0),
+        server_version,
+    }
+}
+
 /// Related Section Component - Shows related entities in a card
 #[component]
 fn RelatedSection(
@@ -5,14 +5,19 @@
 use leptos::*;
 use leptos_router::*;
 use crate::api::{
-    fetch_field_defs, fetch_entity_list, FieldDef, ViewDef
+    fetch_field_defs, fetch_entity_page, save_view_columns, FieldDef, ViewDef
 };
+use crate::core::view_layout::{ColumnLayout, COLUMN_PERSIST_DEBOUNCE_MS};
+use gloo_timers::future::TimeoutFuture;
 use crate::components::editable_table::EditableTable;
 use crate::components::view_switcher::ViewSwitcher;
 use crate::components::create_modal::{CreateModal, CreatedRecord};
 use crate::components::kanban::{KanbanView, KanbanConfig};
 use crate::components::filter_builder::{FilterBuilder, FilterChipBar, FilterCondition};
 
+/// Records fetched per page as the table scrolls
+const PAGE_SIZE: u32 = 100;
+
 #[component]
 pub fn EntityListPage() -> impl IntoView {
     let params = use_params_map();
@@ -27,6 +32,10 @@ pub fn EntityListPage() -> impl IntoView {
     // data holds the filtered list passed to views
     let data = create_rw_signal(Vec::<serde_json::Value>::new());
     
+    // Cursor for the next page; None once everything is loaded
+    let next_cursor = store_value(Option::<String>::None);
+    let loading_more = store_value(false);
+    
     let (loading, set_loading) = create_signal(true);
     let (error, set_error) = create_signal(Option::<String>::None);
     
@@ -34,6 +43,33 @@ pub fn EntityListPage() -> impl IntoView {
     let (active_view, set_active_view) = create_signal::<Option<ViewDef>>(None);
     let (show_create, set_show_create) = create_signal(false);
     
+    // Column layout of the active view; the id moves to the personal
+    // override once a shared view is resized
+    let layout = store_value(ColumnLayout::default());
+    let layout_view_id = store_value(Option::<String>::None);
+    let column_widths = create_rw_signal(std::collections::HashMap::new());
+    create_effect(move |_| {
+        let view = active_view.get();
+        let columns = view.as_ref().map(|v| v.columns.clone()).unwrap_or_default();
+        layout_view_id.set_value(view.map(|v| v.id));
+        layout.set_value(ColumnLayout::new(columns));
+        column_widths.set(layout.with_value(|l| l.widths()));
+    });
+    
+    // Save widths once resizing pauses
+    let handle_column_resize = move |(field, width): (String, u32)| {
+        let Some(generation) = layout.try_update_value(|l| l.resize(&field, width)) else { return };
+        spawn_local(async move {
+            TimeoutFuture::new(COLUMN_PERSIST_DEBOUNCE_MS).await;
+            let Some(payload) = layout.try_update_value(|l| l.take(generation)).flatten() else { return };
+            let Some(view_id) = layout_view_id.get_value() else { return };
+            match save_view_columns(&view_id, &payload).await {
+                Ok(saved_id) => layout_view_id.set_value(Some(saved_id)),
+                Err(e) => logging::error!("Failed to save column widths: {}", e),
+            }
+        });
+    };
+    
     // Filter State
     let (filters, set_filters) = create_signal(Vec::<FilterCondition>::new());
     let show_filter_popover = create_rw_signal(false);
@@ -100,9 +136,10 @@ pub fn EntityListPage() -> impl IntoView {
             
             // 2. Fetch Entity Data for Table
             if current_view_type() == "table" {
-                match fetch_entity_list(&etype).await {
-                    Ok(response) => {
-                        raw_data.set(response.data);
+                match fetch_entity_page(&etype, None, PAGE_SIZE).await {
+                    Ok(page) => {
+                        next_cursor.set_value(page.next_cursor);
+                        raw_data.set(page.items);
                         // Filter effect will trigger and populate 'data'
                     },
                     Err(e) => set_error.set(Some(e)),
@@ -113,6 +150,28 @@ pub fn EntityListPage() -> impl IntoView {
         });
     });
     
+    // Append the next page when the table nears its last row
+    let load_more = move |_| {
+        let Some(cursor) = next_cursor.get_value() else { return };
+        if loading_more.get_value() {
+            return;
+        }
+        loading_more.set_value(true);
+        let etype = entity_type();
+        spawn_local(async move {
+            match fetch_entity_page(&etype, Some(&cursor), PAGE_SIZE).await {
+                // Drop a page that arrives after switching entity
+                Ok(page) if etype == untrack(entity_type) => {
+                    next_cursor.set_value(page.next_cursor);
+                    raw_data.update(|rows| rows.extend(page.items));
+                }
+                Ok(_) => {}
+                Err(e) => set_error.set(Some(e)),
+            }
+            loading_more.set_value(false);
+        });
+    };
+    
     let navigate = use_navigate();
     let nav_store = store_value(navigate);
     
@@ -122,8 +181,9 @@ pub fn EntityListPage() -> impl IntoView {
         // Reload data
         let etype = entity_type();
         spawn_local(async move {
-             if let Ok(response) = fetch_entity_list(&etype).await {
-                raw_data.set(response.data);
+             if let Ok(page) = fetch_entity_page(&etype, None, PAGE_SIZE).await {
+                next_cursor.set_value(page.next_cursor);
+                raw_data.set(page.items);
             }
         });
     };
@@ -229,6 +289,9 @@ pub fn EntityListPage() -> impl IntoView {
                                     columns={fields.get().into_iter().filter(|f| f.show_in_list).collect()}
                                     data={data}
                                     density="comfortable".to_string()
+                                    on_end_reached=load_more
+                                    column_widths=column_widths
+                                    on_column_resize=handle_column_resize
                                     on_row_click={move |id| {
                                         nav_store.get_value()(&format!("/app/crm/entity/{}/{}", entity_type(), id), Default::default());
                                     }}
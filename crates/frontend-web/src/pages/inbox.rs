@@ -5,6 +5,7 @@ use crate::api::{fetch_inbox_threads, fetch_thread_messages, send_inbox_reply};
 use crate::components::inbox_thread_list::InboxThreadList;
 use crate::components::message_bubble::MessageBubble;
 use crate::components::composer::LegacyComposer;
+use crate::context::socket::{SocketContext, WsEvent};
 
 /// Main Inbox Page with 3-pane layout
 #[component]
@@ -58,6 +59,23 @@ pub fn InboxPage() -> impl IntoView {
         );
     }
     
+    // Refresh live when threads change on the server
+    if let Some(socket) = use_context::<SocketContext>() {
+        create_effect(move |_| {
+            if let Some(
+                WsEvent::NewMessage { .. }
+                | WsEvent::ThreadsMerged { .. }
+                | WsEvent::ThreadSplit { .. }
+                | WsEvent::UnreadCountChanged { .. },
+            ) =
+                socket.last_event.get()
+            {
+                threads_resource.refetch();
+                messages_resource.refetch();
+            }
+        });
+    }
+
     // Handle thread selection
     let on_select_thread = move |entity_id: String, entity_type: String| {
         set_selected_thread_id.set(Some(entity_id));
@@ -1,26 +1,59 @@
 //! Shared utility functions for the frontend
 
+use core_models::{format_phone, region_from_locale, Money, DEFAULT_CURRENCY, DEFAULT_LOCALE, DEFAULT_PHONE_REGION};
 use serde_json::Value;
 
+/// A General Settings value, if set
+fn setting(key: &str) -> Option<String> {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|s| s.get_item(key).ok().flatten())
+        .filter(|v| !v.is_empty())
+}
+
+/// Tenant locale and currency from General Settings, as `(locale, currency)`
+pub fn tenant_money_settings() -> (String, String) {
+    (
+        setting("locale").unwrap_or_else(|| DEFAULT_LOCALE.to_string()),
+        setting("currency").unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+    )
+}
+
+/// Country national phone numbers are read in: the tenant's country, else
+/// the region of its locale
+pub fn tenant_phone_region() -> String {
+    setting("country")
+        .map(|c| c.to_uppercase())
+        .or_else(|| setting("locale").and_then(|l| region_from_locale(&l)))
+        .unwrap_or_else(|| DEFAULT_PHONE_REGION.to_string())
+}
+
+/// Format a stored money value in the tenant locale; amounts without a
+/// currency use `currency_code`, else the tenant currency
+pub fn format_money(value: &Value, currency_code: Option<&str>) -> Option<String> {
+    let (locale, tenant_currency) = tenant_money_settings();
+    Money::from_value(value, currency_code.unwrap_or(&tenant_currency)).map(|m| m.format(&locale))
+}
+
 /// Format a field value for display based on field type
 pub fn format_field_display(value: &Value, field_type: &str) -> String {
+    if matches!(field_type.to_lowercase().as_str(), "currency" | "money" | "price") {
+        if let Some(formatted) = format_money(value, None) {
+            return formatted;
+        }
+    }
     match value {
         Value::Null => "—".to_string(),
         Value::String(s) if s.is_empty() => "—".to_string(),
         Value::String(s) => {
             match field_type.to_lowercase().as_str() {
                 "email" => format!("📧 {}", s),
-                "phone" => format!("📞 {}", s),
+                "phone" => format!("📞 {}", format_phone(s, &tenant_phone_region())),
                 "url" => format!("🔗 {}", s),
                 _ => s.clone(),
             }
         }
-        Value::Number(n) => {
-            match field_type.to_lowercase().as_str() {
-                "currency" | "money" | "price" => format!("${}", n),
-                _ => n.to_string(),
-            }
-        }
+        Value::Number(n) => n.to_string(),
         Value::Bool(b) => if *b { "Yes" } else { "No" }.to_string(),
         other => other.to_string(),
     }
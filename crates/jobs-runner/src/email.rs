@@ -1,8 +1,13 @@
 //! Email Worker - SMTP email sending using lettre
 //!
 //! Sends emails using SMTP credentials stored in the integrations table.
+//! Account emails (password reset, email verification) fall back to the
+//! platform's own SMTP account (`SMTP_*`) when the tenant hasn't set one up,
+//! since a new tenant has to verify before it can configure anything.
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use core_integrations::encryption::KeyRing;
 use lettre::{
     message::{header::ContentType, Mailbox},
     transport::smtp::authentication::Credentials,
@@ -10,7 +15,7 @@ use lettre::{
 };
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 /// Email job payload
@@ -25,6 +30,23 @@ pub struct EmailJobPayload {
     pub interaction_id: Option<Uuid>,
 }
 
+/// Job type of a password reset mail
+pub const SEND_PASSWORD_RESET_EMAIL: &str = "send_password_reset_email";
+/// Job type of an email verification mail
+pub const SEND_VERIFICATION_EMAIL: &str = "send_verification_email";
+
+/// Account email job payload, as queued by the API
+#[derive(Debug, Deserialize)]
+pub struct AccountEmailPayload {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub to: String,
+    pub name: String,
+    /// Key ring ciphertext of the token, hex
+    pub token_encrypted: String,
+    pub expires_at: DateTime<Utc>,
+}
+
 /// SMTP credentials from integrations table
 #[derive(Debug)]
 struct SmtpCredentials {
@@ -48,6 +70,13 @@ impl EmailService {
 
     /// Fetch SMTP credentials for a tenant
     async fn get_smtp_credentials(&self, tenant_id: Uuid) -> Result<SmtpCredentials> {
+        self.tenant_smtp_credentials(tenant_id)
+            .await?
+            .ok_or_else(|| anyhow!("No SMTP integration configured for tenant {}", tenant_id))
+    }
+
+    /// The tenant's SMTP integration, if it has one
+    async fn tenant_smtp_credentials(&self, tenant_id: Uuid) -> Result<Option<SmtpCredentials>> {
         // Fetch from integrations table
         let row: Option<(Vec<u8>,)> = sqlx::query_as(
             r#"
@@ -62,21 +91,23 @@ impl EmailService {
 
         match row {
             Some((credentials_bytes,)) => {
-                // TODO: Decrypt credentials using AES-256-GCM
-                // For now, parse as JSON directly (not encrypted in dev)
-                let creds: serde_json::Value = serde_json::from_slice(&credentials_bytes)
-                    .map_err(|e| anyhow!("Failed to parse SMTP credentials: {}", e))?;
+                // Stored encrypted by IntegrationService; plain JSON in dev
+                let creds: serde_json::Value = match KeyRing::from_env().decrypt_json(&credentials_bytes) {
+                    Ok(creds) => creds,
+                    Err(_) => serde_json::from_slice(&credentials_bytes)
+                        .map_err(|e| anyhow!("Failed to parse SMTP credentials: {}", e))?,
+                };
 
-                Ok(SmtpCredentials {
+                Ok(Some(SmtpCredentials {
                     host: creds["host"].as_str().unwrap_or("smtp.gmail.com").to_string(),
                     port: creds["port"].as_u64().unwrap_or(587) as u16,
                     username: creds["username"].as_str().unwrap_or("").to_string(),
                     password: creds["password"].as_str().unwrap_or("").to_string(),
                     from_email: creds["from_email"].as_str().unwrap_or("").to_string(),
                     from_name: creds["from_name"].as_str().unwrap_or("Jirsi CRM").to_string(),
-                })
+                }))
             }
-            None => Err(anyhow!("No SMTP integration configured for tenant {}", tenant_id)),
+            None => Ok(None),
         }
     }
 
@@ -91,7 +122,22 @@ impl EmailService {
 
         // Get SMTP credentials
         let creds = self.get_smtp_credentials(payload.tenant_id).await?;
+        self.send_with(&creds, payload).await
+    }
+
+    /// Send a password reset or verification mail, through the tenant's SMTP
+    /// integration or else the platform account
+    pub async fn send_account_email(&self, job_type: &str, payload: &AccountEmailPayload) -> Result<()> {
+        let creds = match self.tenant_smtp_credentials(payload.tenant_id).await? {
+            Some(creds) => creds,
+            None => platform_smtp_credentials()
+                .ok_or_else(|| anyhow!("No SMTP integration for tenant {} and SMTP_HOST is not set", payload.tenant_id))?,
+        };
+        let mail = account_email(job_type, payload, &KeyRing::from_env(), &app_base_url())?;
+        self.send_with(&creds, &mail).await
+    }
 
+    async fn send_with(&self, creds: &SmtpCredentials, payload: &EmailJobPayload) -> Result<()> {
         // Build the email message
         let from_mailbox: Mailbox = format!("{} <{}>", creds.from_name, creds.from_email)
             .parse()
@@ -179,6 +225,73 @@ impl EmailService {
     }
 }
 
+/// The platform's own SMTP account, for mail sent before a tenant has one
+fn platform_smtp_credentials() -> Option<SmtpCredentials> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let host = env("SMTP_HOST")?;
+    Some(SmtpCredentials {
+        port: env("SMTP_PORT").and_then(|p| p.parse().ok()).unwrap_or(587),
+        username: env("SMTP_USERNAME").unwrap_or_default(),
+        password: env("SMTP_PASSWORD").unwrap_or_default(),
+        from_email: env("SMTP_FROM_EMAIL").unwrap_or_else(|| "no-reply@jirsi.com".to_string()),
+        from_name: env("SMTP_FROM_NAME").unwrap_or_else(|| "Jirsi".to_string()),
+        host,
+    })
+}
+
+/// Where links in account emails point
+fn app_base_url() -> String {
+    std::env::var("APP_BASE_URL").unwrap_or_else(|_| "https://app.jirsi.com".to_string())
+}
+
+/// Build the mail for an account email job
+fn account_email(job_type: &str, payload: &AccountEmailPayload, keys: &KeyRing, base_url: &str) -> Result<EmailJobPayload> {
+    let encrypted = hex::decode(&payload.token_encrypted).map_err(|e| anyhow!("Invalid token encoding: {}", e))?;
+    let token = String::from_utf8(keys.decrypt(&encrypted)?)?;
+    let expires = payload.expires_at.format("%Y-%m-%d %H:%M UTC");
+    let base_url = base_url.trim_end_matches('/');
+
+    let (subject, body) = match job_type {
+        SEND_PASSWORD_RESET_EMAIL => (
+            "Reset your password",
+            format!(
+                "Hi {},\n\nSomeone asked to reset the password for your account. Choose a new one here before {}:\n\n{}/reset-password?token={}\n\nIf this wasn't you, ignore this email; your password hasn't changed.",
+                payload.name, expires, base_url, token
+            ),
+        ),
+        SEND_VERIFICATION_EMAIL => (
+            "Confirm your email address",
+            format!(
+                "Hi {},\n\nConfirm this is your email address by opening this link before {}:\n\n{}/verify-email?token={}",
+                payload.name, expires, base_url, token
+            ),
+        ),
+        other => return Err(anyhow!("Not an account email job: {}", other)),
+    };
+
+    Ok(EmailJobPayload {
+        recipient: payload.to.clone(),
+        subject: subject.to_string(),
+        body,
+        html_body: None,
+        from_name: None,
+        tenant_id: payload.tenant_id,
+        interaction_id: None,
+    })
+}
+
+/// Process a password reset or verification mail job
+pub async fn process_account_email_job(pool: &PgPool, job_type: &str, payload: &serde_json::Value) -> Result<()> {
+    let account_payload: AccountEmailPayload = serde_json::from_value(payload.clone())
+        .map_err(|e| anyhow!("Invalid account email job payload: {}", e))?;
+    if account_payload.expires_at <= Utc::now() {
+        warn!(user_id = %account_payload.user_id, job_type, "Account email token expired before sending; dropped");
+        return Ok(());
+    }
+
+    EmailService::new(pool.clone()).send_account_email(job_type, &account_payload).await
+}
+
 /// Process an email job payload
 pub async fn process_email_job(pool: &PgPool, payload: &serde_json::Value) -> Result<()> {
     let email_payload: EmailJobPayload = serde_json::from_value(payload.clone())
@@ -187,3 +300,35 @@ pub async fn process_email_job(pool: &PgPool, payload: &serde_json::Value) -> Re
     let service = EmailService::new(pool.clone());
     service.send_email(&email_payload).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(keys: &KeyRing, token: &str) -> AccountEmailPayload {
+        AccountEmailPayload {
+            tenant_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            to: "owner@example.com".to_string(),
+            name: "Owner".to_string(),
+            token_encrypted: hex::encode(keys.encrypt(token.as_bytes()).unwrap()),
+            expires_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_account_emails_link_the_decrypted_token() {
+        let keys = KeyRing::new(1, core_integrations::encryption::generate_key());
+        let payload = payload(&keys, "tok_123");
+
+        let reset = account_email(SEND_PASSWORD_RESET_EMAIL, &payload, &keys, "https://app.example.com/").unwrap();
+        assert_eq!(reset.recipient, "owner@example.com");
+        assert!(reset.body.contains("https://app.example.com/reset-password?token=tok_123"));
+
+        let verify = account_email(SEND_VERIFICATION_EMAIL, &payload, &keys, "https://app.example.com").unwrap();
+        assert_eq!(verify.subject, "Confirm your email address");
+        assert!(verify.body.contains("https://app.example.com/verify-email?token=tok_123"));
+
+        assert!(account_email("send_email", &payload, &keys, "https://app.example.com").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Embedding Worker - embeds queued record text in batches
+//!
+//! Saving a record whose embeddable fields changed queues its text in
+//! `entity_embeddings.pending_content`. Each pass sends up to `BATCH_SIZE`
+//! queued texts to OpenAI in a single request, so a burst of edits costs a
+//! few calls rather than one per save.
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Texts per embeddings request
+pub const BATCH_SIZE: i64 = 100;
+
+/// Must produce the 1536 dimensions `entity_embeddings.embedding` holds
+const MODEL: &str = "text-embedding-ada-002";
+
+/// Characters of the embedded text kept as `content_preview`
+const PREVIEW_CHARS: i32 = 500;
+
+/// Embed one batch of queued texts; returns how many were embedded
+///
+/// The rows stay locked until their vectors are stored, so a save that
+/// queues new text meanwhile waits and is picked up by the next pass.
+pub async fn process_pending_embeddings(pool: &PgPool, client: &Client, api_key: &str) -> Result<u32> {
+    let mut tx = pool.begin().await?;
+    let rows: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT id, pending_content
+        FROM entity_embeddings
+        WHERE pending_content IS NOT NULL
+        ORDER BY updated_at
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .fetch_all(&mut *tx)
+    .await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let texts: Vec<&str> = rows.iter().map(|(_, text)| text.as_str()).collect();
+    let vectors = embed_batch(client, api_key, &texts).await?;
+
+    for ((id, text), vector) in rows.iter().zip(&vectors) {
+        let literal = format!("[{}]", vector.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(","));
+        sqlx::query(
+            r#"
+            UPDATE entity_embeddings
+            SET embedding = $2::vector, content_preview = left($3, $4), model_name = $5, pending_content = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(literal)
+        .bind(text)
+        .bind(PREVIEW_CHARS)
+        .bind(MODEL)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(rows.len() as u32)
+}
+
+/// One embeddings request for all of `texts`, vectors in the same order
+async fn embed_batch(client: &Client, api_key: &str, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    let response = client
+        .post("https://api.openai.com/v1/embeddings")
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({ "model": MODEL, "input": texts }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("Embeddings request failed ({}): {}", status, body));
+    }
+
+    let json: serde_json::Value = response.json().await?;
+    let data = json["data"].as_array().ok_or_else(|| anyhow!("Embeddings response has no data"))?;
+    if data.len() != texts.len() {
+        return Err(anyhow!("Expected {} embeddings, got {}", texts.len(), data.len()));
+    }
+
+    let mut vectors = vec![Vec::new(); texts.len()];
+    for item in data {
+        let index = item["index"].as_u64().ok_or_else(|| anyhow!("Embedding without index"))? as usize;
+        let vector = item["embedding"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Embedding without vector"))?
+            .iter()
+            .filter_map(|v| v.as_f64().map(|f| f as f32))
+            .collect();
+        *vectors.get_mut(index).ok_or_else(|| anyhow!("Embedding index {} out of range", index))? = vector;
+    }
+    Ok(vectors)
+}
@@ -1,5 +1,6 @@
 //! Job processing
 
+use core_node_engine::GraphExecutor;
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -57,7 +58,11 @@ pub async fn process_pending_jobs(pool: &PgPool) -> anyhow::Result<u32> {
         // Process based on job type
         let result = match job.job_type.as_str() {
             "node_graph_execution" => process_node_graph_job(pool, &job.payload).await,
+            "graph_resume" => process_graph_resume_job(pool, &job.payload).await,
             "send_email" => email::process_email_job(pool, &job.payload).await,
+            email::SEND_PASSWORD_RESET_EMAIL | email::SEND_VERIFICATION_EMAIL => {
+                email::process_account_email_job(pool, &job.job_type, &job.payload).await
+            }
             "send_whatsapp" => whatsapp::process_whatsapp_job(pool, &job.payload).await,
             _ => {
                 tracing::warn!("Unknown job type: {}", job.job_type);
@@ -95,3 +100,23 @@ async fn process_node_graph_job(_pool: &PgPool, payload: &serde_json::Value) ->
     // TODO: Execute node graph
     Ok(())
 }
+
+/// Continue a suspended graph execution from its checkpoint
+async fn process_graph_resume_job(pool: &PgPool, payload: &serde_json::Value) -> anyhow::Result<()> {
+    let execution_id: Uuid = payload
+        .get("execution_id")
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("graph_resume job missing execution_id"))?;
+
+    tracing::info!(execution_id = %execution_id, "Resuming graph execution");
+
+    let execution = GraphExecutor::new(pool.clone()).resume(execution_id).await?;
+
+    tracing::info!(
+        execution_id = %execution_id,
+        status = ?execution.status,
+        "Graph execution resume finished"
+    );
+    Ok(())
+}
@@ -3,12 +3,17 @@
 //! Processes async jobs like node graph executions, email sending, etc.
 
 use sqlx::postgres::PgPoolOptions;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Time between embedding passes; edits queued meanwhile share one request
+const EMBED_INTERVAL: Duration = Duration::from_secs(10);
+
 mod jobs;
 mod email;
+mod embeddings;
 mod whatsapp;
+mod webhooks;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -32,6 +37,14 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("Connected to database");
 
+    // Record embeddings need an OpenAI key
+    let openai_key = std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty() && k != "mock");
+    let http = reqwest::Client::new();
+    let mut last_embed_pass = Instant::now();
+
+    // Tenants' outbound webhook subscriptions
+    let webhook_dispatcher = webhooks::WebhookDispatcher::new(pool.clone());
+
     // Job processing loop
     loop {
         // Check for pending jobs
@@ -46,6 +59,21 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
+        if let Some(key) = openai_key.as_deref().filter(|_| last_embed_pass.elapsed() >= EMBED_INTERVAL) {
+            last_embed_pass = Instant::now();
+            match embeddings::process_pending_embeddings(&pool, &http, key).await {
+                Ok(count) if count > 0 => tracing::info!("Embedded {} records", count),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Error embedding records: {}", e),
+            }
+        }
+
+        match webhook_dispatcher.run_once().await {
+            Ok(count) if count > 0 => tracing::info!("Attempted {} webhook deliveries", count),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Error delivering webhooks: {}", e),
+        }
+
         // Sleep before next check
         tokio::time::sleep(Duration::from_secs(1)).await;
     }
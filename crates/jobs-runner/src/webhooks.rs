@@ -0,0 +1,508 @@
+//! Webhook Worker - notify tenant endpoints of entity events
+//!
+//! Each pass first matches new `event_outbox` rows against the tenants'
+//! active `webhook_subscriptions`, queuing one `webhook_deliveries` row per
+//! match, then POSTs the due deliveries signed with the subscription's
+//! secret (`X-Jirsi-Signature`). Non-2xx responses and network errors are
+//! retried with exponential backoff and jitter; a delivery still failing
+//! after the subscription's `max_attempts` is dead-lettered.
+//!
+//! Target URLs are tenant-supplied, so each send re-checks that the host
+//! resolves to public addresses only, connects to exactly those, and doesn't
+//! follow redirects. Only the status code and a fixed error are recorded -
+//! never the response body, which the tenant can read back in the log.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use core_integrations::egress::EgressPolicy;
+use core_integrations::encryption::KeyRing;
+use core_integrations::webhook::{sign_outbound, OUTBOUND_SIGNATURE_HEADER};
+use futures::stream::{self, StreamExt};
+use rand::Rng;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Outbox events matched against subscriptions per pass
+pub const QUEUE_BATCH_SIZE: i64 = 200;
+
+/// Deliveries sent per pass
+pub const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Deliveries in flight at once
+pub const DELIVERY_CONCURRENCY: usize = 10;
+
+/// Delivery timing
+#[derive(Debug, Clone)]
+pub struct DeliveryConfig {
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Delay before the first retry; doubles per attempt
+    pub base_backoff: Duration,
+    /// Longest delay between attempts
+    pub max_backoff: Duration,
+    /// How long a claimed delivery is left to its worker before another
+    /// may retry it
+    pub lease: Duration,
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            base_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(6 * 60 * 60),
+            lease: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Delay before attempt `attempts + 1`, with up to 50% added at random so
+/// deliveries that failed together don't retry together
+pub fn backoff(config: &DeliveryConfig, attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let delay = config.base_backoff.saturating_mul(2u32.pow(exponent)).min(config.max_backoff);
+    delay.mul_f64(1.0 + rand::thread_rng().gen_range(0.0..0.5))
+}
+
+/// A due delivery with what's needed to send it
+#[derive(Debug, sqlx::FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    event_type: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    target_url: String,
+    secret_encrypted: Vec<u8>,
+    max_attempts: i32,
+}
+
+/// Sends subscribed tenants their entity events
+pub struct WebhookDispatcher {
+    pool: PgPool,
+    keys: KeyRing,
+    egress: EgressPolicy,
+    config: DeliveryConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_config(pool, DeliveryConfig::default())
+    }
+
+    pub fn with_config(pool: PgPool, config: DeliveryConfig) -> Self {
+        Self {
+            pool,
+            keys: KeyRing::from_env(),
+            egress: EgressPolicy::public_only(),
+            config,
+        }
+    }
+
+    /// Send to targets whatever they resolve to; for tests against local
+    /// mock servers
+    pub fn with_egress(mut self, egress: EgressPolicy) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    /// Queue deliveries for new events, then send the due ones; returns how
+    /// many deliveries were attempted
+    pub async fn run_once(&self) -> Result<u32> {
+        let queued = self.queue_deliveries().await?;
+        if queued > 0 {
+            info!(queued, "Queued webhook deliveries");
+        }
+        self.deliver_due().await
+    }
+
+    /// Match unqueued outbox events against active subscriptions
+    ///
+    /// Subscriptions only see events from after they were created, and an
+    /// event is queued at most once per subscription.
+    pub async fn queue_deliveries(&self) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let ids: Vec<i64> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM event_outbox
+            WHERE webhooks_queued_at IS NULL
+            ORDER BY id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(QUEUE_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let queued = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (subscription_id, tenant_id, event_id, event_type, payload)
+            SELECT s.id, e.tenant_id, e.dedup_key, e.entity_type || '.' || e.event_type,
+                   jsonb_build_object(
+                       'event', e.entity_type || '.' || e.event_type,
+                       'event_id', e.dedup_key,
+                       'tenant_id', e.tenant_id,
+                       'occurred_at', e.created_at,
+                       'data', e.payload
+                   )
+            FROM event_outbox e
+            JOIN webhook_subscriptions s
+              ON s.tenant_id = e.tenant_id
+             AND s.is_active
+             AND s.created_at <= e.created_at
+             AND s.event_types && ARRAY[
+                     e.entity_type || '.' || e.event_type,
+                     e.entity_type || '.*',
+                     '*.' || e.event_type,
+                     '*'
+                 ]::TEXT[]
+            WHERE e.id = ANY($1)
+            ON CONFLICT (subscription_id, event_id) DO NOTHING
+            "#,
+        )
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("UPDATE event_outbox SET webhooks_queued_at = NOW() WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(queued)
+    }
+
+    /// Send one batch of due deliveries
+    ///
+    /// The batch is claimed in one statement that pushes each delivery's
+    /// `next_attempt_at` out by the lease, so other workers skip it while
+    /// it's in flight and pick it up again if this one dies. Sends run
+    /// outside any transaction, `DELIVERY_CONCURRENCY` at a time, and each
+    /// outcome is stored on its own.
+    pub async fn deliver_due(&self) -> Result<u32> {
+        let due: Vec<DueDelivery> = sqlx::query_as(
+            r#"
+            WITH due AS (
+                SELECT d.id
+                FROM webhook_deliveries d
+                JOIN webhook_subscriptions s ON s.id = d.subscription_id
+                WHERE d.status = 'pending' AND d.next_attempt_at <= NOW() AND s.is_active
+                ORDER BY d.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF d SKIP LOCKED
+            )
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM due, webhook_subscriptions s
+            WHERE d.id = due.id AND s.id = d.subscription_id
+            RETURNING d.id, d.event_type, d.payload, d.attempts, s.target_url, s.secret_encrypted, s.max_attempts
+            "#,
+        )
+        .bind(DELIVERY_BATCH_SIZE)
+        .bind(self.config.lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = due.len() as u32;
+        stream::iter(due)
+            .for_each_concurrent(DELIVERY_CONCURRENCY, |delivery| async move {
+                let (status_code, result) = self.send(&delivery).await;
+                if let Err(e) = self.record(&delivery, status_code, result).await {
+                    warn!(delivery_id = %delivery.id, error = %e, "Failed to record webhook delivery outcome");
+                }
+            })
+            .await;
+
+        Ok(count)
+    }
+
+    /// Store how an attempt went: delivered, dead-lettered, or due again
+    /// after a backoff
+    async fn record(&self, delivery: &DueDelivery, status_code: Option<i32>, result: Result<()>) -> Result<()> {
+        let attempts = delivery.attempts + 1;
+
+        match result {
+            Ok(()) => {
+                sqlx::query(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET status = 'delivered', attempts = $2, last_status_code = $3, last_error = NULL, delivered_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(delivery.id)
+                .bind(attempts)
+                .bind(status_code)
+                .execute(&self.pool)
+                .await?;
+            }
+            Err(error) if attempts >= delivery.max_attempts => {
+                warn!(delivery_id = %delivery.id, attempts, error = %error, "Webhook delivery dead-lettered");
+                sqlx::query(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET status = 'dead', attempts = $2, last_status_code = $3, last_error = $4
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(delivery.id)
+                .bind(attempts)
+                .bind(status_code)
+                .bind(error.to_string())
+                .execute(&self.pool)
+                .await?;
+            }
+            Err(error) => {
+                let delay = backoff(&self.config, attempts);
+                warn!(delivery_id = %delivery.id, attempts, retry_in_secs = delay.as_secs(), error = %error, "Webhook delivery failed");
+                sqlx::query(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET attempts = $2, last_status_code = $3, last_error = $4,
+                        next_attempt_at = NOW() + make_interval(secs => $5)
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(delivery.id)
+                .bind(attempts)
+                .bind(status_code)
+                .bind(error.to_string())
+                .bind(delay.as_secs_f64())
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// POST one delivery; the status code is `None` when no response came back
+    async fn send(&self, delivery: &DueDelivery) -> (Option<i32>, Result<()>) {
+        let secret = match self.keys.decrypt(&delivery.secret_encrypted) {
+            Ok(secret) => String::from_utf8_lossy(&secret).into_owned(),
+            Err(e) => return (None, Err(anyhow!("Unreadable subscription secret: {}", e))),
+        };
+        let body = match serde_json::to_vec(&delivery.payload) {
+            Ok(body) => body,
+            Err(e) => return (None, Err(anyhow!("Unserializable payload: {}", e))),
+        };
+        let signature = sign_outbound(&secret, Utc::now().timestamp(), &body);
+
+        // Checked per send so a host re-pointed at an internal address since
+        // the subscription was saved is still refused
+        let target = match self.egress.check(&delivery.target_url).await {
+            Ok(target) => target,
+            Err(e) => return (None, Err(anyhow!("{}", e))),
+        };
+        let client = match target.client_builder().timeout(self.config.timeout).build() {
+            Ok(client) => client,
+            Err(_) => return (None, Err(anyhow!("Could not build HTTP client"))),
+        };
+
+        let response = client
+            .post(target.url)
+            .header("Content-Type", "application/json")
+            .header(OUTBOUND_SIGNATURE_HEADER, signature)
+            .header("X-Jirsi-Event", &delivery.event_type)
+            .header("X-Jirsi-Delivery", delivery.id.to_string())
+            .body(body)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16() as i32), Ok(())),
+            Ok(response) => {
+                let status = response.status();
+                (Some(status.as_u16() as i32), Err(anyhow!("Endpoint returned {}", status)))
+            }
+            Err(e) if e.is_timeout() => (None, Err(anyhow!("Request timed out"))),
+            Err(e) if e.is_connect() => (None, Err(anyhow!("Could not connect to endpoint"))),
+            Err(_) => (None, Err(anyhow!("Request failed"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use core_integrations::webhook::SignatureScheme;
+    use core_node_engine::EntityEvent;
+    use std::sync::Arc;
+    use test_support::TestTenant;
+    use tokio::sync::Mutex;
+
+    /// Requests received by the mock endpoint: (signature header, body)
+    type Received = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+    async fn accept(State(received): State<Received>, headers: HeaderMap, body: axum::body::Bytes) -> StatusCode {
+        let signature = headers.get(OUTBOUND_SIGNATURE_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+        received.lock().await.push((signature.to_string(), body.to_vec()));
+        StatusCode::NO_CONTENT
+    }
+
+    async fn reject(State(received): State<Received>, body: axum::body::Bytes) -> StatusCode {
+        received.lock().await.push((String::new(), body.to_vec()));
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    async fn leak(State(received): State<Received>, body: axum::body::Bytes) -> (StatusCode, &'static str) {
+        received.lock().await.push((String::new(), body.to_vec()));
+        (StatusCode::INTERNAL_SERVER_ERROR, "internal-only-secret")
+    }
+
+    async fn mock_endpoint() -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/ok", post(accept))
+            .route("/down", post(reject))
+            .route("/leak", post(leak))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), received)
+    }
+
+    async fn subscribe(pool: &PgPool, tenant_id: Uuid, url: &str, event_types: &[&str], secret: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO webhook_subscriptions (tenant_id, target_url, event_types, secret_encrypted, max_attempts)
+             VALUES ($1, $2, $3, $4, 3) RETURNING id",
+        )
+        .bind(tenant_id)
+        .bind(url)
+        .bind(event_types.iter().map(|t| t.to_string()).collect::<Vec<_>>())
+        .bind(KeyRing::from_env().encrypt(secret.as_bytes()).unwrap())
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Write `event` to the outbox the way an entity write does
+    async fn commit(pool: &PgPool, event: &EntityEvent) {
+        sqlx::query(
+            "INSERT INTO event_outbox (dedup_key, tenant_id, entity_type, record_id, event_type, payload)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(event.id)
+        .bind(event.tenant_id)
+        .bind(&event.entity_type)
+        .bind(event.record_id)
+        .bind(event.event_type.to_string())
+        .bind(serde_json::to_value(event).unwrap())
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn delivery(pool: &PgPool, subscription_id: Uuid) -> Option<(String, i32)> {
+        sqlx::query_as("SELECT status, attempts FROM webhook_deliveries WHERE subscription_id = $1")
+            .bind(subscription_id)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter_up_to_the_cap() {
+        let config = DeliveryConfig {
+            base_backoff: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(60),
+            ..Default::default()
+        };
+        for (attempts, base) in [(1, 10), (2, 20), (3, 40), (4, 60), (30, 60)] {
+            let delay = backoff(&config, attempts);
+            let base = Duration::from_secs(base);
+            assert!(delay >= base && delay <= base.mul_f64(1.5), "attempt {}: {:?}", attempts, delay);
+        }
+    }
+
+    fn due(target_url: &str) -> DueDelivery {
+        DueDelivery {
+            id: Uuid::new_v4(),
+            event_type: "contact.create".to_string(),
+            payload: serde_json::json!({ "event": "contact.create" }),
+            attempts: 0,
+            target_url: target_url.to_string(),
+            secret_encrypted: KeyRing::from_env().encrypt(b"whsec_tenant").unwrap(),
+            max_attempts: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_refuses_internal_targets_and_never_records_the_response_body() {
+        let (base, received) = mock_endpoint().await;
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+
+        // The mock server is on loopback, so the default policy won't reach it
+        let dispatcher = WebhookDispatcher::new(pool.clone());
+        let (status, result) = dispatcher.send(&due(&format!("{}/ok", base))).await;
+        assert_eq!(status, None);
+        assert!(result.unwrap_err().to_string().contains("not a public address"));
+        assert!(received.lock().await.is_empty());
+
+        let dispatcher = WebhookDispatcher::new(pool).with_egress(EgressPolicy::allow_private());
+        let (status, result) = dispatcher.send(&due(&format!("{}/leak", base))).await;
+        assert_eq!(status, Some(500));
+        assert!(!result.unwrap_err().to_string().contains("internal-only-secret"));
+        assert_eq!(received.lock().await.len(), 1);
+    }
+
+    // One test, since a pass claims every tenant's events and deliveries
+    #[tokio::test]
+    #[ignore = "Requires database connection - run with --ignored"]
+    async fn test_subscribed_event_is_posted_signed_and_failures_dead_letter() {
+        let tenant = TestTenant::new("Webhook Test").await;
+        let (pool, tenant_id) = (tenant.pool.clone(), tenant.id);
+
+        let (base, received) = mock_endpoint().await;
+        let ok = subscribe(&pool, tenant_id, &format!("{}/ok", base), &["contact.create"], "whsec_tenant").await;
+        let down = subscribe(&pool, tenant_id, &format!("{}/down", base), &["*.create"], "whsec_tenant").await;
+        let deals_only = subscribe(&pool, tenant_id, &format!("{}/ok", base), &["deal.*"], "whsec_tenant").await;
+
+        let event = EntityEvent::create(tenant_id, "contact", Uuid::new_v4(), serde_json::json!({ "email": "sara@example.com" }), None);
+        commit(&pool, &event).await;
+
+        let config = DeliveryConfig { base_backoff: Duration::ZERO, ..Default::default() };
+        let dispatcher = WebhookDispatcher::with_config(pool.clone(), config).with_egress(EgressPolicy::allow_private());
+        dispatcher.run_once().await.unwrap();
+
+        // The matching subscription got one signed POST of the event
+        assert_eq!(delivery(&pool, ok).await, Some(("delivered".to_string(), 1)));
+        assert_eq!(delivery(&pool, deals_only).await, None);
+        {
+            let received = received.lock().await;
+            let (signature, body) = received.iter().find(|(signature, _)| !signature.is_empty()).unwrap();
+            let mut headers = HeaderMap::new();
+            headers.insert("Stripe-Signature", signature.parse().unwrap());
+            assert_eq!(SignatureScheme::StripeV1.verify("", &headers, body, "whsec_tenant"), Ok(()));
+
+            let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(payload["event"], "contact.create");
+            assert_eq!(payload["event_id"], event.id.to_string());
+            assert_eq!(payload["data"]["new_values"]["email"], "sara@example.com");
+        }
+
+        // The failing endpoint is retried, then dead-lettered at max_attempts
+        assert_eq!(delivery(&pool, down).await, Some(("pending".to_string(), 1)));
+        dispatcher.run_once().await.unwrap();
+        assert_eq!(delivery(&pool, down).await, Some(("pending".to_string(), 2)));
+        dispatcher.run_once().await.unwrap();
+        assert_eq!(delivery(&pool, down).await, Some(("dead".to_string(), 3)));
+        dispatcher.run_once().await.unwrap();
+        let down_hits = received.lock().await.iter().filter(|(signature, _)| signature.is_empty()).count();
+        assert_eq!(down_hits, 3);
+
+        // Delivered events aren't sent again
+        assert_eq!(delivery(&pool, ok).await, Some(("delivered".to_string(), 1)));
+        assert_eq!(received.lock().await.len(), 4);
+
+        tenant.cleanup().await;
+    }
+}
@@ -0,0 +1,101 @@
+//! Shared helpers for tests that need Postgres
+//!
+//! Those tests are `#[ignore]`d; run them against `DATABASE_URL` with
+//! `cargo test -- --ignored`.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Pool for the database in `DATABASE_URL`
+pub async fn get_test_pool() -> PgPool {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+/// A throwaway tenant that tests build their data on
+///
+/// Everything the tenant owns is removed again by [`TestTenant::cleanup`].
+pub struct TestTenant {
+    pub pool: PgPool,
+    pub id: Uuid,
+    pub subdomain: String,
+}
+
+impl TestTenant {
+    pub async fn new(name: &str) -> Self {
+        let pool = get_test_pool().await;
+        let id = Uuid::new_v4();
+        let slug = name.to_lowercase().replace(' ', "-");
+        let subdomain = format!("{}-{}", slug, &id.simple().to_string()[..12]);
+        sqlx::query("INSERT INTO tenants (id, name, subdomain) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(name)
+            .bind(&subdomain)
+            .execute(&pool)
+            .await
+            .unwrap();
+        Self { pool, id, subdomain }
+    }
+
+    /// Add an entity type without fields
+    pub async fn entity_type(&self, app_id: &str, name: &str, label: &str, label_plural: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO entity_types (id, tenant_id, app_id, name, label, label_plural)
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        )
+        .bind(Uuid::new_v4())
+        .bind(self.id)
+        .bind(app_id)
+        .bind(name)
+        .bind(label)
+        .bind(label_plural)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap()
+    }
+
+    /// Add a user with the default role and an unusable password
+    pub async fn user(&self, email: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO users (id, tenant_id, email, name, password_hash) VALUES ($1, $2, $3, 'Agent', 'x') RETURNING id",
+        )
+        .bind(Uuid::new_v4())
+        .bind(self.id)
+        .bind(email)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap()
+    }
+
+    /// Delete every row the tenant owns, then the tenant itself
+    pub async fn cleanup(self) {
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT c.table_name::text FROM information_schema.columns c
+             JOIN information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name
+             WHERE c.table_schema = current_schema() AND c.column_name = 'tenant_id' AND t.table_type = 'BASE TABLE'",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap();
+
+        // Rows reference each other across tables, so keep sweeping until no
+        // delete is blocked by a foreign key; whatever is left (e.g. rows the
+        // schema only lets go of by cascade) goes with the tenant
+        let mut remaining = tables;
+        while !remaining.is_empty() {
+            let mut blocked = Vec::new();
+            for table in &remaining {
+                let sql = format!("DELETE FROM \"{}\" WHERE tenant_id = $1", table);
+                if sqlx::query(&sql).bind(self.id).execute(&self.pool).await.is_err() {
+                    blocked.push(table.clone());
+                }
+            }
+            if blocked.len() == remaining.len() {
+                break;
+            }
+            remaining = blocked;
+        }
+
+        sqlx::query("DELETE FROM tenants WHERE id = $1").bind(self.id).execute(&self.pool).await.unwrap();
+    }
+}
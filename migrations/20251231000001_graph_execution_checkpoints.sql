@@ -0,0 +1,11 @@
+-- ============================================================================
+-- Graph Execution Checkpoints
+-- Persists suspended graph runs so they survive worker restarts
+-- ============================================================================
+
+ALTER TABLE graph_executions ADD COLUMN IF NOT EXISTS checkpoint JSONB;  -- Completed outputs + pending frontier
+ALTER TABLE graph_executions ADD COLUMN IF NOT EXISTS resume_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_graph_executions_resume
+    ON graph_executions(resume_at)
+    WHERE status = 'suspended' AND resume_at IS NOT NULL;
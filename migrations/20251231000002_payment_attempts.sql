@@ -0,0 +1,20 @@
+-- ============================================================================
+-- Payment Attempts
+-- One row per idempotency key so retried payment nodes never charge twice
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS payment_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,       -- tenant:execution:node unless overridden
+    graph_execution_id UUID NOT NULL,
+    node_id UUID NOT NULL,
+    amount BIGINT NOT NULL,                      -- Smallest currency unit
+    currency VARCHAR(3) NOT NULL,
+    payment_intent_id VARCHAR(255) NOT NULL,
+    result JSONB NOT NULL,                       -- Node output replayed on retry
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_attempts_execution ON payment_attempts(graph_execution_id);
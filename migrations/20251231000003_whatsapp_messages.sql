@@ -0,0 +1,26 @@
+-- ============================================================================
+-- WhatsApp Message Tracking
+-- Outbound messages with delivery status updated from Meta status callbacks
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS whatsapp_messages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    recipient VARCHAR(50) NOT NULL,
+    message JSONB NOT NULL,                      -- Serialized WhatsAppMessage
+    provider_message_id VARCHAR(255),            -- wamid.* once accepted by Meta
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'delivered', 'read', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_whatsapp_messages_tenant
+    ON whatsapp_messages(tenant_id, created_at DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_whatsapp_messages_provider_id
+    ON whatsapp_messages(tenant_id, provider_message_id)
+    WHERE provider_message_id IS NOT NULL;
@@ -0,0 +1,24 @@
+-- ============================================================================
+-- Agent Targets
+-- Per-user (or team-wide when user_id is NULL) goals over a date period
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS agent_targets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    metric_type VARCHAR(50) NOT NULL,            -- revenue, deals_won, leads_created, ...
+    target_value NUMERIC NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_date >= start_date)
+);
+
+-- Pacing needs to know whether beating the number means going over or under it
+ALTER TABLE agent_targets
+    ADD COLUMN IF NOT EXISTS direction VARCHAR(20) NOT NULL DEFAULT 'higher_is_better';
+
+CREATE INDEX IF NOT EXISTS idx_agent_targets_lookup ON agent_targets(tenant_id, metric_type, start_date, end_date);
+CREATE INDEX IF NOT EXISTS idx_agent_targets_user ON agent_targets(user_id);
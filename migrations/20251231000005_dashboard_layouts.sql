@@ -0,0 +1,18 @@
+-- ============================================================================
+-- Dashboard Layouts
+-- Saved widget placement per user; a row with NULL user_id is the tenant default
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS dashboard_layouts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    widgets JSONB NOT NULL DEFAULT '[]',          -- [{metric_id, x, y, w, h}] on a 12-column grid
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_dashboard_layouts_user
+    ON dashboard_layouts(tenant_id, user_id) WHERE user_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_dashboard_layouts_tenant_default
+    ON dashboard_layouts(tenant_id) WHERE user_id IS NULL;
@@ -0,0 +1,16 @@
+-- ============================================================================
+-- Metadata Versions
+-- Snapshot of an entity type and its fields for every schema change
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS metadata_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    entity_type_id UUID NOT NULL REFERENCES entity_types(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,                    -- 1, 2, 3... per entity type
+    snapshot JSONB NOT NULL,                     -- {entity_type, fields}
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (entity_type_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_metadata_versions_tenant ON metadata_versions(tenant_id);
@@ -0,0 +1,7 @@
+-- ============================================================================
+-- Entity Record Versions
+-- Optimistic concurrency for whole-record updates: writers send the version
+-- they read and the update only applies if it still matches
+-- ============================================================================
+
+ALTER TABLE entity_records ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- Audit Logs
+-- Tenant audit trail; record updates carry field-level before/after changes
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID,
+    action TEXT NOT NULL,                        -- create, update, delete, ...
+    resource_type TEXT NOT NULL,                 -- entity code or "api"
+    resource_id UUID,
+    details JSONB NOT NULL DEFAULT '{}',
+    changes JSONB NOT NULL DEFAULT '[]',         -- [{field, label, old, new}]
+    ip_address TEXT,
+    user_agent TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_tenant_created ON audit_logs(tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_resource ON audit_logs(tenant_id, resource_type, resource_id, created_at DESC);
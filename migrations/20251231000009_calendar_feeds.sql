@@ -0,0 +1,43 @@
+-- ============================================================================
+-- Calendar Events & Feed Tokens
+-- Events owned by a user, and the per-user secrets that authenticate their
+-- iCalendar feed in external calendar apps
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS calendar_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    calendar_id UUID,
+    title TEXT NOT NULL,
+    description TEXT,
+    location TEXT,
+    start_at TIMESTAMPTZ NOT NULL,
+    end_at TIMESTAMPTZ NOT NULL,
+    all_day BOOLEAN NOT NULL DEFAULT false,
+    status TEXT NOT NULL DEFAULT 'confirmed',    -- tentative, confirmed, cancelled
+    meeting_url TEXT,
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    linked_entity_type TEXT,
+    linked_entity_id UUID,
+    recurrence_rule TEXT,                        -- RRULE subset: FREQ, INTERVAL, COUNT, UNTIL, BYDAY
+    timezone TEXT,                               -- IANA name the rule is expanded in
+    exdates JSONB NOT NULL DEFAULT '[]',         -- [original_start]
+    overrides JSONB NOT NULL DEFAULT '[]',       -- [{original_start, start_at, end_at, title, location, status}]
+    external_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_calendar_events_owner ON calendar_events(tenant_id, owner_id, start_at);
+
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,             -- hex SHA-256; the token itself is shown once
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_feed_tokens_active
+    ON calendar_feed_tokens(tenant_id, user_id) WHERE revoked_at IS NULL;
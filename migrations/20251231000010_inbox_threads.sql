@@ -0,0 +1,86 @@
+-- ============================================================================
+-- Inbox Threads
+-- Conversation identity for interactions: one thread per (record, channel),
+-- where the channel is the interaction type. Threads can be merged into
+-- another thread or split; merged threads keep pointing at the survivor.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS inbox_threads (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    entity_type VARCHAR(100) NOT NULL,
+    record_id UUID NOT NULL,
+    channel VARCHAR(50) NOT NULL,                -- email, message, call, ...
+    merged_into UUID REFERENCES inbox_threads(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_inbox_threads_key
+    ON inbox_threads(tenant_id, entity_type, record_id, channel, created_at DESC);
+
+ALTER TABLE interactions ADD COLUMN IF NOT EXISTS thread_id UUID REFERENCES inbox_threads(id);
+CREATE INDEX IF NOT EXISTS idx_interactions_thread ON interactions(thread_id, occurred_at);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'inbox_threads' AND policyname = 'tenant_isolation_inbox_threads') THEN
+        ALTER TABLE inbox_threads ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_inbox_threads ON inbox_threads
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
+
+-- ============================================================================
+-- Thread identity resolution
+-- Every inserted interaction without a thread joins the newest thread for its
+-- (record, channel), following a merge to the surviving thread, or starts one
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION assign_interaction_thread()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.thread_id IS NULL THEN
+        SELECT COALESCE(merged_into, id) INTO NEW.thread_id
+        FROM inbox_threads
+        WHERE tenant_id = NEW.tenant_id
+          AND entity_type = NEW.entity_type
+          AND record_id = NEW.record_id
+          AND channel = LOWER(NEW.interaction_type)
+        ORDER BY created_at DESC
+        LIMIT 1;
+
+        IF NEW.thread_id IS NULL THEN
+            INSERT INTO inbox_threads (tenant_id, entity_type, record_id, channel)
+            VALUES (NEW.tenant_id, NEW.entity_type, NEW.record_id, LOWER(NEW.interaction_type))
+            RETURNING id INTO NEW.thread_id;
+        END IF;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS tr_interactions_assign_thread ON interactions;
+CREATE TRIGGER tr_interactions_assign_thread
+    BEFORE INSERT ON interactions
+    FOR EACH ROW
+    EXECUTE FUNCTION assign_interaction_thread();
+
+-- Backfill existing interactions
+INSERT INTO inbox_threads (tenant_id, entity_type, record_id, channel, created_at)
+SELECT tenant_id, entity_type, record_id, LOWER(interaction_type), MIN(occurred_at)
+FROM interactions
+WHERE thread_id IS NULL
+GROUP BY tenant_id, entity_type, record_id, LOWER(interaction_type);
+
+UPDATE interactions i
+SET thread_id = t.id
+FROM inbox_threads t
+WHERE i.thread_id IS NULL
+  AND t.tenant_id = i.tenant_id
+  AND t.entity_type = i.entity_type
+  AND t.record_id = i.record_id
+  AND t.channel = LOWER(i.interaction_type);
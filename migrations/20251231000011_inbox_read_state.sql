@@ -0,0 +1,34 @@
+-- ============================================================================
+-- Inbox Read State
+-- How far each agent has read each thread. Threads marked shared_read keep a
+-- single team-wide marker (user_id NULL) instead, so one agent reading clears
+-- the thread for everyone.
+-- ============================================================================
+
+ALTER TABLE inbox_threads ADD COLUMN IF NOT EXISTS shared_read BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE IF NOT EXISTS inbox_read_state (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    thread_id UUID NOT NULL REFERENCES inbox_threads(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,  -- NULL for shared threads
+    last_read_at TIMESTAMPTZ NOT NULL,           -- occurred_at of the last read message
+    last_read_message_id UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_inbox_read_state_user
+    ON inbox_read_state(thread_id, user_id) WHERE user_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_inbox_read_state_shared
+    ON inbox_read_state(thread_id) WHERE user_id IS NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'inbox_read_state' AND policyname = 'tenant_isolation_inbox_read_state') THEN
+        ALTER TABLE inbox_read_state ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_inbox_read_state ON inbox_read_state
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
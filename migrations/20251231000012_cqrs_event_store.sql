@@ -0,0 +1,66 @@
+-- ============================================================================
+-- CQRS Event Store
+-- The 20241224 event store migration used CREATE TABLE IF NOT EXISTS events,
+-- which silently no-ops because the workflow engine already owns a table of
+-- that name. Event-sourced aggregates get their own log here instead.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS domain_events (
+    sequence_number BIGSERIAL PRIMARY KEY,
+    event_id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    aggregate_id UUID NOT NULL,
+    aggregate_type VARCHAR(100) NOT NULL,
+    aggregate_version BIGINT NOT NULL,
+    event_type VARCHAR(200) NOT NULL,
+    event_data JSONB NOT NULL,
+    metadata JSONB,
+    correlation_id UUID,
+    causation_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID,
+    -- Optimistic concurrency: one event per aggregate version
+    UNIQUE (aggregate_id, aggregate_version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_domain_events_type_sequence
+    ON domain_events(aggregate_type, sequence_number);
+
+CREATE TABLE IF NOT EXISTS aggregate_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    aggregate_id UUID NOT NULL,
+    version BIGINT NOT NULL,
+    state_data JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (aggregate_id, version)
+);
+
+-- ============================================================================
+-- Deal Summaries
+-- Read model projected from Deal events; rebuildable from domain_events.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS deal_summaries (
+    deal_id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    title TEXT NOT NULL,
+    value NUMERIC,
+    stage VARCHAR(100) NOT NULL,
+    contact_id UUID,
+    property_id UUID,
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    version BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_deal_summaries_tenant ON deal_summaries(tenant_id, stage);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'deal_summaries' AND policyname = 'tenant_isolation_deal_summaries') THEN
+        ALTER TABLE deal_summaries ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_deal_summaries ON deal_summaries
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
@@ -0,0 +1,24 @@
+-- ============================================================================
+-- Event Outbox
+-- Entity events written in the same transaction as the data change, so a
+-- crash between commit and publish can't lose them. Rows are published
+-- at-least-once; consumers dedupe on dedup_key (the event id).
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    dedup_key UUID NOT NULL UNIQUE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    entity_type VARCHAR(100) NOT NULL,
+    record_id UUID NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,                      -- the serialized EntityEvent
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    available_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),  -- pushed back after a failed attempt
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    dispatched_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_pending
+    ON event_outbox(available_at, id) WHERE dispatched_at IS NULL;
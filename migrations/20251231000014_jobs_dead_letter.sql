@@ -0,0 +1,21 @@
+-- ============================================================================
+-- Jobs Dead Letter Queue
+-- Jobs that failed more than max_attempts times. They stay here, with the
+-- error of every attempt, until requeued by hand after a fix.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS jobs_dead_letter (
+    job_id UUID PRIMARY KEY,
+    queue_name VARCHAR(100) NOT NULL,
+    job_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL,
+    max_attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    context JSONB NOT NULL DEFAULT '{}',         -- worker and per-attempt errors
+    enqueued_at TIMESTAMPTZ NOT NULL,
+    failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_dead_letter_queue
+    ON jobs_dead_letter(queue_name, job_type);
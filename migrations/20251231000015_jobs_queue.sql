@@ -0,0 +1,29 @@
+-- ============================================================================
+-- Jobs Queue
+-- Background jobs, moved from Redis lists so dequeue can honour priority and
+-- run_at. Workers claim rows with FOR UPDATE SKIP LOCKED.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    queue_name VARCHAR(100) NOT NULL,
+    job_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    priority SMALLINT NOT NULL DEFAULT 0,        -- higher runs first
+    run_at TIMESTAMPTZ,                          -- not before; NULL = as soon as possible
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    error TEXT,
+    attempt_errors JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_dequeue
+    ON jobs(queue_name, priority DESC, (COALESCE(run_at, created_at)), created_at)
+    WHERE status IN ('pending', 'retrying');
+
+-- Requeued dead letters keep their priority
+ALTER TABLE jobs_dead_letter ADD COLUMN IF NOT EXISTS priority SMALLINT NOT NULL DEFAULT 0;
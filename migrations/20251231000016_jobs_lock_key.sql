@@ -0,0 +1,14 @@
+-- ============================================================================
+-- Jobs Lock Key
+-- Jobs sharing a lock_key (e.g. the record they touch) run one at a time.
+-- A running job holds its key until it finishes or its lease expires, so a
+-- crashed worker can't block the key forever.
+-- ============================================================================
+
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS lock_key VARCHAR(255);
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS lease_expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_running_lock_key
+    ON jobs(queue_name, lock_key) WHERE status = 'running' AND lock_key IS NOT NULL;
+
+ALTER TABLE jobs_dead_letter ADD COLUMN IF NOT EXISTS lock_key VARCHAR(255);
@@ -0,0 +1,12 @@
+-- ============================================================================
+-- Cron Job Runs
+-- Last fire time of each recurring job, so a restarted scheduler knows which
+-- occurrences it missed. Updated compare-and-set, so with several scheduler
+-- instances only one enqueues each occurrence.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS cron_job_runs (
+    name VARCHAR(200) PRIMARY KEY,
+    last_fired_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
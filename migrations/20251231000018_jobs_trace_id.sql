@@ -0,0 +1,8 @@
+-- ============================================================================
+-- Job Trace IDs
+-- Correlation id of the request (or job) that enqueued each job, so one
+-- logical operation can be followed from the API through background work.
+-- ============================================================================
+
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS trace_id VARCHAR(128);
+ALTER TABLE jobs_dead_letter ADD COLUMN IF NOT EXISTS trace_id VARCHAR(128);
@@ -0,0 +1,7 @@
+-- ============================================================================
+-- Tenant Trial End
+-- When a trial tenant's trial runs out; past it the tenant is treated as
+-- suspended until it reactivates.
+-- ============================================================================
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS trial_ends_at TIMESTAMPTZ;
@@ -0,0 +1,11 @@
+-- ============================================================================
+-- View Sharing
+-- Saved views are private to their creator unless shared with the creator's
+-- teams, the whole tenant, or listed users. Existing views stay tenant-wide.
+-- ============================================================================
+
+ALTER TABLE view_defs ADD COLUMN IF NOT EXISTS visibility VARCHAR(20) NOT NULL DEFAULT 'tenant'
+    CHECK (visibility IN ('private', 'team', 'tenant'));
+ALTER TABLE view_defs ADD COLUMN IF NOT EXISTS shared_with UUID[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_view_defs_shared_with ON view_defs USING GIN (shared_with);
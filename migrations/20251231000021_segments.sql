@@ -0,0 +1,30 @@
+-- ============================================================================
+-- Segments
+-- Named, saved record filters ("Hot Leads") over one entity type. The filter
+-- is a FilterExpr evaluated when the segment is counted or listed, so
+-- membership follows the data.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS segments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    entity_type VARCHAR(100) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    filter JSONB NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, entity_type, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_segments_tenant ON segments(tenant_id, entity_type);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'segments' AND policyname = 'tenant_isolation_segments') THEN
+        ALTER TABLE segments ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_segments ON segments
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- Lead Capture Forms
+-- Public website forms posting to /webhooks/lead/:token. The token is looked
+-- up before the tenant is known, so like calendar feed tokens the table has
+-- no RLS policy; handlers always filter by tenant_id.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS lead_capture_forms (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name VARCHAR(200) NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,             -- hex SHA-256; the token itself is shown once
+    field_mapping JSONB NOT NULL DEFAULT '{}',   -- {posted_key: contact_field}; empty = same names
+    honeypot_field VARCHAR(100) NOT NULL DEFAULT '_honeypot',
+    dedup_window_secs INTEGER NOT NULL DEFAULT 86400,
+    is_enabled BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_lead_capture_forms_tenant ON lead_capture_forms(tenant_id);
@@ -0,0 +1,11 @@
+-- ============================================================================
+-- Entity Display Field
+-- The field whose value labels a record in lookups and link pickers. NULL
+-- keeps the built-in label (e.g. a contact's first and last name).
+-- ============================================================================
+
+ALTER TABLE entity_types ADD COLUMN IF NOT EXISTS display_field VARCHAR(100);
+
+UPDATE entity_types SET display_field = 'name' WHERE display_field IS NULL AND name IN ('company', 'deal');
+UPDATE entity_types SET display_field = 'title' WHERE display_field IS NULL AND name IN ('task', 'property');
+UPDATE entity_types SET display_field = 'contract_number' WHERE display_field IS NULL AND name = 'contract';
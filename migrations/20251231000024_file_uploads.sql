@@ -0,0 +1,51 @@
+-- ============================================================================
+-- File Uploads
+-- One row per file stored by POST /uploads. Records keep only the URL; this
+-- is where its size, type and checksum live. Signatures are `retained`: they
+-- are evidence of what was signed, so they outlive edits to the record and
+-- their rows can't be deleted.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS file_uploads (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    storage_key VARCHAR(500) NOT NULL UNIQUE,
+    url TEXT NOT NULL,
+    file_name VARCHAR(500) NOT NULL,
+    size BIGINT NOT NULL,
+    content_type VARCHAR(200) NOT NULL,
+    checksum CHAR(64) NOT NULL,
+    -- 'file' or 'signature'
+    purpose VARCHAR(20) NOT NULL DEFAULT 'file',
+    retained BOOLEAN NOT NULL DEFAULT false,
+    uploaded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_uploads_tenant ON file_uploads(tenant_id, created_at DESC);
+
+CREATE OR REPLACE FUNCTION prevent_retained_upload_delete() RETURNS TRIGGER AS $$
+BEGIN
+    -- Removing the tenant still removes its uploads
+    IF OLD.retained AND EXISTS (SELECT 1 FROM tenants WHERE id = OLD.tenant_id) THEN
+        RAISE EXCEPTION 'Upload % is retained for audit and cannot be deleted', OLD.id;
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS file_uploads_retained ON file_uploads;
+CREATE TRIGGER file_uploads_retained
+    BEFORE DELETE ON file_uploads
+    FOR EACH ROW EXECUTE FUNCTION prevent_retained_upload_delete();
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'file_uploads' AND policyname = 'tenant_isolation_file_uploads') THEN
+        ALTER TABLE file_uploads ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_file_uploads ON file_uploads
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
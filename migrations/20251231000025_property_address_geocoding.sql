@@ -0,0 +1,18 @@
+-- ============================================================================
+-- Property address geocoding
+-- Property addresses become Location fields that geocode into the lat/lng
+-- fields the map view reads, wherever the property type has both.
+-- ============================================================================
+
+UPDATE field_defs AS address
+SET field_type = 'location',
+    options = COALESCE(address.options, '{}'::jsonb)
+        || '{"lat_field": "lat", "lng_field": "lng", "show_map": true}'::jsonb,
+    updated_at = NOW()
+FROM entity_types et
+WHERE address.entity_type_id = et.id
+  AND et.name = 'property'
+  AND address.name = 'address'
+  AND address.field_type IN ('text', 'textarea')
+  AND EXISTS (SELECT 1 FROM field_defs f WHERE f.entity_type_id = et.id AND f.name = 'lat')
+  AND EXISTS (SELECT 1 FROM field_defs f WHERE f.entity_type_id = et.id AND f.name = 'lng');
@@ -0,0 +1,37 @@
+-- ============================================================================
+-- Document Chunks
+-- Tenant documents split into overlapping chunks with one embedding each,
+-- retrieved by similarity to ground AI chat answers. A document's chunks are
+-- replaced as a whole when it is ingested again.
+-- ============================================================================
+
+CREATE EXTENSION IF NOT EXISTS vector;
+
+CREATE TABLE IF NOT EXISTS document_chunks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    document_id UUID NOT NULL,
+    title VARCHAR(500) NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    embedding vector(1536) NOT NULL,
+    model_name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, document_id, chunk_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_chunks_vector
+    ON document_chunks
+    USING hnsw (embedding vector_cosine_ops)
+    WITH (m = 16, ef_construction = 64);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'document_chunks' AND policyname = 'tenant_isolation_document_chunks') THEN
+        ALTER TABLE document_chunks ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_document_chunks ON document_chunks
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
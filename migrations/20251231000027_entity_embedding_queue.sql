@@ -0,0 +1,12 @@
+-- ============================================================================
+-- Entity Embedding Queue
+-- Saving a record whose embeddable fields changed stores the text to embed
+-- in `pending_content`; the jobs runner embeds pending rows in batches and
+-- clears it. The previous vector stays searchable in the meantime.
+-- ============================================================================
+
+ALTER TABLE entity_embeddings ADD COLUMN IF NOT EXISTS pending_content TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_entity_embeddings_pending
+    ON entity_embeddings(updated_at)
+    WHERE pending_content IS NOT NULL;
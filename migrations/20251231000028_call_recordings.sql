@@ -0,0 +1,38 @@
+-- ============================================================================
+-- Call Recordings
+-- One row per call placed with recording on, so the recording webhook knows
+-- the tenant, the agent and the contact the call belongs to. Twilio's
+-- recording URL is stored encrypted; the transcription job decrypts it to
+-- download the audio and logs the transcript as an interaction on the contact.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS call_recordings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    call_sid VARCHAR(64) NOT NULL UNIQUE,
+    placed_by UUID NOT NULL REFERENCES users(id),
+    entity_type VARCHAR(100) NOT NULL,
+    record_id UUID NOT NULL,
+    phone VARCHAR(50) NOT NULL,
+    recording_sid VARCHAR(64),
+    recording_url_encrypted BYTEA,
+    duration_seconds INTEGER,
+    -- 'recording', 'queued', 'transcribed' or 'discarded'
+    status VARCHAR(20) NOT NULL DEFAULT 'recording',
+    interaction_id UUID REFERENCES interactions(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_call_recordings_record ON call_recordings(tenant_id, entity_type, record_id);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'call_recordings' AND policyname = 'tenant_isolation_call_recordings') THEN
+        ALTER TABLE call_recordings ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_call_recordings ON call_recordings
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
@@ -0,0 +1,44 @@
+-- ============================================================================
+-- Call Sessions
+-- One row per call placed from the dialer. Twilio's status callbacks move it
+-- through its states and fill in when it was answered, when it ended and how
+-- long it lasted. Afterwards the agent sets a disposition, which is logged as
+-- a call interaction on the contact's timeline.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS call_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    call_sid VARCHAR(64) NOT NULL UNIQUE,
+    agent_id UUID NOT NULL REFERENCES users(id),
+    entity_type VARCHAR(100),
+    record_id UUID,
+    phone VARCHAR(50) NOT NULL,
+    -- 'outbound' or 'inbound'
+    direction VARCHAR(10) NOT NULL DEFAULT 'outbound',
+    -- queued, ringing, in_progress, then completed, busy, no_answer, failed or canceled
+    state VARCHAR(20) NOT NULL DEFAULT 'queued',
+    answered_at TIMESTAMPTZ,
+    ended_at TIMESTAMPTZ,
+    duration_seconds INTEGER,
+    -- connected, voicemail, no_answer, busy or wrong_number
+    disposition VARCHAR(30),
+    notes TEXT,
+    interaction_id UUID REFERENCES interactions(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_call_sessions_agent ON call_sessions(tenant_id, agent_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_call_sessions_record ON call_sessions(tenant_id, entity_type, record_id);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_policies WHERE tablename = 'call_sessions' AND policyname = 'tenant_isolation_call_sessions') THEN
+        ALTER TABLE call_sessions ENABLE ROW LEVEL SECURITY;
+        CREATE POLICY tenant_isolation_call_sessions ON call_sessions
+            FOR ALL
+            USING (tenant_id = get_current_tenant())
+            WITH CHECK (tenant_id = get_current_tenant());
+    END IF;
+END $$;
@@ -0,0 +1,25 @@
+-- ============================================================================
+-- API Keys
+-- Long-lived credentials tenants create for integrations. Only a SHA-256 hash
+-- of the secret is stored, along with its first characters so keys can be
+-- told apart in a list. Scopes are `resource:action` pairs (`*` matches any
+-- part); entity_types, when set, limits record access to those entities.
+-- Like sessions, keys are looked up by hash before the tenant is known, so
+-- the table has no row level security policy.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    entity_types TEXT[],
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_tenant ON api_keys(tenant_id, created_at DESC);
@@ -0,0 +1,19 @@
+-- ============================================================================
+-- Password Reset Tokens
+-- One row per reset requested. Only a SHA-256 hash of the token is stored;
+-- the token itself goes out in the reset email. A token works once, until
+-- expires_at. Like sessions, tokens are looked up by hash before the tenant
+-- is known, so the table has no row level security policy.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user ON password_reset_tokens(user_id);
@@ -0,0 +1,12 @@
+-- ============================================================================
+-- Session Activity
+-- last_seen_at lets users tell their devices apart when reviewing active
+-- sessions; revoked_at ends a session remotely ("log out other devices")
+-- while keeping the row for the record.
+-- ============================================================================
+
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS revoked_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id) WHERE revoked_at IS NULL;
@@ -0,0 +1,20 @@
+-- ============================================================================
+-- Login Attempts
+-- Failed login counters per account (tenant + email, whether or not such a
+-- user exists) and per client IP. Enough failures inside the window lock
+-- the key until locked_until. Kept in the database so every server
+-- instance sees the same counts; checked before the tenant's users are
+-- read, so there's no row level security policy.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS login_attempts (
+    scope VARCHAR(16) NOT NULL CHECK (scope IN ('account', 'ip')),
+    key TEXT NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    window_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (scope, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_attempts_updated ON login_attempts(updated_at);
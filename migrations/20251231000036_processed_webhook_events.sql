@@ -0,0 +1,19 @@
+-- ============================================================================
+-- Processed Webhook Events
+-- Inbound provider webhooks already handled, keyed by the provider's event
+-- id (or a hash of the body when it has none). Providers redeliver on
+-- timeouts and errors; a key claimed here is acknowledged without running
+-- again until it expires. Written before the tenant is known to the
+-- request, so there's no row level security policy.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS processed_webhook_events (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL,
+    event_key TEXT NOT NULL,
+    processed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, provider, event_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_processed_webhook_events_expires ON processed_webhook_events(expires_at);
@@ -0,0 +1,55 @@
+-- ============================================================================
+-- Webhook Subscriptions
+-- Tenant endpoints notified when records change. The jobs worker matches
+-- each outbox event against the tenant's subscriptions and queues one
+-- delivery per match, then POSTs it with an HMAC signature made with the
+-- subscription's secret, retrying with backoff until max_attempts, after
+-- which the delivery is dead-lettered. The deliveries double as the log
+-- shown per subscription. Read by the worker across tenants, so there's no
+-- row level security policy.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    target_url TEXT NOT NULL,
+    -- '<entity_type>.<event>' names such as 'contact.create'; either side may be '*'
+    event_types TEXT[] NOT NULL,
+    -- encrypted with the credentials key ring
+    secret_encrypted BYTEA NOT NULL,
+    max_attempts INTEGER NOT NULL DEFAULT 8,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_tenant ON webhook_subscriptions(tenant_id) WHERE is_active;
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    subscription_id UUID NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL,
+    event_type VARCHAR(200) NOT NULL,
+    payload JSONB NOT NULL,
+    -- pending, delivered or dead
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_status_code INTEGER,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ,
+    UNIQUE (subscription_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription ON webhook_deliveries(subscription_id, created_at DESC);
+
+-- Outbox events not yet matched against subscriptions; earlier events
+-- predate every subscription
+ALTER TABLE event_outbox ADD COLUMN IF NOT EXISTS webhooks_queued_at TIMESTAMPTZ;
+UPDATE event_outbox SET webhooks_queued_at = created_at WHERE webhooks_queued_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_webhooks_unqueued ON event_outbox(id) WHERE webhooks_queued_at IS NULL;
@@ -0,0 +1,27 @@
+-- ============================================================================
+-- Idempotency Keys
+-- First response to a mutating API request sent with an Idempotency-Key
+-- header, per tenant, key and route. A retry with the same key and request
+-- gets the stored response instead of running again; the same key with a
+-- different request is a conflict. Rows are claimed before the handler
+-- runs (response_status still NULL) so concurrent retries can't both run.
+-- Claimed by the middleware before the request's database session is set
+-- up, so there's no row level security policy.
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    -- method and path, e.g. 'POST /entities/contact'
+    route TEXT NOT NULL,
+    -- SHA-256 of the caller and request body
+    request_hash VARCHAR(64) NOT NULL,
+    response_status INTEGER,
+    response_headers JSONB,
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, idempotency_key, route)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires ON idempotency_keys(expires_at);
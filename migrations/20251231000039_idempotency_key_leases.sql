@@ -0,0 +1,11 @@
+-- ============================================================================
+-- Idempotency Key Leases
+-- A claim blocks retries only until locked_until; if the request that made
+-- it never finished (say the server restarted mid-request), a retry with
+-- the same request takes the key over once the lease runs out. Completed
+-- keys clear it. A completed key with no response_body is one whose
+-- response couldn't be kept (a stream, or too large); retries are refused.
+-- ============================================================================
+
+ALTER TABLE idempotency_keys
+    ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;